@@ -0,0 +1,2 @@
+ALTER TABLE payments ALTER COLUMN amount TYPE DECIMAL(15,2) USING ROUND(amount::numeric, 2);
+ALTER TABLE installments ALTER COLUMN amount TYPE DECIMAL(15,2) USING ROUND(amount::numeric, 2);
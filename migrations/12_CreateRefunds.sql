@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS refunds (
+    id TEXT PRIMARY KEY,
+    payment_id TEXT NOT NULL,
+    amount DECIMAL(15,2) NOT NULL,
+    reason TEXT NOT NULL,
+    method TEXT NOT NULL,
+    notes TEXT,
+    previous_status TEXT NOT NULL,
+    resulting_status TEXT NOT NULL,
+    refund_date TEXT NOT NULL,
+    FOREIGN KEY (payment_id) REFERENCES payments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_refunds_payment_id ON refunds(payment_id);
@@ -0,0 +1,44 @@
+CREATE TABLE IF NOT EXISTS cash_drawer_sessions (
+    id TEXT PRIMARY KEY,
+    status TEXT NOT NULL,
+    opened_by_user_id INTEGER NOT NULL,
+    opened_by TEXT NOT NULL,
+    opening_float DECIMAL(15,2) NOT NULL,
+    opened_at TEXT NOT NULL,
+    closed_by TEXT,
+    closed_at TEXT,
+    expected_amount DECIMAL(15,2),
+    counted_amount DECIMAL(15,2),
+    variance DECIMAL(15,2),
+    notes TEXT
+);
+
+-- Hanya satu laci kas yang boleh terbuka pada satu waktu
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cash_drawer_sessions_open ON cash_drawer_sessions(status) WHERE status = 'BUKA';
+
+CREATE TABLE IF NOT EXISTS cash_movements (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    movement_type TEXT NOT NULL,
+    amount DECIMAL(15,2) NOT NULL,
+    reason TEXT NOT NULL,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES cash_drawer_sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_cash_movements_session_id ON cash_movements(session_id);
+
+CREATE TABLE IF NOT EXISTS cash_session_payments (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    payment_id TEXT NOT NULL,
+    reference_id TEXT NOT NULL,
+    method TEXT NOT NULL,
+    amount DECIMAL(15,2) NOT NULL,
+    recorded_at TEXT NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES cash_drawer_sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_cash_session_payments_session_id ON cash_session_payments(session_id);
+CREATE INDEX IF NOT EXISTS idx_cash_session_payments_payment_id ON cash_session_payments(payment_id);
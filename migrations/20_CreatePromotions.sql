@@ -0,0 +1,31 @@
+-- Aturan promosi disimpan sebagai JSON di kolom aturan; cakupan dipecah ke id_produk/kategori.
+-- diskon_detail_transaksi menyimpan jejak potongan tiap detail untuk audit.
+CREATE TABLE IF NOT EXISTS promosi (
+    id SERIAL PRIMARY KEY,
+    nama VARCHAR(255) NOT NULL,
+    aturan TEXT NOT NULL,
+    cakupan VARCHAR(20) NOT NULL,
+    id_produk INTEGER,
+    kategori VARCHAR(255),
+    id_pelanggan INTEGER,
+    berlaku_mulai TEXT,
+    berlaku_sampai TEXT,
+    prioritas INTEGER NOT NULL DEFAULT 0,
+    dapat_digabung INTEGER NOT NULL DEFAULT 1,
+    aktif INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL
+);
+
+ALTER TABLE detail_transaksi ADD COLUMN diskon DECIMAL(15,2) NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS diskon_detail_transaksi (
+    id SERIAL PRIMARY KEY,
+    id_detail INTEGER NOT NULL,
+    id_transaksi INTEGER NOT NULL,
+    id_promosi INTEGER NOT NULL,
+    nama_promosi VARCHAR(255) NOT NULL,
+    potongan DECIMAL(15,2) NOT NULL,
+    FOREIGN KEY (id_detail) REFERENCES detail_transaksi(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_diskon_detail_transaksi_id_transaksi ON diskon_detail_transaksi(id_transaksi);
@@ -0,0 +1,46 @@
+-- Retur penjualan merujuk detail transaksi asal; transaksi asal tidak pernah diubah.
+-- Pengembalian dana dicatat di refunds (ditautkan lewat refund_retur_penjualan) atau sebagai kredit toko.
+CREATE TABLE IF NOT EXISTS retur_penjualan (
+    id SERIAL PRIMARY KEY,
+    id_transaksi INTEGER NOT NULL,
+    id_pelanggan INTEGER NOT NULL,
+    tanggal_retur TEXT NOT NULL,
+    alasan VARCHAR(50) NOT NULL,
+    metode_pengembalian VARCHAR(20) NOT NULL,
+    total_pengembalian DECIMAL(15,2) NOT NULL,
+    catatan TEXT,
+    FOREIGN KEY (id_transaksi) REFERENCES transaksi(id)
+);
+
+CREATE TABLE IF NOT EXISTS detail_retur_penjualan (
+    id SERIAL PRIMARY KEY,
+    id_retur INTEGER NOT NULL,
+    id_detail_transaksi INTEGER NOT NULL,
+    id_produk INTEGER NOT NULL,
+    jumlah INTEGER NOT NULL,
+    dapat_dijual_kembali INTEGER NOT NULL DEFAULT 1,
+    nilai DECIMAL(15,2) NOT NULL,
+    FOREIGN KEY (id_retur) REFERENCES retur_penjualan(id) ON DELETE CASCADE,
+    FOREIGN KEY (id_detail_transaksi) REFERENCES detail_transaksi(id)
+);
+
+CREATE TABLE IF NOT EXISTS refund_retur_penjualan (
+    id_retur INTEGER NOT NULL,
+    id_refund VARCHAR(255) NOT NULL,
+    PRIMARY KEY (id_retur, id_refund),
+    FOREIGN KEY (id_retur) REFERENCES retur_penjualan(id) ON DELETE CASCADE
+);
+
+-- Buku besar kredit toko; saldo pelanggan = SUM(jumlah)
+CREATE TABLE IF NOT EXISTS kredit_toko (
+    id SERIAL PRIMARY KEY,
+    id_pelanggan INTEGER NOT NULL,
+    id_retur INTEGER,
+    jumlah DECIMAL(15,2) NOT NULL,
+    keterangan TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_retur_penjualan_transaksi ON retur_penjualan(id_transaksi);
+CREATE INDEX IF NOT EXISTS idx_detail_retur_penjualan_detail ON detail_retur_penjualan(id_detail_transaksi);
+CREATE INDEX IF NOT EXISTS idx_kredit_toko_pelanggan ON kredit_toko(id_pelanggan);
@@ -0,0 +1,33 @@
+-- Surat jalan untuk barang yang dikirim ke pelanggan. Satu transaksi boleh punya beberapa
+-- pengiriman (pengiriman parsial); baris pengiriman yang gagal bisa dijadwalkan ulang.
+CREATE TABLE IF NOT EXISTS pengiriman (
+    id SERIAL PRIMARY KEY,
+    id_transaksi INTEGER NOT NULL,
+    alamat TEXT NOT NULL,
+    tanggal_kirim VARCHAR(10) NOT NULL,
+    kendaraan VARCHAR(100) NOT NULL DEFAULT '',
+    pengemudi VARCHAR(255) NOT NULL DEFAULT '',
+    status VARCHAR(20) NOT NULL,
+    catatan TEXT NOT NULL DEFAULT '',
+    -- Bukti pengiriman (catatan serah terima) atau alasan gagal
+    bukti_pengiriman TEXT NOT NULL DEFAULT '',
+    penerima VARCHAR(255) NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (id_transaksi) REFERENCES transaksi(id)
+);
+
+CREATE TABLE IF NOT EXISTS detail_pengiriman (
+    id SERIAL PRIMARY KEY,
+    id_pengiriman INTEGER NOT NULL,
+    id_detail_transaksi INTEGER NOT NULL,
+    id_produk INTEGER NOT NULL,
+    nama_produk VARCHAR(255) NOT NULL,
+    jumlah INTEGER NOT NULL,
+    FOREIGN KEY (id_pengiriman) REFERENCES pengiriman(id) ON DELETE CASCADE,
+    FOREIGN KEY (id_detail_transaksi) REFERENCES detail_transaksi(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_pengiriman_transaksi ON pengiriman(id_transaksi);
+CREATE INDEX IF NOT EXISTS idx_pengiriman_tanggal_kirim ON pengiriman(tanggal_kirim);
+CREATE INDEX IF NOT EXISTS idx_detail_pengiriman_pengiriman ON detail_pengiriman(id_pengiriman);
@@ -0,0 +1,3 @@
+-- SQLite has no fixed-point column type; amounts are read back as text and
+-- parsed into exact sen by the application, so the REAL columns stay as-is.
+SELECT 1;
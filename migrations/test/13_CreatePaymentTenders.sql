@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS payment_tenders (
+    id TEXT PRIMARY KEY,
+    payment_id TEXT NOT NULL,
+    line_no INTEGER NOT NULL,
+    method TEXT NOT NULL,
+    amount REAL NOT NULL,
+    reference_number TEXT,
+    FOREIGN KEY (payment_id) REFERENCES payments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_tenders_payment_id ON payment_tenders(payment_id);
+CREATE INDEX IF NOT EXISTS idx_payment_tenders_method ON payment_tenders(method);
@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS installment_plans (
+    id TEXT PRIMARY KEY,
+    payment_id TEXT NOT NULL UNIQUE,
+    number_of_terms INTEGER NOT NULL,
+    frequency TEXT NOT NULL,
+    first_due_date TEXT NOT NULL,
+    down_payment REAL NOT NULL,
+    principal REAL NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (payment_id) REFERENCES payments(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS installment_schedules (
+    id TEXT PRIMARY KEY,
+    plan_id TEXT NOT NULL,
+    term_number INTEGER NOT NULL,
+    due_date TEXT NOT NULL,
+    amount_due REAL NOT NULL,
+    FOREIGN KEY (plan_id) REFERENCES installment_plans(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_installment_schedules_plan_id ON installment_schedules(plan_id);
+CREATE INDEX IF NOT EXISTS idx_installment_schedules_due_date ON installment_schedules(due_date);
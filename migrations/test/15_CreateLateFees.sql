@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS late_fees (
+    id TEXT PRIMARY KEY,
+    payment_id TEXT NOT NULL,
+    period_number INTEGER NOT NULL,
+    amount REAL NOT NULL,
+    assessed_at TEXT NOT NULL,
+    FOREIGN KEY (payment_id) REFERENCES payments(id) ON DELETE CASCADE,
+    UNIQUE (payment_id, period_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_late_fees_payment_id ON late_fees(payment_id);
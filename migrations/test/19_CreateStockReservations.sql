@@ -0,0 +1,13 @@
+-- Stok yang ditahan oleh transaksi MASIH_DIPROSES, satu baris per detail transaksi.
+-- Baris dihapus saat transaksi selesai/dibatalkan; yang melewati expires_at dilepas oleh job.
+CREATE TABLE IF NOT EXISTS reservasi_stok (
+    id_detail INTEGER PRIMARY KEY,
+    id_transaksi INTEGER NOT NULL,
+    id_produk INTEGER NOT NULL,
+    jumlah INTEGER NOT NULL,
+    expires_at TEXT NOT NULL,
+    FOREIGN KEY (id_detail) REFERENCES detail_transaksi(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_reservasi_stok_transaksi ON reservasi_stok(id_transaksi);
+CREATE INDEX IF NOT EXISTS idx_reservasi_stok_expires_at ON reservasi_stok(expires_at);
@@ -0,0 +1,20 @@
+-- PPN per baris dan per transaksi. Tarif dan mode harga disalin ke header saat transaksi dibuat.
+-- Transaksi lama dihitung ulang sebagai harga termasuk PPN 11%, sama seperti struk sebelumnya.
+ALTER TABLE produk ADD COLUMN kategori_pajak VARCHAR(20) NOT NULL DEFAULT 'KENA_PAJAK';
+
+ALTER TABLE transaksi ADD COLUMN total_dpp REAL NOT NULL DEFAULT 0;
+ALTER TABLE transaksi ADD COLUMN total_ppn REAL NOT NULL DEFAULT 0;
+ALTER TABLE transaksi ADD COLUMN tarif_pajak_bp INTEGER NOT NULL DEFAULT 1100;
+ALTER TABLE transaksi ADD COLUMN harga_termasuk_pajak INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE detail_transaksi ADD COLUMN kategori_pajak VARCHAR(20) NOT NULL DEFAULT 'KENA_PAJAK';
+ALTER TABLE detail_transaksi ADD COLUMN tarif_pajak_bp INTEGER NOT NULL DEFAULT 1100;
+ALTER TABLE detail_transaksi ADD COLUMN dpp REAL NOT NULL DEFAULT 0;
+ALTER TABLE detail_transaksi ADD COLUMN ppn REAL NOT NULL DEFAULT 0;
+ALTER TABLE detail_transaksi ADD COLUMN total REAL NOT NULL DEFAULT 0;
+
+UPDATE transaksi SET total_dpp = ROUND(total_harga * 100 / 111, 2);
+UPDATE transaksi SET total_ppn = total_harga - total_dpp;
+
+UPDATE detail_transaksi SET dpp = ROUND(subtotal * 100 / 111, 2), total = subtotal;
+UPDATE detail_transaksi SET ppn = subtotal - dpp;
@@ -0,0 +1,13 @@
+-- Riwayat perubahan status transaksi. Tanpa foreign key ke transaksi agar riwayat
+-- tetap ada setelah transaksi yang dibatalkan dihapus.
+CREATE TABLE IF NOT EXISTS transaksi_status_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    id_transaksi INTEGER NOT NULL,
+    dari_status VARCHAR(20) NOT NULL,
+    ke_status VARCHAR(20) NOT NULL,
+    aktor VARCHAR(255) NOT NULL,
+    alasan TEXT NOT NULL DEFAULT '',
+    waktu TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaksi_status_history_transaksi ON transaksi_status_history(id_transaksi);
@@ -0,0 +1,43 @@
+-- Penawaran harga tidak mereservasi stok. Harga, diskon promosi dan PPN disimpan per baris
+-- seperti transaksi, agar konversi tanpa hitung ulang menghasilkan total yang sama.
+CREATE TABLE IF NOT EXISTS penawaran_harga (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    id_pelanggan INTEGER NOT NULL,
+    nama_pelanggan VARCHAR(255) NOT NULL,
+    tanggal_penawaran TEXT NOT NULL,
+    berlaku_sampai TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    total_harga REAL NOT NULL,
+    total_diskon REAL NOT NULL DEFAULT 0,
+    total_dpp REAL NOT NULL DEFAULT 0,
+    total_ppn REAL NOT NULL DEFAULT 0,
+    tarif_pajak_bp INTEGER NOT NULL,
+    harga_termasuk_pajak INTEGER NOT NULL,
+    catatan TEXT NOT NULL DEFAULT '',
+    updated_at TEXT NOT NULL
+);
+
+-- diskon_diterapkan berisi JSON daftar DiskonTerapan
+CREATE TABLE IF NOT EXISTS detail_penawaran_harga (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    id_penawaran INTEGER NOT NULL,
+    id_produk INTEGER NOT NULL,
+    nama_produk VARCHAR(255) NOT NULL,
+    harga_satuan REAL NOT NULL,
+    jumlah INTEGER NOT NULL,
+    diskon REAL NOT NULL DEFAULT 0,
+    diskon_diterapkan TEXT NOT NULL DEFAULT '[]',
+    subtotal REAL NOT NULL,
+    kategori_pajak VARCHAR(20) NOT NULL,
+    tarif_pajak_bp INTEGER NOT NULL,
+    dpp REAL NOT NULL,
+    ppn REAL NOT NULL,
+    total REAL NOT NULL,
+    FOREIGN KEY (id_penawaran) REFERENCES penawaran_harga(id) ON DELETE CASCADE
+);
+
+-- Transaksi hasil konversi merujuk penawarannya
+ALTER TABLE transaksi ADD COLUMN id_penawaran INTEGER REFERENCES penawaran_harga(id);
+
+CREATE INDEX IF NOT EXISTS idx_detail_penawaran_harga_penawaran ON detail_penawaran_harga(id_penawaran);
+CREATE INDEX IF NOT EXISTS idx_transaksi_penawaran ON transaksi(id_penawaran);
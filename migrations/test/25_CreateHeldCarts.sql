@@ -0,0 +1,26 @@
+-- Keranjang yang ditahan kasir saat melayani pelanggan berikutnya. Hanya draf: tidak ada baris
+-- transaksi, harga dan stok baru dihitung saat keranjang dikonversi menjadi transaksi.
+CREATE TABLE IF NOT EXISTS keranjang_tertahan (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    id_kasir INTEGER NOT NULL,
+    nama VARCHAR(100) NOT NULL,
+    id_pelanggan INTEGER,
+    nama_pelanggan VARCHAR(255) NOT NULL DEFAULT '',
+    catatan TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS detail_keranjang_tertahan (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    id_keranjang INTEGER NOT NULL,
+    id_produk INTEGER NOT NULL,
+    nama_produk VARCHAR(255) NOT NULL,
+    jumlah INTEGER NOT NULL,
+    FOREIGN KEY (id_keranjang) REFERENCES keranjang_tertahan(id) ON DELETE CASCADE
+);
+
+-- Nama keranjang unik per kasir
+CREATE UNIQUE INDEX IF NOT EXISTS idx_keranjang_tertahan_kasir_nama ON keranjang_tertahan(id_kasir, nama);
+CREATE INDEX IF NOT EXISTS idx_keranjang_tertahan_updated_at ON keranjang_tertahan(updated_at);
+CREATE INDEX IF NOT EXISTS idx_detail_keranjang_tertahan_keranjang ON detail_keranjang_tertahan(id_keranjang);
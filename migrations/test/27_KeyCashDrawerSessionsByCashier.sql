@@ -0,0 +1,4 @@
+-- Setiap kasir memegang laci kasnya sendiri: satu sesi terbuka per kasir, bukan per toko
+DROP INDEX IF EXISTS idx_cash_drawer_sessions_open;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cash_drawer_sessions_open_cashier ON cash_drawer_sessions(opened_by_user_id) WHERE status = 'BUKA';
@@ -0,0 +1,409 @@
+use std::future::Future;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use sqlx::{Any, Pool, Row};
+
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+pub const DEFAULT_WINDOW_SECS: i64 = 24 * 60 * 60;
+pub const DEFAULT_IN_PROGRESS_SECS: i64 = 60;
+const MAX_KEY_LENGTH: usize = 255;
+
+// Header `Idempotency-Key` bersifat opsional; key kosong atau terlalu panjang ditolak dengan 400
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotencyKey(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IdempotencyKey {
+    type Error = String;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.headers().get_one(IDEMPOTENCY_HEADER) {
+            None => Outcome::Success(IdempotencyKey(None)),
+            Some(key) => {
+                let key = key.trim();
+                if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+                    Outcome::Error((
+                        Status::BadRequest,
+                        format!("{} harus berisi 1 sampai {} karakter", IDEMPOTENCY_HEADER, MAX_KEY_LENGTH),
+                    ))
+                } else {
+                    Outcome::Success(IdempotencyKey(Some(key.to_string())))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum IdempotencyError {
+    Conflict(String),
+    InProgress(String),
+    DatabaseError(String),
+}
+
+impl IdempotencyError {
+    pub fn status(&self) -> Status {
+        match self {
+            IdempotencyError::Conflict(_) | IdempotencyError::InProgress(_) => Status::Conflict,
+            IdempotencyError::DatabaseError(_) => Status::InternalServerError,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            IdempotencyError::Conflict(msg)
+            | IdempotencyError::InProgress(msg)
+            | IdempotencyError::DatabaseError(msg) => msg.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredResponse {
+    pub status_code: u16,
+    pub body: String,
+}
+
+impl StoredResponse {
+    pub fn new<T: Serialize>(status: Status, body: &T) -> Result<Self, IdempotencyError> {
+        let body = serde_json::to_string(body)
+            .map_err(|e| IdempotencyError::DatabaseError(format!("Respons tidak dapat disimpan: {}", e)))?;
+
+        Ok(StoredResponse { status_code: status.code, body })
+    }
+
+    pub fn status(&self) -> Status {
+        Status::new(self.status_code)
+    }
+
+    pub fn body_as<T: DeserializeOwned>(&self) -> Result<T, IdempotencyError> {
+        serde_json::from_str(&self.body)
+            .map_err(|e| IdempotencyError::DatabaseError(format!("Respons tersimpan tidak dapat dibaca: {}", e)))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum IdempotencyOutcome {
+    Proceed,
+    Replay(StoredResponse),
+}
+
+// Bentuk respons handler yang bisa disimpan lalu diputar ulang oleh `with_idempotency`
+pub trait IdempotentResponse: Sized {
+    fn to_stored(&self) -> Result<StoredResponse, IdempotencyError>;
+    fn from_stored(stored: StoredResponse) -> Result<Self, IdempotencyError>;
+    fn from_error(error: IdempotencyError) -> Self;
+}
+
+// Menjalankan `process` paling banyak sekali per key. Tanpa header request diproses biasa;
+// dengan header yang sudah selesai, respons tersimpan diputar ulang.
+pub async fn with_idempotency<T, R, F, Fut>(db: &Pool<Any>, scope: &str, key: IdempotencyKey, request: &T, process: F) -> R
+where
+    T: Serialize,
+    R: IdempotentResponse,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = R>,
+{
+    let Some(key) = key.0 else {
+        return process().await;
+    };
+
+    let store = IdempotencyStore::from_env();
+    match store.begin(db, scope, &key, &IdempotencyStore::fingerprint(request), Utc::now()).await {
+        Ok(IdempotencyOutcome::Proceed) => {}
+        Ok(IdempotencyOutcome::Replay(stored)) => return R::from_stored(stored).unwrap_or_else(R::from_error),
+        Err(e) => return R::from_error(e),
+    }
+
+    let response = process().await;
+    let stored = match response.to_stored() {
+        Ok(stored) => store.complete(db, scope, &key, &stored).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = stored {
+        eprintln!("[ERROR] Gagal menyimpan respons idempoten untuk key {}: {:?}", key, e);
+        // Key dilepas agar retry tidak tertahan sebagai request yang masih diproses
+        if let Err(e) = store.release(db, scope, &key).await {
+            eprintln!("[ERROR] Gagal melepas key idempoten {}: {:?}", key, e);
+        }
+    }
+    response
+}
+
+pub struct IdempotencyStore {
+    window: Duration,
+    in_progress_timeout: Duration,
+}
+
+impl Default for IdempotencyStore {
+    fn default() -> Self {
+        Self::new(Duration::seconds(DEFAULT_WINDOW_SECS))
+    }
+}
+
+impl IdempotencyStore {
+    pub fn new(window: Duration) -> Self {
+        Self { window, in_progress_timeout: Duration::seconds(DEFAULT_IN_PROGRESS_SECS) }
+    }
+
+    // Lama penyimpanan dibaca dari IDEMPOTENCY_WINDOW_SECS (default 24 jam). Key yang belum
+    // selesai setelah IDEMPOTENCY_IN_PROGRESS_SECS (default 60 detik) dianggap ditinggalkan,
+    // misalnya karena proses berhenti di tengah request, dan boleh diklaim ulang.
+    pub fn from_env() -> Self {
+        let secs = |name: &str, default: i64| std::env::var(name)
+            .ok()
+            .and_then(|value| value.trim().parse::<i64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(default);
+
+        Self {
+            window: Duration::seconds(secs("IDEMPOTENCY_WINDOW_SECS", DEFAULT_WINDOW_SECS)),
+            in_progress_timeout: Duration::seconds(secs("IDEMPOTENCY_IN_PROGRESS_SECS", DEFAULT_IN_PROGRESS_SECS)),
+        }
+    }
+
+    pub fn fingerprint<T: Serialize>(body: &T) -> String {
+        let bytes = serde_json::to_vec(body).unwrap_or_default();
+        format!("{:x}", Sha256::digest(&bytes))
+    }
+
+    // Mengklaim key sebelum request diproses. Baris tanpa status_code menandakan request
+    // dengan key yang sama masih berjalan, sehingga retry paralel tidak ikut membuat data.
+    pub async fn begin(&self, db: &Pool<Any>, scope: &str, key: &str, fingerprint: &str, now: DateTime<Utc>) -> Result<IdempotencyOutcome, IdempotencyError> {
+        let mut conn = db.acquire().await
+            .map_err(|e| IdempotencyError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("DELETE FROM idempotency_keys WHERE created_at < $1 OR (status_code IS NULL AND created_at < $2)")
+            .bind(Self::timestamp(now - self.window))
+            .bind(Self::timestamp(now - self.in_progress_timeout))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| IdempotencyError::DatabaseError(e.to_string()))?;
+
+        let claimed = sqlx::query("
+            INSERT INTO idempotency_keys (scope, idempotency_key, fingerprint, status_code, response_body, created_at)
+            VALUES ($1, $2, $3, NULL, NULL, $4)
+            ON CONFLICT (scope, idempotency_key) DO NOTHING
+        ")
+            .bind(scope)
+            .bind(key)
+            .bind(fingerprint)
+            .bind(Self::timestamp(now))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| IdempotencyError::DatabaseError(e.to_string()))?;
+
+        if claimed.rows_affected() == 1 {
+            return Ok(IdempotencyOutcome::Proceed);
+        }
+
+        let row = sqlx::query("
+            SELECT fingerprint, status_code, response_body
+            FROM idempotency_keys
+            WHERE scope = $1 AND idempotency_key = $2
+        ")
+            .bind(scope)
+            .bind(key)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| IdempotencyError::DatabaseError(e.to_string()))?;
+
+        let stored_fingerprint: String = row.get("fingerprint");
+        if stored_fingerprint != fingerprint {
+            return Err(IdempotencyError::Conflict(format!(
+                "{} '{}' sudah dipakai untuk request dengan isi berbeda", IDEMPOTENCY_HEADER, key
+            )));
+        }
+
+        let status_code: Option<i64> = row.try_get("status_code").ok();
+        let body: Option<String> = row.try_get("response_body").ok();
+        match (status_code, body) {
+            (Some(status_code), Some(body)) => Ok(IdempotencyOutcome::Replay(StoredResponse {
+                status_code: status_code as u16,
+                body,
+            })),
+            _ => Err(IdempotencyError::InProgress(format!(
+                "Request dengan {} '{}' masih diproses", IDEMPOTENCY_HEADER, key
+            ))),
+        }
+    }
+
+    // Respons 5xx tidak disimpan: key dilepas agar retry berikutnya bisa diproses ulang
+    pub async fn complete(&self, db: &Pool<Any>, scope: &str, key: &str, response: &StoredResponse) -> Result<(), IdempotencyError> {
+        if response.status_code >= 500 {
+            return self.release(db, scope, key).await;
+        }
+
+        let mut conn = db.acquire().await
+            .map_err(|e| IdempotencyError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("
+            UPDATE idempotency_keys
+            SET status_code = $1, response_body = $2
+            WHERE scope = $3 AND idempotency_key = $4
+        ")
+            .bind(response.status_code as i64)
+            .bind(&response.body)
+            .bind(scope)
+            .bind(key)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| IdempotencyError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn release(&self, db: &Pool<Any>, scope: &str, key: &str) -> Result<(), IdempotencyError> {
+        let mut conn = db.acquire().await
+            .map_err(|e| IdempotencyError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("DELETE FROM idempotency_keys WHERE scope = $1 AND idempotency_key = $2")
+            .bind(scope)
+            .bind(key)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| IdempotencyError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Format tetap agar perbandingan string di SQL sama dengan urutan waktu
+    fn timestamp(at: DateTime<Utc>) -> String {
+        at.to_rfc3339_opts(SecondsFormat::Micros, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::any::{install_default_drivers, AnyPoolOptions};
+
+    const SCOPE: &str = "POST /api/payments";
+
+    async fn setup() -> Pool<Any> {
+        install_default_drivers();
+        let db = AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        db
+    }
+
+    fn stored<T: Serialize>(status: Status, body: T) -> StoredResponse {
+        StoredResponse::new(status, &body).unwrap()
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_and_body_sensitive() {
+        let first = IdempotencyStore::fingerprint(&serde_json::json!({ "amount": "1000.00" }));
+        let again = IdempotencyStore::fingerprint(&serde_json::json!({ "amount": "1000.00" }));
+        let other = IdempotencyStore::fingerprint(&serde_json::json!({ "amount": "1000.01" }));
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        assert_eq!(first.len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_begin_complete_and_replay() {
+        let db = setup().await;
+        let store = IdempotencyStore::default();
+        let now = Utc::now();
+
+        assert_eq!(store.begin(&db, SCOPE, "key-1", "abc", now).await.unwrap(), IdempotencyOutcome::Proceed);
+        assert!(matches!(
+            store.begin(&db, SCOPE, "key-1", "abc", now).await,
+            Err(IdempotencyError::InProgress(_))
+        ));
+
+        store.complete(&db, SCOPE, "key-1", &stored(Status::Created, serde_json::json!({ "id": "PMT-1" }))).await.unwrap();
+
+        let IdempotencyOutcome::Replay(stored) = store.begin(&db, SCOPE, "key-1", "abc", now).await.unwrap() else {
+            panic!("expected a stored response");
+        };
+        assert_eq!(stored.status(), Status::Created);
+        let body: serde_json::Value = stored.body_as().unwrap();
+        assert_eq!(body["id"], "PMT-1");
+
+        // Key yang sama di scope lain tidak saling mengganggu
+        assert_eq!(store.begin(&db, "POST /api/transaksi", "key-1", "xyz", now).await.unwrap(), IdempotencyOutcome::Proceed);
+    }
+
+    #[tokio::test]
+    async fn test_reused_key_with_different_body_conflicts() {
+        let db = setup().await;
+        let store = IdempotencyStore::default();
+        let now = Utc::now();
+
+        store.begin(&db, SCOPE, "key-1", "abc", now).await.unwrap();
+        store.complete(&db, SCOPE, "key-1", &stored(Status::Created, "ok")).await.unwrap();
+
+        let result = store.begin(&db, SCOPE, "key-1", "def", now).await;
+        assert!(matches!(result, Err(IdempotencyError::Conflict(_))));
+        assert_eq!(result.unwrap_err().status(), Status::Conflict);
+    }
+
+    #[tokio::test]
+    async fn test_server_error_releases_key() {
+        let db = setup().await;
+        let store = IdempotencyStore::default();
+        let now = Utc::now();
+
+        store.begin(&db, SCOPE, "key-1", "abc", now).await.unwrap();
+        store.complete(&db, SCOPE, "key-1", &stored(Status::InternalServerError, "boom")).await.unwrap();
+
+        assert_eq!(store.begin(&db, SCOPE, "key-1", "abc", now).await.unwrap(), IdempotencyOutcome::Proceed);
+    }
+
+    #[tokio::test]
+    async fn test_expired_key_can_be_reused() {
+        let db = setup().await;
+        let store = IdempotencyStore::new(Duration::minutes(10));
+        let now = Utc::now();
+
+        store.begin(&db, SCOPE, "key-1", "abc", now).await.unwrap();
+        store.complete(&db, SCOPE, "key-1", &stored(Status::Created, "ok")).await.unwrap();
+
+        let later = now + Duration::minutes(11);
+        assert_eq!(store.begin(&db, SCOPE, "key-1", "def", later).await.unwrap(), IdempotencyOutcome::Proceed);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_in_progress_key_can_be_reclaimed() {
+        let db = setup().await;
+        let store = IdempotencyStore::default();
+        let now = Utc::now();
+
+        store.begin(&db, SCOPE, "key-1", "abc", now).await.unwrap();
+        assert!(matches!(
+            store.begin(&db, SCOPE, "key-1", "abc", now + Duration::seconds(DEFAULT_IN_PROGRESS_SECS - 1)).await,
+            Err(IdempotencyError::InProgress(_))
+        ));
+
+        let later = now + Duration::seconds(DEFAULT_IN_PROGRESS_SECS + 1);
+        assert_eq!(store.begin(&db, SCOPE, "key-1", "abc", later).await.unwrap(), IdempotencyOutcome::Proceed);
+    }
+
+    #[tokio::test]
+    async fn test_released_key_can_be_reused() {
+        let db = setup().await;
+        let store = IdempotencyStore::default();
+        let now = Utc::now();
+
+        store.begin(&db, SCOPE, "key-1", "abc", now).await.unwrap();
+        store.release(&db, SCOPE, "key-1").await.unwrap();
+
+        assert_eq!(store.begin(&db, SCOPE, "key-1", "abc", now).await.unwrap(), IdempotencyOutcome::Proceed);
+    }
+}
@@ -0,0 +1,52 @@
+use std::future::Future;
+use std::time::Duration;
+use rocket::fairing::AdHoc;
+use sqlx::{Any, Pool};
+
+// Interval dibaca dari env_var dalam detik; nilai kosong, tidak valid, atau 0 memakai default_secs
+pub fn interval_from_env(env_var: &str, default_secs: u64) -> Duration {
+    let secs = std::env::var(env_var)
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(default_secs);
+    Duration::from_secs(secs)
+}
+
+// Job latar yang menjalankan task setiap interval setelah Rocket liftoff. Task menerima pool
+// database dan bertanggung jawab mencatat hasil maupun error-nya sendiri.
+pub fn interval_job<F, Fut>(name: &'static str, env_var: &'static str, default_secs: u64, task: F) -> AdHoc
+where
+    F: Fn(Pool<Any>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    AdHoc::on_liftoff(name, move |rocket| Box::pin(async move {
+        let db = match rocket.state::<Pool<Any>>() {
+            Some(pool) => pool.clone(),
+            None => {
+                eprintln!("[ERROR] Pool<Any> not found, {} not started.", name);
+                return;
+            }
+        };
+
+        let interval = interval_from_env(env_var, default_secs);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                task(db.clone()).await;
+            }
+        });
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_from_env_defaults_when_unset() {
+        assert_eq!(interval_from_env("COMMON_JOB_UNSET_INTERVAL_SECS", 30), Duration::from_secs(30));
+    }
+}
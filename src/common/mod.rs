@@ -0,0 +1,4 @@
+pub mod money;
+pub mod idempotency;
+pub mod query_builder;
+pub mod job;
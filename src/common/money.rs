@@ -0,0 +1,398 @@
+// Nilai uang dalam rupiah dengan presisi tetap dua desimal (sen).
+//
+// Semua nilai disimpan sebagai bilangan bulat sen (`i64`) sehingga penjumlahan
+// dan pengurangan selalu eksak. Pembulatan hanya terjadi saat:
+// - mengonversi dari `f64` atau string dengan lebih dari dua desimal,
+// - mengalikan dengan rasio/persentase (`apply_rate`, `percent_bp`).
+// Aturan pembulatannya adalah half-up (setengah menjauhi nol), sama dengan
+// `ROUND()` pada kolom DECIMAL(15,2) di PostgreSQL.
+//
+// Operator aritmetika (`+`, `-`, `*`, negasi) bersifat saturating: nilai dari input
+// pengguna yang terlalu besar berhenti di batas `i64` dan tidak membuat panic
+// maupun berputar menjadi negatif.
+//
+// Di JSON nilai dikirim sebagai string desimal (`"150000.00"`) agar tidak
+// kehilangan presisi. Untuk kompatibilitas, deserialisasi tetap menerima angka.
+
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::any::AnyRow;
+use sqlx::{Row, TypeInfo, ValueRef};
+
+pub const SEN_PER_RUPIAH: i64 = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rupiah(i64);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRupiahError(String);
+
+impl fmt::Display for ParseRupiahError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Nilai rupiah tidak valid: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseRupiahError {}
+
+impl Rupiah {
+    pub const ZERO: Rupiah = Rupiah(0);
+
+    pub const fn from_sen(sen: i64) -> Self {
+        Rupiah(sen)
+    }
+
+    pub const fn from_rupiah(rupiah: i64) -> Self {
+        Rupiah(rupiah * SEN_PER_RUPIAH)
+    }
+
+    /// Mengonversi `f64` menggunakan representasi desimal terpendeknya,
+    /// sehingga `1.005` dibulatkan menjadi `1.01` dan bukan `1.00`.
+    pub fn from_f64(value: f64) -> Result<Self, ParseRupiahError> {
+        if !value.is_finite() {
+            return Err(ParseRupiahError(value.to_string()));
+        }
+        value.to_string().parse()
+    }
+
+    pub const fn sen(self) -> i64 {
+        self.0
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / SEN_PER_RUPIAH as f64
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub const fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub const fn abs(self) -> Self {
+        Rupiah(self.0.abs())
+    }
+
+    /// Mengalikan dengan `numerator / denominator` lalu membulatkan half-up ke sen terdekat.
+    pub fn apply_rate(self, numerator: i64, denominator: i64) -> Self {
+        assert!(denominator != 0, "denominator tidak boleh 0");
+        let product = self.0 as i128 * numerator as i128;
+        let denominator = denominator as i128;
+        let quotient = product / denominator;
+        let remainder = product % denominator;
+        let rounded = if remainder.abs() * 2 >= denominator.abs() {
+            quotient + (product.signum() * denominator.signum())
+        } else {
+            quotient
+        };
+        Rupiah(rounded as i64)
+    }
+
+    /// Persentase dalam basis poin, misalnya `1100` untuk 11%.
+    pub fn percent_bp(self, basis_points: i64) -> Self {
+        self.apply_rate(basis_points, 10_000)
+    }
+
+    /// Membagi nilai menjadi `parts` bagian yang jumlahnya tetap sama persis.
+    /// Sisa sen diberikan satu per satu ke bagian-bagian pertama.
+    pub fn allocate(self, parts: usize) -> Vec<Rupiah> {
+        if parts == 0 {
+            return Vec::new();
+        }
+        let parts_i64 = parts as i64;
+        let base = self.0 / parts_i64;
+        let remainder = self.0 % parts_i64;
+        (0..parts_i64)
+            .map(|i| {
+                if i < remainder.abs() {
+                    Rupiah(base + remainder.signum())
+                } else {
+                    Rupiah(base)
+                }
+            })
+            .collect()
+    }
+
+    /// Membaca kolom uang dari baris `Any`. Kolom DECIMAL sebaiknya di-`CAST(... AS TEXT)`
+    /// di query karena driver `Any` tidak mendukung NUMERIC PostgreSQL, namun kolom
+    /// REAL/INTEGER lama tetap dapat dibaca.
+    pub fn from_row(row: &AnyRow, column: &str) -> Result<Self, sqlx::Error> {
+        let decode_error = |e: ParseRupiahError| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: Box::new(e),
+        };
+
+        if let Ok(text) = row.try_get::<String, _>(column) {
+            return text.parse().map_err(decode_error);
+        }
+        if let Ok(value) = row.try_get::<f64, _>(column) {
+            return Self::from_f64(value).map_err(decode_error);
+        }
+        let value: i64 = row.try_get(column)?;
+        Ok(Self::from_rupiah(value))
+    }
+
+    pub fn from_row_optional(row: &AnyRow, column: &str) -> Result<Option<Self>, sqlx::Error> {
+        // Driver `Any` tidak bisa men-decode NULL ke `Option<String>` (dan `ValueRef::is_null`
+        // selalu `false`), jadi cek jenis tipe nilai mentahnya.
+        if row.try_get_raw(column)?.type_info().name() == "NULL" {
+            return Ok(None);
+        }
+        Self::from_row(row, column).map(Some)
+    }
+}
+
+impl FromStr for Rupiah {
+    type Err = ParseRupiahError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseRupiahError(s.to_string());
+        let trimmed = s.trim();
+        let (negative, digits) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+
+        let (whole, fraction) = match digits.split_once('.') {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (digits, ""),
+        };
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(error());
+        }
+        if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(error());
+        }
+
+        let whole_value: i64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| error())? };
+        let mut fraction_digits = fraction.bytes().map(|b| (b - b'0') as i64);
+        let tenths = fraction_digits.next().unwrap_or(0);
+        let hundredths = fraction_digits.next().unwrap_or(0);
+        let round_up = fraction_digits.next().unwrap_or(0) >= 5;
+
+        let sen = whole_value
+            .checked_mul(SEN_PER_RUPIAH)
+            .and_then(|v| v.checked_add(tenths * 10 + hundredths + round_up as i64))
+            .ok_or_else(error)?;
+
+        Ok(Rupiah(if negative { -sen } else { sen }))
+    }
+}
+
+impl fmt::Display for Rupiah {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, abs / SEN_PER_RUPIAH as u64, abs % SEN_PER_RUPIAH as u64)
+    }
+}
+
+impl Add for Rupiah {
+    type Output = Rupiah;
+
+    fn add(self, rhs: Rupiah) -> Rupiah {
+        Rupiah(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign for Rupiah {
+    fn add_assign(&mut self, rhs: Rupiah) {
+        self.0 = self.0.saturating_add(rhs.0);
+    }
+}
+
+impl Sub for Rupiah {
+    type Output = Rupiah;
+
+    fn sub(self, rhs: Rupiah) -> Rupiah {
+        Rupiah(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl SubAssign for Rupiah {
+    fn sub_assign(&mut self, rhs: Rupiah) {
+        self.0 = self.0.saturating_sub(rhs.0);
+    }
+}
+
+impl Neg for Rupiah {
+    type Output = Rupiah;
+
+    fn neg(self) -> Rupiah {
+        Rupiah(self.0.saturating_neg())
+    }
+}
+
+impl Mul<u32> for Rupiah {
+    type Output = Rupiah;
+
+    fn mul(self, quantity: u32) -> Rupiah {
+        Rupiah(self.0.saturating_mul(quantity as i64))
+    }
+}
+
+impl Sum for Rupiah {
+    fn sum<I: Iterator<Item = Rupiah>>(iter: I) -> Rupiah {
+        iter.fold(Rupiah::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Rupiah> for Rupiah {
+    fn sum<I: Iterator<Item = &'a Rupiah>>(iter: I) -> Rupiah {
+        iter.copied().sum()
+    }
+}
+
+impl Serialize for Rupiah {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+struct RupiahVisitor;
+
+impl Visitor<'_> for RupiahVisitor {
+    type Value = Rupiah;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("nilai rupiah berupa string desimal atau angka")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Rupiah, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Rupiah, E> {
+        value.checked_mul(SEN_PER_RUPIAH).map(Rupiah).ok_or_else(|| E::custom("nilai rupiah terlalu besar"))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Rupiah, E> {
+        i64::try_from(value)
+            .ok()
+            .and_then(|v| v.checked_mul(SEN_PER_RUPIAH))
+            .map(Rupiah)
+            .ok_or_else(|| E::custom("nilai rupiah terlalu besar"))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Rupiah, E> {
+        Rupiah::from_f64(value).map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rupiah {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Rupiah, D::Error> {
+        deserializer.deserialize_any(RupiahVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("1500".parse::<Rupiah>().unwrap(), Rupiah::from_rupiah(1500));
+        assert_eq!("1500.5".parse::<Rupiah>().unwrap(), Rupiah::from_sen(150_050));
+        assert_eq!("-12.34".parse::<Rupiah>().unwrap(), Rupiah::from_sen(-1234));
+        assert_eq!(".75".parse::<Rupiah>().unwrap(), Rupiah::from_sen(75));
+        assert_eq!(Rupiah::from_sen(150_050).to_string(), "1500.50");
+        assert_eq!(Rupiah::from_sen(-5).to_string(), "-0.05");
+        assert!("".parse::<Rupiah>().is_err());
+        assert!("12a".parse::<Rupiah>().is_err());
+        assert!("1.2.3".parse::<Rupiah>().is_err());
+    }
+
+    #[test]
+    fn test_parse_rounds_half_up() {
+        assert_eq!("1.005".parse::<Rupiah>().unwrap(), Rupiah::from_sen(101));
+        assert_eq!("1.0049".parse::<Rupiah>().unwrap(), Rupiah::from_sen(100));
+        assert_eq!("-1.005".parse::<Rupiah>().unwrap(), Rupiah::from_sen(-101));
+    }
+
+    #[test]
+    fn test_from_f64() {
+        assert_eq!(Rupiah::from_f64(1000.0).unwrap(), Rupiah::from_rupiah(1000));
+        assert_eq!(Rupiah::from_f64(1.005).unwrap(), Rupiah::from_sen(101));
+        assert_eq!(Rupiah::from_f64(0.1 + 0.2).unwrap(), Rupiah::from_sen(30));
+        assert!(Rupiah::from_f64(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_sum_is_exact() {
+        let installments = [Rupiah::from_sen(10); 10];
+        let total: Rupiah = installments.iter().sum();
+        assert_eq!(total, Rupiah::from_rupiah(1));
+
+        let floats: f64 = std::iter::repeat_n(0.1, 10).sum();
+        assert_ne!(floats, 1.0);
+    }
+
+    #[test]
+    fn test_apply_rate_rounding() {
+        let price = Rupiah::from_sen(999);
+        assert_eq!(price.percent_bp(1100), Rupiah::from_sen(110));
+        assert_eq!(Rupiah::from_sen(5).apply_rate(1, 2), Rupiah::from_sen(3));
+        assert_eq!(Rupiah::from_sen(-5).apply_rate(1, 2), Rupiah::from_sen(-3));
+        assert_eq!(Rupiah::from_rupiah(100).apply_rate(1, 3), Rupiah::from_sen(3333));
+    }
+
+    #[test]
+    fn test_allocate_keeps_total() {
+        let parts = Rupiah::from_rupiah(100).allocate(3);
+        assert_eq!(parts, vec![Rupiah::from_sen(3334), Rupiah::from_sen(3333), Rupiah::from_sen(3333)]);
+        assert_eq!(parts.iter().sum::<Rupiah>(), Rupiah::from_rupiah(100));
+        assert!(Rupiah::from_rupiah(1).allocate(0).is_empty());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut total = Rupiah::from_rupiah(10);
+        total += Rupiah::from_sen(50);
+        total -= Rupiah::from_rupiah(1);
+        assert_eq!(total, Rupiah::from_sen(950));
+        assert_eq!(Rupiah::from_sen(250) * 4, Rupiah::from_rupiah(10));
+        assert_eq!(-Rupiah::from_rupiah(1), Rupiah::from_rupiah(-1));
+        assert!(Rupiah::from_rupiah(2) > Rupiah::from_rupiah(1));
+    }
+
+    #[test]
+    fn test_arithmetic_saturates() {
+        let max = Rupiah::from_sen(i64::MAX);
+        let min = Rupiah::from_sen(i64::MIN);
+
+        assert_eq!(max + Rupiah::from_sen(1), max);
+        assert_eq!(min - Rupiah::from_sen(1), min);
+        assert_eq!(Rupiah::from_rupiah(i64::MAX / 1_000) * u32::MAX, max);
+        assert_eq!(Rupiah::from_rupiah(-1) * u32::MAX, Rupiah::from_rupiah(-(u32::MAX as i64)));
+        assert_eq!(-min, max);
+        assert_eq!([max, max].iter().sum::<Rupiah>(), max);
+
+        let mut total = max;
+        total += Rupiah::from_rupiah(1);
+        assert_eq!(total, max);
+        total = min;
+        total -= Rupiah::from_rupiah(1);
+        assert_eq!(total, min);
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let json = serde_json::to_string(&Rupiah::from_sen(150_000_050)).unwrap();
+        assert_eq!(json, "\"1500000.50\"");
+        assert_eq!(serde_json::from_str::<Rupiah>(&json).unwrap(), Rupiah::from_sen(150_000_050));
+        assert_eq!(serde_json::from_str::<Rupiah>("1000").unwrap(), Rupiah::from_rupiah(1000));
+        assert_eq!(serde_json::from_str::<Rupiah>("1000.25").unwrap(), Rupiah::from_sen(100_025));
+        assert!(serde_json::from_str::<Rupiah>("\"abc\"").is_err());
+    }
+}
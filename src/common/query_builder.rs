@@ -0,0 +1,216 @@
+// Penyusun query SQL dinamis untuk driver `Any`.
+//
+// `sqlx::QueryBuilder` untuk `Any` menulis placeholder `?`, yang tidak dikenali
+// PostgreSQL. Builder ini selalu menulis `$1`, `$2`, ... (diterima PostgreSQL
+// maupun SQLite) dan menyimpan nilai bind dalam vektor, sehingga jumlah filter
+// tidak dibatasi.
+//
+// Kondisi ditulis sebagai template dengan `{}` sebagai tempat placeholder:
+//
+//     let mut builder = SqlQueryBuilder::new();
+//     builder.and_where("status = {}", "LUNAS");
+//     builder.and_where("(method = {} OR id IN (SELECT payment_id FROM payment_tenders WHERE method = {}))", "CASH");
+//     let sql = builder.to_sql("SELECT id FROM payments");
+//     let rows = builder.bind(&sql).fetch_all(&mut *db).await?;
+//
+// Semua `{}` dalam satu template memakai nilai yang sama. LIMIT dan OFFSET
+// ditulis langsung sebagai angka karena tipenya sudah `i64`.
+
+use sqlx::any::AnyArguments;
+use sqlx::query::Query;
+use sqlx::Any;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Text(String),
+    Integer(i64),
+}
+
+impl From<String> for SqlValue {
+    fn from(value: String) -> Self {
+        SqlValue::Text(value)
+    }
+}
+
+impl From<&str> for SqlValue {
+    fn from(value: &str) -> Self {
+        SqlValue::Text(value.to_string())
+    }
+}
+
+impl From<i64> for SqlValue {
+    fn from(value: i64) -> Self {
+        SqlValue::Integer(value)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SqlQueryBuilder {
+    conditions: Vec<String>,
+    binds: Vec<SqlValue>,
+    order_by: Vec<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+impl SqlQueryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn and_where(&mut self, template: &str, value: impl Into<SqlValue>) -> &mut Self {
+        let placeholder = self.push_bind(value.into());
+        self.conditions.push(template.replace("{}", &placeholder));
+        self
+    }
+
+    // `column IN ($n, $n+1, ...)`; daftar kosong menghasilkan kondisi yang selalu salah
+    pub fn and_where_in<V: Into<SqlValue>>(&mut self, column: &str, values: impl IntoIterator<Item = V>) -> &mut Self {
+        let placeholders: Vec<String> = values.into_iter()
+            .map(|value| self.push_bind(value.into()))
+            .collect();
+
+        if placeholders.is_empty() {
+            self.conditions.push("1 = 0".to_string());
+        } else {
+            self.conditions.push(format!("{} IN ({})", column, placeholders.join(", ")));
+        }
+        self
+    }
+
+    // `expression` tidak di-escape, jadi hanya boleh berasal dari daftar kolom yang sudah ditentukan
+    pub fn order_by(&mut self, expression: &str) -> &mut Self {
+        self.order_by.push(expression.to_string());
+        self
+    }
+
+    pub fn limit(&mut self, limit: i64) -> &mut Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(&mut self, offset: i64) -> &mut Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn bind_count(&self) -> usize {
+        self.binds.len()
+    }
+
+    // SELECT + WHERE saja, misalnya untuk `SELECT COUNT(*)` dengan filter yang sama
+    pub fn to_filter_sql(&self, select: &str) -> String {
+        let mut sql = select.to_string();
+        if !self.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.conditions.join(" AND "));
+        }
+        sql
+    }
+
+    pub fn to_sql(&self, select: &str) -> String {
+        let mut sql = self.to_filter_sql(select);
+        if !self.order_by.is_empty() {
+            sql.push_str(" ORDER BY ");
+            sql.push_str(&self.order_by.join(", "));
+        }
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(offset) = self.offset {
+            sql.push_str(&format!(" OFFSET {}", offset));
+        }
+        sql
+    }
+
+    pub fn bind<'q>(&self, sql: &'q str) -> Query<'q, Any, AnyArguments<'q>> {
+        self.binds.iter().fold(sqlx::query(sql), |query, value| match value {
+            SqlValue::Text(text) => query.bind(text.clone()),
+            SqlValue::Integer(number) => query.bind(*number),
+        })
+    }
+
+    fn push_bind(&mut self, value: SqlValue) -> String {
+        self.binds.push(value);
+        format!("${}", self.binds.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Row;
+
+    #[test]
+    fn test_placeholders_are_numbered_in_order() {
+        let mut builder = SqlQueryBuilder::new();
+        builder.and_where("status = {}", "LUNAS")
+            .and_where("(method = {} OR id IN (SELECT payment_id FROM payment_tenders WHERE method = {}))", "CASH")
+            .and_where_in("transaction_id", ["1", "2"])
+            .order_by("payment_date DESC")
+            .limit(20)
+            .offset(40);
+
+        assert_eq!(builder.bind_count(), 4);
+        assert_eq!(
+            builder.to_sql("SELECT id FROM payments"),
+            "SELECT id FROM payments WHERE status = $1 AND (method = $2 OR id IN (SELECT payment_id FROM payment_tenders WHERE method = $2)) \
+             AND transaction_id IN ($3, $4) ORDER BY payment_date DESC LIMIT 20 OFFSET 40"
+        );
+        assert_eq!(
+            builder.to_filter_sql("SELECT COUNT(*) AS total FROM payments"),
+            "SELECT COUNT(*) AS total FROM payments WHERE status = $1 AND (method = $2 OR id IN (SELECT payment_id FROM payment_tenders WHERE method = $2)) \
+             AND transaction_id IN ($3, $4)"
+        );
+    }
+
+    #[test]
+    fn test_empty_builder_and_empty_in_list() {
+        let builder = SqlQueryBuilder::new();
+        assert_eq!(builder.to_sql("SELECT id FROM payments"), "SELECT id FROM payments");
+
+        let mut builder = SqlQueryBuilder::new();
+        builder.and_where_in("payment_id", Vec::<String>::new());
+        assert_eq!(builder.to_sql("SELECT id FROM installments"), "SELECT id FROM installments WHERE 1 = 0");
+        assert_eq!(builder.bind_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_binds_many_values() {
+        sqlx::any::install_default_drivers();
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE items (id INTEGER NOT NULL, name TEXT NOT NULL)").execute(&db).await.unwrap();
+        for id in 1..=10i64 {
+            sqlx::query("INSERT INTO items (id, name) VALUES ($1, $2)")
+                .bind(id)
+                .bind(format!("item-{}", id))
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+
+        let mut builder = SqlQueryBuilder::new();
+        builder.and_where("id >= {}", 2i64)
+            .and_where("name <> {}", "item-5")
+            .and_where_in("id", (2..=8i64).collect::<Vec<_>>())
+            .order_by("id DESC")
+            .limit(3)
+            .offset(1);
+        assert_eq!(builder.bind_count(), 9);
+
+        let sql = builder.to_sql("SELECT id FROM items");
+        let ids: Vec<i64> = builder.bind(&sql).fetch_all(&db).await.unwrap()
+            .iter()
+            .map(|row| row.get("id"))
+            .collect();
+        assert_eq!(ids, vec![7, 6, 4]);
+
+        let sql = builder.to_filter_sql("SELECT COUNT(*) AS total FROM items");
+        let total: i64 = builder.bind(&sql).fetch_one(&db).await.unwrap().get("total");
+        assert_eq!(total, 6);
+    }
+}
@@ -7,6 +7,7 @@ use sqlx::any::install_default_drivers;
 use autometrics::prometheus_exporter;
 
 pub mod auth;
+pub mod common;
 // pub mod manajemen_produk;
 pub mod manajemen_pelanggan;
 pub mod manajemen_pembayaran;
@@ -60,7 +61,10 @@ async fn rocket() -> _ {
         .attach(auth::controller::route_stage())
         .attach(manajemen_pelanggan::controller::route_stage())
         .attach(manajemen_pembayaran::controller::route_stage())
+        .attach(manajemen_pembayaran::service::overdue_job::stage())
         .attach(transaksi_penjualan::controller::route_stage())
+        .attach(transaksi_penjualan::service::reservasi_job::stage())
+        .attach(transaksi_penjualan::service::keranjang_job::stage())
         .attach(manajemen_supplier::controller::route_stage())
         // .attach(manajemen_produk::controller::route_stage())
         .mount("/", routes![index, metrics])
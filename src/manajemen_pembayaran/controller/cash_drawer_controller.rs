@@ -0,0 +1,404 @@
+use serde::Deserialize;
+use chrono::Utc;
+use rocket::{get, post, routes, Route, State};
+use rocket::serde::json::Json;
+use rocket::http::Status;
+use autometrics::autometrics;
+
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::controller::payment_controller::ApiResponse;
+use crate::manajemen_pembayaran::enums::cash_movement_type::CashMovementType;
+use crate::manajemen_pembayaran::model::cash_drawer::{CashDrawerSession, ShiftReport};
+use crate::manajemen_pembayaran::service::cash_drawer_service::{CashDrawerService, NewCashMovement};
+use crate::manajemen_pembayaran::service::payment_service::PaymentError;
+use sqlx::{Any, Pool};
+
+#[derive(Deserialize)]
+pub struct OpenCashSessionRequest {
+    pub opening_float: Rupiah,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CashMovementRequest {
+    pub movement_type: String,
+    pub amount: Rupiah,
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+pub struct CloseCashSessionRequest {
+    pub counted_amount: Rupiah,
+    pub notes: Option<String>,
+}
+
+fn error_response<T>(e: PaymentError, context: &str) -> (Status, Json<ApiResponse<T>>) {
+    let (status, message) = match e {
+        PaymentError::NotFound(msg) => (Status::NotFound, msg),
+        PaymentError::InvalidInput(msg) => (Status::BadRequest, msg),
+        PaymentError::InvalidTransition(msg) => (Status::Conflict, msg),
+        PaymentError::Unauthorized(msg) => (Status::Unauthorized, msg),
+        e => (Status::InternalServerError, format!("{}: {:?}", context, e)),
+    };
+
+    (
+        status,
+        Json(ApiResponse {
+            success: false,
+            message,
+            data: None,
+        }),
+    )
+}
+
+fn success_response<T>(status: Status, message: &str, data: T) -> (Status, Json<ApiResponse<T>>) {
+    (
+        status,
+        Json(ApiResponse {
+            success: true,
+            message: message.to_string(),
+            data: Some(data),
+        }),
+    )
+}
+
+#[autometrics]
+#[post("/cash-sessions", format = "json", data = "<open_request>")]
+pub async fn open_cash_session(
+    user: AuthenticatedUser,
+    open_request: Json<OpenCashSessionRequest>,
+    db: &State<Pool<Any>>
+) -> (Status, Json<ApiResponse<CashDrawerSession>>) {
+    let open_request = open_request.into_inner();
+
+    match CashDrawerService::new().open_session(db, &user, open_request.opening_float, open_request.notes, Utc::now()).await {
+        Ok(session) => success_response(Status::Created, "Cash drawer session opened", session),
+        Err(e) => error_response(e, "Failed to open cash drawer session"),
+    }
+}
+
+#[autometrics]
+#[get("/cash-sessions/current")]
+pub async fn get_current_cash_session(user: AuthenticatedUser, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<CashDrawerSession>>) {
+    match CashDrawerService::new().get_open_session(db, &user).await {
+        Ok(Some(session)) => success_response(Status::Ok, "Open cash drawer session found", session),
+        Ok(None) => error_response(PaymentError::NotFound("No cash drawer session is open".to_string()), ""),
+        Err(e) => error_response(e, "Failed to retrieve cash drawer session"),
+    }
+}
+
+#[autometrics]
+#[get("/cash-sessions/<id>")]
+pub async fn get_cash_session(_user: AuthenticatedUser, id: &str, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<CashDrawerSession>>) {
+    match CashDrawerService::new().get_session(db, id).await {
+        Ok(session) => success_response(Status::Ok, "Cash drawer session retrieved", session),
+        Err(e) => error_response(e, "Failed to retrieve cash drawer session"),
+    }
+}
+
+#[autometrics]
+#[post("/cash-sessions/<id>/movements", format = "json", data = "<movement_request>")]
+pub async fn add_cash_movement(
+    user: AuthenticatedUser,
+    id: &str,
+    movement_request: Json<CashMovementRequest>,
+    db: &State<Pool<Any>>
+) -> (Status, Json<ApiResponse<CashDrawerSession>>) {
+    let movement_request = movement_request.into_inner();
+
+    let Some(movement_type) = CashMovementType::from_string(&movement_request.movement_type) else {
+        return error_response(
+            PaymentError::InvalidInput(format!("Invalid cash movement type: {}", movement_request.movement_type)),
+            "",
+        );
+    };
+
+    let new_movement = NewCashMovement {
+        movement_type,
+        amount: movement_request.amount,
+        reason: movement_request.reason,
+    };
+
+    match CashDrawerService::new().record_movement(db, id, &user, new_movement, Utc::now()).await {
+        Ok(session) => success_response(Status::Created, "Cash movement recorded", session),
+        Err(e) => error_response(e, "Failed to record cash movement"),
+    }
+}
+
+#[autometrics]
+#[post("/cash-sessions/<id>/close", format = "json", data = "<close_request>")]
+pub async fn close_cash_session(
+    user: AuthenticatedUser,
+    id: &str,
+    close_request: Json<CloseCashSessionRequest>,
+    db: &State<Pool<Any>>
+) -> (Status, Json<ApiResponse<ShiftReport>>) {
+    let close_request = close_request.into_inner();
+
+    match CashDrawerService::new().close_session(db, id, &user, close_request.counted_amount, close_request.notes, Utc::now()).await {
+        Ok(report) => success_response(Status::Ok, "Cash drawer session closed", report),
+        Err(e) => error_response(e, "Failed to close cash drawer session"),
+    }
+}
+
+#[autometrics]
+#[get("/cash-sessions/<id>/report")]
+pub async fn get_shift_report(_user: AuthenticatedUser, id: &str, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<ShiftReport>>) {
+    match CashDrawerService::new().get_shift_report(db, id).await {
+        Ok(report) => success_response(Status::Ok, "Shift report generated", report),
+        Err(e) => error_response(e, "Failed to build shift report"),
+    }
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![
+        open_cash_session,
+        get_current_cash_session,
+        get_cash_session,
+        add_cash_movement,
+        close_cash_session,
+        get_shift_report
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::local::asynchronous::Client;
+    use crate::auth::controller::auth::{login, logout, AuthForm};
+    use crate::auth::model::user::User;
+    use crate::auth::service::auth::AuthService;
+    use crate::manajemen_pembayaran::controller::{payment_controller, refund_controller};
+    use crate::manajemen_pembayaran::enums::cash_session_status::CashSessionStatus;
+    use crate::manajemen_pembayaran::model::payment::{Payment, PaymentMethod};
+
+    async fn setup_client() -> Client {
+        let client = setup_anonymous_client().await;
+        client.post("/api/auth/login")
+            .json(&AuthForm { username: "kasir".to_string(), password: "kasir123".to_string() })
+            .dispatch()
+            .await;
+
+        client
+    }
+
+    async fn setup_anonymous_client() -> Client {
+        sqlx::any::install_default_drivers();
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        for username in ["kasir", "kasir2"] {
+            AuthService::register_user(db.clone(), User::new(username.to_string(), "kasir123".to_string(), false))
+                .await
+                .unwrap();
+        }
+
+        let rocket = rocket::build()
+            .manage(db)
+            .manage(false)
+            .mount("/api", payment_controller::routes())
+            .mount("/api", refund_controller::routes())
+            .mount("/api", routes())
+            .mount("/api/auth", rocket::routes![login, logout]);
+
+        Client::tracked(rocket).await.expect("Must provide a valid Rocket instance")
+    }
+
+    async fn create_payment(client: &Client, body: serde_json::Value) -> Payment {
+        use crate::transaksi_penjualan::model::transaksi::Transaksi;
+        use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        let transaksi = Transaksi::new(1, "Budi".to_string(), Rupiah::from_rupiah(1_000_000), None);
+        let transaksi = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi)
+            .await
+            .unwrap();
+
+        let mut body = body;
+        body["transaction_id"] = serde_json::json!(transaksi.id.to_string());
+        let response = client.post("/api/payments").json(&body).dispatch().await;
+        assert_eq!(response.status(), Status::Created);
+
+        let created: ApiResponse<Payment> = response.into_json().await.unwrap();
+        created.data.unwrap()
+    }
+
+    async fn login_as(client: &Client, username: &str) {
+        let response = client.post("/api/auth/login")
+            .json(&AuthForm { username: username.to_string(), password: "kasir123".to_string() })
+            .dispatch()
+            .await;
+        assert!(response.status().code < 400);
+    }
+
+    async fn current_session(client: &Client) -> CashDrawerSession {
+        let response = client.get("/api/cash-sessions/current").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let current: ApiResponse<CashDrawerSession> = response.into_json().await.unwrap();
+        current.data.unwrap()
+    }
+
+    async fn open_session(client: &Client, opening_float: &str) -> CashDrawerSession {
+        let response = client.post("/api/cash-sessions")
+            .json(&serde_json::json!({ "opening_float": opening_float }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let session: ApiResponse<CashDrawerSession> = response.into_json().await.unwrap();
+        session.data.unwrap()
+    }
+
+    #[rocket::async_test]
+    async fn test_cash_session_shift_and_reconciliation() {
+        let client = setup_client().await;
+
+        // Pembayaran sebelum laci dibuka tidak ikut dihitung
+        create_payment(&client, serde_json::json!({ "amount": "50000.00", "method": "CASH", "status": "LUNAS" })).await;
+
+        let session = open_session(&client, "500000.00").await;
+        assert_eq!(session.opened_by, "kasir");
+
+        let response = client.post("/api/cash-sessions").json(&serde_json::json!({ "opening_float": "100000.00" })).dispatch().await;
+        assert_eq!(response.status(), Status::Conflict);
+
+        create_payment(&client, serde_json::json!({ "amount": "250000.00", "method": "CASH", "status": "LUNAS" })).await;
+        create_payment(&client, serde_json::json!({
+            "amount": "300000.00",
+            "method": "CASH",
+            "status": "LUNAS",
+            "tender_lines": [
+                { "method": "CASH", "amount": "100000.00" },
+                { "method": "CREDIT_CARD", "amount": "200000.00", "reference_number": "APPR-1" }
+            ]
+        })).await;
+        let installment_payment = create_payment(&client, serde_json::json!({ "amount": "400000.00", "method": "CASH", "status": "CICILAN" })).await;
+        let response = client.post(format!("/api/payments/{}/installments", installment_payment.id))
+            .json(&serde_json::json!({ "amount": "150000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        create_payment(&client, serde_json::json!({ "amount": "75000.00", "method": "E_WALLET", "status": "LUNAS" })).await;
+
+        let response = client.post(format!("/api/cash-sessions/{}/movements", session.id))
+            .json(&serde_json::json!({ "movement_type": "KELUAR", "amount": "200000.00", "reason": "Setor ke bank" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let response = client.post(format!("/api/cash-sessions/{}/movements", session.id))
+            .json(&serde_json::json!({ "movement_type": "PINJAM", "amount": "1000.00", "reason": "?" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.get(format!("/api/cash-sessions/{}/report", session.id)).dispatch().await;
+        let report: ApiResponse<ShiftReport> = response.into_json().await.unwrap();
+        let report = report.data.unwrap();
+        // 500.000 + 250.000 + 100.000 + 150.000 - 200.000
+        assert_eq!(report.cash_sales, Rupiah::from_rupiah(500_000));
+        assert_eq!(report.expected_cash, Rupiah::from_rupiah(800_000));
+        assert_eq!(report.totals_by_method.len(), 3);
+        assert_eq!(report.totals_by_method[1].method, PaymentMethod::CreditCard);
+        assert_eq!(report.totals_by_method[1].total, Rupiah::from_rupiah(200_000));
+        assert_eq!(report.totals_by_method[2].method, PaymentMethod::EWallet);
+
+        let response = client.post(format!("/api/cash-sessions/{}/close", session.id))
+            .json(&serde_json::json!({ "counted_amount": "790000.00", "notes": "Selisih dicek besok" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let report: ApiResponse<ShiftReport> = response.into_json().await.unwrap();
+        let report = report.data.unwrap();
+        assert_eq!(report.status, CashSessionStatus::Closed);
+        assert_eq!(report.counted_amount, Some(Rupiah::from_rupiah(790_000)));
+        assert_eq!(report.variance, Some(Rupiah::from_rupiah(-10_000)));
+        assert_eq!(report.closed_by.as_deref(), Some("kasir"));
+
+        let response = client.post(format!("/api/cash-sessions/{}/close", session.id))
+            .json(&serde_json::json!({ "counted_amount": "790000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Conflict);
+
+        let response = client.post(format!("/api/cash-sessions/{}/movements", session.id))
+            .json(&serde_json::json!({ "movement_type": "KELUAR", "amount": "1000.00", "reason": "Terlambat" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Conflict);
+
+        let response = client.get("/api/cash-sessions/current").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn test_each_cashier_has_own_drawer() {
+        let client = setup_client().await;
+        let first = open_session(&client, "100000.00").await;
+
+        login_as(&client, "kasir2").await;
+        let second = open_session(&client, "50000.00").await;
+        assert_ne!(first.id, second.id);
+        assert_eq!(second.opened_by, "kasir2");
+
+        create_payment(&client, serde_json::json!({ "amount": "40000.00", "method": "CASH", "status": "LUNAS" })).await;
+        let current = current_session(&client).await;
+        assert_eq!(current.id, second.id);
+        assert_eq!(current.expected_cash(), Rupiah::from_rupiah(90_000));
+
+        login_as(&client, "kasir").await;
+        let current = current_session(&client).await;
+        assert_eq!(current.id, first.id);
+        assert!(current.payments.is_empty());
+
+        // Pembayaran tanpa kasir yang login tidak masuk ke laci mana pun
+        assert_eq!(client.get("/api/auth/logout").dispatch().await.status(), Status::Ok);
+        create_payment(&client, serde_json::json!({ "amount": "10000.00", "method": "CASH", "status": "LUNAS" })).await;
+        login_as(&client, "kasir").await;
+        assert!(current_session(&client).await.payments.is_empty());
+    }
+
+    #[rocket::async_test]
+    async fn test_cash_refund_reduces_expected_cash() {
+        let client = setup_client().await;
+        let session = open_session(&client, "100000.00").await;
+
+        let payment = create_payment(&client, serde_json::json!({ "amount": "80000.00", "method": "CASH", "status": "LUNAS" })).await;
+        let response = client.post(format!("/api/payments/{}/refunds", payment.id))
+            .json(&serde_json::json!({ "amount": "30000.00", "reason": "BARANG_RUSAK" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let response = client.get("/api/cash-sessions/current").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let current: ApiResponse<CashDrawerSession> = response.into_json().await.unwrap();
+        let current = current.data.unwrap();
+        assert_eq!(current.id, session.id);
+        assert_eq!(current.payments.len(), 2);
+        assert_eq!(current.expected_cash(), Rupiah::from_rupiah(150_000));
+    }
+
+    #[rocket::async_test]
+    async fn test_cash_sessions_require_login() {
+        let client = setup_anonymous_client().await;
+        let response = client.post("/api/cash-sessions")
+            .json(&serde_json::json!({ "opening_float": "1000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let client = setup_client().await;
+        let response = client.get("/api/cash-sessions/CDS-404").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
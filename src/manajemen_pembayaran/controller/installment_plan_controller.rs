@@ -0,0 +1,284 @@
+use serde::Deserialize;
+use rocket::{get, post, routes, Route, State};
+use rocket::serde::json::Json;
+use rocket::http::Status;
+use autometrics::autometrics;
+use chrono::{DateTime, Utc};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::controller::payment_controller::ApiResponse;
+use crate::manajemen_pembayaran::model::installment_plan::InstallmentPlan;
+use crate::manajemen_pembayaran::service::payment_service::PaymentError;
+use crate::manajemen_pembayaran::service::installment_plan_service::{InstallmentPlanService, NewInstallmentPlan};
+use sqlx::{Any, Pool};
+
+#[derive(Deserialize)]
+pub struct CreateInstallmentPlanRequest {
+    pub number_of_terms: i32,
+    pub frequency: String,
+    pub first_due_date: DateTime<Utc>,
+    pub down_payment: Option<Rupiah>,
+}
+
+#[autometrics]
+#[post("/payments/<id>/plan", format = "json", data = "<plan_request>")]
+pub async fn create_installment_plan(
+    id: String,
+    plan_request: Json<CreateInstallmentPlanRequest>,
+    db: &State<Pool<Any>>
+) -> (Status, Json<ApiResponse<InstallmentPlan>>) {
+    let plan_service = InstallmentPlanService::new();
+
+    let frequency = match plan_service.parse_plan_frequency(&plan_request.frequency) {
+        Ok(f) => f,
+        Err(e) => {
+            return (
+                Status::BadRequest,
+                Json(ApiResponse {
+                    success: false,
+                    message: format!("Invalid plan frequency: {:?}", e),
+                    data: None,
+                }),
+            );
+        }
+    };
+
+    let new_plan = NewInstallmentPlan {
+        number_of_terms: plan_request.number_of_terms,
+        frequency,
+        first_due_date: plan_request.first_due_date,
+        down_payment: plan_request.down_payment,
+    };
+
+    match plan_service.create_plan(db, &id, new_plan).await {
+        Ok(plan) => (
+            Status::Created,
+            Json(ApiResponse {
+                success: true,
+                message: "Installment plan created successfully".to_string(),
+                data: Some(plan),
+            }),
+        ),
+        Err(PaymentError::NotFound(msg)) => (
+            Status::NotFound,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
+        Err(PaymentError::InvalidInput(msg)) => (
+            Status::BadRequest,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
+        Err(PaymentError::InvalidTransition(msg)) => (
+            Status::Conflict,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
+        Err(e) => (
+            Status::InternalServerError,
+            Json(ApiResponse {
+                success: false,
+                message: format!("Failed to create installment plan: {:?}", e),
+                data: None,
+            }),
+        ),
+    }
+}
+
+#[autometrics]
+#[get("/payments/<id>/plan")]
+pub async fn get_installment_plan(id: String, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<InstallmentPlan>>) {
+    let plan_service = InstallmentPlanService::new();
+
+    match plan_service.get_plan(db, &id).await {
+        Ok(plan) => (
+            Status::Ok,
+            Json(ApiResponse {
+                success: true,
+                message: "Installment plan retrieved successfully".to_string(),
+                data: Some(plan),
+            }),
+        ),
+        Err(PaymentError::NotFound(msg)) => (
+            Status::NotFound,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
+        Err(e) => (
+            Status::InternalServerError,
+            Json(ApiResponse {
+                success: false,
+                message: format!("Failed to retrieve installment plan: {:?}", e),
+                data: None,
+            }),
+        ),
+    }
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![create_installment_plan, get_installment_plan]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rocket::local::asynchronous::Client;
+    use crate::manajemen_pembayaran::controller::payment_controller;
+    use crate::manajemen_pembayaran::enums::schedule_status::ScheduleStatus;
+    use crate::manajemen_pembayaran::model::payment::Payment;
+
+    async fn setup_client() -> Client {
+        sqlx::any::install_default_drivers();
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        let rocket = rocket::build()
+            .manage(db)
+            .mount("/api", payment_controller::routes())
+            .mount("/api", routes());
+
+        Client::tracked(rocket).await.expect("Must provide a valid Rocket instance")
+    }
+
+    async fn create_installment_payment(client: &Client) -> String {
+        use crate::transaksi_penjualan::model::transaksi::Transaksi;
+        use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        let transaksi = Transaksi::new(1, "Budi".to_string(), Rupiah::from_rupiah(1_000_000), None);
+        let transaksi = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi)
+            .await
+            .unwrap();
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": transaksi.id.to_string(),
+                "amount": "1000000.00",
+                "method": "CASH",
+                "status": "CICILAN"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let created: ApiResponse<Payment> = response.into_json().await.unwrap();
+        created.data.unwrap().id
+    }
+
+    #[rocket::async_test]
+    async fn test_create_plan_and_track_installments() {
+        let client = setup_client().await;
+        let payment_id = create_installment_payment(&client).await;
+        let first_due_date = Utc::now() + Duration::days(30);
+
+        let response = client.post(format!("/api/payments/{}/plan", payment_id))
+            .json(&serde_json::json!({
+                "number_of_terms": 3,
+                "frequency": "BULANAN",
+                "first_due_date": first_due_date,
+                "down_payment": "100000.00"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+        let plan: ApiResponse<InstallmentPlan> = response.into_json().await.unwrap();
+        let plan = plan.data.unwrap();
+        assert_eq!(plan.schedule.len(), 3);
+        assert!(plan.schedule.iter().all(|term| term.status == ScheduleStatus::Upcoming));
+
+        let response = client.post(format!("/api/payments/{}/installments", payment_id))
+            .json(&serde_json::json!({ "amount": "450000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.get(format!("/api/payments/{}/plan", payment_id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let plan: ApiResponse<InstallmentPlan> = response.into_json().await.unwrap();
+        let schedule = plan.data.unwrap().schedule;
+        assert_eq!(schedule[0].status, ScheduleStatus::Paid);
+        assert_eq!(schedule[1].status, ScheduleStatus::PartiallyPaid);
+        assert_eq!(schedule[1].amount_paid, Rupiah::from_rupiah(150_000));
+        assert_eq!(schedule[2].status, ScheduleStatus::Upcoming);
+
+        let response = client.get(format!("/api/payments/{}", payment_id)).dispatch().await;
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let payment = payment.data.unwrap();
+        assert_eq!(payment.installments.len(), 2);
+        assert_eq!(payment.due_date.map(|d| d.timestamp()), Some(first_due_date.timestamp()));
+
+        let response = client.post(format!("/api/payments/{}/plan", payment_id))
+            .json(&serde_json::json!({
+                "number_of_terms": 2,
+                "frequency": "MINGGUAN",
+                "first_due_date": first_due_date
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Conflict);
+    }
+
+    #[rocket::async_test]
+    async fn test_create_plan_invalid_frequency() {
+        let client = setup_client().await;
+        let payment_id = create_installment_payment(&client).await;
+
+        let response = client.post(format!("/api/payments/{}/plan", payment_id))
+            .json(&serde_json::json!({
+                "number_of_terms": 3,
+                "frequency": "HARIAN",
+                "first_due_date": Utc::now()
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[rocket::async_test]
+    async fn test_plan_for_unknown_payment() {
+        let client = setup_client().await;
+
+        let response = client.get("/api/payments/PMT-404/plan").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        let response = client.post("/api/payments/PMT-404/plan")
+            .json(&serde_json::json!({
+                "number_of_terms": 3,
+                "frequency": "BULANAN",
+                "first_due_date": Utc::now()
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn test_get_plan_missing() {
+        let client = setup_client().await;
+        let payment_id = create_installment_payment(&client).await;
+
+        let response = client.get(format!("/api/payments/{}/plan", payment_id)).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
@@ -1,9 +1,25 @@
 use rocket::fairing::AdHoc;
 
+use crate::manajemen_pembayaran::service::webhook_service::WebhookService;
+
 pub mod payment_controller;
+pub mod refund_controller;
+pub mod installment_plan_controller;
+pub mod overdue_controller;
+pub mod report_controller;
+pub mod webhook_controller;
+pub mod cash_drawer_controller;
 
 pub fn route_stage() -> AdHoc {
     AdHoc::on_ignite("Manajemen Pembayaran Routes", |rocket| async {
-        rocket.mount("/api", payment_controller::routes())
+        rocket
+            .manage(WebhookService::from_env())
+            .mount("/api", payment_controller::routes())
+            .mount("/api", refund_controller::routes())
+            .mount("/api", installment_plan_controller::routes())
+            .mount("/api", overdue_controller::routes())
+            .mount("/api", report_controller::routes())
+            .mount("/api", webhook_controller::routes())
+            .mount("/api", cash_drawer_controller::routes())
     })
 }
\ No newline at end of file
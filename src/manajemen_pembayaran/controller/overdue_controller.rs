@@ -0,0 +1,124 @@
+use rocket::{get, routes, Route, State};
+use rocket::serde::json::Json;
+use rocket::http::Status;
+use autometrics::autometrics;
+use chrono::Utc;
+
+use crate::manajemen_pembayaran::controller::payment_controller::ApiResponse;
+use crate::manajemen_pembayaran::model::overdue::OverdueAccount;
+use crate::manajemen_pembayaran::service::overdue_service::{OverdueService, OverduePolicy};
+use sqlx::{Any, Pool};
+
+#[autometrics]
+#[get("/payments/overdue")]
+pub async fn get_overdue_accounts(db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<Vec<OverdueAccount>>>) {
+    let overdue_service = OverdueService::new(OverduePolicy::from_env());
+
+    match overdue_service.get_overdue_accounts(db, Utc::now()).await {
+        Ok(accounts) => (
+            Status::Ok,
+            Json(ApiResponse {
+                success: true,
+                message: format!("Successfully retrieved {} overdue accounts", accounts.len()),
+                data: Some(accounts),
+            }),
+        ),
+        Err(e) => (
+            Status::InternalServerError,
+            Json(ApiResponse {
+                success: false,
+                message: format!("Failed to retrieve overdue accounts: {:?}", e),
+                data: None,
+            }),
+        ),
+    }
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![get_overdue_accounts]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rocket::local::asynchronous::Client;
+    use crate::common::money::Rupiah;
+    use crate::manajemen_pembayaran::controller::payment_controller;
+    use crate::manajemen_pembayaran::model::payment::Payment;
+
+    async fn setup_client() -> Client {
+        sqlx::any::install_default_drivers();
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        let rocket = rocket::build()
+            .manage(db)
+            .mount("/api", payment_controller::routes())
+            .mount("/api", routes());
+
+        Client::tracked(rocket).await.expect("Must provide a valid Rocket instance")
+    }
+
+    async fn create_installment_payment(client: &Client, due_date: chrono::DateTime<Utc>) -> String {
+        use crate::transaksi_penjualan::model::transaksi::Transaksi;
+        use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        let transaksi = Transaksi::new(1, "Budi".to_string(), Rupiah::from_rupiah(500_000), None);
+        let transaksi = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi)
+            .await
+            .unwrap();
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": transaksi.id.to_string(),
+                "amount": "500000.00",
+                "method": "CASH",
+                "status": "CICILAN",
+                "due_date": due_date.to_rfc3339()
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let created: ApiResponse<Payment> = response.into_json().await.unwrap();
+        created.data.unwrap().id
+    }
+
+    #[rocket::async_test]
+    async fn test_get_overdue_accounts() {
+        let client = setup_client().await;
+        let overdue_id = create_installment_payment(&client, Utc::now() - Duration::days(45)).await;
+        create_installment_payment(&client, Utc::now() + Duration::days(10)).await;
+
+        let response = client.get("/api/payments/overdue").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let accounts: ApiResponse<Vec<OverdueAccount>> = response.into_json().await.unwrap();
+        let accounts = accounts.data.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].payment_id, overdue_id);
+        assert_eq!(accounts[0].days_overdue, 45);
+        assert_eq!(accounts[0].outstanding, Rupiah::from_rupiah(500_000));
+    }
+
+    #[rocket::async_test]
+    async fn test_overdue_route_does_not_shadow_payment_lookup() {
+        let client = setup_client().await;
+
+        let response = client.get("/api/payments/overdue").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.get("/api/payments/PMT-404").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
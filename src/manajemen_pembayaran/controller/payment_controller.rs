@@ -1,33 +1,46 @@
-use std::collections::HashMap;
 use chrono::Utc;
 use serde::{Serialize, Deserialize};
-use rocket::{get, post, put, delete, routes, Route, State, catch};
+use rocket::{get, post, put, delete, routes, Responder, Route, State, catch};
 use rocket::serde::json::Json;
-use rocket::http::Status;
+use rocket::http::{Header, Status};
 use autometrics::autometrics;
+use uuid::Uuid;
 
-use crate::manajemen_pembayaran::model::payment::Payment;
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::common::idempotency::{with_idempotency, IdempotencyError, IdempotencyKey, IdempotentResponse, StoredResponse};
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::model::payment::{Payment, PaymentMethodSummary, TenderLine, TransaksiPaymentSummary};
+use crate::manajemen_pembayaran::model::payment_query::PaymentQueryParams;
 use crate::manajemen_pembayaran::service::payment_service::{PaymentService, PaymentError};
 use sqlx::{Any, Pool};
 
+#[derive(Serialize, Deserialize)]
+pub struct TenderLineRequest {
+    pub method: String,
+    pub amount: Rupiah,
+    pub reference_number: Option<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct CreatePaymentRequest {
     pub transaction_id: String,
-    pub amount: f64,
+    pub amount: Rupiah,
     pub method: String,
     pub status: String,
     pub due_date: Option<String>,
+    #[serde(default)]
+    pub tender_lines: Vec<TenderLineRequest>,
 }
 
 #[derive(Deserialize)]
 pub struct UpdatePaymentStatusRequest {
     pub new_status: String,
-    pub additional_amount: Option<f64>,
+    pub additional_amount: Option<Rupiah>,
 }
 
 #[derive(Deserialize)]
 pub struct AddInstallmentRequest {
-    pub amount: f64,
+    pub amount: Rupiah,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -37,9 +50,39 @@ pub struct ApiResponse<T> {
     pub data: Option<T>,
 }
 
+const CREATE_PAYMENT_SCOPE: &str = "POST /api/payments";
+
 #[autometrics]
 #[post("/payments", format = "json", data = "<payment_request>")]
-pub async fn create_payment(payment_request: Json<CreatePaymentRequest>, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<Payment>>) {
+pub async fn create_payment(cashier: Option<AuthenticatedUser>, payment_request: Json<CreatePaymentRequest>, idempotency_key: IdempotencyKey, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<Payment>>) {
+    with_idempotency(db, CREATE_PAYMENT_SCOPE, idempotency_key, &*payment_request, || {
+        process_create_payment(cashier.as_ref(), &payment_request, db)
+    }).await
+}
+
+impl IdempotentResponse for (Status, Json<ApiResponse<Payment>>) {
+    fn to_stored(&self) -> Result<StoredResponse, IdempotencyError> {
+        StoredResponse::new(self.0, &*self.1)
+    }
+
+    fn from_stored(stored: StoredResponse) -> Result<Self, IdempotencyError> {
+        Ok((stored.status(), Json(stored.body_as()?)))
+    }
+
+    fn from_error(e: IdempotencyError) -> Self {
+        (
+            e.status(),
+            Json(ApiResponse {
+                success: false,
+                message: e.message(),
+                data: None,
+            }),
+        )
+    }
+}
+
+// Kasir yang login menentukan laci kas yang menerima pembayaran; tanpa login pembayaran tetap dicatat
+async fn process_create_payment(cashier: Option<&AuthenticatedUser>, payment_request: &CreatePaymentRequest, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<Payment>>) {
     let payment_service = PaymentService::new();
     
     let method: crate::manajemen_pembayaran::model::payment::PaymentMethod = match payment_service.parse_payment_method(&payment_request.method) {
@@ -87,18 +130,45 @@ pub async fn create_payment(payment_request: Json<CreatePaymentRequest>, db: &St
         None => None,
     };
     
+    let payment_id = payment_service.generate_payment_id();
+    let mut tender_lines = Vec::with_capacity(payment_request.tender_lines.len());
+    for line in &payment_request.tender_lines {
+        let tender_method = match payment_service.parse_payment_method(&line.method) {
+            Ok(m) => m,
+            Err(e) => {
+                return (
+                    Status::BadRequest,
+                    Json(ApiResponse {
+                        success: false,
+                        message: format!("Invalid tender method: {:?}", e),
+                        data: None,
+                    }),
+                );
+            }
+        };
+        tender_lines.push(TenderLine {
+            id: format!("TND-{}", Uuid::new_v4()),
+            payment_id: payment_id.clone(),
+            method: tender_method,
+            amount: line.amount,
+            reference_number: line.reference_number.clone(),
+        });
+    }
+    
     let payment = Payment {
-        id: payment_service.generate_payment_id(),
+        id: payment_id,
         transaction_id: payment_request.transaction_id.clone(),
         amount: payment_request.amount,
         method,
         status,
         payment_date: Utc::now(),
         installments: Vec::new(),
+        tender_lines,
         due_date,
+        late_fee_total: Rupiah::ZERO,
     };
     
-    match payment_service.create_payment(db, payment).await {
+    match payment_service.create_payment(db, cashier, payment).await {
         Ok(created_payment) => (
             Status::Created,
             Json(ApiResponse {
@@ -107,6 +177,22 @@ pub async fn create_payment(payment_request: Json<CreatePaymentRequest>, db: &St
                 data: Some(created_payment),
             }),
         ),
+        Err(PaymentError::InvalidInput(msg)) => (
+            Status::BadRequest,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
+        Err(PaymentError::NotFound(msg)) => (
+            Status::NotFound,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
         Err(e) => (
             Status::InternalServerError,
             Json(ApiResponse {
@@ -151,53 +237,116 @@ pub async fn get_payment_by_id(id: String, db: &State<Pool<Any>>) -> (Status, Js
     }
 }
 
+// Badan respons tetap daftar pembayaran; jumlah seluruh hasil untuk paginasi ada di header X-Total-Count
+#[derive(Responder)]
+pub struct PaymentListResponse {
+    body: Json<ApiResponse<Vec<Payment>>>,
+    total_count: Header<'static>,
+}
+
 #[autometrics]
-#[get("/payments?<status>&<method>&<transaction_id>")]
-pub async fn get_all_payments(
-    status: Option<String>,
-    method: Option<String>,
-    transaction_id: Option<String>,
-    db: &State<Pool<Any>>
-) -> (Status, Json<ApiResponse<Vec<Payment>>>) {
+#[get("/payments?<params..>")]
+pub async fn get_all_payments(params: PaymentQueryParams, db: &State<Pool<Any>>) -> Result<PaymentListResponse, (Status, Json<ApiResponse<Vec<Payment>>>)> {
     let payment_service = PaymentService::new();
+
+    let query = match payment_service.parse_payment_query(params) {
+        Ok(query) => query,
+        Err(e) => {
+            return Err((
+                Status::BadRequest,
+                Json(ApiResponse {
+                    success: false,
+                    message: format!("{:?}", e),
+                    data: None,
+                }),
+            ));
+        }
+    };
     
-    let mut filters = HashMap::new();
-    if let Some(status_str) = status {
-        filters.insert("status".to_string(), status_str);
-    }
-    if let Some(method_str) = method {
-        filters.insert("method".to_string(), method_str);
-    }
-    if let Some(tx_id) = transaction_id {
-        filters.insert("transaction_id".to_string(), tx_id);
+    match payment_service.search_payments(db, &query).await {
+        Ok(page) => Ok(PaymentListResponse {
+            body: Json(ApiResponse {
+                success: true,
+                message: format!("Successfully retrieved {} of {} payments", page.data.len(), page.total_count),
+                data: Some(page.data),
+            }),
+            total_count: Header::new("X-Total-Count", page.total_count.to_string()),
+        }),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(ApiResponse {
+                success: false,
+                message: format!("Failed to retrieve payments: {:?}", e),
+                data: None,
+            }),
+        )),
     }
-    
-    let filters_option = if filters.is_empty() { None } else { Some(filters) };
-    
-    match payment_service.get_all_payments(db, filters_option).await {
-        Ok(payments) => (
+}
+
+
+// Ranked below the transaksi routes mounted under /api/transaksi, which share this path shape
+#[autometrics]
+#[get("/transaksi/<id>/payments", rank = 2)]
+pub async fn get_transaksi_payments(id: i32, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<TransaksiPaymentSummary>>) {
+    let payment_service = PaymentService::new();
+
+    match payment_service.get_transaksi_payment_summary(db, id).await {
+        Ok(summary) => (
             Status::Ok,
             Json(ApiResponse {
                 success: true,
-                message: format!("Successfully retrieved {} payments", payments.len()),
-                data: Some(payments),
+                message: "Transaksi payments retrieved successfully".to_string(),
+                data: Some(summary),
+            }),
+        ),
+        Err(PaymentError::NotFound(msg)) => (
+            Status::NotFound,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
             }),
         ),
         Err(e) => (
             Status::InternalServerError,
             Json(ApiResponse {
                 success: false,
-                message: format!("Failed to retrieve payments: {:?}", e),
+                message: format!("Failed to retrieve transaksi payments: {:?}", e),
                 data: None,
             }),
         ),
     }
 }
 
+#[autometrics]
+#[get("/payments/summary/methods")]
+pub async fn get_totals_by_method(db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<Vec<PaymentMethodSummary>>>) {
+    let payment_service = PaymentService::new();
+
+    match payment_service.get_totals_by_method(db).await {
+        Ok(summaries) => (
+            Status::Ok,
+            Json(ApiResponse {
+                success: true,
+                message: "Payment totals by method retrieved successfully".to_string(),
+                data: Some(summaries),
+            }),
+        ),
+        Err(e) => (
+            Status::InternalServerError,
+            Json(ApiResponse {
+                success: false,
+                message: format!("Failed to retrieve payment totals: {:?}", e),
+                data: None,
+            }),
+        ),
+    }
+}
 
 #[autometrics]
 #[put("/payments/<id>/status", format = "json", data = "<status_request>")]
 pub async fn update_payment_status(
+    cashier: Option<AuthenticatedUser>,
     id: String,
     status_request: Json<UpdatePaymentStatusRequest>,
     db: &State<Pool<Any>>
@@ -218,7 +367,7 @@ pub async fn update_payment_status(
         }
     };
     
-    match payment_service.update_payment_status(db, id, new_status, status_request.additional_amount).await {
+    match payment_service.update_payment_status(db, cashier.as_ref(), id, new_status, status_request.additional_amount).await {
         Ok(updated_payment) => (
             Status::Ok,
             Json(ApiResponse {
@@ -235,6 +384,22 @@ pub async fn update_payment_status(
                 data: None,
             }),
         ),
+        Err(PaymentError::InvalidTransition(msg)) => (
+            Status::Conflict,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
+        Err(PaymentError::InvalidInput(msg)) => (
+            Status::BadRequest,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
         Err(e) => (
             Status::InternalServerError,
             Json(ApiResponse {
@@ -250,13 +415,14 @@ pub async fn update_payment_status(
 #[autometrics]
 #[post("/payments/<id>/installments", format = "json", data = "<installment_request>")]
 pub async fn add_installment(
+    cashier: Option<AuthenticatedUser>,
     id: String,
     installment_request: Json<AddInstallmentRequest>,
     db: &State<Pool<Any>>
 ) -> (Status, Json<ApiResponse<Payment>>) {
     let payment_service = PaymentService::new();
     
-    match payment_service.add_installment(db, &id, installment_request.amount).await {
+    match payment_service.add_installment(db, cashier.as_ref(), &id, installment_request.amount).await {
         Ok(updated_payment) => (
             Status::Ok,
             Json(ApiResponse {
@@ -330,6 +496,8 @@ pub fn routes() -> Vec<Route> {
         create_payment,
         get_payment_by_id,
         get_all_payments,
+        get_totals_by_method,
+        get_transaksi_payments,
         update_payment_status,
         add_installment,
         delete_payment
@@ -357,7 +525,10 @@ pub fn bad_request_catcher() -> Json<ApiResponse<()>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
     use chrono::{Utc};
+    use crate::manajemen_pembayaran::model::payment::PaymentMethod;
+    use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
     
     #[test]
     fn test_api_response_serialization() {
@@ -402,7 +573,7 @@ mod tests {
 
         let request: CreatePaymentRequest = serde_json::from_str(json_str).unwrap();
         assert_eq!(request.transaction_id, "TXN-123");
-        assert_eq!(request.amount, 1000.0);
+        assert_eq!(request.amount, Rupiah::from_rupiah(1000));
         assert_eq!(request.method, "CASH");
         assert_eq!(request.status, "PENDING");
         assert!(request.due_date.is_some());
@@ -419,7 +590,7 @@ mod tests {
 
         let request: CreatePaymentRequest = serde_json::from_str(json_str).unwrap();
         assert_eq!(request.transaction_id, "TXN-456");
-        assert_eq!(request.amount, 500.0);
+        assert_eq!(request.amount, Rupiah::from_rupiah(500));
         assert_eq!(request.method, "CREDIT_CARD");
         assert_eq!(request.status, "COMPLETED");
         assert!(request.due_date.is_none());
@@ -434,7 +605,7 @@ mod tests {
 
         let request: UpdatePaymentStatusRequest = serde_json::from_str(json_str).unwrap();
         assert_eq!(request.new_status, "COMPLETED");
-        assert_eq!(request.additional_amount, Some(250.0));
+        assert_eq!(request.additional_amount, Some(Rupiah::from_rupiah(250)));
     }
 
     #[test]
@@ -455,7 +626,7 @@ mod tests {
         }"#;
 
         let request: AddInstallmentRequest = serde_json::from_str(json_str).unwrap();
-        assert_eq!(request.amount, 300.0);
+        assert_eq!(request.amount, Rupiah::from_rupiah(300));
     }
 
     #[test]
@@ -491,12 +662,14 @@ mod tests {
 
         let payment = Payment {
             id: "PMT-123".to_string(),
-            transaction_id: "TXN-456".to_string(),            amount: 1000.0,
+            transaction_id: "TXN-456".to_string(),            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
 
         let response = ApiResponse {
@@ -522,20 +695,24 @@ mod tests {
             Payment {
                 id: "PMT-1".to_string(),
                 transaction_id: "TXN-1".to_string(),
-                amount: 500.0,                method: PaymentMethod::Cash,
+                amount: Rupiah::from_rupiah(500),                method: PaymentMethod::Cash,
                 status: PaymentStatus::Paid,
                 payment_date: Utc::now(),
                 installments: Vec::new(),
+                tender_lines: Vec::new(),
                 due_date: None,
+                late_fee_total: Rupiah::ZERO,
             },
             Payment {
                 id: "PMT-2".to_string(),
                 transaction_id: "TXN-2".to_string(),
-                amount: 750.0,                method: PaymentMethod::CreditCard,
+                amount: Rupiah::from_rupiah(750),                method: PaymentMethod::CreditCard,
                 status: PaymentStatus::Installment,
                 payment_date: Utc::now(),
                 installments: Vec::new(),
+                tender_lines: Vec::new(),
                 due_date: Some(Utc::now()),
+                late_fee_total: Rupiah::ZERO,
             },
         ];
 
@@ -605,9 +782,10 @@ mod tests {
     fn test_json_serialization_roundtrip() {
         let original_request = CreatePaymentRequest {
             transaction_id: "TXN-TEST".to_string(),
-            amount: 1234.56,
+            amount: Rupiah::from_sen(123_456),
             method: "BANK_TRANSFER".to_string(),
             status: "INSTALLMENT".to_string(),
+            tender_lines: Vec::new(),
             due_date: Some("2024-06-15T10:30:00Z".to_string()),
         };
 
@@ -646,12 +824,14 @@ mod tests {
         let payment = Payment {
             id: "PMT-TEST-123".to_string(),
             transaction_id: "TXN-TEST-456".to_string(),
-            amount: 1500.0,
+            amount: Rupiah::from_rupiah(1500),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
 
         let response = ApiResponse {
@@ -666,7 +846,7 @@ mod tests {
         
         if let Some(data) = response.data {
             assert_eq!(data.id, "PMT-TEST-123");
-            assert_eq!(data.amount, 1500.0);
+            assert_eq!(data.amount, Rupiah::from_rupiah(1500));
         }
     }
 
@@ -723,11 +903,11 @@ mod tests {
         assert_eq!(payment_id.len(), 40);
         
         let installment_request = AddInstallmentRequest {
-            amount: 500.0,
+            amount: Rupiah::from_rupiah(500),
         };
         
-        assert_eq!(installment_request.amount, 500.0);
-        assert!(installment_request.amount > 0.0);
+        assert_eq!(installment_request.amount, Rupiah::from_rupiah(500));
+        assert!(installment_request.amount.is_positive());
     }
 
     #[test]
@@ -751,7 +931,7 @@ mod tests {
     fn test_routes_function() {
         let route_list = routes();
         
-        assert_eq!(route_list.len(), 6);
+        assert_eq!(route_list.len(), 8);
         
         assert!(!route_list.is_empty());
     }    
@@ -843,4 +1023,692 @@ mod tests {
         let delete_error = format!("Failed to delete payment: {:?}", "TestError");
         assert!(delete_error.contains("Failed to delete payment"));
     }
-}
\ No newline at end of file
+
+    async fn setup_client() -> rocket::local::asynchronous::Client {
+        setup_client_on("sqlite::memory:", 1).await
+    }
+
+    async fn setup_client_on(url: &str, max_connections: u32) -> rocket::local::asynchronous::Client {
+        sqlx::any::install_default_drivers();
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        let rocket = rocket::build()
+            .manage(db)
+            .mount("/api", routes())
+            .attach(crate::transaksi_penjualan::controller::route_stage());
+
+        rocket::local::asynchronous::Client::tracked(rocket).await.expect("Must provide a valid Rocket instance")
+    }
+
+    async fn create_transaksi(client: &rocket::local::asynchronous::Client, total_harga: Rupiah) -> String {
+        use crate::transaksi_penjualan::model::transaksi::Transaksi;
+        use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        let transaksi = Transaksi::new(1, "Budi".to_string(), total_harga, None);
+        let created = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi)
+            .await
+            .unwrap();
+        created.id.to_string()
+    }
+
+    #[rocket::async_test]
+    async fn test_update_payment_status_enforces_transitions() {
+        let client = setup_client().await;
+        let transaksi_id = create_transaksi(&client, Rupiah::from_rupiah(500_000)).await;
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": transaksi_id,
+                "amount": 500000,
+                "method": "BANK_TRANSFER",
+                "status": "MENUNGGU"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+        let created: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let payment_id = created.data.unwrap().id;
+
+        let response = client.put(format!("/api/payments/{}/status", payment_id))
+            .json(&serde_json::json!({ "new_status": "DIKEMBALIKAN" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Conflict);
+
+        let response = client.put(format!("/api/payments/{}/status", payment_id))
+            .json(&serde_json::json!({ "new_status": "LUNAS" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let updated: ApiResponse<Payment> = response.into_json().await.unwrap();
+        assert_eq!(updated.data.unwrap().status.to_string(), "LUNAS");
+
+        let response = client.put(format!("/api/payments/{}/status", payment_id))
+            .json(&serde_json::json!({ "new_status": "CICILAN" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Conflict);
+
+        // DIKEMBALIKAN hanya lewat endpoint refund agar selalu ada catatan refund
+        let response = client.put(format!("/api/payments/{}/status", payment_id))
+            .json(&serde_json::json!({ "new_status": "DIKEMBALIKAN" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Conflict);
+        let response = client.get(format!("/api/payments/{}", payment_id)).dispatch().await;
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        assert_eq!(payment.data.unwrap().status, PaymentStatus::Paid);
+    }
+
+    #[rocket::async_test]
+    async fn test_create_payment_rejects_terminal_status() {
+        let client = setup_client().await;
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": "TRX-TERMINAL",
+                "amount": 1000,
+                "method": "CASH",
+                "status": "DIKEMBALIKAN"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[rocket::async_test]
+    async fn test_create_split_tender_payment() {
+        let client = setup_client().await;
+        let transaksi_id = create_transaksi(&client, Rupiah::from_rupiah(1_500_000)).await;
+        let other_transaksi_id = create_transaksi(&client, Rupiah::from_rupiah(200_000)).await;
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": transaksi_id,
+                "amount": "1500000.00",
+                "method": "CASH",
+                "status": "LUNAS",
+                "tender_lines": [
+                    { "method": "CASH", "amount": "500000.00" },
+                    { "method": "BANK_TRANSFER", "amount": "750000.00", "reference_number": "BCA-0001" },
+                    { "method": "E_WALLET", "amount": "250000.00", "reference_number": "OVO-77" }
+                ]
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+        let created: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let payment = created.data.unwrap();
+        assert_eq!(payment.tender_lines.len(), 3);
+        assert_eq!(payment.tender_lines[1].reference_number, Some("BCA-0001".to_string()));
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": other_transaksi_id,
+                "amount": 200000,
+                "method": "BANK_TRANSFER",
+                "status": "LUNAS"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let response = client.get("/api/payments?method=BANK_TRANSFER").dispatch().await;
+        let payments: ApiResponse<Vec<Payment>> = response.into_json().await.unwrap();
+        assert_eq!(payments.data.unwrap().len(), 2);
+
+        let response = client.get("/api/payments/summary/methods").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let summary: ApiResponse<Vec<PaymentMethodSummary>> = response.into_json().await.unwrap();
+        let summary = summary.data.unwrap();
+        let bank = summary.iter().find(|s| s.method == PaymentMethod::BankTransfer).unwrap();
+        assert_eq!(bank.total, Rupiah::from_rupiah(950_000));
+        assert_eq!(bank.tender_count, 2);
+        let cash = summary.iter().find(|s| s.method == PaymentMethod::Cash).unwrap();
+        assert_eq!(cash.total, Rupiah::from_rupiah(500_000));
+    }
+
+    #[rocket::async_test]
+    async fn test_create_split_tender_payment_total_mismatch() {
+        let client = setup_client().await;
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": "TRX-SPLIT-BAD",
+                "amount": 1000,
+                "method": "CASH",
+                "status": "LUNAS",
+                "tender_lines": [
+                    { "method": "CASH", "amount": 400 },
+                    { "method": "E_WALLET", "amount": 500 }
+                ]
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[rocket::async_test]
+    async fn test_create_payment_validates_transaksi() {
+        let client = setup_client().await;
+        let transaksi_id = create_transaksi(&client, Rupiah::from_rupiah(300_000)).await;
+
+        let payment_for = |transaction_id: &str, amount: i64| serde_json::json!({
+            "transaction_id": transaction_id,
+            "amount": amount,
+            "method": "CASH",
+            "status": "LUNAS"
+        });
+
+        let response = client.post("/api/payments").json(&payment_for("TRX-FREEFORM", 1000)).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.post("/api/payments").json(&payment_for("9999", 1000)).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        let response = client.post("/api/payments").json(&payment_for(&transaksi_id, 300_001)).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.post("/api/payments").json(&payment_for(&transaksi_id, 200_000)).dispatch().await;
+        assert_eq!(response.status(), Status::Created);
+
+        let response = client.post("/api/payments").json(&payment_for(&transaksi_id, 100_001)).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        sqlx::query("UPDATE transaksi SET status = 'DIBATALKAN' WHERE id = $1")
+            .bind(transaksi_id.parse::<i32>().unwrap())
+            .execute(db)
+            .await
+            .unwrap();
+
+        let response = client.post("/api/payments").json(&payment_for(&transaksi_id, 1000)).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[rocket::async_test]
+    async fn test_get_transaksi_payments_summary() {
+        let client = setup_client().await;
+        let transaksi_id = create_transaksi(&client, Rupiah::from_rupiah(1_000_000)).await;
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": transaksi_id,
+                "amount": 400_000,
+                "method": "CASH",
+                "status": "LUNAS"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": transaksi_id,
+                "amount": 600_000,
+                "method": "BANK_TRANSFER",
+                "status": "MENUNGGU"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let response = client.get(format!("/api/transaksi/{}/payments", transaksi_id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let summary: ApiResponse<TransaksiPaymentSummary> = response.into_json().await.unwrap();
+        let summary = summary.data.unwrap();
+        assert_eq!(summary.payments.len(), 2);
+        assert_eq!(summary.total_harga, Rupiah::from_rupiah(1_000_000));
+        assert_eq!(summary.paid, Rupiah::from_rupiah(400_000));
+        assert_eq!(summary.outstanding, Rupiah::from_rupiah(600_000));
+
+        let response = client.get("/api/transaksi/9999/payments").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn test_refund_reopens_outstanding_balance() {
+        use crate::manajemen_pembayaran::enums::refund_reason::RefundReason;
+        use crate::manajemen_pembayaran::service::refund_service::{NewRefund, RefundService};
+
+        let client = setup_client().await;
+        let transaksi_id = create_transaksi(&client, Rupiah::from_rupiah(300_000)).await;
+        let payment_for = |amount: i64| serde_json::json!({
+            "transaction_id": transaksi_id,
+            "amount": amount,
+            "method": "CASH",
+            "status": "LUNAS"
+        });
+
+        let response = client.post("/api/payments").json(&payment_for(300_000)).dispatch().await;
+        assert_eq!(response.status(), Status::Created);
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let payment = payment.data.unwrap();
+
+        RefundService::new().create_refund(State::get(client.rocket()).unwrap(), None, &payment.id, NewRefund {
+            amount: Rupiah::from_rupiah(100_000),
+            reason: RefundReason::Defective,
+            method: None,
+            notes: None,
+        }).await.unwrap();
+
+        let response = client.get(format!("/api/transaksi/{}/payments", transaksi_id)).dispatch().await;
+        let summary: ApiResponse<TransaksiPaymentSummary> = response.into_json().await.unwrap();
+        let summary = summary.data.unwrap();
+        assert_eq!(summary.refunded, Rupiah::from_rupiah(100_000));
+        assert_eq!(summary.outstanding, Rupiah::from_rupiah(100_000));
+
+        let response = client.post("/api/payments").json(&payment_for(100_001)).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+        let response = client.post("/api/payments").json(&payment_for(100_000)).dispatch().await;
+        assert_eq!(response.status(), Status::Created);
+    }
+
+    #[rocket::async_test]
+    async fn test_concurrent_payments_cannot_exceed_total() {
+        let path = std::env::temp_dir().join(format!("transaksi-payments-{}.db", uuid::Uuid::new_v4()));
+        let client = setup_client_on(&format!("sqlite://{}?mode=rwc", path.display()), 4).await;
+        let transaksi_id = create_transaksi(&client, Rupiah::from_rupiah(100_000)).await;
+
+        let body = serde_json::json!({
+            "transaction_id": transaksi_id,
+            "amount": "60000.00",
+            "method": "CASH",
+            "status": "LUNAS"
+        });
+        let (first, second) = futures::join!(
+            client.post("/api/payments").json(&body).dispatch(),
+            client.post("/api/payments").json(&body).dispatch()
+        );
+
+        let mut statuses = vec![first.status(), second.status()];
+        drop((first, second));
+        statuses.sort_by_key(|s| s.code);
+        assert_eq!(statuses, vec![Status::Created, Status::BadRequest]);
+
+        drop(client);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    async fn create_installment_payment(client: &rocket::local::asynchronous::Client, amount: &str) -> String {
+        let transaction_id = create_transaksi(client, Rupiah::from_rupiah(1_000_000)).await;
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": transaction_id,
+                "amount": amount,
+                "method": "CASH",
+                "status": "CICILAN"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let created: ApiResponse<Payment> = response.into_json().await.unwrap();
+        created.data.unwrap().id
+    }
+
+    #[rocket::async_test]
+    async fn test_refunded_installment_payment_counts_only_installments_paid() {
+        use crate::manajemen_pembayaran::enums::refund_reason::RefundReason;
+        use crate::manajemen_pembayaran::service::refund_service::RefundService;
+
+        let client = setup_client().await;
+        let payment_id = create_installment_payment(&client, "1000000.00").await;
+        let response = client.post(format!("/api/payments/{}/installments", payment_id))
+            .json(&serde_json::json!({ "amount": "300000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let refund = RefundService::new()
+            .refund_remaining(State::get(client.rocket()).unwrap(), None, &payment_id, RefundReason::Cancellation, None)
+            .await
+            .unwrap();
+        assert_eq!(refund.amount, Rupiah::from_rupiah(300_000));
+        assert_eq!(refund.resulting_status, PaymentStatus::Refunded);
+
+        let response = client.get(format!("/api/payments/{}", payment_id)).dispatch().await;
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let transaksi_id = payment.data.unwrap().transaction_id;
+
+        let response = client.get(format!("/api/transaksi/{}/payments", transaksi_id)).dispatch().await;
+        let summary: ApiResponse<TransaksiPaymentSummary> = response.into_json().await.unwrap();
+        let summary = summary.data.unwrap();
+        assert_eq!(summary.paid, Rupiah::from_rupiah(300_000));
+        assert_eq!(summary.refunded, Rupiah::from_rupiah(300_000));
+        assert_eq!(summary.outstanding, Rupiah::from_rupiah(1_000_000));
+    }
+
+    #[rocket::async_test]
+    async fn test_installment_cannot_be_marked_paid_while_outstanding() {
+        let client = setup_client().await;
+        let payment_id = create_installment_payment(&client, "1000000.00").await;
+        let response = client.post(format!("/api/payments/{}/installments", payment_id))
+            .json(&serde_json::json!({ "amount": "300000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        for status in ["LUNAS", "TERLAMBAT", "LUNAS"] {
+            let response = client.put(format!("/api/payments/{}/status", payment_id))
+                .json(&serde_json::json!({ "new_status": status }))
+                .dispatch()
+                .await;
+            let expected = if status == "LUNAS" { Status::Conflict } else { Status::Ok };
+            assert_eq!(response.status(), expected, "status: {}", status);
+        }
+
+        let response = client.get(format!("/api/payments/{}", payment_id)).dispatch().await;
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let payment = payment.data.unwrap();
+        assert_eq!(payment.status, PaymentStatus::Overdue);
+        assert_eq!(payment.outstanding(), Rupiah::from_rupiah(700_000));
+    }
+
+    #[rocket::async_test]
+    async fn test_add_installment_persists_and_marks_paid() {
+        let client = setup_client().await;
+        let payment_id = create_installment_payment(&client, "300000.00").await;
+
+        let response = client.post(format!("/api/payments/{}/installments", payment_id))
+            .json(&serde_json::json!({ "amount": "100000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let payment = payment.data.unwrap();
+        assert_eq!(payment.installments.len(), 1);
+        assert_eq!(payment.status, PaymentStatus::Installment);
+
+        let response = client.post(format!("/api/payments/{}/installments", payment_id))
+            .json(&serde_json::json!({ "amount": "200000.01" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.post(format!("/api/payments/{}/installments", payment_id))
+            .json(&serde_json::json!({ "amount": "200000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.get(format!("/api/payments/{}", payment_id)).dispatch().await;
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let payment = payment.data.unwrap();
+        assert_eq!(payment.status, PaymentStatus::Paid);
+        assert_eq!(payment.installments_total(), Rupiah::from_rupiah(300_000));
+
+        let response = client.post(format!("/api/payments/{}/installments", payment_id))
+            .json(&serde_json::json!({ "amount": "1.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[rocket::async_test]
+    async fn test_add_installment_rejects_non_positive_and_unknown() {
+        let client = setup_client().await;
+        let payment_id = create_installment_payment(&client, "300000.00").await;
+
+        let response = client.post(format!("/api/payments/{}/installments", payment_id))
+            .json(&serde_json::json!({ "amount": "0" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.post("/api/payments/PMT-404/installments")
+            .json(&serde_json::json!({ "amount": "1000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn test_update_status_additional_amount_uses_installment_checks() {
+        let client = setup_client().await;
+        let payment_id = create_installment_payment(&client, "300000.00").await;
+        let url = format!("/api/payments/{}/status", payment_id);
+
+        let response = client.put(url.as_str())
+            .json(&serde_json::json!({ "new_status": "CICILAN", "additional_amount": "300000.01" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.put(url.as_str())
+            .json(&serde_json::json!({ "new_status": "LUNAS", "additional_amount": "1000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.put(url.as_str())
+            .json(&serde_json::json!({ "new_status": "CICILAN", "additional_amount": "300000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let payment = payment.data.unwrap();
+        assert_eq!(payment.status, PaymentStatus::Paid);
+        assert_eq!(payment.installments_total(), Rupiah::from_rupiah(300_000));
+    }
+
+    #[rocket::async_test]
+    async fn test_installments_cover_late_fees_before_paid() {
+        let client = setup_client().await;
+        let payment_id = create_installment_payment(&client, "100000.00").await;
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        sqlx::query("
+            INSERT INTO late_fees (id, payment_id, period_number, amount, assessed_at)
+            VALUES ('LF-1', $1, 1, CAST('10000.00' AS DECIMAL(15,2)), $2)
+        ")
+            .bind(&payment_id)
+            .bind(Utc::now().to_rfc3339())
+            .execute(db)
+            .await
+            .unwrap();
+
+        let url = format!("/api/payments/{}/installments", payment_id);
+        let response = client.post(url.as_str())
+            .json(&serde_json::json!({ "amount": "100000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let payment = payment.data.unwrap();
+        assert_eq!(payment.status, PaymentStatus::Installment);
+        assert_eq!(payment.late_fee_total, Rupiah::from_rupiah(10_000));
+        assert_eq!(payment.outstanding(), Rupiah::from_rupiah(10_000));
+
+        let response = client.post(url.as_str())
+            .json(&serde_json::json!({ "amount": "10000.01" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.post(url.as_str())
+            .json(&serde_json::json!({ "amount": "10000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        assert_eq!(payment.data.unwrap().status, PaymentStatus::Paid);
+    }
+
+    #[rocket::async_test]
+    async fn test_concurrent_installments_cannot_overpay() {
+        let path = std::env::temp_dir().join(format!("installments-{}.db", uuid::Uuid::new_v4()));
+        let client = setup_client_on(&format!("sqlite://{}?mode=rwc", path.display()), 4).await;
+        let payment_id = create_installment_payment(&client, "100000.00").await;
+
+        let url = format!("/api/payments/{}/installments", payment_id);
+        let body = serde_json::json!({ "amount": "60000.00" });
+        let (first, second) = futures::join!(
+            client.post(url.as_str()).json(&body).dispatch(),
+            client.post(url.as_str()).json(&body).dispatch()
+        );
+
+        let mut statuses = vec![first.status(), second.status()];
+        drop((first, second));
+        statuses.sort_by_key(|s| s.code);
+        assert_eq!(statuses, vec![Status::Ok, Status::BadRequest]);
+
+        let response = client.get(format!("/api/payments/{}", payment_id)).dispatch().await;
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        let payment = payment.data.unwrap();
+        assert_eq!(payment.installments.len(), 1);
+        assert_eq!(payment.installments_total(), Rupiah::from_rupiah(60_000));
+
+        drop(client);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[rocket::async_test]
+    async fn test_create_payment_with_idempotency_key_replays_response() {
+        use rocket::http::Header;
+
+        let client = setup_client().await;
+        let transaksi_id = create_transaksi(&client, Rupiah::from_rupiah(500_000)).await;
+        let body = serde_json::json!({
+            "transaction_id": transaksi_id,
+            "amount": "500000.00",
+            "method": "CASH",
+            "status": "LUNAS"
+        });
+
+        let mut created_ids = Vec::new();
+        for _ in 0..2 {
+            let response = client.post("/api/payments")
+                .header(Header::new("Idempotency-Key", "kasir-1-0001"))
+                .json(&body)
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Created);
+            let created: ApiResponse<Payment> = response.into_json().await.unwrap();
+            created_ids.push(created.data.unwrap().id);
+        }
+        assert_eq!(created_ids[0], created_ids[1]);
+
+        let response = client.get(format!("/api/transaksi/{}/payments", transaksi_id)).dispatch().await;
+        let summary: ApiResponse<TransaksiPaymentSummary> = response.into_json().await.unwrap();
+        assert_eq!(summary.data.unwrap().payments.len(), 1);
+
+        let response = client.post("/api/payments")
+            .header(Header::new("Idempotency-Key", "kasir-1-0001"))
+            .json(&serde_json::json!({
+                "transaction_id": transaksi_id,
+                "amount": "250000.00",
+                "method": "CASH",
+                "status": "LUNAS"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Conflict);
+    }
+
+    async fn seed_dated_payments(client: &rocket::local::asynchronous::Client) {
+        use chrono::TimeZone;
+        use crate::manajemen_pembayaran::model::payment::Installment;
+        use crate::manajemen_pembayaran::repository::payment_repository::PembayaranRepository;
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        for day in 1..=5u32 {
+            let id = format!("PMT-DAY-{}", day);
+            let status = if day == 3 { PaymentStatus::Installment } else { PaymentStatus::Paid };
+            let installments = if day == 3 {
+                (1..=2).map(|n| Installment {
+                    id: format!("INST-DAY-3-{}", n),
+                    payment_id: id.clone(),
+                    amount: Rupiah::from_rupiah(50_000),
+                    payment_date: Utc.with_ymd_and_hms(2025, 1, 3 + n, 9, 0, 0).unwrap(),
+                }).collect()
+            } else {
+                Vec::new()
+            };
+            let payment = Payment {
+                id: id.clone(),
+                transaction_id: format!("TXN-{}", day),
+                amount: Rupiah::from_rupiah(100_000 * day as i64),
+                method: PaymentMethod::Cash,
+                status,
+                payment_date: Utc.with_ymd_and_hms(2025, 1, day, 10, 30, 0).unwrap(),
+                installments,
+                tender_lines: Vec::new(),
+                due_date: (day % 2 == 1).then(|| Utc.with_ymd_and_hms(2025, 2, day, 0, 0, 0).unwrap()),
+                late_fee_total: Rupiah::ZERO,
+            };
+            PembayaranRepository::create(db.acquire().await.unwrap(), &payment).await.unwrap();
+        }
+    }
+
+    async fn list_payments(client: &rocket::local::asynchronous::Client, query: &str) -> (Vec<Payment>, i64) {
+        let response = client.get(format!("/api/payments?{}", query)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let total_count = response.headers().get_one("X-Total-Count").unwrap().parse().unwrap();
+        let payments: ApiResponse<Vec<Payment>> = response.into_json().await.unwrap();
+        (payments.data.unwrap(), total_count)
+    }
+
+    #[rocket::async_test]
+    async fn test_list_payments_filters_sorts_and_paginates() {
+        let client = setup_client().await;
+        seed_dated_payments(&client).await;
+
+        let (payments, total_count) = list_payments(&client, "paid_from=2025-01-02&paid_to=2025-01-04").await;
+        assert_eq!(total_count, 3);
+        let ids: Vec<&str> = payments.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["PMT-DAY-4", "PMT-DAY-3", "PMT-DAY-2"]);
+
+        let (payments, _) = list_payments(&client, "min_amount=250000&max_amount=450000.00&sort=amount&order=asc").await;
+        let amounts: Vec<Rupiah> = payments.iter().map(|p| p.amount).collect();
+        assert_eq!(amounts, vec![Rupiah::from_rupiah(300_000), Rupiah::from_rupiah(400_000)]);
+        assert_eq!(payments[0].installments.len(), 2);
+        assert_eq!(payments[1].installments.len(), 0);
+
+        let (payments, total_count) = list_payments(&client, "due_from=2025-02-02&due_to=2025-02-05&status=LUNAS").await;
+        assert_eq!(total_count, 1);
+        assert_eq!(payments[0].id, "PMT-DAY-5");
+
+        let (payments, total_count) = list_payments(&client, "sort=amount&order=desc&limit=2&offset=1").await;
+        assert_eq!(total_count, 5);
+        let ids: Vec<&str> = payments.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["PMT-DAY-4", "PMT-DAY-3"]);
+
+        let (payments, total_count) = list_payments(&client, "").await;
+        assert_eq!((payments.len(), total_count), (5, 5));
+
+        let (payments, total_count) = list_payments(&client, "offset=10").await;
+        assert_eq!(total_count, 5);
+        assert!(payments.is_empty());
+    }
+
+    #[rocket::async_test]
+    async fn test_list_payments_rejects_invalid_filters() {
+        let client = setup_client().await;
+
+        for query in [
+            "limit=0",
+            "limit=1000",
+            "offset=-1",
+            "sort=amount;DROP",
+            "order=up",
+            "paid_from=kemarin",
+            "min_amount=abc",
+            "min_amount=500&max_amount=100",
+            "paid_from=2025-02-01&paid_to=2025-01-01",
+            "status=UNKNOWN",
+        ] {
+            let response = client.get(format!("/api/payments?{}", query)).dispatch().await;
+            assert_eq!(response.status(), Status::BadRequest, "query: {}", query);
+        }
+    }
+}
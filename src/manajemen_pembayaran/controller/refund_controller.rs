@@ -0,0 +1,318 @@
+use serde::Deserialize;
+use rocket::{get, post, routes, Route, State};
+use rocket::serde::json::Json;
+use rocket::http::Status;
+use autometrics::autometrics;
+
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::controller::payment_controller::ApiResponse;
+use crate::manajemen_pembayaran::model::refund::Refund;
+use crate::manajemen_pembayaran::service::payment_service::{PaymentService, PaymentError};
+use crate::manajemen_pembayaran::service::refund_service::{RefundService, NewRefund};
+use sqlx::{Any, Pool};
+
+#[derive(Deserialize)]
+pub struct CreateRefundRequest {
+    pub amount: Rupiah,
+    pub reason: String,
+    pub method: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[autometrics]
+#[post("/payments/<id>/refunds", format = "json", data = "<refund_request>")]
+pub async fn create_refund(
+    cashier: Option<AuthenticatedUser>,
+    id: String,
+    refund_request: Json<CreateRefundRequest>,
+    db: &State<Pool<Any>>
+) -> (Status, Json<ApiResponse<Refund>>) {
+    let refund_service = RefundService::new();
+
+    let reason = match refund_service.parse_refund_reason(&refund_request.reason) {
+        Ok(r) => r,
+        Err(e) => {
+            return (
+                Status::BadRequest,
+                Json(ApiResponse {
+                    success: false,
+                    message: format!("Invalid refund reason: {:?}", e),
+                    data: None,
+                }),
+            );
+        }
+    };
+
+    let method = match &refund_request.method {
+        Some(method_str) => match PaymentService::new().parse_payment_method(method_str) {
+            Ok(m) => Some(m),
+            Err(e) => {
+                return (
+                    Status::BadRequest,
+                    Json(ApiResponse {
+                        success: false,
+                        message: format!("Invalid refund method: {:?}", e),
+                        data: None,
+                    }),
+                );
+            }
+        },
+        None => None,
+    };
+
+    let new_refund = NewRefund {
+        amount: refund_request.amount,
+        reason,
+        method,
+        notes: refund_request.notes.clone(),
+    };
+
+    match refund_service.create_refund(db, cashier.as_ref(), &id, new_refund).await {
+        Ok(refund) => (
+            Status::Created,
+            Json(ApiResponse {
+                success: true,
+                message: "Refund created successfully".to_string(),
+                data: Some(refund),
+            }),
+        ),
+        Err(PaymentError::NotFound(msg)) => (
+            Status::NotFound,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
+        Err(PaymentError::InvalidInput(msg)) => (
+            Status::BadRequest,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
+        Err(PaymentError::InvalidTransition(msg)) => (
+            Status::Conflict,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        ),
+        Err(e) => (
+            Status::InternalServerError,
+            Json(ApiResponse {
+                success: false,
+                message: format!("Failed to create refund: {:?}", e),
+                data: None,
+            }),
+        ),
+    }
+}
+
+#[autometrics]
+#[get("/payments/<id>/refunds")]
+pub async fn get_refunds(id: String, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<Vec<Refund>>>) {
+    let refund_service = RefundService::new();
+
+    if let Err(PaymentError::NotFound(msg)) = PaymentService::new().get_payment_by_id(db, &id).await {
+        return (
+            Status::NotFound,
+            Json(ApiResponse {
+                success: false,
+                message: msg,
+                data: None,
+            }),
+        );
+    }
+
+    match refund_service.get_refunds(db, &id).await {
+        Ok(refunds) => (
+            Status::Ok,
+            Json(ApiResponse {
+                success: true,
+                message: format!("Successfully retrieved {} refunds", refunds.len()),
+                data: Some(refunds),
+            }),
+        ),
+        Err(e) => (
+            Status::InternalServerError,
+            Json(ApiResponse {
+                success: false,
+                message: format!("Failed to retrieve refunds: {:?}", e),
+                data: None,
+            }),
+        ),
+    }
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![create_refund, get_refunds]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::local::asynchronous::Client;
+    use crate::manajemen_pembayaran::controller::payment_controller;
+    use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+    use crate::manajemen_pembayaran::model::payment::Payment;
+
+    async fn setup_client() -> Client {
+        setup_client_on("sqlite::memory:", 1).await
+    }
+
+    async fn setup_client_on(url: &str, max_connections: u32) -> Client {
+        sqlx::any::install_default_drivers();
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        let rocket = rocket::build()
+            .manage(db)
+            .mount("/api", payment_controller::routes())
+            .mount("/api", routes());
+
+        Client::tracked(rocket).await.expect("Must provide a valid Rocket instance")
+    }
+
+    async fn create_paid_payment(client: &Client) -> String {
+        use crate::transaksi_penjualan::model::transaksi::Transaksi;
+        use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        let transaksi = Transaksi::new(1, "Budi".to_string(), Rupiah::from_rupiah(130_000), None);
+        let transaksi = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi)
+            .await
+            .unwrap();
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": transaksi.id.to_string(),
+                "amount": "130000.00",
+                "method": "CASH",
+                "status": "LUNAS"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let created: ApiResponse<Payment> = response.into_json().await.unwrap();
+        created.data.unwrap().id
+    }
+
+    #[rocket::async_test]
+    async fn test_partial_then_full_refund() {
+        let client = setup_client().await;
+        let payment_id = create_paid_payment(&client).await;
+
+        let response = client.post(format!("/api/payments/{}/refunds", payment_id))
+            .json(&serde_json::json!({
+                "amount": "65000.00",
+                "reason": "BARANG_RUSAK",
+                "notes": "1 sak semen pecah"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+        let refund: ApiResponse<Refund> = response.into_json().await.unwrap();
+        assert_eq!(refund.data.unwrap().resulting_status, PaymentStatus::Paid);
+
+        let response = client.post(format!("/api/payments/{}/refunds", payment_id))
+            .json(&serde_json::json!({
+                "amount": "65000.01",
+                "reason": "BARANG_RUSAK"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.post(format!("/api/payments/{}/refunds", payment_id))
+            .json(&serde_json::json!({
+                "amount": "65000.00",
+                "reason": "PEMBATALAN",
+                "method": "BANK_TRANSFER"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let response = client.get(format!("/api/payments/{}/refunds", payment_id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let refunds: ApiResponse<Vec<Refund>> = response.into_json().await.unwrap();
+        let refunds = refunds.data.unwrap();
+        assert_eq!(refunds.len(), 2);
+        assert_eq!(refunds[1].resulting_status, PaymentStatus::Refunded);
+
+        let response = client.get(format!("/api/payments/{}", payment_id)).dispatch().await;
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        assert_eq!(payment.data.unwrap().status, PaymentStatus::Refunded);
+
+        let response = client.post(format!("/api/payments/{}/refunds", payment_id))
+            .json(&serde_json::json!({ "amount": 1, "reason": "LAINNYA" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Conflict);
+    }
+
+    #[rocket::async_test]
+    async fn test_concurrent_refunds_cannot_exceed_amount_paid() {
+        let path = std::env::temp_dir().join(format!("refunds-{}.db", uuid::Uuid::new_v4()));
+        let client = setup_client_on(&format!("sqlite://{}?mode=rwc", path.display()), 4).await;
+        let payment_id = create_paid_payment(&client).await;
+
+        let url = format!("/api/payments/{}/refunds", payment_id);
+        let body = serde_json::json!({ "amount": "100000.00", "reason": "BARANG_RUSAK" });
+        let (first, second) = futures::join!(
+            client.post(url.as_str()).json(&body).dispatch(),
+            client.post(url.as_str()).json(&body).dispatch()
+        );
+
+        let mut statuses = vec![first.status(), second.status()];
+        drop((first, second));
+        statuses.sort_by_key(|s| s.code);
+        assert_eq!(statuses, vec![Status::Created, Status::BadRequest]);
+
+        let response = client.get(url.as_str()).dispatch().await;
+        let refunds: ApiResponse<Vec<Refund>> = response.into_json().await.unwrap();
+        assert_eq!(refunds.data.unwrap().len(), 1);
+
+        drop(client);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[rocket::async_test]
+    async fn test_refund_invalid_reason() {
+        let client = setup_client().await;
+        let payment_id = create_paid_payment(&client).await;
+
+        let response = client.post(format!("/api/payments/{}/refunds", payment_id))
+            .json(&serde_json::json!({ "amount": 1000, "reason": "BOSAN" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[rocket::async_test]
+    async fn test_refunds_for_unknown_payment() {
+        let client = setup_client().await;
+
+        let response = client.get("/api/payments/PMT-404/refunds").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        let response = client.post("/api/payments/PMT-404/refunds")
+            .json(&serde_json::json!({ "amount": 1000, "reason": "LAINNYA" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
@@ -0,0 +1,188 @@
+use rocket::{get, routes, Route, State};
+use rocket::serde::json::Json;
+use rocket::http::{ContentType, Status};
+use autometrics::autometrics;
+use chrono::{DateTime, Utc};
+
+use crate::manajemen_pembayaran::controller::payment_controller::ApiResponse;
+use crate::manajemen_pembayaran::model::aging::AgingReport;
+use crate::manajemen_pembayaran::service::aging_service::AgingService;
+use crate::manajemen_pembayaran::service::payment_service::PaymentError;
+use sqlx::{Any, Pool};
+
+fn resolve_as_of(aging_service: &AgingService, as_of: Option<&str>) -> Result<DateTime<Utc>, PaymentError> {
+    match as_of {
+        Some(value) => aging_service.parse_as_of(value),
+        None => Ok(Utc::now()),
+    }
+}
+
+#[autometrics]
+#[get("/payments/reports/aging?<as_of>")]
+pub async fn get_aging_report(as_of: Option<String>, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<AgingReport>>) {
+    let aging_service = AgingService::new();
+
+    let as_of = match resolve_as_of(&aging_service, as_of.as_deref()) {
+        Ok(as_of) => as_of,
+        Err(e) => {
+            return (
+                Status::BadRequest,
+                Json(ApiResponse {
+                    success: false,
+                    message: format!("{:?}", e),
+                    data: None,
+                }),
+            );
+        }
+    };
+
+    match aging_service.build_report(db, as_of).await {
+        Ok(report) => (
+            Status::Ok,
+            Json(ApiResponse {
+                success: true,
+                message: format!("Aging report for {} customers", report.customers.len()),
+                data: Some(report),
+            }),
+        ),
+        Err(e) => (
+            Status::InternalServerError,
+            Json(ApiResponse {
+                success: false,
+                message: format!("Failed to build aging report: {:?}", e),
+                data: None,
+            }),
+        ),
+    }
+}
+
+#[autometrics]
+#[get("/payments/reports/aging/csv?<as_of>")]
+pub async fn get_aging_report_csv(as_of: Option<String>, db: &State<Pool<Any>>) -> (Status, (ContentType, String)) {
+    let aging_service = AgingService::new();
+
+    let as_of = match resolve_as_of(&aging_service, as_of.as_deref()) {
+        Ok(as_of) => as_of,
+        Err(e) => return (Status::BadRequest, (ContentType::Plain, format!("{:?}", e))),
+    };
+
+    match aging_service.build_report(db, as_of).await {
+        Ok(report) => (Status::Ok, (ContentType::CSV, report.to_csv())),
+        Err(e) => (
+            Status::InternalServerError,
+            (ContentType::Plain, format!("Failed to build aging report: {:?}", e)),
+        ),
+    }
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![get_aging_report, get_aging_report_csv]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rocket::local::asynchronous::Client;
+    use crate::common::money::Rupiah;
+    use crate::manajemen_pembayaran::controller::payment_controller;
+
+    async fn setup_client() -> Client {
+        sqlx::any::install_default_drivers();
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        let rocket = rocket::build()
+            .manage(db)
+            .mount("/api", payment_controller::routes())
+            .mount("/api", routes());
+
+        Client::tracked(rocket).await.expect("Must provide a valid Rocket instance")
+    }
+
+    async fn create_installment_payment(client: &Client, id_pelanggan: i32, nama: &str, amount: &str, days_past_due: i64) {
+        use crate::transaksi_penjualan::model::transaksi::Transaksi;
+        use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        let transaksi = Transaksi::new(id_pelanggan, nama.to_string(), Rupiah::from_rupiah(5_000_000), None);
+        let transaksi = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi)
+            .await
+            .unwrap();
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": transaksi.id.to_string(),
+                "amount": amount,
+                "method": "CASH",
+                "status": "CICILAN",
+                "due_date": (Utc::now() - Duration::days(days_past_due)).to_rfc3339()
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+    }
+
+    #[rocket::async_test]
+    async fn test_aging_report_json() {
+        let client = setup_client().await;
+        create_installment_payment(&client, 1, "Andi", "100000.00", 10).await;
+        create_installment_payment(&client, 1, "Andi", "250000.00", 75).await;
+        create_installment_payment(&client, 2, "Budi", "400000.00", -5).await;
+
+        let response = client.get("/api/payments/reports/aging").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let report: ApiResponse<AgingReport> = response.into_json().await.unwrap();
+        let report = report.data.unwrap();
+        assert_eq!(report.customers.len(), 2);
+
+        let andi = report.customers.iter().find(|c| c.id_pelanggan == Some(1)).unwrap();
+        assert_eq!(andi.payment_count, 2);
+        assert_eq!(andi.buckets.days_1_30, Rupiah::from_rupiah(100_000));
+        assert_eq!(andi.buckets.days_61_90, Rupiah::from_rupiah(250_000));
+
+        assert_eq!(report.total.current, Rupiah::from_rupiah(400_000));
+        assert_eq!(report.total.total, Rupiah::from_rupiah(750_000));
+    }
+
+    #[rocket::async_test]
+    async fn test_aging_report_csv() {
+        let client = setup_client().await;
+        create_installment_payment(&client, 1, "Andi", "100000.00", 10).await;
+
+        let response = client.get("/api/payments/reports/aging/csv").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::CSV));
+
+        let body = response.into_string().await.unwrap();
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines[0], "id_pelanggan,nama_pelanggan,jumlah_pembayaran,current,1_30,31_60,61_90,over_90,total");
+        assert_eq!(lines[1], "1,Andi,1,0.00,100000.00,0.00,0.00,0.00,100000.00");
+        assert_eq!(lines[2], ",TOTAL,1,0.00,100000.00,0.00,0.00,0.00,100000.00");
+    }
+
+    #[rocket::async_test]
+    async fn test_aging_report_as_of() {
+        let client = setup_client().await;
+
+        let response = client.get("/api/payments/reports/aging?as_of=2025-05-01").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let report: ApiResponse<AgingReport> = response.into_json().await.unwrap();
+        assert_eq!(report.data.unwrap().as_of.to_rfc3339(), "2025-05-01T00:00:00+00:00");
+
+        let response = client.get("/api/payments/reports/aging?as_of=kemarin").dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.get("/api/payments/reports/aging/csv?as_of=kemarin").dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+}
@@ -0,0 +1,430 @@
+use serde::Deserialize;
+use chrono::Utc;
+use rocket::{get, post, routes, Route, State};
+use rocket::serde::json::Json;
+use rocket::http::{HeaderMap, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use autometrics::autometrics;
+
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::controller::payment_controller::ApiResponse;
+use crate::manajemen_pembayaran::model::webhook_event::{WebhookEvent, WebhookReceipt};
+use crate::manajemen_pembayaran::service::payment_service::PaymentError;
+use crate::manajemen_pembayaran::service::webhook_service::{SimulatedEvent, WebhookService};
+use sqlx::{Any, Pool};
+
+// Setiap provider memakai header signature yang berbeda, jadi seluruh header diteruskan ke service
+pub struct WebhookHeaders<'r>(&'r HeaderMap<'r>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for WebhookHeaders<'r> {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(WebhookHeaders(request.headers()))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SimulateWebhookRequest {
+    pub payment_id: String,
+    pub status: String,
+    pub amount: Option<Rupiah>,
+    pub event_id: Option<String>,
+}
+
+fn error_response<T>(e: PaymentError, context: &str) -> (Status, Json<ApiResponse<T>>) {
+    let (status, message) = match e {
+        PaymentError::Unauthorized(msg) => (Status::Unauthorized, msg),
+        PaymentError::NotFound(msg) => (Status::NotFound, msg),
+        PaymentError::InvalidInput(msg) => (Status::BadRequest, msg),
+        PaymentError::InvalidTransition(msg) => (Status::Conflict, msg),
+        e => (Status::InternalServerError, format!("{}: {:?}", context, e)),
+    };
+
+    (
+        status,
+        Json(ApiResponse {
+            success: false,
+            message,
+            data: None,
+        }),
+    )
+}
+
+fn receipt_response(receipt: WebhookReceipt) -> (Status, Json<ApiResponse<WebhookReceipt>>) {
+    let message = if receipt.duplicate {
+        "Duplicate webhook event ignored".to_string()
+    } else {
+        "Webhook event processed".to_string()
+    };
+
+    (
+        Status::Ok,
+        Json(ApiResponse {
+            success: true,
+            message,
+            data: Some(receipt),
+        }),
+    )
+}
+
+#[autometrics]
+#[post("/webhooks/<provider>", data = "<payload>")]
+pub async fn receive_webhook(
+    provider: &str,
+    payload: Vec<u8>,
+    headers: WebhookHeaders<'_>,
+    webhook_service: &State<WebhookService>,
+    db: &State<Pool<Any>>
+) -> (Status, Json<ApiResponse<WebhookReceipt>>) {
+
+    let signature_header = match webhook_service.provider(provider) {
+        Ok(p) => p.signature_header(),
+        Err(e) => return error_response(e, "Failed to process webhook"),
+    };
+    let signature = headers.0.get_one(signature_header);
+
+    match webhook_service.receive(db, provider, &payload, signature, Utc::now()).await {
+        Ok(receipt) => receipt_response(receipt),
+        Err(e) => error_response(e, "Failed to process webhook"),
+    }
+}
+
+// Simulator menandatangani event sendiri, jadi hanya pengguna yang login yang boleh memakainya
+#[autometrics]
+#[post("/webhooks/simulator/events", format = "json", data = "<simulate_request>")]
+pub async fn simulate_webhook(
+    _user: AuthenticatedUser,
+    simulate_request: Json<SimulateWebhookRequest>,
+    webhook_service: &State<WebhookService>,
+    db: &State<Pool<Any>>
+) -> (Status, Json<ApiResponse<WebhookReceipt>>) {
+    let simulate_request = simulate_request.into_inner();
+
+    let simulated = SimulatedEvent {
+        payment_id: simulate_request.payment_id,
+        status: simulate_request.status,
+        amount: simulate_request.amount,
+        event_id: simulate_request.event_id,
+    };
+
+    match webhook_service.simulate(db, simulated, Utc::now()).await {
+        Ok(receipt) => receipt_response(receipt),
+        Err(e) => error_response(e, "Failed to simulate webhook"),
+    }
+}
+
+// Daftar event memuat payload gateway dan replay mengubah pembayaran, jadi keduanya perlu login
+#[autometrics]
+#[get("/webhooks/events?<payment_id>")]
+pub async fn get_webhook_events(_user: AuthenticatedUser, payment_id: Option<String>, webhook_service: &State<WebhookService>, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<Vec<WebhookEvent>>>) {
+
+    match webhook_service.get_events(db, payment_id.as_deref()).await {
+        Ok(events) => (
+            Status::Ok,
+            Json(ApiResponse {
+                success: true,
+                message: format!("Found {} webhook events", events.len()),
+                data: Some(events),
+            }),
+        ),
+        Err(e) => error_response(e, "Failed to retrieve webhook events"),
+    }
+}
+
+#[autometrics]
+#[post("/webhooks/events/<id>/replay")]
+pub async fn replay_webhook_event(_user: AuthenticatedUser, id: &str, webhook_service: &State<WebhookService>, db: &State<Pool<Any>>) -> (Status, Json<ApiResponse<WebhookEvent>>) {
+
+    match webhook_service.replay(db, id, Utc::now()).await {
+        Ok(event) => (
+            Status::Ok,
+            Json(ApiResponse {
+                success: true,
+                message: "Webhook event replayed".to_string(),
+                data: Some(event),
+            }),
+        ),
+        Err(e) => error_response(e, "Failed to replay webhook event"),
+    }
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![receive_webhook, simulate_webhook, get_webhook_events, replay_webhook_event]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::{ContentType, Header};
+    use rocket::local::asynchronous::Client;
+    use crate::manajemen_pembayaran::controller::payment_controller;
+    use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+    use crate::manajemen_pembayaran::enums::webhook_result::WebhookResult;
+    use crate::manajemen_pembayaran::model::payment::Payment;
+    use crate::manajemen_pembayaran::repository::refund_repository::RefundRepository;
+    use crate::auth::controller::auth::{login, AuthForm};
+    use crate::auth::model::user::User;
+    use crate::auth::service::auth::AuthService;
+    use crate::manajemen_pembayaran::patterns::payment_gateway::SimulatorProvider;
+
+    const SIMULATOR_SECRET: &str = "rahasia-uji";
+
+    async fn setup_client() -> Client {
+        let client = setup_anonymous_client().await;
+        client.post("/api/auth/login")
+            .json(&AuthForm { username: "kasir".to_string(), password: "kasir123".to_string() })
+            .dispatch()
+            .await;
+
+        client
+    }
+
+    async fn setup_anonymous_client() -> Client {
+        sqlx::any::install_default_drivers();
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        AuthService::register_user(db.clone(), User::new("kasir".to_string(), "kasir123".to_string(), false))
+            .await
+            .unwrap();
+
+        let rocket = rocket::build()
+            .manage(db)
+            .manage(false)
+            .manage(WebhookService::new().with_simulator(SimulatorProvider::new(SIMULATOR_SECRET)))
+            .mount("/api", payment_controller::routes())
+            .mount("/api", routes())
+            .mount("/api/auth", rocket::routes![login]);
+
+        Client::tracked(rocket).await.expect("Must provide a valid Rocket instance")
+    }
+
+    async fn create_pending_payment(client: &Client) -> String {
+        use crate::transaksi_penjualan::model::transaksi::Transaksi;
+        use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        let transaksi = Transaksi::new(1, "Budi".to_string(), Rupiah::from_rupiah(150_000), None);
+        let transaksi = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi)
+            .await
+            .unwrap();
+
+        let response = client.post("/api/payments")
+            .json(&serde_json::json!({
+                "transaction_id": transaksi.id.to_string(),
+                "amount": "150000.00",
+                "method": "E_WALLET",
+                "status": "MENUNGGU"
+            }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let created: ApiResponse<Payment> = response.into_json().await.unwrap();
+        created.data.unwrap().id
+    }
+
+    async fn payment_status(client: &Client, payment_id: &str) -> PaymentStatus {
+        let response = client.get(format!("/api/payments/{}", payment_id)).dispatch().await;
+        let payment: ApiResponse<Payment> = response.into_json().await.unwrap();
+        payment.data.unwrap().status
+    }
+
+    fn signed_body(payment_id: &str, event_id: &str, status: &str) -> (String, String) {
+        let body = serde_json::json!({
+            "event_id": event_id,
+            "payment_id": payment_id,
+            "status": status,
+            "amount": "150000.00"
+        }).to_string();
+        let signature = SimulatorProvider::new(SIMULATOR_SECRET).sign(body.as_bytes());
+        (body, signature)
+    }
+
+    #[rocket::async_test]
+    async fn test_receive_webhook_applies_status_and_deduplicates() {
+        let client = setup_client().await;
+        let payment_id = create_pending_payment(&client).await;
+        let (body, signature) = signed_body(&payment_id, "EVT-1", "settlement");
+
+        let response = client.post("/api/webhooks/simulator")
+            .header(ContentType::JSON)
+            .header(Header::new("X-Simulator-Signature", signature.clone()))
+            .body(&body)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let receipt: ApiResponse<WebhookReceipt> = response.into_json().await.unwrap();
+        let receipt = receipt.data.unwrap();
+        assert!(!receipt.duplicate);
+        assert_eq!(receipt.event.result, Some(WebhookResult::Applied));
+        assert_eq!(receipt.event.payload, body);
+        assert_eq!(payment_status(&client, &payment_id).await, PaymentStatus::Paid);
+
+        let response = client.post("/api/webhooks/simulator")
+            .header(ContentType::JSON)
+            .header(Header::new("X-Simulator-Signature", signature))
+            .body(&body)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let receipt: ApiResponse<WebhookReceipt> = response.into_json().await.unwrap();
+        assert!(receipt.data.unwrap().duplicate);
+
+        let response = client.get(format!("/api/webhooks/events?payment_id={}", payment_id)).dispatch().await;
+        let events: ApiResponse<Vec<WebhookEvent>> = response.into_json().await.unwrap();
+        assert_eq!(events.data.unwrap().len(), 1);
+    }
+
+    #[rocket::async_test]
+    async fn test_receive_webhook_rejects_bad_signature() {
+        let client = setup_client().await;
+        let payment_id = create_pending_payment(&client).await;
+        let (body, _) = signed_body(&payment_id, "EVT-1", "settlement");
+
+        let response = client.post("/api/webhooks/simulator")
+            .header(Header::new("X-Simulator-Signature", "00".repeat(32)))
+            .body(&body)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = client.post("/api/webhooks/simulator")
+            .body(&body)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = client.post("/api/webhooks/midtrans")
+            .body(&body)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        assert_eq!(payment_status(&client, &payment_id).await, PaymentStatus::Pending);
+    }
+
+    #[rocket::async_test]
+    async fn test_invalid_transition_is_acknowledged_and_replayable() {
+        let client = setup_client().await;
+        let payment_id = create_pending_payment(&client).await;
+
+        // MENUNGGU tidak bisa langsung menjadi DIKEMBALIKAN
+        let response = client.post("/api/webhooks/simulator/events")
+            .json(&serde_json::json!({ "payment_id": payment_id, "status": "refund", "event_id": "EVT-REFUND" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let receipt: ApiResponse<WebhookReceipt> = response.into_json().await.unwrap();
+        let refund_event = receipt.data.unwrap().event;
+        assert_eq!(refund_event.result, Some(WebhookResult::Ignored));
+        assert_eq!(payment_status(&client, &payment_id).await, PaymentStatus::Pending);
+
+        let response = client.post("/api/webhooks/simulator/events")
+            .json(&serde_json::json!({ "payment_id": payment_id, "status": "settlement" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(payment_status(&client, &payment_id).await, PaymentStatus::Paid);
+
+        let response = client.post(format!("/api/webhooks/events/{}/replay", refund_event.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let replayed: ApiResponse<WebhookEvent> = response.into_json().await.unwrap();
+        assert_eq!(replayed.data.unwrap().result, Some(WebhookResult::Applied));
+        assert_eq!(payment_status(&client, &payment_id).await, PaymentStatus::Refunded);
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        let refunds = RefundRepository::find_by_payment_id(db.acquire().await.unwrap(), &payment_id).await.unwrap();
+        assert_eq!(refunds.len(), 1);
+        assert_eq!(refunds[0].amount, Rupiah::from_rupiah(150_000));
+
+        let response = client.post("/api/webhooks/events/WH-404/replay").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[rocket::async_test]
+    async fn test_replaying_refund_event_does_not_refund_twice() {
+        let client = setup_client().await;
+        let payment_id = create_pending_payment(&client).await;
+
+        client.post("/api/webhooks/simulator/events")
+            .json(&serde_json::json!({ "payment_id": payment_id, "status": "settlement" }))
+            .dispatch()
+            .await;
+
+        // Refund sebagian membuat status tetap LUNAS, jadi replay tidak tertahan oleh cek status
+        let response = client.post("/api/webhooks/simulator/events")
+            .json(&serde_json::json!({ "payment_id": payment_id, "status": "refund", "amount": "50000.00", "event_id": "EVT-PARTIAL" }))
+            .dispatch()
+            .await;
+        let receipt: ApiResponse<WebhookReceipt> = response.into_json().await.unwrap();
+        let refund_event = receipt.data.unwrap().event;
+        assert_eq!(refund_event.result, Some(WebhookResult::Applied));
+
+        for _ in 0..2 {
+            let response = client.post(format!("/api/webhooks/events/{}/replay", refund_event.id)).dispatch().await;
+            assert_eq!(response.status(), Status::Ok);
+            let replayed: ApiResponse<WebhookEvent> = response.into_json().await.unwrap();
+            assert_eq!(replayed.data.unwrap().result, Some(WebhookResult::Unchanged));
+        }
+
+        let db = client.rocket().state::<Pool<Any>>().unwrap();
+        let refunds = RefundRepository::find_by_payment_id(db.acquire().await.unwrap(), &payment_id).await.unwrap();
+        assert_eq!(refunds.len(), 1);
+        assert_eq!(refunds[0].amount, Rupiah::from_rupiah(50_000));
+        assert_eq!(payment_status(&client, &payment_id).await, PaymentStatus::Paid);
+    }
+
+    #[rocket::async_test]
+    async fn test_simulator_requires_login_and_configured_secret() {
+        let client = setup_anonymous_client().await;
+        let payment_id = create_pending_payment(&client).await;
+
+        let response = client.post("/api/webhooks/simulator/events")
+            .json(&serde_json::json!({ "payment_id": payment_id, "status": "settlement" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert_eq!(payment_status(&client, &payment_id).await, PaymentStatus::Pending);
+
+        let response = client.get(format!("/api/webhooks/events?payment_id={}", payment_id)).dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+        let response = client.post("/api/webhooks/events/WH-1/replay").dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let (body, _) = signed_body(&payment_id, "EVT-1", "settlement");
+        let signature = SimulatorProvider::new("buildingstore-simulator-secret").sign(body.as_bytes());
+        let response = client.post("/api/webhooks/simulator")
+            .header(Header::new("X-Simulator-Signature", signature))
+            .body(&body)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[rocket::async_test]
+    async fn test_settlement_amount_mismatch_is_ignored() {
+        let client = setup_client().await;
+        let payment_id = create_pending_payment(&client).await;
+
+        let response = client.post("/api/webhooks/simulator/events")
+            .json(&serde_json::json!({ "payment_id": payment_id, "status": "settlement", "amount": "1000.00" }))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let receipt: ApiResponse<WebhookReceipt> = response.into_json().await.unwrap();
+        assert_eq!(receipt.data.unwrap().event.result, Some(WebhookResult::Ignored));
+        assert_eq!(payment_status(&client, &payment_id).await, PaymentStatus::Pending);
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CashMovementType {
+    CashIn,   // MASUK (tambahan modal, penukaran uang kecil)
+    CashOut,  // KELUAR (setoran ke bank, pengeluaran kecil)
+}
+
+impl CashMovementType {
+    pub fn from_string(movement_type: &str) -> Option<Self> {
+        match movement_type.to_uppercase().as_str() {
+            "MASUK" => Some(CashMovementType::CashIn),
+            "KELUAR" => Some(CashMovementType::CashOut),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CashMovementType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            CashMovementType::CashIn => "MASUK",
+            CashMovementType::CashOut => "KELUAR",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cash_movement_type_roundtrip() {
+        assert_eq!(CashMovementType::CashIn.to_string(), "MASUK");
+        assert_eq!(CashMovementType::CashOut.to_string(), "KELUAR");
+        assert_eq!(CashMovementType::from_string("masuk"), Some(CashMovementType::CashIn));
+        assert_eq!(CashMovementType::from_string("KELUAR"), Some(CashMovementType::CashOut));
+        assert_eq!(CashMovementType::from_string("SETOR"), None);
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CashSessionStatus {
+    Open,    // BUKA
+    Closed,  // TUTUP
+}
+
+impl CashSessionStatus {
+    pub fn from_string(status: &str) -> Option<Self> {
+        match status.to_uppercase().as_str() {
+            "BUKA" => Some(CashSessionStatus::Open),
+            "TUTUP" => Some(CashSessionStatus::Closed),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CashSessionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            CashSessionStatus::Open => "BUKA",
+            CashSessionStatus::Closed => "TUTUP",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cash_session_status_roundtrip() {
+        assert_eq!(CashSessionStatus::Open.to_string(), "BUKA");
+        assert_eq!(CashSessionStatus::Closed.to_string(), "TUTUP");
+        assert_eq!(CashSessionStatus::from_string("buka"), Some(CashSessionStatus::Open));
+        assert_eq!(CashSessionStatus::from_string("TUTUP"), Some(CashSessionStatus::Closed));
+        assert_eq!(CashSessionStatus::from_string("OPEN"), None);
+    }
+}
@@ -1 +1,7 @@
-pub mod payment_status;
\ No newline at end of file
+pub mod payment_status;
+pub mod refund_reason;
+pub mod plan_frequency;
+pub mod schedule_status;
+pub mod webhook_result;
+pub mod cash_session_status;
+pub mod cash_movement_type;
\ No newline at end of file
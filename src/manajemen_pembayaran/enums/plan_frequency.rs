@@ -0,0 +1,68 @@
+use std::fmt;
+use chrono::{DateTime, Duration, Months, Utc};
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PlanFrequency {
+    Weekly,    // MINGGUAN
+    Biweekly,  // DUA_MINGGUAN
+    Monthly,   // BULANAN
+}
+
+impl PlanFrequency {
+    pub fn from_string(frequency: &str) -> Option<Self> {
+        match frequency.to_uppercase().as_str() {
+            "MINGGUAN" => Some(PlanFrequency::Weekly),
+            "DUA_MINGGUAN" => Some(PlanFrequency::Biweekly),
+            "BULANAN" => Some(PlanFrequency::Monthly),
+            _ => None,
+        }
+    }
+
+    // Jatuh tempo ke-n (dimulai dari 0) relatif terhadap jatuh tempo pertama.
+    // Tanggal bulanan yang tidak ada (mis. 31 Februari) dimundurkan ke akhir bulan.
+    pub fn due_date(&self, first_due_date: DateTime<Utc>, term_index: u32) -> Option<DateTime<Utc>> {
+        match self {
+            PlanFrequency::Weekly => first_due_date.checked_add_signed(Duration::weeks(term_index as i64)),
+            PlanFrequency::Biweekly => first_due_date.checked_add_signed(Duration::weeks(2 * term_index as i64)),
+            PlanFrequency::Monthly => first_due_date.checked_add_months(Months::new(term_index)),
+        }
+    }
+}
+
+impl fmt::Display for PlanFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            PlanFrequency::Weekly => "MINGGUAN",
+            PlanFrequency::Biweekly => "DUA_MINGGUAN",
+            PlanFrequency::Monthly => "BULANAN",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_plan_frequency_roundtrip() {
+        for frequency in [PlanFrequency::Weekly, PlanFrequency::Biweekly, PlanFrequency::Monthly] {
+            assert_eq!(PlanFrequency::from_string(&frequency.to_string()), Some(frequency));
+        }
+        assert_eq!(PlanFrequency::from_string("bulanan"), Some(PlanFrequency::Monthly));
+        assert!(PlanFrequency::from_string("MONTHLY").is_none());
+    }
+
+    #[test]
+    fn test_due_dates() {
+        let first = Utc.with_ymd_and_hms(2025, 1, 31, 0, 0, 0).unwrap();
+
+        assert_eq!(PlanFrequency::Weekly.due_date(first, 0), Some(first));
+        assert_eq!(PlanFrequency::Weekly.due_date(first, 2), Some(Utc.with_ymd_and_hms(2025, 2, 14, 0, 0, 0).unwrap()));
+        assert_eq!(PlanFrequency::Biweekly.due_date(first, 1), Some(Utc.with_ymd_and_hms(2025, 2, 14, 0, 0, 0).unwrap()));
+        assert_eq!(PlanFrequency::Monthly.due_date(first, 1), Some(Utc.with_ymd_and_hms(2025, 2, 28, 0, 0, 0).unwrap()));
+        assert_eq!(PlanFrequency::Monthly.due_date(first, 2), Some(Utc.with_ymd_and_hms(2025, 3, 31, 0, 0, 0).unwrap()));
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RefundReason {
+    Defective,     // BARANG_RUSAK
+    WrongItem,     // SALAH_BARANG
+    Overpayment,   // KELEBIHAN_BAYAR
+    Cancellation,  // PEMBATALAN
+    Other,         // LAINNYA
+}
+
+impl RefundReason {
+    pub fn from_string(reason: &str) -> Option<Self> {
+        match reason.to_uppercase().as_str() {
+            "BARANG_RUSAK" => Some(RefundReason::Defective),
+            "SALAH_BARANG" => Some(RefundReason::WrongItem),
+            "KELEBIHAN_BAYAR" => Some(RefundReason::Overpayment),
+            "PEMBATALAN" => Some(RefundReason::Cancellation),
+            "LAINNYA" => Some(RefundReason::Other),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RefundReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            RefundReason::Defective => "BARANG_RUSAK",
+            RefundReason::WrongItem => "SALAH_BARANG",
+            RefundReason::Overpayment => "KELEBIHAN_BAYAR",
+            RefundReason::Cancellation => "PEMBATALAN",
+            RefundReason::Other => "LAINNYA",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refund_reason_roundtrip() {
+        let reasons = vec![
+            RefundReason::Defective,
+            RefundReason::WrongItem,
+            RefundReason::Overpayment,
+            RefundReason::Cancellation,
+            RefundReason::Other,
+        ];
+
+        for reason in reasons {
+            let parsed = RefundReason::from_string(&reason.to_string());
+            assert_eq!(parsed, Some(reason));
+        }
+    }
+
+    #[test]
+    fn test_refund_reason_case_insensitive() {
+        assert_eq!(RefundReason::from_string("barang_rusak"), Some(RefundReason::Defective));
+        assert_eq!(RefundReason::from_string("Pembatalan"), Some(RefundReason::Cancellation));
+    }
+
+    #[test]
+    fn test_refund_reason_invalid() {
+        assert!(RefundReason::from_string("").is_none());
+        assert!(RefundReason::from_string("DEFECTIVE").is_none());
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ScheduleStatus {
+    Upcoming,       // BELUM_JATUH_TEMPO
+    PartiallyPaid,  // SEBAGIAN
+    Paid,           // LUNAS
+    Overdue,        // TERLAMBAT
+}
+
+impl fmt::Display for ScheduleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ScheduleStatus::Upcoming => "BELUM_JATUH_TEMPO",
+            ScheduleStatus::PartiallyPaid => "SEBAGIAN",
+            ScheduleStatus::Paid => "LUNAS",
+            ScheduleStatus::Overdue => "TERLAMBAT",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_status_display() {
+        assert_eq!(ScheduleStatus::Upcoming.to_string(), "BELUM_JATUH_TEMPO");
+        assert_eq!(ScheduleStatus::PartiallyPaid.to_string(), "SEBAGIAN");
+        assert_eq!(ScheduleStatus::Paid.to_string(), "LUNAS");
+        assert_eq!(ScheduleStatus::Overdue.to_string(), "TERLAMBAT");
+    }
+}
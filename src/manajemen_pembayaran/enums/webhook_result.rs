@@ -0,0 +1,45 @@
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WebhookResult {
+    Applied,    // DITERAPKAN
+    Unchanged,  // TIDAK_BERUBAH (status pembayaran sudah sama)
+    Ignored,    // DIABAIKAN (status tidak dikenal, transisi tidak valid, atau pembayaran tidak ada)
+}
+
+impl WebhookResult {
+    pub fn from_string(result: &str) -> Option<Self> {
+        match result.to_uppercase().as_str() {
+            "DITERAPKAN" => Some(WebhookResult::Applied),
+            "TIDAK_BERUBAH" => Some(WebhookResult::Unchanged),
+            "DIABAIKAN" => Some(WebhookResult::Ignored),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for WebhookResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            WebhookResult::Applied => "DITERAPKAN",
+            WebhookResult::Unchanged => "TIDAK_BERUBAH",
+            WebhookResult::Ignored => "DIABAIKAN",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_result_roundtrip() {
+        for result in [WebhookResult::Applied, WebhookResult::Unchanged, WebhookResult::Ignored] {
+            assert_eq!(WebhookResult::from_string(&result.to_string()), Some(result));
+        }
+        assert_eq!(WebhookResult::from_string("diterapkan"), Some(WebhookResult::Applied));
+        assert_eq!(WebhookResult::from_string("SELESAI"), None);
+    }
+}
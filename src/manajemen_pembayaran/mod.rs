@@ -1,5 +1,6 @@
 pub mod model;
 pub mod repository;
 pub mod enums;
+pub mod patterns;
 pub mod service;
 pub mod controller;
\ No newline at end of file
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::common::money::Rupiah;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AgingBuckets {
+    pub current: Rupiah,
+    pub days_1_30: Rupiah,
+    pub days_31_60: Rupiah,
+    pub days_61_90: Rupiah,
+    pub days_over_90: Rupiah,
+    pub total: Rupiah,
+}
+
+impl AgingBuckets {
+    // Saldo yang belum jatuh tempo (atau jatuh tempo hari ini) masuk ke `current`
+    pub fn add(&mut self, days_past_due: i64, amount: Rupiah) {
+        let bucket = match days_past_due {
+            d if d <= 0 => &mut self.current,
+            1..=30 => &mut self.days_1_30,
+            31..=60 => &mut self.days_31_60,
+            61..=90 => &mut self.days_61_90,
+            _ => &mut self.days_over_90,
+        };
+        *bucket += amount;
+        self.total += amount;
+    }
+
+    pub fn merge(&mut self, other: &AgingBuckets) {
+        self.current += other.current;
+        self.days_1_30 += other.days_1_30;
+        self.days_31_60 += other.days_31_60;
+        self.days_61_90 += other.days_61_90;
+        self.days_over_90 += other.days_over_90;
+        self.total += other.total;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CustomerAging {
+    pub id_pelanggan: Option<i32>,
+    pub nama_pelanggan: String,
+    pub payment_count: usize,
+    pub buckets: AgingBuckets,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AgingReport {
+    pub as_of: DateTime<Utc>,
+    pub customers: Vec<CustomerAging>,
+    pub total: AgingBuckets,
+}
+
+impl AgingReport {
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("id_pelanggan,nama_pelanggan,jumlah_pembayaran,current,1_30,31_60,61_90,over_90,total\n");
+
+        for customer in &self.customers {
+            let id = customer.id_pelanggan.map(|id| id.to_string()).unwrap_or_default();
+            csv.push_str(&Self::csv_row(&id, &customer.nama_pelanggan, customer.payment_count, &customer.buckets));
+        }
+
+        let payment_count = self.customers.iter().map(|c| c.payment_count).sum();
+        csv.push_str(&Self::csv_row("", "TOTAL", payment_count, &self.total));
+        csv
+    }
+
+    fn csv_row(id: &str, name: &str, payment_count: usize, buckets: &AgingBuckets) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            id,
+            Self::csv_field(name),
+            payment_count,
+            buckets.current,
+            buckets.days_1_30,
+            buckets.days_31_60,
+            buckets.days_61_90,
+            buckets.days_over_90,
+            buckets.total,
+        )
+    }
+
+    fn csv_field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_boundaries() {
+        let mut buckets = AgingBuckets::default();
+        for days in [-5, 0, 1, 30, 31, 60, 61, 90, 91, 400] {
+            buckets.add(days, Rupiah::from_rupiah(1_000));
+        }
+
+        assert_eq!(buckets.current, Rupiah::from_rupiah(2_000));
+        assert_eq!(buckets.days_1_30, Rupiah::from_rupiah(2_000));
+        assert_eq!(buckets.days_31_60, Rupiah::from_rupiah(2_000));
+        assert_eq!(buckets.days_61_90, Rupiah::from_rupiah(2_000));
+        assert_eq!(buckets.days_over_90, Rupiah::from_rupiah(2_000));
+        assert_eq!(buckets.total, Rupiah::from_rupiah(10_000));
+    }
+
+    #[test]
+    fn test_to_csv_escapes_names_and_adds_total_row() {
+        let mut buckets = AgingBuckets::default();
+        buckets.add(45, Rupiah::from_sen(12_345_050));
+
+        let report = AgingReport {
+            as_of: Utc::now(),
+            customers: vec![CustomerAging {
+                id_pelanggan: Some(7),
+                nama_pelanggan: "Toko \"Maju\", Jaya".to_string(),
+                payment_count: 2,
+                buckets: buckets.clone(),
+            }],
+            total: buckets,
+        };
+
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "7,\"Toko \"\"Maju\"\", Jaya\",2,0.00,0.00,123450.50,0.00,0.00,123450.50");
+        assert_eq!(lines[2], ",TOTAL,2,0.00,0.00,123450.50,0.00,0.00,123450.50");
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::cash_movement_type::CashMovementType;
+use crate::manajemen_pembayaran::enums::cash_session_status::CashSessionStatus;
+use crate::manajemen_pembayaran::model::payment::{PaymentMethod, PaymentMethodSummary};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CashDrawerSession {
+    pub id: String,
+    pub status: CashSessionStatus,
+    pub opened_by_user_id: i64,
+    pub opened_by: String,
+    pub opening_float: Rupiah,
+    pub opened_at: DateTime<Utc>,
+    pub closed_by: Option<String>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub expected_amount: Option<Rupiah>,
+    pub counted_amount: Option<Rupiah>,
+    pub variance: Option<Rupiah>,
+    pub notes: Option<String>,
+    pub movements: Vec<CashMovement>,
+    pub payments: Vec<SessionPayment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CashMovement {
+    pub id: String,
+    pub session_id: String,
+    pub movement_type: CashMovementType,
+    pub amount: Rupiah,
+    pub reason: String,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+// Uang yang diterima (atau dikembalikan, bernilai negatif) selama sesi, per metode pembayaran.
+// `reference_id` menunjuk ke pembayaran, baris tender, cicilan, atau refund asalnya.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SessionPayment {
+    pub id: String,
+    pub session_id: String,
+    pub payment_id: String,
+    pub reference_id: String,
+    pub method: PaymentMethod,
+    pub amount: Rupiah,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ShiftReport {
+    pub session_id: String,
+    pub status: CashSessionStatus,
+    pub opened_by: String,
+    pub opened_at: DateTime<Utc>,
+    pub closed_by: Option<String>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub opening_float: Rupiah,
+    pub totals_by_method: Vec<PaymentMethodSummary>,
+    pub cash_sales: Rupiah,
+    pub cash_in: Rupiah,
+    pub cash_out: Rupiah,
+    pub expected_cash: Rupiah,
+    pub counted_amount: Option<Rupiah>,
+    pub variance: Option<Rupiah>,
+}
+
+impl CashDrawerSession {
+    pub fn cash_sales(&self) -> Rupiah {
+        self.payments.iter()
+            .filter(|p| p.method == PaymentMethod::Cash)
+            .map(|p| p.amount)
+            .sum()
+    }
+
+    pub fn movement_total(&self, movement_type: &CashMovementType) -> Rupiah {
+        self.movements.iter()
+            .filter(|m| &m.movement_type == movement_type)
+            .map(|m| m.amount)
+            .sum()
+    }
+
+    pub fn expected_cash(&self) -> Rupiah {
+        self.opening_float + self.cash_sales()
+            + self.movement_total(&CashMovementType::CashIn)
+            - self.movement_total(&CashMovementType::CashOut)
+    }
+
+    pub fn totals_by_method(&self) -> Vec<PaymentMethodSummary> {
+        [PaymentMethod::Cash, PaymentMethod::CreditCard, PaymentMethod::BankTransfer, PaymentMethod::EWallet]
+            .into_iter()
+            .filter_map(|method| {
+                let entries: Vec<&SessionPayment> = self.payments.iter().filter(|p| p.method == method).collect();
+                if entries.is_empty() {
+                    return None;
+                }
+                Some(PaymentMethodSummary {
+                    total: entries.iter().map(|p| p.amount).sum(),
+                    tender_count: entries.len() as i64,
+                    method,
+                })
+            })
+            .collect()
+    }
+
+    pub fn shift_report(&self) -> ShiftReport {
+        ShiftReport {
+            session_id: self.id.clone(),
+            status: self.status.clone(),
+            opened_by: self.opened_by.clone(),
+            opened_at: self.opened_at,
+            closed_by: self.closed_by.clone(),
+            closed_at: self.closed_at,
+            opening_float: self.opening_float,
+            totals_by_method: self.totals_by_method(),
+            cash_sales: self.cash_sales(),
+            cash_in: self.movement_total(&CashMovementType::CashIn),
+            cash_out: self.movement_total(&CashMovementType::CashOut),
+            expected_cash: self.expected_amount.unwrap_or_else(|| self.expected_cash()),
+            counted_amount: self.counted_amount,
+            variance: self.variance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> CashDrawerSession {
+        let entry = |method: PaymentMethod, amount: i64| SessionPayment {
+            id: "CSP-1".to_string(),
+            session_id: "CDS-1".to_string(),
+            payment_id: "PMT-1".to_string(),
+            reference_id: "PMT-1".to_string(),
+            method,
+            amount: Rupiah::from_rupiah(amount),
+            recorded_at: Utc::now(),
+        };
+        let movement = |movement_type: CashMovementType, amount: i64| CashMovement {
+            id: "CMV-1".to_string(),
+            session_id: "CDS-1".to_string(),
+            movement_type,
+            amount: Rupiah::from_rupiah(amount),
+            reason: "Setoran".to_string(),
+            created_by: "kasir".to_string(),
+            created_at: Utc::now(),
+        };
+
+        CashDrawerSession {
+            id: "CDS-1".to_string(),
+            status: CashSessionStatus::Open,
+            opened_by_user_id: 1,
+            opened_by: "kasir".to_string(),
+            opening_float: Rupiah::from_rupiah(500_000),
+            opened_at: Utc::now(),
+            closed_by: None,
+            closed_at: None,
+            expected_amount: None,
+            counted_amount: None,
+            variance: None,
+            notes: None,
+            movements: vec![
+                movement(CashMovementType::CashIn, 100_000),
+                movement(CashMovementType::CashOut, 300_000),
+            ],
+            payments: vec![
+                entry(PaymentMethod::Cash, 250_000),
+                entry(PaymentMethod::Cash, 75_000),
+                entry(PaymentMethod::Cash, -25_000),
+                entry(PaymentMethod::EWallet, 400_000),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_expected_cash() {
+        let session = session();
+
+        assert_eq!(session.cash_sales(), Rupiah::from_rupiah(300_000));
+        assert_eq!(session.expected_cash(), Rupiah::from_rupiah(600_000));
+    }
+
+    #[test]
+    fn test_totals_by_method_skips_unused_methods() {
+        let totals = session().totals_by_method();
+
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].method, PaymentMethod::Cash);
+        assert_eq!(totals[0].total, Rupiah::from_rupiah(300_000));
+        assert_eq!(totals[0].tender_count, 3);
+        assert_eq!(totals[1].method, PaymentMethod::EWallet);
+        assert_eq!(totals[1].total, Rupiah::from_rupiah(400_000));
+    }
+
+    #[test]
+    fn test_shift_report_prefers_recorded_expected_amount() {
+        let mut session = session();
+        assert_eq!(session.shift_report().expected_cash, Rupiah::from_rupiah(600_000));
+
+        session.expected_amount = Some(Rupiah::from_rupiah(550_000));
+        session.counted_amount = Some(Rupiah::from_rupiah(540_000));
+        session.variance = Some(Rupiah::from_rupiah(-10_000));
+
+        let report = session.shift_report();
+        assert_eq!(report.expected_cash, Rupiah::from_rupiah(550_000));
+        assert_eq!(report.variance, Some(Rupiah::from_rupiah(-10_000)));
+    }
+}
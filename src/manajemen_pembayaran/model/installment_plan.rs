@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::plan_frequency::PlanFrequency;
+use crate::manajemen_pembayaran::enums::schedule_status::ScheduleStatus;
+use crate::manajemen_pembayaran::model::payment::Payment;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct InstallmentPlan {
+    pub id: String,
+    pub payment_id: String,
+    pub number_of_terms: i32,
+    pub frequency: PlanFrequency,
+    pub first_due_date: DateTime<Utc>,
+    pub down_payment: Rupiah,
+    pub principal: Rupiah,
+    pub created_at: DateTime<Utc>,
+    pub schedule: Vec<ScheduledInstallment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ScheduledInstallment {
+    pub id: String,
+    pub plan_id: String,
+    pub term_number: i32,
+    pub due_date: DateTime<Utc>,
+    pub amount_due: Rupiah,
+    pub amount_paid: Rupiah,
+    pub status: ScheduleStatus,
+}
+
+impl InstallmentPlan {
+    // Bagian dari total cicilan yang dihitung untuk jadwal; cicilan sebelum rencana dibuat
+    // dan uang muka tidak ikut dialokasikan ke termin.
+    pub fn paid_toward_schedule(&self, payment: &Payment) -> Rupiah {
+        let offset = payment.amount - self.principal;
+        let toward_schedule = payment.installments_total() - offset;
+        if toward_schedule.is_negative() {
+            Rupiah::ZERO
+        } else {
+            toward_schedule
+        }
+    }
+
+    // Cocokkan cicilan yang masuk ke termin secara berurutan berdasarkan jatuh tempo
+    pub fn match_installments(&mut self, payment: &Payment, now: DateTime<Utc>) {
+        let mut remaining = self.paid_toward_schedule(payment);
+
+        self.schedule.sort_by_key(|term| term.term_number);
+        for term in self.schedule.iter_mut() {
+            let paid = remaining.min(term.amount_due);
+            remaining -= paid;
+
+            term.amount_paid = paid;
+            term.status = if paid == term.amount_due {
+                ScheduleStatus::Paid
+            } else if term.due_date < now {
+                ScheduleStatus::Overdue
+            } else if paid.is_positive() {
+                ScheduleStatus::PartiallyPaid
+            } else {
+                ScheduleStatus::Upcoming
+            };
+        }
+    }
+
+    pub fn outstanding(&self) -> Rupiah {
+        self.schedule.iter().map(|term| term.amount_due - term.amount_paid).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+    use crate::manajemen_pembayaran::model::payment::{Installment, PaymentMethod};
+
+    fn installment_payment(installments: &[i64]) -> Payment {
+        Payment {
+            id: "PMT-PLAN".to_string(),
+            transaction_id: "1".to_string(),
+            amount: Rupiah::from_rupiah(1_000_000),
+            method: PaymentMethod::Cash,
+            status: PaymentStatus::Installment,
+            payment_date: Utc::now(),
+            installments: installments.iter().enumerate().map(|(i, amount)| Installment {
+                id: format!("INST-{}", i),
+                payment_id: "PMT-PLAN".to_string(),
+                amount: Rupiah::from_rupiah(*amount),
+                payment_date: Utc::now(),
+            }).collect(),
+            tender_lines: Vec::new(),
+            due_date: None,
+            late_fee_total: Rupiah::ZERO,
+        }
+    }
+
+    fn plan(first_due_date: DateTime<Utc>) -> InstallmentPlan {
+        let schedule = (0..3).map(|i| ScheduledInstallment {
+            id: format!("SCH-{}", i),
+            plan_id: "PLAN-1".to_string(),
+            term_number: i + 1,
+            due_date: first_due_date + Duration::weeks(4 * i as i64),
+            amount_due: Rupiah::from_rupiah(300_000),
+            amount_paid: Rupiah::ZERO,
+            status: ScheduleStatus::Upcoming,
+        }).collect();
+
+        InstallmentPlan {
+            id: "PLAN-1".to_string(),
+            payment_id: "PMT-PLAN".to_string(),
+            number_of_terms: 3,
+            frequency: PlanFrequency::Monthly,
+            first_due_date,
+            down_payment: Rupiah::from_rupiah(100_000),
+            principal: Rupiah::from_rupiah(900_000),
+            created_at: Utc::now(),
+            schedule,
+        }
+    }
+
+    #[test]
+    fn test_down_payment_not_applied_to_schedule() {
+        let mut plan = plan(Utc::now() + Duration::days(1));
+        let payment = installment_payment(&[100_000]);
+
+        plan.match_installments(&payment, Utc::now());
+
+        assert_eq!(plan.paid_toward_schedule(&payment), Rupiah::ZERO);
+        assert!(plan.schedule.iter().all(|term| term.status == ScheduleStatus::Upcoming));
+        assert_eq!(plan.outstanding(), Rupiah::from_rupiah(900_000));
+    }
+
+    #[test]
+    fn test_installments_fill_terms_in_order() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        let mut plan = plan(Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap());
+        let payment = installment_payment(&[100_000, 300_000, 150_000]);
+
+        plan.match_installments(&payment, now);
+
+        assert_eq!(plan.schedule[0].status, ScheduleStatus::Paid);
+        assert_eq!(plan.schedule[1].status, ScheduleStatus::PartiallyPaid);
+        assert_eq!(plan.schedule[1].amount_paid, Rupiah::from_rupiah(150_000));
+        assert_eq!(plan.schedule[2].status, ScheduleStatus::Upcoming);
+        assert_eq!(plan.outstanding(), Rupiah::from_rupiah(450_000));
+    }
+
+    #[test]
+    fn test_unpaid_term_past_due_is_overdue() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 15, 0, 0, 0).unwrap();
+        let mut plan = plan(Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap());
+        let payment = installment_payment(&[100_000, 300_000, 100_000]);
+
+        plan.match_installments(&payment, now);
+
+        assert_eq!(plan.schedule[0].status, ScheduleStatus::Paid);
+        assert_eq!(plan.schedule[1].status, ScheduleStatus::Overdue);
+        assert_eq!(plan.schedule[1].amount_paid, Rupiah::from_rupiah(100_000));
+        assert_eq!(plan.schedule[2].status, ScheduleStatus::Upcoming);
+    }
+}
@@ -1 +1,8 @@
 pub mod payment;
+pub mod refund;
+pub mod installment_plan;
+pub mod overdue;
+pub mod aging;
+pub mod webhook_event;
+pub mod cash_drawer;
+pub mod payment_query;
\ No newline at end of file
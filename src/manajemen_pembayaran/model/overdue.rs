@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct LateFee {
+    pub id: String,
+    pub payment_id: String,
+    pub period_number: i32,
+    pub amount: Rupiah,
+    pub assessed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OverdueAccount {
+    pub payment_id: String,
+    pub transaction_id: String,
+    pub status: PaymentStatus,
+    pub overdue_since: DateTime<Utc>,
+    pub days_overdue: i64,
+    pub overdue_amount: Rupiah,
+    pub outstanding: Rupiah,
+    pub late_fee_total: Rupiah,
+    pub late_fees: Vec<LateFee>,
+}
+
+// Event pengingat yang dikirim ke notifier setiap kali pembayaran baru terlambat
+// atau dikenakan denda periode berikutnya.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ReminderEvent {
+    pub account: OverdueAccount,
+    pub newly_overdue: bool,
+    pub new_late_fee: Rupiah,
+    pub raised_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OverdueRunReport {
+    pub checked: usize,
+    pub marked_overdue: usize,
+    pub cleared: usize,
+    pub late_fees_assessed: usize,
+    pub reminders_sent: usize,
+    pub failed: usize,
+}
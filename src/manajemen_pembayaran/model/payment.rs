@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use crate::common::money::Rupiah;
 use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
 use serde::{Serialize, Deserialize};
 use std::fmt;
@@ -11,6 +12,18 @@ pub enum PaymentMethod {
     EWallet,
 }
 
+impl PaymentMethod {
+    pub fn from_string(method: &str) -> Option<Self> {
+        match method.to_uppercase().as_str() {
+            "CASH" => Some(PaymentMethod::Cash),
+            "CREDIT_CARD" => Some(PaymentMethod::CreditCard),
+            "BANK_TRANSFER" => Some(PaymentMethod::BankTransfer),
+            "E_WALLET" => Some(PaymentMethod::EWallet),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for PaymentMethod {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -27,12 +40,71 @@ impl fmt::Display for PaymentMethod {
 pub struct Payment {
     pub id: String,
     pub transaction_id: String,
-    pub amount: f64,
+    pub amount: Rupiah,
     pub method: PaymentMethod,
     pub status: PaymentStatus,
     pub payment_date: DateTime<Utc>,
     pub installments: Vec<Installment>,
+    pub tender_lines: Vec<TenderLine>,
     pub due_date: Option<DateTime<Utc>>,
+    // Total denda keterlambatan yang sudah dikenakan; dimuat dari tabel late_fees
+    #[serde(default)]
+    pub late_fee_total: Rupiah,
+}
+
+impl Payment {
+    pub fn tender_total(&self) -> Rupiah {
+        self.tender_lines.iter().map(|t| t.amount).sum()
+    }
+
+    // Validasi pembayaran split tender: setiap baris harus positif dan totalnya sama dengan jumlah pembayaran
+    pub fn validate_tender_lines(&self) -> Result<(), String> {
+        if self.tender_lines.is_empty() {
+            return Ok(());
+        }
+
+        if self.tender_lines.iter().any(|t| !t.amount.is_positive()) {
+            return Err("Tender line amount must be greater than 0".to_string());
+        }
+
+        if !self.tender_lines.iter().any(|t| t.method == self.method) {
+            return Err(format!("Payment method {} must be one of the tender line methods", self.method));
+        }
+
+        let total = self.tender_total();
+        if total != self.amount {
+            return Err(format!("Tender lines total {} does not match payment amount {}", total, self.amount));
+        }
+
+        Ok(())
+    }
+
+    pub fn installments_total(&self) -> Rupiah {
+        self.installments.iter().map(|i| i.amount).sum()
+    }
+
+    // Kewajiban pelanggan: pokok pembayaran ditambah denda keterlambatan
+    pub fn total_due(&self) -> Rupiah {
+        self.amount + self.late_fee_total
+    }
+
+    // Sisa tagihan setelah cicilan, termasuk denda yang belum dibayar
+    pub fn outstanding(&self) -> Rupiah {
+        self.total_due() - self.installments_total()
+    }
+
+    // Uang yang benar-benar sudah diterima: pembayaran lunas dihitung penuh,
+    // cicilan dihitung dari jumlah cicilan yang tercatat. Pembayaran cicilan yang direfund
+    // hanya pernah menerima cicilannya, bukan seluruh jumlah pembayaran.
+    pub fn amount_paid(&self) -> Rupiah {
+        match self.status {
+            PaymentStatus::Paid => self.amount.max(self.installments_total()),
+            PaymentStatus::Refunded if !self.installments.is_empty() => self.installments_total(),
+            PaymentStatus::Refunded => self.amount,
+            PaymentStatus::Installment | PaymentStatus::Overdue => self.installments_total(),
+            PaymentStatus::Pending | PaymentStatus::Failed | PaymentStatus::Void => Rupiah::ZERO,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,10 +112,40 @@ pub struct Payment {
 pub struct Installment {
     pub id: String,
     pub payment_id: String,
-    pub amount: f64,
+    pub amount: Rupiah,
     pub payment_date: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TenderLine {
+    pub id: String,
+    pub payment_id: String,
+    pub method: PaymentMethod,
+    pub amount: Rupiah,
+    pub reference_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PaymentMethodSummary {
+    pub method: PaymentMethod,
+    pub total: Rupiah,
+    pub tender_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TransaksiPaymentSummary {
+    pub transaksi_id: i32,
+    pub total_harga: Rupiah,
+    pub paid: Rupiah,
+    pub refunded: Rupiah,
+    pub late_fees: Rupiah,
+    pub outstanding: Rupiah,
+    pub payments: Vec<Payment>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,17 +202,19 @@ mod tests {
         let payment = Payment {
             id: payment_id.clone(),
             transaction_id: transaction_id.clone(),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         
         assert_eq!(payment.id, payment_id);
         assert_eq!(payment.transaction_id, transaction_id);
-        assert_eq!(payment.amount, 1000.0);
+        assert_eq!(payment.amount, Rupiah::from_rupiah(1000));
         assert_eq!(payment.method, PaymentMethod::Cash);
         assert_eq!(payment.status, PaymentStatus::Paid);
         assert!(payment.installments.is_empty());
@@ -125,17 +229,19 @@ mod tests {
         let payment = Payment {
             id: payment_id.clone(),
             transaction_id: transaction_id.clone(),
-            amount: 2000.0,
+            amount: Rupiah::from_rupiah(2000),
             method: PaymentMethod::CreditCard,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: Some(due_date),
+            late_fee_total: Rupiah::ZERO,
         };
         
         assert_eq!(payment.id, payment_id);
         assert_eq!(payment.transaction_id, transaction_id);
-        assert_eq!(payment.amount, 2000.0);
+        assert_eq!(payment.amount, Rupiah::from_rupiah(2000));
         assert_eq!(payment.method, PaymentMethod::CreditCard);
         assert_eq!(payment.status, PaymentStatus::Installment);
         assert!(payment.due_date.is_some());
@@ -148,25 +254,27 @@ mod tests {
         let mut payment = Payment {
             id: payment_id.clone(),
             transaction_id: format!("TRX-{}", Uuid::new_v4()),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         
         let installment = Installment {
             id: format!("INST-{}", Uuid::new_v4()),
             payment_id: payment_id.clone(),
-            amount: 500.0,
+            amount: Rupiah::from_rupiah(500),
             payment_date: Utc::now(),
         };
         
         payment.installments.push(installment);
         
         assert_eq!(payment.installments.len(), 1);
-        assert_eq!(payment.installments[0].amount, 500.0);
+        assert_eq!(payment.installments[0].amount, Rupiah::from_rupiah(500));
         assert_eq!(payment.installments[0].payment_id, payment_id);
     }
 
@@ -177,25 +285,27 @@ mod tests {
         let mut payment = Payment {
             id: payment_id.clone(),
             transaction_id: format!("TRX-{}", Uuid::new_v4()),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::BankTransfer,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         
         let installment1 = Installment {
             id: format!("INST-{}", Uuid::new_v4()),
             payment_id: payment_id.clone(),
-            amount: 300.0,
+            amount: Rupiah::from_rupiah(300),
             payment_date: Utc::now(),
         };
         
         let installment2 = Installment {
             id: format!("INST-{}", Uuid::new_v4()),
             payment_id: payment_id.clone(),
-            amount: 400.0,
+            amount: Rupiah::from_rupiah(400),
             payment_date: Utc::now() + chrono::Duration::days(1),
         };
         
@@ -204,8 +314,8 @@ mod tests {
         
         assert_eq!(payment.installments.len(), 2);
         
-        let total_installments: f64 = payment.installments.iter().map(|i| i.amount).sum();
-        assert_eq!(total_installments, 700.0);
+        let total_installments: Rupiah = payment.installments.iter().map(|i| i.amount).sum();
+        assert_eq!(total_installments, Rupiah::from_rupiah(700));
     }
 
     #[test]
@@ -217,13 +327,13 @@ mod tests {
         let installment = Installment {
             id: installment_id.clone(),
             payment_id: payment_id.clone(),
-            amount: 250.0,
+            amount: Rupiah::from_rupiah(250),
             payment_date,
         };
         
         assert_eq!(installment.id, installment_id);
         assert_eq!(installment.payment_id, payment_id);
-        assert_eq!(installment.amount, 250.0);
+        assert_eq!(installment.amount, Rupiah::from_rupiah(250));
         assert_eq!(installment.payment_date, payment_date);
     }
 
@@ -232,12 +342,14 @@ mod tests {
         let payment = Payment {
             id: "PMT-123".to_string(),
             transaction_id: "TRX-456".to_string(),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::EWallet,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         
         let serialized = serde_json::to_string(&payment);
@@ -254,7 +366,7 @@ mod tests {
         let installment = Installment {
             id: "INST-789".to_string(),
             payment_id: "PMT-123".to_string(),
-            amount: 500.0,
+            amount: Rupiah::from_rupiah(500),
             payment_date: Utc::now(),
         };
         
@@ -266,4 +378,92 @@ mod tests {
         assert!(json_str.contains("PMT-123"));
         assert!(json_str.contains("500"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_payment_method_from_string() {
+        assert_eq!(PaymentMethod::from_string("cash"), Some(PaymentMethod::Cash));
+        assert_eq!(PaymentMethod::from_string("BANK_TRANSFER"), Some(PaymentMethod::BankTransfer));
+        assert_eq!(PaymentMethod::from_string("CHEQUE"), None);
+    }
+
+    #[test]
+    fn test_amount_paid_by_status() {
+        let mut payment = Payment {
+            id: "PMT-PAID".to_string(),
+            transaction_id: "TRX-PAID".to_string(),
+            amount: Rupiah::from_rupiah(1000),
+            method: PaymentMethod::Cash,
+            status: PaymentStatus::Paid,
+            payment_date: Utc::now(),
+            installments: Vec::new(),
+            tender_lines: Vec::new(),
+            due_date: None,
+            late_fee_total: Rupiah::ZERO,
+        };
+        assert_eq!(payment.amount_paid(), Rupiah::from_rupiah(1000));
+
+        payment.status = PaymentStatus::Installment;
+        payment.installments.push(Installment {
+            id: "INST-1".to_string(),
+            payment_id: payment.id.clone(),
+            amount: Rupiah::from_rupiah(300),
+            payment_date: Utc::now(),
+        });
+        assert_eq!(payment.amount_paid(), Rupiah::from_rupiah(300));
+
+        payment.status = PaymentStatus::Refunded;
+        assert_eq!(payment.amount_paid(), Rupiah::from_rupiah(300));
+
+        payment.installments.clear();
+        assert_eq!(payment.amount_paid(), Rupiah::from_rupiah(1000));
+
+        payment.status = PaymentStatus::Pending;
+        assert_eq!(payment.amount_paid(), Rupiah::ZERO);
+    }
+
+    #[test]
+    fn test_validate_tender_lines() {
+        let mut payment = Payment {
+            id: "PMT-SPLIT".to_string(),
+            transaction_id: "TRX-SPLIT".to_string(),
+            amount: Rupiah::from_rupiah(1_000_000),
+            method: PaymentMethod::Cash,
+            status: PaymentStatus::Paid,
+            payment_date: Utc::now(),
+            installments: Vec::new(),
+            tender_lines: Vec::new(),
+            due_date: None,
+            late_fee_total: Rupiah::ZERO,
+        };
+        assert!(payment.validate_tender_lines().is_ok());
+
+        payment.tender_lines = vec![
+            TenderLine {
+                id: "TND-1".to_string(),
+                payment_id: payment.id.clone(),
+                method: PaymentMethod::Cash,
+                amount: Rupiah::from_rupiah(400_000),
+                reference_number: None,
+            },
+            TenderLine {
+                id: "TND-2".to_string(),
+                payment_id: payment.id.clone(),
+                method: PaymentMethod::BankTransfer,
+                amount: Rupiah::from_sen(59_999_999),
+                reference_number: Some("BCA-889123".to_string()),
+            },
+        ];
+        assert!(payment.validate_tender_lines().unwrap_err().contains("does not match"));
+
+        payment.tender_lines[1].amount = Rupiah::from_rupiah(600_000);
+        assert!(payment.validate_tender_lines().is_ok());
+        assert_eq!(payment.tender_total(), payment.amount);
+
+        payment.method = PaymentMethod::EWallet;
+        assert!(payment.validate_tender_lines().is_err());
+
+        payment.method = PaymentMethod::Cash;
+        payment.tender_lines[0].amount = Rupiah::ZERO;
+        assert!(payment.validate_tender_lines().is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use rocket::FromForm;
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::model::payment::{Payment, PaymentMethod};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaymentSortField {
+    PaymentDate,
+    Amount,
+    DueDate,
+    Status,
+}
+
+impl PaymentSortField {
+    pub fn from_string(field: &str) -> Option<Self> {
+        match field.to_lowercase().as_str() {
+            "payment_date" => Some(PaymentSortField::PaymentDate),
+            "amount" => Some(PaymentSortField::Amount),
+            "due_date" => Some(PaymentSortField::DueDate),
+            "status" => Some(PaymentSortField::Status),
+            _ => None,
+        }
+    }
+
+    pub fn column(&self) -> &'static str {
+        match self {
+            PaymentSortField::PaymentDate => "payment_date",
+            PaymentSortField::Amount => "amount",
+            PaymentSortField::DueDate => "due_date",
+            PaymentSortField::Status => "status",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    pub fn from_string(direction: &str) -> Option<Self> {
+        match direction.to_lowercase().as_str() {
+            "asc" => Some(SortDirection::Asc),
+            "desc" => Some(SortDirection::Desc),
+            _ => None,
+        }
+    }
+
+    pub fn sql(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+}
+
+// Parameter query mentah dari `GET /payments`, divalidasi oleh `PaymentService::parse_payment_query`
+#[derive(Debug, Clone, Default, FromForm)]
+pub struct PaymentQueryParams {
+    pub status: Option<String>,
+    pub method: Option<String>,
+    pub transaction_id: Option<String>,
+    pub paid_from: Option<String>,
+    pub paid_to: Option<String>,
+    pub min_amount: Option<String>,
+    pub max_amount: Option<String>,
+    pub due_from: Option<String>,
+    pub due_to: Option<String>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// Filter daftar pembayaran. Semua rentang inklusif; `limit` kosong berarti tanpa batas.
+// Endpoint publik hanya mengisinya bila klien mengirim limit atau offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentQuery {
+    pub status: Option<PaymentStatus>,
+    pub method: Option<PaymentMethod>,
+    pub transaction_id: Option<String>,
+    pub paid_from: Option<DateTime<Utc>>,
+    pub paid_to: Option<DateTime<Utc>>,
+    pub min_amount: Option<Rupiah>,
+    pub max_amount: Option<Rupiah>,
+    pub due_from: Option<DateTime<Utc>>,
+    pub due_to: Option<DateTime<Utc>>,
+    pub sort: PaymentSortField,
+    pub direction: SortDirection,
+    pub limit: Option<i64>,
+    pub offset: i64,
+}
+
+impl Default for PaymentQuery {
+    fn default() -> Self {
+        Self {
+            status: None,
+            method: None,
+            transaction_id: None,
+            paid_from: None,
+            paid_to: None,
+            min_amount: None,
+            max_amount: None,
+            due_from: None,
+            due_to: None,
+            sort: PaymentSortField::PaymentDate,
+            direction: SortDirection::Desc,
+            limit: None,
+            offset: 0,
+        }
+    }
+}
+
+impl PaymentQuery {
+    pub fn by_status(status: PaymentStatus) -> Self {
+        Self {
+            status: Some(status),
+            ..Self::default()
+        }
+    }
+
+    pub fn by_transaction_id(transaction_id: &str) -> Self {
+        Self {
+            transaction_id: Some(transaction_id.to_string()),
+            sort: PaymentSortField::PaymentDate,
+            direction: SortDirection::Asc,
+            ..Self::default()
+        }
+    }
+}
+
+// Satu halaman hasil pencarian beserta jumlah seluruh pembayaran yang cocok dengan filter
+#[derive(Debug, Clone)]
+pub struct PaymentPage {
+    pub data: Vec<Payment>,
+    pub total_count: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_field_and_direction_parsing() {
+        assert_eq!(PaymentSortField::from_string("AMOUNT"), Some(PaymentSortField::Amount));
+        assert_eq!(PaymentSortField::from_string("due_date").unwrap().column(), "due_date");
+        assert_eq!(PaymentSortField::from_string("amount; DROP TABLE payments"), None);
+
+        assert_eq!(SortDirection::from_string("Asc").unwrap().sql(), "ASC");
+        assert_eq!(SortDirection::from_string("sideways"), None);
+    }
+
+    #[test]
+    fn test_default_query_is_newest_first_without_limit() {
+        let query = PaymentQuery::by_status(PaymentStatus::Overdue);
+
+        assert_eq!(query.status, Some(PaymentStatus::Overdue));
+        assert_eq!(query.sort, PaymentSortField::PaymentDate);
+        assert_eq!(query.direction, SortDirection::Desc);
+        assert_eq!(query.limit, None);
+        assert_eq!(query.offset, 0);
+    }
+}
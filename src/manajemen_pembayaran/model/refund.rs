@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::enums::refund_reason::RefundReason;
+use crate::manajemen_pembayaran::model::payment::PaymentMethod;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Refund {
+    pub id: String,
+    pub payment_id: String,
+    pub amount: Rupiah,
+    pub reason: RefundReason,
+    pub method: PaymentMethod,
+    pub notes: Option<String>,
+    pub previous_status: PaymentStatus,
+    pub resulting_status: PaymentStatus,
+    pub refund_date: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refund_serialization() {
+        let refund = Refund {
+            id: "RFD-1".to_string(),
+            payment_id: "PMT-1".to_string(),
+            amount: Rupiah::from_rupiah(65_000),
+            reason: RefundReason::Defective,
+            method: PaymentMethod::Cash,
+            notes: Some("Semen 50kg sobek".to_string()),
+            previous_status: PaymentStatus::Paid,
+            resulting_status: PaymentStatus::Paid,
+            refund_date: Utc::now(),
+        };
+
+        let json = serde_json::to_value(&refund).unwrap();
+        assert_eq!(json["amount"], "65000.00");
+        assert_eq!(json["reason"], "Defective");
+        assert_eq!(json["method"], "Cash");
+    }
+}
@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::manajemen_pembayaran::enums::webhook_result::WebhookResult;
+
+// Event mentah dari payment gateway, disimpan apa adanya agar bisa diproses ulang
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct WebhookEvent {
+    pub id: String,
+    pub provider: String,
+    pub event_id: String,
+    pub payment_id: String,
+    pub provider_status: String,
+    pub payload: String,
+    pub signature: String,
+    pub received_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+    pub result: Option<WebhookResult>,
+    pub result_message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct WebhookReceipt {
+    pub duplicate: bool,
+    pub event: WebhookEvent,
+}
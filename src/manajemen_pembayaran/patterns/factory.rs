@@ -1,6 +1,8 @@
-use crate::manajemen_pembayaran::model::payment::PaymentMethod;
 use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
-use crate::manajemen_pembayaran::patterns::state::{PaymentState, PaidState, InstallmentState};
+use crate::manajemen_pembayaran::patterns::state::{
+    PaymentState, PaidState, InstallmentState, PendingState, FailedState, VoidState, RefundedState,
+    OverdueState,
+};
 
 pub struct PaymentStateFactory;
 impl PaymentStateFactory {
@@ -8,6 +10,33 @@ impl PaymentStateFactory {
         match status {
             PaymentStatus::Paid => Box::new(PaidState),
             PaymentStatus::Installment => Box::new(InstallmentState),
+            PaymentStatus::Pending => Box::new(PendingState),
+            PaymentStatus::Failed => Box::new(FailedState),
+            PaymentStatus::Void => Box::new(VoidState),
+            PaymentStatus::Refunded => Box::new(RefundedState),
+            PaymentStatus::Overdue => Box::new(OverdueState),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factory_state_names_match_status_strings() {
+        let statuses = vec![
+            PaymentStatus::Paid,
+            PaymentStatus::Installment,
+            PaymentStatus::Pending,
+            PaymentStatus::Failed,
+            PaymentStatus::Void,
+            PaymentStatus::Refunded,
+            PaymentStatus::Overdue,
+        ];
+
+        for status in statuses {
+            assert_eq!(PaymentStateFactory::create(&status).get_name(), status.to_string());
         }
     }
 }
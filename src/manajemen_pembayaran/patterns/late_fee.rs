@@ -0,0 +1,67 @@
+use crate::common::money::Rupiah;
+
+// Besaran denda keterlambatan untuk satu periode, dihitung dari tunggakan saat denda dikenakan
+pub trait LateFeeStrategy: Send + Sync {
+    fn fee_for_period(&self, overdue_amount: Rupiah) -> Rupiah;
+    fn get_name(&self) -> &'static str;
+}
+
+pub struct FlatLateFee {
+    pub amount: Rupiah,
+}
+
+impl LateFeeStrategy for FlatLateFee {
+    fn fee_for_period(&self, _overdue_amount: Rupiah) -> Rupiah {
+        self.amount
+    }
+
+    fn get_name(&self) -> &'static str { "flat" }
+}
+
+pub struct PercentageLateFee {
+    pub basis_points: i64,
+}
+
+impl LateFeeStrategy for PercentageLateFee {
+    fn fee_for_period(&self, overdue_amount: Rupiah) -> Rupiah {
+        overdue_amount.percent_bp(self.basis_points)
+    }
+
+    fn get_name(&self) -> &'static str { "percentage" }
+}
+
+pub struct NoLateFee;
+
+impl LateFeeStrategy for NoLateFee {
+    fn fee_for_period(&self, _overdue_amount: Rupiah) -> Rupiah {
+        Rupiah::ZERO
+    }
+
+    fn get_name(&self) -> &'static str { "none" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_late_fee_ignores_overdue_amount() {
+        let strategy = FlatLateFee { amount: Rupiah::from_rupiah(25_000) };
+
+        assert_eq!(strategy.fee_for_period(Rupiah::from_rupiah(100)), Rupiah::from_rupiah(25_000));
+        assert_eq!(strategy.fee_for_period(Rupiah::from_rupiah(10_000_000)), Rupiah::from_rupiah(25_000));
+    }
+
+    #[test]
+    fn test_percentage_late_fee() {
+        let strategy = PercentageLateFee { basis_points: 200 };
+
+        assert_eq!(strategy.fee_for_period(Rupiah::from_rupiah(300_000)), Rupiah::from_rupiah(6_000));
+        assert_eq!(strategy.fee_for_period(Rupiah::from_sen(33_333)), Rupiah::from_sen(667));
+    }
+
+    #[test]
+    fn test_no_late_fee() {
+        assert_eq!(NoLateFee.fee_for_period(Rupiah::from_rupiah(300_000)), Rupiah::ZERO);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod state;
-pub mod factory;
\ No newline at end of file
+pub mod factory;
+pub mod late_fee;
+pub mod payment_gateway;
\ No newline at end of file
@@ -0,0 +1,158 @@
+use hmac::{Hmac, Mac};
+use serde::{Serialize, Deserialize};
+use sha2::Sha256;
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Event webhook yang sudah dinormalisasi dari format masing-masing provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct GatewayEvent {
+    pub event_id: String,
+    pub payment_id: String,
+    pub provider_status: String,
+    pub amount: Option<Rupiah>,
+}
+
+pub trait PaymentGatewayProvider: Send + Sync {
+    fn get_name(&self) -> &'static str;
+    fn signature_header(&self) -> &'static str;
+    fn verify_signature(&self, payload: &[u8], signature: &str) -> bool;
+    fn parse_event(&self, payload: &[u8]) -> Result<GatewayEvent, String>;
+    fn map_status(&self, provider_status: &str) -> Option<PaymentStatus>;
+}
+
+pub fn hmac_sha256_hex(secret: &[u8], payload: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// Perbandingan dilakukan oleh `verify_slice` dalam waktu konstan
+pub fn verify_hmac_sha256_hex(secret: &[u8], payload: &[u8], signature: &str) -> bool {
+    let signature = signature.trim();
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+    let Ok(expected) = hex::decode(signature) else {
+        return false;
+    };
+
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    mac.verify_slice(&expected).is_ok()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatorPayload {
+    pub event_id: String,
+    pub payment_id: String,
+    pub status: String,
+    pub amount: Option<Rupiah>,
+}
+
+// Provider lokal untuk pengujian offline; status mengikuti istilah yang umum dipakai gateway
+pub struct SimulatorProvider {
+    secret: String,
+}
+
+impl SimulatorProvider {
+    pub const NAME: &'static str = "simulator";
+
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self { secret: secret.into() }
+    }
+
+    pub fn sign(&self, payload: &[u8]) -> String {
+        hmac_sha256_hex(self.secret.as_bytes(), payload)
+    }
+}
+
+impl PaymentGatewayProvider for SimulatorProvider {
+    fn get_name(&self) -> &'static str { Self::NAME }
+
+    fn signature_header(&self) -> &'static str { "X-Simulator-Signature" }
+
+    fn verify_signature(&self, payload: &[u8], signature: &str) -> bool {
+        verify_hmac_sha256_hex(self.secret.as_bytes(), payload, signature)
+    }
+
+    fn parse_event(&self, payload: &[u8]) -> Result<GatewayEvent, String> {
+        let payload: SimulatorPayload = serde_json::from_slice(payload)
+            .map_err(|e| format!("Invalid simulator payload: {}", e))?;
+
+        if payload.event_id.trim().is_empty() || payload.payment_id.trim().is_empty() {
+            return Err("event_id and payment_id are required".to_string());
+        }
+
+        Ok(GatewayEvent {
+            event_id: payload.event_id,
+            payment_id: payload.payment_id,
+            provider_status: payload.status.to_lowercase(),
+            amount: payload.amount,
+        })
+    }
+
+    fn map_status(&self, provider_status: &str) -> Option<PaymentStatus> {
+        match provider_status.to_lowercase().as_str() {
+            "settlement" | "capture" => Some(PaymentStatus::Paid),
+            "pending" => Some(PaymentStatus::Pending),
+            "deny" | "failure" => Some(PaymentStatus::Failed),
+            "expire" | "cancel" => Some(PaymentStatus::Void),
+            "refund" => Some(PaymentStatus::Refunded),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_signature_roundtrip() {
+        let signature = hmac_sha256_hex(b"rahasia", b"{\"a\":1}");
+
+        assert_eq!(signature.len(), 64);
+        assert!(verify_hmac_sha256_hex(b"rahasia", b"{\"a\":1}", &signature));
+        assert!(verify_hmac_sha256_hex(b"rahasia", b"{\"a\":1}", &format!("sha256={}", signature)));
+        assert!(!verify_hmac_sha256_hex(b"rahasia", b"{\"a\":2}", &signature));
+        assert!(!verify_hmac_sha256_hex(b"lain", b"{\"a\":1}", &signature));
+        assert!(!verify_hmac_sha256_hex(b"rahasia", b"{\"a\":1}", "bukan-hex"));
+    }
+
+    #[test]
+    fn test_hmac_known_vector() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hmac_sha256_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_simulator_parse_event() {
+        let provider = SimulatorProvider::new("rahasia");
+        let event = provider.parse_event(br#"{"event_id":"EVT-1","payment_id":"PMT-1","status":"SETTLEMENT","amount":"150000.00"}"#).unwrap();
+
+        assert_eq!(event.event_id, "EVT-1");
+        assert_eq!(event.payment_id, "PMT-1");
+        assert_eq!(event.provider_status, "settlement");
+        assert_eq!(event.amount, Some(Rupiah::from_rupiah(150_000)));
+
+        assert!(provider.parse_event(b"not json").is_err());
+        assert!(provider.parse_event(br#"{"event_id":"","payment_id":"PMT-1","status":"pending"}"#).is_err());
+    }
+
+    #[test]
+    fn test_simulator_status_mapping() {
+        let provider = SimulatorProvider::new("rahasia");
+
+        assert_eq!(provider.map_status("settlement"), Some(PaymentStatus::Paid));
+        assert_eq!(provider.map_status("pending"), Some(PaymentStatus::Pending));
+        assert_eq!(provider.map_status("deny"), Some(PaymentStatus::Failed));
+        assert_eq!(provider.map_status("expire"), Some(PaymentStatus::Void));
+        assert_eq!(provider.map_status("refund"), Some(PaymentStatus::Refunded));
+        assert_eq!(provider.map_status("authorize"), None);
+    }
+}
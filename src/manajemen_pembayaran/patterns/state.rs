@@ -1,18 +1,24 @@
 use uuid::Uuid;
 use chrono::Utc;
 
+use crate::common::money::Rupiah;
 use crate::manajemen_pembayaran::model::payment::{Payment, Installment};
 use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
 
 pub trait PaymentState: Send + Sync {
-    fn process_payment(&self, payment: &mut Payment, amount: f64) -> Result<(), String>;
+    fn process_payment(&self, payment: &mut Payment, amount: Rupiah) -> Result<(), String>;
     fn can_delete(&self) -> bool;
     fn get_name(&self) -> String;
+    fn allowed_transitions(&self) -> Vec<PaymentStatus>;
+
+    fn can_transition_to(&self, next: &PaymentStatus) -> bool {
+        self.allowed_transitions().contains(next)
+    }
 }
 
 pub struct PaidState;
 impl PaymentState for PaidState {
-    fn process_payment(&self, _payment: &mut Payment, _amount: f64) -> Result<(), String> {
+    fn process_payment(&self, _payment: &mut Payment, _amount: Rupiah) -> Result<(), String> {
         Err("Pembayaran sudah lunas, tidak dapat menambahkan pembayaran lagi".to_string())
     }
     
@@ -23,12 +29,16 @@ impl PaymentState for PaidState {
     fn get_name(&self) -> String {
         "LUNAS".to_string()
     }
+
+    fn allowed_transitions(&self) -> Vec<PaymentStatus> {
+        vec![PaymentStatus::Refunded]
+    }
 }
 
 pub struct InstallmentState;
 impl PaymentState for InstallmentState {
-    fn process_payment(&self, payment: &mut Payment, amount: f64) -> Result<(), String> {
-        if amount <= 0.0 {
+    fn process_payment(&self, payment: &mut Payment, amount: Rupiah) -> Result<(), String> {
+        if !amount.is_positive() {
             return Err("Jumlah cicilan harus lebih dari 0".to_string());
         }
         
@@ -40,8 +50,8 @@ impl PaymentState for InstallmentState {
         };
         payment.installments.push(installment);
 
-        let total_paid: f64 = payment.installments.iter().map(|i| i.amount).sum();
-        if total_paid >= payment.amount {
+        let total_paid: Rupiah = payment.installments.iter().map(|i| i.amount).sum();
+        if total_paid >= payment.total_due() {
             payment.status = PaymentStatus::Paid;
         }
 
@@ -55,6 +65,124 @@ impl PaymentState for InstallmentState {
     fn get_name(&self) -> String {
         "CICILAN".to_string()
     }
+
+    fn allowed_transitions(&self) -> Vec<PaymentStatus> {
+        vec![
+            PaymentStatus::Installment,
+            PaymentStatus::Paid,
+            PaymentStatus::Void,
+            PaymentStatus::Refunded,
+            PaymentStatus::Overdue,
+        ]
+    }
+}
+
+// Cicilan yang melewati jatuh tempo; tetap menerima cicilan dan kembali ke CICILAN
+// setelah tunggakan dilunasi.
+pub struct OverdueState;
+impl PaymentState for OverdueState {
+    fn process_payment(&self, payment: &mut Payment, amount: Rupiah) -> Result<(), String> {
+        InstallmentState.process_payment(payment, amount)
+    }
+
+    fn can_delete(&self) -> bool {
+        false
+    }
+
+    fn get_name(&self) -> String {
+        "TERLAMBAT".to_string()
+    }
+
+    fn allowed_transitions(&self) -> Vec<PaymentStatus> {
+        vec![
+            PaymentStatus::Overdue,
+            PaymentStatus::Installment,
+            PaymentStatus::Paid,
+            PaymentStatus::Void,
+            PaymentStatus::Refunded,
+        ]
+    }
+}
+
+pub struct PendingState;
+impl PaymentState for PendingState {
+    fn process_payment(&self, _payment: &mut Payment, _amount: Rupiah) -> Result<(), String> {
+        Err("Pembayaran masih menunggu konfirmasi, belum dapat menerima cicilan".to_string())
+    }
+
+    fn can_delete(&self) -> bool {
+        true
+    }
+
+    fn get_name(&self) -> String {
+        "MENUNGGU".to_string()
+    }
+
+    fn allowed_transitions(&self) -> Vec<PaymentStatus> {
+        vec![
+            PaymentStatus::Paid,
+            PaymentStatus::Installment,
+            PaymentStatus::Failed,
+            PaymentStatus::Void,
+        ]
+    }
+}
+
+pub struct FailedState;
+impl PaymentState for FailedState {
+    fn process_payment(&self, _payment: &mut Payment, _amount: Rupiah) -> Result<(), String> {
+        Err("Pembayaran gagal, tidak dapat menambahkan pembayaran".to_string())
+    }
+
+    fn can_delete(&self) -> bool {
+        true
+    }
+
+    fn get_name(&self) -> String {
+        "GAGAL".to_string()
+    }
+
+    fn allowed_transitions(&self) -> Vec<PaymentStatus> {
+        vec![PaymentStatus::Pending]
+    }
+}
+
+pub struct VoidState;
+impl PaymentState for VoidState {
+    fn process_payment(&self, _payment: &mut Payment, _amount: Rupiah) -> Result<(), String> {
+        Err("Pembayaran sudah dibatalkan, tidak dapat menambahkan pembayaran".to_string())
+    }
+
+    fn can_delete(&self) -> bool {
+        false
+    }
+
+    fn get_name(&self) -> String {
+        "DIBATALKAN".to_string()
+    }
+
+    fn allowed_transitions(&self) -> Vec<PaymentStatus> {
+        Vec::new()
+    }
+}
+
+pub struct RefundedState;
+impl PaymentState for RefundedState {
+    fn process_payment(&self, _payment: &mut Payment, _amount: Rupiah) -> Result<(), String> {
+        Err("Pembayaran sudah dikembalikan, tidak dapat menambahkan pembayaran".to_string())
+    }
+
+    fn can_delete(&self) -> bool {
+        false
+    }
+
+    fn get_name(&self) -> String {
+        "DIKEMBALIKAN".to_string()
+    }
+
+    fn allowed_transitions(&self) -> Vec<PaymentStatus> {
+        Vec::new()
+    }
 }
 
 #[cfg(test)]
@@ -70,15 +198,17 @@ mod tests {
         let mut payment = Payment {
             id: format!("PMT-{}", Uuid::new_v4()),
             transaction_id: format!("TRX-{}", Uuid::new_v4()),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         
-        let result = state.process_payment(&mut payment, 500.0);
+        let result = state.process_payment(&mut payment, Rupiah::from_rupiah(500));
         
         assert!(result.is_err());
         assert_eq!(payment.installments.len(), 0);
@@ -90,22 +220,24 @@ mod tests {
         let mut payment = Payment {
             id: format!("PMT-{}", Uuid::new_v4()),
             transaction_id: format!("TRX-{}", Uuid::new_v4()),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         
-        let result = state.process_payment(&mut payment, 400.0);
+        let result = state.process_payment(&mut payment, Rupiah::from_rupiah(400));
         
         assert!(result.is_ok()); 
         assert_eq!(payment.installments.len(), 1);
-        assert_eq!(payment.installments[0].amount, 400.0);
+        assert_eq!(payment.installments[0].amount, Rupiah::from_rupiah(400));
         assert_eq!(payment.status, PaymentStatus::Installment);
         
-        let result = state.process_payment(&mut payment, 600.0);
+        let result = state.process_payment(&mut payment, Rupiah::from_rupiah(600));
         
         assert!(result.is_ok());
         assert_eq!(payment.installments.len(), 2);
@@ -118,20 +250,46 @@ mod tests {
         let mut payment = Payment {
             id: format!("PMT-{}", Uuid::new_v4()),
             transaction_id: format!("TRX-{}", Uuid::new_v4()),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         
-        let result = state.process_payment(&mut payment, -100.0);
+        let result = state.process_payment(&mut payment, Rupiah::from_rupiah(-100));
         
         assert!(result.is_err());
         assert_eq!(payment.installments.len(), 0);
     }
 
+    #[test]
+    fn test_installment_state_sums_exactly() {
+        let state = InstallmentState;
+        let mut payment = Payment {
+            id: format!("PMT-{}", Uuid::new_v4()),
+            transaction_id: format!("TRX-{}", Uuid::new_v4()),
+            amount: Rupiah::from_rupiah(1),
+            method: PaymentMethod::Cash,
+            status: PaymentStatus::Installment,
+            payment_date: Utc::now(),
+            installments: Vec::new(),
+            tender_lines: Vec::new(),
+            due_date: None,
+            late_fee_total: Rupiah::ZERO,
+        };
+
+        for _ in 0..10 {
+            state.process_payment(&mut payment, Rupiah::from_sen(10)).unwrap();
+        }
+
+        assert_eq!(payment.installments.len(), 10);
+        assert_eq!(payment.status, PaymentStatus::Paid);
+    }
+
     #[test]
     fn test_state_can_delete() {
         let paid_state = PaidState;
@@ -140,4 +298,58 @@ mod tests {
         let installment_state = InstallmentState;
         assert!(installment_state.can_delete());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_allowed_transitions() {
+        assert!(PendingState.can_transition_to(&PaymentStatus::Paid));
+        assert!(PendingState.can_transition_to(&PaymentStatus::Failed));
+        assert!(!PendingState.can_transition_to(&PaymentStatus::Refunded));
+
+        assert!(InstallmentState.can_transition_to(&PaymentStatus::Installment));
+        assert!(InstallmentState.can_transition_to(&PaymentStatus::Paid));
+        assert!(!InstallmentState.can_transition_to(&PaymentStatus::Pending));
+
+        assert!(PaidState.can_transition_to(&PaymentStatus::Refunded));
+        assert!(!PaidState.can_transition_to(&PaymentStatus::Installment));
+        assert!(!PaidState.can_transition_to(&PaymentStatus::Paid));
+
+        assert!(FailedState.can_transition_to(&PaymentStatus::Pending));
+        assert!(!FailedState.can_transition_to(&PaymentStatus::Paid));
+
+        assert!(InstallmentState.can_transition_to(&PaymentStatus::Overdue));
+        assert!(OverdueState.can_transition_to(&PaymentStatus::Installment));
+        assert!(OverdueState.can_transition_to(&PaymentStatus::Paid));
+        assert!(!OverdueState.can_transition_to(&PaymentStatus::Pending));
+        assert!(!PendingState.can_transition_to(&PaymentStatus::Overdue));
+
+        assert!(VoidState.allowed_transitions().is_empty());
+        assert!(RefundedState.allowed_transitions().is_empty());
+    }
+
+    #[test]
+    fn test_terminal_states_reject_payment() {
+        let mut payment = Payment {
+            id: format!("PMT-{}", Uuid::new_v4()),
+            transaction_id: format!("TRX-{}", Uuid::new_v4()),
+            amount: Rupiah::from_rupiah(1000),
+            method: PaymentMethod::BankTransfer,
+            status: PaymentStatus::Pending,
+            payment_date: Utc::now(),
+            installments: Vec::new(),
+            tender_lines: Vec::new(),
+            due_date: None,
+            late_fee_total: Rupiah::ZERO,
+        };
+
+        let states: Vec<Box<dyn PaymentState>> = vec![
+            Box::new(PendingState),
+            Box::new(FailedState),
+            Box::new(VoidState),
+            Box::new(RefundedState),
+        ];
+        for state in states {
+            assert!(state.process_payment(&mut payment, Rupiah::from_rupiah(100)).is_err());
+        }
+        assert!(payment.installments.is_empty());
+    }
+}
@@ -0,0 +1,406 @@
+use sqlx::any::AnyRow;
+use sqlx::{Any, AnyConnection, pool::PoolConnection};
+use sqlx::Row;
+use chrono::{DateTime, Utc};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::cash_movement_type::CashMovementType;
+use crate::manajemen_pembayaran::enums::cash_session_status::CashSessionStatus;
+use crate::manajemen_pembayaran::model::cash_drawer::{CashDrawerSession, CashMovement, SessionPayment};
+use crate::manajemen_pembayaran::model::payment::PaymentMethod;
+
+pub struct CashDrawerSessionClosing<'a> {
+    pub closed_by: &'a str,
+    pub closed_at: DateTime<Utc>,
+    pub expected_amount: Rupiah,
+    pub counted_amount: Rupiah,
+    pub variance: Rupiah,
+    pub notes: Option<&'a str>,
+}
+
+pub struct CashDrawerRepository;
+
+impl CashDrawerRepository {
+    // Mengembalikan false bila kasir yang sama masih punya sesi lain yang terbuka
+    pub async fn open(mut db: PoolConnection<Any>, session: &CashDrawerSession) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("
+            INSERT INTO cash_drawer_sessions (id, status, opened_by_user_id, opened_by, opening_float, opened_at, notes)
+            VALUES ($1, $2, $3, $4, CAST($5 AS DECIMAL(15,2)), $6, $7)
+            ON CONFLICT DO NOTHING
+        ")
+            .bind(&session.id)
+            .bind(session.status.to_string())
+            .bind(session.opened_by_user_id)
+            .bind(&session.opened_by)
+            .bind(session.opening_float.to_string())
+            .bind(session.opened_at.to_rfc3339())
+            .bind(&session.notes)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn find_by_id(mut db: PoolConnection<Any>, id: &str) -> Result<CashDrawerSession, sqlx::Error> {
+        Self::load_session(&mut db, id).await
+    }
+
+    pub async fn find_open(mut db: PoolConnection<Any>, user_id: i64) -> Result<Option<CashDrawerSession>, sqlx::Error> {
+        let row = sqlx::query("SELECT id FROM cash_drawer_sessions WHERE opened_by_user_id = $1 AND status = $2")
+            .bind(user_id)
+            .bind(CashSessionStatus::Open.to_string())
+            .fetch_optional(&mut *db)
+            .await?;
+
+        match row {
+            Some(row) => {
+                let id: String = row.get("id");
+                Self::load_session(&mut db, &id).await.map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Mengunci baris sesi untuk mutasi kas dan penutupan, lalu memuat ulang isinya
+    pub async fn lock_session(db: &mut AnyConnection, id: &str) -> Result<CashDrawerSession, sqlx::Error> {
+        if db.backend_name().eq_ignore_ascii_case("postgresql") {
+            sqlx::query("SELECT id FROM cash_drawer_sessions WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_one(&mut *db)
+                .await?;
+        } else {
+            sqlx::query("UPDATE cash_drawer_sessions SET status = status WHERE id = $1")
+                .bind(id)
+                .execute(&mut *db)
+                .await?;
+        }
+
+        Self::load_session(db, id).await
+    }
+
+    // Sesi terbuka milik kasir, dikunci bersama agar penutupan laci menunggu pembayaran yang sedang dicatat
+    pub async fn lock_open_session_id(db: &mut AnyConnection, user_id: i64) -> Result<Option<String>, sqlx::Error> {
+        let sql = if db.backend_name().eq_ignore_ascii_case("postgresql") {
+            "SELECT id FROM cash_drawer_sessions WHERE opened_by_user_id = $1 AND status = $2 FOR SHARE"
+        } else {
+            sqlx::query("UPDATE cash_drawer_sessions SET status = status WHERE opened_by_user_id = $1 AND status = $2")
+                .bind(user_id)
+                .bind(CashSessionStatus::Open.to_string())
+                .execute(&mut *db)
+                .await?;
+            "SELECT id FROM cash_drawer_sessions WHERE opened_by_user_id = $1 AND status = $2"
+        };
+
+        let row = sqlx::query(sql)
+            .bind(user_id)
+            .bind(CashSessionStatus::Open.to_string())
+            .fetch_optional(&mut *db)
+            .await?;
+
+        Ok(row.map(|row| row.get("id")))
+    }
+
+    // Mengembalikan false bila sesi sudah ditutup
+    pub async fn add_movement(db: &mut AnyConnection, movement: &CashMovement) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("
+            INSERT INTO cash_movements (id, session_id, movement_type, amount, reason, created_by, created_at)
+            SELECT $1, $2, $3, CAST($4 AS DECIMAL(15,2)), $5, $6, $7
+            WHERE EXISTS (SELECT 1 FROM cash_drawer_sessions WHERE id = $2 AND status = $8)
+        ")
+            .bind(&movement.id)
+            .bind(&movement.session_id)
+            .bind(movement.movement_type.to_string())
+            .bind(movement.amount.to_string())
+            .bind(&movement.reason)
+            .bind(&movement.created_by)
+            .bind(movement.created_at.to_rfc3339())
+            .bind(CashSessionStatus::Open.to_string())
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    // Baris untuk sesi yang sudah ditutup tidak disimpan; mengembalikan jumlah baris yang tersimpan
+    pub async fn add_session_payments(db: &mut AnyConnection, entries: &[SessionPayment]) -> Result<u64, sqlx::Error> {
+        let mut saved = 0;
+
+        for entry in entries {
+            let result = sqlx::query("
+                INSERT INTO cash_session_payments (id, session_id, payment_id, reference_id, method, amount, recorded_at)
+                SELECT $1, $2, $3, $4, $5, CAST($6 AS DECIMAL(15,2)), $7
+                WHERE EXISTS (SELECT 1 FROM cash_drawer_sessions WHERE id = $2 AND status = $8)
+            ")
+                .bind(&entry.id)
+                .bind(&entry.session_id)
+                .bind(&entry.payment_id)
+                .bind(&entry.reference_id)
+                .bind(entry.method.to_string())
+                .bind(entry.amount.to_string())
+                .bind(entry.recorded_at.to_rfc3339())
+                .bind(CashSessionStatus::Open.to_string())
+                .execute(&mut *db)
+                .await?;
+            saved += result.rows_affected();
+        }
+
+        Ok(saved)
+    }
+
+    // Mengembalikan false bila sesi tidak ada atau sudah ditutup
+    pub async fn close(db: &mut AnyConnection, id: &str, closing: &CashDrawerSessionClosing<'_>) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("
+            UPDATE cash_drawer_sessions
+            SET status = $1, closed_by = $2, closed_at = $3,
+                expected_amount = CAST($4 AS DECIMAL(15,2)),
+                counted_amount = CAST($5 AS DECIMAL(15,2)),
+                variance = CAST($6 AS DECIMAL(15,2)),
+                notes = COALESCE($7, notes)
+            WHERE id = $8 AND status = $9
+        ")
+            .bind(CashSessionStatus::Closed.to_string())
+            .bind(closing.closed_by)
+            .bind(closing.closed_at.to_rfc3339())
+            .bind(closing.expected_amount.to_string())
+            .bind(closing.counted_amount.to_string())
+            .bind(closing.variance.to_string())
+            .bind(closing.notes)
+            .bind(id)
+            .bind(CashSessionStatus::Open.to_string())
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn load_session(db: &mut AnyConnection, id: &str) -> Result<CashDrawerSession, sqlx::Error> {
+        let row = sqlx::query("
+            SELECT id, status, opened_by_user_id, opened_by, CAST(opening_float AS TEXT) AS opening_float, opened_at,
+                   closed_by, closed_at, CAST(expected_amount AS TEXT) AS expected_amount,
+                   CAST(counted_amount AS TEXT) AS counted_amount, CAST(variance AS TEXT) AS variance, notes
+            FROM cash_drawer_sessions
+            WHERE id = $1
+        ")
+            .bind(id)
+            .fetch_one(&mut *db)
+            .await?;
+        let mut session = Self::parse_row_to_session(row)?;
+
+        let movement_rows = sqlx::query("
+            SELECT id, session_id, movement_type, CAST(amount AS TEXT) AS amount, reason, created_by, created_at
+            FROM cash_movements
+            WHERE session_id = $1
+            ORDER BY created_at ASC
+        ")
+            .bind(id)
+            .fetch_all(&mut *db)
+            .await?;
+        for row in movement_rows {
+            session.movements.push(Self::parse_row_to_movement(row)?);
+        }
+
+        let payment_rows = sqlx::query("
+            SELECT id, session_id, payment_id, reference_id, method, CAST(amount AS TEXT) AS amount, recorded_at
+            FROM cash_session_payments
+            WHERE session_id = $1
+            ORDER BY recorded_at ASC
+        ")
+            .bind(id)
+            .fetch_all(&mut *db)
+            .await?;
+        for row in payment_rows {
+            session.payments.push(Self::parse_row_to_session_payment(row)?);
+        }
+
+        Ok(session)
+    }
+
+    fn parse_datetime(row: &AnyRow, column: &str) -> Result<DateTime<Utc>, sqlx::Error> {
+        let value: String = row.get(column);
+        DateTime::parse_from_rfc3339(&value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| {
+                eprintln!("Failed to parse {} '{}': {}", column, value, e);
+                sqlx::Error::RowNotFound
+            })
+    }
+
+    fn parse_row_to_session(row: AnyRow) -> Result<CashDrawerSession, sqlx::Error> {
+        let status: String = row.get("status");
+        let closed_at = match row.try_get::<String, _>("closed_at") {
+            Ok(_) => Some(Self::parse_datetime(&row, "closed_at")?),
+            Err(_) => None,
+        };
+
+        Ok(CashDrawerSession {
+            id: row.get("id"),
+            status: CashSessionStatus::from_string(&status)
+                .ok_or_else(|| sqlx::Error::Decode(format!("Invalid cash session status: {}", status).into()))?,
+            opened_by_user_id: row.get("opened_by_user_id"),
+            opened_by: row.get("opened_by"),
+            opening_float: Rupiah::from_row(&row, "opening_float")?,
+            opened_at: Self::parse_datetime(&row, "opened_at")?,
+            closed_by: row.try_get("closed_by").ok(),
+            closed_at,
+            expected_amount: Rupiah::from_row_optional(&row, "expected_amount")?,
+            counted_amount: Rupiah::from_row_optional(&row, "counted_amount")?,
+            variance: Rupiah::from_row_optional(&row, "variance")?,
+            notes: row.try_get("notes").ok(),
+            movements: Vec::new(),
+            payments: Vec::new(),
+        })
+    }
+
+    fn parse_row_to_movement(row: AnyRow) -> Result<CashMovement, sqlx::Error> {
+        let movement_type: String = row.get("movement_type");
+
+        Ok(CashMovement {
+            id: row.get("id"),
+            session_id: row.get("session_id"),
+            movement_type: CashMovementType::from_string(&movement_type)
+                .ok_or_else(|| sqlx::Error::Decode(format!("Invalid cash movement type: {}", movement_type).into()))?,
+            amount: Rupiah::from_row(&row, "amount")?,
+            reason: row.get("reason"),
+            created_by: row.get("created_by"),
+            created_at: Self::parse_datetime(&row, "created_at")?,
+        })
+    }
+
+    fn parse_row_to_session_payment(row: AnyRow) -> Result<SessionPayment, sqlx::Error> {
+        let method: String = row.get("method");
+
+        Ok(SessionPayment {
+            id: row.get("id"),
+            session_id: row.get("session_id"),
+            payment_id: row.get("payment_id"),
+            reference_id: row.get("reference_id"),
+            method: PaymentMethod::from_string(&method)
+                .ok_or_else(|| sqlx::Error::Decode(format!("Invalid payment method: {}", method).into()))?,
+            amount: Rupiah::from_row(&row, "amount")?,
+            recorded_at: Self::parse_datetime(&row, "recorded_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::any::{install_default_drivers, AnyPoolOptions};
+    use sqlx::Pool;
+
+    async fn setup() -> Pool<Any> {
+        install_default_drivers();
+        let db = AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        db
+    }
+
+    fn session(id: &str) -> CashDrawerSession {
+        CashDrawerSession {
+            id: id.to_string(),
+            status: CashSessionStatus::Open,
+            opened_by_user_id: 1,
+            opened_by: "kasir".to_string(),
+            opening_float: Rupiah::from_sen(50_000_050),
+            opened_at: Utc::now(),
+            closed_by: None,
+            closed_at: None,
+            expected_amount: None,
+            counted_amount: None,
+            variance: None,
+            notes: None,
+            movements: Vec::new(),
+            payments: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_one_session_per_cashier_can_be_open() {
+        let db = setup().await;
+
+        assert!(CashDrawerRepository::open(db.acquire().await.unwrap(), &session("CDS-1")).await.unwrap());
+        assert!(!CashDrawerRepository::open(db.acquire().await.unwrap(), &session("CDS-2")).await.unwrap());
+
+        let mut other_cashier = session("CDS-3");
+        other_cashier.opened_by_user_id = 2;
+        other_cashier.opened_by = "kasir2".to_string();
+        assert!(CashDrawerRepository::open(db.acquire().await.unwrap(), &other_cashier).await.unwrap());
+        let open = CashDrawerRepository::find_open(db.acquire().await.unwrap(), 2).await.unwrap().unwrap();
+        assert_eq!(open.id, "CDS-3");
+
+        let open = CashDrawerRepository::find_open(db.acquire().await.unwrap(), 1).await.unwrap().unwrap();
+        assert_eq!(open.id, "CDS-1");
+        assert_eq!(open.opening_float, Rupiah::from_sen(50_000_050));
+        assert_eq!(open.closed_at, None);
+        assert_eq!(open.counted_amount, None);
+    }
+
+    #[tokio::test]
+    async fn test_movements_payments_and_close() {
+        let db = setup().await;
+        CashDrawerRepository::open(db.acquire().await.unwrap(), &session("CDS-1")).await.unwrap();
+        let mut conn = db.acquire().await.unwrap();
+
+        let movement = CashMovement {
+            id: "CMV-1".to_string(),
+            session_id: "CDS-1".to_string(),
+            movement_type: CashMovementType::CashOut,
+            amount: Rupiah::from_rupiah(200_000),
+            reason: "Setor ke bank".to_string(),
+            created_by: "kasir".to_string(),
+            created_at: Utc::now(),
+        };
+        assert!(CashDrawerRepository::add_movement(&mut conn, &movement).await.unwrap());
+
+        let entry = |id: &str| SessionPayment {
+            id: id.to_string(),
+            session_id: "CDS-1".to_string(),
+            payment_id: "PMT-1".to_string(),
+            reference_id: "PMT-1".to_string(),
+            method: PaymentMethod::Cash,
+            amount: Rupiah::from_rupiah(125_000),
+            recorded_at: Utc::now(),
+        };
+        assert_eq!(CashDrawerRepository::add_session_payments(&mut conn, &[entry("CSP-1")]).await.unwrap(), 1);
+
+        let closing = CashDrawerSessionClosing {
+            closed_by: "supervisor",
+            closed_at: Utc::now(),
+            expected_amount: Rupiah::from_rupiah(425_000),
+            counted_amount: Rupiah::from_rupiah(420_000),
+            variance: Rupiah::from_rupiah(-5_000),
+            notes: Some("Kurang 5 ribu"),
+        };
+        assert!(CashDrawerRepository::close(&mut conn, "CDS-1", &closing).await.unwrap());
+        assert!(!CashDrawerRepository::close(&mut conn, "CDS-1", &closing).await.unwrap());
+
+        // Sesi yang sudah ditutup tidak menerima mutasi atau pembayaran baru
+        let mut late_movement = movement.clone();
+        late_movement.id = "CMV-2".to_string();
+        assert!(!CashDrawerRepository::add_movement(&mut conn, &late_movement).await.unwrap());
+        assert_eq!(CashDrawerRepository::add_session_payments(&mut conn, &[entry("CSP-2")]).await.unwrap(), 0);
+        drop(conn);
+
+        let closed = CashDrawerRepository::find_by_id(db.acquire().await.unwrap(), "CDS-1").await.unwrap();
+        assert_eq!(closed.status, CashSessionStatus::Closed);
+        assert_eq!(closed.closed_by.as_deref(), Some("supervisor"));
+        assert!(closed.closed_at.is_some());
+        assert_eq!(closed.variance, Some(Rupiah::from_rupiah(-5_000)));
+        assert_eq!(closed.notes.as_deref(), Some("Kurang 5 ribu"));
+        assert_eq!(closed.movements.len(), 1);
+        assert_eq!(closed.payments.len(), 1);
+        assert_eq!(closed.payments[0].amount, Rupiah::from_rupiah(125_000));
+
+        assert!(CashDrawerRepository::find_open(db.acquire().await.unwrap(), 1).await.unwrap().is_none());
+        assert!(CashDrawerRepository::open(db.acquire().await.unwrap(), &session("CDS-2")).await.unwrap());
+    }
+}
@@ -0,0 +1,286 @@
+use sqlx::any::AnyRow;
+use sqlx::{Any, Connection, pool::PoolConnection};
+use sqlx::Row;
+use chrono::{DateTime, Utc};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::plan_frequency::PlanFrequency;
+use crate::manajemen_pembayaran::enums::schedule_status::ScheduleStatus;
+use crate::manajemen_pembayaran::model::installment_plan::{InstallmentPlan, ScheduledInstallment};
+use crate::manajemen_pembayaran::model::payment::Installment;
+
+pub struct InstallmentPlanRepository;
+
+impl InstallmentPlanRepository {
+    // Menyimpan rencana, jadwal termin, uang muka, dan jatuh tempo pembayaran dalam satu transaksi database
+    pub async fn create(mut db: PoolConnection<Any>, plan: &InstallmentPlan, down_payment: Option<&Installment>) -> Result<InstallmentPlan, sqlx::Error> {
+        let mut tx = (*db).begin().await?;
+
+        sqlx::query("
+            INSERT INTO installment_plans (id, payment_id, number_of_terms, frequency, first_due_date, down_payment, principal, created_at)
+            VALUES ($1, $2, $3, $4, $5, CAST($6 AS DECIMAL(15,2)), CAST($7 AS DECIMAL(15,2)), $8)
+        ")
+            .bind(&plan.id)
+            .bind(&plan.payment_id)
+            .bind(plan.number_of_terms)
+            .bind(plan.frequency.to_string())
+            .bind(plan.first_due_date.to_rfc3339())
+            .bind(plan.down_payment.to_string())
+            .bind(plan.principal.to_string())
+            .bind(plan.created_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+
+        for term in &plan.schedule {
+            sqlx::query("
+                INSERT INTO installment_schedules (id, plan_id, term_number, due_date, amount_due)
+                VALUES ($1, $2, $3, $4, CAST($5 AS DECIMAL(15,2)))
+            ")
+                .bind(&term.id)
+                .bind(&term.plan_id)
+                .bind(term.term_number)
+                .bind(term.due_date.to_rfc3339())
+                .bind(term.amount_due.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        if let Some(installment) = down_payment {
+            sqlx::query("
+                INSERT INTO installments (id, payment_id, amount, payment_date)
+                VALUES ($1, $2, CAST($3 AS DECIMAL(15,2)), $4)
+            ")
+                .bind(&installment.id)
+                .bind(&installment.payment_id)
+                .bind(installment.amount.to_string())
+                .bind(installment.payment_date.to_rfc3339())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("UPDATE payments SET due_date = $1 WHERE id = $2")
+            .bind(plan.first_due_date.to_rfc3339())
+            .bind(&plan.payment_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(plan.clone())
+    }
+
+    pub async fn find_by_payment_id(mut db: PoolConnection<Any>, payment_id: &str) -> Result<Option<InstallmentPlan>, sqlx::Error> {
+        let plan_row = sqlx::query("
+            SELECT id, payment_id, number_of_terms, frequency, first_due_date,
+                   CAST(down_payment AS TEXT) AS down_payment, CAST(principal AS TEXT) AS principal, created_at
+            FROM installment_plans
+            WHERE payment_id = $1
+        ")
+            .bind(payment_id)
+            .fetch_optional(&mut *db)
+            .await?;
+
+        let mut plan = match plan_row {
+            Some(row) => Self::parse_row_to_plan(row)?,
+            None => return Ok(None),
+        };
+
+        let schedule_rows = sqlx::query("
+            SELECT id, plan_id, term_number, due_date, CAST(amount_due AS TEXT) AS amount_due
+            FROM installment_schedules
+            WHERE plan_id = $1
+            ORDER BY term_number ASC
+        ")
+            .bind(&plan.id)
+            .fetch_all(&mut *db)
+            .await?;
+
+        let mut schedule = Vec::with_capacity(schedule_rows.len());
+        for row in schedule_rows {
+            schedule.push(Self::parse_row_to_scheduled_installment(row)?);
+        }
+        plan.schedule = schedule;
+
+        Ok(Some(plan))
+    }
+
+    pub async fn delete_by_payment_id(db: &mut PoolConnection<Any>, payment_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM installment_schedules WHERE plan_id IN (SELECT id FROM installment_plans WHERE payment_id = $1)")
+            .bind(payment_id)
+            .execute(&mut **db)
+            .await?;
+
+        sqlx::query("DELETE FROM installment_plans WHERE payment_id = $1")
+            .bind(payment_id)
+            .execute(&mut **db)
+            .await?;
+
+        Ok(())
+    }
+
+    fn parse_datetime(row: &AnyRow, column: &str) -> Result<DateTime<Utc>, sqlx::Error> {
+        let value: String = row.get(column);
+        DateTime::parse_from_rfc3339(&value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| {
+                eprintln!("Failed to parse {} '{}': {}", column, value, e);
+                sqlx::Error::RowNotFound
+            })
+    }
+
+    fn parse_row_to_plan(row: AnyRow) -> Result<InstallmentPlan, sqlx::Error> {
+        let frequency_str: String = row.get("frequency");
+        let frequency = PlanFrequency::from_string(&frequency_str).ok_or(sqlx::Error::RowNotFound)?;
+
+        Ok(InstallmentPlan {
+            id: row.get("id"),
+            payment_id: row.get("payment_id"),
+            number_of_terms: row.get("number_of_terms"),
+            frequency,
+            first_due_date: Self::parse_datetime(&row, "first_due_date")?,
+            down_payment: Rupiah::from_row(&row, "down_payment")?,
+            principal: Rupiah::from_row(&row, "principal")?,
+            created_at: Self::parse_datetime(&row, "created_at")?,
+            schedule: Vec::new(),
+        })
+    }
+
+    fn parse_row_to_scheduled_installment(row: AnyRow) -> Result<ScheduledInstallment, sqlx::Error> {
+        Ok(ScheduledInstallment {
+            id: row.get("id"),
+            plan_id: row.get("plan_id"),
+            term_number: row.get("term_number"),
+            due_date: Self::parse_datetime(&row, "due_date")?,
+            amount_due: Rupiah::from_row(&row, "amount_due")?,
+            amount_paid: Rupiah::ZERO,
+            status: ScheduleStatus::Upcoming,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use sqlx::any::{install_default_drivers, AnyPoolOptions};
+    use sqlx::Pool;
+
+    async fn setup() -> Pool<Any> {
+        install_default_drivers();
+        let db = AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        sqlx::query("
+            INSERT INTO payments (id, transaction_id, amount, method, status, payment_date, due_date)
+            VALUES ('PMT-1', '1', 1000000, 'CASH', 'CICILAN', $1, NULL)
+        ")
+            .bind(Utc::now().to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+
+        db
+    }
+
+    fn sample_plan(first_due_date: DateTime<Utc>) -> InstallmentPlan {
+        let schedule = (0..2).map(|i| ScheduledInstallment {
+            id: format!("SCH-{}", i),
+            plan_id: "PLAN-1".to_string(),
+            term_number: i + 1,
+            due_date: first_due_date + Duration::weeks(i as i64),
+            amount_due: Rupiah::from_sen(45_000_050),
+            amount_paid: Rupiah::ZERO,
+            status: ScheduleStatus::Upcoming,
+        }).collect();
+
+        InstallmentPlan {
+            id: "PLAN-1".to_string(),
+            payment_id: "PMT-1".to_string(),
+            number_of_terms: 2,
+            frequency: PlanFrequency::Weekly,
+            first_due_date,
+            down_payment: Rupiah::from_sen(9_999_900),
+            principal: Rupiah::from_sen(90_000_100),
+            created_at: Utc::now(),
+            schedule,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find_plan() {
+        let db = setup().await;
+        let first_due_date = Utc::now() + Duration::days(7);
+        let down_payment = Installment {
+            id: "INST-DP".to_string(),
+            payment_id: "PMT-1".to_string(),
+            amount: Rupiah::from_sen(9_999_900),
+            payment_date: Utc::now(),
+        };
+
+        InstallmentPlanRepository::create(db.acquire().await.unwrap(), &sample_plan(first_due_date), Some(&down_payment))
+            .await
+            .unwrap();
+
+        let plan = InstallmentPlanRepository::find_by_payment_id(db.acquire().await.unwrap(), "PMT-1")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(plan.frequency, PlanFrequency::Weekly);
+        assert_eq!(plan.principal, Rupiah::from_sen(90_000_100));
+        assert_eq!(plan.schedule.len(), 2);
+        assert_eq!(plan.schedule[1].term_number, 2);
+        assert_eq!(plan.schedule[1].amount_due, Rupiah::from_sen(45_000_050));
+
+        let installment_count: i64 = sqlx::query("SELECT COUNT(*) AS count FROM installments WHERE payment_id = 'PMT-1'")
+            .fetch_one(&db)
+            .await
+            .unwrap()
+            .get("count");
+        assert_eq!(installment_count, 1);
+
+        let due_date: String = sqlx::query("SELECT due_date FROM payments WHERE id = 'PMT-1'")
+            .fetch_one(&db)
+            .await
+            .unwrap()
+            .get("due_date");
+        assert_eq!(due_date, first_due_date.to_rfc3339());
+    }
+
+    #[tokio::test]
+    async fn test_find_plan_missing() {
+        let db = setup().await;
+
+        let plan = InstallmentPlanRepository::find_by_payment_id(db.acquire().await.unwrap(), "PMT-1")
+            .await
+            .unwrap();
+        assert!(plan.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delete_plan_by_payment_id() {
+        let db = setup().await;
+
+        InstallmentPlanRepository::create(db.acquire().await.unwrap(), &sample_plan(Utc::now()), None)
+            .await
+            .unwrap();
+        InstallmentPlanRepository::delete_by_payment_id(&mut db.acquire().await.unwrap(), "PMT-1")
+            .await
+            .unwrap();
+
+        let schedule_count: i64 = sqlx::query("SELECT COUNT(*) AS count FROM installment_schedules")
+            .fetch_one(&db)
+            .await
+            .unwrap()
+            .get("count");
+        assert_eq!(schedule_count, 0);
+    }
+}
@@ -0,0 +1,197 @@
+use sqlx::any::AnyRow;
+use sqlx::{Any, Connection, pool::PoolConnection};
+use sqlx::Row;
+use chrono::{DateTime, Utc};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::model::overdue::LateFee;
+
+pub struct LateFeeRepository;
+
+impl LateFeeRepository {
+    // Menyimpan denda baru dan perubahan status keterlambatan dalam satu transaksi database.
+    // Status ditulis bersyarat pada status yang dibaca saat evaluasi (sekaligus mengunci baris);
+    // bila sudah berubah, misalnya karena cicilan yang melunasi, tidak ada yang disimpan dan hasilnya false.
+    pub async fn record_assessment(mut db: PoolConnection<Any>, payment_id: &str, current_status: &PaymentStatus, new_status: Option<&PaymentStatus>, late_fees: &[LateFee]) -> Result<bool, sqlx::Error> {
+        let mut tx = (*db).begin().await?;
+
+        let result = sqlx::query("UPDATE payments SET status = $1 WHERE id = $2 AND status = $3")
+            .bind(new_status.unwrap_or(current_status).to_string())
+            .bind(payment_id)
+            .bind(current_status.to_string())
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() != 1 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        for late_fee in late_fees {
+            sqlx::query("
+                INSERT INTO late_fees (id, payment_id, period_number, amount, assessed_at)
+                VALUES ($1, $2, $3, CAST($4 AS DECIMAL(15,2)), $5)
+            ")
+                .bind(&late_fee.id)
+                .bind(&late_fee.payment_id)
+                .bind(late_fee.period_number)
+                .bind(late_fee.amount.to_string())
+                .bind(late_fee.assessed_at.to_rfc3339())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    pub async fn find_by_payment_id(mut db: PoolConnection<Any>, payment_id: &str) -> Result<Vec<LateFee>, sqlx::Error> {
+        let rows = sqlx::query("
+            SELECT id, payment_id, period_number, CAST(amount AS TEXT) AS amount, assessed_at
+            FROM late_fees
+            WHERE payment_id = $1
+            ORDER BY period_number ASC
+        ")
+            .bind(payment_id)
+            .fetch_all(&mut *db)
+            .await?;
+
+        let mut late_fees = Vec::with_capacity(rows.len());
+        for row in rows {
+            late_fees.push(Self::parse_row_to_late_fee(row)?);
+        }
+
+        Ok(late_fees)
+    }
+
+    fn parse_row_to_late_fee(row: AnyRow) -> Result<LateFee, sqlx::Error> {
+        let assessed_at_str: String = row.get("assessed_at");
+        let assessed_at = DateTime::parse_from_rfc3339(&assessed_at_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| {
+                eprintln!("Failed to parse assessed_at '{}': {}", assessed_at_str, e);
+                sqlx::Error::RowNotFound
+            })?;
+
+        Ok(LateFee {
+            id: row.get("id"),
+            payment_id: row.get("payment_id"),
+            period_number: row.get("period_number"),
+            amount: Rupiah::from_row(&row, "amount")?,
+            assessed_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::any::{install_default_drivers, AnyPoolOptions};
+    use sqlx::Pool;
+
+    async fn setup() -> Pool<Any> {
+        install_default_drivers();
+        let db = AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        sqlx::query("
+            INSERT INTO payments (id, transaction_id, amount, method, status, payment_date, due_date)
+            VALUES ('PMT-1', '1', 1000000, 'CASH', 'CICILAN', $1, NULL)
+        ")
+            .bind(Utc::now().to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+
+        db
+    }
+
+    fn late_fee(period_number: i32) -> LateFee {
+        LateFee {
+            id: format!("LF-{}", period_number),
+            payment_id: "PMT-1".to_string(),
+            period_number,
+            amount: Rupiah::from_sen(2_500_050),
+            assessed_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_assessment_and_find() {
+        let db = setup().await;
+
+        LateFeeRepository::record_assessment(db.acquire().await.unwrap(), "PMT-1", &PaymentStatus::Installment, Some(&PaymentStatus::Overdue), &[late_fee(1), late_fee(2)])
+            .await
+            .unwrap();
+
+        let late_fees = LateFeeRepository::find_by_payment_id(db.acquire().await.unwrap(), "PMT-1")
+            .await
+            .unwrap();
+        assert_eq!(late_fees.len(), 2);
+        assert_eq!(late_fees[1].period_number, 2);
+        assert_eq!(late_fees[0].amount, Rupiah::from_sen(2_500_050));
+
+        let status: String = sqlx::query("SELECT status FROM payments WHERE id = 'PMT-1'")
+            .fetch_one(&db)
+            .await
+            .unwrap()
+            .get("status");
+        assert_eq!(status, "TERLAMBAT");
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_period_rolls_back() {
+        let db = setup().await;
+
+        LateFeeRepository::record_assessment(db.acquire().await.unwrap(), "PMT-1", &PaymentStatus::Installment, None, &[late_fee(1)])
+            .await
+            .unwrap();
+
+        let mut duplicate = late_fee(1);
+        duplicate.id = "LF-DUP".to_string();
+        let result = LateFeeRepository::record_assessment(db.acquire().await.unwrap(), "PMT-1", &PaymentStatus::Installment, Some(&PaymentStatus::Overdue), &[duplicate])
+            .await;
+        assert!(result.is_err());
+
+        let status: String = sqlx::query("SELECT status FROM payments WHERE id = 'PMT-1'")
+            .fetch_one(&db)
+            .await
+            .unwrap()
+            .get("status");
+        assert_eq!(status, "CICILAN");
+    }
+
+    #[tokio::test]
+    async fn test_stale_status_is_not_overwritten() {
+        let db = setup().await;
+        sqlx::query("UPDATE payments SET status = 'LUNAS' WHERE id = 'PMT-1'")
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let recorded = LateFeeRepository::record_assessment(db.acquire().await.unwrap(), "PMT-1", &PaymentStatus::Installment, Some(&PaymentStatus::Overdue), &[late_fee(1)])
+            .await
+            .unwrap();
+        assert!(!recorded);
+
+        let status: String = sqlx::query("SELECT status FROM payments WHERE id = 'PMT-1'")
+            .fetch_one(&db)
+            .await
+            .unwrap()
+            .get("status");
+        assert_eq!(status, "LUNAS");
+        let late_fees = LateFeeRepository::find_by_payment_id(db.acquire().await.unwrap(), "PMT-1")
+            .await
+            .unwrap();
+        assert!(late_fees.is_empty());
+    }
+}
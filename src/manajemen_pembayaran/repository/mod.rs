@@ -1 +1,6 @@
-pub mod payment_repository;
\ No newline at end of file
+pub mod payment_repository;
+pub mod refund_repository;
+pub mod installment_plan_repository;
+pub mod late_fee_repository;
+pub mod webhook_event_repository;
+pub mod cash_drawer_repository;
\ No newline at end of file
@@ -1,24 +1,36 @@
 use sqlx::any::AnyRow;
-use sqlx::{Any, pool::PoolConnection};
+use sqlx::{Any, AnyConnection, Connection, pool::PoolConnection};
 use sqlx::Row;
 use chrono::{DateTime, Utc, NaiveDateTime};
 use std::collections::HashMap;
-use uuid::Uuid;
 
+use crate::common::money::Rupiah;
+use crate::common::query_builder::SqlQueryBuilder;
 use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
-use crate::manajemen_pembayaran::model::payment::{Payment, PaymentMethod, Installment};
+use crate::manajemen_pembayaran::model::payment::{Payment, PaymentMethod, Installment, TenderLine, PaymentMethodSummary};
+use crate::manajemen_pembayaran::model::payment_query::PaymentQuery;
+use crate::manajemen_pembayaran::repository::installment_plan_repository::InstallmentPlanRepository;
 
 pub struct PembayaranRepository;
 
-impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>, payment: &Payment) -> Result<Payment, sqlx::Error>{        
+impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>, payment: &Payment) -> Result<Payment, sqlx::Error>{
+        let mut tx = (*db).begin().await?;
+        let created_payment = Self::insert(&mut tx, payment).await?;
+        tx.commit().await?;
+
+        Ok(created_payment)
+    }
+
+    // Pembayaran beserta cicilan dan baris tender, untuk pemanggil yang sudah membuka transaksi database sendiri
+    pub async fn insert(db: &mut AnyConnection, payment: &Payment) -> Result<Payment, sqlx::Error> {
         eprintln!("DEBUG: Creating payment with ID: {}, Transaction ID: {}", payment.id, payment.transaction_id);
         sqlx::query("
             INSERT INTO payments (id, transaction_id, amount, method, status, payment_date, due_date)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            VALUES ($1, $2, CAST($3 AS DECIMAL(15,2)), $4, $5, $6, $7)
         ")
             .bind(&payment.id)
             .bind(&payment.transaction_id)
-            .bind(payment.amount)
+            .bind(payment.amount.to_string())
             .bind(payment.method.to_string())
             .bind(payment.status.to_string())
             .bind(payment.payment_date.to_rfc3339())
@@ -31,7 +43,7 @@ impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>,
             })?;
 
         let result = sqlx::query("
-            SELECT id, transaction_id, amount, method, status, payment_date, due_date
+            SELECT id, transaction_id, CAST(amount AS TEXT) AS amount, method, status, payment_date, due_date
             FROM payments
             WHERE id = $1
         ")
@@ -41,12 +53,16 @@ impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>,
 
         let mut created_payment = Self::parse_row_to_payment(result)?;
         
-        if !payment.installments.is_empty() {
+        if !payment.installments.is_empty() || !payment.tender_lines.is_empty() {
             for installment in &payment.installments {
-                Self::add_installment(&mut db, installment).await?;
+                Self::add_installment(&mut *db, installment).await?;
+            }
+
+            for (line_no, tender_line) in payment.tender_lines.iter().enumerate() {
+                Self::add_tender_line(&mut *db, tender_line, line_no as i32 + 1).await?;
             }
             
-            created_payment = Self::load_payment_with_installments(&mut db, &created_payment.id).await?;
+            created_payment = Self::load_payment_with_installments(&mut *db, &created_payment.id).await?;
         }
 
         Ok(created_payment)
@@ -54,59 +70,134 @@ impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>,
         let payment_with_installments = Self::load_payment_with_installments(&mut db, id).await?;
 
         Ok(payment_with_installments)
-    }    pub async fn find_all(mut db: PoolConnection<Any>, filters: Option<HashMap<String, String>>) -> Result<Vec<Payment>, sqlx::Error> {
-        let mut base_query = "SELECT id, transaction_id, amount, method, status, payment_date, due_date FROM payments".to_string();
-        let mut where_clauses = Vec::new();
-        let mut bind_values: Vec<&str> = Vec::new();
-        
-        if let Some(filter_map) = &filters {
-            if let Some(status_str) = filter_map.get("status") {
-                where_clauses.push("status = $1".to_string());
-                bind_values.push(status_str);
-            }
-            if let Some(method) = filter_map.get("method") {
-                let param_num = bind_values.len() + 1;
-                where_clauses.push(format!("method = ${}", param_num));
-                bind_values.push(method);
-            }
-            if let Some(transaction_id) = filter_map.get("transaction_id") {
-                let param_num = bind_values.len() + 1;
-                where_clauses.push(format!("transaction_id = ${}", param_num));
-                bind_values.push(transaction_id);
-            }
+    }    pub async fn find_all(mut db: PoolConnection<Any>, query: &PaymentQuery) -> Result<Vec<Payment>, sqlx::Error> {
+        Self::find_matching(&mut db, query).await
+    }
+
+    // Untuk pemanggil yang sudah membuka transaksi database sendiri
+    pub async fn find_matching(db: &mut AnyConnection, query: &PaymentQuery) -> Result<Vec<Payment>, sqlx::Error> {
+        let mut builder = Self::filter_builder(query);
+        builder.order_by(&format!("{} {}", query.sort.column(), query.direction.sql()))
+            .order_by(&format!("id {}", query.direction.sql()));
+        if let Some(limit) = query.limit {
+            builder.limit(limit);
         }
-        
-        if !where_clauses.is_empty() {
-            base_query.push_str(" WHERE ");
-            base_query.push_str(&where_clauses.join(" AND "));
-        }        
-        let rows = match bind_values.len() {
-            0 => sqlx::query(&base_query).fetch_all(&mut *db).await?,
-            1 => sqlx::query(&base_query).bind(bind_values[0]).fetch_all(&mut *db).await?,
-            2 => sqlx::query(&base_query).bind(bind_values[0]).bind(bind_values[1]).fetch_all(&mut *db).await?,
-            3 => sqlx::query(&base_query).bind(bind_values[0]).bind(bind_values[1]).bind(bind_values[2]).fetch_all(&mut *db).await?,
-            _ => return Err(sqlx::Error::ColumnNotFound("Too many filters".to_string())),
-        };
-        
+        if query.offset > 0 {
+            builder.offset(query.offset);
+        }
+
+        let sql = builder.to_sql("SELECT id, transaction_id, CAST(amount AS TEXT) AS amount, method, status, payment_date, due_date FROM payments");
+        let rows = builder.bind(&sql).fetch_all(&mut *db).await?;
+
         let mut payments = Vec::with_capacity(rows.len());
         for row in rows {
-            let payment_id: String = row.get("id");
-            let payment_with_installments = Self::load_payment_with_installments(&mut db, &payment_id).await?;
-            payments.push(payment_with_installments);
+            payments.push(Self::parse_row_to_payment(row)?);
         }
-        
+
+        Self::load_details(&mut *db, &mut payments).await?;
+
         Ok(payments)
     }
+
+    pub async fn count(mut db: PoolConnection<Any>, query: &PaymentQuery) -> Result<i64, sqlx::Error> {
+        let builder = Self::filter_builder(query);
+        let sql = builder.to_filter_sql("SELECT COUNT(*) AS total FROM payments");
+        let row = builder.bind(&sql).fetch_one(&mut *db).await?;
+
+        Ok(row.get("total"))
+    }
+
+    fn filter_builder(query: &PaymentQuery) -> SqlQueryBuilder {
+        let mut builder = SqlQueryBuilder::new();
+
+        if let Some(status) = &query.status {
+            builder.and_where("status = {}", status.to_string());
+        }
+        if let Some(method) = &query.method {
+            builder.and_where(
+                "(method = {} OR id IN (SELECT payment_id FROM payment_tenders WHERE method = {}))",
+                method.to_string(),
+            );
+        }
+        if let Some(transaction_id) = &query.transaction_id {
+            builder.and_where("transaction_id = {}", transaction_id.as_str());
+        }
+        // Tanggal disimpan sebagai teks RFC3339 UTC sehingga perbandingan string sama dengan urutan waktu
+        if let Some(paid_from) = query.paid_from {
+            builder.and_where("payment_date >= {}", paid_from.to_rfc3339());
+        }
+        if let Some(paid_to) = query.paid_to {
+            builder.and_where("payment_date <= {}", paid_to.to_rfc3339());
+        }
+        if let Some(min_amount) = query.min_amount {
+            builder.and_where("amount >= CAST({} AS DECIMAL(15,2))", min_amount.to_string());
+        }
+        if let Some(max_amount) = query.max_amount {
+            builder.and_where("amount <= CAST({} AS DECIMAL(15,2))", max_amount.to_string());
+        }
+        if let Some(due_from) = query.due_from {
+            builder.and_where("due_date >= {}", due_from.to_rfc3339());
+        }
+        if let Some(due_to) = query.due_to {
+            builder.and_where("due_date <= {}", due_to.to_rfc3339());
+        }
+
+        builder
+    }
+
+    // Cicilan, baris tender, dan total denda untuk seluruh halaman dimuat dengan satu query masing-masing
+    async fn load_details(db: &mut AnyConnection, payments: &mut [Payment]) -> Result<(), sqlx::Error> {
+        if payments.is_empty() {
+            return Ok(());
+        }
+        let payment_ids: Vec<String> = payments.iter().map(|p| p.id.clone()).collect();
+
+        let mut builder = SqlQueryBuilder::new();
+        builder.and_where_in("payment_id", payment_ids.clone())
+            .order_by("payment_date ASC");
+        let sql = builder.to_sql("SELECT id, payment_id, CAST(amount AS TEXT) AS amount, payment_date FROM installments");
+        let mut installments: HashMap<String, Vec<Installment>> = HashMap::new();
+        for row in builder.bind(&sql).fetch_all(&mut *db).await? {
+            let installment = Self::parse_row_to_installment(row)?;
+            installments.entry(installment.payment_id.clone()).or_default().push(installment);
+        }
+
+        let mut builder = SqlQueryBuilder::new();
+        builder.and_where_in("payment_id", payment_ids.clone())
+            .order_by("line_no ASC");
+        let sql = builder.to_sql("SELECT id, payment_id, method, CAST(amount AS TEXT) AS amount, reference_number FROM payment_tenders");
+        let mut tender_lines: HashMap<String, Vec<TenderLine>> = HashMap::new();
+        for row in builder.bind(&sql).fetch_all(&mut *db).await? {
+            let tender_line = Self::parse_row_to_tender_line(row)?;
+            tender_lines.entry(tender_line.payment_id.clone()).or_default().push(tender_line);
+        }
+
+        let mut builder = SqlQueryBuilder::new();
+        builder.and_where_in("payment_id", payment_ids);
+        let sql = builder.to_filter_sql("SELECT payment_id, CAST(SUM(amount) AS TEXT) AS total FROM late_fees") + " GROUP BY payment_id";
+        let mut late_fees: HashMap<String, Rupiah> = HashMap::new();
+        for row in builder.bind(&sql).fetch_all(&mut *db).await? {
+            late_fees.insert(row.get("payment_id"), Rupiah::from_row(&row, "total")?);
+        }
+
+        for payment in payments.iter_mut() {
+            payment.installments = installments.remove(&payment.id).unwrap_or_default();
+            payment.tender_lines = tender_lines.remove(&payment.id).unwrap_or_default();
+            payment.late_fee_total = late_fees.remove(&payment.id).unwrap_or_default();
+        }
+
+        Ok(())
+    }
       pub async fn update(mut db: PoolConnection<Any>, payment: &Payment) -> Result<Payment, sqlx::Error>{
         let payment_method_str = payment.method.to_string();
         let status_str = payment.status.to_string();
         sqlx::query("
             UPDATE payments
-            SET transaction_id = $1, amount = $2, method = $3, status = $4, payment_date = $5, due_date = $6
+            SET transaction_id = $1, amount = CAST($2 AS DECIMAL(15,2)), method = $3, status = $4, payment_date = $5, due_date = $6
             WHERE id = $7
         ")
         .bind(&payment.transaction_id)
-        .bind(payment.amount)
+        .bind(payment.amount.to_string())
         .bind(&payment_method_str)
         .bind(&status_str)
         .bind(payment.payment_date.to_rfc3339())
@@ -116,7 +207,7 @@ impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>,
         .await?;
 
         let result = sqlx::query("
-            SELECT id, transaction_id, amount, method, status, payment_date, due_date
+            SELECT id, transaction_id, CAST(amount AS TEXT) AS amount, method, status, payment_date, due_date
             FROM payments
             WHERE id = $1
         ")
@@ -131,37 +222,28 @@ impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>,
         Ok(payment_with_installments)
     }
 
-    pub async fn update_payment_status(mut db: PoolConnection<Any>, payment_id: String, new_status: PaymentStatus, additional_amount: Option<f64>) -> Result<Payment, sqlx::Error> {        let payment_result = sqlx::query("
-            SELECT id, transaction_id, amount, method, status, payment_date, due_date
-            FROM payments
-            WHERE id = $1
-        ")
-        .bind(&payment_id)        .fetch_one(&mut *db)
-        .await?;
-        
-        let mut payment = Self::parse_row_to_payment(payment_result)?;
-        
-        payment.status = new_status;
-        
-        if let Some(amount) = additional_amount {
-            let installment = Installment {
-                id: format!("INST-{}", Uuid::new_v4()),
-                payment_id: payment_id.clone(),
-                amount,
-                payment_date: Utc::now(),
-            };
-            
-            Self::add_installment(&mut db, &installment).await?;
-        }
-        
-        Self::update(db, &payment).await
-    }
-
     pub async fn delete(mut db: PoolConnection<Any>, id: &str) -> Result<(), sqlx::Error>{
         sqlx::query("DELETE FROM installments WHERE payment_id = $1")
             .bind(id)
             .execute(&mut *db)
             .await?;
+
+        sqlx::query("DELETE FROM late_fees WHERE payment_id = $1")
+            .bind(id)
+            .execute(&mut *db)
+            .await?;
+
+        sqlx::query("DELETE FROM refunds WHERE payment_id = $1")
+            .bind(id)
+            .execute(&mut *db)
+            .await?;
+
+        sqlx::query("DELETE FROM payment_tenders WHERE payment_id = $1")
+            .bind(id)
+            .execute(&mut *db)
+            .await?;
+
+        InstallmentPlanRepository::delete_by_payment_id(&mut db, id).await?;
         
         sqlx::query("DELETE FROM payments WHERE id = $1")
             .bind(id)
@@ -170,27 +252,113 @@ impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>,
         
         Ok(())
     }
-      pub async fn add_installment(db: &mut PoolConnection<Any>, installment: &Installment) -> Result<(), sqlx::Error> {
+      pub async fn add_installment(db: &mut AnyConnection, installment: &Installment) -> Result<(), sqlx::Error> {
         sqlx::query("
             INSERT INTO installments (id, payment_id, amount, payment_date)
-            VALUES ($1, $2, $3, $4)
+            VALUES ($1, $2, CAST($3 AS DECIMAL(15,2)), $4)
         ")
         .bind(&installment.id)
         .bind(&installment.payment_id)
-        .bind(installment.amount)
+        .bind(installment.amount.to_string())
         .bind(installment.payment_date.to_rfc3339())
-        .execute(&mut **db)
+        .execute(&mut *db)
         .await?;
         
         Ok(())
-    }    pub async fn load_payment_with_installments(db: &mut PoolConnection<Any>, payment_id: &str) -> Result<Payment, sqlx::Error> {        
+    }
+
+    // Kunci baris pembayaran sampai transaksi database selesai. PostgreSQL memakai
+    // SELECT ... FOR UPDATE; SQLite tidak punya row lock sehingga UPDATE tanpa perubahan
+    // dipakai untuk mengambil write lock lebih awal.
+    pub async fn lock_payment(db: &mut AnyConnection, payment_id: &str) -> Result<Payment, sqlx::Error> {
+        if db.backend_name().eq_ignore_ascii_case("postgresql") {
+            sqlx::query("SELECT id FROM payments WHERE id = $1 FOR UPDATE")
+                .bind(payment_id)
+                .fetch_one(&mut *db)
+                .await?;
+        } else {
+            sqlx::query("UPDATE payments SET status = status WHERE id = $1")
+                .bind(payment_id)
+                .execute(&mut *db)
+                .await?;
+        }
+
+        Self::load_payment_with_installments(db, payment_id).await
+    }
+
+    // Hanya berhasil bila status di database masih `from`, sehingga perubahan status
+    // yang dibaca sebelumnya tidak menimpa perubahan lain yang sudah tersimpan.
+    pub async fn set_status_if(db: &mut AnyConnection, payment_id: &str, from: &PaymentStatus, to: &PaymentStatus) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE payments SET status = $1 WHERE id = $2 AND status = $3")
+            .bind(to.to_string())
+            .bind(payment_id)
+            .bind(from.to_string())
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn add_tender_line(db: &mut AnyConnection, tender_line: &TenderLine, line_no: i32) -> Result<(), sqlx::Error> {
+        sqlx::query("
+            INSERT INTO payment_tenders (id, payment_id, line_no, method, amount, reference_number)
+            VALUES ($1, $2, $3, $4, CAST($5 AS DECIMAL(15,2)), $6)
+        ")
+        .bind(&tender_line.id)
+        .bind(&tender_line.payment_id)
+        .bind(line_no)
+        .bind(tender_line.method.to_string())
+        .bind(tender_line.amount.to_string())
+        .bind(&tender_line.reference_number)
+        .execute(&mut *db)
+        .await?;
+
+        Ok(())
+    }
+
+    // Total per metode pembayaran; pembayaran split tender dihitung per baris tender,
+    // pembayaran biasa dihitung dari metode utamanya.
+    pub async fn total_by_method(mut db: PoolConnection<Any>) -> Result<Vec<PaymentMethodSummary>, sqlx::Error> {
+        let rows = sqlx::query("
+            SELECT method, CAST(SUM(amount) AS TEXT) AS total, COUNT(*) AS tender_count
+            FROM (
+                SELECT t.method AS method, t.amount AS amount
+                FROM payment_tenders t
+                JOIN payments p ON p.id = t.payment_id
+                WHERE p.status IN ('LUNAS', 'CICILAN', 'TERLAMBAT')
+                UNION ALL
+                SELECT p.method AS method, p.amount AS amount
+                FROM payments p
+                WHERE p.status IN ('LUNAS', 'CICILAN', 'TERLAMBAT')
+                AND NOT EXISTS (SELECT 1 FROM payment_tenders t WHERE t.payment_id = p.id)
+            ) AS tenders
+            GROUP BY method
+            ORDER BY method
+        ")
+        .fetch_all(&mut *db)
+        .await?;
+
+        let mut summaries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let method_str: String = row.get("method");
+            summaries.push(PaymentMethodSummary {
+                method: PaymentMethod::from_string(&method_str).ok_or(sqlx::Error::RowNotFound)?,
+                total: Rupiah::from_row(&row, "total")?,
+                tender_count: row.get("tender_count"),
+            });
+        }
+
+        Ok(summaries)
+    }
+
+    pub async fn load_payment_with_installments(db: &mut AnyConnection, payment_id: &str) -> Result<Payment, sqlx::Error> {        
         let payment_row = sqlx::query("
-            SELECT id, transaction_id, amount, method, status, payment_date, due_date
+            SELECT id, transaction_id, CAST(amount AS TEXT) AS amount, method, status, payment_date, due_date
             FROM payments
             WHERE id = $1
         ")
         .bind(payment_id)
-        .fetch_one(&mut **db)
+        .fetch_one(&mut *db)
         .await
         .map_err(|e| {
             e
@@ -199,13 +367,13 @@ impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>,
         let mut payment = Self::parse_row_to_payment(payment_row)?;
         
         let installment_rows = sqlx::query("
-            SELECT id, payment_id, amount, payment_date
+            SELECT id, payment_id, CAST(amount AS TEXT) AS amount, payment_date
             FROM installments
             WHERE payment_id = $1
             ORDER BY payment_date ASC
         ")
         .bind(payment_id)
-        .fetch_all(&mut **db)
+        .fetch_all(&mut *db)
         .await?;
         
         let mut installments = Vec::with_capacity(installment_rows.len());
@@ -215,15 +383,40 @@ impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>,
         }
         
         payment.installments = installments;
+
+        let tender_rows = sqlx::query("
+            SELECT id, payment_id, method, CAST(amount AS TEXT) AS amount, reference_number
+            FROM payment_tenders
+            WHERE payment_id = $1
+            ORDER BY line_no ASC
+        ")
+        .bind(payment_id)
+        .fetch_all(&mut *db)
+        .await?;
+
+        let mut tender_lines = Vec::with_capacity(tender_rows.len());
+        for row in tender_rows {
+            tender_lines.push(Self::parse_row_to_tender_line(row)?);
+        }
+
+        payment.tender_lines = tender_lines;
+
+        let late_fee_row = sqlx::query("
+            SELECT CAST(COALESCE(SUM(amount), 0) AS TEXT) AS total
+            FROM late_fees
+            WHERE payment_id = $1
+        ")
+        .bind(payment_id)
+        .fetch_one(&mut *db)
+        .await?;
+        payment.late_fee_total = Rupiah::from_row(&late_fee_row, "total")?;
         
         Ok(payment)
     }
       fn parse_row_to_payment(row: AnyRow) -> Result<Payment, sqlx::Error> {
         let id: String = row.get("id");
         let transaction_id: String = row.get("transaction_id");
-        let amount: f64 = row.try_get("amount").unwrap_or_else(|_| {
-            row.try_get::<f32, _>("amount").map(|v| v as f64).unwrap_or(0.0)
-        });        let payment_method_str: String = row.get("method");
+        let amount = Rupiah::from_row(&row, "amount")?;        let payment_method_str: String = row.get("method");
         let status_str: String = row.get("status");
         let payment_date_str: String = row.get("payment_date");
         let due_date_str: Option<String> = row.try_get("due_date").ok();
@@ -274,15 +467,27 @@ impl PembayaranRepository {    pub async fn create(mut db: PoolConnection<Any>,
             status: payment_status,
             payment_date,
             installments: Vec::new(), 
+            tender_lines: Vec::new(),
             due_date,
+            late_fee_total: Rupiah::ZERO,
+        })
+    }
+    fn parse_row_to_tender_line(row: AnyRow) -> Result<TenderLine, sqlx::Error> {
+        let method_str: String = row.get("method");
+
+        Ok(TenderLine {
+            id: row.get("id"),
+            payment_id: row.get("payment_id"),
+            method: PaymentMethod::from_string(&method_str).ok_or(sqlx::Error::RowNotFound)?,
+            amount: Rupiah::from_row(&row, "amount")?,
+            reference_number: row.try_get("reference_number").ok(),
         })
     }
+
       fn parse_row_to_installment(row: AnyRow) -> Result<Installment, sqlx::Error> {
         let id: String = row.get("id");
         let payment_id: String = row.get("payment_id");
-        let amount: f64 = row.try_get("amount").unwrap_or_else(|_| {
-            row.try_get::<f32, _>("amount").map(|v| v as f64).unwrap_or(0.0)
-        });
+        let amount = Rupiah::from_row(&row, "amount")?;
         let payment_date_str: String = row.get("payment_date");        let payment_date = DateTime::parse_from_rfc3339(&payment_date_str)
             .map(|dt| dt.with_timezone(&Utc))
             .or_else(|_| {
@@ -492,17 +697,19 @@ mod tests {
     fn test_update_payment_status_logic() {
         let payment_id = "PMT-UPDATE-001".to_string();
         let new_status = PaymentStatus::Installment;
-        let additional_amount = Some(250.0);
+        let additional_amount = Some(Rupiah::from_rupiah(250));
 
         let mut test_payment = Payment {
             id: payment_id.clone(),
             transaction_id: "TXN-UPDATE-001".to_string(),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::BankTransfer,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
 
         test_payment.status = new_status;
@@ -517,7 +724,7 @@ mod tests {
             };
 
             assert_eq!(installment.payment_id, payment_id);
-            assert_eq!(installment.amount, 250.0);
+            assert_eq!(installment.amount, Rupiah::from_rupiah(250));
             assert!(installment.id.starts_with("INST-"));
         }
     }
@@ -527,7 +734,7 @@ mod tests {
         let payment = Payment {
             id: "PMT-CREATE-001".to_string(),
             transaction_id: "TXN-CREATE-001".to_string(),
-            amount: 1500.0,
+            amount: Rupiah::from_rupiah(1500),
             method: PaymentMethod::CreditCard,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
@@ -535,27 +742,29 @@ mod tests {
                 Installment {
                     id: "INST-001".to_string(),
                     payment_id: "PMT-CREATE-001".to_string(),
-                    amount: 500.0,
+                    amount: Rupiah::from_rupiah(500),
                     payment_date: Utc::now(),
                 },
                 Installment {
                     id: "INST-002".to_string(),
                     payment_id: "PMT-CREATE-001".to_string(),
-                    amount: 300.0,
+                    amount: Rupiah::from_rupiah(300),
                     payment_date: Utc::now(),
                 },
             ],
+            tender_lines: Vec::new(),
             due_date: Some(Utc::now()),
+            late_fee_total: Rupiah::ZERO,
         };
 
         assert!(!payment.installments.is_empty());
         assert_eq!(payment.installments.len(), 2);
 
-        let total_installments: f64 = payment.installments.iter().map(|i| i.amount).sum();
-        assert_eq!(total_installments, 800.0);
+        let total_installments: Rupiah = payment.installments.iter().map(|i| i.amount).sum();
+        assert_eq!(total_installments, Rupiah::from_rupiah(800));
 
         let remaining_amount = payment.amount - total_installments;
-        assert_eq!(remaining_amount, 700.0);
+        assert_eq!(remaining_amount, Rupiah::from_rupiah(700));
     }
 
     #[test]
@@ -622,7 +831,7 @@ mod tests {
             let installment = Installment {
                 id: format!("INST-{}", i),
                 payment_id: "PMT-001".to_string(),
-                amount: 100.0 * (i + 1) as f64,
+                amount: Rupiah::from_rupiah(100) * (i + 1),
                 payment_date: Utc::now(),
             };
             installments.push(installment);
@@ -652,12 +861,14 @@ mod tests {
         let payment = Payment {
             id: "PMT-UPDATE-002".to_string(),
             transaction_id: "TXN-UPDATE-002".to_string(),
-            amount: 2000.0,
+            amount: Rupiah::from_rupiah(2000),
             method: PaymentMethod::EWallet,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
 
         let payment_method_str = payment.method.to_string();
@@ -761,7 +972,7 @@ mod tests {
     fn test_payment_creation_structure() {
         let id = Uuid::new_v4().to_string();
         let transaction_id = format!("TXN-{}", Uuid::new_v4());
-        let amount = 1000.0;
+        let amount = Rupiah::from_rupiah(1000);
         let method = PaymentMethod::Cash;
         let status = PaymentStatus::Paid;
         let payment_date = Utc::now();
@@ -775,7 +986,9 @@ mod tests {
             status,
             payment_date,
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date,
+            late_fee_total: Rupiah::ZERO,
         };
 
         assert_eq!(payment.id, id);
@@ -791,7 +1004,7 @@ mod tests {
     fn test_installment_creation() {
         let id = format!("INST-{}", Uuid::new_v4());
         let payment_id = Uuid::new_v4().to_string();
-        let amount = 500.0;
+        let amount = Rupiah::from_rupiah(500);
         let payment_date = Utc::now();
 
         let installment = Installment {
@@ -852,7 +1065,7 @@ mod tests {
         let main_payment = Payment {
             id: Uuid::new_v4().to_string(),
             transaction_id: format!("TXN-{}", Uuid::new_v4()),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
@@ -860,24 +1073,26 @@ mod tests {
                 Installment {
                     id: format!("INST-{}", Uuid::new_v4()),
                     payment_id: "payment-1".to_string(),
-                    amount: 300.0,
+                    amount: Rupiah::from_rupiah(300),
                     payment_date: Utc::now(),
                 },
                 Installment {
                     id: format!("INST-{}", Uuid::new_v4()),
                     payment_id: "payment-1".to_string(),
-                    amount: 200.0,
+                    amount: Rupiah::from_rupiah(200),
                     payment_date: Utc::now(),
                 },
             ],
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
 
-        let total_installments: f64 = main_payment.installments.iter().map(|i| i.amount).sum();
-        assert_eq!(total_installments, 500.0);
+        let total_installments: Rupiah = main_payment.installments.iter().map(|i| i.amount).sum();
+        assert_eq!(total_installments, Rupiah::from_rupiah(500));
         
         let remaining_amount = main_payment.amount - total_installments;
-        assert_eq!(remaining_amount, 500.0);
+        assert_eq!(remaining_amount, Rupiah::from_rupiah(500));
     }
 
     #[test]
@@ -885,16 +1100,18 @@ mod tests {
         let payment = Payment {
             id: Uuid::new_v4().to_string(),
             transaction_id: format!("TXN-{}", Uuid::new_v4()),
-            amount: 1500.0,
+            amount: Rupiah::from_rupiah(1500),
             method: PaymentMethod::BankTransfer,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: Some(Utc::now()),
+            late_fee_total: Rupiah::ZERO,
         };
 
         assert!(payment.installments.is_empty());
-        assert_eq!(payment.amount, 1500.0);
+        assert_eq!(payment.amount, Rupiah::from_rupiah(1500));
         assert_eq!(payment.status, PaymentStatus::Paid);
     }
 
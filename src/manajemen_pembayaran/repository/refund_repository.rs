@@ -0,0 +1,239 @@
+use sqlx::any::AnyRow;
+use sqlx::{Any, AnyConnection, Connection, pool::PoolConnection};
+use sqlx::Row;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::common::money::Rupiah;
+use crate::common::query_builder::SqlQueryBuilder;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::enums::refund_reason::RefundReason;
+use crate::manajemen_pembayaran::model::payment::PaymentMethod;
+use crate::manajemen_pembayaran::model::refund::Refund;
+
+pub struct RefundRepository;
+
+impl RefundRepository {
+    // Menyimpan refund dan perubahan status pembayaran dalam satu transaksi database
+    pub async fn create(mut db: PoolConnection<Any>, refund: &Refund) -> Result<Refund, sqlx::Error> {
+        let mut tx = (*db).begin().await?;
+        Self::insert(&mut tx, refund).await?;
+        tx.commit().await?;
+
+        Ok(refund.clone())
+    }
+
+    // Untuk pemanggil yang sudah membuka transaksi database sendiri, misalnya retur penjualan
+    pub async fn insert(db: &mut AnyConnection, refund: &Refund) -> Result<(), sqlx::Error> {
+        sqlx::query("
+            INSERT INTO refunds (id, payment_id, amount, reason, method, notes, previous_status, resulting_status, refund_date)
+            VALUES ($1, $2, CAST($3 AS DECIMAL(15,2)), $4, $5, $6, $7, $8, $9)
+        ")
+            .bind(&refund.id)
+            .bind(&refund.payment_id)
+            .bind(refund.amount.to_string())
+            .bind(refund.reason.to_string())
+            .bind(refund.method.to_string())
+            .bind(&refund.notes)
+            .bind(refund.previous_status.to_string())
+            .bind(refund.resulting_status.to_string())
+            .bind(refund.refund_date.to_rfc3339())
+            .execute(&mut *db)
+            .await?;
+
+        if refund.resulting_status != refund.previous_status {
+            sqlx::query("UPDATE payments SET status = $1 WHERE id = $2")
+                .bind(refund.resulting_status.to_string())
+                .bind(&refund.payment_id)
+                .execute(&mut *db)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn find_by_payment_id(mut db: PoolConnection<Any>, payment_id: &str) -> Result<Vec<Refund>, sqlx::Error> {
+        Self::find_for_payment(&mut db, payment_id).await
+    }
+
+    // Dipakai di dalam transaksi database yang sudah mengunci baris pembayaran
+    pub async fn find_for_payment(db: &mut AnyConnection, payment_id: &str) -> Result<Vec<Refund>, sqlx::Error> {
+        let rows = sqlx::query("
+            SELECT id, payment_id, CAST(amount AS TEXT) AS amount, reason, method, notes, previous_status, resulting_status, refund_date
+            FROM refunds
+            WHERE payment_id = $1
+            ORDER BY refund_date ASC
+        ")
+            .bind(payment_id)
+            .fetch_all(&mut *db)
+            .await?;
+
+        let mut refunds = Vec::with_capacity(rows.len());
+        for row in rows {
+            refunds.push(Self::parse_row_to_refund(row)?);
+        }
+
+        Ok(refunds)
+    }
+
+    // Total refund per pembayaran dalam satu query; pembayaran tanpa refund tidak muncul di hasil
+    pub async fn totals_for_payments(db: &mut AnyConnection, payment_ids: &[String]) -> Result<HashMap<String, Rupiah>, sqlx::Error> {
+        if payment_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut builder = SqlQueryBuilder::new();
+        builder.and_where_in("payment_id", payment_ids.iter().cloned());
+        let sql = builder.to_filter_sql("SELECT payment_id, CAST(SUM(amount) AS TEXT) AS total FROM refunds") + " GROUP BY payment_id";
+
+        let mut totals = HashMap::new();
+        for row in builder.bind(&sql).fetch_all(&mut *db).await? {
+            totals.insert(row.get("payment_id"), Rupiah::from_row(&row, "total")?);
+        }
+        Ok(totals)
+    }
+
+    fn parse_row_to_refund(row: AnyRow) -> Result<Refund, sqlx::Error> {
+        let reason_str: String = row.get("reason");
+        let method_str: String = row.get("method");
+        let previous_status_str: String = row.get("previous_status");
+        let resulting_status_str: String = row.get("resulting_status");
+        let refund_date_str: String = row.get("refund_date");
+
+        let reason = RefundReason::from_string(&reason_str).ok_or(sqlx::Error::RowNotFound)?;
+        let method = PaymentMethod::from_string(&method_str).ok_or(sqlx::Error::RowNotFound)?;
+        let previous_status = PaymentStatus::from_string(&previous_status_str).ok_or(sqlx::Error::RowNotFound)?;
+        let resulting_status = PaymentStatus::from_string(&resulting_status_str).ok_or(sqlx::Error::RowNotFound)?;
+        let refund_date = DateTime::parse_from_rfc3339(&refund_date_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| {
+                eprintln!("Failed to parse refund_date '{}': {}", refund_date_str, e);
+                sqlx::Error::RowNotFound
+            })?;
+
+        Ok(Refund {
+            id: row.get("id"),
+            payment_id: row.get("payment_id"),
+            amount: Rupiah::from_row(&row, "amount")?,
+            reason,
+            method,
+            notes: row.try_get("notes").ok(),
+            previous_status,
+            resulting_status,
+            refund_date,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::any::{install_default_drivers, AnyPoolOptions};
+    use sqlx::Pool;
+
+    async fn setup() -> Pool<Any> {
+        install_default_drivers();
+        let db = AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        sqlx::query("
+            INSERT INTO payments (id, transaction_id, amount, method, status, payment_date, due_date)
+            VALUES ('PMT-1', 'TRX-1', 100000, 'CASH', 'LUNAS', $1, NULL)
+        ")
+            .bind(Utc::now().to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+
+        db
+    }
+
+    fn sample_refund(id: &str, resulting_status: PaymentStatus) -> Refund {
+        Refund {
+            id: id.to_string(),
+            payment_id: "PMT-1".to_string(),
+            amount: Rupiah::from_sen(2_500_050),
+            reason: RefundReason::Defective,
+            method: PaymentMethod::Cash,
+            notes: Some("2 sak semen bocor".to_string()),
+            previous_status: PaymentStatus::Paid,
+            resulting_status,
+            refund_date: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find_refunds() {
+        let db = setup().await;
+
+        RefundRepository::create(db.acquire().await.unwrap(), &sample_refund("RFD-1", PaymentStatus::Paid))
+            .await
+            .unwrap();
+
+        let refunds = RefundRepository::find_by_payment_id(db.acquire().await.unwrap(), "PMT-1")
+            .await
+            .unwrap();
+
+        assert_eq!(refunds.len(), 1);
+        assert_eq!(refunds[0].amount, Rupiah::from_sen(2_500_050));
+        assert_eq!(refunds[0].reason, RefundReason::Defective);
+        assert_eq!(refunds[0].notes, Some("2 sak semen bocor".to_string()));
+
+        let status: String = sqlx::query("SELECT status FROM payments WHERE id = 'PMT-1'")
+            .fetch_one(&db)
+            .await
+            .unwrap()
+            .get("status");
+        assert_eq!(status, "LUNAS");
+    }
+
+    #[tokio::test]
+    async fn test_create_refund_updates_payment_status() {
+        let db = setup().await;
+
+        RefundRepository::create(db.acquire().await.unwrap(), &sample_refund("RFD-1", PaymentStatus::Refunded))
+            .await
+            .unwrap();
+
+        let status: String = sqlx::query("SELECT status FROM payments WHERE id = 'PMT-1'")
+            .fetch_one(&db)
+            .await
+            .unwrap()
+            .get("status");
+        assert_eq!(status, "DIKEMBALIKAN");
+    }
+
+    #[tokio::test]
+    async fn test_find_refunds_empty() {
+        let db = setup().await;
+
+        let refunds = RefundRepository::find_by_payment_id(db.acquire().await.unwrap(), "PMT-404")
+            .await
+            .unwrap();
+        assert!(refunds.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_totals_for_payments_sums_per_payment() {
+        let db = setup().await;
+        for id in ["RFD-1", "RFD-2"] {
+            RefundRepository::create(db.acquire().await.unwrap(), &sample_refund(id, PaymentStatus::Paid))
+                .await
+                .unwrap();
+        }
+
+        let totals = RefundRepository::totals_for_payments(&mut db.acquire().await.unwrap(), &["PMT-1".to_string(), "PMT-404".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals["PMT-1"], Rupiah::from_sen(5_000_100));
+    }
+}
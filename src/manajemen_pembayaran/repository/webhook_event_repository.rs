@@ -0,0 +1,197 @@
+use sqlx::any::AnyRow;
+use sqlx::{Any, pool::PoolConnection};
+use sqlx::Row;
+use chrono::{DateTime, Utc};
+
+use crate::manajemen_pembayaran::enums::webhook_result::WebhookResult;
+use crate::manajemen_pembayaran::model::webhook_event::WebhookEvent;
+
+pub struct WebhookEventRepository;
+
+impl WebhookEventRepository {
+    // Mengembalikan false bila event dengan (provider, event_id) yang sama sudah tersimpan
+    pub async fn insert(mut db: PoolConnection<Any>, event: &WebhookEvent) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("
+            INSERT INTO webhook_events (id, provider, event_id, payment_id, provider_status, payload, signature, received_at, processed_at, result, result_message)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NULL, NULL, NULL)
+            ON CONFLICT (provider, event_id) DO NOTHING
+        ")
+            .bind(&event.id)
+            .bind(&event.provider)
+            .bind(&event.event_id)
+            .bind(&event.payment_id)
+            .bind(&event.provider_status)
+            .bind(&event.payload)
+            .bind(&event.signature)
+            .bind(event.received_at.to_rfc3339())
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn mark_processed(mut db: PoolConnection<Any>, id: &str, result: &WebhookResult, message: Option<&str>, processed_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        let updated = sqlx::query("
+            UPDATE webhook_events
+            SET processed_at = $1, result = $2, result_message = $3
+            WHERE id = $4
+        ")
+            .bind(processed_at.to_rfc3339())
+            .bind(result.to_string())
+            .bind(message)
+            .bind(id)
+            .execute(&mut *db)
+            .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    pub async fn find_by_id(mut db: PoolConnection<Any>, id: &str) -> Result<WebhookEvent, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM webhook_events WHERE id = $1")
+            .bind(id)
+            .fetch_one(&mut *db)
+            .await?;
+
+        Self::parse_row_to_event(row)
+    }
+
+    pub async fn find_by_event_id(mut db: PoolConnection<Any>, provider: &str, event_id: &str) -> Result<WebhookEvent, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM webhook_events WHERE provider = $1 AND event_id = $2")
+            .bind(provider)
+            .bind(event_id)
+            .fetch_one(&mut *db)
+            .await?;
+
+        Self::parse_row_to_event(row)
+    }
+
+    pub async fn find_all(mut db: PoolConnection<Any>, payment_id: Option<&str>) -> Result<Vec<WebhookEvent>, sqlx::Error> {
+        let rows = match payment_id {
+            Some(payment_id) => {
+                sqlx::query("SELECT * FROM webhook_events WHERE payment_id = $1 ORDER BY received_at DESC")
+                    .bind(payment_id)
+                    .fetch_all(&mut *db)
+                    .await?
+            }
+            None => {
+                sqlx::query("SELECT * FROM webhook_events ORDER BY received_at DESC")
+                    .fetch_all(&mut *db)
+                    .await?
+            }
+        };
+
+        let mut events = Vec::with_capacity(rows.len());
+        for row in rows {
+            events.push(Self::parse_row_to_event(row)?);
+        }
+
+        Ok(events)
+    }
+
+    fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, sqlx::Error> {
+        DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| {
+                eprintln!("Failed to parse webhook timestamp '{}': {}", value, e);
+                sqlx::Error::RowNotFound
+            })
+    }
+
+    fn parse_row_to_event(row: AnyRow) -> Result<WebhookEvent, sqlx::Error> {
+        let received_at: String = row.get("received_at");
+        let processed_at: Option<String> = row.try_get("processed_at").ok();
+        let result: Option<String> = row.try_get("result").ok();
+
+        Ok(WebhookEvent {
+            id: row.get("id"),
+            provider: row.get("provider"),
+            event_id: row.get("event_id"),
+            payment_id: row.get("payment_id"),
+            provider_status: row.get("provider_status"),
+            payload: row.get("payload"),
+            signature: row.get("signature"),
+            received_at: Self::parse_timestamp(&received_at)?,
+            processed_at: processed_at.as_deref().map(Self::parse_timestamp).transpose()?,
+            result: result.as_deref().and_then(WebhookResult::from_string),
+            result_message: row.try_get("result_message").ok(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::any::{install_default_drivers, AnyPoolOptions};
+    use sqlx::Pool;
+
+    async fn setup() -> Pool<Any> {
+        install_default_drivers();
+        let db = AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        db
+    }
+
+    fn event(id: &str, event_id: &str) -> WebhookEvent {
+        WebhookEvent {
+            id: id.to_string(),
+            provider: "simulator".to_string(),
+            event_id: event_id.to_string(),
+            payment_id: "PMT-1".to_string(),
+            provider_status: "settlement".to_string(),
+            payload: "{}".to_string(),
+            signature: "abc".to_string(),
+            received_at: Utc::now(),
+            processed_at: None,
+            result: None,
+            result_message: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insert_deduplicates_by_event_id() {
+        let db = setup().await;
+
+        assert!(WebhookEventRepository::insert(db.acquire().await.unwrap(), &event("WH-1", "EVT-1")).await.unwrap());
+        assert!(!WebhookEventRepository::insert(db.acquire().await.unwrap(), &event("WH-2", "EVT-1")).await.unwrap());
+        assert!(WebhookEventRepository::insert(db.acquire().await.unwrap(), &event("WH-3", "EVT-2")).await.unwrap());
+
+        let stored = WebhookEventRepository::find_by_event_id(db.acquire().await.unwrap(), "simulator", "EVT-1").await.unwrap();
+        assert_eq!(stored.id, "WH-1");
+        assert_eq!(stored.processed_at, None);
+        assert_eq!(stored.result, None);
+
+        let all = WebhookEventRepository::find_all(db.acquire().await.unwrap(), Some("PMT-1")).await.unwrap();
+        assert_eq!(all.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mark_processed() {
+        let db = setup().await;
+        WebhookEventRepository::insert(db.acquire().await.unwrap(), &event("WH-1", "EVT-1")).await.unwrap();
+
+        WebhookEventRepository::mark_processed(db.acquire().await.unwrap(), "WH-1", &WebhookResult::Ignored, Some("Payment not found"), Utc::now())
+            .await
+            .unwrap();
+
+        let stored = WebhookEventRepository::find_by_id(db.acquire().await.unwrap(), "WH-1").await.unwrap();
+        assert_eq!(stored.result, Some(WebhookResult::Ignored));
+        assert_eq!(stored.result_message.as_deref(), Some("Payment not found"));
+        assert!(stored.processed_at.is_some());
+
+        let missing = WebhookEventRepository::mark_processed(db.acquire().await.unwrap(), "WH-404", &WebhookResult::Applied, None, Utc::now()).await;
+        assert!(matches!(missing, Err(sqlx::Error::RowNotFound)));
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{Any, Pool};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::model::aging::{AgingBuckets, AgingReport, CustomerAging};
+use crate::manajemen_pembayaran::model::installment_plan::InstallmentPlan;
+use crate::manajemen_pembayaran::model::payment::Payment;
+use crate::manajemen_pembayaran::model::payment_query::PaymentQuery;
+use crate::manajemen_pembayaran::repository::installment_plan_repository::InstallmentPlanRepository;
+use crate::manajemen_pembayaran::repository::payment_repository::PembayaranRepository;
+use crate::manajemen_pembayaran::service::payment_service::PaymentError;
+use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+
+const UNKNOWN_CUSTOMER: &str = "Tanpa pelanggan";
+
+#[derive(Default)]
+pub struct AgingService;
+
+impl AgingService {
+    pub fn new() -> Self {
+        AgingService {}
+    }
+
+    pub async fn build_report(&self, db: &Pool<Any>, as_of: DateTime<Utc>) -> Result<AgingReport, PaymentError> {
+        let mut customers: HashMap<Option<i32>, CustomerAging> = HashMap::new();
+
+        for status in [PaymentStatus::Installment, PaymentStatus::Overdue] {
+            let conn = db.acquire().await
+                .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+            let payments = PembayaranRepository::find_all(conn, &PaymentQuery::by_status(status)).await
+                .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+            for payment in payments {
+                let conn = db.acquire().await
+                    .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+                let plan = InstallmentPlanRepository::find_by_payment_id(conn, &payment.id).await
+                    .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+                let buckets = self.age_payment(&payment, plan, as_of);
+                if buckets.total.is_zero() {
+                    continue;
+                }
+
+                let (id_pelanggan, nama_pelanggan) = self.find_customer(db, &payment.transaction_id).await?;
+                let entry = customers.entry(id_pelanggan).or_insert_with(|| CustomerAging {
+                    id_pelanggan,
+                    nama_pelanggan,
+                    payment_count: 0,
+                    buckets: AgingBuckets::default(),
+                });
+                entry.payment_count += 1;
+                entry.buckets.merge(&buckets);
+            }
+        }
+
+        Ok(self.summarize(customers.into_values().collect(), as_of))
+    }
+
+    pub fn summarize(&self, mut customers: Vec<CustomerAging>, as_of: DateTime<Utc>) -> AgingReport {
+        customers.sort_by(|a, b| b.buckets.total.cmp(&a.buckets.total).then_with(|| a.nama_pelanggan.cmp(&b.nama_pelanggan)));
+
+        let mut total = AgingBuckets::default();
+        for customer in &customers {
+            total.merge(&customer.buckets);
+        }
+
+        AgingReport { as_of, customers, total }
+    }
+
+    // Umur saldo dihitung per termin bila pembayaran punya rencana cicilan. Sisa saldo di luar
+    // jadwal (atau seluruh saldo tanpa rencana) memakai `due_date`, atau tanggal pembayaran
+    // bila jatuh tempo tidak diisi.
+    pub fn age_payment(&self, payment: &Payment, plan: Option<InstallmentPlan>, as_of: DateTime<Utc>) -> AgingBuckets {
+        let mut buckets = AgingBuckets::default();
+        let outstanding = payment.outstanding();
+        if !outstanding.is_positive() {
+            return buckets;
+        }
+
+        let mut scheduled = Rupiah::ZERO;
+        if let Some(mut plan) = plan {
+            plan.match_installments(payment, as_of);
+            for term in &plan.schedule {
+                let unpaid = term.amount_due - term.amount_paid;
+                if unpaid.is_positive() {
+                    buckets.add((as_of - term.due_date).num_days(), unpaid);
+                    scheduled += unpaid;
+                }
+            }
+        }
+
+        let unscheduled = outstanding - scheduled;
+        if unscheduled.is_positive() {
+            let due_date = payment.due_date.unwrap_or(payment.payment_date);
+            buckets.add((as_of - due_date).num_days(), unscheduled);
+        }
+
+        buckets
+    }
+
+    async fn find_customer(&self, db: &Pool<Any>, transaction_id: &str) -> Result<(Option<i32>, String), PaymentError> {
+        let Ok(transaksi_id) = transaction_id.trim().parse::<i32>() else {
+            return Ok((None, UNKNOWN_CUSTOMER.to_string()));
+        };
+
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        match TransaksiRepository::get_transaksi_by_id(conn, transaksi_id).await {
+            Ok(transaksi) => Ok((Some(transaksi.id_pelanggan), transaksi.nama_pelanggan)),
+            Err(sqlx::Error::RowNotFound) => Ok((None, UNKNOWN_CUSTOMER.to_string())),
+            Err(e) => Err(PaymentError::DatabaseError(e.to_string())),
+        }
+    }
+
+    // Terima tanggal (YYYY-MM-DD, dianggap awal hari UTC) atau timestamp RFC 3339
+    pub fn parse_as_of(&self, as_of: &str) -> Result<DateTime<Utc>, PaymentError> {
+        if let Ok(date) = NaiveDate::parse_from_str(as_of, "%Y-%m-%d") {
+            return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+        }
+
+        DateTime::parse_from_rfc3339(as_of)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|_| PaymentError::InvalidInput(format!("Invalid as_of date: {}", as_of)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use crate::manajemen_pembayaran::enums::plan_frequency::PlanFrequency;
+    use crate::manajemen_pembayaran::model::payment::{Installment, PaymentMethod};
+    use crate::manajemen_pembayaran::service::installment_plan_service::{InstallmentPlanService, NewInstallmentPlan};
+
+    fn as_of() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 5, 1, 0, 0, 0).unwrap()
+    }
+
+    fn installment_payment(due_date: Option<DateTime<Utc>>) -> Payment {
+        Payment {
+            id: "PMT-AGING".to_string(),
+            transaction_id: "1".to_string(),
+            amount: Rupiah::from_rupiah(1_200_000),
+            method: PaymentMethod::Cash,
+            status: PaymentStatus::Installment,
+            payment_date: as_of() - Duration::days(100),
+            installments: Vec::new(),
+            tender_lines: Vec::new(),
+            due_date,
+            late_fee_total: Rupiah::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_age_payment_without_plan_uses_due_date() {
+        let service = AgingService::new();
+        let mut payment = installment_payment(Some(as_of() - Duration::days(45)));
+        payment.installments.push(Installment {
+            id: "INST-1".to_string(),
+            payment_id: payment.id.clone(),
+            amount: Rupiah::from_rupiah(200_000),
+            payment_date: as_of(),
+        });
+
+        let buckets = service.age_payment(&payment, None, as_of());
+        assert_eq!(buckets.days_31_60, Rupiah::from_rupiah(1_000_000));
+        assert_eq!(buckets.total, Rupiah::from_rupiah(1_000_000));
+    }
+
+    #[test]
+    fn test_age_payment_without_due_date_uses_payment_date() {
+        let service = AgingService::new();
+
+        let buckets = service.age_payment(&installment_payment(None), None, as_of());
+        assert_eq!(buckets.days_over_90, Rupiah::from_rupiah(1_200_000));
+    }
+
+    #[test]
+    fn test_age_payment_with_plan_buckets_each_term() {
+        let service = AgingService::new();
+        let mut payment = installment_payment(None);
+        let (plan, _) = InstallmentPlanService::new().build_plan(&payment, NewInstallmentPlan {
+            number_of_terms: 4,
+            frequency: PlanFrequency::Monthly,
+            first_due_date: Utc.with_ymd_and_hms(2025, 2, 15, 0, 0, 0).unwrap(),
+            down_payment: None,
+        }).unwrap();
+        payment.installments.push(Installment {
+            id: "INST-1".to_string(),
+            payment_id: payment.id.clone(),
+            amount: Rupiah::from_rupiah(100_000),
+            payment_date: as_of(),
+        });
+
+        let buckets = service.age_payment(&payment, Some(plan), as_of());
+
+        assert_eq!(buckets.days_61_90, Rupiah::from_rupiah(200_000));
+        assert_eq!(buckets.days_31_60, Rupiah::from_rupiah(300_000));
+        assert_eq!(buckets.days_1_30, Rupiah::from_rupiah(300_000));
+        assert_eq!(buckets.current, Rupiah::from_rupiah(300_000));
+        assert_eq!(buckets.total, Rupiah::from_rupiah(1_100_000));
+    }
+
+    #[test]
+    fn test_summarize_totals_and_orders_customers() {
+        let service = AgingService::new();
+        let customer = |id: i32, name: &str, days: i64, amount: i64| {
+            let mut buckets = AgingBuckets::default();
+            buckets.add(days, Rupiah::from_rupiah(amount));
+            CustomerAging { id_pelanggan: Some(id), nama_pelanggan: name.to_string(), payment_count: 1, buckets }
+        };
+
+        let report = service.summarize(vec![customer(1, "Andi", 10, 50_000), customer(2, "Budi", 100, 75_000)], as_of());
+
+        assert_eq!(report.customers[0].nama_pelanggan, "Budi");
+        assert_eq!(report.total.days_1_30, Rupiah::from_rupiah(50_000));
+        assert_eq!(report.total.days_over_90, Rupiah::from_rupiah(75_000));
+        assert_eq!(report.total.total, Rupiah::from_rupiah(125_000));
+    }
+
+    #[test]
+    fn test_parse_as_of() {
+        let service = AgingService::new();
+
+        assert_eq!(service.parse_as_of("2025-05-01").unwrap(), as_of());
+        assert_eq!(service.parse_as_of("2025-05-01T00:00:00+00:00").unwrap(), as_of());
+        assert!(matches!(service.parse_as_of("01/05/2025"), Err(PaymentError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,361 @@
+use chrono::{DateTime, Utc};
+use rocket::State;
+use sqlx::{Any, AnyConnection, Connection, Pool};
+use uuid::Uuid;
+
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::cash_movement_type::CashMovementType;
+use crate::manajemen_pembayaran::enums::cash_session_status::CashSessionStatus;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::model::cash_drawer::{CashDrawerSession, CashMovement, SessionPayment, ShiftReport};
+use crate::manajemen_pembayaran::model::payment::{Installment, Payment, PaymentMethod};
+use crate::manajemen_pembayaran::model::refund::Refund;
+use crate::manajemen_pembayaran::repository::cash_drawer_repository::{CashDrawerRepository, CashDrawerSessionClosing};
+use crate::manajemen_pembayaran::service::payment_service::PaymentError;
+
+pub struct NewCashMovement {
+    pub movement_type: CashMovementType,
+    pub amount: Rupiah,
+    pub reason: String,
+}
+
+#[derive(Default)]
+pub struct CashDrawerService;
+
+impl CashDrawerService {
+    pub fn new() -> Self {
+        CashDrawerService {}
+    }
+
+    pub async fn open_session(&self, db: &State<Pool<Any>>, cashier: &AuthenticatedUser, opening_float: Rupiah, notes: Option<String>, now: DateTime<Utc>) -> Result<CashDrawerSession, PaymentError> {
+        if opening_float.is_negative() {
+            return Err(PaymentError::InvalidInput("Opening float cannot be negative".to_string()));
+        }
+
+        let session = CashDrawerSession {
+            id: format!("CDS-{}", Uuid::new_v4()),
+            status: CashSessionStatus::Open,
+            opened_by_user_id: cashier.user_id,
+            opened_by: cashier.username.clone(),
+            opening_float,
+            opened_at: now,
+            closed_by: None,
+            closed_at: None,
+            expected_amount: None,
+            counted_amount: None,
+            variance: None,
+            notes,
+            movements: Vec::new(),
+            payments: Vec::new(),
+        };
+
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let opened = CashDrawerRepository::open(conn, &session).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        if !opened {
+            return Err(PaymentError::InvalidTransition(format!("Cashier {} already has an open cash drawer session", cashier.username)));
+        }
+
+        Ok(session)
+    }
+
+    pub async fn get_session(&self, db: &State<Pool<Any>>, id: &str) -> Result<CashDrawerSession, PaymentError> {
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        CashDrawerRepository::find_by_id(conn, id).await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => PaymentError::NotFound(format!("Cash drawer session {} not found", id)),
+                _ => PaymentError::DatabaseError(e.to_string()),
+            })
+    }
+
+    pub async fn get_open_session(&self, db: &State<Pool<Any>>, cashier: &AuthenticatedUser) -> Result<Option<CashDrawerSession>, PaymentError> {
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        CashDrawerRepository::find_open(conn, cashier.user_id).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))
+    }
+
+    // Sesi dikunci agar batas kas keluar dihitung dari isi laci terbaru dan tidak bentrok dengan penutupan
+    pub async fn record_movement(&self, db: &State<Pool<Any>>, session_id: &str, cashier: &AuthenticatedUser, new_movement: NewCashMovement, now: DateTime<Utc>) -> Result<CashDrawerSession, PaymentError> {
+        let mut conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let mut tx = (*conn).begin().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        let session = Self::lock_session(&mut tx, session_id).await?;
+        let movement = self.build_movement(&session, cashier, new_movement, now)?;
+
+        let added = CashDrawerRepository::add_movement(&mut tx, &movement).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        if !added {
+            return Err(PaymentError::InvalidTransition(format!("Cash drawer session {} is already closed", session_id)));
+        }
+
+        tx.commit().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        drop(conn);
+
+        self.get_session(db, session_id).await
+    }
+
+    pub fn build_movement(&self, session: &CashDrawerSession, cashier: &AuthenticatedUser, new_movement: NewCashMovement, now: DateTime<Utc>) -> Result<CashMovement, PaymentError> {
+        if session.status != CashSessionStatus::Open {
+            return Err(PaymentError::InvalidTransition(format!("Cash drawer session {} is already closed", session.id)));
+        }
+
+        if !new_movement.amount.is_positive() {
+            return Err(PaymentError::InvalidInput("Cash movement amount must be greater than 0".to_string()));
+        }
+
+        if new_movement.reason.trim().is_empty() {
+            return Err(PaymentError::InvalidInput("Cash movement reason is required".to_string()));
+        }
+
+        if new_movement.movement_type == CashMovementType::CashOut && new_movement.amount > session.expected_cash() {
+            return Err(PaymentError::InvalidInput(format!(
+                "Cash out {} exceeds cash in drawer {}", new_movement.amount, session.expected_cash()
+            )));
+        }
+
+        Ok(CashMovement {
+            id: format!("CMV-{}", Uuid::new_v4()),
+            session_id: session.id.clone(),
+            movement_type: new_movement.movement_type,
+            amount: new_movement.amount,
+            reason: new_movement.reason.trim().to_string(),
+            created_by: cashier.username.clone(),
+            created_at: now,
+        })
+    }
+
+    // Selisih positif berarti uang di laci lebih banyak dari yang seharusnya
+    pub async fn close_session(&self, db: &State<Pool<Any>>, session_id: &str, cashier: &AuthenticatedUser, counted_amount: Rupiah, notes: Option<String>, now: DateTime<Utc>) -> Result<ShiftReport, PaymentError> {
+        if counted_amount.is_negative() {
+            return Err(PaymentError::InvalidInput("Counted amount cannot be negative".to_string()));
+        }
+
+        let mut conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let mut tx = (*conn).begin().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        // Kas seharusnya dihitung dari sesi yang terkunci, sehingga pembayaran yang sedang dicatat
+        // selesai lebih dulu atau tidak lagi masuk ke sesi ini
+        let session = Self::lock_session(&mut tx, session_id).await?;
+        if session.status != CashSessionStatus::Open {
+            return Err(PaymentError::InvalidTransition(format!("Cash drawer session {} is already closed", session.id)));
+        }
+
+        let expected_amount = session.expected_cash();
+        let closing = CashDrawerSessionClosing {
+            closed_by: &cashier.username,
+            closed_at: now,
+            expected_amount,
+            counted_amount,
+            variance: counted_amount - expected_amount,
+            notes: notes.as_deref(),
+        };
+
+        let closed = CashDrawerRepository::close(&mut tx, session_id, &closing).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        if !closed {
+            return Err(PaymentError::InvalidTransition(format!("Cash drawer session {} is already closed", session_id)));
+        }
+
+        tx.commit().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        drop(conn);
+
+        Ok(self.get_session(db, session_id).await?.shift_report())
+    }
+
+    pub async fn get_shift_report(&self, db: &State<Pool<Any>>, session_id: &str) -> Result<ShiftReport, PaymentError> {
+        Ok(self.get_session(db, session_id).await?.shift_report())
+    }
+
+    // Pembayaran yang langsung lunas dicatat per baris tender; cicilan dicatat saat uangnya diterima
+    pub fn payment_entries(&self, session_id: &str, payment: &Payment, now: DateTime<Utc>) -> Vec<SessionPayment> {
+        if payment.status != PaymentStatus::Paid {
+            return Vec::new();
+        }
+
+        if payment.tender_lines.is_empty() {
+            return vec![self.entry(session_id, &payment.id, &payment.id, payment.method.clone(), payment.amount, now)];
+        }
+
+        payment.tender_lines.iter()
+            .map(|line| self.entry(session_id, &payment.id, &line.id, line.method.clone(), line.amount, now))
+            .collect()
+    }
+
+    // Pencatatan ke laci kas berjalan di dalam transaksi database pemanggil, sehingga pembayaran
+    // dan tautan laci kasnya tersimpan bersama. Tanpa kasir yang login atau tanpa sesi terbuka
+    // milik kasir tersebut, tidak ada yang dicatat.
+    pub async fn record_payment(&self, db: &mut AnyConnection, cashier: Option<&AuthenticatedUser>, payment: &Payment, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        self.save_entries(db, cashier, |session_id| self.payment_entries(session_id, payment, now)).await
+    }
+
+    pub async fn record_installment(&self, db: &mut AnyConnection, cashier: Option<&AuthenticatedUser>, payment: &Payment, installment: &Installment, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        self.save_entries(db, cashier, |session_id| {
+            vec![self.entry(session_id, &payment.id, &installment.id, payment.method.clone(), installment.amount, now)]
+        }).await
+    }
+
+    // Refund mengurangi uang yang diterima pada metode refund tersebut
+    pub async fn record_refund(&self, db: &mut AnyConnection, cashier: Option<&AuthenticatedUser>, refund: &Refund, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        self.save_entries(db, cashier, |session_id| {
+            vec![self.entry(session_id, &refund.payment_id, &refund.id, refund.method.clone(), -refund.amount, now)]
+        }).await
+    }
+
+    fn entry(&self, session_id: &str, payment_id: &str, reference_id: &str, method: PaymentMethod, amount: Rupiah, now: DateTime<Utc>) -> SessionPayment {
+        SessionPayment {
+            id: format!("CSP-{}", Uuid::new_v4()),
+            session_id: session_id.to_string(),
+            payment_id: payment_id.to_string(),
+            reference_id: reference_id.to_string(),
+            method,
+            amount,
+            recorded_at: now,
+        }
+    }
+
+    async fn save_entries(&self, db: &mut AnyConnection, cashier: Option<&AuthenticatedUser>, entries: impl FnOnce(&str) -> Vec<SessionPayment>) -> Result<(), sqlx::Error> {
+        let Some(cashier) = cashier else {
+            return Ok(());
+        };
+        let Some(session_id) = CashDrawerRepository::lock_open_session_id(db, cashier.user_id).await? else {
+            return Ok(());
+        };
+
+        let entries = entries(&session_id);
+        if entries.is_empty() {
+            return Ok(());
+        }
+        CashDrawerRepository::add_session_payments(db, &entries).await?;
+
+        Ok(())
+    }
+
+    async fn lock_session(db: &mut AnyConnection, session_id: &str) -> Result<CashDrawerSession, PaymentError> {
+        CashDrawerRepository::lock_session(db, session_id).await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => PaymentError::NotFound(format!("Cash drawer session {} not found", session_id)),
+                _ => PaymentError::DatabaseError(e.to_string()),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manajemen_pembayaran::model::payment::TenderLine;
+
+    fn cashier() -> AuthenticatedUser {
+        AuthenticatedUser { user_id: 1, username: "kasir".to_string(), is_admin: false }
+    }
+
+    fn open_session() -> CashDrawerSession {
+        CashDrawerSession {
+            id: "CDS-1".to_string(),
+            status: CashSessionStatus::Open,
+            opened_by_user_id: 1,
+            opened_by: "kasir".to_string(),
+            opening_float: Rupiah::from_rupiah(200_000),
+            opened_at: Utc::now(),
+            closed_by: None,
+            closed_at: None,
+            expected_amount: None,
+            counted_amount: None,
+            variance: None,
+            notes: None,
+            movements: Vec::new(),
+            payments: Vec::new(),
+        }
+    }
+
+    fn payment(status: PaymentStatus, tender_lines: Vec<TenderLine>) -> Payment {
+        Payment {
+            id: "PMT-1".to_string(),
+            transaction_id: "1".to_string(),
+            amount: Rupiah::from_rupiah(300_000),
+            method: PaymentMethod::Cash,
+            status,
+            payment_date: Utc::now(),
+            installments: Vec::new(),
+            tender_lines,
+            due_date: None,
+            late_fee_total: Rupiah::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_payment_entries_follow_tender_lines() {
+        let service = CashDrawerService::new();
+        let tender = |id: &str, method: PaymentMethod, amount: i64| TenderLine {
+            id: id.to_string(),
+            payment_id: "PMT-1".to_string(),
+            method,
+            amount: Rupiah::from_rupiah(amount),
+            reference_number: None,
+        };
+
+        let entries = service.payment_entries("CDS-1", &payment(PaymentStatus::Paid, Vec::new()), Utc::now());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].method, PaymentMethod::Cash);
+        assert_eq!(entries[0].amount, Rupiah::from_rupiah(300_000));
+
+        let split = payment(PaymentStatus::Paid, vec![
+            tender("TND-1", PaymentMethod::Cash, 100_000),
+            tender("TND-2", PaymentMethod::CreditCard, 200_000),
+        ]);
+        let entries = service.payment_entries("CDS-1", &split, Utc::now());
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].method, PaymentMethod::CreditCard);
+        assert_eq!(entries[1].reference_id, "TND-2");
+
+        assert!(service.payment_entries("CDS-1", &payment(PaymentStatus::Installment, Vec::new()), Utc::now()).is_empty());
+        assert!(service.payment_entries("CDS-1", &payment(PaymentStatus::Pending, Vec::new()), Utc::now()).is_empty());
+    }
+
+    #[test]
+    fn test_build_movement_validation() {
+        let service = CashDrawerService::new();
+        let session = open_session();
+        let movement = |movement_type: CashMovementType, amount: i64, reason: &str| NewCashMovement {
+            movement_type,
+            amount: Rupiah::from_rupiah(amount),
+            reason: reason.to_string(),
+        };
+
+        let built = service.build_movement(&session, &cashier(), movement(CashMovementType::CashIn, 50_000, " Uang kecil "), Utc::now()).unwrap();
+        assert_eq!(built.reason, "Uang kecil");
+        assert_eq!(built.created_by, "kasir");
+
+        assert!(matches!(
+            service.build_movement(&session, &cashier(), movement(CashMovementType::CashIn, 0, "Kosong"), Utc::now()),
+            Err(PaymentError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            service.build_movement(&session, &cashier(), movement(CashMovementType::CashIn, 10_000, "  "), Utc::now()),
+            Err(PaymentError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            service.build_movement(&session, &cashier(), movement(CashMovementType::CashOut, 250_000, "Setor"), Utc::now()),
+            Err(PaymentError::InvalidInput(_))
+        ));
+
+        let mut closed = open_session();
+        closed.status = CashSessionStatus::Closed;
+        assert!(matches!(
+            service.build_movement(&closed, &cashier(), movement(CashMovementType::CashIn, 10_000, "Modal"), Utc::now()),
+            Err(PaymentError::InvalidTransition(_))
+        ));
+    }
+}
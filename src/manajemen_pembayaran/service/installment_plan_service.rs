@@ -0,0 +1,265 @@
+use rocket::State;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use sqlx::{Any, Pool};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::enums::plan_frequency::PlanFrequency;
+use crate::manajemen_pembayaran::enums::schedule_status::ScheduleStatus;
+use crate::manajemen_pembayaran::model::installment_plan::{InstallmentPlan, ScheduledInstallment};
+use crate::manajemen_pembayaran::model::payment::{Payment, Installment};
+use crate::manajemen_pembayaran::repository::installment_plan_repository::InstallmentPlanRepository;
+use crate::manajemen_pembayaran::service::payment_service::{PaymentService, PaymentError};
+
+pub const MAX_PLAN_TERMS: i32 = 60;
+
+pub struct NewInstallmentPlan {
+    pub number_of_terms: i32,
+    pub frequency: PlanFrequency,
+    pub first_due_date: DateTime<Utc>,
+    pub down_payment: Option<Rupiah>,
+}
+
+#[derive(Default)]
+pub struct InstallmentPlanService;
+
+impl InstallmentPlanService {
+    pub fn new() -> Self {
+        InstallmentPlanService {}
+    }
+
+    pub async fn create_plan(&self, db: &State<Pool<Any>>, payment_id: &str, new_plan: NewInstallmentPlan) -> Result<InstallmentPlan, PaymentError> {
+        let payment = PaymentService::new().get_payment_by_id(db, payment_id).await?;
+
+        if self.find_plan(db, payment_id).await?.is_some() {
+            return Err(PaymentError::InvalidTransition(format!("Payment {} already has an installment plan", payment_id)));
+        }
+
+        let (plan, down_payment) = self.build_plan(&payment, new_plan)?;
+
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        InstallmentPlanRepository::create(conn, &plan, down_payment.as_ref()).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        self.get_plan(db, payment_id).await
+    }
+
+    // Rencana cicilan beserta status tiap termin berdasarkan cicilan yang sudah tercatat
+    pub async fn get_plan(&self, db: &State<Pool<Any>>, payment_id: &str) -> Result<InstallmentPlan, PaymentError> {
+        let payment = PaymentService::new().get_payment_by_id(db, payment_id).await?;
+
+        let mut plan = self.find_plan(db, payment_id).await?
+            .ok_or_else(|| PaymentError::NotFound(format!("Payment {} has no installment plan", payment_id)))?;
+
+        plan.match_installments(&payment, Utc::now());
+        Ok(plan)
+    }
+
+    async fn find_plan(&self, db: &State<Pool<Any>>, payment_id: &str) -> Result<Option<InstallmentPlan>, PaymentError> {
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        InstallmentPlanRepository::find_by_payment_id(conn, payment_id).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))
+    }
+
+    // Validasi permintaan rencana dan bangkitkan jadwal termin; sisa sen pembagian dibebankan ke termin awal
+    pub fn build_plan(&self, payment: &Payment, new_plan: NewInstallmentPlan) -> Result<(InstallmentPlan, Option<Installment>), PaymentError> {
+        if !matches!(payment.status, PaymentStatus::Installment | PaymentStatus::Overdue) {
+            return Err(PaymentError::InvalidInput("Installment plans can only be created for payments in INSTALLMENT status".to_string()));
+        }
+
+        if new_plan.number_of_terms < 1 || new_plan.number_of_terms > MAX_PLAN_TERMS {
+            return Err(PaymentError::InvalidInput(format!("Number of terms must be between 1 and {}", MAX_PLAN_TERMS)));
+        }
+
+        let down_payment = new_plan.down_payment.unwrap_or(Rupiah::ZERO);
+        if down_payment.is_negative() {
+            return Err(PaymentError::InvalidInput("Down payment cannot be negative".to_string()));
+        }
+
+        let principal = payment.amount - payment.installments_total() - down_payment;
+        if !principal.is_positive() {
+            return Err(PaymentError::InvalidInput(format!(
+                "Nothing left to schedule: outstanding amount is {}", payment.amount - payment.installments_total()
+            )));
+        }
+
+        // Termin bernilai 0 akan langsung dianggap lunas, jadi sisa tagihan harus cukup untuk setiap termin
+        let amounts = principal.allocate(new_plan.number_of_terms as usize);
+        if amounts.iter().any(|amount| !amount.is_positive()) {
+            return Err(PaymentError::InvalidInput(format!(
+                "Outstanding amount {} is too small to split into {} terms", principal, new_plan.number_of_terms
+            )));
+        }
+
+        let plan_id = format!("PLAN-{}", Uuid::new_v4());
+        let mut schedule = Vec::with_capacity(new_plan.number_of_terms as usize);
+        for (index, amount_due) in amounts.into_iter().enumerate() {
+            let due_date = new_plan.frequency.due_date(new_plan.first_due_date, index as u32)
+                .ok_or_else(|| PaymentError::InvalidInput("Due date out of range".to_string()))?;
+
+            schedule.push(ScheduledInstallment {
+                id: format!("SCH-{}", Uuid::new_v4()),
+                plan_id: plan_id.clone(),
+                term_number: index as i32 + 1,
+                due_date,
+                amount_due,
+                amount_paid: Rupiah::ZERO,
+                status: ScheduleStatus::Upcoming,
+            });
+        }
+
+        let down_payment_installment = if down_payment.is_positive() {
+            Some(Installment {
+                id: format!("INST-{}", Uuid::new_v4()),
+                payment_id: payment.id.clone(),
+                amount: down_payment,
+                payment_date: Utc::now(),
+            })
+        } else {
+            None
+        };
+
+        let plan = InstallmentPlan {
+            id: plan_id,
+            payment_id: payment.id.clone(),
+            number_of_terms: new_plan.number_of_terms,
+            frequency: new_plan.frequency,
+            first_due_date: new_plan.first_due_date,
+            down_payment,
+            principal,
+            created_at: Utc::now(),
+            schedule,
+        };
+
+        Ok((plan, down_payment_installment))
+    }
+
+    pub fn parse_plan_frequency(&self, frequency_str: &str) -> Result<PlanFrequency, PaymentError> {
+        PlanFrequency::from_string(frequency_str)
+            .ok_or_else(|| PaymentError::InvalidInput(format!("Invalid plan frequency: {}", frequency_str)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::manajemen_pembayaran::model::payment::PaymentMethod;
+
+    fn installment_payment() -> Payment {
+        Payment {
+            id: "PMT-PLAN".to_string(),
+            transaction_id: "1".to_string(),
+            amount: Rupiah::from_rupiah(1_000_000),
+            method: PaymentMethod::Cash,
+            status: PaymentStatus::Installment,
+            payment_date: Utc::now(),
+            installments: Vec::new(),
+            tender_lines: Vec::new(),
+            due_date: None,
+            late_fee_total: Rupiah::ZERO,
+        }
+    }
+
+    fn new_plan(number_of_terms: i32, down_payment: Option<Rupiah>) -> NewInstallmentPlan {
+        NewInstallmentPlan {
+            number_of_terms,
+            frequency: PlanFrequency::Monthly,
+            first_due_date: Utc.with_ymd_and_hms(2025, 1, 31, 0, 0, 0).unwrap(),
+            down_payment,
+        }
+    }
+
+    #[test]
+    fn test_build_plan_generates_schedule() {
+        let service = InstallmentPlanService::new();
+
+        let (plan, down_payment) = service
+            .build_plan(&installment_payment(), new_plan(3, Some(Rupiah::from_rupiah(100_000))))
+            .unwrap();
+
+        assert_eq!(plan.principal, Rupiah::from_rupiah(900_000));
+        assert_eq!(plan.schedule.len(), 3);
+        assert!(plan.schedule.iter().all(|term| term.amount_due == Rupiah::from_rupiah(300_000)));
+        assert_eq!(plan.schedule[1].due_date, Utc.with_ymd_and_hms(2025, 2, 28, 0, 0, 0).unwrap());
+        assert_eq!(plan.schedule[2].term_number, 3);
+        assert_eq!(down_payment.unwrap().amount, Rupiah::from_rupiah(100_000));
+    }
+
+    #[test]
+    fn test_build_plan_spreads_remainder_over_first_terms() {
+        let service = InstallmentPlanService::new();
+        let mut payment = installment_payment();
+        payment.amount = Rupiah::from_sen(100_000_001);
+
+        let (plan, down_payment) = service.build_plan(&payment, new_plan(3, None)).unwrap();
+
+        assert!(down_payment.is_none());
+        let amounts: Vec<Rupiah> = plan.schedule.iter().map(|term| term.amount_due).collect();
+        assert_eq!(amounts, [Rupiah::from_sen(33_333_334), Rupiah::from_sen(33_333_334), Rupiah::from_sen(33_333_333)]);
+        assert_eq!(amounts.iter().sum::<Rupiah>(), payment.amount);
+    }
+
+    #[test]
+    fn test_build_plan_excludes_installments_already_paid() {
+        let service = InstallmentPlanService::new();
+        let mut payment = installment_payment();
+        payment.installments.push(Installment {
+            id: "INST-1".to_string(),
+            payment_id: payment.id.clone(),
+            amount: Rupiah::from_rupiah(400_000),
+            payment_date: Utc::now(),
+        });
+
+        let (plan, _) = service.build_plan(&payment, new_plan(2, None)).unwrap();
+        assert_eq!(plan.principal, Rupiah::from_rupiah(600_000));
+
+        let result = service.build_plan(&payment, new_plan(2, Some(Rupiah::from_rupiah(600_000))));
+        assert!(matches!(result, Err(PaymentError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_build_plan_rejects_invalid_requests() {
+        let service = InstallmentPlanService::new();
+
+        for terms in [0, MAX_PLAN_TERMS + 1] {
+            assert!(matches!(
+                service.build_plan(&installment_payment(), new_plan(terms, None)),
+                Err(PaymentError::InvalidInput(_))
+            ));
+        }
+
+        assert!(matches!(
+            service.build_plan(&installment_payment(), new_plan(3, Some(-Rupiah::from_rupiah(1)))),
+            Err(PaymentError::InvalidInput(_))
+        ));
+
+        let mut small = installment_payment();
+        small.amount = Rupiah::from_sen(2);
+        match service.build_plan(&small, new_plan(3, None)) {
+            Err(PaymentError::InvalidInput(msg)) => assert!(msg.contains("too small to split into 3 terms")),
+            _ => panic!("Expected PaymentError::InvalidInput"),
+        }
+        assert_eq!(service.build_plan(&small, new_plan(2, None)).unwrap().0.schedule.len(), 2);
+
+        let mut paid = installment_payment();
+        paid.status = PaymentStatus::Paid;
+        assert!(matches!(
+            service.build_plan(&paid, new_plan(3, None)),
+            Err(PaymentError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_plan_frequency() {
+        let service = InstallmentPlanService::new();
+
+        assert_eq!(service.parse_plan_frequency("MINGGUAN").unwrap(), PlanFrequency::Weekly);
+        assert!(matches!(service.parse_plan_frequency("HARIAN"), Err(PaymentError::InvalidInput(_))));
+    }
+}
@@ -1 +1,9 @@
-pub mod payment_service;
\ No newline at end of file
+pub mod payment_service;
+pub mod refund_service;
+pub mod installment_plan_service;
+pub mod payment_notifier;
+pub mod overdue_service;
+pub mod overdue_job;
+pub mod aging_service;
+pub mod webhook_service;
+pub mod cash_drawer_service;
\ No newline at end of file
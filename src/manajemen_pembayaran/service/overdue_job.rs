@@ -0,0 +1,36 @@
+use std::sync::Arc;
+use chrono::Utc;
+use rocket::fairing::AdHoc;
+
+use crate::common::job::interval_job;
+use crate::manajemen_pembayaran::service::overdue_service::{OverdueService, OverduePolicy};
+use crate::manajemen_pembayaran::service::payment_notifier::{PaymentNotifier, LogPaymentNotifier, FilePaymentNotifier};
+
+const DEFAULT_INTERVAL_SECS: u64 = 3600;
+
+// PAYMENT_REMINDER_FILE diisi untuk menulis pengingat ke file, selain itu pengingat hanya dicetak ke log
+pub fn notifier_from_env() -> Arc<dyn PaymentNotifier> {
+    match std::env::var("PAYMENT_REMINDER_FILE") {
+        Ok(path) if !path.trim().is_empty() => Arc::new(FilePaymentNotifier::new(path)),
+        _ => Arc::new(LogPaymentNotifier),
+    }
+}
+
+pub fn stage() -> AdHoc {
+    let service = Arc::new(OverdueService::new(OverduePolicy::from_env()));
+    let notifier = notifier_from_env();
+
+    interval_job("Overdue Installment Job", "OVERDUE_JOB_INTERVAL_SECS", DEFAULT_INTERVAL_SECS, move |db| {
+        let service = service.clone();
+        let notifier = notifier.clone();
+        async move {
+            match service.run(&db, notifier.as_ref(), Utc::now()).await {
+                Ok(report) => println!(
+                    "[INFO] Overdue job checked {} payments: {} marked overdue, {} cleared, {} late fees, {} reminders, {} failed",
+                    report.checked, report.marked_overdue, report.cleared, report.late_fees_assessed, report.reminders_sent, report.failed
+                ),
+                Err(err) => eprintln!("[ERROR] Overdue job failed: {:?}", err),
+            }
+        }
+    })
+}
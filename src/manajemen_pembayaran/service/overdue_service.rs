@@ -0,0 +1,520 @@
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+use sqlx::{Any, Pool};
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::enums::schedule_status::ScheduleStatus;
+use crate::manajemen_pembayaran::model::installment_plan::InstallmentPlan;
+use crate::manajemen_pembayaran::model::overdue::{LateFee, OverdueAccount, OverdueRunReport, ReminderEvent};
+use crate::manajemen_pembayaran::model::payment::Payment;
+use crate::manajemen_pembayaran::model::payment_query::PaymentQuery;
+use crate::manajemen_pembayaran::patterns::factory::PaymentStateFactory;
+use crate::manajemen_pembayaran::patterns::late_fee::{LateFeeStrategy, FlatLateFee, PercentageLateFee, NoLateFee};
+use crate::manajemen_pembayaran::repository::installment_plan_repository::InstallmentPlanRepository;
+use crate::manajemen_pembayaran::repository::late_fee_repository::LateFeeRepository;
+use crate::manajemen_pembayaran::repository::payment_repository::PembayaranRepository;
+use crate::manajemen_pembayaran::service::payment_notifier::PaymentNotifier;
+use crate::manajemen_pembayaran::service::payment_service::PaymentError;
+
+#[derive(Clone)]
+pub struct OverduePolicy {
+    pub grace_days: i64,
+    pub period_days: i64,
+    pub max_periods: Option<i32>,
+    pub late_fee: Arc<dyn LateFeeStrategy>,
+}
+
+impl Default for OverduePolicy {
+    fn default() -> Self {
+        OverduePolicy {
+            grace_days: 0,
+            period_days: 30,
+            max_periods: None,
+            late_fee: Arc::new(NoLateFee),
+        }
+    }
+}
+
+impl OverduePolicy {
+    // LATE_FEE_TYPE: FLAT (pakai LATE_FEE_AMOUNT), PERSEN (pakai LATE_FEE_BASIS_POINTS) atau NONE
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let default = OverduePolicy::default();
+
+        let late_fee: Arc<dyn LateFeeStrategy> = match lookup("LATE_FEE_TYPE").map(|t| t.to_uppercase()).as_deref() {
+            Some("FLAT") => match lookup("LATE_FEE_AMOUNT").and_then(|v| v.parse::<Rupiah>().ok()) {
+                Some(amount) => Arc::new(FlatLateFee { amount }),
+                None => default.late_fee.clone(),
+            },
+            Some("PERSEN") => match lookup("LATE_FEE_BASIS_POINTS").and_then(|v| v.parse::<i64>().ok()) {
+                Some(basis_points) => Arc::new(PercentageLateFee { basis_points }),
+                None => default.late_fee.clone(),
+            },
+            _ => default.late_fee.clone(),
+        };
+
+        OverduePolicy {
+            grace_days: lookup("LATE_FEE_GRACE_DAYS").and_then(|v| v.parse().ok()).filter(|d: &i64| *d >= 0).unwrap_or(default.grace_days),
+            period_days: lookup("LATE_FEE_PERIOD_DAYS").and_then(|v| v.parse().ok()).filter(|d: &i64| *d > 0).unwrap_or(default.period_days),
+            max_periods: lookup("LATE_FEE_MAX_PERIODS").and_then(|v| v.parse().ok()).filter(|p: &i32| *p > 0),
+            late_fee,
+        }
+    }
+}
+
+pub struct OverdueService {
+    policy: OverduePolicy,
+}
+
+impl OverdueService {
+    pub fn new(policy: OverduePolicy) -> Self {
+        OverdueService { policy }
+    }
+
+    // Jalankan satu putaran pengecekan: tandai pembayaran terlambat, kenakan denda per periode,
+    // kembalikan yang sudah lunas tunggakannya ke CICILAN, lalu kirim pengingat.
+    // Kegagalan pada satu pembayaran dicatat di log dan dihitung, pembayaran lain tetap diproses.
+    pub async fn run(&self, db: &Pool<Any>, notifier: &dyn PaymentNotifier, now: DateTime<Utc>) -> Result<OverdueRunReport, PaymentError> {
+        let mut report = OverdueRunReport::default();
+
+        for (payment, plan, existing_fees) in self.load_candidates(db).await? {
+            report.checked += 1;
+
+            let account = self.evaluate(&payment, plan, existing_fees, now);
+            let Some(mut account) = account else {
+                if payment.status == PaymentStatus::Overdue {
+                    match self.record(db, &payment, Some(PaymentStatus::Installment), &[]).await {
+                        Ok(true) => report.cleared += 1,
+                        Ok(false) => {}
+                        Err(err) => {
+                            eprintln!("[ERROR] Overdue check failed for payment {}: {:?}", payment.id, err);
+                            report.failed += 1;
+                        }
+                    }
+                }
+                continue;
+            };
+
+            let newly_overdue = payment.status != PaymentStatus::Overdue;
+            let new_fees = self.pending_late_fees(&account, now);
+            if !newly_overdue && new_fees.is_empty() {
+                continue;
+            }
+
+            let new_status = if newly_overdue { Some(PaymentStatus::Overdue) } else { None };
+            match self.record(db, &payment, new_status, &new_fees).await {
+                Ok(true) => {}
+                // Status berubah sejak dibaca; pembayaran dievaluasi ulang pada putaran berikutnya
+                Ok(false) => continue,
+                Err(err) => {
+                    eprintln!("[ERROR] Overdue check failed for payment {}: {:?}", payment.id, err);
+                    report.failed += 1;
+                    continue;
+                }
+            }
+
+            let new_late_fee: Rupiah = new_fees.iter().map(|f| f.amount).sum();
+            account.status = PaymentStatus::Overdue;
+            account.late_fee_total += new_late_fee;
+            account.late_fees.extend(new_fees.iter().cloned());
+
+            if newly_overdue {
+                report.marked_overdue += 1;
+            }
+            report.late_fees_assessed += new_fees.len();
+
+            notifier.notify_overdue(&ReminderEvent {
+                account,
+                newly_overdue,
+                new_late_fee,
+                raised_at: now,
+            }).await;
+            report.reminders_sent += 1;
+        }
+
+        Ok(report)
+    }
+
+    pub async fn get_overdue_accounts(&self, db: &Pool<Any>, now: DateTime<Utc>) -> Result<Vec<OverdueAccount>, PaymentError> {
+        let mut accounts: Vec<OverdueAccount> = self.load_candidates(db).await?
+            .into_iter()
+            .filter_map(|(payment, plan, existing_fees)| self.evaluate(&payment, plan, existing_fees, now))
+            .collect();
+
+        accounts.sort_by(|a, b| b.days_overdue.cmp(&a.days_overdue).then_with(|| a.payment_id.cmp(&b.payment_id)));
+        Ok(accounts)
+    }
+
+    async fn load_candidates(&self, db: &Pool<Any>) -> Result<Vec<(Payment, Option<InstallmentPlan>, Vec<LateFee>)>, PaymentError> {
+        let mut candidates = Vec::new();
+
+        for status in [PaymentStatus::Installment, PaymentStatus::Overdue] {
+            let conn = db.acquire().await
+                .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+            let payments = PembayaranRepository::find_all(conn, &PaymentQuery::by_status(status)).await
+                .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+            for payment in payments {
+                let conn = db.acquire().await
+                    .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+                let plan = InstallmentPlanRepository::find_by_payment_id(conn, &payment.id).await
+                    .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+                let conn = db.acquire().await
+                    .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+                let late_fees = LateFeeRepository::find_by_payment_id(conn, &payment.id).await
+                    .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+                candidates.push((payment, plan, late_fees));
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    async fn record(&self, db: &Pool<Any>, payment: &Payment, new_status: Option<PaymentStatus>, late_fees: &[LateFee]) -> Result<bool, PaymentError> {
+        if let Some(status) = &new_status
+            && !PaymentStateFactory::create(&payment.status).can_transition_to(status) {
+            return Err(PaymentError::InvalidTransition(format!(
+                "Cannot change payment status from {} to {}", payment.status, status
+            )));
+        }
+
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        LateFeeRepository::record_assessment(conn, &payment.id, &payment.status, new_status.as_ref(), late_fees).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))
+    }
+
+    // Tentukan apakah pembayaran menunggak. Dengan rencana cicilan, tunggakan adalah termin
+    // yang lewat jatuh tempo (ditambah masa tenggang) dan belum lunas; tanpa rencana,
+    // seluruh sisa tagihan setelah `due_date` pembayaran.
+    pub fn evaluate(&self, payment: &Payment, plan: Option<InstallmentPlan>, late_fees: Vec<LateFee>, now: DateTime<Utc>) -> Option<OverdueAccount> {
+        let outstanding = payment.outstanding();
+        if !outstanding.is_positive() {
+            return None;
+        }
+
+        let grace = Duration::days(self.policy.grace_days);
+        let (overdue_since, overdue_amount) = match plan {
+            Some(mut plan) => {
+                plan.match_installments(payment, now);
+                let past_due: Vec<_> = plan.schedule.iter()
+                    .filter(|term| term.status != ScheduleStatus::Paid && term.due_date + grace < now)
+                    .collect();
+
+                let overdue_since = past_due.first()?.due_date;
+                let overdue_amount: Rupiah = past_due.iter().map(|term| term.amount_due - term.amount_paid).sum();
+                (overdue_since, overdue_amount)
+            }
+            None => {
+                let due_date = payment.due_date?;
+                if due_date + grace >= now {
+                    return None;
+                }
+                // Denda dihitung dari pokok yang belum dibayar, bukan dari denda sebelumnya
+                (due_date, (payment.amount - payment.installments_total()).max(Rupiah::ZERO))
+            }
+        };
+
+        Some(OverdueAccount {
+            payment_id: payment.id.clone(),
+            transaction_id: payment.transaction_id.clone(),
+            status: payment.status.clone(),
+            overdue_since,
+            days_overdue: (now - overdue_since).num_days(),
+            overdue_amount,
+            outstanding,
+            late_fee_total: late_fees.iter().map(|f| f.amount).sum(),
+            late_fees,
+        })
+    }
+
+    // Periode denda dihitung sejak masa tenggang berakhir; periode pertama langsung dikenakan
+    pub fn periods_elapsed(&self, overdue_since: DateTime<Utc>, now: DateTime<Utc>) -> i32 {
+        let overdue_start = overdue_since + Duration::days(self.policy.grace_days);
+        if now <= overdue_start {
+            return 0;
+        }
+
+        let periods = ((now - overdue_start).num_days() / self.policy.period_days + 1) as i32;
+        match self.policy.max_periods {
+            Some(max) => periods.min(max),
+            None => periods,
+        }
+    }
+
+    pub fn pending_late_fees(&self, account: &OverdueAccount, now: DateTime<Utc>) -> Vec<LateFee> {
+        let last_period = account.late_fees.iter().map(|f| f.period_number).max().unwrap_or(0);
+        let amount = self.policy.late_fee.fee_for_period(account.overdue_amount);
+        if !amount.is_positive() {
+            return Vec::new();
+        }
+
+        (last_period + 1..=self.periods_elapsed(account.overdue_since, now))
+            .map(|period_number| LateFee {
+                id: format!("LF-{}", Uuid::new_v4()),
+                payment_id: account.payment_id.clone(),
+                period_number,
+                amount,
+                assessed_at: now,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use chrono::TimeZone;
+    use sqlx::any::{install_default_drivers, AnyPoolOptions};
+    use crate::manajemen_pembayaran::enums::plan_frequency::PlanFrequency;
+    use crate::manajemen_pembayaran::model::payment::{Installment, PaymentMethod};
+    use crate::manajemen_pembayaran::service::installment_plan_service::{InstallmentPlanService, NewInstallmentPlan};
+    use crate::manajemen_pembayaran::service::payment_notifier::MockPaymentNotifier;
+
+    fn flat_policy() -> OverduePolicy {
+        OverduePolicy {
+            grace_days: 3,
+            period_days: 30,
+            max_periods: None,
+            late_fee: Arc::new(FlatLateFee { amount: Rupiah::from_rupiah(25_000) }),
+        }
+    }
+
+    fn due(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).unwrap()
+    }
+
+    fn installment_payment(due_date: Option<DateTime<Utc>>) -> Payment {
+        Payment {
+            id: "PMT-LATE".to_string(),
+            transaction_id: "1".to_string(),
+            amount: Rupiah::from_rupiah(900_000),
+            method: PaymentMethod::Cash,
+            status: PaymentStatus::Installment,
+            payment_date: due(1),
+            installments: Vec::new(),
+            tender_lines: Vec::new(),
+            due_date,
+            late_fee_total: Rupiah::ZERO,
+        }
+    }
+
+    fn pay(payment: &mut Payment, amount: i64) {
+        payment.installments.push(Installment {
+            id: format!("INST-{}", payment.installments.len()),
+            payment_id: payment.id.clone(),
+            amount: Rupiah::from_rupiah(amount),
+            payment_date: due(1),
+        });
+    }
+
+    fn monthly_plan(payment: &Payment) -> InstallmentPlan {
+        let (plan, _) = InstallmentPlanService::new().build_plan(payment, NewInstallmentPlan {
+            number_of_terms: 3,
+            frequency: PlanFrequency::Monthly,
+            first_due_date: due(10),
+            down_payment: None,
+        }).unwrap();
+        plan
+    }
+
+    #[test]
+    fn test_policy_from_lookup() {
+        let env: HashMap<&str, &str> = [
+            ("LATE_FEE_TYPE", "persen"),
+            ("LATE_FEE_BASIS_POINTS", "150"),
+            ("LATE_FEE_GRACE_DAYS", "5"),
+            ("LATE_FEE_PERIOD_DAYS", "7"),
+            ("LATE_FEE_MAX_PERIODS", "4"),
+        ].into_iter().collect();
+
+        let policy = OverduePolicy::from_lookup(|key| env.get(key).map(|v| v.to_string()));
+        assert_eq!(policy.late_fee.get_name(), "percentage");
+        assert_eq!(policy.grace_days, 5);
+        assert_eq!(policy.period_days, 7);
+        assert_eq!(policy.max_periods, Some(4));
+
+        let policy = OverduePolicy::from_lookup(|key| match key {
+            "LATE_FEE_TYPE" => Some("FLAT".to_string()),
+            "LATE_FEE_AMOUNT" => Some("15000".to_string()),
+            "LATE_FEE_PERIOD_DAYS" => Some("0".to_string()),
+            _ => None,
+        });
+        assert_eq!(policy.late_fee.fee_for_period(Rupiah::ZERO), Rupiah::from_rupiah(15_000));
+        assert_eq!(policy.period_days, 30);
+
+        let policy = OverduePolicy::from_lookup(|_| None);
+        assert_eq!(policy.late_fee.get_name(), "none");
+    }
+
+    #[test]
+    fn test_payment_without_plan_overdue_after_grace() {
+        let service = OverdueService::new(flat_policy());
+        let payment = installment_payment(Some(due(10)));
+
+        assert!(service.evaluate(&payment, None, Vec::new(), due(12)).is_none());
+
+        let account = service.evaluate(&payment, None, Vec::new(), due(20)).unwrap();
+        assert_eq!(account.overdue_since, due(10));
+        assert_eq!(account.days_overdue, 10);
+        assert_eq!(account.overdue_amount, Rupiah::from_rupiah(900_000));
+
+        assert!(service.evaluate(&installment_payment(None), None, Vec::new(), due(20)).is_none());
+    }
+
+    #[test]
+    fn test_plan_only_counts_past_due_terms() {
+        let service = OverdueService::new(flat_policy());
+        let mut payment = installment_payment(None);
+        let plan = monthly_plan(&payment);
+        pay(&mut payment, 100_000);
+
+        let now = Utc.with_ymd_and_hms(2025, 2, 20, 0, 0, 0).unwrap();
+        let account = service.evaluate(&payment, Some(plan.clone()), Vec::new(), now).unwrap();
+
+        assert_eq!(account.overdue_since, due(10));
+        assert_eq!(account.overdue_amount, Rupiah::from_rupiah(500_000));
+        assert_eq!(account.outstanding, Rupiah::from_rupiah(800_000));
+
+        pay(&mut payment, 500_000);
+        assert!(service.evaluate(&payment, Some(plan), Vec::new(), now).is_none());
+    }
+
+    #[test]
+    fn test_late_fees_charged_once_per_period() {
+        let service = OverdueService::new(flat_policy());
+        let payment = installment_payment(Some(due(10)));
+
+        let now = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        assert_eq!(service.periods_elapsed(due(10), now), 2);
+
+        let account = service.evaluate(&payment, None, Vec::new(), now).unwrap();
+        let fees = service.pending_late_fees(&account, now);
+        assert_eq!(fees.iter().map(|f| f.period_number).collect::<Vec<_>>(), [1, 2]);
+        assert!(fees.iter().all(|f| f.amount == Rupiah::from_rupiah(25_000)));
+
+        let account = service.evaluate(&payment, None, fees, now).unwrap();
+        assert_eq!(account.late_fee_total, Rupiah::from_rupiah(50_000));
+        assert!(service.pending_late_fees(&account, now).is_empty());
+    }
+
+    #[test]
+    fn test_late_fee_periods_capped() {
+        let mut policy = flat_policy();
+        policy.max_periods = Some(2);
+        let service = OverdueService::new(policy);
+
+        let now = Utc.with_ymd_and_hms(2025, 12, 1, 0, 0, 0).unwrap();
+        assert_eq!(service.periods_elapsed(due(10), now), 2);
+        assert_eq!(service.periods_elapsed(due(10), due(13)), 0);
+    }
+
+    async fn setup_db() -> Pool<Any> {
+        install_default_drivers();
+        let db = AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        db
+    }
+
+    async fn insert_payment(db: &Pool<Any>, id: &str, status: &str, due_date: DateTime<Utc>) {
+        sqlx::query("
+            INSERT INTO payments (id, transaction_id, amount, method, status, payment_date, due_date)
+            VALUES ($1, '1', 900000, 'CASH', $2, $3, $4)
+        ")
+            .bind(id)
+            .bind(status)
+            .bind(due(1).to_rfc3339())
+            .bind(due_date.to_rfc3339())
+            .execute(db)
+            .await
+            .unwrap();
+    }
+
+    async fn payment_status(db: &Pool<Any>, id: &str) -> String {
+        use sqlx::Row;
+        sqlx::query("SELECT status FROM payments WHERE id = $1")
+            .bind(id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+            .get("status")
+    }
+
+    #[tokio::test]
+    async fn test_run_marks_overdue_and_notifies_once() {
+        let db = setup_db().await;
+        insert_payment(&db, "PMT-LATE", "CICILAN", due(10)).await;
+        insert_payment(&db, "PMT-CURRENT", "CICILAN", Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap()).await;
+        insert_payment(&db, "PMT-PAID", "LUNAS", due(10)).await;
+
+        let service = OverdueService::new(flat_policy());
+        let now = Utc.with_ymd_and_hms(2025, 1, 20, 0, 0, 0).unwrap();
+
+        let mut notifier = MockPaymentNotifier::new();
+        notifier.expect_notify_overdue()
+            .withf(|event| event.account.payment_id == "PMT-LATE" && event.newly_overdue && event.new_late_fee == Rupiah::from_rupiah(25_000))
+            .times(1)
+            .return_const(());
+
+        let report = service.run(&db, &notifier, now).await.unwrap();
+        assert_eq!(report, OverdueRunReport {
+            checked: 2,
+            marked_overdue: 1,
+            cleared: 0,
+            late_fees_assessed: 1,
+            reminders_sent: 1,
+            failed: 0,
+        });
+        assert_eq!(payment_status(&db, "PMT-LATE").await, "TERLAMBAT");
+        assert_eq!(payment_status(&db, "PMT-CURRENT").await, "CICILAN");
+
+        let mut silent = MockPaymentNotifier::new();
+        silent.expect_notify_overdue().times(0);
+        let report = service.run(&db, &silent, now).await.unwrap();
+        assert_eq!(report.reminders_sent, 0);
+
+        let mut next_period = MockPaymentNotifier::new();
+        next_period.expect_notify_overdue()
+            .withf(|event| !event.newly_overdue && event.account.late_fee_total == Rupiah::from_rupiah(50_000))
+            .times(1)
+            .return_const(());
+        let later = Utc.with_ymd_and_hms(2025, 2, 20, 0, 0, 0).unwrap();
+        service.run(&db, &next_period, later).await.unwrap();
+
+        let accounts = service.get_overdue_accounts(&db, later).await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].late_fees.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_clears_overdue_once_caught_up() {
+        let db = setup_db().await;
+        insert_payment(&db, "PMT-LATE", "TERLAMBAT", Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap()).await;
+
+        let service = OverdueService::new(flat_policy());
+        let mut notifier = MockPaymentNotifier::new();
+        notifier.expect_notify_overdue().times(0);
+
+        let report = service.run(&db, &notifier, due(20)).await.unwrap();
+        assert_eq!(report.cleared, 1);
+        assert_eq!(payment_status(&db, "PMT-LATE").await, "CICILAN");
+    }
+}
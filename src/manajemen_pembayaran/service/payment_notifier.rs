@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+use async_trait::async_trait;
+use mockall::automock;
+use tokio::io::AsyncWriteExt;
+
+use crate::manajemen_pembayaran::model::overdue::ReminderEvent;
+
+#[automock]
+#[async_trait]
+pub trait PaymentNotifier: Send + Sync {
+    async fn notify_overdue(&self, event: &ReminderEvent);
+}
+
+pub struct LogPaymentNotifier;
+
+#[async_trait]
+impl PaymentNotifier for LogPaymentNotifier {
+    async fn notify_overdue(&self, event: &ReminderEvent) {
+        println!(
+            "[REMINDER] Payment {} overdue {} days: overdue {} of outstanding {}, late fees {}",
+            event.account.payment_id,
+            event.account.days_overdue,
+            event.account.overdue_amount,
+            event.account.outstanding,
+            event.account.late_fee_total,
+        );
+    }
+}
+
+// Menulis setiap event sebagai satu baris JSON ke file
+pub struct FilePaymentNotifier {
+    path: PathBuf,
+}
+
+impl FilePaymentNotifier {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl PaymentNotifier for FilePaymentNotifier {
+    async fn notify_overdue(&self, event: &ReminderEvent) {
+        let line = match serde_json::to_string(event) {
+            Ok(json) => json + "\n",
+            Err(err) => {
+                eprintln!("[ERROR] Failed to serialize reminder for payment {}: {}", event.account.payment_id, err);
+                return;
+            }
+        };
+
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await;
+
+        match file {
+            Ok(mut file) => {
+                if let Err(err) = file.write_all(line.as_bytes()).await {
+                    eprintln!("[ERROR] Failed to write reminder to {}: {}", self.path.display(), err);
+                }
+            }
+            Err(err) => {
+                eprintln!("[ERROR] Failed to open reminder file {}: {}", self.path.display(), err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::common::money::Rupiah;
+    use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+    use crate::manajemen_pembayaran::model::overdue::OverdueAccount;
+
+    fn reminder(payment_id: &str) -> ReminderEvent {
+        ReminderEvent {
+            account: OverdueAccount {
+                payment_id: payment_id.to_string(),
+                transaction_id: "1".to_string(),
+                status: PaymentStatus::Overdue,
+                overdue_since: Utc::now(),
+                days_overdue: 3,
+                overdue_amount: Rupiah::from_rupiah(300_000),
+                outstanding: Rupiah::from_rupiah(900_000),
+                late_fee_total: Rupiah::from_rupiah(25_000),
+                late_fees: Vec::new(),
+            },
+            newly_overdue: true,
+            new_late_fee: Rupiah::from_rupiah(25_000),
+            raised_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_notifier_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("reminders-{}.jsonl", uuid::Uuid::new_v4()));
+        let notifier = FilePaymentNotifier::new(&path);
+
+        notifier.notify_overdue(&reminder("PMT-1")).await;
+        notifier.notify_overdue(&reminder("PMT-2")).await;
+
+        let contents = tokio::fs::read_to_string(&path).await.unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let second: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second["account"]["payment_id"], "PMT-2");
+        assert_eq!(second["new_late_fee"], "25000.00");
+
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_log_notifier_does_not_panic() {
+        LogPaymentNotifier.notify_overdue(&reminder("PMT-1")).await;
+    }
+}
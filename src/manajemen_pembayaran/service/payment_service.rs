@@ -1,13 +1,24 @@
-use std::collections::HashMap;
+use chrono::{DateTime, NaiveDate, Utc};
 use rocket::State;
-use chrono::{Utc};
 use uuid::Uuid;
 
-use crate::manajemen_pembayaran::model::payment::{Payment, PaymentMethod, Installment};
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::model::payment::{Payment, PaymentMethod, PaymentMethodSummary, TransaksiPaymentSummary};
+use crate::manajemen_pembayaran::model::payment_query::{PaymentPage, PaymentQuery, PaymentQueryParams, PaymentSortField, SortDirection};
 use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::patterns::factory::PaymentStateFactory;
 use crate::manajemen_pembayaran::repository::payment_repository::PembayaranRepository;
-use sqlx::{Any, Pool};
+use crate::manajemen_pembayaran::repository::refund_repository::RefundRepository;
+use crate::manajemen_pembayaran::service::cash_drawer_service::CashDrawerService;
+use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+use sqlx::{Any, AnyConnection, Connection, Pool};
 
+pub const DEFAULT_PAGE_LIMIT: i64 = 50;
+pub const MAX_PAGE_LIMIT: i64 = 200;
+
+#[derive(Default)]
 pub struct PaymentService;
 
 #[derive(Debug)]
@@ -15,6 +26,8 @@ pub enum PaymentError {
     DatabaseError(String),
     NotFound(String),
     InvalidInput(String),
+    InvalidTransition(String),
+    Unauthorized(String),
 }
 
 impl PaymentService {
@@ -22,12 +35,29 @@ impl PaymentService {
         PaymentService {}
     }
     
-    pub async fn create_payment(&self, db: &State<Pool<Any>>, payment: Payment) -> Result<Payment, PaymentError> {
-        let conn = db.acquire().await
+    // Pembayaran dan tautan laci kas kasir yang menerimanya disimpan dalam satu transaksi database
+    pub async fn create_payment(&self, db: &State<Pool<Any>>, cashier: Option<&AuthenticatedUser>, payment: Payment) -> Result<Payment, PaymentError> {
+        if !matches!(payment.status, PaymentStatus::Paid | PaymentStatus::Installment | PaymentStatus::Pending) {
+            return Err(PaymentError::InvalidInput(format!("A payment cannot be created with status {}", payment.status)));
+        }
+
+        payment.validate_tender_lines().map_err(PaymentError::InvalidInput)?;
+
+        let mut conn = db.acquire().await
             .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
-        
-        PembayaranRepository::create(conn, &payment).await
-            .map_err(|e| PaymentError::DatabaseError(e.to_string()))
+        let mut tx = (*conn).begin().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        self.validate_transaksi_link(&mut tx, &payment).await?;
+        let created = PembayaranRepository::insert(&mut tx, &payment).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        CashDrawerService::new().record_payment(&mut tx, cashier, &created, Utc::now()).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        Ok(created)
     }
 
     pub async fn get_payment_by_id(&self, db: &State<Pool<Any>>, id: &str) -> Result<Payment, PaymentError> {
@@ -41,14 +71,204 @@ impl PaymentService {
             })
     }
 
-    pub async fn get_all_payments(&self, db: &State<Pool<Any>>, filters: Option<HashMap<String, String>>) -> Result<Vec<Payment>, PaymentError> {
+    // Pastikan pembayaran menunjuk ke transaksi yang ada, belum dibatalkan,
+    // dan jumlahnya tidak melebihi sisa tagihan transaksi tersebut. Baris transaksi dikunci
+    // sampai pembayaran tersimpan, sehingga dua pembayaran bersamaan tidak sama-sama lolos.
+    async fn validate_transaksi_link(&self, db: &mut AnyConnection, payment: &Payment) -> Result<(), PaymentError> {
+        if !payment.amount.is_positive() {
+            return Err(PaymentError::InvalidInput("Payment amount must be greater than 0".to_string()));
+        }
+
+        let transaksi_id = self.parse_transaksi_id(&payment.transaction_id)?;
+        let transaksi = TransaksiRepository::kunci_transaksi(db, transaksi_id).await
+            .map_err(|e| Self::transaksi_error(transaksi_id, e))?;
+
+        if transaksi.status == StatusTransaksi::Dibatalkan {
+            return Err(PaymentError::InvalidInput(format!("Cannot create a payment for cancelled transaksi {}", transaksi_id)));
+        }
+
+        let existing_payments: Vec<Payment> = PembayaranRepository::find_matching(db, &PaymentQuery::by_transaction_id(&transaksi_id.to_string())).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .filter(|p| matches!(p.status, PaymentStatus::Paid | PaymentStatus::Installment | PaymentStatus::Overdue | PaymentStatus::Pending))
+            .collect();
+        let refunded = Self::refunded_total(db, &existing_payments).await?;
+        let committed: Rupiah = existing_payments.iter().map(|p| p.amount).sum();
+        let outstanding = transaksi.total_harga - (committed - refunded);
+
+        if payment.amount > outstanding {
+            return Err(PaymentError::InvalidInput(format!(
+                "Payment amount {} exceeds outstanding amount {} for transaksi {}", payment.amount, outstanding, transaksi_id
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_transaksi_payment_summary(&self, db: &State<Pool<Any>>, transaksi_id: i32) -> Result<TransaksiPaymentSummary, PaymentError> {
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let transaksi = TransaksiRepository::get_transaksi_by_id(conn, transaksi_id).await
+            .map_err(|e| Self::transaksi_error(transaksi_id, e))?;
+
+        let mut conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let payments = PembayaranRepository::find_matching(&mut conn, &PaymentQuery::by_transaction_id(&transaksi_id.to_string())).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let refunded = Self::refunded_total(&mut conn, &payments).await?;
+
+        let paid: Rupiah = payments.iter().map(|p| p.amount_paid()).sum();
+        let late_fees: Rupiah = payments.iter().map(|p| p.late_fee_total).sum();
+
+        Ok(TransaksiPaymentSummary {
+            transaksi_id,
+            total_harga: transaksi.total_harga,
+            paid,
+            refunded,
+            late_fees,
+            // Uang yang sudah dikembalikan tidak lagi dihitung sebagai terbayar
+            outstanding: transaksi.total_harga + late_fees - (paid - refunded),
+            payments,
+        })
+    }
+
+    // Total refund untuk sekumpulan pembayaran, dibaca dalam satu query
+    async fn refunded_total(db: &mut AnyConnection, payments: &[Payment]) -> Result<Rupiah, PaymentError> {
+        let payment_ids: Vec<String> = payments.iter().map(|p| p.id.clone()).collect();
+        let totals = RefundRepository::totals_for_payments(db, &payment_ids).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        Ok(totals.into_values().sum())
+    }
+
+    fn transaksi_error(transaksi_id: i32, e: sqlx::Error) -> PaymentError {
+        match e {
+            sqlx::Error::RowNotFound => PaymentError::NotFound(format!("Transaksi with id {} not found", transaksi_id)),
+            _ => PaymentError::DatabaseError(e.to_string())
+        }
+    }
+
+    pub fn parse_transaksi_id(&self, transaction_id: &str) -> Result<i32, PaymentError> {
+        transaction_id.trim().parse::<i32>()
+            .map_err(|_| PaymentError::InvalidInput(format!("Invalid transaksi id: {}", transaction_id)))
+    }
+
+    pub async fn get_totals_by_method(&self, db: &State<Pool<Any>>) -> Result<Vec<PaymentMethodSummary>, PaymentError> {
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        PembayaranRepository::total_by_method(conn).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn get_all_payments(&self, db: &State<Pool<Any>>, query: &PaymentQuery) -> Result<Vec<Payment>, PaymentError> {
         let conn = db.acquire().await
             .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
         
-        PembayaranRepository::find_all(conn, filters).await
+        PembayaranRepository::find_all(conn, query).await
             .map_err(|e| PaymentError::DatabaseError(e.to_string()))
     }
 
+    pub async fn search_payments(&self, db: &State<Pool<Any>>, query: &PaymentQuery) -> Result<PaymentPage, PaymentError> {
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let total_count = PembayaranRepository::count(conn, query).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        let payments = self.get_all_payments(db, query).await?;
+
+        Ok(PaymentPage {
+            data: payments,
+            total_count,
+        })
+    }
+
+    pub fn parse_payment_query(&self, params: PaymentQueryParams) -> Result<PaymentQuery, PaymentError> {
+        let invalid = |name: &str, value: &str| PaymentError::InvalidInput(format!("Invalid {}: {}", name, value));
+
+        let status = params.status
+            .map(|s| PaymentStatus::from_string(&s).ok_or_else(|| invalid("status", &s)))
+            .transpose()?;
+        let method = params.method
+            .map(|m| PaymentMethod::from_string(&m).ok_or_else(|| invalid("method", &m)))
+            .transpose()?;
+        let amount = |name: &str, value: Option<String>| value
+            .map(|v| v.parse::<Rupiah>().map_err(|_| invalid(name, &v)))
+            .transpose();
+        let min_amount = amount("min_amount", params.min_amount)?;
+        let max_amount = amount("max_amount", params.max_amount)?;
+        let date = |name: &str, value: Option<String>, end_of_day: bool| value
+            .map(|v| Self::parse_date_bound(&v, end_of_day).ok_or_else(|| invalid(name, &v)))
+            .transpose();
+        let paid_from = date("paid_from", params.paid_from, false)?;
+        let paid_to = date("paid_to", params.paid_to, true)?;
+        let due_from = date("due_from", params.due_from, false)?;
+        let due_to = date("due_to", params.due_to, true)?;
+        let sort = params.sort
+            .map(|s| PaymentSortField::from_string(&s).ok_or_else(|| invalid("sort", &s)))
+            .transpose()?
+            .unwrap_or(PaymentSortField::PaymentDate);
+        let direction = params.order
+            .map(|o| SortDirection::from_string(&o).ok_or_else(|| invalid("order", &o)))
+            .transpose()?
+            .unwrap_or(SortDirection::Desc);
+
+        // Tanpa limit maupun offset semua pembayaran dikembalikan seperti sebelumnya;
+        // halaman default hanya dipakai bila klien meminta offset saja
+        let limit = match (params.limit, params.offset) {
+            (None, None) => None,
+            (limit, _) => Some(limit.unwrap_or(DEFAULT_PAGE_LIMIT)),
+        };
+        if let Some(limit) = limit && !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+            return Err(PaymentError::InvalidInput(format!("limit must be between 1 and {}", MAX_PAGE_LIMIT)));
+        }
+        let offset = params.offset.unwrap_or(0);
+        if offset < 0 {
+            return Err(PaymentError::InvalidInput("offset must not be negative".to_string()));
+        }
+
+        if matches!((min_amount, max_amount), (Some(min), Some(max)) if min > max) {
+            return Err(PaymentError::InvalidInput("min_amount must not exceed max_amount".to_string()));
+        }
+        if matches!((paid_from, paid_to), (Some(from), Some(to)) if from > to)
+            || matches!((due_from, due_to), (Some(from), Some(to)) if from > to) {
+            return Err(PaymentError::InvalidInput("Date range start must not be after its end".to_string()));
+        }
+
+        Ok(PaymentQuery {
+            status,
+            method,
+            transaction_id: params.transaction_id,
+            paid_from,
+            paid_to,
+            min_amount,
+            max_amount,
+            due_from,
+            due_to,
+            sort,
+            direction,
+            limit,
+            offset,
+        })
+    }
+
+    // Tanggal tanpa jam (`2025-05-01`) mencakup satu hari penuh: awal hari untuk batas bawah,
+    // akhir hari untuk batas atas
+    fn parse_date_bound(value: &str, end_of_day: bool) -> Option<DateTime<Utc>> {
+        if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            let time = if end_of_day {
+                date.and_hms_nano_opt(23, 59, 59, 999_999_999)
+            } else {
+                date.and_hms_opt(0, 0, 0)
+            };
+            return time.map(|t| t.and_utc());
+        }
+
+        DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+
     pub async fn update_payment(&self, db: &State<Pool<Any>>, payment: Payment) -> Result<Payment, PaymentError> {
         let conn = db.acquire().await
             .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
@@ -59,15 +279,58 @@ impl PaymentService {
                 _ => PaymentError::DatabaseError(e.to_string())
             })
     }
-    pub async fn update_payment_status(&self, db: &State<Pool<Any>>, payment_id: String, new_status: PaymentStatus, additional_amount: Option<f64>) -> Result<Payment, PaymentError> {
-        let conn = db.acquire().await
+    // Perubahan status dibaca dan ditulis dalam satu transaksi database dengan baris pembayaran
+    // terkunci. Tambahan nominal dicatat lewat jalur cicilan agar sisa tagihan, status LUNAS
+    // dan laci kas ikut diperiksa. DIKEMBALIKAN hanya lewat refund agar selalu ada catatan refund.
+    pub async fn update_payment_status(&self, db: &State<Pool<Any>>, cashier: Option<&AuthenticatedUser>, payment_id: String, new_status: PaymentStatus, additional_amount: Option<Rupiah>) -> Result<Payment, PaymentError> {
+        if let Some(amount) = additional_amount {
+            if new_status != PaymentStatus::Installment {
+                return Err(PaymentError::InvalidInput(format!(
+                    "additional_amount can only be used with status {}; {} is set automatically once the balance is settled",
+                    PaymentStatus::Installment, PaymentStatus::Paid
+                )));
+            }
+            return self.add_installment(db, cashier, &payment_id, amount).await;
+        }
+        if new_status == PaymentStatus::Refunded {
+            return Err(PaymentError::InvalidTransition(format!(
+                "Status {} can only be set by creating a refund for payment {}", PaymentStatus::Refunded, payment_id
+            )));
+        }
+
+        let mut conn = db.acquire().await
             .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
-        
-        PembayaranRepository::update_payment_status(conn, payment_id.clone(), new_status, additional_amount).await
+        let mut tx = (*conn).begin().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        let payment = PembayaranRepository::lock_payment(&mut tx, &payment_id).await
             .map_err(|e| match e {
                 sqlx::Error::RowNotFound => PaymentError::NotFound(format!("Payment with id {} not found", payment_id)),
                 _ => PaymentError::DatabaseError(e.to_string())
-            })
+            })?;
+        self.validate_status_transition(&payment.status, &new_status)?;
+        // Cicilan menjadi LUNAS otomatis saat tagihan tertutup; selama masih ada sisa, LUNAS manual ditolak
+        if new_status == PaymentStatus::Paid
+            && matches!(payment.status, PaymentStatus::Installment | PaymentStatus::Overdue)
+            && payment.outstanding().is_positive() {
+            return Err(PaymentError::InvalidTransition(format!(
+                "Payment {} still has {} outstanding; record the remaining installment instead", payment_id, payment.outstanding()
+            )));
+        }
+
+        let updated = PembayaranRepository::set_status_if(&mut tx, &payment_id, &payment.status, &new_status).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        if !updated {
+            return Err(PaymentError::InvalidTransition(format!(
+                "Payment {} is no longer in status {}", payment_id, payment.status
+            )));
+        }
+
+        tx.commit().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        PembayaranRepository::load_payment_with_installments(&mut conn, &payment_id).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))
     }
     
     pub async fn delete_payment(&self, db: &State<Pool<Any>>, payment_id: &str) -> Result<(), PaymentError> {
@@ -78,42 +341,78 @@ impl PaymentService {
             .map_err(|e| PaymentError::DatabaseError(e.to_string()))
     }
     
-    pub async fn add_installment(&self, db: &State<Pool<Any>>, payment_id: &str, amount: f64) -> Result<Payment, PaymentError> {
-        let payment: Payment = self.get_payment_by_id(db, payment_id).await?;
-        
-        if payment.status != PaymentStatus::Installment {
+    // Cicilan dicatat dalam satu transaksi database dengan baris pembayaran terkunci, sehingga
+    // dua cicilan bersamaan tidak bisa melebihi sisa tagihan dan status LUNAS serta tautan laci kas ikut tersimpan.
+    pub async fn add_installment(&self, db: &State<Pool<Any>>, cashier: Option<&AuthenticatedUser>, payment_id: &str, amount: Rupiah) -> Result<Payment, PaymentError> {
+        if !amount.is_positive() {
+            return Err(PaymentError::InvalidInput("Installment amount must be greater than 0".to_string()));
+        }
+
+        let mut conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let mut tx = (*conn).begin().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        let mut payment = PembayaranRepository::lock_payment(&mut tx, payment_id).await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => PaymentError::NotFound(format!("Payment with id {} not found", payment_id)),
+                _ => PaymentError::DatabaseError(e.to_string())
+            })?;
+
+        if !matches!(payment.status, PaymentStatus::Installment | PaymentStatus::Overdue) {
             return Err(PaymentError::InvalidInput("Cannot add installment to a payment that is not in INSTALLMENT status".to_string()));
         }
-        
-        let installment = Installment {
-            id: format!("INST-{}", Uuid::new_v4()),
-            payment_id: payment_id.to_string(),
-            amount,
-            payment_date: Utc::now(),
-        };
-        
-        let conn = db.acquire().await
+
+        // Denda keterlambatan ikut ditagih, jadi cicilan boleh menutup pokok sekaligus dendanya
+        let remaining = payment.outstanding();
+        if amount > remaining {
+            return Err(PaymentError::InvalidInput(format!(
+                "Installment amount {} exceeds remaining balance {}", amount, remaining
+            )));
+        }
+
+        let previous_status = payment.status.clone();
+        PaymentStateFactory::create(&previous_status)
+            .process_payment(&mut payment, amount)
+            .map_err(PaymentError::InvalidInput)?;
+
+        let installment = payment.installments.last()
+            .cloned()
+            .ok_or_else(|| PaymentError::DatabaseError("Installment was not recorded".to_string()))?;
+        PembayaranRepository::add_installment(&mut tx, &installment).await
             .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
-        
-        let mut updated_payment = payment.clone();
-        updated_payment.installments.push(installment);
-        
-        PembayaranRepository::update(conn, &updated_payment).await
+
+        if payment.status != previous_status {
+            self.validate_status_transition(&previous_status, &payment.status)?;
+            PembayaranRepository::set_status_if(&mut tx, payment_id, &previous_status, &payment.status).await
+                .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        }
+        CashDrawerService::new().record_installment(&mut tx, cashier, &payment, &installment, Utc::now()).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        tx.commit().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        PembayaranRepository::load_payment_with_installments(&mut conn, payment_id).await
             .map_err(|e| PaymentError::DatabaseError(e.to_string()))
     }
     
+    pub fn validate_status_transition(&self, current: &PaymentStatus, next: &PaymentStatus) -> Result<(), PaymentError> {
+        let state = PaymentStateFactory::create(current);
+        if state.can_transition_to(next) {
+            Ok(())
+        } else {
+            Err(PaymentError::InvalidTransition(format!("Cannot change payment status from {} to {}", current, next)))
+        }
+    }
+
     pub fn generate_payment_id(&self) -> String {
         format!("PMT-{}", Uuid::new_v4())
     }
     
     pub fn parse_payment_method(&self, method_str: &str) -> Result<PaymentMethod, PaymentError> {
-        match method_str.to_uppercase().as_str() {
-            "CASH" => Ok(PaymentMethod::Cash),
-            "CREDIT_CARD" => Ok(PaymentMethod::CreditCard),
-            "BANK_TRANSFER" => Ok(PaymentMethod::BankTransfer),
-            "E_WALLET" => Ok(PaymentMethod::EWallet),
-            _ => Err(PaymentError::InvalidInput(format!("Invalid payment method: {}", method_str))),
-        }
+        PaymentMethod::from_string(method_str)
+            .ok_or_else(|| PaymentError::InvalidInput(format!("Invalid payment method: {}", method_str)))
     }
     
     pub fn parse_payment_status(&self, status_str: &str) -> Result<PaymentStatus, PaymentError> {
@@ -127,6 +426,7 @@ mod tests {
     use super::*;
     use chrono::{Utc};
     use uuid::Uuid;
+    use crate::manajemen_pembayaran::model::payment::Installment;
     use std::collections::HashMap;
 
     #[test]
@@ -165,6 +465,20 @@ mod tests {
         assert_eq!(service.parse_payment_method("e_wallet").unwrap(), PaymentMethod::EWallet);
     }
 
+    #[test]
+    fn test_parse_payment_query_limit_only_when_paging() {
+        let service = PaymentService::new();
+
+        let query = service.parse_payment_query(PaymentQueryParams::default()).unwrap();
+        assert_eq!((query.limit, query.offset), (None, 0));
+
+        let query = service.parse_payment_query(PaymentQueryParams { offset: Some(20), ..Default::default() }).unwrap();
+        assert_eq!((query.limit, query.offset), (Some(DEFAULT_PAGE_LIMIT), 20));
+
+        let query = service.parse_payment_query(PaymentQueryParams { limit: Some(10), ..Default::default() }).unwrap();
+        assert_eq!((query.limit, query.offset), (Some(10), 0));
+    }
+
     #[test]
     fn test_parse_payment_method_invalid() {
         let service = PaymentService::new();
@@ -187,6 +501,41 @@ mod tests {
 
         assert_eq!(service.parse_payment_status("LUNAS").unwrap(), PaymentStatus::Paid);
         assert_eq!(service.parse_payment_status("CICILAN").unwrap(), PaymentStatus::Installment);
+        assert_eq!(service.parse_payment_status("MENUNGGU").unwrap(), PaymentStatus::Pending);
+        assert_eq!(service.parse_payment_status("GAGAL").unwrap(), PaymentStatus::Failed);
+        assert_eq!(service.parse_payment_status("DIBATALKAN").unwrap(), PaymentStatus::Void);
+        assert_eq!(service.parse_payment_status("DIKEMBALIKAN").unwrap(), PaymentStatus::Refunded);
+    }
+
+    #[test]
+    fn test_parse_transaksi_id() {
+        let service = PaymentService::new();
+
+        assert_eq!(service.parse_transaksi_id("42").unwrap(), 42);
+        assert_eq!(service.parse_transaksi_id(" 7 ").unwrap(), 7);
+        assert!(matches!(service.parse_transaksi_id("TRX-42"), Err(PaymentError::InvalidInput(_))));
+        assert!(matches!(service.parse_transaksi_id(""), Err(PaymentError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_validate_status_transition() {
+        let service = PaymentService::new();
+
+        assert!(service.validate_status_transition(&PaymentStatus::Pending, &PaymentStatus::Paid).is_ok());
+        assert!(service.validate_status_transition(&PaymentStatus::Installment, &PaymentStatus::Installment).is_ok());
+        assert!(service.validate_status_transition(&PaymentStatus::Paid, &PaymentStatus::Refunded).is_ok());
+
+        let result = service.validate_status_transition(&PaymentStatus::Paid, &PaymentStatus::Installment);
+        match result {
+            Err(PaymentError::InvalidTransition(msg)) => {
+                assert!(msg.contains("LUNAS"));
+                assert!(msg.contains("CICILAN"));
+            },
+            _ => panic!("Expected PaymentError::InvalidTransition"),
+        }
+
+        assert!(service.validate_status_transition(&PaymentStatus::Void, &PaymentStatus::Paid).is_err());
+        assert!(service.validate_status_transition(&PaymentStatus::Refunded, &PaymentStatus::Paid).is_err());
     }
 
     #[test]
@@ -234,12 +583,14 @@ mod tests {
         let payment = Payment {
             id: "payment-123".to_string(),
             transaction_id: "txn-456".to_string(),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
 
         assert_eq!(payment.status, PaymentStatus::Installment);
@@ -254,7 +605,7 @@ mod tests {
     #[test]
     fn test_installment_creation_in_service() {
         let payment_id = "payment-123";
-        let amount = 500.0;
+        let amount = Rupiah::from_rupiah(500);
         let installment_id = format!("INST-{}", Uuid::new_v4());
 
         let installment = Installment {
@@ -288,7 +639,7 @@ mod tests {
         let payment = Payment {
             id: "PMT-123".to_string(),
             transaction_id: "TXN-456".to_string(),
-            amount: 1500.0,
+            amount: Rupiah::from_rupiah(1500),
             method: PaymentMethod::BankTransfer,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
@@ -296,24 +647,26 @@ mod tests {
                 Installment {
                     id: "INST-1".to_string(),
                     payment_id: "PMT-123".to_string(),
-                    amount: 750.0,
+                    amount: Rupiah::from_rupiah(750),
                     payment_date: Utc::now(),
                 },
                 Installment {
                     id: "INST-2".to_string(),
                     payment_id: "PMT-123".to_string(),
-                    amount: 750.0,
+                    amount: Rupiah::from_rupiah(750),
                     payment_date: Utc::now(),
                 },
             ],
+            tender_lines: Vec::new(),
             due_date: Some(Utc::now()),
+            late_fee_total: Rupiah::ZERO,
         };
 
         assert_eq!(payment.installments.len(), 2);
-        assert_eq!(payment.amount, 1500.0);
+        assert_eq!(payment.amount, Rupiah::from_rupiah(1500));
         
-        let total_installments: f64 = payment.installments.iter().map(|i| i.amount).sum();
-        assert_eq!(total_installments, 1500.0);
+        let total_installments: Rupiah = payment.installments.iter().map(|i| i.amount).sum();
+        assert_eq!(total_installments, Rupiah::from_rupiah(1500));
     }
 
     #[test]
@@ -321,12 +674,14 @@ mod tests {
         let original_payment = Payment {
             id: "PMT-789".to_string(),
             transaction_id: "TXN-101112".to_string(),
-            amount: 2000.0,
+            amount: Rupiah::from_rupiah(2000),
             method: PaymentMethod::CreditCard,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
 
         let cloned_payment = original_payment.clone();
@@ -355,12 +710,14 @@ mod tests {
         let _payment = Payment {
             id: "PMT-TEST-001".to_string(),
             transaction_id: "TXN-TEST-001".to_string(),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         let db_error = sqlx::Error::PoolClosed;
         let payment_error = PaymentError::DatabaseError(db_error.to_string());
@@ -431,12 +788,14 @@ mod tests {
         let payment = Payment {
             id: "PMT-UPDATE-001".to_string(),
             transaction_id: "TXN-UPDATE-001".to_string(),
-            amount: 1500.0,
+            amount: Rupiah::from_rupiah(1500),
             method: PaymentMethod::CreditCard,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         
         let row_not_found_error = sqlx::Error::RowNotFound;
@@ -526,17 +885,19 @@ mod tests {
         let _service = PaymentService::new();
         
         let payment_id = "PMT-INSTALLMENT-001";
-        let amount = 500.0;
+        let amount = Rupiah::from_rupiah(500);
         
         let valid_payment = Payment {
             id: payment_id.to_string(),
             transaction_id: "TXN-INSTALLMENT-001".to_string(),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::BankTransfer,
             status: PaymentStatus::Installment,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         
         if valid_payment.status != PaymentStatus::Installment {
@@ -548,12 +909,14 @@ mod tests {
         let invalid_payment = Payment {
             id: payment_id.to_string(),
             transaction_id: "TXN-INSTALLMENT-002".to_string(),
-            amount: 1000.0,
+            amount: Rupiah::from_rupiah(1000),
             method: PaymentMethod::Cash,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: None,
+            late_fee_total: Rupiah::ZERO,
         };
         
         if invalid_payment.status != PaymentStatus::Installment {
@@ -620,16 +983,18 @@ mod tests {
         let payment = Payment {
             id: "PMT-PARAM-001".to_string(),
             transaction_id: "TXN-PARAM-001".to_string(),
-            amount: 750.0,
+            amount: Rupiah::from_rupiah(750),
             method: PaymentMethod::EWallet,
             status: PaymentStatus::Paid,
             payment_date: Utc::now(),
             installments: Vec::new(),
+            tender_lines: Vec::new(),
             due_date: Some(Utc::now()),
+            late_fee_total: Rupiah::ZERO,
         };
         
         assert_eq!(payment.id, "PMT-PARAM-001");
-        assert_eq!(payment.amount, 750.0);
+        assert_eq!(payment.amount, Rupiah::from_rupiah(750));
         assert_eq!(payment.method, PaymentMethod::EWallet);
         let payment_id = "PMT-SEARCH-001";
         assert_eq!(payment_id.len(), 14);
@@ -0,0 +1,266 @@
+use rocket::State;
+use chrono::Utc;
+use uuid::Uuid;
+use sqlx::{Any, Connection, Pool};
+
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::enums::refund_reason::RefundReason;
+use crate::manajemen_pembayaran::model::payment::{Payment, PaymentMethod};
+use crate::manajemen_pembayaran::model::refund::Refund;
+use crate::manajemen_pembayaran::patterns::factory::PaymentStateFactory;
+use crate::manajemen_pembayaran::repository::payment_repository::PembayaranRepository;
+use crate::manajemen_pembayaran::repository::refund_repository::RefundRepository;
+use crate::manajemen_pembayaran::service::cash_drawer_service::CashDrawerService;
+use crate::manajemen_pembayaran::service::payment_service::PaymentError;
+
+pub struct NewRefund {
+    pub amount: Rupiah,
+    pub reason: RefundReason,
+    pub method: Option<PaymentMethod>,
+    pub notes: Option<String>,
+}
+
+#[derive(Default)]
+pub struct RefundService;
+
+impl RefundService {
+    pub fn new() -> Self {
+        RefundService {}
+    }
+
+    pub async fn create_refund(&self, db: &State<Pool<Any>>, cashier: Option<&AuthenticatedUser>, payment_id: &str, new_refund: NewRefund) -> Result<Refund, PaymentError> {
+        self.create_locked(db, cashier, payment_id, None, |_| new_refund).await
+            .map(|(refund, _)| refund)
+    }
+
+    // Mengembalikan seluruh sisa yang bisa direfund
+    pub async fn refund_remaining(&self, db: &State<Pool<Any>>, cashier: Option<&AuthenticatedUser>, payment_id: &str, reason: RefundReason, notes: Option<String>) -> Result<Refund, PaymentError> {
+        self.create_locked(db, cashier, payment_id, None, |refundable| NewRefund {
+            amount: refundable,
+            reason,
+            method: None,
+            notes,
+        }).await
+            .map(|(refund, _)| refund)
+    }
+
+    // Refund dari event payment gateway; tanpa jumlah berarti seluruh sisa yang bisa direfund.
+    // `reference` disimpan sebagai catatan refund dan menjadi kunci dedup: event yang sama
+    // mengembalikan refund yang sudah ada dengan nilai false, bukan membuat refund kedua.
+    pub async fn record_gateway_refund(&self, db: &State<Pool<Any>>, payment_id: &str, amount: Option<Rupiah>, reference: &str) -> Result<(Refund, bool), PaymentError> {
+        self.create_locked(db, None, payment_id, Some(reference), |refundable| NewRefund {
+            amount: amount.unwrap_or(refundable),
+            reason: RefundReason::Other,
+            method: None,
+            notes: Some(reference.to_string()),
+        }).await
+    }
+
+    // Pembayaran dan refund sebelumnya dibaca dengan baris pembayaran terkunci, sehingga dua refund
+    // bersamaan tidak bisa melebihi jumlah yang sudah dibayar. Tautan laci kas ikut dalam transaksi yang sama.
+    async fn create_locked(&self, db: &State<Pool<Any>>, cashier: Option<&AuthenticatedUser>, payment_id: &str, reference: Option<&str>, new_refund: impl FnOnce(Rupiah) -> NewRefund) -> Result<(Refund, bool), PaymentError> {
+        let mut conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let mut tx = (*conn).begin().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        let payment = PembayaranRepository::lock_payment(&mut tx, payment_id).await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => PaymentError::NotFound(format!("Payment with id {} not found", payment_id)),
+                _ => PaymentError::DatabaseError(e.to_string())
+            })?;
+        let existing_refunds = RefundRepository::find_for_payment(&mut tx, payment_id).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        if let Some(reference) = reference
+            && let Some(recorded) = existing_refunds.iter().find(|r| r.notes.as_deref() == Some(reference)) {
+            return Ok((recorded.clone(), false));
+        }
+
+        let new_refund = new_refund(self.refundable_amount(&payment, &existing_refunds));
+        let created = self.build_refund(&payment, &existing_refunds, new_refund)?;
+
+        RefundRepository::insert(&mut tx, &created).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        CashDrawerService::new().record_refund(&mut tx, cashier, &created, Utc::now()).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        tx.commit().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        Ok((created, true))
+    }
+
+    pub async fn get_refunds(&self, db: &State<Pool<Any>>, payment_id: &str) -> Result<Vec<Refund>, PaymentError> {
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        RefundRepository::find_by_payment_id(conn, payment_id).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))
+    }
+
+    pub fn refundable_amount(&self, payment: &Payment, existing_refunds: &[Refund]) -> Rupiah {
+        let refunded: Rupiah = existing_refunds.iter().map(|r| r.amount).sum();
+        payment.amount_paid() - refunded
+    }
+
+    // Validasi refund terhadap pembayaran asal dan tentukan status pembayaran setelah refund
+    pub fn build_refund(&self, payment: &Payment, existing_refunds: &[Refund], new_refund: NewRefund) -> Result<Refund, PaymentError> {
+        if !new_refund.amount.is_positive() {
+            return Err(PaymentError::InvalidInput("Refund amount must be greater than 0".to_string()));
+        }
+
+        let state = PaymentStateFactory::create(&payment.status);
+        if !state.can_transition_to(&PaymentStatus::Refunded) {
+            return Err(PaymentError::InvalidTransition(format!("Cannot refund a payment with status {}", payment.status)));
+        }
+
+        let refundable = self.refundable_amount(payment, existing_refunds);
+        if new_refund.amount > refundable {
+            return Err(PaymentError::InvalidInput(format!(
+                "Refund amount {} exceeds refundable amount {}", new_refund.amount, refundable
+            )));
+        }
+
+        let resulting_status = if new_refund.amount == refundable {
+            PaymentStatus::Refunded
+        } else {
+            payment.status.clone()
+        };
+
+        Ok(Refund {
+            id: format!("RFD-{}", Uuid::new_v4()),
+            payment_id: payment.id.clone(),
+            amount: new_refund.amount,
+            reason: new_refund.reason,
+            method: new_refund.method.unwrap_or_else(|| payment.method.clone()),
+            notes: new_refund.notes,
+            previous_status: payment.status.clone(),
+            resulting_status,
+            refund_date: Utc::now(),
+        })
+    }
+
+    pub fn parse_refund_reason(&self, reason_str: &str) -> Result<RefundReason, PaymentError> {
+        RefundReason::from_string(reason_str)
+            .ok_or_else(|| PaymentError::InvalidInput(format!("Invalid refund reason: {}", reason_str)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manajemen_pembayaran::model::payment::Installment;
+
+    fn paid_payment() -> Payment {
+        Payment {
+            id: "PMT-REFUND".to_string(),
+            transaction_id: "TRX-REFUND".to_string(),
+            amount: Rupiah::from_rupiah(650_000),
+            method: PaymentMethod::BankTransfer,
+            status: PaymentStatus::Paid,
+            payment_date: Utc::now(),
+            installments: Vec::new(),
+            tender_lines: Vec::new(),
+            due_date: None,
+            late_fee_total: Rupiah::ZERO,
+        }
+    }
+
+    fn new_refund(amount: Rupiah) -> NewRefund {
+        NewRefund {
+            amount,
+            reason: RefundReason::Defective,
+            method: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn test_partial_refund_keeps_status() {
+        let service = RefundService::new();
+        let payment = paid_payment();
+
+        let refund = service.build_refund(&payment, &[], new_refund(Rupiah::from_rupiah(65_000))).unwrap();
+
+        assert!(refund.id.starts_with("RFD-"));
+        assert_eq!(refund.method, PaymentMethod::BankTransfer);
+        assert_eq!(refund.previous_status, PaymentStatus::Paid);
+        assert_eq!(refund.resulting_status, PaymentStatus::Paid);
+    }
+
+    #[test]
+    fn test_refund_of_remaining_amount_marks_refunded() {
+        let service = RefundService::new();
+        let payment = paid_payment();
+
+        let first = service.build_refund(&payment, &[], new_refund(Rupiah::from_rupiah(150_000))).unwrap();
+        let second = service.build_refund(&payment, &[first], new_refund(Rupiah::from_rupiah(500_000))).unwrap();
+
+        assert_eq!(second.resulting_status, PaymentStatus::Refunded);
+    }
+
+    #[test]
+    fn test_refund_cannot_exceed_amount_paid() {
+        let service = RefundService::new();
+        let payment = paid_payment();
+
+        let first = service.build_refund(&payment, &[], new_refund(Rupiah::from_rupiah(600_000))).unwrap();
+        let result = service.build_refund(&payment, &[first], new_refund(Rupiah::from_rupiah(50_001)));
+
+        match result {
+            Err(PaymentError::InvalidInput(msg)) => assert!(msg.contains("exceeds refundable amount")),
+            _ => panic!("Expected PaymentError::InvalidInput"),
+        }
+    }
+
+    #[test]
+    fn test_refund_of_installments_limited_to_installments_paid() {
+        let service = RefundService::new();
+        let mut payment = paid_payment();
+        payment.status = PaymentStatus::Installment;
+        payment.installments.push(Installment {
+            id: "INST-1".to_string(),
+            payment_id: payment.id.clone(),
+            amount: Rupiah::from_rupiah(200_000),
+            payment_date: Utc::now(),
+        });
+
+        assert!(service.build_refund(&payment, &[], new_refund(Rupiah::from_rupiah(200_001))).is_err());
+
+        let refund = service.build_refund(&payment, &[], new_refund(Rupiah::from_rupiah(200_000))).unwrap();
+        assert_eq!(refund.resulting_status, PaymentStatus::Refunded);
+    }
+
+    #[test]
+    fn test_refund_rejected_for_unpaid_states() {
+        let service = RefundService::new();
+
+        for status in [PaymentStatus::Pending, PaymentStatus::Failed, PaymentStatus::Void, PaymentStatus::Refunded] {
+            let mut payment = paid_payment();
+            payment.status = status;
+
+            let result = service.build_refund(&payment, &[], new_refund(Rupiah::from_rupiah(1)));
+            assert!(matches!(result, Err(PaymentError::InvalidTransition(_))));
+        }
+    }
+
+    #[test]
+    fn test_refund_amount_must_be_positive() {
+        let service = RefundService::new();
+        let payment = paid_payment();
+
+        assert!(matches!(
+            service.build_refund(&payment, &[], new_refund(Rupiah::ZERO)),
+            Err(PaymentError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_refund_reason() {
+        let service = RefundService::new();
+
+        assert_eq!(service.parse_refund_reason("BARANG_RUSAK").unwrap(), RefundReason::Defective);
+        assert!(matches!(service.parse_refund_reason("RUSAK"), Err(PaymentError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use rocket::State;
+use sqlx::{Any, Pool};
+use uuid::Uuid;
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::manajemen_pembayaran::enums::webhook_result::WebhookResult;
+use crate::manajemen_pembayaran::model::webhook_event::{WebhookEvent, WebhookReceipt};
+use crate::manajemen_pembayaran::patterns::payment_gateway::{GatewayEvent, PaymentGatewayProvider, SimulatorPayload, SimulatorProvider};
+use crate::manajemen_pembayaran::repository::webhook_event_repository::WebhookEventRepository;
+use crate::manajemen_pembayaran::service::payment_service::{PaymentError, PaymentService};
+use crate::manajemen_pembayaran::service::refund_service::RefundService;
+
+pub struct SimulatedEvent {
+    pub payment_id: String,
+    pub status: String,
+    pub amount: Option<Rupiah>,
+    pub event_id: Option<String>,
+}
+
+#[derive(Default)]
+pub struct WebhookService {
+    providers: HashMap<&'static str, Arc<dyn PaymentGatewayProvider>>,
+    simulator: Option<Arc<SimulatorProvider>>,
+}
+
+impl WebhookService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_provider(mut self, provider: Arc<dyn PaymentGatewayProvider>) -> Self {
+        self.providers.insert(provider.get_name(), provider);
+        self
+    }
+
+    pub fn with_simulator(mut self, simulator: SimulatorProvider) -> Self {
+        let simulator = Arc::new(simulator);
+        self.providers.insert(SimulatorProvider::NAME, simulator.clone());
+        self.simulator = Some(simulator);
+        self
+    }
+
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    // Simulator hanya aktif bila PAYMENT_SIMULATOR_SECRET diisi secara eksplisit; tidak ada secret
+    // bawaan karena siapa pun yang tahu secret bisa menandai pembayaran LUNAS.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        match lookup("PAYMENT_SIMULATOR_SECRET").filter(|secret| !secret.trim().is_empty()) {
+            Some(secret) => Self::new().with_simulator(SimulatorProvider::new(secret)),
+            None => Self::new(),
+        }
+    }
+
+    pub fn provider(&self, name: &str) -> Result<Arc<dyn PaymentGatewayProvider>, PaymentError> {
+        self.providers
+            .get(name)
+            .cloned()
+            .ok_or_else(|| PaymentError::NotFound(format!("Payment provider {} is not configured", name)))
+    }
+
+    pub async fn receive(&self, db: &State<Pool<Any>>, provider_name: &str, payload: &[u8], signature: Option<&str>, now: DateTime<Utc>) -> Result<WebhookReceipt, PaymentError> {
+        let provider = self.provider(provider_name)?;
+
+        let signature = signature
+            .ok_or_else(|| PaymentError::Unauthorized(format!("Missing {} header", provider.signature_header())))?;
+        if !provider.verify_signature(payload, signature) {
+            return Err(PaymentError::Unauthorized("Invalid webhook signature".to_string()));
+        }
+
+        let gateway_event = provider.parse_event(payload)
+            .map_err(PaymentError::InvalidInput)?;
+        let raw_payload = String::from_utf8(payload.to_vec())
+            .map_err(|_| PaymentError::InvalidInput("Webhook payload must be valid UTF-8".to_string()))?;
+
+        let event = WebhookEvent {
+            id: format!("WH-{}", Uuid::new_v4()),
+            provider: provider.get_name().to_string(),
+            event_id: gateway_event.event_id.clone(),
+            payment_id: gateway_event.payment_id.clone(),
+            provider_status: gateway_event.provider_status.clone(),
+            payload: raw_payload,
+            signature: signature.to_string(),
+            received_at: now,
+            processed_at: None,
+            result: None,
+            result_message: None,
+        };
+
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let inserted = WebhookEventRepository::insert(conn, &event).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        if !inserted {
+            let conn = db.acquire().await
+                .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+            let existing = WebhookEventRepository::find_by_event_id(conn, provider.get_name(), &gateway_event.event_id).await
+                .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+            // Event yang tersimpan tetapi gagal diproses sebelumnya diproses pada pengiriman ulang
+            let event = match existing.processed_at {
+                Some(_) => existing,
+                None => self.process(db, provider.as_ref(), existing, &gateway_event, now).await?,
+            };
+            return Ok(WebhookReceipt { duplicate: true, event });
+        }
+
+        let event = self.process(db, provider.as_ref(), event, &gateway_event, now).await?;
+        Ok(WebhookReceipt { duplicate: false, event })
+    }
+
+    // Memproses ulang event mentah yang tersimpan tanpa memeriksa signature lagi
+    pub async fn replay(&self, db: &State<Pool<Any>>, id: &str, now: DateTime<Utc>) -> Result<WebhookEvent, PaymentError> {
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        let event = WebhookEventRepository::find_by_id(conn, id).await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => PaymentError::NotFound(format!("Webhook event {} not found", id)),
+                _ => PaymentError::DatabaseError(e.to_string()),
+            })?;
+
+        let provider = self.provider(&event.provider)?;
+        let gateway_event = provider.parse_event(event.payload.as_bytes())
+            .map_err(PaymentError::InvalidInput)?;
+
+        self.process(db, provider.as_ref(), event, &gateway_event, now).await
+    }
+
+    pub async fn simulate(&self, db: &State<Pool<Any>>, simulated: SimulatedEvent, now: DateTime<Utc>) -> Result<WebhookReceipt, PaymentError> {
+        let simulator = self.simulator.as_ref()
+            .ok_or_else(|| PaymentError::NotFound("Simulator provider is disabled".to_string()))?;
+
+        let payload = SimulatorPayload {
+            event_id: simulated.event_id.unwrap_or_else(|| format!("SIM-{}", Uuid::new_v4())),
+            payment_id: simulated.payment_id,
+            status: simulated.status,
+            amount: simulated.amount,
+        };
+        let body = serde_json::to_vec(&payload)
+            .map_err(|e| PaymentError::InvalidInput(e.to_string()))?;
+        let signature = simulator.sign(&body);
+
+        self.receive(db, SimulatorProvider::NAME, &body, Some(&signature), now).await
+    }
+
+    pub async fn get_events(&self, db: &State<Pool<Any>>, payment_id: Option<&str>) -> Result<Vec<WebhookEvent>, PaymentError> {
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        WebhookEventRepository::find_all(conn, payment_id).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))
+    }
+
+    async fn process(&self, db: &State<Pool<Any>>, provider: &dyn PaymentGatewayProvider, mut event: WebhookEvent, gateway_event: &GatewayEvent, now: DateTime<Utc>) -> Result<WebhookEvent, PaymentError> {
+        let (result, message) = self.apply(db, provider, gateway_event).await?;
+
+        let conn = db.acquire().await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+        WebhookEventRepository::mark_processed(conn, &event.id, &result, Some(&message), now).await
+            .map_err(|e| PaymentError::DatabaseError(e.to_string()))?;
+
+        event.processed_at = Some(now);
+        event.result = Some(result);
+        event.result_message = Some(message);
+        Ok(event)
+    }
+
+    // Event yang tidak bisa diterapkan tetap diakui (DIABAIKAN) agar provider tidak mengirim ulang terus-menerus
+    async fn apply(&self, db: &State<Pool<Any>>, provider: &dyn PaymentGatewayProvider, gateway_event: &GatewayEvent) -> Result<(WebhookResult, String), PaymentError> {
+        let Some(new_status) = provider.map_status(&gateway_event.provider_status) else {
+            return Ok((WebhookResult::Ignored, format!("Unknown provider status {}", gateway_event.provider_status)));
+        };
+
+        let payment_service = PaymentService::new();
+        let payment = match payment_service.get_payment_by_id(db, &gateway_event.payment_id).await {
+            Ok(payment) => payment,
+            Err(PaymentError::NotFound(msg)) => return Ok((WebhookResult::Ignored, msg)),
+            Err(e) => return Err(e),
+        };
+
+        if payment.status == new_status {
+            return Ok((WebhookResult::Unchanged, format!("Payment is already {}", new_status)));
+        }
+
+        if new_status == PaymentStatus::Paid
+            && let Some(amount) = gateway_event.amount
+            && amount != payment.amount {
+            return Ok((
+                WebhookResult::Ignored,
+                format!("Settled amount {} does not match payment amount {}", amount, payment.amount),
+            ));
+        }
+
+        // Refund dari gateway dicatat sebagai refund agar jumlah yang dikembalikan tetap terlacak.
+        // Provider dan event id menjadi kunci dedup, sehingga replay event tidak merefund dua kali.
+        if new_status == PaymentStatus::Refunded {
+            let reference = format!("{} event {}", provider.get_name(), gateway_event.event_id);
+            let refund = RefundService::new()
+                .record_gateway_refund(db, &payment.id, gateway_event.amount, &reference)
+                .await;
+            return match refund {
+                Ok((refund, false)) => Ok((
+                    WebhookResult::Unchanged,
+                    format!("Refund {} for this event was already recorded", refund.id),
+                )),
+                Ok((refund, true)) => Ok((
+                    WebhookResult::Applied,
+                    format!("Refund {} of {} recorded, payment status is {}", refund.id, refund.amount, refund.resulting_status),
+                )),
+                Err(PaymentError::InvalidTransition(msg)) | Err(PaymentError::InvalidInput(msg)) => Ok((WebhookResult::Ignored, msg)),
+                Err(e) => Err(e),
+            };
+        }
+
+        match payment_service.update_payment_status(db, None, payment.id.clone(), new_status.clone(), None).await {
+            Ok(_) => Ok((WebhookResult::Applied, format!("Payment status changed from {} to {}", payment.status, new_status))),
+            Err(PaymentError::InvalidTransition(msg)) => Ok((WebhookResult::Ignored, msg)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_lookup_disables_simulator_without_secret() {
+        let service = WebhookService::from_lookup(|_| None);
+        assert!(matches!(service.provider(SimulatorProvider::NAME), Err(PaymentError::NotFound(_))));
+        assert!(service.simulator.is_none());
+
+        let service = WebhookService::from_lookup(|key| match key {
+            "PRODUCTION" => Some("false".to_string()),
+            "PAYMENT_SIMULATOR_SECRET" => Some("  ".to_string()),
+            _ => None,
+        });
+        assert!(service.simulator.is_none());
+    }
+
+    #[test]
+    fn test_from_lookup_enables_simulator_with_secret() {
+        let service = WebhookService::from_lookup(|key| (key == "PAYMENT_SIMULATOR_SECRET").then(|| "rahasia".to_string()));
+        assert!(service.provider(SimulatorProvider::NAME).is_ok());
+        assert!(service.simulator.is_some());
+    }
+
+    #[test]
+    fn test_unknown_provider() {
+        let service = WebhookService::new();
+        assert!(matches!(service.provider("midtrans"), Err(PaymentError::NotFound(_))));
+    }
+}
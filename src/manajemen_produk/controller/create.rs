@@ -13,13 +13,14 @@ pub async fn tambah_produk(
     // Validasi stok tidak boleh negatif
     let stok = if request.stok < 0 { 0 } else { request.stok as u32 };
     
-    let produk = Produk::new(
+    let mut produk = Produk::new(
         request.nama.clone(),
         request.kategori.clone(),
         request.harga,
         stok,
         request.deskripsi.clone(),
     );
+    produk.kategori_pajak = request.kategori_pajak;
 
     match repository::create::tambah_produk(&produk).await {
         Ok(id) => {
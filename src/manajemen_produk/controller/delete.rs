@@ -44,6 +44,7 @@ mod tests {
     use rocket::local::asynchronous::Client;
     use crate::manajemen_produk::controller::{ApiResponse, routes};
     use crate::manajemen_produk::model::Produk;
+    use crate::common::money::Rupiah;
     use crate::manajemen_produk::repository;
 
     async fn setup_test_client() -> Client {
@@ -55,7 +56,7 @@ mod tests {
         let produk1 = Produk::new(
             "Laptop Gaming".to_string(),
             "Elektronik".to_string(),
-            15_000_000.0,
+            Rupiah::from_rupiah(15_000_000),
             10,
             Some("Laptop dengan RTX 4060".to_string()),
         );
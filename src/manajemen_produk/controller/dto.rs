@@ -1,14 +1,18 @@
 use rocket::serde::{Deserialize, Serialize};
+use crate::common::money::Rupiah;
 use crate::manajemen_produk::model::Produk;
+use crate::transaksi_penjualan::model::pajak::KategoriPajak;
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(crate = "rocket::serde")]
 pub struct ProdukRequest {
     pub nama: String,
     pub kategori: String,
-    pub harga: f64,
+    pub harga: Rupiah,
     pub stok: i32,
     pub deskripsi: Option<String>,
+    #[serde(default)]
+    pub kategori_pajak: KategoriPajak,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -17,9 +21,10 @@ pub struct ProdukResponse {
     pub id: Option<i64>,
     pub nama: String,
     pub kategori: String,
-    pub harga: f64,
+    pub harga: Rupiah,
     pub stok: u32,
     pub deskripsi: Option<String>,
+    pub kategori_pajak: KategoriPajak,
 }
 
 impl From<Produk> for ProdukResponse {
@@ -31,6 +36,7 @@ impl From<Produk> for ProdukResponse {
             harga: produk.harga,
             stok: produk.stok,
             deskripsi: produk.deskripsi,
+            kategori_pajak: produk.kategori_pajak,
         }
     }
 }
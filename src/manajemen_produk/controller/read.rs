@@ -58,6 +58,7 @@ mod tests {
     use rocket::local::asynchronous::Client;
     use crate::manajemen_produk::controller::dto::{ApiResponse, ProdukResponse};
     use crate::manajemen_produk::model::Produk;
+    use crate::common::money::Rupiah;
     use crate::manajemen_produk::repository;
 
     async fn setup_test_client() -> Client {
@@ -70,7 +71,7 @@ mod tests {
         let produk1 = Produk::new(
             "Laptop Gaming".to_string(),
             "Elektronik".to_string(),
-            15_000_000.0,
+            Rupiah::from_rupiah(15_000_000),
             10,
             Some("Laptop dengan RTX 4060".to_string()),
         );
@@ -20,6 +20,7 @@ pub async fn update_produk(
                 .harga(request.harga)
                 .stok(request.stok.try_into().unwrap_or(0))
                 .deskripsi(request.deskripsi.clone().unwrap_or_default())
+                .kategori_pajak(request.kategori_pajak)
                 .build();
                 
             match updated_produk {
@@ -128,6 +129,7 @@ mod tests {
     use rocket::serde::json::json;
     use crate::manajemen_produk::controller::{ApiResponse, ProdukResponse};
     use crate::manajemen_produk::model::Produk;
+    use crate::common::money::Rupiah;
     use crate::manajemen_produk::repository;
 
     async fn setup_test_client() -> Client {
@@ -142,7 +144,7 @@ mod tests {
         let produk1 = Produk::new(
             "Laptop Gaming".to_string(),
             "Elektronik".to_string(),
-            15_000_000.0,
+            Rupiah::from_rupiah(15_000_000),
             10,
             Some("Laptop dengan RTX 4060".to_string()),
         );
@@ -190,7 +192,7 @@ mod tests {
         assert!(json.success, "Expected success but got failure: {:?}", json.message);
         let produk = json.data.unwrap();
         assert_eq!(produk.nama, "Updated Laptop Gaming");
-        assert_eq!(produk.harga, 16_000_000.0);
+        assert_eq!(produk.harga, Rupiah::from_rupiah(16_000_000));
         assert_eq!(produk.stok, 8);
         assert_eq!(produk.deskripsi, Some("Updated description".to_string()));
         
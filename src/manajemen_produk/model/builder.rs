@@ -7,17 +7,21 @@
 // - `harga()`: Menetapkan harga produk
 // - `stok()`: Menetapkan stok produk
 // - `deskripsi()`: Menetapkan deskripsi produk (opsional)
+// - `kategori_pajak()`: Menetapkan kategori PPN produk (opsional)
 // - `build()`: Membuat Produk dan memvalidasinya, mengembalikan Result
 
 use crate::manajemen_produk::model::Produk;
+use crate::common::money::Rupiah;
+use crate::transaksi_penjualan::model::pajak::KategoriPajak;
 
 pub struct ProdukBuilder {
     id: Option<i64>,
     nama: String,
     kategori: String,
-    harga: f64,
+    harga: Rupiah,
     stok: u32,
     deskripsi: Option<String>,
+    kategori_pajak: KategoriPajak,
 }
 
 impl ProdukBuilder {
@@ -28,9 +32,10 @@ impl ProdukBuilder {
             id: None,
             nama,
             kategori,
-            harga: 0.0,
+            harga: Rupiah::ZERO,
             stok: 0,
             deskripsi: None,
+            kategori_pajak: KategoriPajak::KenaPajak,
         }
     }
     
@@ -43,7 +48,7 @@ impl ProdukBuilder {
     
     /// Menetapkan harga produk.
     /// Harus berupa nilai positif.
-    pub fn harga(mut self, harga: f64) -> Self {
+    pub fn harga(mut self, harga: Rupiah) -> Self {
         self.harga = harga;
         self
     }
@@ -61,6 +66,13 @@ impl ProdukBuilder {
         self
     }
     
+    /// Menetapkan kategori PPN produk (opsional).
+    /// Default kena pajak.
+    pub fn kategori_pajak(mut self, kategori_pajak: KategoriPajak) -> Self {
+        self.kategori_pajak = kategori_pajak;
+        self
+    }
+    
     /// Membuild instance Produk dan memvalidasi data.
     /// Mengembalikan Ok(Produk) jika valid, atau Err dengan daftar error jika tidak valid.
     pub fn build(self) -> Result<Produk, Vec<String>> {
@@ -71,6 +83,7 @@ impl ProdukBuilder {
             harga: self.harga,
             stok: self.stok,
             deskripsi: self.deskripsi,
+            kategori_pajak: self.kategori_pajak,
         };
         
         match produk.validate() {
@@ -88,7 +101,7 @@ mod tests {
     fn test_produk_builder() {
         // Using the builder pattern
         let produk_result = ProdukBuilder::new("Laptop Gaming".to_string(), "Elektronik".to_string())
-            .harga(15_000_000.0)
+            .harga(Rupiah::from_rupiah(15_000_000))
             .stok(10)
             .deskripsi("Laptop dengan RTX 4060".to_string())
             .build();
@@ -98,7 +111,7 @@ mod tests {
         
         assert_eq!(produk.nama, "Laptop Gaming");
         assert_eq!(produk.kategori, "Elektronik");
-        assert_eq!(produk.harga, 15_000_000.0);
+        assert_eq!(produk.harga, Rupiah::from_rupiah(15_000_000));
         assert_eq!(produk.stok, 10);
         assert_eq!(produk.deskripsi, Some("Laptop dengan RTX 4060".to_string()));
     }
@@ -107,7 +120,7 @@ mod tests {
     fn test_builder_validation() {
         // Test validation with empty name
         let produk_result = ProdukBuilder::new("".to_string(), "Elektronik".to_string())
-            .harga(15_000_000.0)
+            .harga(Rupiah::from_rupiah(15_000_000))
             .stok(10)
             .build();
         
@@ -116,7 +129,7 @@ mod tests {
         
         // Test validation with negative price
         let produk_result = ProdukBuilder::new("Laptop Gaming".to_string(), "Elektronik".to_string())
-            .harga(-5000.0)
+            .harga(Rupiah::from_rupiah(-5000))
             .stok(10)
             .build();
         
@@ -128,7 +141,7 @@ mod tests {
     fn test_builder_with_id() {
         let produk_result = ProdukBuilder::new("Mouse Gaming".to_string(), "Elektronik".to_string())
             .id(100)
-            .harga(500_000.0)
+            .harga(Rupiah::from_rupiah(500_000))
             .stok(25)
             .build();
         
@@ -137,10 +150,23 @@ mod tests {
         assert_eq!(produk.id, Some(100));
     }
 
+    #[test]
+    fn test_builder_kategori_pajak() {
+        let produk = ProdukBuilder::new("Beras 5kg".to_string(), "Sembako".to_string())
+            .harga(Rupiah::from_rupiah(75_000))
+            .kategori_pajak(KategoriPajak::BebasPajak)
+            .build()
+            .unwrap();
+        assert_eq!(produk.kategori_pajak, KategoriPajak::BebasPajak);
+
+        let produk = ProdukBuilder::new("Semen".to_string(), "Material".to_string()).build().unwrap();
+        assert_eq!(produk.kategori_pajak, KategoriPajak::KenaPajak);
+    }
+
     #[test]
     fn test_builder_minimal_fields() {
         let produk_result = ProdukBuilder::new("Produk Test".to_string(), "Test".to_string())
-            .harga(1000.0)
+            .harga(Rupiah::from_rupiah(1000))
             .stok(1)
             .build();
         
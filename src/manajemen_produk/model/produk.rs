@@ -4,23 +4,28 @@
 // - `id`: ID unik produk (opsional, None untuk produk baru)
 // - `nama`: Nama produk (wajib)
 // - `kategori`: Kategori produk (wajib)
-// - `harga`: Harga produk dalam bentuk float (wajib)
+// - `harga`: Harga produk dalam Rupiah fixed-point (wajib)
 // - `stok`: Jumlah stok tersedia (wajib)
 // - `deskripsi`: Deskripsi tambahan produk (opsional)
+// - `kategori_pajak`: Kategori PPN produk (default kena pajak)
 
 // # Methods
 // - `with_id()`: Constructor untuk produk yang sudah ada di database
 // - `new()`: Constructor untuk produk baru
 // - `validate()`: Validasi data produk sebelum disimpan
 
+use crate::common::money::Rupiah;
+use crate::transaksi_penjualan::model::pajak::KategoriPajak;
+
 #[derive(Debug, Clone)]
 pub struct Produk {
     pub id: Option<i64>,
     pub nama: String,
     pub kategori: String,
-    pub harga: f64,
+    pub harga: Rupiah,
     pub stok: u32,
     pub deskripsi: Option<String>,
+    pub kategori_pajak: KategoriPajak,
 }
 
 impl Produk {
@@ -28,7 +33,7 @@ impl Produk {
         id: i64,
         nama: String,
         kategori: String,
-        harga: f64,
+        harga: Rupiah,
         stok: u32,
         deskripsi: Option<String>,
     ) -> Self {
@@ -39,13 +44,14 @@ impl Produk {
             harga,
             stok,
             deskripsi,
+            kategori_pajak: KategoriPajak::KenaPajak,
         }
     }
     
     pub fn new(
         nama: String,
         kategori: String,
-        harga: f64,
+        harga: Rupiah,
         stok: u32,
         deskripsi: Option<String>,
     ) -> Self {
@@ -56,6 +62,7 @@ impl Produk {
             harga,
             stok,
             deskripsi,
+            kategori_pajak: KategoriPajak::KenaPajak,
         }
     }
     
@@ -72,21 +79,21 @@ fn setup_test_products() -> Vec<Produk> {
         Produk::new(
             "Laptop Gaming".to_string(),
             "Elektronik".to_string(),
-            15_000_000.0,
+            Rupiah::from_rupiah(15_000_000),
             10,
             Some("Laptop dengan RTX 4060".to_string()),
         ),
         Produk::new(
             "Cat Tembok".to_string(),
             "Material".to_string(),
-            150_000.0,
+            Rupiah::from_rupiah(150_000),
             50,
             Some("Cat tembok anti air".to_string()),
         ),
         Produk::new(
             "Smartphone".to_string(),
             "Elektronik".to_string(),
-            8_000_000.0,
+            Rupiah::from_rupiah(8_000_000),
             20,
             Some("Smartphone dengan kamera 108MP".to_string()),
         ),
@@ -99,14 +106,14 @@ fn test_create_produk_baru() {
     let produk = Produk::new(
         "Laptop Gaming".to_string(),
         "Elektronik".to_string(),
-        15_000_000.0,
+        Rupiah::from_rupiah(15_000_000),
         10,
         Some("Laptop dengan RTX 4060".to_string()),
     );
 
     assert_eq!(produk.nama, "Laptop Gaming");
     assert_eq!(produk.kategori, "Elektronik");
-    assert_eq!(produk.harga, 15_000_000.0);
+    assert_eq!(produk.harga, Rupiah::from_rupiah(15_000_000));
     assert_eq!(produk.stok, 10);
     assert_eq!(produk.deskripsi, Some("Laptop dengan RTX 4060".to_string()));
 }
@@ -116,14 +123,14 @@ fn test_create_produk_without_deskripsi() {
     let produk = Produk::new(
         "Cat Tembok".to_string(),
         "Material".to_string(),
-        150_000.0,
+        Rupiah::from_rupiah(150_000),
         50,
         None,
     );
 
     assert_eq!(produk.nama, "Cat Tembok");
     assert_eq!(produk.kategori, "Material");
-    assert_eq!(produk.harga, 150_000.0);
+    assert_eq!(produk.harga, Rupiah::from_rupiah(150_000));
     assert_eq!(produk.stok, 50);
     assert_eq!(produk.deskripsi, None);
 }
@@ -167,7 +174,7 @@ fn test_find_produk_by_nama() {
     assert!(found_produk.is_some());
     let produk = found_produk.unwrap();
     assert_eq!(produk.kategori, "Elektronik");
-    assert_eq!(produk.harga, 8_000_000.0);
+    assert_eq!(produk.harga, Rupiah::from_rupiah(8_000_000));
 }
 
 #[test]
@@ -175,14 +182,14 @@ fn test_update_produk_harga() {
     let mut produk = Produk::new(
         "Laptop Gaming".to_string(),
         "Elektronik".to_string(),
-        15_000_000.0,
+        Rupiah::from_rupiah(15_000_000),
         10,
         Some("Laptop dengan RTX 4060".to_string()),
     );
     
-    produk.harga = 14_500_000.0;
+    produk.harga = Rupiah::from_rupiah(14_500_000);
     
-    assert_eq!(produk.harga, 14_500_000.0);
+    assert_eq!(produk.harga, Rupiah::from_rupiah(14_500_000));
 }
 
 #[test]
@@ -190,7 +197,7 @@ fn test_update_produk_stok() {
     let mut produk = Produk::new(
         "Laptop Gaming".to_string(),
         "Elektronik".to_string(),
-        15_000_000.0,
+        Rupiah::from_rupiah(15_000_000),
         10,
         Some("Laptop dengan RTX 4060".to_string()),
     );
@@ -205,7 +212,7 @@ fn test_update_produk_deskripsi() {
     let mut produk = Produk::new(
         "Laptop Gaming".to_string(),
         "Elektronik".to_string(),
-        15_000_000.0,
+        Rupiah::from_rupiah(15_000_000),
         10,
         Some("Laptop dengan RTX 4060".to_string()),
     );
@@ -235,7 +242,7 @@ fn test_validasi_produk() {
     let produk = Produk::new(
         "Laptop Gaming".to_string(),
         "Elektronik".to_string(),
-        15_000_000.0,
+        Rupiah::from_rupiah(15_000_000),
         10,
         Some("Laptop dengan RTX 4060".to_string()),
     );
@@ -245,7 +252,7 @@ fn test_validasi_produk() {
     let produk_invalid = Produk::new(
         "".to_string(),
         "Elektronik".to_string(),
-        15_000_000.0,
+        Rupiah::from_rupiah(15_000_000),
         10,
         Some("Laptop dengan RTX 4060".to_string()),
     );
@@ -256,7 +263,7 @@ fn test_validasi_produk() {
     let produk_invalid_price = Produk::new(
         "Laptop Gaming".to_string(),
         "Elektronik".to_string(),
-        -5000.0,
+        Rupiah::from_rupiah(-5000),
         10,
         Some("Laptop dengan RTX 4060".to_string()),
     );
@@ -10,16 +10,17 @@ pub async fn tambah_produk(produk: &Produk) -> Result<i64, RepositoryError> {
     
     let result = sqlx::query(
         r#"
-        INSERT INTO produk (nama, kategori, harga, stok, deskripsi)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO produk (nama, kategori, harga, stok, deskripsi, kategori_pajak)
+        VALUES ($1, $2, CAST($3 AS DECIMAL(15,2)), $4, $5, $6)
         RETURNING id
         "#
     )
     .bind(&produk.nama)
     .bind(&produk.kategori)
-    .bind(produk.harga)
+    .bind(produk.harga.to_string())
     .bind(produk.stok as i32)
     .bind(&produk.deskripsi)
+    .bind(produk.kategori_pajak.to_string())
     .fetch_one(pool)
     .await?;
     
@@ -32,6 +33,7 @@ mod tests {
     use crate::manajemen_produk::repository::delete::clear_all;
     use crate::manajemen_produk::repository::read::ambil_produk_by_id;
     use crate::manajemen_produk::repository::dto::init_database;
+    use crate::common::money::Rupiah;
 
     // Helper function to create test products
     fn create_test_products() -> Vec<Produk> {
@@ -39,21 +41,21 @@ mod tests {
             Produk::new(
                 "Laptop Gaming".to_string(),
                 "Elektronik".to_string(),
-                15_000_000.0,
+                Rupiah::from_rupiah(15_000_000),
                 10,
                 Some("Laptop dengan RTX 4060".to_string()),
             ),
             Produk::new(
                 "Cat Tembok".to_string(),
                 "Material".to_string(),
-                150_000.0,
+                Rupiah::from_rupiah(150_000),
                 50,
                 Some("Cat tembok anti air".to_string()),
             ),
             Produk::new(
                 "Smartphone".to_string(),
                 "Elektronik".to_string(),
-                8_000_000.0,
+                Rupiah::from_rupiah(8_000_000),
                 20,
                 Some("Smartphone dengan kamera 108MP".to_string()),
             ),
@@ -76,7 +78,7 @@ mod tests {
         let produk = Produk::new(
             "Test Laptop".to_string(),
             "Elektronik".to_string(),
-            15_000_000.0,
+            Rupiah::from_rupiah(15_000_000),
             10,
             Some("Test Description".to_string())
         );
@@ -112,7 +114,7 @@ mod tests {
             let produk = Produk::new(
                 format!("Product {}", i),
                 "Test".to_string(),
-                1000.0,
+                Rupiah::from_rupiah(1000),
                 10,
                 None,
             );
@@ -134,7 +136,7 @@ mod tests {
         let produk = Produk::new(
             "New Product".to_string(),
             "Test".to_string(),
-            1000.0,
+            Rupiah::from_rupiah(1000),
             10,
             None,
         );
@@ -157,7 +159,7 @@ mod tests {
         let invalid_produk = Produk::new(
             "".to_string(),
             "Test".to_string(),
-            1000.0,
+            Rupiah::from_rupiah(1000),
             10,
             None,
         );
@@ -170,7 +172,7 @@ mod tests {
         let invalid_price_produk = Produk::new(
             "Valid Name".to_string(),
             "Test".to_string(),
-            -1000.0,
+            Rupiah::from_rupiah(-1000),
             10,
             None,
         );
@@ -183,7 +185,7 @@ mod tests {
         let invalid_category_produk = Produk::new(
             "Valid Name".to_string(),
             "".to_string(),
-            1000.0,
+            Rupiah::from_rupiah(1000),
             10,
             None,
         );
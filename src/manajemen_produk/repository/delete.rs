@@ -37,6 +37,7 @@ pub async fn clear_all() -> Result<(), RepositoryError> {
 mod tests {
     use super::*;
     use crate::manajemen_produk::model::Produk;
+    use crate::common::money::Rupiah;
     use crate::manajemen_produk::repository::create::tambah_produk;
     use crate::manajemen_produk::repository::read::{ambil_produk_by_id, ambil_semua_produk};
     use crate::manajemen_produk::repository::dto::init_database;
@@ -45,7 +46,7 @@ mod tests {
         Produk::new(
             "Laptop Gaming".to_string(),
             "Elektronik".to_string(),
-            15_000_000.0,
+            Rupiah::from_rupiah(15_000_000),
             10,
             Some("Laptop dengan RTX 4060".to_string()),
         )
@@ -141,7 +142,7 @@ mod tests {
         let new_product = Produk::new(
             "New Product".to_string(), 
             "Test".to_string(), 
-            1000.0, 
+            Rupiah::from_rupiah(1000), 
             10, 
             None
         );
@@ -154,7 +155,7 @@ mod tests {
         let another_product = Produk::new(
             "Another Product".to_string(), 
             "Test".to_string(), 
-            2000.0, 
+            Rupiah::from_rupiah(2000), 
             5, 
             None
         );
@@ -177,7 +178,7 @@ mod tests {
             let product = Produk::new(
                 format!("Product {}", i),
                 "Test".to_string(),
-                1000.0 * i as f64,
+                Rupiah::from_rupiah(1000) * i,
                 10 + i,
                 Some(format!("Description {}", i))
             );
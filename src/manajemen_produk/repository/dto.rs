@@ -2,7 +2,9 @@ use sqlx::{PgPool, Row};
 use std::sync::OnceLock;
 use std::error::Error as StdError;
 use std::fmt;
+use crate::common::money::Rupiah;
 use crate::manajemen_produk::model::Produk;
+use crate::transaksi_penjualan::model::pajak::KategoriPajak;
 
 // Global database connection pool
 static DB_POOL: OnceLock<PgPool> = OnceLock::new();
@@ -53,6 +55,7 @@ pub async fn init_database() -> Result<(), RepositoryError> {
             harga DECIMAL(15,2) NOT NULL,
             stok INTEGER NOT NULL,
             deskripsi TEXT,
+            kategori_pajak VARCHAR(20) NOT NULL DEFAULT 'KENA_PAJAK',
             created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
             updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
         )
@@ -101,7 +104,7 @@ pub fn validate_produk(produk: &Produk) -> Result<(), RepositoryError> {
         return Err(RepositoryError::ValidationError("Kategori tidak boleh kosong".to_string()));
     }
     
-    if produk.harga < 0.0 {
+    if produk.harga.is_negative() {
         return Err(RepositoryError::ValidationError("Harga tidak boleh negatif".to_string()));
     }
     
@@ -113,15 +116,30 @@ pub fn validate_produk(produk: &Produk) -> Result<(), RepositoryError> {
 }
 
 // Convert database row to Produk
+// Kolom harga dibaca sebagai teks (CAST) agar nilai DECIMAL tidak melewati f64
 pub fn row_to_produk(row: &sqlx::postgres::PgRow) -> Result<Produk, sqlx::Error> {
-    Ok(Produk::with_id(
+    let harga: String = row.try_get("harga")?;
+    let harga = harga.parse::<Rupiah>().map_err(|e| sqlx::Error::ColumnDecode {
+        index: "harga".to_string(),
+        source: Box::new(e),
+    })?;
+
+    let kategori_pajak: String = row.try_get("kategori_pajak")?;
+    let kategori_pajak = KategoriPajak::from_string(&kategori_pajak).ok_or_else(|| sqlx::Error::ColumnDecode {
+        index: "kategori_pajak".to_string(),
+        source: format!("kategori pajak tidak dikenal: {}", kategori_pajak).into(),
+    })?;
+
+    let mut produk = Produk::with_id(
         row.try_get("id")?,
         row.try_get("nama")?,
         row.try_get("kategori")?,
-        row.try_get("harga")?,
+        harga,
         row.try_get::<i32, _>("stok")? as u32,
         row.try_get("deskripsi")?,
-    ))
+    );
+    produk.kategori_pajak = kategori_pajak;
+    Ok(produk)
 }
 
 // Statistics helper
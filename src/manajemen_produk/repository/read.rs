@@ -4,7 +4,7 @@ use crate::manajemen_produk::repository::dto::{get_db_pool, row_to_produk, Repos
 pub async fn ambil_semua_produk() -> Result<Vec<Produk>, RepositoryError> {
     let pool = get_db_pool()?;
     
-    let rows = sqlx::query("SELECT * FROM produk ORDER BY id")
+    let rows = sqlx::query("SELECT id, nama, kategori, CAST(harga AS TEXT) AS harga, stok, deskripsi, kategori_pajak FROM produk ORDER BY id")
         .fetch_all(pool)
         .await?;
     
@@ -19,7 +19,7 @@ pub async fn ambil_semua_produk() -> Result<Vec<Produk>, RepositoryError> {
 pub async fn ambil_produk_by_id(id: i64) -> Result<Option<Produk>, RepositoryError> {
     let pool = get_db_pool()?;
     
-    let row = sqlx::query("SELECT * FROM produk WHERE id = $1")
+    let row = sqlx::query("SELECT id, nama, kategori, CAST(harga AS TEXT) AS harga, stok, deskripsi, kategori_pajak FROM produk WHERE id = $1")
         .bind(id)
         .fetch_optional(pool)
         .await?;
@@ -36,27 +36,28 @@ mod tests {
     use crate::manajemen_produk::repository::create::tambah_produk;
     use crate::manajemen_produk::repository::delete::clear_all;
     use crate::manajemen_produk::repository::dto::init_database;
+    use crate::common::money::Rupiah;
 
     fn create_test_products() -> Vec<Produk> {
         vec![
             Produk::new(
                 "Laptop Gaming".to_string(), 
                 "Elektronik".to_string(), 
-                15_000_000.0, 
+                Rupiah::from_rupiah(15_000_000), 
                 10, 
                 Some("Laptop dengan RTX 4060".to_string())
             ),
             Produk::new(
                 "Cat Tembok".to_string(), 
                 "Material".to_string(), 
-                150_000.0, 
+                Rupiah::from_rupiah(150_000), 
                 50, 
                 Some("Cat tembok anti air".to_string())
             ),
             Produk::new(
                 "Smartphone".to_string(), 
                 "Elektronik".to_string(), 
-                8_000_000.0, 
+                Rupiah::from_rupiah(8_000_000), 
                 20, 
                 Some("Smartphone dengan kamera 108MP".to_string())
             ),
@@ -162,13 +163,13 @@ mod tests {
         
         // Create products with different categories
         let elektronik_products = vec![
-            Produk::new("Laptop".to_string(), "Elektronik".to_string(), 10_000_000.0, 5, None),
-            Produk::new("HP".to_string(), "Elektronik".to_string(), 5_000_000.0, 15, None),
+            Produk::new("Laptop".to_string(), "Elektronik".to_string(), Rupiah::from_rupiah(10_000_000), 5, None),
+            Produk::new("HP".to_string(), "Elektronik".to_string(), Rupiah::from_rupiah(5_000_000), 15, None),
         ];
         
         let material_products = vec![
-            Produk::new("Semen".to_string(), "Material".to_string(), 50_000.0, 100, None),
-            Produk::new("Batu Bata".to_string(), "Material".to_string(), 1_000.0, 500, None),
+            Produk::new("Semen".to_string(), "Material".to_string(), Rupiah::from_rupiah(50_000), 100, None),
+            Produk::new("Batu Bata".to_string(), "Material".to_string(), Rupiah::from_rupiah(1_000), 500, None),
         ];
         
         let mut all_inserted_ids = Vec::new();
@@ -223,7 +224,7 @@ mod tests {
         let original_product = Produk::new(
             "Test Product".to_string(),
             "Test Category".to_string(),
-            1_234_567.89,
+            Rupiah::from_sen(123_456_789),
             42,
             Some("Test description with special chars: éñ@#$%".to_string())
         );
@@ -237,9 +238,8 @@ mod tests {
         assert_eq!(retrieved.nama, original_product.nama);
         assert_eq!(retrieved.kategori, original_product.kategori);
         
-        // For floating point comparison, allow small epsilon
-        let price_diff = (retrieved.harga - original_product.harga).abs();
-        assert!(price_diff < 0.01, "Price difference too large: {}", price_diff);
+        // Harga disimpan sebagai DECIMAL sehingga harus kembali persis sama
+        assert_eq!(retrieved.harga, original_product.harga);
         
         assert_eq!(retrieved.stok, original_product.stok);
         assert_eq!(retrieved.deskripsi, original_product.deskripsi);
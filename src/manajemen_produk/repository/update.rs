@@ -1,3 +1,4 @@
+use crate::common::money::Rupiah;
 use crate::manajemen_produk::model::Produk;
 use crate::manajemen_produk::repository::dto::{get_db_pool, validate_produk, RepositoryError};
 
@@ -10,15 +11,16 @@ pub async fn update_produk(id: i64, produk: &Produk) -> Result<bool, RepositoryE
     let result = sqlx::query(
         r#"
         UPDATE produk 
-        SET nama = $1, kategori = $2, harga = $3, stok = $4, deskripsi = $5
-        WHERE id = $6
+        SET nama = $1, kategori = $2, harga = CAST($3 AS DECIMAL(15,2)), stok = $4, deskripsi = $5, kategori_pajak = $6
+        WHERE id = $7
         "#
     )
     .bind(&produk.nama)
     .bind(&produk.kategori)
-    .bind(produk.harga)
+    .bind(produk.harga.to_string())
     .bind(produk.stok as i32)
     .bind(&produk.deskripsi)
+    .bind(produk.kategori_pajak.to_string())
     .bind(id)
     .execute(pool)
     .await?;
@@ -46,15 +48,15 @@ pub async fn update_stok(id: i64, new_stok: u32) -> Result<bool, RepositoryError
     }
 }
 
-pub async fn update_harga(id: i64, new_harga: f64) -> Result<bool, RepositoryError> {
-    if new_harga < 0.0 {
+pub async fn update_harga(id: i64, new_harga: Rupiah) -> Result<bool, RepositoryError> {
+    if new_harga.is_negative() {
         return Err(RepositoryError::ValidationError("Harga tidak boleh negatif".to_string()));
     }
     
     let pool = get_db_pool()?;
     
-    let result = sqlx::query("UPDATE produk SET harga = $1 WHERE id = $2")
-        .bind(new_harga)
+    let result = sqlx::query("UPDATE produk SET harga = CAST($1 AS DECIMAL(15,2)) WHERE id = $2")
+        .bind(new_harga.to_string())
         .bind(id)
         .execute(pool)
         .await?;
@@ -84,7 +86,7 @@ mod tests {
         Produk::new(
             format!("Laptop Gaming {}", unique_id),
             "Elektronik".to_string(),
-            15_000_000.0,
+            Rupiah::from_rupiah(15_000_000),
             10,
             Some(format!("Laptop dengan RTX 4060 {}", unique_id)),
         )
@@ -95,7 +97,7 @@ mod tests {
         Produk::new(
             format!("{} {}", name, unique_id),
             "Elektronik".to_string(),
-            15_000_000.0,
+            Rupiah::from_rupiah(15_000_000),
             10,
             Some(format!("Product description {}", unique_id)),
         )
@@ -133,7 +135,7 @@ mod tests {
         // Update product data
         let unique_suffix = Uuid::new_v4().to_string()[..8].to_string();
         produk.nama = format!("Updated Laptop Gaming {}", unique_suffix);
-        produk.harga = 17_500_000.0;
+        produk.harga = Rupiah::from_rupiah(17_500_000);
         produk.stok = 15;
         produk.deskripsi = Some(format!("Updated laptop dengan RTX 4070 {}", unique_suffix));
         
@@ -148,7 +150,7 @@ mod tests {
             .expect("Product should exist after update");
         
         assert_eq!(updated_product.nama, produk.nama);
-        assert_eq!(updated_product.harga, 17_500_000.0);
+        assert_eq!(updated_product.harga, Rupiah::from_rupiah(17_500_000));
         assert_eq!(updated_product.stok, 15);
         assert_eq!(updated_product.deskripsi, produk.deskripsi);
         assert_eq!(updated_product.kategori, "Elektronik"); // Should remain unchanged
@@ -230,7 +232,7 @@ mod tests {
         let id = tambah_produk(&produk).await
             .expect("Failed to create test product");
         
-        let new_harga = 18_750_000.0;
+        let new_harga = Rupiah::from_rupiah(18_750_000);
         let update_result = update_harga(id, new_harga).await
             .expect("Failed to update price");
         assert!(update_result, "Price update should return true on success");
@@ -256,7 +258,7 @@ mod tests {
         let id = tambah_produk(&produk).await
             .expect("Failed to create test product");
         
-        let negative_price = -1000.0;
+        let negative_price = Rupiah::from_rupiah(-1000);
         let update_result = update_harga(id, negative_price).await;
         
         assert!(update_result.is_err(), "Negative price should be rejected");
@@ -282,7 +284,7 @@ mod tests {
         setup_test_environment().await.expect("Failed to setup test environment");
         
         let non_existent_id = i64::MAX - 3000;
-        let new_harga = 20_000_000.0;
+        let new_harga = Rupiah::from_rupiah(20_000_000);
         
         let update_result = update_harga(non_existent_id, new_harga).await;
         
@@ -308,7 +310,7 @@ mod tests {
         let invalid_produk = Produk::new(
             "".to_string(), // Invalid: empty name
             "Elektronik".to_string(), 
-            15_000_000.0, 
+            Rupiah::from_rupiah(15_000_000), 
             10, 
             None
         );
@@ -348,7 +350,7 @@ mod tests {
         let updated_produk = Produk::new(
             format!("Laptop Gaming RGB Xtreme Pro Max Ultra {}", unique_suffix), // Long name with unique suffix
             "Elektronik & Komputer".to_string(), // Name with special characters
-            99_999_999.99, // Large decimal price
+            Rupiah::from_sen(9_999_999_999), // Large decimal price
             999999, // Large stock number
             Some(format!("Deskripsi panjang dengan karakter khusus: àáâãäåæçèéêë & symbols: @#$%^&*() {}", unique_suffix)),
         );
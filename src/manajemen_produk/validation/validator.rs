@@ -1,4 +1,5 @@
 use crate::manajemen_produk::model::Produk;
+use crate::common::money::Rupiah;
 use super::rules::{
     ValidationRule,
     NamaNotEmpty,
@@ -45,7 +46,7 @@ fn test_validation_edge_cases() {
     let min_produk = Produk::new(
         "A".into(),  // Minimum name length
         "B".into(),  // Minimum category length
-        0.0,         // Minimum price
+        Rupiah::ZERO,         // Minimum price
         0,           // Minimum stock
         None,        // No description
     );
@@ -55,7 +56,7 @@ fn test_validation_edge_cases() {
     let max_produk = Produk::new(
         "Very Long Product Name".into(),
         "Very Long Category Name".into(),
-        f64::MAX,     // Maximum float value
+        Rupiah::from_sen(i64::MAX), // Maximum representable price
         u32::MAX,     // Maximum u32 value
         Some("a".repeat(500)),  // Maximum description length
     );
@@ -68,7 +69,7 @@ fn test_produk_validation_all_rules() {
     let valid_produk = Produk::new(
         "Laptop Gaming".into(),
         "Elektronik".into(),
-        15_000_000.0,
+        Rupiah::from_rupiah(15_000_000),
         10,
         Some("Laptop dengan spesifikasi tinggi".into())
     );
@@ -80,7 +81,7 @@ fn test_produk_validation_all_rules() {
     let invalid_produk = Produk::new(
         "".into(),
         "".into(),
-        -1000.0,
+        Rupiah::from_rupiah(-1000),
         10,
         Some("a".repeat(501))
     );
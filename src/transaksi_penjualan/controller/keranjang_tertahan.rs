@@ -0,0 +1,301 @@
+use rocket::{delete, get, post, put};
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use sqlx::{Any, Pool};
+use autometrics::autometrics;
+
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::transaksi_penjualan::controller::transaksi::{transaksi_error_response, ApiResponse, ErrorResponse};
+use crate::transaksi_penjualan::dto::keranjang_request::{KonversiKeranjangRequest, TahanKeranjangRequest};
+use crate::transaksi_penjualan::model::keranjang_tertahan::KeranjangTertahan;
+use crate::transaksi_penjualan::model::transaksi::Transaksi;
+use crate::transaksi_penjualan::service::keranjang_tertahan::KeranjangTertahanService;
+
+// Keranjang tertahan selalu milik kasir yang sedang login
+#[autometrics]
+#[post("/keranjang", data = "<request>")]
+pub async fn tahan_keranjang(
+    user: AuthenticatedUser,
+    db: &State<Pool<Any>>,
+    request: Json<TahanKeranjangRequest>
+) -> Result<Json<ApiResponse<KeranjangTertahan>>, (Status, Json<ErrorResponse>)> {
+    match KeranjangTertahanService::tahan_keranjang(db.inner().clone(), &user, &request).await {
+        Ok(keranjang) => Ok(Json(ApiResponse::success("Keranjang berhasil ditahan", keranjang))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal menahan keranjang")),
+    }
+}
+
+#[autometrics]
+#[get("/keranjang")]
+pub async fn get_keranjang_kasir(
+    user: AuthenticatedUser,
+    db: &State<Pool<Any>>
+) -> Result<Json<ApiResponse<Vec<KeranjangTertahan>>>, (Status, Json<ErrorResponse>)> {
+    match KeranjangTertahanService::get_keranjang_kasir(db.inner().clone(), &user).await {
+        Ok(keranjang) => Ok(Json(ApiResponse::success("Data keranjang tertahan berhasil diambil", keranjang))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(ErrorResponse::new("Gagal mengambil data keranjang tertahan", "FETCH_ERROR"))
+        ))
+    }
+}
+
+#[autometrics]
+#[get("/keranjang/<id>")]
+pub async fn lanjutkan_keranjang(
+    user: AuthenticatedUser,
+    db: &State<Pool<Any>>,
+    id: i32
+) -> Result<Json<ApiResponse<KeranjangTertahan>>, (Status, Json<ErrorResponse>)> {
+    match KeranjangTertahanService::get_keranjang_by_id(db.inner().clone(), &user, id).await {
+        Ok(keranjang) => Ok(Json(ApiResponse::success("Data keranjang berhasil diambil", keranjang))),
+        Err(e) => Err(transaksi_error_response(e.into(), "Gagal mengambil keranjang")),
+    }
+}
+
+#[autometrics]
+#[put("/keranjang/<id>", data = "<request>")]
+pub async fn update_keranjang(
+    user: AuthenticatedUser,
+    db: &State<Pool<Any>>,
+    id: i32,
+    request: Json<TahanKeranjangRequest>
+) -> Result<Json<ApiResponse<KeranjangTertahan>>, (Status, Json<ErrorResponse>)> {
+    match KeranjangTertahanService::update_keranjang(db.inner().clone(), &user, id, &request).await {
+        Ok(keranjang) => Ok(Json(ApiResponse::success("Keranjang berhasil diperbarui", keranjang))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal memperbarui keranjang")),
+    }
+}
+
+#[autometrics]
+#[delete("/keranjang/<id>")]
+pub async fn delete_keranjang(
+    user: AuthenticatedUser,
+    db: &State<Pool<Any>>,
+    id: i32
+) -> Result<Json<ApiResponse<String>>, (Status, Json<ErrorResponse>)> {
+    match KeranjangTertahanService::delete_keranjang(db.inner().clone(), &user, id).await {
+        Ok(()) => Ok(Json(ApiResponse::success("Keranjang berhasil dihapus", "Deleted".to_string()))),
+        Err(e) => Err(transaksi_error_response(e.into(), "Gagal menghapus keranjang")),
+    }
+}
+
+#[autometrics]
+#[post("/keranjang/<id>/transaksi", data = "<request>")]
+pub async fn konversi_keranjang(
+    user: AuthenticatedUser,
+    db: &State<Pool<Any>>,
+    id: i32,
+    request: Json<KonversiKeranjangRequest>
+) -> Result<Json<ApiResponse<Transaksi>>, (Status, Json<ErrorResponse>)> {
+    match KeranjangTertahanService::konversi_keranjang(db.inner().clone(), &user, id, &request).await {
+        Ok(transaksi) => Ok(Json(ApiResponse::success("Keranjang berhasil dikonversi menjadi transaksi", transaksi))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal mengonversi keranjang")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use rocket::local::asynchronous::Client;
+    use rocket::{routes, uri, async_test};
+    use sqlx::Row;
+    use sqlx::any::install_default_drivers;
+    use crate::auth::controller::auth::{login, AuthForm};
+    use crate::auth::model::user::User;
+    use crate::auth::service::auth::AuthService;
+    use crate::common::money::Rupiah;
+    use crate::transaksi_penjualan::dto::keranjang_request::BarisKeranjangRequest;
+    use crate::transaksi_penjualan::model::keranjang_tertahan::DEFAULT_HELD_CART_TTL_HOURS;
+
+    async fn setup() -> (Client, Pool<Any>) {
+        install_default_drivers();
+
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        for (id, nama, harga, stok) in [(1, "Pasir Cor 1m3", 300_000, 10), (2, "Bata Merah", 1_000, 500)] {
+            sqlx::query("INSERT INTO produk (id, nama, kategori, harga, stok) VALUES ($1, $2, 'Material', CAST($3 AS DECIMAL(15,2)), $4)")
+                .bind(id)
+                .bind(nama)
+                .bind(Rupiah::from_rupiah(harga).to_string())
+                .bind(stok)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+        for username in ["kasir1", "kasir2"] {
+            AuthService::register_user(db.clone(), User::new(username.to_string(), "rahasia123".to_string(), false))
+                .await
+                .unwrap();
+        }
+
+        let rocket = rocket::build()
+            .manage(db.clone())
+            .manage(false)
+            .mount("/", routes![
+                tahan_keranjang, get_keranjang_kasir, lanjutkan_keranjang, update_keranjang,
+                delete_keranjang, konversi_keranjang
+            ])
+            .mount("/api/auth", routes![login]);
+        let client = Client::tracked(rocket).await.expect("Must provide a valid Rocket instance");
+
+        (client, db)
+    }
+
+    async fn login_sebagai(client: &Client, username: &str) {
+        client.post("/api/auth/login")
+            .json(&AuthForm { username: username.to_string(), password: "rahasia123".to_string() })
+            .dispatch()
+            .await;
+    }
+
+    fn request(nama: &str, baris: &[(i32, u32)]) -> TahanKeranjangRequest {
+        TahanKeranjangRequest {
+            nama: nama.to_string(),
+            id_pelanggan: None,
+            nama_pelanggan: None,
+            catatan: None,
+            detail: baris.iter().map(|(id_produk, jumlah)| BarisKeranjangRequest {
+                id_produk: *id_produk,
+                nama_produk: "Dari klien".to_string(),
+                jumlah: *jumlah,
+            }).collect(),
+        }
+    }
+
+    async fn tahan(client: &Client, request: &TahanKeranjangRequest) -> KeranjangTertahan {
+        let response = client.post(uri!(tahan_keranjang)).json(request).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let keranjang: ApiResponse<KeranjangTertahan> = response.into_json().await.unwrap();
+        keranjang.data.unwrap()
+    }
+
+    async fn count(db: &Pool<Any>, table: &str) -> i64 {
+        sqlx::query(&format!("SELECT COUNT(*) AS jumlah FROM {}", table))
+            .fetch_one(db)
+            .await
+            .unwrap()
+            .get("jumlah")
+    }
+
+    #[async_test]
+    async fn test_held_carts_are_per_cashier_and_do_not_touch_stock() {
+        let (client, db) = setup().await;
+
+        let response = client.get(uri!(get_keranjang_kasir)).dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        login_sebagai(&client, "kasir1").await;
+        let keranjang = tahan(&client, &request("Bapak kaos merah", &[(1, 2), (2, 100)])).await;
+        assert_eq!(keranjang.detail.len(), 2);
+        assert_eq!(count(&db, "transaksi").await, 0);
+
+        let response = client.post(uri!(tahan_keranjang)).json(&request("Bapak kaos merah", &[(2, 1)])).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let mut ubah = request("Bapak kaos merah", &[(1, 3)]);
+        ubah.catatan = Some("Tambah pasir".to_string());
+        let response = client.put(uri!(update_keranjang(keranjang.id))).json(&ubah).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.get(uri!(lanjutkan_keranjang(keranjang.id))).dispatch().await;
+        let dilanjutkan: ApiResponse<KeranjangTertahan> = response.into_json().await.unwrap();
+        let dilanjutkan = dilanjutkan.data.unwrap();
+        assert_eq!(dilanjutkan.detail.len(), 1);
+        assert_eq!(dilanjutkan.detail[0].jumlah, 3);
+        assert_eq!(dilanjutkan.catatan.as_deref(), Some("Tambah pasir"));
+
+        // Kasir lain tidak melihat keranjang ini
+        login_sebagai(&client, "kasir2").await;
+        let response = client.get(uri!(get_keranjang_kasir)).dispatch().await;
+        let list: ApiResponse<Vec<KeranjangTertahan>> = response.into_json().await.unwrap();
+        assert!(list.data.unwrap().is_empty());
+        let response = client.get(uri!(lanjutkan_keranjang(keranjang.id))).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+        let response = client.delete(uri!(delete_keranjang(keranjang.id))).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        login_sebagai(&client, "kasir1").await;
+        let response = client.delete(uri!(delete_keranjang(keranjang.id))).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(count(&db, "keranjang_tertahan").await, 0);
+        assert_eq!(count(&db, "detail_keranjang_tertahan").await, 0);
+    }
+
+    #[async_test]
+    async fn test_convert_held_cart_into_transaksi() {
+        let (client, db) = setup().await;
+        login_sebagai(&client, "kasir1").await;
+
+        let keranjang = tahan(&client, &request("Antrian 1", &[(1, 2), (2, 100)])).await;
+
+        // Pelanggan wajib diisi saat konversi bila belum ada di keranjang
+        let response = client.post(uri!(konversi_keranjang(keranjang.id))).json(&KonversiKeranjangRequest::default()).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.post(uri!(konversi_keranjang(keranjang.id)))
+            .json(&KonversiKeranjangRequest { id_pelanggan: Some(5), nama_pelanggan: Some("Pak Darto".to_string()), catatan: None })
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let transaksi: ApiResponse<Transaksi> = response.into_json().await.unwrap();
+        let transaksi = transaksi.data.unwrap();
+        assert_eq!(transaksi.nama_pelanggan, "Pak Darto");
+        assert_eq!(transaksi.total_harga, Rupiah::from_rupiah(700_000));
+        assert_eq!(count(&db, "keranjang_tertahan").await, 0);
+
+        let stok: i32 = sqlx::query("SELECT stok FROM produk WHERE id = 1").fetch_one(&db).await.unwrap().get("stok");
+        assert_eq!(stok, 8);
+
+        let response = client.post(uri!(konversi_keranjang(keranjang.id))).json(&KonversiKeranjangRequest::default()).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+
+        // Stok tidak cukup: keranjang tetap ada
+        let mut kurang = request("Antrian 2", &[(1, 20)]);
+        kurang.id_pelanggan = Some(6);
+        kurang.nama_pelanggan = Some("Bu Sri".to_string());
+        let keranjang = tahan(&client, &kurang).await;
+        let response = client.post(uri!(konversi_keranjang(keranjang.id))).json(&KonversiKeranjangRequest::default()).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+        let error: ErrorResponse = response.into_json().await.unwrap();
+        assert_eq!(error.code, "INSUFFICIENT_STOCK");
+        assert_eq!(count(&db, "keranjang_tertahan").await, 1);
+        assert_eq!(count(&db, "transaksi").await, 1);
+    }
+
+    #[async_test]
+    async fn test_expired_held_carts_are_purged() {
+        let (client, db) = setup().await;
+        login_sebagai(&client, "kasir1").await;
+
+        let lama = tahan(&client, &request("Lama", &[(2, 10)])).await;
+        let baru = tahan(&client, &request("Baru", &[(2, 10)])).await;
+        sqlx::query("UPDATE keranjang_tertahan SET updated_at = $1 WHERE id = $2")
+            .bind((Utc::now() - Duration::hours(DEFAULT_HELD_CART_TTL_HOURS + 1)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+            .bind(lama.id)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let purged = KeranjangTertahanService::purge_expired(&db, Utc::now()).await.unwrap();
+        assert_eq!(purged, vec![lama.id]);
+
+        let response = client.get(uri!(get_keranjang_kasir)).dispatch().await;
+        let list: ApiResponse<Vec<KeranjangTertahan>> = response.into_json().await.unwrap();
+        let list = list.data.unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].id, baru.id);
+        assert_eq!(count(&db, "detail_keranjang_tertahan").await, 1);
+    }
+}
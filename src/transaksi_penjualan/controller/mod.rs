@@ -1,6 +1,11 @@
 use rocket::{fairing::AdHoc, routes};
 
 pub mod transaksi;
+pub mod promosi;
+pub mod retur_penjualan;
+pub mod penawaran;
+pub mod keranjang_tertahan;
+pub mod pengiriman;
 
 pub fn route_stage() -> AdHoc {
     AdHoc::on_ignite("Initializing Transaksi routes...", |rocket| async {
@@ -11,7 +16,37 @@ pub fn route_stage() -> AdHoc {
                 transaksi::get_transaksi_by_id,
                 transaksi::create_transaksi,
                 transaksi::update_transaksi,
-                transaksi::delete_transaksi
+                transaksi::delete_transaksi,
+                transaksi::complete_transaksi,
+                transaksi::cancel_transaksi,
+                transaksi::reopen_transaksi,
+                transaksi::get_riwayat_status,
+                transaksi::get_receipt,
+                transaksi::get_receipt_escpos,
+                promosi::get_all_promosi,
+                promosi::create_promosi,
+                promosi::nonaktifkan_promosi,
+                retur_penjualan::create_retur,
+                retur_penjualan::get_retur_by_transaksi,
+                retur_penjualan::get_saldo_kredit_toko,
+                penawaran::create_penawaran,
+                penawaran::get_penawaran_by_id,
+                penawaran::terima_penawaran,
+                penawaran::tolak_penawaran,
+                penawaran::konversi_penawaran,
+                penawaran::get_dokumen_penawaran,
+                keranjang_tertahan::tahan_keranjang,
+                keranjang_tertahan::get_keranjang_kasir,
+                keranjang_tertahan::lanjutkan_keranjang,
+                keranjang_tertahan::update_keranjang,
+                keranjang_tertahan::delete_keranjang,
+                keranjang_tertahan::konversi_keranjang,
+                pengiriman::create_pengiriman,
+                pengiriman::get_pengiriman_by_transaksi,
+                pengiriman::get_jadwal_pengiriman,
+                pengiriman::get_pengiriman_by_id,
+                pengiriman::jadwal_ulang_pengiriman,
+                pengiriman::update_status_pengiriman
             ],
         )
     })
@@ -0,0 +1,310 @@
+use rocket::{get, post, put};
+use rocket::State;
+use rocket::http::{ContentType, Status};
+use rocket::serde::json::Json;
+use sqlx::{Any, Pool};
+use autometrics::autometrics;
+
+use crate::transaksi_penjualan::controller::transaksi::{transaksi_error_response, ApiResponse, ErrorResponse};
+use crate::transaksi_penjualan::dto::penawaran_request::{CreatePenawaranRequest, KonversiPenawaranRequest};
+use crate::transaksi_penjualan::model::penawaran::Penawaran;
+use crate::transaksi_penjualan::model::receipt::StoreProfile;
+use crate::transaksi_penjualan::model::transaksi::Transaksi;
+use crate::transaksi_penjualan::patterns::strategy::receipt_renderer::ReceiptRendererFactory;
+use crate::transaksi_penjualan::service::penawaran::PenawaranService;
+
+#[autometrics]
+#[post("/penawaran", data = "<request>")]
+pub async fn create_penawaran(
+    db: &State<Pool<Any>>,
+    request: Json<CreatePenawaranRequest>
+) -> Result<Json<ApiResponse<Penawaran>>, (Status, Json<ErrorResponse>)> {
+    match PenawaranService::create_penawaran(db.inner().clone(), &request).await {
+        Ok(penawaran) => Ok(Json(ApiResponse::success("Penawaran harga berhasil dibuat", penawaran))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal membuat penawaran harga")),
+    }
+}
+
+#[autometrics]
+#[get("/penawaran/<id>")]
+pub async fn get_penawaran_by_id(
+    db: &State<Pool<Any>>,
+    id: i32
+) -> Result<Json<ApiResponse<Penawaran>>, (Status, Json<ErrorResponse>)> {
+    match PenawaranService::get_penawaran_by_id(db.inner().clone(), id).await {
+        Ok(penawaran) => Ok(Json(ApiResponse::success("Data penawaran harga berhasil diambil", penawaran))),
+        Err(e) => Err(transaksi_error_response(e.into(), "Gagal mengambil penawaran harga")),
+    }
+}
+
+#[autometrics]
+#[put("/penawaran/<id>/terima")]
+pub async fn terima_penawaran(
+    db: &State<Pool<Any>>,
+    id: i32
+) -> Result<Json<ApiResponse<Penawaran>>, (Status, Json<ErrorResponse>)> {
+    match PenawaranService::terima_penawaran(db.inner().clone(), id).await {
+        Ok(penawaran) => Ok(Json(ApiResponse::success("Penawaran harga diterima", penawaran))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal menerima penawaran harga")),
+    }
+}
+
+#[autometrics]
+#[put("/penawaran/<id>/tolak")]
+pub async fn tolak_penawaran(
+    db: &State<Pool<Any>>,
+    id: i32
+) -> Result<Json<ApiResponse<Penawaran>>, (Status, Json<ErrorResponse>)> {
+    match PenawaranService::tolak_penawaran(db.inner().clone(), id).await {
+        Ok(penawaran) => Ok(Json(ApiResponse::success("Penawaran harga ditolak", penawaran))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal menolak penawaran harga")),
+    }
+}
+
+#[autometrics]
+#[post("/penawaran/<id>/konversi", data = "<request>")]
+pub async fn konversi_penawaran(
+    db: &State<Pool<Any>>,
+    id: i32,
+    request: Json<KonversiPenawaranRequest>
+) -> Result<Json<ApiResponse<Transaksi>>, (Status, Json<ErrorResponse>)> {
+    match PenawaranService::konversi_penawaran(db.inner().clone(), id, &request).await {
+        Ok(transaksi) => Ok(Json(ApiResponse::success("Penawaran harga berhasil dikonversi menjadi transaksi", transaksi))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal mengonversi penawaran harga")),
+    }
+}
+
+// Format sama dengan struk: 58mm, 80mm, html, pdf
+#[autometrics]
+#[get("/penawaran/<id>/dokumen?<format>")]
+pub async fn get_dokumen_penawaran(
+    db: &State<Pool<Any>>,
+    id: i32,
+    format: Option<String>
+) -> Result<(ContentType, Vec<u8>), (Status, Json<ErrorResponse>)> {
+    let format = format.unwrap_or_else(|| "pdf".to_string());
+    let Some(renderer) = ReceiptRendererFactory::create(&format) else {
+        return Err((
+            Status::BadRequest,
+            Json(ErrorResponse::new(&format!("Format dokumen tidak dikenal: {}", format), "INVALID_FORMAT"))
+        ));
+    };
+
+    match PenawaranService::get_penawaran_by_id(db.inner().clone(), id).await {
+        Ok(penawaran) => {
+            let dokumen = PenawaranService::dokumen(&penawaran, StoreProfile::from_env());
+            Ok((renderer.content_type(), renderer.render(&dokumen)))
+        }
+        Err(e) => Err(transaksi_error_response(e.into(), "Gagal membuat dokumen penawaran harga")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use rocket::local::asynchronous::Client;
+    use rocket::{routes, uri, async_test};
+    use sqlx::Row;
+    use sqlx::any::install_default_drivers;
+    use crate::common::money::Rupiah;
+    use crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest;
+    use crate::transaksi_penjualan::model::penawaran::StatusPenawaran;
+    use crate::transaksi_penjualan::service::transaksi::TransaksiService;
+
+    async fn setup() -> (Client, Pool<Any>) {
+        install_default_drivers();
+
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        for (id, nama, harga, stok) in [(1, "Semen Portland 50kg", 100_000, 100), (2, "Besi Beton 10mm", 80_000, 5)] {
+            sqlx::query("INSERT INTO produk (id, nama, kategori, harga, stok) VALUES ($1, $2, 'Material', CAST($3 AS DECIMAL(15,2)), $4)")
+                .bind(id)
+                .bind(nama)
+                .bind(Rupiah::from_rupiah(harga).to_string())
+                .bind(stok)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+
+        let rocket = rocket::build()
+            .manage(db.clone())
+            .mount("/", routes![
+                create_penawaran, get_penawaran_by_id, terima_penawaran, tolak_penawaran,
+                konversi_penawaran, get_dokumen_penawaran
+            ]);
+        let client = Client::tracked(rocket).await.expect("Must provide a valid Rocket instance");
+
+        (client, db)
+    }
+
+    async fn stok(db: &Pool<Any>, id_produk: i32) -> i32 {
+        sqlx::query("SELECT stok FROM produk WHERE id = $1")
+            .bind(id_produk)
+            .fetch_one(db)
+            .await
+            .unwrap()
+            .get("stok")
+    }
+
+    async fn buat_penawaran(client: &Client, baris: &[(i32, u32)]) -> Penawaran {
+        let response = client.post(uri!(create_penawaran))
+            .json(&CreatePenawaranRequest {
+                id_pelanggan: 7,
+                nama_pelanggan: "CV Kontraktor Maju".to_string(),
+                catatan: Some("Proyek ruko".to_string()),
+                berlaku_sampai: None,
+                detail: baris.iter().map(|(id_produk, jumlah)| CreateDetailTransaksiRequest {
+                    id_produk: *id_produk,
+                    nama_produk: "Dari klien".to_string(),
+                    harga_satuan: Rupiah::from_rupiah(1),
+                    jumlah: *jumlah,
+                }).collect(),
+            })
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let penawaran: ApiResponse<Penawaran> = response.into_json().await.unwrap();
+        penawaran.data.unwrap()
+    }
+
+    async fn konversi(client: &Client, id: i32, harga_ulang: bool) -> rocket::local::asynchronous::LocalResponse<'_> {
+        client.post(uri!(konversi_penawaran(id)))
+            .json(&KonversiPenawaranRequest { harga_ulang, catatan: None })
+            .dispatch()
+            .await
+    }
+
+    #[async_test]
+    async fn test_accepted_quotation_converts_at_quoted_price() {
+        let (client, db) = setup().await;
+
+        let penawaran = buat_penawaran(&client, &[(1, 10), (2, 2)]).await;
+        assert_eq!(penawaran.status, StatusPenawaran::Diajukan);
+        assert_eq!(penawaran.total_harga, Rupiah::from_rupiah(1_160_000));
+        assert_eq!(penawaran.detail[0].nama_produk, "Semen Portland 50kg");
+        assert_eq!(stok(&db, 1).await, 100);
+
+        // Belum diterima
+        let response = konversi(&client, penawaran.id, false).await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.put(uri!(terima_penawaran(penawaran.id))).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        // Harga katalog naik setelah penawaran dibuat
+        sqlx::query("UPDATE produk SET harga = CAST($1 AS DECIMAL(15,2)) WHERE id = 1")
+            .bind(Rupiah::from_rupiah(120_000).to_string())
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let response = konversi(&client, penawaran.id, false).await;
+        assert_eq!(response.status(), Status::Ok);
+        let transaksi: ApiResponse<Transaksi> = response.into_json().await.unwrap();
+        let transaksi = transaksi.data.unwrap();
+        assert_eq!(transaksi.id_penawaran, Some(penawaran.id));
+        assert_eq!(transaksi.total_harga, penawaran.total_harga);
+        assert_eq!(transaksi.catatan.as_deref(), Some("Proyek ruko"));
+        assert_eq!(stok(&db, 1).await, 90);
+        assert_eq!(stok(&db, 2).await, 3);
+
+        let tersimpan = TransaksiService::get_transaksi_by_id(db.clone(), transaksi.id).await.unwrap();
+        assert_eq!(tersimpan.id_penawaran, Some(penawaran.id));
+
+        let response = client.get(uri!(get_penawaran_by_id(penawaran.id))).dispatch().await;
+        let penawaran: ApiResponse<Penawaran> = response.into_json().await.unwrap();
+        let penawaran = penawaran.data.unwrap();
+        assert_eq!(penawaran.status, StatusPenawaran::Dikonversi);
+        assert_eq!(penawaran.id_transaksi, Some(transaksi.id));
+
+        // Tidak bisa dikonversi dua kali
+        let response = konversi(&client, penawaran.id, false).await;
+        assert_eq!(response.status(), Status::BadRequest);
+        assert_eq!(stok(&db, 1).await, 90);
+    }
+
+    #[async_test]
+    async fn test_conversion_can_reprice_and_rejects_insufficient_stock() {
+        let (client, db) = setup().await;
+
+        let penawaran = buat_penawaran(&client, &[(1, 1)]).await;
+        client.put(uri!(terima_penawaran(penawaran.id))).dispatch().await;
+        sqlx::query("UPDATE produk SET harga = CAST($1 AS DECIMAL(15,2)) WHERE id = 1")
+            .bind(Rupiah::from_rupiah(120_000).to_string())
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let response = konversi(&client, penawaran.id, true).await;
+        let transaksi: ApiResponse<Transaksi> = response.into_json().await.unwrap();
+        assert_eq!(transaksi.data.unwrap().total_harga, Rupiah::from_rupiah(120_000));
+
+        // Stok besi hanya 5; penawaran tetap boleh dibuat tetapi konversinya ditolak
+        let penawaran = buat_penawaran(&client, &[(1, 3), (2, 6)]).await;
+        client.put(uri!(terima_penawaran(penawaran.id))).dispatch().await;
+        let response = konversi(&client, penawaran.id, false).await;
+        assert_eq!(response.status(), Status::BadRequest);
+        let error: ErrorResponse = response.into_json().await.unwrap();
+        assert_eq!(error.code, "INSUFFICIENT_STOCK");
+        assert_eq!(stok(&db, 1).await, 99);
+
+        let penawaran = PenawaranService::get_penawaran_by_id(db.clone(), penawaran.id).await.unwrap();
+        assert_eq!(penawaran.status, StatusPenawaran::Diterima);
+    }
+
+    #[async_test]
+    async fn test_expired_quotation_cannot_be_accepted_or_converted() {
+        let (client, db) = setup().await;
+
+        let diterima = buat_penawaran(&client, &[(1, 2)]).await;
+        client.put(uri!(terima_penawaran(diterima.id))).dispatch().await;
+        let diajukan = buat_penawaran(&client, &[(1, 2)]).await;
+
+        sqlx::query("UPDATE penawaran_harga SET berlaku_sampai = $1")
+            .bind((Utc::now() - Duration::days(1)).to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let response = konversi(&client, diterima.id, false).await;
+        assert_eq!(response.status(), Status::BadRequest);
+        let response = client.put(uri!(terima_penawaran(diajukan.id))).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+        let response = client.put(uri!(tolak_penawaran(diajukan.id))).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(stok(&db, 1).await, 100);
+
+        let response = client.get(uri!(get_penawaran_by_id(999))).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[async_test]
+    async fn test_quotation_document() {
+        let (client, _) = setup().await;
+        let penawaran = buat_penawaran(&client, &[(1, 10)]).await;
+
+        let response = client.get(uri!(get_dokumen_penawaran(penawaran.id, Some("80mm")))).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+        let text = response.into_string().await.unwrap();
+        assert!(text.contains("PENAWARAN HARGA"));
+        assert!(text.contains(&format!("QUO-{:06}", penawaran.id)));
+        assert!(text.contains("Berlaku s/d"));
+        assert!(text.contains("Semen Portland 50kg"));
+
+        let response = client.get(uri!(get_dokumen_penawaran(penawaran.id, None::<String>))).dispatch().await;
+        assert_eq!(response.content_type(), Some(ContentType::PDF));
+        assert!(response.into_bytes().await.unwrap().starts_with(b"%PDF"));
+    }
+}
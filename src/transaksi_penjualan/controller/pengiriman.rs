@@ -0,0 +1,399 @@
+use chrono::{NaiveDate, Utc};
+use rocket::{get, post, put};
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use sqlx::{Any, Pool};
+use autometrics::autometrics;
+
+use crate::transaksi_penjualan::controller::transaksi::{transaksi_error_response, ApiResponse, ErrorResponse};
+use crate::transaksi_penjualan::dto::pengiriman_request::{
+    CreatePengirimanRequest, JadwalPengirimanRequest, UpdateStatusPengirimanRequest,
+};
+use crate::transaksi_penjualan::model::pengiriman::Pengiriman;
+use crate::transaksi_penjualan::service::pengiriman::PengirimanService;
+
+#[autometrics]
+#[post("/transaksi/<id>/pengiriman", data = "<request>")]
+pub async fn create_pengiriman(
+    db: &State<Pool<Any>>,
+    id: i32,
+    request: Json<CreatePengirimanRequest>
+) -> Result<Json<ApiResponse<Pengiriman>>, (Status, Json<ErrorResponse>)> {
+    match PengirimanService::create_pengiriman(db.inner().clone(), id, &request).await {
+        Ok(pengiriman) => Ok(Json(ApiResponse::success("Pengiriman berhasil dijadwalkan", pengiriman))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal menjadwalkan pengiriman")),
+    }
+}
+
+#[autometrics]
+#[get("/transaksi/<id>/pengiriman")]
+pub async fn get_pengiriman_by_transaksi(
+    db: &State<Pool<Any>>,
+    id: i32
+) -> Result<Json<ApiResponse<Vec<Pengiriman>>>, (Status, Json<ErrorResponse>)> {
+    match PengirimanService::get_pengiriman_by_transaksi(db.inner().clone(), id).await {
+        Ok(pengiriman) => Ok(Json(ApiResponse::success("Data pengiriman berhasil diambil", pengiriman))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(ErrorResponse::new("Gagal mengambil data pengiriman", "FETCH_ERROR"))
+        ))
+    }
+}
+
+// Daftar pengiriman harian untuk bagian gudang; tanpa `tanggal` memakai hari ini (YYYY-MM-DD)
+#[autometrics]
+#[get("/pengiriman/jadwal?<tanggal>")]
+pub async fn get_jadwal_pengiriman(
+    db: &State<Pool<Any>>,
+    tanggal: Option<&str>
+) -> Result<Json<ApiResponse<Vec<Pengiriman>>>, (Status, Json<ErrorResponse>)> {
+    let tanggal = match tanggal {
+        Some(tanggal) => NaiveDate::parse_from_str(tanggal, "%Y-%m-%d").map_err(|_| (
+            Status::BadRequest,
+            Json(ErrorResponse::new("Format tanggal harus YYYY-MM-DD", "VALIDATION_ERROR"))
+        ))?,
+        None => Utc::now().date_naive(),
+    };
+
+    match PengirimanService::get_jadwal(db.inner().clone(), tanggal).await {
+        Ok(pengiriman) => Ok(Json(ApiResponse::success("Jadwal pengiriman berhasil diambil", pengiriman))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(ErrorResponse::new("Gagal mengambil jadwal pengiriman", "FETCH_ERROR"))
+        ))
+    }
+}
+
+#[autometrics]
+#[get("/pengiriman/<id>")]
+pub async fn get_pengiriman_by_id(
+    db: &State<Pool<Any>>,
+    id: i32
+) -> Result<Json<ApiResponse<Pengiriman>>, (Status, Json<ErrorResponse>)> {
+    match PengirimanService::get_pengiriman_by_id(db.inner().clone(), id).await {
+        Ok(pengiriman) => Ok(Json(ApiResponse::success("Data pengiriman berhasil diambil", pengiriman))),
+        Err(e) => Err(transaksi_error_response(e.into(), "Gagal mengambil data pengiriman")),
+    }
+}
+
+#[autometrics]
+#[put("/pengiriman/<id>", data = "<request>")]
+pub async fn jadwal_ulang_pengiriman(
+    db: &State<Pool<Any>>,
+    id: i32,
+    request: Json<JadwalPengirimanRequest>
+) -> Result<Json<ApiResponse<Pengiriman>>, (Status, Json<ErrorResponse>)> {
+    match PengirimanService::jadwal_ulang(db.inner().clone(), id, &request).await {
+        Ok(pengiriman) => Ok(Json(ApiResponse::success("Jadwal pengiriman berhasil diubah", pengiriman))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal mengubah jadwal pengiriman")),
+    }
+}
+
+#[autometrics]
+#[put("/pengiriman/<id>/status", data = "<request>")]
+pub async fn update_status_pengiriman(
+    db: &State<Pool<Any>>,
+    id: i32,
+    request: Json<UpdateStatusPengirimanRequest>
+) -> Result<Json<ApiResponse<Pengiriman>>, (Status, Json<ErrorResponse>)> {
+    match PengirimanService::update_status(db.inner().clone(), id, &request).await {
+        Ok(pengiriman) => Ok(Json(ApiResponse::success("Status pengiriman berhasil diubah", pengiriman))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal mengubah status pengiriman")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Days;
+    use rocket::http::ContentType;
+    use rocket::local::asynchronous::Client;
+    use rocket::{routes, uri, async_test};
+    use sqlx::any::install_default_drivers;
+    use crate::common::money::Rupiah;
+    use crate::transaksi_penjualan::dto::pengiriman_request::CreateDetailPengirimanRequest;
+    use crate::transaksi_penjualan::dto::transaksi_request::{CreateDetailTransaksiRequest, CreateTransaksiRequest};
+    use crate::transaksi_penjualan::model::pengiriman::StatusPengiriman;
+    use crate::transaksi_penjualan::model::riwayat_status::PerubahanStatus;
+    use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+    use crate::transaksi_penjualan::service::transaksi::TransaksiService;
+
+    // Transaksi selesai berisi 1000 bata dan 20 besi beton untuk pelanggan yang punya alamat
+    async fn setup() -> (Client, Pool<Any>, i32, Vec<i32>) {
+        install_default_drivers();
+
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        for (id, nama, harga, stok) in [(1, "Bata Merah", 800, 5000), (2, "Besi Beton 10mm", 85_000, 200)] {
+            sqlx::query("INSERT INTO produk (id, nama, kategori, harga, stok) VALUES ($1, $2, 'Material', CAST($3 AS DECIMAL(15,2)), $4)")
+                .bind(id)
+                .bind(nama)
+                .bind(Rupiah::from_rupiah(harga).to_string())
+                .bind(stok)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+        sqlx::query("INSERT INTO pelanggan (id, nama, alamat, no_telp, tanggal_gabung) VALUES (7, 'CV Bangun Jaya', 'Jl. Raya Bogor Km 30', '0812', '2025-01-01')")
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let transaksi = TransaksiService::create_transaksi_with_details(db.clone(), &CreateTransaksiRequest {
+            id_pelanggan: 7,
+            nama_pelanggan: "CV Bangun Jaya".to_string(),
+            catatan: None,
+            detail_transaksi: [(1, 1000), (2, 20)].iter().map(|(id_produk, jumlah)| CreateDetailTransaksiRequest {
+                id_produk: *id_produk,
+                nama_produk: "Dari klien".to_string(),
+                harga_satuan: Rupiah::from_rupiah(1),
+                jumlah: *jumlah,
+            }).collect(),
+        }).await.unwrap();
+        TransaksiService::complete_transaksi(db.clone(), transaksi.id, &PerubahanStatus::oleh(None, None)).await.unwrap();
+
+        let mut conn = db.acquire().await.unwrap();
+        let details = TransaksiRepository::get_detail_by_transaksi_id(&mut conn, transaksi.id).await.unwrap();
+        drop(conn);
+
+        let rocket = rocket::build()
+            .manage(db.clone())
+            .mount("/", routes![
+                create_pengiriman,
+                get_pengiriman_by_transaksi,
+                get_jadwal_pengiriman,
+                get_pengiriman_by_id,
+                jadwal_ulang_pengiriman,
+                update_status_pengiriman
+            ]);
+        let client = Client::tracked(rocket).await.expect("Must provide a valid Rocket instance");
+
+        (client, db, transaksi.id, details.iter().map(|d| d.id).collect())
+    }
+
+    fn body(tanggal_kirim: NaiveDate, alamat: Option<&str>, baris: &[(i32, u32)]) -> String {
+        rocket::serde::json::to_string(&CreatePengirimanRequest {
+            alamat: alamat.map(str::to_string),
+            tanggal_kirim,
+            kendaraan: Some("Truk B 9123 KX".to_string()),
+            pengemudi: Some("Joko".to_string()),
+            catatan: None,
+            detail: baris.iter().map(|(id, jumlah)| CreateDetailPengirimanRequest {
+                id_detail_transaksi: *id,
+                jumlah: *jumlah,
+            }).collect(),
+        }).unwrap()
+    }
+
+    fn status_body(status: StatusPengiriman, bukti: Option<&str>) -> String {
+        rocket::serde::json::to_string(&UpdateStatusPengirimanRequest {
+            status,
+            bukti_pengiriman: bukti.map(str::to_string),
+            penerima: Some("Pak Mandor".to_string()),
+        }).unwrap()
+    }
+
+    async fn buat(client: &Client, id: i32, body: String) -> (Status, Option<Pengiriman>) {
+        let response = client.post(uri!(create_pengiriman(id)))
+            .header(ContentType::JSON)
+            .body(body)
+            .dispatch()
+            .await;
+        let status = response.status();
+        let data: Option<ApiResponse<Pengiriman>> = response.into_json().await;
+        (status, data.and_then(|d| d.data))
+    }
+
+    #[async_test]
+    async fn test_partial_delivery_defaults_address_and_limits_quantity() {
+        let (client, _db, id, detail) = setup().await;
+        let hari_ini = Utc::now().date_naive();
+
+        let (status, pengiriman) = buat(&client, id, body(hari_ini, None, &[(detail[0], 600), (detail[1], 20)])).await;
+        assert_eq!(status, Status::Ok);
+        let pengiriman = pengiriman.unwrap();
+        assert_eq!(pengiriman.alamat, "Jl. Raya Bogor Km 30");
+        assert_eq!(pengiriman.nama_pelanggan, "CV Bangun Jaya");
+        assert_eq!(pengiriman.status, StatusPengiriman::Dijadwalkan);
+        assert_eq!(pengiriman.detail[0].nama_produk, "Bata Merah");
+        assert_eq!(pengiriman.detail[0].jumlah, 600);
+
+        // Sisa bata 400, besi sudah habis terjadwal
+        let (status, _) = buat(&client, id, body(hari_ini, None, &[(detail[0], 401)])).await;
+        assert_eq!(status, Status::BadRequest);
+        let (status, _) = buat(&client, id, body(hari_ini, None, &[(detail[1], 1)])).await;
+        assert_eq!(status, Status::BadRequest);
+
+        let besok = hari_ini.checked_add_days(Days::new(1)).unwrap();
+        let (status, kedua) = buat(&client, id, body(besok, Some("Proyek Cibubur Blok C"), &[(detail[0], 400)])).await;
+        assert_eq!(status, Status::Ok);
+        assert_eq!(kedua.unwrap().alamat, "Proyek Cibubur Blok C");
+
+        let response = client.get(uri!(get_pengiriman_by_transaksi(id))).dispatch().await;
+        let list: ApiResponse<Vec<Pengiriman>> = response.into_json().await.unwrap();
+        assert_eq!(list.data.unwrap().len(), 2);
+    }
+
+    #[async_test]
+    async fn test_returned_quantity_is_not_delivered() {
+        use rocket::State;
+        use crate::transaksi_penjualan::dto::retur_request::{CreateDetailReturRequest, CreateReturRequest};
+        use crate::transaksi_penjualan::model::retur_penjualan::MetodePengembalian;
+        use crate::transaksi_penjualan::service::retur_penjualan::ReturPenjualanService;
+
+        let (client, _db, id, detail) = setup().await;
+        let hari_ini = Utc::now().date_naive();
+
+        ReturPenjualanService::create_retur(State::get(client.rocket()).unwrap(), None, id, &CreateReturRequest {
+            alasan: "BARANG_RUSAK".to_string(),
+            metode_pengembalian: MetodePengembalian::KreditToko,
+            catatan: None,
+            detail: vec![CreateDetailReturRequest { id_detail_transaksi: detail[0], jumlah: 100, dapat_dijual_kembali: false }],
+        }).await.unwrap();
+
+        // 1000 bata terjual, 100 diretur: hanya 900 yang masih bisa dikirim
+        let (status, _) = buat(&client, id, body(hari_ini, None, &[(detail[0], 901)])).await;
+        assert_eq!(status, Status::BadRequest);
+        let (status, _) = buat(&client, id, body(hari_ini, None, &[(detail[0], 900)])).await;
+        assert_eq!(status, Status::Ok);
+    }
+
+    #[async_test]
+    async fn test_status_flow_and_failed_delivery_frees_quantity() {
+        let (client, _db, id, detail) = setup().await;
+        let hari_ini = Utc::now().date_naive();
+        let (_, pengiriman) = buat(&client, id, body(hari_ini, None, &[(detail[0], 1000)])).await;
+        let pengiriman = pengiriman.unwrap();
+
+        // Tidak bisa langsung terkirim tanpa berangkat
+        let response = client.put(uri!(update_status_pengiriman(pengiriman.id)))
+            .header(ContentType::JSON)
+            .body(status_body(StatusPengiriman::Terkirim, Some("Diterima")))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.put(uri!(update_status_pengiriman(pengiriman.id)))
+            .header(ContentType::JSON)
+            .body(status_body(StatusPengiriman::DalamPerjalanan, None))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        // Setelah berangkat jadwal tidak bisa diubah
+        let response = client.put(uri!(jadwal_ulang_pengiriman(pengiriman.id)))
+            .header(ContentType::JSON)
+            .body(rocket::serde::json::to_string(&JadwalPengirimanRequest::default()).unwrap())
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.put(uri!(update_status_pengiriman(pengiriman.id)))
+            .header(ContentType::JSON)
+            .body(status_body(StatusPengiriman::Gagal, Some("Akses jalan ditutup")))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.get(uri!(get_pengiriman_by_id(pengiriman.id))).dispatch().await;
+        let gagal: ApiResponse<Pengiriman> = response.into_json().await.unwrap();
+        let gagal = gagal.data.unwrap();
+        assert_eq!(gagal.status, StatusPengiriman::Gagal);
+        assert_eq!(gagal.bukti_pengiriman.as_deref(), Some("Akses jalan ditutup"));
+
+        // Barang dari pengiriman gagal bisa dijadwalkan ulang
+        let (status, ulang) = buat(&client, id, body(hari_ini, None, &[(detail[0], 1000)])).await;
+        assert_eq!(status, Status::Ok);
+        let ulang = ulang.unwrap();
+
+        for (status, bukti) in [(StatusPengiriman::DalamPerjalanan, None), (StatusPengiriman::Terkirim, Some("Diterima lengkap, 1000 bata"))] {
+            let response = client.put(uri!(update_status_pengiriman(ulang.id)))
+                .header(ContentType::JSON)
+                .body(status_body(status, bukti))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
+        }
+
+        let response = client.get(uri!(get_pengiriman_by_id(ulang.id))).dispatch().await;
+        let terkirim: ApiResponse<Pengiriman> = response.into_json().await.unwrap();
+        let terkirim = terkirim.data.unwrap();
+        assert_eq!(terkirim.status, StatusPengiriman::Terkirim);
+        assert_eq!(terkirim.penerima.as_deref(), Some("Pak Mandor"));
+    }
+
+    #[async_test]
+    async fn test_daily_dispatch_list_by_date() {
+        let (client, _db, id, detail) = setup().await;
+        let hari_ini = Utc::now().date_naive();
+        let besok = hari_ini.checked_add_days(Days::new(1)).unwrap();
+
+        buat(&client, id, body(hari_ini, None, &[(detail[0], 500)])).await.1.unwrap();
+        let (_, kedua) = buat(&client, id, body(hari_ini, None, &[(detail[1], 10)])).await;
+        let kedua = kedua.unwrap();
+
+        // Pindah ke besok bersama kendaraan lain
+        let response = client.put(uri!(jadwal_ulang_pengiriman(kedua.id)))
+            .header(ContentType::JSON)
+            .body(rocket::serde::json::to_string(&JadwalPengirimanRequest {
+                tanggal_kirim: Some(besok),
+                kendaraan: Some("Pickup B 1234 CD".to_string()),
+                ..Default::default()
+            }).unwrap())
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.get("/pengiriman/jadwal").dispatch().await;
+        let jadwal: ApiResponse<Vec<Pengiriman>> = response.into_json().await.unwrap();
+        let jadwal = jadwal.data.unwrap();
+        assert_eq!(jadwal.len(), 1);
+        assert_eq!(jadwal[0].detail[0].jumlah, 500);
+
+        let response = client.get(format!("/pengiriman/jadwal?tanggal={}", besok.format("%Y-%m-%d"))).dispatch().await;
+        let jadwal: ApiResponse<Vec<Pengiriman>> = response.into_json().await.unwrap();
+        let jadwal = jadwal.data.unwrap();
+        assert_eq!(jadwal.len(), 1);
+        assert_eq!(jadwal[0].id, kedua.id);
+        assert_eq!(jadwal[0].kendaraan.as_deref(), Some("Pickup B 1234 CD"));
+
+        let response = client.get("/pengiriman/jadwal?tanggal=kemarin").dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[async_test]
+    async fn test_delivery_requires_completed_transaksi() {
+        let (client, db, _id, _detail) = setup().await;
+
+        let transaksi = TransaksiService::create_transaksi_with_details(db.clone(), &CreateTransaksiRequest {
+            id_pelanggan: 7,
+            nama_pelanggan: "CV Bangun Jaya".to_string(),
+            catatan: None,
+            detail_transaksi: vec![CreateDetailTransaksiRequest {
+                id_produk: 1,
+                nama_produk: "Bata Merah".to_string(),
+                harga_satuan: Rupiah::from_rupiah(800),
+                jumlah: 100,
+            }],
+        }).await.unwrap();
+        let mut conn = db.acquire().await.unwrap();
+        let detail = TransaksiRepository::get_detail_by_transaksi_id(&mut conn, transaksi.id).await.unwrap();
+        drop(conn);
+
+        let (status, _) = buat(&client, transaksi.id, body(Utc::now().date_naive(), None, &[(detail[0].id, 100)])).await;
+        assert_eq!(status, Status::BadRequest);
+
+        let response = client.get(uri!(get_pengiriman_by_id(999))).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
@@ -0,0 +1,141 @@
+use rocket::{get, post, delete};
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use sqlx::{Any, Pool};
+use autometrics::autometrics;
+
+use crate::transaksi_penjualan::controller::transaksi::{transaksi_error_response, ApiResponse, ErrorResponse};
+use crate::transaksi_penjualan::dto::promosi_request::CreatePromosiRequest;
+use crate::transaksi_penjualan::model::promosi::Promosi;
+use crate::transaksi_penjualan::service::promosi::PromosiService;
+
+#[autometrics]
+#[get("/promosi")]
+pub async fn get_all_promosi(
+    db: &State<Pool<Any>>
+) -> Result<Json<ApiResponse<Vec<Promosi>>>, (Status, Json<ErrorResponse>)> {
+    match PromosiService::get_all_promosi(db.inner().clone()).await {
+        Ok(promosi) => Ok(Json(ApiResponse::success("Data promosi berhasil diambil", promosi))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(ErrorResponse::new("Gagal mengambil data promosi", "FETCH_ERROR"))
+        ))
+    }
+}
+
+#[autometrics]
+#[post("/promosi", data = "<request>")]
+pub async fn create_promosi(
+    db: &State<Pool<Any>>,
+    request: Json<CreatePromosiRequest>
+) -> Result<Json<ApiResponse<Promosi>>, (Status, Json<ErrorResponse>)> {
+    match PromosiService::create_promosi(db.inner().clone(), &request).await {
+        Ok(promosi) => Ok(Json(ApiResponse::success("Promosi berhasil dibuat", promosi))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal membuat promosi")),
+    }
+}
+
+#[autometrics]
+#[delete("/promosi/<id>")]
+pub async fn nonaktifkan_promosi(
+    db: &State<Pool<Any>>,
+    id: i32
+) -> Result<Json<ApiResponse<String>>, (Status, Json<ErrorResponse>)> {
+    match PromosiService::nonaktifkan_promosi(db.inner().clone(), id).await {
+        Ok(_) => Ok(Json(ApiResponse::success("Promosi berhasil dinonaktifkan", "Deactivated".to_string()))),
+        Err(sqlx::Error::RowNotFound) => Err((
+            Status::NotFound,
+            Json(ErrorResponse::new("Promosi tidak ditemukan atau sudah nonaktif", "NOT_FOUND"))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(ErrorResponse::new("Gagal menonaktifkan promosi", "INTERNAL_ERROR"))
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::ContentType;
+    use rocket::local::asynchronous::Client;
+    use rocket::{routes, uri, async_test};
+    use sqlx::any::install_default_drivers;
+
+    async fn setup() -> Client {
+        install_default_drivers();
+
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        let rocket = rocket::build()
+            .manage(db)
+            .mount("/", routes![get_all_promosi, create_promosi, nonaktifkan_promosi]);
+
+        Client::tracked(rocket).await.expect("Must provide a valid Rocket instance")
+    }
+
+    #[async_test]
+    async fn test_create_list_and_deactivate_promosi() {
+        let client = setup().await;
+
+        let response = client.post(uri!(create_promosi))
+            .header(ContentType::JSON)
+            .body(r#"{
+                "nama": "Grosir Semen",
+                "aturan": {"jenis": "HARGA_BERTINGKAT", "tingkat": [{"min_jumlah": 10, "harga_satuan": "95000.00"}]},
+                "cakupan": {"jenis": "KATEGORI", "kategori": "Semen"},
+                "id_pelanggan": null,
+                "berlaku_mulai": "2025-01-01T00:00:00Z",
+                "berlaku_sampai": null,
+                "prioritas": 5
+            }"#)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let created: ApiResponse<Promosi> = response.into_json().await.unwrap();
+        let created = created.data.unwrap();
+        assert_eq!(created.prioritas, 5);
+        assert!(created.dapat_digabung && created.aktif);
+
+        let response = client.get(uri!(get_all_promosi)).dispatch().await;
+        let list: ApiResponse<Vec<Promosi>> = response.into_json().await.unwrap();
+        assert_eq!(list.data.unwrap(), vec![created.clone()]);
+
+        let response = client.delete(uri!(nonaktifkan_promosi(created.id))).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let response = client.delete(uri!(nonaktifkan_promosi(created.id))).dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[async_test]
+    async fn test_create_promosi_rejects_invalid_rule() {
+        let client = setup().await;
+
+        let response = client.post(uri!(create_promosi))
+            .header(ContentType::JSON)
+            .body(r#"{
+                "nama": "Harga Khusus",
+                "aturan": {"jenis": "HARGA_PELANGGAN", "harga_satuan": "90000.00"},
+                "cakupan": {"jenis": "PRODUK", "id_produk": 1},
+                "id_pelanggan": null,
+                "berlaku_mulai": null,
+                "berlaku_sampai": null
+            }"#)
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::BadRequest);
+        let error: ErrorResponse = response.into_json().await.unwrap();
+        assert_eq!(error.code, "VALIDATION_ERROR");
+    }
+}
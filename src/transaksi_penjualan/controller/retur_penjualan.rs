@@ -0,0 +1,292 @@
+use rocket::{get, post};
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use sqlx::{Any, Pool};
+use autometrics::autometrics;
+
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::transaksi_penjualan::controller::transaksi::{transaksi_error_response, ApiResponse, ErrorResponse};
+use crate::transaksi_penjualan::dto::retur_request::CreateReturRequest;
+use crate::transaksi_penjualan::model::retur_penjualan::{ReturPenjualan, SaldoKreditToko};
+use crate::transaksi_penjualan::service::retur_penjualan::ReturPenjualanService;
+
+#[autometrics]
+#[post("/transaksi/<id>/retur", data = "<request>")]
+pub async fn create_retur(
+    kasir: Option<AuthenticatedUser>,
+    db: &State<Pool<Any>>,
+    id: i32,
+    request: Json<CreateReturRequest>
+) -> Result<Json<ApiResponse<ReturPenjualan>>, (Status, Json<ErrorResponse>)> {
+    match ReturPenjualanService::create_retur(db, kasir.as_ref(), id, &request).await {
+        Ok(retur) => Ok(Json(ApiResponse::success("Retur penjualan berhasil dibuat", retur))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal membuat retur penjualan")),
+    }
+}
+
+#[autometrics]
+#[get("/transaksi/<id>/retur")]
+pub async fn get_retur_by_transaksi(
+    db: &State<Pool<Any>>,
+    id: i32
+) -> Result<Json<ApiResponse<Vec<ReturPenjualan>>>, (Status, Json<ErrorResponse>)> {
+    match ReturPenjualanService::get_retur_by_transaksi(db.inner().clone(), id).await {
+        Ok(retur) => Ok(Json(ApiResponse::success("Data retur penjualan berhasil diambil", retur))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(ErrorResponse::new("Gagal mengambil data retur penjualan", "FETCH_ERROR"))
+        ))
+    }
+}
+
+#[autometrics]
+#[get("/kredit-toko/<id_pelanggan>")]
+pub async fn get_saldo_kredit_toko(
+    db: &State<Pool<Any>>,
+    id_pelanggan: i32
+) -> Result<Json<ApiResponse<SaldoKreditToko>>, (Status, Json<ErrorResponse>)> {
+    match ReturPenjualanService::get_saldo_kredit_toko(db.inner().clone(), id_pelanggan).await {
+        Ok(saldo) => Ok(Json(ApiResponse::success("Saldo kredit toko berhasil diambil", saldo))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(ErrorResponse::new("Gagal mengambil saldo kredit toko", "FETCH_ERROR"))
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rocket::http::ContentType;
+    use rocket::local::asynchronous::Client;
+    use rocket::{routes, uri, async_test};
+    use sqlx::Row;
+    use sqlx::any::install_default_drivers;
+    use crate::common::money::Rupiah;
+    use crate::transaksi_penjualan::dto::transaksi_request::{CreateDetailTransaksiRequest, CreateTransaksiRequest};
+    use crate::transaksi_penjualan::model::retur_penjualan::MetodePengembalian;
+    use crate::transaksi_penjualan::model::riwayat_status::PerubahanStatus;
+    use crate::transaksi_penjualan::service::transaksi::TransaksiService;
+
+    // Transaksi selesai berisi 4 semen (100.000) dan 2 cat (250.000), dibayar lunas
+    async fn setup() -> (Client, Pool<Any>, i32, Vec<i32>) {
+        setup_on("sqlite::memory:", 1).await
+    }
+
+    async fn setup_on(url: &str, max_connections: u32) -> (Client, Pool<Any>, i32, Vec<i32>) {
+        install_default_drivers();
+
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await
+            .unwrap();
+
+        sqlx::migrate!("migrations/test")
+            .run(&db)
+            .await
+            .unwrap();
+
+        for (id, nama, harga, stok) in [(1, "Semen Portland 50kg", 100_000, 100), (2, "Cat Tembok 5kg", 250_000, 50)] {
+            sqlx::query("INSERT INTO produk (id, nama, kategori, harga, stok) VALUES ($1, $2, 'Material', CAST($3 AS DECIMAL(15,2)), $4)")
+                .bind(id)
+                .bind(nama)
+                .bind(Rupiah::from_rupiah(harga).to_string())
+                .bind(stok)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+
+        let transaksi = TransaksiService::create_transaksi_with_details(db.clone(), &CreateTransaksiRequest {
+            id_pelanggan: 7,
+            nama_pelanggan: "Retur Test".to_string(),
+            catatan: None,
+            detail_transaksi: [(1, 4), (2, 2)].iter().map(|(id_produk, jumlah)| CreateDetailTransaksiRequest {
+                id_produk: *id_produk,
+                nama_produk: "Dari klien".to_string(),
+                harga_satuan: Rupiah::from_rupiah(1),
+                jumlah: *jumlah,
+            }).collect(),
+        }).await.unwrap();
+        let transaksi = TransaksiService::complete_transaksi(db.clone(), transaksi.id, &PerubahanStatus::oleh(None, None)).await.unwrap();
+
+        sqlx::query("
+            INSERT INTO payments (id, transaction_id, amount, method, status, payment_date, due_date)
+            VALUES ('PMT-1', $1, CAST($2 AS DECIMAL(15,2)), 'CASH', 'LUNAS', $3, NULL)
+        ")
+            .bind(transaksi.id.to_string())
+            .bind(transaksi.total_harga.to_string())
+            .bind(Utc::now().to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let mut conn = db.acquire().await.unwrap();
+        let details = crate::transaksi_penjualan::repository::transaksi::TransaksiRepository::get_detail_by_transaksi_id(&mut conn, transaksi.id)
+            .await
+            .unwrap();
+        drop(conn);
+
+        let rocket = rocket::build()
+            .manage(db.clone())
+            .mount("/", routes![create_retur, get_retur_by_transaksi, get_saldo_kredit_toko]);
+        let client = Client::tracked(rocket).await.expect("Must provide a valid Rocket instance");
+
+        (client, db, transaksi.id, details.iter().map(|d| d.id).collect())
+    }
+
+    async fn stok(db: &Pool<Any>, id_produk: i32) -> i32 {
+        sqlx::query("SELECT stok FROM produk WHERE id = $1")
+            .bind(id_produk)
+            .fetch_one(db)
+            .await
+            .unwrap()
+            .get("stok")
+    }
+
+    fn body(metode: MetodePengembalian, baris: &[(i32, u32, bool)]) -> String {
+        rocket::serde::json::to_string(&CreateReturRequest {
+            alasan: "BARANG_RUSAK".to_string(),
+            metode_pengembalian: metode,
+            catatan: Some("Karung sobek".to_string()),
+            detail: baris.iter().map(|(id, jumlah, dapat_dijual_kembali)| crate::transaksi_penjualan::dto::retur_request::CreateDetailReturRequest {
+                id_detail_transaksi: *id,
+                jumlah: *jumlah,
+                dapat_dijual_kembali: *dapat_dijual_kembali,
+            }).collect(),
+        }).unwrap()
+    }
+
+    #[async_test]
+    async fn test_partial_return_with_refund_restores_resellable_stock() {
+        let (client, db, id, detail) = setup().await;
+        let total_awal = TransaksiService::get_transaksi_by_id(db.clone(), id).await.unwrap().total_harga;
+
+        let response = client.post(uri!(create_retur(id)))
+            .header(ContentType::JSON)
+            .body(body(MetodePengembalian::Refund, &[(detail[0], 1, true), (detail[1], 1, false)]))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let retur: ApiResponse<ReturPenjualan> = response.into_json().await.unwrap();
+        let retur = retur.data.unwrap();
+
+        assert_eq!(retur.total_pengembalian, Rupiah::from_rupiah(350_000));
+        assert_eq!(retur.id_refund.len(), 1);
+        assert_eq!(stok(&db, 1).await, 97);
+        assert_eq!(stok(&db, 2).await, 48);
+
+        let refund: (String, String) = sqlx::query("SELECT CAST(amount AS TEXT) AS amount, payment_id FROM refunds WHERE id = $1")
+            .bind(&retur.id_refund[0])
+            .fetch_one(&db)
+            .await
+            .map(|row| (row.get("amount"), row.get("payment_id")))
+            .unwrap();
+        assert_eq!(refund.1, "PMT-1");
+        assert_eq!(refund.0.parse::<Rupiah>().unwrap(), Rupiah::from_rupiah(350_000));
+
+        // Transaksi asal tidak berubah
+        let transaksi = TransaksiService::get_transaksi_by_id(db.clone(), id).await.unwrap();
+        assert_eq!(transaksi.total_harga, total_awal);
+
+        let response = client.get(uri!(get_retur_by_transaksi(id))).dispatch().await;
+        let list: ApiResponse<Vec<ReturPenjualan>> = response.into_json().await.unwrap();
+        let list = list.data.unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].detail, retur.detail);
+        assert_eq!(list[0].catatan.as_deref(), Some("Karung sobek"));
+    }
+
+    #[async_test]
+    async fn test_concurrent_refund_and_return_cannot_exceed_amount_paid() {
+        use crate::manajemen_pembayaran::enums::refund_reason::RefundReason;
+        use crate::manajemen_pembayaran::service::refund_service::{NewRefund, RefundService};
+
+        let path = std::env::temp_dir().join(format!("retur-{}.db", uuid::Uuid::new_v4()));
+        let (client, db, id, detail) = setup_on(&format!("sqlite://{}?mode=rwc", path.display()), 4).await;
+        let state = State::get(client.rocket()).unwrap();
+
+        // Total dibayar 900.000: refund 600.000 dan retur 4 semen (400.000) tidak boleh keduanya berhasil
+        let refund_service = RefundService::new();
+        let (retur, refund) = futures::join!(
+            client.post(uri!(create_retur(id)))
+                .header(ContentType::JSON)
+                .body(body(MetodePengembalian::Refund, &[(detail[0], 4, true)]))
+                .dispatch(),
+            refund_service.create_refund(state, None, "PMT-1", NewRefund {
+                amount: Rupiah::from_rupiah(600_000),
+                reason: RefundReason::Other,
+                method: None,
+                notes: None,
+            })
+        );
+        assert_ne!(retur.status() == Status::Ok, refund.is_ok());
+        drop(retur);
+
+        let refunded: Vec<String> = sqlx::query("SELECT CAST(amount AS TEXT) AS amount FROM refunds WHERE payment_id = 'PMT-1'")
+            .fetch_all(&db)
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get("amount"))
+            .collect();
+        assert_eq!(refunded.len(), 1);
+
+        drop(client);
+        db.close().await;
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[async_test]
+    async fn test_return_rejects_quantity_above_sold_minus_returned() {
+        let (client, db, id, detail) = setup().await;
+
+        let response = client.post(uri!(create_retur(id)))
+            .header(ContentType::JSON)
+            .body(body(MetodePengembalian::KreditToko, &[(detail[0], 3, true)]))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.post(uri!(create_retur(id)))
+            .header(ContentType::JSON)
+            .body(body(MetodePengembalian::KreditToko, &[(detail[0], 2, true)]))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+        let error: ErrorResponse = response.into_json().await.unwrap();
+        assert_eq!(error.code, "VALIDATION_ERROR");
+        assert_eq!(stok(&db, 1).await, 99);
+
+        let response = client.get(uri!(get_saldo_kredit_toko(7))).dispatch().await;
+        let saldo: ApiResponse<SaldoKreditToko> = response.into_json().await.unwrap();
+        assert_eq!(saldo.data.unwrap().saldo, Rupiah::from_rupiah(300_000));
+    }
+
+    #[async_test]
+    async fn test_return_requires_completed_transaksi() {
+        let (client, db, _, _) = setup().await;
+
+        let baru = TransaksiService::create_transaksi_with_details(db.clone(), &CreateTransaksiRequest {
+            id_pelanggan: 7,
+            nama_pelanggan: "Retur Test".to_string(),
+            catatan: None,
+            detail_transaksi: vec![CreateDetailTransaksiRequest {
+                id_produk: 1,
+                nama_produk: "Semen".to_string(),
+                harga_satuan: Rupiah::from_rupiah(1),
+                jumlah: 1,
+            }],
+        }).await.unwrap();
+
+        let response = client.post(uri!(create_retur(baru.id)))
+            .header(ContentType::JSON)
+            .body(body(MetodePengembalian::KreditToko, &[(1, 1, true)]))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+}
@@ -1,13 +1,21 @@
 use rocket::{get, post, patch, delete, put};
 use rocket::State;
-use rocket::http::Status;
+use rocket::http::{ContentType, Status};
 use rocket::serde::json::Json;
 use sqlx::{Any, Pool};
 use autometrics::autometrics;
 
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::common::idempotency::{with_idempotency, IdempotencyError, IdempotencyKey, IdempotentResponse, StoredResponse};
 use crate::transaksi_penjualan::model::transaksi::Transaksi;
 use crate::transaksi_penjualan::model::detail_transaksi::DetailTransaksi;
-use crate::transaksi_penjualan::service::transaksi::TransaksiService;
+use crate::transaksi_penjualan::model::receipt::{ReceiptDocument, StoreProfile};
+use crate::transaksi_penjualan::model::riwayat_status::{PerubahanStatus, RiwayatStatusTransaksi};
+use crate::manajemen_pembayaran::model::payment::PaymentMethod;
+use crate::transaksi_penjualan::patterns::strategy::escpos_renderer::EscPosReceiptRenderer;
+use crate::transaksi_penjualan::patterns::strategy::receipt_renderer::{ReceiptRenderer, ReceiptRendererFactory, THERMAL_58MM_COLUMNS, THERMAL_80MM_COLUMNS};
+use crate::transaksi_penjualan::service::receipt::{ReceiptError, ReceiptService};
+use crate::transaksi_penjualan::service::transaksi::{TransaksiError, TransaksiService};
 
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(crate = "rocket::serde")]
@@ -76,39 +84,74 @@ pub async fn get_all_transaksi(
     }
 }
 
+const CREATE_TRANSAKSI_SCOPE: &str = "POST /api/transaksi";
+
 #[autometrics]
 #[post("/transaksi", data = "<request>")]
 pub async fn create_transaksi(
     db: &State<Pool<Any>>, 
+    idempotency_key: IdempotencyKey,
     request: Json<crate::transaksi_penjualan::dto::transaksi_request::CreateTransaksiRequest>
 ) -> Result<Json<ApiResponse<Transaksi>>, (Status, Json<ErrorResponse>)> {
-    if let Err(err_msg) = request.validate() {
-        return Err((
-            Status::BadRequest,
-            Json(ErrorResponse::new(&err_msg, "VALIDATION_ERROR"))
-        ));
+    with_idempotency(db, CREATE_TRANSAKSI_SCOPE, idempotency_key, &*request, || {
+        process_create_transaksi(db, &request)
+    }).await
+}
+
+// Respons sukses disimpan sebagai ApiResponse, respons gagal sebagai ErrorResponse
+impl IdempotentResponse for Result<Json<ApiResponse<Transaksi>>, (Status, Json<ErrorResponse>)> {
+    fn to_stored(&self) -> Result<StoredResponse, IdempotencyError> {
+        match self {
+            Ok(response) => StoredResponse::new(Status::Ok, &**response),
+            Err((status, response)) => StoredResponse::new(*status, &**response),
+        }
     }
 
-    if let Err(err_msg) = TransaksiService::validate_product_stock(&request.detail_transaksi).await {
-        return Err((
-            Status::BadRequest,
-            Json(ErrorResponse::new(&err_msg, "INSUFFICIENT_STOCK"))
-        ));
+    fn from_stored(stored: StoredResponse) -> Result<Self, IdempotencyError> {
+        if stored.status().class().is_success() {
+            Ok(Ok(Json(stored.body_as()?)))
+        } else {
+            Ok(Err((stored.status(), Json(stored.body_as()?))))
+        }
+    }
+
+    fn from_error(e: IdempotencyError) -> Self {
+        let code = match e {
+            IdempotencyError::DatabaseError(_) => "INTERNAL_ERROR",
+            IdempotencyError::Conflict(_) | IdempotencyError::InProgress(_) => "IDEMPOTENCY_CONFLICT",
+        };
+        Err((e.status(), Json(ErrorResponse::new(&e.message(), code))))
     }
+}
 
-    match TransaksiService::create_transaksi_with_details(db.inner().clone(), &request).await {
+async fn process_create_transaksi(
+    db: &State<Pool<Any>>,
+    request: &crate::transaksi_penjualan::dto::transaksi_request::CreateTransaksiRequest
+) -> Result<Json<ApiResponse<Transaksi>>, (Status, Json<ErrorResponse>)> {
+    match TransaksiService::create_transaksi_with_details(db.inner().clone(), request).await {
         Ok(new_transaksi) => {
             Ok(Json(ApiResponse::success("Transaksi berhasil dibuat", new_transaksi)))
         }
-        Err(_) => {
-            Err((
-                Status::InternalServerError,
-                Json(ErrorResponse::new("Gagal membuat transaksi", "INTERNAL_ERROR"))
-            ))
-        }
+        Err(e) => Err(transaksi_error_response(e, "Gagal membuat transaksi")),
     }
 }
 
+pub(crate) fn transaksi_error_response(error: TransaksiError, fallback: &str) -> (Status, Json<ErrorResponse>) {
+    let code = match &error {
+        TransaksiError::Validasi(_) => "VALIDATION_ERROR",
+        TransaksiError::ProdukTidakDitemukan(_) => "PRODUCT_NOT_FOUND",
+        TransaksiError::StokTidakCukup { .. } => "INSUFFICIENT_STOCK",
+        TransaksiError::Database(sqlx::Error::RowNotFound) => {
+            return (Status::NotFound, Json(ErrorResponse::new("Data tidak ditemukan", "NOT_FOUND")));
+        }
+        TransaksiError::Database(_) => {
+            return (Status::InternalServerError, Json(ErrorResponse::new(fallback, "INTERNAL_ERROR")));
+        }
+    };
+
+    (Status::BadRequest, Json(ErrorResponse::new(&error.message(), code)))
+}
+
 #[autometrics]
 #[get("/transaksi/<id>")]
 pub async fn get_transaksi_by_id(
@@ -172,10 +215,12 @@ pub async fn update_transaksi(
 }
 
 #[autometrics]
-#[delete("/transaksi/<id>")]
+#[delete("/transaksi/<id>?<alasan>")]
 pub async fn delete_transaksi(
     db: &State<Pool<Any>>, 
-    id: i32
+    user: Option<AuthenticatedUser>,
+    id: i32,
+    alasan: Option<String>
 ) -> Result<Json<ApiResponse<String>>, (Status, Json<ErrorResponse>)> {
     match TransaksiService::get_transaksi_by_id(db.inner().clone(), id).await {
         Ok(existing_transaksi) => {
@@ -197,7 +242,7 @@ pub async fn delete_transaksi(
         }
     }
 
-    match TransaksiService::delete_transaksi(db.inner().clone(), id).await {
+    match TransaksiService::delete_transaksi(db.inner().clone(), id, &PerubahanStatus::oleh(user.as_ref(), alasan)).await {
         Ok(_) => {
             Ok(Json(ApiResponse::success("Transaksi berhasil dibatalkan", "Deleted".to_string())))
         }
@@ -211,12 +256,14 @@ pub async fn delete_transaksi(
 }
 
 #[autometrics]
-#[put("/transaksi/<id>/complete")]
+#[put("/transaksi/<id>/complete?<alasan>")]
 pub async fn complete_transaksi(
     db: &State<Pool<Any>>, 
-    id: i32
+    user: Option<AuthenticatedUser>,
+    id: i32,
+    alasan: Option<String>
 ) -> Result<Json<ApiResponse<Transaksi>>, (Status, Json<ErrorResponse>)> {
-    match TransaksiService::complete_transaksi(db.inner().clone(), id).await {
+    match TransaksiService::complete_transaksi(db.inner().clone(), id, &PerubahanStatus::oleh(user.as_ref(), alasan)).await {
         Ok(completed_transaksi) => {
             Ok(Json(ApiResponse::success("Transaksi berhasil diselesaikan", completed_transaksi)))
         }
@@ -230,12 +277,14 @@ pub async fn complete_transaksi(
 }
 
 #[autometrics]
-#[put("/transaksi/<id>/cancel")]
+#[put("/transaksi/<id>/cancel?<alasan>")]
 pub async fn cancel_transaksi(
     db: &State<Pool<Any>>, 
-    id: i32
+    user: Option<AuthenticatedUser>,
+    id: i32,
+    alasan: Option<String>
 ) -> Result<Json<ApiResponse<Transaksi>>, (Status, Json<ErrorResponse>)> {
-    match TransaksiService::cancel_transaksi(db.inner().clone(), id).await {
+    match TransaksiService::cancel_transaksi(db.inner().clone(), id, &PerubahanStatus::oleh(user.as_ref(), alasan)).await {
         Ok(cancelled_transaksi) => {
             Ok(Json(ApiResponse::success("Transaksi berhasil dibatalkan", cancelled_transaksi)))
         }
@@ -248,6 +297,35 @@ pub async fn cancel_transaksi(
     }
 }
 
+#[autometrics]
+#[put("/transaksi/<id>/reopen?<alasan>")]
+pub async fn reopen_transaksi(
+    db: &State<Pool<Any>>, 
+    user: Option<AuthenticatedUser>,
+    id: i32,
+    alasan: Option<String>
+) -> Result<Json<ApiResponse<Transaksi>>, (Status, Json<ErrorResponse>)> {
+    match TransaksiService::reopen_transaksi(db.inner().clone(), id, &PerubahanStatus::oleh(user.as_ref(), alasan)).await {
+        Ok(transaksi) => Ok(Json(ApiResponse::success("Transaksi berhasil dibuka kembali", transaksi))),
+        Err(e) => Err(transaksi_error_response(e, "Gagal membuka kembali transaksi")),
+    }
+}
+
+#[autometrics]
+#[get("/transaksi/<id>/history")]
+pub async fn get_riwayat_status(
+    db: &State<Pool<Any>>, 
+    id: i32
+) -> Result<Json<ApiResponse<Vec<RiwayatStatusTransaksi>>>, (Status, Json<ErrorResponse>)> {
+    match TransaksiService::get_riwayat_status(db.inner().clone(), id).await {
+        Ok(riwayat) => Ok(Json(ApiResponse::success("Riwayat status transaksi berhasil diambil", riwayat))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(ErrorResponse::new("Gagal mengambil riwayat status transaksi", "FETCH_ERROR"))
+        ))
+    }
+}
+
 #[autometrics]
 #[get("/transaksi/<id_transaksi>/detail")]
 pub async fn get_detail_transaksi(
@@ -301,12 +379,7 @@ pub async fn add_detail_transaksi(
         Ok(new_detail) => {
             Ok(Json(ApiResponse::success("Detail transaksi berhasil ditambahkan", new_detail)))
         }
-        Err(_) => {
-            Err((
-                Status::InternalServerError,
-                Json(ErrorResponse::new("Gagal menambahkan detail transaksi", "INTERNAL_ERROR"))
-            ))
-        }
+        Err(e) => Err(transaksi_error_response(e, "Gagal menambahkan detail transaksi")),
     }
 }
 
@@ -349,12 +422,7 @@ pub async fn update_detail_transaksi(
         Ok(updated_detail) => {
             Ok(Json(ApiResponse::success("Detail transaksi berhasil diperbarui", updated_detail)))
         }
-        Err(_) => {
-            Err((
-                Status::InternalServerError,
-                Json(ErrorResponse::new("Gagal memperbarui detail transaksi", "INTERNAL_ERROR"))
-            ))
-        }
+        Err(e) => Err(transaksi_error_response(e, "Gagal memperbarui detail transaksi")),
     }
 }
 
@@ -425,6 +493,10 @@ pub async fn get_transaksi_with_details(
         nama_pelanggan: transaksi.nama_pelanggan,
         tanggal_transaksi: transaksi.tanggal_transaksi,
         total_harga: transaksi.total_harga,
+        total_dpp: transaksi.total_dpp,
+        total_ppn: transaksi.total_ppn,
+        tarif_pajak_bp: transaksi.tarif_pajak_bp,
+        harga_termasuk_pajak: transaksi.harga_termasuk_pajak,
         status: transaksi.status.to_string(),
         catatan: transaksi.catatan,
         detail_transaksi: details,
@@ -433,27 +505,101 @@ pub async fn get_transaksi_with_details(
     Ok(Json(ApiResponse::success("Data transaksi berhasil diambil", response)))
 }
 
+// format: 58mm, 80mm (default), html atau pdf; document: struk (default) atau faktur
+#[autometrics]
+#[get("/transaksi/<id>/receipt?<format>&<document>")]
+pub async fn get_receipt(
+    db: &State<Pool<Any>>,
+    id: i32,
+    format: Option<String>,
+    document: Option<String>
+) -> Result<(ContentType, Vec<u8>), (Status, Json<ErrorResponse>)> {
+    let format = format.unwrap_or_else(|| "80mm".to_string());
+    let Some(renderer) = ReceiptRendererFactory::create(&format) else {
+        return Err((
+            Status::BadRequest,
+            Json(ErrorResponse::new(&format!("Format struk tidak dikenal: {}", format), "INVALID_FORMAT"))
+        ));
+    };
+
+    let document = document.unwrap_or_else(|| "struk".to_string());
+    let Some(document) = ReceiptDocument::from_string(&document) else {
+        return Err((
+            Status::BadRequest,
+            Json(ErrorResponse::new(&format!("Jenis dokumen tidak dikenal: {}", document), "INVALID_DOCUMENT"))
+        ));
+    };
+
+    match ReceiptService::build_receipt(db, id, document, StoreProfile::from_env()).await {
+        Ok(receipt) => Ok((renderer.content_type(), renderer.render(&receipt))),
+        Err(e) => Err(receipt_error_response(e)),
+    }
+}
+
+// Byte stream ESC/POS untuk dikirim langsung ke printer thermal kasir.
+// Laci kas dibuka secara default bila ada pembayaran tunai.
+#[autometrics]
+#[get("/transaksi/<id>/receipt/escpos?<paper>&<kick_drawer>")]
+pub async fn get_receipt_escpos(
+    db: &State<Pool<Any>>,
+    id: i32,
+    paper: Option<String>,
+    kick_drawer: Option<bool>
+) -> Result<(ContentType, Vec<u8>), (Status, Json<ErrorResponse>)> {
+    let width = match paper.as_deref().unwrap_or("80mm") {
+        "58mm" => THERMAL_58MM_COLUMNS,
+        "80mm" => THERMAL_80MM_COLUMNS,
+        other => return Err((
+            Status::BadRequest,
+            Json(ErrorResponse::new(&format!("Ukuran kertas tidak dikenal: {}", other), "INVALID_PAPER"))
+        )),
+    };
+
+    match ReceiptService::build_receipt(db, id, ReceiptDocument::Struk, StoreProfile::from_env()).await {
+        Ok(receipt) => {
+            let kick_drawer = kick_drawer.unwrap_or_else(|| receipt.payments.iter().any(|p| p.method == PaymentMethod::Cash));
+            let renderer = EscPosReceiptRenderer::new(width, kick_drawer);
+            Ok((renderer.content_type(), renderer.render(&receipt)))
+        }
+        Err(e) => Err(receipt_error_response(e)),
+    }
+}
+
+fn receipt_error_response(error: ReceiptError) -> (Status, Json<ErrorResponse>) {
+    match error {
+        ReceiptError::NotFound(message) => (
+            Status::NotFound,
+            Json(ErrorResponse::new(&message, "NOT_FOUND"))
+        ),
+        ReceiptError::NotCompleted(message) => (
+            Status::Conflict,
+            Json(ErrorResponse::new(&message, "TRANSACTION_NOT_COMPLETED"))
+        ),
+        ReceiptError::DatabaseError(_) => (
+            Status::InternalServerError,
+            Json(ErrorResponse::new("Gagal membuat struk", "INTERNAL_ERROR"))
+        ),
+    }
+}
+
 #[autometrics]
 #[post("/transaksi/validate-stock", data = "<products>")]
 pub async fn validate_product_stock(
+    db: &State<Pool<Any>>,
     products: Json<Vec<crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest>>
 ) -> Result<Json<ApiResponse<String>>, (Status, Json<ErrorResponse>)> {
-    match TransaksiService::validate_product_stock(&products).await {
+    match TransaksiService::validate_product_stock(db.inner().clone(), &products).await {
         Ok(_) => {
             Ok(Json(ApiResponse::success("Semua produk tersedia", "Valid".to_string())))
         }
-        Err(err_msg) => {
-            Err((
-                Status::BadRequest,
-                Json(ErrorResponse::new(&err_msg, "INSUFFICIENT_STOCK"))
-            ))
-        }
+        Err(e) => Err(transaksi_error_response(e, "Gagal memeriksa stok produk")),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::money::Rupiah;
     use rocket::local::asynchronous::Client;
     use rocket::{routes, uri, Rocket, async_test};
     use sqlx::any::install_default_drivers;
@@ -465,6 +611,28 @@ mod tests {
     const ADMIN_USERNAME: &str = "admin";
     const ADMIN_PASSWORD: &str = "admin123";
 
+    // Katalog produk uji: (id, nama, harga, stok)
+    const PRODUK_UJI: [(i64, &str, i64, i32); 5] = [
+        (1, "Semen Portland 50kg", 100_000, 100),
+        (2, "Cat Tembok 5kg", 250_000, 50),
+        (3, "Keramik Lantai 60x60", 500_000, 25),
+        (4, "Pipa PVC 3m", 75_000, 15),
+        (5, "Besi Beton 10mm", 150_000, 30),
+    ];
+
+    async fn seed_produk(db: &Pool<Any>) {
+        for (id, nama, harga, stok) in PRODUK_UJI {
+            sqlx::query("INSERT INTO produk (id, nama, kategori, harga, stok) VALUES ($1, $2, 'Material', CAST($3 AS DECIMAL(15,2)), $4)")
+                .bind(id)
+                .bind(nama)
+                .bind(Rupiah::from_rupiah(harga).to_string())
+                .bind(stok)
+                .execute(db)
+                .await
+                .unwrap();
+        }
+    }
+
     async fn setup() -> Client {
         install_default_drivers();
         
@@ -478,6 +646,7 @@ mod tests {
             .run(&db)
             .await
             .unwrap();
+        seed_produk(&db).await;
 
         // Create an admin user for testing
         AuthService::register_user(
@@ -493,9 +662,9 @@ mod tests {
             .manage(production)
             .mount("/", routes![
                 get_all_transaksi, create_transaksi, get_transaksi_by_id, 
-                update_transaksi, delete_transaksi, complete_transaksi, cancel_transaksi,
+                update_transaksi, delete_transaksi, complete_transaksi, cancel_transaksi, reopen_transaksi, get_riwayat_status,
                 get_detail_transaksi, add_detail_transaksi, update_detail_transaksi, delete_detail_transaksi,
-                get_transaksi_with_details, validate_product_stock, login
+                get_transaksi_with_details, validate_product_stock, get_receipt, get_receipt_escpos, login
             ]);
         
         let client = Client::tracked(rocket).await.expect("Must provide a valid Rocket instance");
@@ -524,7 +693,7 @@ mod tests {
                 crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest {
                     id_produk: 1,
                     nama_produk: "Contoh Produk".to_string(),
-                    harga_satuan: 10000.0,
+                    harga_satuan: Rupiah::from_rupiah(10000),
                     jumlah: 2,
                 },
             ],
@@ -541,10 +710,59 @@ mod tests {
         assert!(body.data.is_some());
         if let Some(transaksi) = body.data {
             assert_eq!(transaksi.nama_pelanggan, new_transaksi_request.nama_pelanggan);
-            assert!(transaksi.total_harga > 0.0);
+            assert!(transaksi.total_harga.is_positive());
         }
     }
 
+    #[async_test]
+    async fn test_create_transaksi_with_idempotency_key() {
+        use rocket::http::Header;
+
+        let client = setup().await;
+        let request = |jumlah: u32| crate::transaksi_penjualan::dto::transaksi_request::CreateTransaksiRequest {
+            id_pelanggan: 1,
+            nama_pelanggan: "Castorice".to_string(),
+            catatan: None,
+            detail_transaksi: vec![
+                crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest {
+                    id_produk: 1,
+                    nama_produk: "Contoh Produk".to_string(),
+                    harga_satuan: Rupiah::from_rupiah(10000),
+                    jumlah,
+                },
+            ],
+        };
+
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let response = client.post(uri!(super::create_transaksi))
+                .header(Header::new("Idempotency-Key", "pos-7-0001"))
+                .json(&request(2))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
+            let body: ApiResponse<Transaksi> = response.into_json().await.unwrap();
+            ids.push(body.data.unwrap().id);
+        }
+        assert_eq!(ids[0], ids[1]);
+
+        let response = client.post(uri!(super::create_transaksi))
+            .header(Header::new("Idempotency-Key", "pos-7-0001"))
+            .json(&request(3))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Conflict);
+        let body: ErrorResponse = response.into_json().await.unwrap();
+        assert_eq!(body.code, "IDEMPOTENCY_CONFLICT");
+
+        let response = client.post(uri!(super::create_transaksi))
+            .header(Header::new("Idempotency-Key", ""))
+            .json(&request(2))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
     #[async_test]
     async fn test_get_all_transaksi() {
         let client = setup().await;
@@ -564,7 +782,7 @@ mod tests {
             crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest {
                 id_produk: 1,
                 nama_produk: "Valid Product".to_string(),
-                harga_satuan: 100000.0,
+                harga_satuan: Rupiah::from_rupiah(100000),
                 jumlah: 50,
             },
         ];
@@ -575,6 +793,26 @@ mod tests {
             .await;
 
         assert_eq!(response.status(), Status::Ok);
+
+        for (id_produk, jumlah, code) in [(99, 1, "PRODUCT_NOT_FOUND"), (1, 101, "INSUFFICIENT_STOCK")] {
+            let products = vec![
+                crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest {
+                    id_produk,
+                    nama_produk: "Invalid Product".to_string(),
+                    harga_satuan: Rupiah::from_rupiah(100000),
+                    jumlah,
+                },
+            ];
+
+            let response = client.post(uri!(super::validate_product_stock))
+                .json(&products)
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::BadRequest);
+            let body: ErrorResponse = response.into_json().await.unwrap();
+            assert_eq!(body.code, code);
+        }
     }
 
     #[async_test]
@@ -589,7 +827,7 @@ mod tests {
                 crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest {
                     id_produk: 1,
                     nama_produk: "Test Product".to_string(),
-                    harga_satuan: 50000.0,
+                    harga_satuan: Rupiah::from_rupiah(50000),
                     jumlah: 2,
                 },
             ],
@@ -614,6 +852,11 @@ mod tests {
             assert_eq!(full_transaksi.id, created_transaksi.id);
             assert_eq!(full_transaksi.nama_pelanggan, "Test Full Details");
             assert!(!full_transaksi.detail_transaksi.is_empty());
+            assert_eq!(full_transaksi.tarif_pajak_bp, 1100);
+            assert!(full_transaksi.total_ppn.is_positive());
+            assert_eq!(full_transaksi.total_dpp + full_transaksi.total_ppn, full_transaksi.total_harga);
+            let ppn_detail: Rupiah = full_transaksi.detail_transaksi.iter().map(|d| d.ppn).sum();
+            assert_eq!(ppn_detail, full_transaksi.total_ppn);
         }
     }
 
@@ -629,7 +872,7 @@ mod tests {
                 crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest {
                     id_produk: 1,
                     nama_produk: "State Test Product".to_string(),
-                    harga_satuan: 100000.0,
+                    harga_satuan: Rupiah::from_rupiah(100000),
                     jumlah: 1,
                 },
             ],
@@ -662,6 +905,46 @@ mod tests {
         assert_eq!(update_response.status(), Status::Forbidden);
     }
 
+    #[async_test]
+    async fn test_status_history_records_actor_and_reason() {
+        let client = setup().await;
+
+        let new_transaksi_request = crate::transaksi_penjualan::dto::transaksi_request::CreateTransaksiRequest {
+            id_pelanggan: 1,
+            nama_pelanggan: "History Test".to_string(),
+            catatan: None,
+            detail_transaksi: vec![
+                crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest {
+                    id_produk: 1,
+                    nama_produk: "Semen".to_string(),
+                    harga_satuan: Rupiah::from_rupiah(100000),
+                    jumlah: 1,
+                },
+            ],
+        };
+        let create_response = client.post(uri!(super::create_transaksi))
+            .json(&new_transaksi_request)
+            .dispatch()
+            .await;
+        let created_transaksi = create_response.into_json::<ApiResponse<Transaksi>>().await.unwrap().data.unwrap();
+        assert!(created_transaksi.get_allowed_actions().contains(&"delete_item".to_string()));
+
+        let response = client.put(format!("/transaksi/{}/cancel?alasan=Salah%20input", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let response = client.put(format!("/transaksi/{}/reopen", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let response = client.put(format!("/transaksi/{}/reopen", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.get(format!("/transaksi/{}/history", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let riwayat = response.into_json::<ApiResponse<Vec<RiwayatStatusTransaksi>>>().await.unwrap().data.unwrap();
+        assert_eq!(riwayat.len(), 2);
+        assert_eq!(riwayat[0].ke_status, crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi::Dibatalkan);
+        assert_eq!(riwayat[0].alasan.as_deref(), Some("Salah input"));
+        assert!(riwayat.iter().all(|r| r.aktor == ADMIN_USERNAME));
+    }
+
     #[async_test]
     async fn test_detail_transaksi_crud() {
         let client = setup().await;
@@ -674,7 +957,7 @@ mod tests {
                 crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest {
                     id_produk: 1,
                     nama_produk: "Initial Product".to_string(),
-                    harga_satuan: 50000.0,
+                    harga_satuan: Rupiah::from_rupiah(50000),
                     jumlah: 1,
                 },
             ],
@@ -712,6 +995,71 @@ mod tests {
         }
     }
 
+    #[async_test]
+    async fn test_get_receipt() {
+        let client = setup().await;
+
+        let new_transaksi_request = crate::transaksi_penjualan::dto::transaksi_request::CreateTransaksiRequest {
+            id_pelanggan: 1,
+            nama_pelanggan: "Receipt Test".to_string(),
+            catatan: None,
+            detail_transaksi: vec![
+                crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest {
+                    id_produk: 2,
+                    nama_produk: "Semen".to_string(),
+                    harga_satuan: Rupiah::from_rupiah(250000),
+                    jumlah: 2,
+                },
+            ],
+        };
+
+        let create_response = client.post(uri!(super::create_transaksi))
+            .json(&new_transaksi_request)
+            .dispatch()
+            .await;
+        let created_transaksi = create_response.into_json::<ApiResponse<Transaksi>>().await.unwrap().data.unwrap();
+
+        let response = client.get(format!("/transaksi/{}/receipt", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Conflict);
+
+        client.put(format!("/transaksi/{}/complete", created_transaksi.id)).dispatch().await;
+
+        let response = client.get(format!("/transaksi/{}/receipt?format=58mm", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+        let struk = response.into_string().await.unwrap();
+        assert!(struk.contains("STRUK PENJUALAN"));
+        assert!(struk.contains("Cat Tembok 5kg"));
+        assert!(struk.contains("500.000"));
+
+        let response = client.get(format!("/transaksi/{}/receipt?format=html&document=faktur", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert!(response.into_string().await.unwrap().contains("INV-"));
+
+        let response = client.get(format!("/transaksi/{}/receipt?format=pdf", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.content_type(), Some(ContentType::PDF));
+        assert!(response.into_bytes().await.unwrap().starts_with(b"%PDF"));
+
+        let response = client.get(format!("/transaksi/{}/receipt?format=docx", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.get(format!("/transaksi/{}/receipt/escpos", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::Binary));
+        let bytes = response.into_bytes().await.unwrap();
+        assert!(bytes.starts_with(&[0x1B, 0x40]));
+        assert!(bytes.ends_with(&[0x1D, 0x56, 0x01]));
+
+        let response = client.get(format!("/transaksi/{}/receipt/escpos?paper=58mm&kick_drawer=true", created_transaksi.id)).dispatch().await;
+        assert!(response.into_bytes().await.unwrap().ends_with(&[0x1B, 0x70, 0x00, 0x19, 0xFA]));
+
+        let response = client.get(format!("/transaksi/{}/receipt/escpos?paper=a4", created_transaksi.id)).dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.get("/transaksi/99999/receipt").dispatch().await;
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
     #[async_test]
     async fn test_error_handling() {
         let client = setup().await;
@@ -0,0 +1,79 @@
+use rocket::serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TahanKeranjangRequest {
+    pub nama: String,
+    #[serde(default)]
+    pub id_pelanggan: Option<i32>,
+    #[serde(default)]
+    pub nama_pelanggan: Option<String>,
+    #[serde(default)]
+    pub catatan: Option<String>,
+    pub detail: Vec<BarisKeranjangRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BarisKeranjangRequest {
+    pub id_produk: i32,
+    pub nama_produk: String,
+    pub jumlah: u32,
+}
+
+// Pelanggan sering baru diketahui saat membayar; kosong berarti memakai data di keranjang
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct KonversiKeranjangRequest {
+    #[serde(default)]
+    pub id_pelanggan: Option<i32>,
+    #[serde(default)]
+    pub nama_pelanggan: Option<String>,
+    #[serde(default)]
+    pub catatan: Option<String>,
+}
+
+impl TahanKeranjangRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.nama.trim().is_empty() {
+            return Err("Nama keranjang tidak boleh kosong".to_string());
+        }
+        if self.detail.is_empty() {
+            return Err("Keranjang tidak boleh kosong".to_string());
+        }
+
+        for (i, baris) in self.detail.iter().enumerate() {
+            if baris.jumlah == 0 {
+                return Err(format!("Jumlah produk di indeks {} tidak boleh 0", i));
+            }
+            if baris.id_produk <= 0 {
+                return Err(format!("ID produk di indeks {} tidak valid", i));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate_tahan_keranjang_request() {
+        let mut request = TahanKeranjangRequest {
+            nama: "Antrian 2".to_string(),
+            id_pelanggan: None,
+            nama_pelanggan: None,
+            catatan: None,
+            detail: vec![BarisKeranjangRequest { id_produk: 1, nama_produk: "Semen".to_string(), jumlah: 2 }],
+        };
+        assert!(request.validate().is_ok());
+
+        request.detail[0].jumlah = 0;
+        assert!(request.validate().is_err());
+
+        request.nama = "  ".to_string();
+        assert_eq!(request.validate().unwrap_err(), "Nama keranjang tidak boleh kosong");
+    }
+}
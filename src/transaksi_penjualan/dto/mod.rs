@@ -1 +1,6 @@
-pub mod transaksi_request;
\ No newline at end of file
+pub mod transaksi_request;
+pub mod promosi_request;
+pub mod retur_request;
+pub mod penawaran_request;
+pub mod keranjang_request;
+pub mod pengiriman_request;
@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use rocket::serde::{Serialize, Deserialize};
+
+use crate::transaksi_penjualan::dto::transaksi_request::{CreateDetailTransaksiRequest, CreateTransaksiRequest};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CreatePenawaranRequest {
+    pub id_pelanggan: i32,
+    pub nama_pelanggan: String,
+    pub catatan: Option<String>,
+    // Kosong: berlaku DEFAULT_MASA_BERLAKU_HARI hari sejak dibuat
+    #[serde(default)]
+    pub berlaku_sampai: Option<DateTime<Utc>>,
+    pub detail: Vec<CreateDetailTransaksiRequest>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct KonversiPenawaranRequest {
+    // true: harga, promosi dan PPN dihitung ulang dari katalog saat konversi
+    #[serde(default)]
+    pub harga_ulang: bool,
+    pub catatan: Option<String>,
+}
+
+impl CreatePenawaranRequest {
+    // Baris penawaran divalidasi dan dihargai sama seperti keranjang transaksi
+    pub fn to_transaksi_request(&self) -> CreateTransaksiRequest {
+        CreateTransaksiRequest {
+            id_pelanggan: self.id_pelanggan,
+            nama_pelanggan: self.nama_pelanggan.clone(),
+            catatan: self.catatan.clone(),
+            detail_transaksi: self.detail.clone(),
+        }
+    }
+
+    pub fn validate(&self, waktu: DateTime<Utc>) -> Result<(), String> {
+        if self.detail.is_empty() {
+            return Err("Detail penawaran tidak boleh kosong".to_string());
+        }
+        if let Some(berlaku_sampai) = self.berlaku_sampai
+            && berlaku_sampai <= waktu
+        {
+            return Err("Tanggal berlaku penawaran harus di masa depan".to_string());
+        }
+
+        self.to_transaksi_request().validate()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Duration;
+    use crate::common::money::Rupiah;
+
+    #[test]
+    fn test_validate_penawaran_request() {
+        let now = Utc::now();
+        let mut request = CreatePenawaranRequest {
+            id_pelanggan: 1,
+            nama_pelanggan: "CV Kontraktor Maju".to_string(),
+            catatan: None,
+            berlaku_sampai: Some(now + Duration::days(7)),
+            detail: vec![CreateDetailTransaksiRequest {
+                id_produk: 1,
+                nama_produk: "Semen".to_string(),
+                harga_satuan: Rupiah::ZERO,
+                jumlah: 40,
+            }],
+        };
+        assert!(request.validate(now).is_ok());
+
+        request.berlaku_sampai = Some(now - Duration::days(1));
+        assert!(request.validate(now).is_err());
+
+        request.berlaku_sampai = None;
+        request.detail[0].jumlah = 0;
+        assert!(request.validate(now).is_err());
+
+        request.detail.clear();
+        assert_eq!(request.validate(now).unwrap_err(), "Detail penawaran tidak boleh kosong");
+    }
+}
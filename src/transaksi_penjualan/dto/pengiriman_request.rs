@@ -0,0 +1,171 @@
+use chrono::NaiveDate;
+use rocket::serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+use crate::transaksi_penjualan::model::detail_transaksi::DetailTransaksi;
+use crate::transaksi_penjualan::model::pengiriman::{DetailPengiriman, StatusPengiriman};
+use crate::transaksi_penjualan::model::produk_stok::ProdukStok;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CreatePengirimanRequest {
+    // Kosong: memakai alamat pelanggan transaksi
+    #[serde(default)]
+    pub alamat: Option<String>,
+    pub tanggal_kirim: NaiveDate,
+    #[serde(default)]
+    pub kendaraan: Option<String>,
+    #[serde(default)]
+    pub pengemudi: Option<String>,
+    #[serde(default)]
+    pub catatan: Option<String>,
+    pub detail: Vec<CreateDetailPengirimanRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CreateDetailPengirimanRequest {
+    pub id_detail_transaksi: i32,
+    pub jumlah: u32,
+}
+
+// Menjadwalkan ulang pengiriman yang belum berangkat; field kosong tidak diubah
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct JadwalPengirimanRequest {
+    #[serde(default)]
+    pub alamat: Option<String>,
+    #[serde(default)]
+    pub tanggal_kirim: Option<NaiveDate>,
+    #[serde(default)]
+    pub kendaraan: Option<String>,
+    #[serde(default)]
+    pub pengemudi: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct UpdateStatusPengirimanRequest {
+    pub status: StatusPengiriman,
+    // Catatan serah terima (terkirim) atau alasan (gagal)
+    #[serde(default)]
+    pub bukti_pengiriman: Option<String>,
+    #[serde(default)]
+    pub penerima: Option<String>,
+}
+
+impl CreatePengirimanRequest {
+    pub fn validate(&self, hari_ini: NaiveDate) -> Result<(), String> {
+        if self.detail.is_empty() {
+            return Err("Detail pengiriman tidak boleh kosong".to_string());
+        }
+        if let Some(i) = self.detail.iter().position(|d| d.jumlah == 0) {
+            return Err(format!("Jumlah pengiriman di indeks {} tidak boleh 0", i));
+        }
+        if self.tanggal_kirim < hari_ini {
+            return Err("Tanggal kirim tidak boleh di masa lalu".to_string());
+        }
+
+        Ok(())
+    }
+
+    // `sudah_dijadwalkan` adalah jumlah per id detail pada pengiriman lain yang tidak gagal.
+    // Baris yang sama boleh muncul lebih dari sekali; jumlahnya dihitung terhadap sisa yang belum dikirim.
+    pub fn to_detail_pengiriman(
+        &self,
+        details: &[DetailTransaksi],
+        sudah_dijadwalkan: &HashMap<i32, u32>,
+        sudah_diretur: &HashMap<i32, u32>,
+        produk: &HashMap<i32, ProdukStok>,
+    ) -> Result<Vec<DetailPengiriman>, String> {
+        let mut dijadwalkan = sudah_dijadwalkan.clone();
+
+        self.detail.iter().map(|baris| {
+            let asal = details.iter()
+                .find(|d| d.id == baris.id_detail_transaksi)
+                .ok_or_else(|| format!("Detail transaksi {} bukan bagian dari transaksi ini", baris.id_detail_transaksi))?;
+
+            let sebelumnya = dijadwalkan.entry(asal.id).or_insert(0);
+            // Barang yang sudah diretur tidak bisa dikirim lagi
+            let diretur = sudah_diretur.get(&asal.id).copied().unwrap_or(0);
+            let sisa = asal.jumlah.saturating_sub(*sebelumnya).saturating_sub(diretur);
+            if baris.jumlah > sisa {
+                return Err(format!(
+                    "Jumlah pengiriman detail {} melebihi sisa yang belum dikirim atau diretur. Sisa: {}, Diminta: {}",
+                    asal.id, sisa, baris.jumlah
+                ));
+            }
+            *sebelumnya += baris.jumlah;
+
+            Ok(DetailPengiriman {
+                id: 0,
+                id_pengiriman: 0,
+                id_detail_transaksi: asal.id,
+                id_produk: asal.id_produk,
+                nama_produk: produk.get(&asal.id_produk)
+                    .map(|p| p.nama.clone())
+                    .unwrap_or_else(|| format!("Produk #{}", asal.id_produk)),
+                jumlah: baris.jumlah,
+            })
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::money::Rupiah;
+
+    fn detail(id: i32, jumlah: u32) -> DetailTransaksi {
+        let mut detail = DetailTransaksi::new(1, id * 10, Rupiah::from_rupiah(1_000), jumlah);
+        detail.id = id;
+        detail
+    }
+
+    fn request(baris: &[(i32, u32)]) -> CreatePengirimanRequest {
+        CreatePengirimanRequest {
+            alamat: None,
+            tanggal_kirim: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            kendaraan: None,
+            pengemudi: None,
+            catatan: None,
+            detail: baris.iter().map(|(id, jumlah)| CreateDetailPengirimanRequest {
+                id_detail_transaksi: *id,
+                jumlah: *jumlah,
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let hari_ini = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        assert!(request(&[(1, 1)]).validate(hari_ini).is_ok());
+        assert!(request(&[]).validate(hari_ini).is_err());
+        assert!(request(&[(1, 0)]).validate(hari_ini).is_err());
+        assert!(request(&[(1, 1)]).validate(hari_ini.succ_opt().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_partial_delivery_limited_to_remaining_quantity() {
+        let details = vec![detail(1, 1000), detail(2, 5)];
+        let sudah = HashMap::from([(1, 600)]);
+
+        let hasil = request(&[(1, 400), (2, 5)]).to_detail_pengiriman(&details, &sudah, &HashMap::new(), &HashMap::new()).unwrap();
+        assert_eq!(hasil[0].jumlah, 400);
+        assert_eq!(hasil[1].nama_produk, "Produk #20");
+
+        assert!(request(&[(1, 300), (1, 101)]).to_detail_pengiriman(&details, &sudah, &HashMap::new(), &HashMap::new()).is_err());
+        assert!(request(&[(3, 1)]).to_detail_pengiriman(&details, &sudah, &HashMap::new(), &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_returned_quantity_cannot_be_delivered() {
+        let details = vec![detail(1, 1000)];
+        let sudah = HashMap::from([(1, 600)]);
+        let diretur = HashMap::from([(1, 100)]);
+
+        assert!(request(&[(1, 301)]).to_detail_pengiriman(&details, &sudah, &diretur, &HashMap::new()).is_err());
+        let hasil = request(&[(1, 300)]).to_detail_pengiriman(&details, &sudah, &diretur, &HashMap::new()).unwrap();
+        assert_eq!(hasil[0].jumlah, 300);
+    }
+}
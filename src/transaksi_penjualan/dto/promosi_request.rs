@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use rocket::serde::{Serialize, Deserialize};
+
+use crate::transaksi_penjualan::model::promosi::{AturanPromosi, CakupanPromosi, Promosi};
+
+fn default_dapat_digabung() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CreatePromosiRequest {
+    pub nama: String,
+    pub aturan: AturanPromosi,
+    pub cakupan: CakupanPromosi,
+    pub id_pelanggan: Option<i32>,
+    pub berlaku_mulai: Option<DateTime<Utc>>,
+    pub berlaku_sampai: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub prioritas: i32,
+    #[serde(default = "default_dapat_digabung")]
+    pub dapat_digabung: bool,
+}
+
+impl CreatePromosiRequest {
+    pub fn to_promosi(&self) -> Promosi {
+        Promosi {
+            id: 0,
+            nama: self.nama.trim().to_string(),
+            aturan: self.aturan.clone(),
+            cakupan: self.cakupan.clone(),
+            id_pelanggan: self.id_pelanggan,
+            berlaku_mulai: self.berlaku_mulai,
+            berlaku_sampai: self.berlaku_sampai,
+            prioritas: self.prioritas,
+            dapat_digabung: self.dapat_digabung,
+            aktif: true,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use rocket::serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::refund_reason::RefundReason;
+use crate::transaksi_penjualan::model::detail_transaksi::DetailTransaksi;
+use crate::transaksi_penjualan::model::retur_penjualan::{DetailRetur, MetodePengembalian};
+
+fn default_dapat_dijual_kembali() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CreateReturRequest {
+    // Kode alasan refund: BARANG_RUSAK, SALAH_BARANG, KELEBIHAN_BAYAR, PEMBATALAN, LAINNYA
+    pub alasan: String,
+    pub metode_pengembalian: MetodePengembalian,
+    pub catatan: Option<String>,
+    pub detail: Vec<CreateDetailReturRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct CreateDetailReturRequest {
+    pub id_detail_transaksi: i32,
+    pub jumlah: u32,
+    #[serde(default = "default_dapat_dijual_kembali")]
+    pub dapat_dijual_kembali: bool,
+}
+
+impl CreateReturRequest {
+    pub fn validate(&self) -> Result<RefundReason, String> {
+        if self.detail.is_empty() {
+            return Err("Detail retur tidak boleh kosong".to_string());
+        }
+        if let Some(i) = self.detail.iter().position(|d| d.jumlah == 0) {
+            return Err(format!("Jumlah retur di indeks {} tidak boleh 0", i));
+        }
+
+        RefundReason::from_string(&self.alasan)
+            .ok_or_else(|| format!("Alasan retur '{}' tidak dikenal", self.alasan))
+    }
+
+    // `sudah_diretur` adalah jumlah per id detail dari retur sebelumnya. Baris yang sama boleh muncul
+    // lebih dari sekali dalam satu permintaan; jumlahnya ikut dihitung terhadap sisa yang bisa diretur.
+    pub fn to_detail_retur(
+        &self,
+        details: &[DetailTransaksi],
+        sudah_diretur: &HashMap<i32, u32>,
+    ) -> Result<Vec<DetailRetur>, String> {
+        let mut diretur = sudah_diretur.clone();
+
+        self.detail.iter().map(|baris| {
+            let asal = details.iter()
+                .find(|d| d.id == baris.id_detail_transaksi)
+                .ok_or_else(|| format!("Detail transaksi {} bukan bagian dari transaksi ini", baris.id_detail_transaksi))?;
+
+            let sebelumnya = diretur.entry(asal.id).or_insert(0);
+            let sisa = asal.jumlah - *sebelumnya;
+            if baris.jumlah > sisa {
+                return Err(format!(
+                    "Jumlah retur detail {} melebihi sisa yang dapat diretur. Sisa: {}, Diminta: {}",
+                    asal.id, sisa, baris.jumlah
+                ));
+            }
+
+            let nilai = DetailRetur::hitung_nilai(asal, *sebelumnya, baris.jumlah);
+            *sebelumnya += baris.jumlah;
+
+            Ok(DetailRetur {
+                id: 0,
+                id_retur: 0,
+                id_detail_transaksi: asal.id,
+                id_produk: asal.id_produk,
+                jumlah: baris.jumlah,
+                dapat_dijual_kembali: baris.dapat_dijual_kembali,
+                nilai,
+            })
+        }).collect()
+    }
+
+    pub fn total_pengembalian(detail: &[DetailRetur]) -> Rupiah {
+        detail.iter().map(|d| d.nilai).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detail(id: i32, jumlah: u32, total: i64) -> DetailTransaksi {
+        let mut detail = DetailTransaksi::new(1, id * 10, Rupiah::from_rupiah(total / jumlah as i64), jumlah);
+        detail.id = id;
+        detail.total = Rupiah::from_rupiah(total);
+        detail
+    }
+
+    fn request(baris: &[(i32, u32)]) -> CreateReturRequest {
+        CreateReturRequest {
+            alasan: "BARANG_RUSAK".to_string(),
+            metode_pengembalian: MetodePengembalian::Refund,
+            catatan: None,
+            detail: baris.iter().map(|(id, jumlah)| CreateDetailReturRequest {
+                id_detail_transaksi: *id,
+                jumlah: *jumlah,
+                dapat_dijual_kembali: true,
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(request(&[(1, 1)]).validate(), Ok(RefundReason::Defective));
+        assert!(request(&[]).validate().is_err());
+        assert!(request(&[(1, 0)]).validate().is_err());
+
+        let mut invalid = request(&[(1, 1)]);
+        invalid.alasan = "BOSAN".to_string();
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_returned_quantity_never_exceeds_sold_minus_returned() {
+        let details = vec![detail(1, 5, 500_000), detail(2, 2, 150_000)];
+        let sudah_diretur = HashMap::from([(1, 3)]);
+
+        let retur = request(&[(1, 2), (2, 1)]).to_detail_retur(&details, &sudah_diretur).unwrap();
+        assert_eq!(retur[0].nilai, Rupiah::from_rupiah(200_000));
+        assert_eq!(retur[1].id_produk, 20);
+        assert_eq!(CreateReturRequest::total_pengembalian(&retur), Rupiah::from_rupiah(275_000));
+
+        assert!(request(&[(1, 3)]).to_detail_retur(&details, &sudah_diretur).is_err());
+        assert!(request(&[(2, 1), (2, 2)]).to_detail_retur(&details, &sudah_diretur).is_err());
+        assert!(request(&[(9, 1)]).to_detail_retur(&details, &sudah_diretur).is_err());
+    }
+}
@@ -1,7 +1,12 @@
+use chrono::{DateTime, Utc};
 use rocket::serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 
-use crate::transaksi_penjualan::model::detail_transaksi::DetailTransaksi;
+use crate::common::money::Rupiah;
+
+use crate::transaksi_penjualan::model::detail_transaksi::{DetailTransaksi, MAX_JUMLAH};
+use crate::transaksi_penjualan::model::produk_stok::ProdukStok;
+use crate::transaksi_penjualan::model::promosi::{BarisPromosi, MesinPromosi, Promosi, RincianHarga};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
@@ -17,7 +22,7 @@ pub struct CreateTransaksiRequest {
 pub struct CreateDetailTransaksiRequest {
     pub id_produk: i32,
     pub nama_produk: String,
-    pub harga_satuan: f64,
+    pub harga_satuan: Rupiah,
     pub jumlah: u32,
 }
 
@@ -34,14 +39,18 @@ pub struct TransaksiWithDetailsResponse {
     pub id_pelanggan: i32,
     pub nama_pelanggan: String,
     pub tanggal_transaksi: String,
-    pub total_harga: f64,
+    pub total_harga: Rupiah,
+    pub total_dpp: Rupiah,
+    pub total_ppn: Rupiah,
+    pub tarif_pajak_bp: i64,
+    pub harga_termasuk_pajak: bool,
     pub status: String,
     pub catatan: Option<String>,
     pub detail_transaksi: Vec<DetailTransaksi>,
 }
 
 impl CreateDetailTransaksiRequest {
-    pub fn to_detail_transaksi(&self, id_transaksi: i32, harga_satuan: f64) -> DetailTransaksi {
+    pub fn to_detail_transaksi(&self, id_transaksi: i32, harga_satuan: Rupiah) -> DetailTransaksi {
         DetailTransaksi::new(
             id_transaksi,
             self.id_produk,
@@ -64,10 +73,13 @@ impl CreateTransaksiRequest {
             if detail.jumlah == 0 {
                 return Err(format!("Jumlah produk di indeks {} tidak boleh 0", i));
             }
+            if detail.jumlah > MAX_JUMLAH {
+                return Err(format!("Jumlah produk di indeks {} tidak boleh lebih dari {}", i, MAX_JUMLAH));
+            }
             if detail.id_produk <= 0 {
                 return Err(format!("ID produk di indeks {} tidak valid", i));
             }
-            if detail.harga_satuan < 0.0 {
+            if detail.harga_satuan.is_negative() {
                 return Err(format!("Harga satuan di indeks {} tidak boleh negatif", i));
             }
         }
@@ -75,24 +87,34 @@ impl CreateTransaksiRequest {
         Ok(())
     }
 
-    pub fn calculate_total(&self, product_prices: &HashMap<i32, f64>) -> f64 {
-        self.detail_transaksi.iter().map(|d| {
-            let harga = product_prices.get(&d.id_produk).unwrap_or(&0.0);
-            *harga * d.jumlah as f64
-        }).sum()
+    // Harga dari katalog produk lalu promosi yang berlaku bagi pelanggan pada `waktu`
+    pub fn calculate_total(&self, katalog: &HashMap<i32, ProdukStok>, promosi: &[Promosi], waktu: DateTime<Utc>) -> RincianHarga {
+        let baris: Vec<BarisPromosi> = self.detail_transaksi.iter().map(|d| {
+            let produk = katalog.get(&d.id_produk);
+            BarisPromosi {
+                id_produk: d.id_produk,
+                kategori: produk.map(|p| p.kategori.clone()),
+                harga_satuan: produk.map(|p| p.harga).unwrap_or(Rupiah::ZERO),
+                jumlah: d.jumlah,
+            }
+        }).collect();
+
+        MesinPromosi::new(promosi, self.id_pelanggan, waktu).hitung(&baris)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transaksi_penjualan::model::pajak::KategoriPajak;
+    use crate::transaksi_penjualan::model::promosi::{AturanPromosi, CakupanPromosi};
 
     #[test]
     fn test_create_detail_transaksi_request_to_detail_transaksi() {
         let request = CreateDetailTransaksiRequest {
             id_produk: 101,
             nama_produk: "Macbook Pro M3".to_string(),
-            harga_satuan: 15000000.0,
+            harga_satuan: Rupiah::from_rupiah(15000000),
             jumlah: 2,
         };
 
@@ -100,9 +122,9 @@ mod tests {
 
         assert_eq!(detail.id_transaksi, 1);
         assert_eq!(detail.id_produk, 101);
-        assert_eq!(detail.harga_satuan, 15000000.0);
+        assert_eq!(detail.harga_satuan, Rupiah::from_rupiah(15000000));
         assert_eq!(detail.jumlah, 2);
-        assert_eq!(detail.subtotal, 30000000.0);
+        assert_eq!(detail.subtotal, Rupiah::from_rupiah(30000000));
     }
 
     #[test]
@@ -115,7 +137,7 @@ mod tests {
                 CreateDetailTransaksiRequest {
                     id_produk: 1,
                     nama_produk: "Produk A".to_string(),
-                    harga_satuan: 10000.0,
+                    harga_satuan: Rupiah::from_rupiah(10000),
                     jumlah: 2,
                 },
             ],
@@ -146,7 +168,7 @@ mod tests {
                 CreateDetailTransaksiRequest {
                     id_produk: 1,
                     nama_produk: "Produk A".to_string(),
-                    harga_satuan: -100.0,
+                    harga_satuan: Rupiah::from_rupiah(-100),
                     jumlah: 2,
                 },
             ],
@@ -165,23 +187,49 @@ mod tests {
                 CreateDetailTransaksiRequest {
                     id_produk: 1,
                     nama_produk: "Produk A".to_string(),
-                    harga_satuan: 0.0,
+                    harga_satuan: Rupiah::ZERO,
                     jumlah: 3,
                 },
                 CreateDetailTransaksiRequest {
                     id_produk: 2,
                     nama_produk: "Produk B".to_string(),
-                    harga_satuan: 0.0,
+                    harga_satuan: Rupiah::ZERO,
                     jumlah: 2,
                 },
             ],
         };
 
-        let mut product_prices = HashMap::new();
-        product_prices.insert(1, 10000.0);
-        product_prices.insert(2, 20000.0);
+        let mut katalog = HashMap::new();
+        for (id, kategori, harga) in [(1, "Semen", 10000), (2, "Cat", 20000)] {
+            katalog.insert(id, ProdukStok {
+                id,
+                nama: format!("Produk {}", id),
+                kategori: kategori.to_string(),
+                kategori_pajak: KategoriPajak::KenaPajak,
+                harga: Rupiah::from_rupiah(harga),
+                stok: 100,
+            });
+        }
 
-        let total = request.calculate_total(&product_prices);
-        assert_eq!(total, 3.0 * 10000.0 + 2.0 * 20000.0);
+        let rincian = request.calculate_total(&katalog, &[], Utc::now());
+        assert_eq!(rincian.total, Rupiah::from_rupiah(3 * 10000 + 2 * 20000));
+        assert_eq!(rincian.total_diskon, Rupiah::ZERO);
+
+        let promosi = vec![Promosi {
+            id: 1,
+            nama: "Diskon Cat".to_string(),
+            aturan: AturanPromosi::DiskonPersen { basis_poin: 1000 },
+            cakupan: CakupanPromosi::Kategori { kategori: "Cat".to_string() },
+            id_pelanggan: None,
+            berlaku_mulai: None,
+            berlaku_sampai: None,
+            prioritas: 0,
+            dapat_digabung: true,
+            aktif: true,
+        }];
+        let rincian = request.calculate_total(&katalog, &promosi, Utc::now());
+        assert_eq!(rincian.total, Rupiah::from_rupiah(3 * 10000 + 2 * 20000 - 4000));
+        assert!(rincian.diskon_per_baris[0].is_empty());
+        assert_eq!(rincian.diskon_per_baris[1][0].potongan, Rupiah::from_rupiah(4000));
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 use serde::{Serialize, Deserialize};
+use crate::transaksi_penjualan::patterns::state::transaksi_state::TransaksiStateFactory;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StatusTransaksi {
@@ -26,11 +27,11 @@ impl StatusTransaksi {
     }
 
     pub fn can_be_modified(&self) -> bool {
-        matches!(self, StatusTransaksi::MasihDiproses)
+        TransaksiStateFactory::create_state(self).can_be_modified()
     }
 
     pub fn can_be_cancelled(&self) -> bool {
-        matches!(self, StatusTransaksi::MasihDiproses)
+        TransaksiStateFactory::create_state(self).can_be_cancelled()
     }
 }
 
@@ -1,4 +1,10 @@
 use rocket::serde::{Serialize, Deserialize};
+use crate::common::money::Rupiah;
+use crate::transaksi_penjualan::model::pajak::{KategoriPajak, PajakBaris};
+use crate::transaksi_penjualan::model::promosi::DiskonTerapan;
+
+// Kolom jumlah dan stok bertipe INTEGER, jadi jumlah di atas i32::MAX tidak bisa disimpan
+pub const MAX_JUMLAH: u32 = i32::MAX as u32;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
@@ -6,19 +12,35 @@ pub struct DetailTransaksi {
     pub id: i32,
     pub id_transaksi: i32,
     pub id_produk: i32,
-    pub harga_satuan: f64, 
+    pub harga_satuan: Rupiah,
     pub jumlah: u32,
-    pub subtotal: f64,
+    // Subtotal sudah dikurangi diskon; harga_satuan tetap harga katalog
+    pub subtotal: Rupiah,
+    #[serde(default)]
+    pub diskon: Rupiah,
+    #[serde(default)]
+    pub diskon_diterapkan: Vec<DiskonTerapan>,
+    // Pajak dihitung dari subtotal: dpp (neto) + ppn = total (bruto)
+    #[serde(default)]
+    pub kategori_pajak: KategoriPajak,
+    #[serde(default)]
+    pub tarif_pajak_bp: i64,
+    #[serde(default)]
+    pub dpp: Rupiah,
+    #[serde(default)]
+    pub ppn: Rupiah,
+    #[serde(default)]
+    pub total: Rupiah,
 }
 
 impl DetailTransaksi {
     pub fn new(
         id_transaksi: i32,
         id_produk: i32,
-        harga_satuan: f64,
+        harga_satuan: Rupiah,
         jumlah: u32,
     ) -> Self {
-        let subtotal = harga_satuan * jumlah as f64;
+        let subtotal = harga_satuan * jumlah;
         
         DetailTransaksi {
             id: 0,
@@ -27,17 +49,47 @@ impl DetailTransaksi {
             harga_satuan,
             jumlah,
             subtotal,
+            diskon: Rupiah::ZERO,
+            diskon_diterapkan: Vec::new(),
+            kategori_pajak: KategoriPajak::KenaPajak,
+            tarif_pajak_bp: 0,
+            dpp: subtotal,
+            ppn: Rupiah::ZERO,
+            total: subtotal,
         }
     }
 
+    // Diskon lama tidak lagi sah setelah jumlah atau harga berubah; layanan menghitungnya ulang
     pub fn update_jumlah(&mut self, jumlah: u32) {
         self.jumlah = jumlah;
-        self.subtotal = self.harga_satuan * jumlah as f64;
+        self.terapkan_diskon(Vec::new());
     }
 
-    pub fn update_harga_satuan(&mut self, harga_satuan: f64) {
+    pub fn update_harga_satuan(&mut self, harga_satuan: Rupiah) {
         self.harga_satuan = harga_satuan;
-        self.subtotal = harga_satuan * self.jumlah as f64;
+        self.terapkan_diskon(Vec::new());
+    }
+
+    pub fn terapkan_diskon(&mut self, diskon: Vec<DiskonTerapan>) {
+        self.diskon = diskon.iter().map(|d| d.potongan).sum();
+        self.subtotal = self.harga_satuan * self.jumlah - self.diskon;
+        self.diskon_diterapkan = diskon;
+        // Pajak lama mengikuti subtotal lama; layanan menerapkan pajak lagi setelah diskon
+        self.terapkan_pajak(PajakBaris {
+            kategori: self.kategori_pajak,
+            tarif_bp: 0,
+            dpp: self.subtotal,
+            ppn: Rupiah::ZERO,
+            total: self.subtotal,
+        });
+    }
+
+    pub fn terapkan_pajak(&mut self, pajak: PajakBaris) {
+        self.kategori_pajak = pajak.kategori;
+        self.tarif_pajak_bp = pajak.tarif_bp;
+        self.dpp = pajak.dpp;
+        self.ppn = pajak.ppn;
+        self.total = pajak.total;
     }
 }
 
@@ -50,15 +102,15 @@ mod test {
         let detail = DetailTransaksi::new(
             1,
             101,
-            15000000.0,
+            Rupiah::from_rupiah(15000000),
             2,
         );
 
         assert_eq!(detail.id_transaksi, 1);
         assert_eq!(detail.id_produk, 101);
-        assert_eq!(detail.harga_satuan, 15000000.0);
+        assert_eq!(detail.harga_satuan, Rupiah::from_rupiah(15000000));
         assert_eq!(detail.jumlah, 2);
-        assert_eq!(detail.subtotal, 30000000.0);
+        assert_eq!(detail.subtotal, Rupiah::from_rupiah(30000000));
     }
 
     #[test]
@@ -66,13 +118,13 @@ mod test {
         let mut detail = DetailTransaksi::new(
             1,
             102,
-            250000.0,
+            Rupiah::from_rupiah(250000),
             1,
         );
 
         detail.update_jumlah(3);
         assert_eq!(detail.jumlah, 3);
-        assert_eq!(detail.subtotal, 750000.0);
+        assert_eq!(detail.subtotal, Rupiah::from_rupiah(750000));
     }
 
     #[test]
@@ -80,12 +132,29 @@ mod test {
         let mut detail = DetailTransaksi::new(
             1,
             103,
-            500000.0,
+            Rupiah::from_rupiah(500000),
             2,
         );
 
-        detail.update_harga_satuan(600000.0);
-        assert_eq!(detail.harga_satuan, 600000.0);
-        assert_eq!(detail.subtotal, 1200000.0);
+        detail.update_harga_satuan(Rupiah::from_rupiah(600000));
+        assert_eq!(detail.harga_satuan, Rupiah::from_rupiah(600000));
+        assert_eq!(detail.subtotal, Rupiah::from_rupiah(1200000));
+    }
+
+    #[test]
+    fn test_terapkan_diskon() {
+        let mut detail = DetailTransaksi::new(1, 104, Rupiah::from_rupiah(100000), 3);
+
+        detail.terapkan_diskon(vec![
+            DiskonTerapan { id_promosi: 1, nama_promosi: "Grosir".to_string(), potongan: Rupiah::from_rupiah(30000) },
+            DiskonTerapan { id_promosi: 2, nama_promosi: "Member".to_string(), potongan: Rupiah::from_rupiah(5000) },
+        ]);
+        assert_eq!(detail.diskon, Rupiah::from_rupiah(35000));
+        assert_eq!(detail.subtotal, Rupiah::from_rupiah(265000));
+
+        detail.update_jumlah(1);
+        assert_eq!(detail.diskon, Rupiah::ZERO);
+        assert!(detail.diskon_diterapkan.is_empty());
+        assert_eq!(detail.subtotal, Rupiah::from_rupiah(100000));
     }
 }
\ No newline at end of file
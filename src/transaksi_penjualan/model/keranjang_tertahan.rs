@@ -0,0 +1,136 @@
+use chrono::{DateTime, Duration, Utc};
+use rocket::serde::{Serialize, Deserialize};
+use crate::common::money::Rupiah;
+use crate::transaksi_penjualan::dto::transaksi_request::{CreateDetailTransaksiRequest, CreateTransaksiRequest};
+
+pub const DEFAULT_HELD_CART_TTL_HOURS: i64 = 12;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BarisKeranjang {
+    pub id: i32,
+    pub id_keranjang: i32,
+    pub id_produk: i32,
+    pub nama_produk: String,
+    pub jumlah: u32,
+}
+
+// Keranjang yang diparkir kasir. Tidak mengurangi atau mereservasi stok; harga diambil dari
+// katalog saat keranjang dikonversi menjadi transaksi.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct KeranjangTertahan {
+    pub id: i32,
+    pub id_kasir: i64,
+    pub nama: String,
+    // Pelanggan boleh belum diketahui saat keranjang ditahan
+    pub id_pelanggan: Option<i32>,
+    pub nama_pelanggan: Option<String>,
+    pub catatan: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub detail: Vec<BarisKeranjang>,
+}
+
+impl KeranjangTertahan {
+    // Data pelanggan dan catatan dari saat checkout menggantikan isi keranjang
+    pub fn to_transaksi_request(
+        &self,
+        id_pelanggan: Option<i32>,
+        nama_pelanggan: Option<String>,
+        catatan: Option<String>,
+    ) -> Result<CreateTransaksiRequest, String> {
+        let id_pelanggan = id_pelanggan.or(self.id_pelanggan)
+            .ok_or_else(|| "Pelanggan harus diisi sebelum keranjang dikonversi".to_string())?;
+
+        Ok(CreateTransaksiRequest {
+            id_pelanggan,
+            nama_pelanggan: nama_pelanggan.or(self.nama_pelanggan.clone()).unwrap_or_default(),
+            catatan: catatan.or(self.catatan.clone()),
+            detail_transaksi: self.detail.iter().map(|baris| CreateDetailTransaksiRequest {
+                id_produk: baris.id_produk,
+                nama_produk: baris.nama_produk.clone(),
+                harga_satuan: Rupiah::ZERO,
+                jumlah: baris.jumlah,
+            }).collect(),
+        })
+    }
+}
+
+// Keranjang yang tidak disentuh lebih lama dari `ttl` dihapus otomatis
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeranjangPolicy {
+    pub ttl: Duration,
+}
+
+impl Default for KeranjangPolicy {
+    fn default() -> Self {
+        KeranjangPolicy {
+            ttl: Duration::hours(DEFAULT_HELD_CART_TTL_HOURS),
+        }
+    }
+}
+
+impl KeranjangPolicy {
+    // HELD_CART_TTL_HOURS, default 12 jam
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        match lookup("HELD_CART_TTL_HOURS").and_then(|v| v.trim().parse::<i64>().ok()) {
+            Some(hours) if hours > 0 => KeranjangPolicy { ttl: Duration::hours(hours) },
+            _ => KeranjangPolicy::default(),
+        }
+    }
+
+    // Keranjang dengan updated_at sebelum batas ini sudah kedaluwarsa
+    pub fn batas_kedaluwarsa(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - self.ttl
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn keranjang() -> KeranjangTertahan {
+        KeranjangTertahan {
+            id: 1,
+            id_kasir: 3,
+            nama: "Bapak kaos merah".to_string(),
+            id_pelanggan: None,
+            nama_pelanggan: None,
+            catatan: Some("Ambil mobil dulu".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            detail: vec![BarisKeranjang { id: 1, id_keranjang: 1, id_produk: 4, nama_produk: "Paku 5cm".to_string(), jumlah: 3 }],
+        }
+    }
+
+    #[test]
+    fn test_to_transaksi_request_requires_pelanggan() {
+        let keranjang = keranjang();
+        assert!(keranjang.to_transaksi_request(None, None, None).is_err());
+
+        let request = keranjang.to_transaksi_request(Some(9), Some("Budi".to_string()), None).unwrap();
+        assert_eq!(request.id_pelanggan, 9);
+        assert_eq!(request.nama_pelanggan, "Budi");
+        assert_eq!(request.catatan.as_deref(), Some("Ambil mobil dulu"));
+        assert_eq!(request.detail_transaksi[0].id_produk, 4);
+        assert_eq!(request.detail_transaksi[0].jumlah, 3);
+    }
+
+    #[test]
+    fn test_policy_from_lookup() {
+        let policy = KeranjangPolicy::from_lookup(|key| (key == "HELD_CART_TTL_HOURS").then(|| "2".to_string()));
+        assert_eq!(policy.ttl, Duration::hours(2));
+
+        for invalid in ["0", "-1", "lama"] {
+            assert_eq!(KeranjangPolicy::from_lookup(|_| Some(invalid.to_string())), KeranjangPolicy::default());
+        }
+
+        let now = Utc::now();
+        assert_eq!(KeranjangPolicy::default().batas_kedaluwarsa(now), now - Duration::hours(DEFAULT_HELD_CART_TTL_HOURS));
+    }
+}
@@ -1,2 +1,12 @@
 pub mod transaksi;
-pub mod detail_transaksi;
\ No newline at end of file
+pub mod detail_transaksi;
+pub mod receipt;
+pub mod produk_stok;
+pub mod reservasi_stok;
+pub mod promosi;
+pub mod pajak;
+pub mod retur_penjualan;
+pub mod riwayat_status;
+pub mod penawaran;
+pub mod keranjang_tertahan;
+pub mod pengiriman;
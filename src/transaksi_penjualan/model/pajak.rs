@@ -0,0 +1,158 @@
+use rocket::serde::{Serialize, Deserialize};
+use crate::common::money::Rupiah;
+
+pub const DEFAULT_PPN_RATE_BP: i64 = 1100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum KategoriPajak {
+    #[default]
+    KenaPajak,
+    BebasPajak,
+}
+
+impl KategoriPajak {
+    pub fn from_string(kategori: &str) -> Option<Self> {
+        match kategori.to_uppercase().as_str() {
+            "KENA_PAJAK" => Some(KategoriPajak::KenaPajak),
+            "BEBAS_PAJAK" => Some(KategoriPajak::BebasPajak),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for KategoriPajak {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KategoriPajak::KenaPajak => write!(f, "KENA_PAJAK"),
+            KategoriPajak::BebasPajak => write!(f, "BEBAS_PAJAK"),
+        }
+    }
+}
+
+// Tarif dan cara membaca harga katalog. Disalin ke header transaksi saat dibuat,
+// jadi perubahan tarif tidak mengubah transaksi lama.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct KebijakanPajak {
+    pub tarif_bp: i64,
+    // true: harga katalog sudah termasuk PPN; false: PPN ditambahkan di atas harga
+    pub harga_termasuk_pajak: bool,
+}
+
+impl Default for KebijakanPajak {
+    fn default() -> Self {
+        KebijakanPajak {
+            tarif_bp: DEFAULT_PPN_RATE_BP,
+            harga_termasuk_pajak: true,
+        }
+    }
+}
+
+// Pajak satu baris: dpp (neto) + ppn = total (bruto)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PajakBaris {
+    pub kategori: KategoriPajak,
+    pub tarif_bp: i64,
+    pub dpp: Rupiah,
+    pub ppn: Rupiah,
+    pub total: Rupiah,
+}
+
+impl KebijakanPajak {
+    // STORE_PPN_BASIS_POINTS (1100 = 11%), STORE_PRICES_INCLUDE_PPN (default true)
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let default = KebijakanPajak::default();
+
+        KebijakanPajak {
+            tarif_bp: lookup("STORE_PPN_BASIS_POINTS")
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|bp: &i64| *bp >= 0)
+                .unwrap_or(default.tarif_bp),
+            harga_termasuk_pajak: match lookup("STORE_PRICES_INCLUDE_PPN").map(|v| v.trim().to_lowercase()) {
+                Some(v) if v == "false" || v == "0" => false,
+                Some(v) if v == "true" || v == "1" => true,
+                _ => default.harga_termasuk_pajak,
+            },
+        }
+    }
+
+    // `jumlah` adalah nilai baris setelah diskon, dalam harga katalog
+    pub fn hitung(&self, jumlah: Rupiah, kategori: KategoriPajak) -> PajakBaris {
+        let tarif_bp = match kategori {
+            KategoriPajak::KenaPajak => self.tarif_bp,
+            KategoriPajak::BebasPajak => 0,
+        };
+
+        let (dpp, ppn) = if self.harga_termasuk_pajak {
+            let dpp = jumlah.apply_rate(10_000, 10_000 + tarif_bp);
+            (dpp, jumlah - dpp)
+        } else {
+            (jumlah, jumlah.percent_bp(tarif_bp))
+        };
+
+        PajakBaris {
+            kategori,
+            tarif_bp,
+            dpp,
+            ppn,
+            total: dpp + ppn,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_lookup() {
+        let kebijakan = KebijakanPajak::from_lookup(|key| match key {
+            "STORE_PPN_BASIS_POINTS" => Some("1200".to_string()),
+            "STORE_PRICES_INCLUDE_PPN" => Some("false".to_string()),
+            _ => None,
+        });
+        assert_eq!(kebijakan, KebijakanPajak { tarif_bp: 1200, harga_termasuk_pajak: false });
+
+        let default = KebijakanPajak::from_lookup(|key| match key {
+            "STORE_PPN_BASIS_POINTS" => Some("-5".to_string()),
+            "STORE_PRICES_INCLUDE_PPN" => Some("kadang".to_string()),
+            _ => None,
+        });
+        assert_eq!(default, KebijakanPajak::default());
+    }
+
+    #[test]
+    fn test_inclusive_and_exclusive_pricing() {
+        let inklusif = KebijakanPajak { tarif_bp: 1100, harga_termasuk_pajak: true };
+        let pajak = inklusif.hitung(Rupiah::from_rupiah(111_000), KategoriPajak::KenaPajak);
+        assert_eq!((pajak.dpp, pajak.ppn, pajak.total), (Rupiah::from_rupiah(100_000), Rupiah::from_rupiah(11_000), Rupiah::from_rupiah(111_000)));
+
+        let eksklusif = KebijakanPajak { tarif_bp: 1100, harga_termasuk_pajak: false };
+        let pajak = eksklusif.hitung(Rupiah::from_rupiah(100_000), KategoriPajak::KenaPajak);
+        assert_eq!((pajak.dpp, pajak.ppn, pajak.total), (Rupiah::from_rupiah(100_000), Rupiah::from_rupiah(11_000), Rupiah::from_rupiah(111_000)));
+
+        let pajak = inklusif.hitung(Rupiah::from_rupiah(195_000), KategoriPajak::KenaPajak);
+        assert_eq!(pajak.dpp, Rupiah::from_sen(17_567_568));
+        assert_eq!(pajak.dpp + pajak.ppn, Rupiah::from_rupiah(195_000));
+    }
+
+    #[test]
+    fn test_exempt_goods_carry_no_tax() {
+        for harga_termasuk_pajak in [true, false] {
+            let kebijakan = KebijakanPajak { tarif_bp: 1100, harga_termasuk_pajak };
+            let pajak = kebijakan.hitung(Rupiah::from_rupiah(50_000), KategoriPajak::BebasPajak);
+            assert_eq!(pajak.tarif_bp, 0);
+            assert_eq!(pajak.ppn, Rupiah::ZERO);
+            assert_eq!(pajak.dpp, Rupiah::from_rupiah(50_000));
+            assert_eq!(pajak.total, Rupiah::from_rupiah(50_000));
+        }
+        assert_eq!(KategoriPajak::from_string("bebas_pajak"), Some(KategoriPajak::BebasPajak));
+        assert_eq!(KategoriPajak::BebasPajak.to_string(), "BEBAS_PAJAK");
+    }
+}
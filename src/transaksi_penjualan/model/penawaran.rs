@@ -0,0 +1,255 @@
+use chrono::{DateTime, Duration, Utc};
+use rocket::serde::{Serialize, Deserialize};
+use crate::common::money::Rupiah;
+use crate::transaksi_penjualan::model::detail_transaksi::DetailTransaksi;
+use crate::transaksi_penjualan::model::pajak::{KategoriPajak, KebijakanPajak, PajakBaris};
+use crate::transaksi_penjualan::model::promosi::DiskonTerapan;
+
+pub const DEFAULT_MASA_BERLAKU_HARI: i64 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StatusPenawaran {
+    Diajukan,
+    Diterima,
+    Ditolak,
+    Dikonversi,
+}
+
+impl StatusPenawaran {
+    pub fn from_string(status: &str) -> Option<Self> {
+        match status.to_uppercase().as_str() {
+            "DIAJUKAN" => Some(StatusPenawaran::Diajukan),
+            "DITERIMA" => Some(StatusPenawaran::Diterima),
+            "DITOLAK" => Some(StatusPenawaran::Ditolak),
+            "DIKONVERSI" => Some(StatusPenawaran::Dikonversi),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for StatusPenawaran {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatusPenawaran::Diajukan => write!(f, "DIAJUKAN"),
+            StatusPenawaran::Diterima => write!(f, "DITERIMA"),
+            StatusPenawaran::Ditolak => write!(f, "DITOLAK"),
+            StatusPenawaran::Dikonversi => write!(f, "DIKONVERSI"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DetailPenawaran {
+    pub id: i32,
+    pub id_penawaran: i32,
+    pub id_produk: i32,
+    pub nama_produk: String,
+    pub harga_satuan: Rupiah,
+    pub jumlah: u32,
+    pub diskon: Rupiah,
+    pub diskon_diterapkan: Vec<DiskonTerapan>,
+    pub subtotal: Rupiah,
+    pub kategori_pajak: KategoriPajak,
+    pub tarif_pajak_bp: i64,
+    pub dpp: Rupiah,
+    pub ppn: Rupiah,
+    pub total: Rupiah,
+}
+
+impl DetailPenawaran {
+    pub fn dari_detail(detail: &DetailTransaksi, nama_produk: String) -> Self {
+        DetailPenawaran {
+            id: 0,
+            id_penawaran: 0,
+            id_produk: detail.id_produk,
+            nama_produk,
+            harga_satuan: detail.harga_satuan,
+            jumlah: detail.jumlah,
+            diskon: detail.diskon,
+            diskon_diterapkan: detail.diskon_diterapkan.clone(),
+            subtotal: detail.subtotal,
+            kategori_pajak: detail.kategori_pajak,
+            tarif_pajak_bp: detail.tarif_pajak_bp,
+            dpp: detail.dpp,
+            ppn: detail.ppn,
+            total: detail.total,
+        }
+    }
+
+    // Harga, diskon dan pajak diambil apa adanya dari penawaran, bukan dari katalog saat ini
+    pub fn to_detail_transaksi(&self, id_transaksi: i32) -> DetailTransaksi {
+        let mut detail = DetailTransaksi::new(id_transaksi, self.id_produk, self.harga_satuan, self.jumlah);
+        detail.terapkan_diskon(self.diskon_diterapkan.clone());
+        detail.terapkan_pajak(self.pajak());
+        detail
+    }
+
+    pub fn pajak(&self) -> PajakBaris {
+        PajakBaris {
+            kategori: self.kategori_pajak,
+            tarif_bp: self.tarif_pajak_bp,
+            dpp: self.dpp,
+            ppn: self.ppn,
+            total: self.total,
+        }
+    }
+}
+
+// Penawaran harga untuk pelanggan (umumnya kontraktor) sebelum membeli. Tidak mereservasi stok;
+// stok baru dikurangi saat penawaran dikonversi menjadi transaksi.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Penawaran {
+    pub id: i32,
+    pub id_pelanggan: i32,
+    pub nama_pelanggan: String,
+    pub tanggal_penawaran: DateTime<Utc>,
+    pub berlaku_sampai: DateTime<Utc>,
+    pub status: StatusPenawaran,
+    // Total bruto (sudah termasuk PPN) = total_dpp + total_ppn
+    pub total_harga: Rupiah,
+    pub total_diskon: Rupiah,
+    pub total_dpp: Rupiah,
+    pub total_ppn: Rupiah,
+    pub tarif_pajak_bp: i64,
+    pub harga_termasuk_pajak: bool,
+    pub catatan: Option<String>,
+    pub detail: Vec<DetailPenawaran>,
+    // Transaksi hasil konversi, bila sudah dikonversi
+    #[serde(default)]
+    pub id_transaksi: Option<i32>,
+}
+
+impl Penawaran {
+    pub fn new(
+        id_pelanggan: i32,
+        nama_pelanggan: String,
+        catatan: Option<String>,
+        tanggal_penawaran: DateTime<Utc>,
+        berlaku_sampai: Option<DateTime<Utc>>,
+    ) -> Self {
+        Penawaran {
+            id: 0,
+            id_pelanggan,
+            nama_pelanggan,
+            tanggal_penawaran,
+            berlaku_sampai: berlaku_sampai.unwrap_or(tanggal_penawaran + Duration::days(DEFAULT_MASA_BERLAKU_HARI)),
+            status: StatusPenawaran::Diajukan,
+            total_harga: Rupiah::ZERO,
+            total_diskon: Rupiah::ZERO,
+            total_dpp: Rupiah::ZERO,
+            total_ppn: Rupiah::ZERO,
+            tarif_pajak_bp: 0,
+            harga_termasuk_pajak: true,
+            catatan,
+            detail: Vec::new(),
+            id_transaksi: None,
+        }
+    }
+
+    pub fn terapkan_detail(&mut self, kebijakan: KebijakanPajak, detail: Vec<DetailPenawaran>) {
+        self.tarif_pajak_bp = kebijakan.tarif_bp;
+        self.harga_termasuk_pajak = kebijakan.harga_termasuk_pajak;
+        self.total_diskon = detail.iter().map(|d| d.diskon).sum();
+        self.total_dpp = detail.iter().map(|d| d.dpp).sum();
+        self.total_ppn = detail.iter().map(|d| d.ppn).sum();
+        self.total_harga = self.total_dpp + self.total_ppn;
+        self.detail = detail;
+    }
+
+    pub fn kebijakan_pajak(&self) -> KebijakanPajak {
+        KebijakanPajak {
+            tarif_bp: self.tarif_pajak_bp,
+            harga_termasuk_pajak: self.harga_termasuk_pajak,
+        }
+    }
+
+    pub fn is_kedaluwarsa(&self, waktu: DateTime<Utc>) -> bool {
+        waktu > self.berlaku_sampai
+    }
+
+    // Penawaran yang sudah lewat masa berlaku tidak bisa diterima lagi, tetapi masih bisa ditolak
+    pub fn terima(&mut self, waktu: DateTime<Utc>) -> Result<(), String> {
+        if self.status != StatusPenawaran::Diajukan {
+            return Err(format!("Penawaran berstatus {} tidak dapat diterima", self.status));
+        }
+        if self.is_kedaluwarsa(waktu) {
+            return Err("Penawaran sudah kedaluwarsa".to_string());
+        }
+        self.status = StatusPenawaran::Diterima;
+        Ok(())
+    }
+
+    pub fn tolak(&mut self) -> Result<(), String> {
+        if !matches!(self.status, StatusPenawaran::Diajukan | StatusPenawaran::Diterima) {
+            return Err(format!("Penawaran berstatus {} tidak dapat ditolak", self.status));
+        }
+        self.status = StatusPenawaran::Ditolak;
+        Ok(())
+    }
+
+    pub fn konversi(&mut self, waktu: DateTime<Utc>) -> Result<(), String> {
+        if self.status != StatusPenawaran::Diterima {
+            return Err(format!("Hanya penawaran yang sudah diterima yang dapat dikonversi (status {})", self.status));
+        }
+        if self.is_kedaluwarsa(waktu) {
+            return Err("Penawaran sudah kedaluwarsa".to_string());
+        }
+        self.status = StatusPenawaran::Dikonversi;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn penawaran() -> Penawaran {
+        let tanggal = DateTime::parse_from_rfc3339("2025-03-01T08:00:00Z").unwrap().with_timezone(&Utc);
+        Penawaran::new(7, "CV Kontraktor Maju".to_string(), None, tanggal, None)
+    }
+
+    #[test]
+    fn test_default_masa_berlaku() {
+        let penawaran = penawaran();
+        assert_eq!(penawaran.berlaku_sampai - penawaran.tanggal_penawaran, Duration::days(DEFAULT_MASA_BERLAKU_HARI));
+        assert_eq!(penawaran.status, StatusPenawaran::Diajukan);
+        assert_eq!(StatusPenawaran::from_string("dikonversi"), Some(StatusPenawaran::Dikonversi));
+    }
+
+    #[test]
+    fn test_terima_dan_konversi_harus_sebelum_kedaluwarsa() {
+        let mut penawaran = penawaran();
+        let masih_berlaku = penawaran.berlaku_sampai - Duration::hours(1);
+        let lewat = penawaran.berlaku_sampai + Duration::hours(1);
+
+        assert!(penawaran.konversi(masih_berlaku).is_err());
+        assert!(penawaran.terima(lewat).is_err());
+        penawaran.terima(masih_berlaku).unwrap();
+        assert!(penawaran.konversi(lewat).is_err());
+        penawaran.konversi(masih_berlaku).unwrap();
+        assert_eq!(penawaran.status, StatusPenawaran::Dikonversi);
+        assert!(penawaran.tolak().is_err());
+    }
+
+    #[test]
+    fn test_detail_dikonversi_dengan_harga_penawaran() {
+        let mut asal = DetailTransaksi::new(0, 3, Rupiah::from_rupiah(100_000), 5);
+        asal.terapkan_diskon(vec![DiskonTerapan { id_promosi: 2, nama_promosi: "Grosir".to_string(), potongan: Rupiah::from_rupiah(50_000) }]);
+        asal.terapkan_pajak(KebijakanPajak::default().hitung(asal.subtotal, KategoriPajak::KenaPajak));
+        let detail = DetailPenawaran::dari_detail(&asal, "Semen".to_string());
+
+        let mut penawaran = penawaran();
+        penawaran.terapkan_detail(KebijakanPajak::default(), vec![detail.clone()]);
+        assert_eq!(penawaran.total_harga, Rupiah::from_rupiah(450_000));
+        assert_eq!(penawaran.total_diskon, Rupiah::from_rupiah(50_000));
+
+        let hasil = detail.to_detail_transaksi(12);
+        assert_eq!(hasil.id_transaksi, 12);
+        assert_eq!(hasil.subtotal, asal.subtotal);
+        assert_eq!(hasil.diskon_diterapkan, asal.diskon_diterapkan);
+        assert_eq!((hasil.dpp, hasil.ppn, hasil.total), (asal.dpp, asal.ppn, asal.total));
+    }
+}
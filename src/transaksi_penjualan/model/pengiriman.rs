@@ -0,0 +1,165 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rocket::serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StatusPengiriman {
+    Dijadwalkan,
+    DalamPerjalanan,
+    Terkirim,
+    Gagal,
+}
+
+impl StatusPengiriman {
+    pub fn from_string(status: &str) -> Option<Self> {
+        match status.to_uppercase().as_str() {
+            "DIJADWALKAN" => Some(StatusPengiriman::Dijadwalkan),
+            "DALAM_PERJALANAN" => Some(StatusPengiriman::DalamPerjalanan),
+            "TERKIRIM" => Some(StatusPengiriman::Terkirim),
+            "GAGAL" => Some(StatusPengiriman::Gagal),
+            _ => None,
+        }
+    }
+
+    // Dijadwalkan -> dalam perjalanan -> terkirim; gagal bisa terjadi sebelum atau saat perjalanan.
+    // Terkirim dan gagal adalah status akhir; barang yang gagal dikirim dijadwalkan lewat pengiriman baru.
+    pub fn bisa_menjadi(&self, ke: StatusPengiriman) -> bool {
+        matches!(
+            (self, ke),
+            (StatusPengiriman::Dijadwalkan, StatusPengiriman::DalamPerjalanan)
+                | (StatusPengiriman::Dijadwalkan, StatusPengiriman::Gagal)
+                | (StatusPengiriman::DalamPerjalanan, StatusPengiriman::Terkirim)
+                | (StatusPengiriman::DalamPerjalanan, StatusPengiriman::Gagal)
+        )
+    }
+}
+
+impl std::fmt::Display for StatusPengiriman {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatusPengiriman::Dijadwalkan => write!(f, "DIJADWALKAN"),
+            StatusPengiriman::DalamPerjalanan => write!(f, "DALAM_PERJALANAN"),
+            StatusPengiriman::Terkirim => write!(f, "TERKIRIM"),
+            StatusPengiriman::Gagal => write!(f, "GAGAL"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DetailPengiriman {
+    pub id: i32,
+    pub id_pengiriman: i32,
+    pub id_detail_transaksi: i32,
+    pub id_produk: i32,
+    pub nama_produk: String,
+    pub jumlah: u32,
+}
+
+// Surat jalan untuk sebagian atau seluruh barang dari satu transaksi
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Pengiriman {
+    pub id: i32,
+    pub id_transaksi: i32,
+    // Diambil dari transaksi, untuk daftar pengiriman harian
+    pub nama_pelanggan: String,
+    pub alamat: String,
+    pub tanggal_kirim: NaiveDate,
+    pub kendaraan: Option<String>,
+    pub pengemudi: Option<String>,
+    pub status: StatusPengiriman,
+    pub catatan: Option<String>,
+    // Catatan serah terima bila terkirim, alasan bila gagal
+    pub bukti_pengiriman: Option<String>,
+    pub penerima: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub detail: Vec<DetailPengiriman>,
+}
+
+impl Pengiriman {
+    pub fn ubah_status(
+        &mut self,
+        ke: StatusPengiriman,
+        bukti_pengiriman: Option<String>,
+        penerima: Option<String>,
+    ) -> Result<(), String> {
+        if !self.status.bisa_menjadi(ke) {
+            return Err(format!("Status pengiriman tidak dapat diubah dari {} ke {}", self.status, ke));
+        }
+
+        let bukti_pengiriman = bukti_pengiriman.filter(|b| !b.trim().is_empty());
+        match ke {
+            StatusPengiriman::DalamPerjalanan if self.kendaraan.is_none() || self.pengemudi.is_none() => {
+                return Err("Kendaraan dan pengemudi harus diisi sebelum berangkat".to_string());
+            }
+            StatusPengiriman::Terkirim if bukti_pengiriman.is_none() => {
+                return Err("Bukti pengiriman harus diisi".to_string());
+            }
+            StatusPengiriman::Gagal if bukti_pengiriman.is_none() => {
+                return Err("Alasan pengiriman gagal harus diisi".to_string());
+            }
+            _ => {}
+        }
+
+        self.status = ke;
+        if bukti_pengiriman.is_some() {
+            self.bukti_pengiriman = bukti_pengiriman;
+        }
+        if let Some(penerima) = penerima.filter(|p| !p.trim().is_empty()) {
+            self.penerima = Some(penerima);
+        }
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pengiriman() -> Pengiriman {
+        Pengiriman {
+            id: 1,
+            id_transaksi: 1,
+            nama_pelanggan: "CV Kontraktor Maju".to_string(),
+            alamat: "Jl. Raya Bogor Km 30".to_string(),
+            tanggal_kirim: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            kendaraan: None,
+            pengemudi: Some("Joko".to_string()),
+            status: StatusPengiriman::Dijadwalkan,
+            catatan: None,
+            bukti_pengiriman: None,
+            penerima: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            detail: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        assert!(StatusPengiriman::Dijadwalkan.bisa_menjadi(StatusPengiriman::DalamPerjalanan));
+        assert!(!StatusPengiriman::Dijadwalkan.bisa_menjadi(StatusPengiriman::Terkirim));
+        assert!(!StatusPengiriman::Terkirim.bisa_menjadi(StatusPengiriman::Gagal));
+        assert!(!StatusPengiriman::Gagal.bisa_menjadi(StatusPengiriman::Dijadwalkan));
+        assert_eq!(StatusPengiriman::from_string("dalam_perjalanan"), Some(StatusPengiriman::DalamPerjalanan));
+        assert_eq!(StatusPengiriman::DalamPerjalanan.to_string(), "DALAM_PERJALANAN");
+    }
+
+    #[test]
+    fn test_ubah_status_requires_vehicle_and_proof() {
+        let mut pengiriman = pengiriman();
+        assert!(pengiriman.ubah_status(StatusPengiriman::DalamPerjalanan, None, None).is_err());
+
+        pengiriman.kendaraan = Some("Truk B 9123 KX".to_string());
+        pengiriman.ubah_status(StatusPengiriman::DalamPerjalanan, None, None).unwrap();
+        assert!(pengiriman.ubah_status(StatusPengiriman::Terkirim, Some(" ".to_string()), None).is_err());
+
+        pengiriman.ubah_status(StatusPengiriman::Terkirim, Some("Diterima lengkap".to_string()), Some("Pak Mandor".to_string())).unwrap();
+        assert_eq!(pengiriman.status, StatusPengiriman::Terkirim);
+        assert_eq!(pengiriman.bukti_pengiriman.as_deref(), Some("Diterima lengkap"));
+        assert_eq!(pengiriman.penerima.as_deref(), Some("Pak Mandor"));
+    }
+}
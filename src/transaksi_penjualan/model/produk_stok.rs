@@ -0,0 +1,15 @@
+use rocket::serde::{Serialize, Deserialize};
+use crate::common::money::Rupiah;
+use crate::transaksi_penjualan::model::pajak::KategoriPajak;
+
+// Potongan data tabel `produk` yang dibutuhkan transaksi penjualan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ProdukStok {
+    pub id: i32,
+    pub nama: String,
+    pub kategori: String,
+    pub kategori_pajak: KategoriPajak,
+    pub harga: Rupiah,
+    pub stok: u32,
+}
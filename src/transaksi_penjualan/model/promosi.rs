@@ -0,0 +1,435 @@
+use chrono::{DateTime, Utc};
+use rocket::serde::{Serialize, Deserialize};
+use crate::common::money::Rupiah;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TingkatHarga {
+    pub min_jumlah: u32,
+    pub harga_satuan: Rupiah,
+}
+
+// Jenis potongan. Disimpan sebagai JSON di kolom `promosi.aturan`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", tag = "jenis", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AturanPromosi {
+    // Persen dalam basis poin (1000 = 10%) dari nilai yang tersisa setelah potongan sebelumnya
+    DiskonPersen { basis_poin: i64 },
+    // Potongan tetap per baris, atau per transaksi bila cakupannya TRANSAKSI
+    DiskonNominal { potongan: Rupiah },
+    BeliXGratisY { beli: u32, gratis: u32 },
+    // Harga grosir: tingkat dengan min_jumlah terbesar yang terpenuhi yang dipakai
+    HargaBertingkat { tingkat: Vec<TingkatHarga> },
+    // Harga khusus untuk satu pelanggan
+    HargaPelanggan { harga_satuan: Rupiah },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", tag = "jenis", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CakupanPromosi {
+    Produk { id_produk: i32 },
+    Kategori { kategori: String },
+    Transaksi,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Promosi {
+    pub id: i32,
+    pub nama: String,
+    pub aturan: AturanPromosi,
+    pub cakupan: CakupanPromosi,
+    pub id_pelanggan: Option<i32>,
+    pub berlaku_mulai: Option<DateTime<Utc>>,
+    pub berlaku_sampai: Option<DateTime<Utc>>,
+    // Prioritas lebih kecil dievaluasi lebih dulu
+    pub prioritas: i32,
+    // Promosi yang tidak dapat digabung hanya berlaku bila belum ada potongan lain,
+    // dan setelah berlaku tidak ada promosi lain yang ditambahkan
+    pub dapat_digabung: bool,
+    pub aktif: bool,
+}
+
+// Jejak potongan per detail transaksi untuk audit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DiskonTerapan {
+    pub id_promosi: i32,
+    pub nama_promosi: String,
+    pub potongan: Rupiah,
+}
+
+// Satu baris belanja yang dinilai mesin promosi
+#[derive(Debug, Clone)]
+pub struct BarisPromosi {
+    pub id_produk: i32,
+    pub kategori: Option<String>,
+    pub harga_satuan: Rupiah,
+    pub jumlah: u32,
+}
+
+impl BarisPromosi {
+    fn bruto(&self) -> Rupiah {
+        self.harga_satuan * self.jumlah
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RincianHarga {
+    pub total: Rupiah,
+    pub total_diskon: Rupiah,
+    // Urutannya sama dengan baris yang dinilai
+    pub diskon_per_baris: Vec<Vec<DiskonTerapan>>,
+}
+
+impl AturanPromosi {
+    fn potongan_baris(&self, baris: &BarisPromosi, neto: Rupiah) -> Rupiah {
+        match self {
+            AturanPromosi::DiskonPersen { basis_poin } => neto.percent_bp(*basis_poin),
+            AturanPromosi::DiskonNominal { potongan } => *potongan,
+            AturanPromosi::BeliXGratisY { beli, gratis } => {
+                let gratis_total = baris.jumlah / (beli + gratis) * gratis;
+                baris.harga_satuan * gratis_total
+            }
+            AturanPromosi::HargaBertingkat { tingkat } => tingkat.iter()
+                .filter(|t| t.min_jumlah <= baris.jumlah)
+                .max_by_key(|t| t.min_jumlah)
+                .map(|t| Self::selisih_harga(baris, t.harga_satuan))
+                .unwrap_or(Rupiah::ZERO),
+            AturanPromosi::HargaPelanggan { harga_satuan } => Self::selisih_harga(baris, *harga_satuan),
+        }
+    }
+
+    fn selisih_harga(baris: &BarisPromosi, harga_khusus: Rupiah) -> Rupiah {
+        if harga_khusus < baris.harga_satuan {
+            (baris.harga_satuan - harga_khusus) * baris.jumlah
+        } else {
+            Rupiah::ZERO
+        }
+    }
+
+    fn potongan_transaksi(&self, neto: Rupiah) -> Rupiah {
+        match self {
+            AturanPromosi::DiskonPersen { basis_poin } => neto.percent_bp(*basis_poin),
+            AturanPromosi::DiskonNominal { potongan } => *potongan,
+            _ => Rupiah::ZERO,
+        }
+    }
+}
+
+impl Promosi {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.nama.trim().is_empty() {
+            return Err("Nama promosi tidak boleh kosong".to_string());
+        }
+
+        match &self.aturan {
+            AturanPromosi::DiskonPersen { basis_poin } if !(1..=10_000).contains(basis_poin) => {
+                return Err("Diskon persen harus antara 1 dan 10000 basis poin".to_string());
+            }
+            AturanPromosi::DiskonNominal { potongan } if !potongan.is_positive() => {
+                return Err("Potongan harus lebih dari 0".to_string());
+            }
+            AturanPromosi::BeliXGratisY { beli, gratis } if *beli == 0 || *gratis == 0 => {
+                return Err("Jumlah beli dan gratis harus lebih dari 0".to_string());
+            }
+            AturanPromosi::HargaBertingkat { tingkat } => {
+                if tingkat.is_empty() {
+                    return Err("Harga bertingkat membutuhkan minimal satu tingkat".to_string());
+                }
+                if tingkat.iter().any(|t| t.min_jumlah == 0 || t.harga_satuan.is_negative()) {
+                    return Err("Tingkat harga membutuhkan min_jumlah lebih dari 0 dan harga tidak negatif".to_string());
+                }
+            }
+            AturanPromosi::HargaPelanggan { harga_satuan } => {
+                if harga_satuan.is_negative() {
+                    return Err("Harga pelanggan tidak boleh negatif".to_string());
+                }
+                if self.id_pelanggan.is_none() {
+                    return Err("Harga pelanggan membutuhkan id_pelanggan".to_string());
+                }
+            }
+            _ => {}
+        }
+
+        let aturan_per_baris = !matches!(self.aturan, AturanPromosi::DiskonPersen { .. } | AturanPromosi::DiskonNominal { .. });
+        if aturan_per_baris && self.cakupan == CakupanPromosi::Transaksi {
+            return Err("Aturan ini hanya berlaku untuk cakupan produk atau kategori".to_string());
+        }
+
+        if let (Some(mulai), Some(sampai)) = (self.berlaku_mulai, self.berlaku_sampai)
+            && mulai >= sampai
+        {
+            return Err("berlaku_mulai harus sebelum berlaku_sampai".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn berlaku_untuk(&self, id_pelanggan: i32, waktu: DateTime<Utc>) -> bool {
+        self.aktif
+            && self.id_pelanggan.is_none_or(|id| id == id_pelanggan)
+            && self.berlaku_mulai.is_none_or(|mulai| mulai <= waktu)
+            && self.berlaku_sampai.is_none_or(|sampai| waktu < sampai)
+    }
+
+    fn cocok_dengan(&self, baris: &BarisPromosi) -> bool {
+        match &self.cakupan {
+            CakupanPromosi::Produk { id_produk } => *id_produk == baris.id_produk,
+            CakupanPromosi::Kategori { kategori } => baris.kategori.as_deref()
+                .is_some_and(|k| k.eq_ignore_ascii_case(kategori)),
+            CakupanPromosi::Transaksi => false,
+        }
+    }
+
+    fn terapan(&self, potongan: Rupiah) -> DiskonTerapan {
+        DiskonTerapan {
+            id_promosi: self.id,
+            nama_promosi: self.nama.clone(),
+            potongan,
+        }
+    }
+}
+
+// Menilai promosi yang berlaku terhadap isi keranjang.
+// Promosi per baris (produk/kategori) diterapkan lebih dulu, lalu promosi transaksi
+// dihitung dari sisa nilai dan dibagi ke baris secara proporsional agar tiap detail
+// menyimpan potongannya sendiri.
+pub struct MesinPromosi<'a> {
+    promosi: Vec<&'a Promosi>,
+}
+
+impl<'a> MesinPromosi<'a> {
+    pub fn new(promosi: &'a [Promosi], id_pelanggan: i32, waktu: DateTime<Utc>) -> Self {
+        let mut promosi: Vec<&Promosi> = promosi.iter()
+            .filter(|p| p.berlaku_untuk(id_pelanggan, waktu))
+            .collect();
+        promosi.sort_by_key(|p| (p.prioritas, p.id));
+        MesinPromosi { promosi }
+    }
+
+    pub fn hitung(&self, baris: &[BarisPromosi]) -> RincianHarga {
+        let mut diskon: Vec<Vec<DiskonTerapan>> = vec![Vec::new(); baris.len()];
+        let mut tertutup = vec![false; baris.len()];
+
+        for (i, item) in baris.iter().enumerate() {
+            for promosi in self.promosi.iter().filter(|p| p.cocok_dengan(item)) {
+                if tertutup[i] {
+                    break;
+                }
+                if !promosi.dapat_digabung && !diskon[i].is_empty() {
+                    continue;
+                }
+
+                let neto = item.bruto() - diskon[i].iter().map(|d| d.potongan).sum();
+                let potongan = promosi.aturan.potongan_baris(item, neto).min(neto);
+                if potongan.is_positive() {
+                    diskon[i].push(promosi.terapan(potongan));
+                    tertutup[i] = !promosi.dapat_digabung;
+                }
+            }
+        }
+
+        for promosi in self.promosi.iter().filter(|p| p.cakupan == CakupanPromosi::Transaksi) {
+            if !promosi.dapat_digabung && diskon.iter().any(|d| !d.is_empty()) {
+                continue;
+            }
+
+            let neto: Vec<Rupiah> = baris.iter().enumerate()
+                .map(|(i, item)| if tertutup[i] {
+                    Rupiah::ZERO
+                } else {
+                    item.bruto() - diskon[i].iter().map(|d| d.potongan).sum()
+                })
+                .collect();
+            let neto_total: Rupiah = neto.iter().sum();
+            let potongan = promosi.aturan.potongan_transaksi(neto_total).min(neto_total);
+            if !potongan.is_positive() {
+                continue;
+            }
+
+            for (i, bagian) in Self::bagi_proporsional(potongan, &neto).into_iter().enumerate() {
+                if bagian.is_positive() {
+                    diskon[i].push(promosi.terapan(bagian));
+                }
+            }
+            if !promosi.dapat_digabung {
+                break;
+            }
+        }
+
+        let bruto: Rupiah = baris.iter().map(|b| b.bruto()).sum();
+        let total_diskon: Rupiah = diskon.iter().flatten().map(|d| d.potongan).sum();
+        RincianHarga {
+            total: bruto - total_diskon,
+            total_diskon,
+            diskon_per_baris: diskon,
+        }
+    }
+
+    // Sisa pembulatan sen diberikan ke baris dengan nilai terbesar
+    fn bagi_proporsional(potongan: Rupiah, neto: &[Rupiah]) -> Vec<Rupiah> {
+        let neto_total: Rupiah = neto.iter().sum();
+        let mut bagian: Vec<Rupiah> = neto.iter()
+            .map(|n| potongan.apply_rate(n.sen(), neto_total.sen()))
+            .collect();
+
+        let sisa = potongan - bagian.iter().sum();
+        if let Some((terbesar, _)) = neto.iter().enumerate().max_by_key(|(_, n)| **n) {
+            bagian[terbesar] += sisa;
+        }
+        bagian
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn promosi(id: i32, aturan: AturanPromosi, cakupan: CakupanPromosi) -> Promosi {
+        Promosi {
+            id,
+            nama: format!("Promo {}", id),
+            aturan,
+            cakupan,
+            id_pelanggan: None,
+            berlaku_mulai: None,
+            berlaku_sampai: None,
+            prioritas: 0,
+            dapat_digabung: true,
+            aktif: true,
+        }
+    }
+
+    fn baris(id_produk: i32, kategori: &str, harga: i64, jumlah: u32) -> BarisPromosi {
+        BarisPromosi {
+            id_produk,
+            kategori: Some(kategori.to_string()),
+            harga_satuan: Rupiah::from_rupiah(harga),
+            jumlah,
+        }
+    }
+
+    fn potongan(rincian: &RincianHarga, i: usize) -> Vec<(i32, Rupiah)> {
+        rincian.diskon_per_baris[i].iter().map(|d| (d.id_promosi, d.potongan)).collect()
+    }
+
+    fn waktu() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, 10, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_line_rules() {
+        let daftar = vec![
+            promosi(1, AturanPromosi::DiskonPersen { basis_poin: 1000 }, CakupanPromosi::Produk { id_produk: 1 }),
+            promosi(2, AturanPromosi::DiskonNominal { potongan: Rupiah::from_rupiah(5_000) }, CakupanPromosi::Kategori { kategori: "cat".to_string() }),
+            promosi(3, AturanPromosi::BeliXGratisY { beli: 2, gratis: 1 }, CakupanPromosi::Produk { id_produk: 3 }),
+            promosi(4, AturanPromosi::HargaBertingkat { tingkat: vec![
+                TingkatHarga { min_jumlah: 10, harga_satuan: Rupiah::from_rupiah(70_000) },
+                TingkatHarga { min_jumlah: 50, harga_satuan: Rupiah::from_rupiah(65_000) },
+            ] }, CakupanPromosi::Produk { id_produk: 4 }),
+        ];
+
+        let rincian = MesinPromosi::new(&daftar, 1, waktu()).hitung(&[
+            baris(1, "Semen", 100_000, 2),
+            baris(2, "Cat", 250_000, 1),
+            baris(3, "Keramik", 50_000, 7),
+            baris(4, "Pipa", 75_000, 12),
+        ]);
+
+        assert_eq!(potongan(&rincian, 0), vec![(1, Rupiah::from_rupiah(20_000))]);
+        assert_eq!(potongan(&rincian, 1), vec![(2, Rupiah::from_rupiah(5_000))]);
+        assert_eq!(potongan(&rincian, 2), vec![(3, Rupiah::from_rupiah(100_000))]);
+        assert_eq!(potongan(&rincian, 3), vec![(4, Rupiah::from_rupiah(60_000))]);
+        assert_eq!(rincian.total_diskon, Rupiah::from_rupiah(185_000));
+        assert_eq!(rincian.total, Rupiah::from_rupiah(200_000 + 250_000 + 350_000 + 900_000 - 185_000));
+    }
+
+    #[test]
+    fn test_customer_price_window_and_priority() {
+        let mut khusus = promosi(1, AturanPromosi::HargaPelanggan { harga_satuan: Rupiah::from_rupiah(90_000) }, CakupanPromosi::Produk { id_produk: 1 });
+        khusus.id_pelanggan = Some(7);
+        khusus.prioritas = 1;
+        let mut kedaluwarsa = promosi(2, AturanPromosi::DiskonPersen { basis_poin: 5000 }, CakupanPromosi::Produk { id_produk: 1 });
+        kedaluwarsa.berlaku_sampai = Some(waktu());
+        let persen = promosi(3, AturanPromosi::DiskonPersen { basis_poin: 1000 }, CakupanPromosi::Produk { id_produk: 1 });
+        let daftar = vec![khusus, kedaluwarsa, persen];
+
+        // Persen (prioritas 0) dihitung lebih dulu, harga pelanggan dari sisa nilainya
+        let rincian = MesinPromosi::new(&daftar, 7, waktu()).hitung(&[baris(1, "Semen", 100_000, 2)]);
+        assert_eq!(potongan(&rincian, 0), vec![(3, Rupiah::from_rupiah(20_000)), (1, Rupiah::from_rupiah(20_000))]);
+
+        let rincian = MesinPromosi::new(&daftar, 8, waktu()).hitung(&[baris(1, "Semen", 100_000, 2)]);
+        assert_eq!(potongan(&rincian, 0), vec![(3, Rupiah::from_rupiah(20_000))]);
+    }
+
+    #[test]
+    fn test_exclusive_rules_do_not_stack() {
+        let mut eksklusif = promosi(1, AturanPromosi::DiskonPersen { basis_poin: 2000 }, CakupanPromosi::Kategori { kategori: "Semen".to_string() });
+        eksklusif.dapat_digabung = false;
+        let lain = promosi(2, AturanPromosi::DiskonNominal { potongan: Rupiah::from_rupiah(1_000) }, CakupanPromosi::Produk { id_produk: 1 });
+        let mut transaksi_eksklusif = promosi(3, AturanPromosi::DiskonPersen { basis_poin: 5000 }, CakupanPromosi::Transaksi);
+        transaksi_eksklusif.dapat_digabung = false;
+        let daftar = vec![eksklusif, lain, transaksi_eksklusif];
+
+        let rincian = MesinPromosi::new(&daftar, 1, waktu()).hitung(&[baris(1, "Semen", 100_000, 1)]);
+        assert_eq!(potongan(&rincian, 0), vec![(1, Rupiah::from_rupiah(20_000))]);
+
+        let rincian = MesinPromosi::new(&daftar, 1, waktu()).hitung(&[baris(2, "Pipa", 100_000, 1)]);
+        assert_eq!(potongan(&rincian, 0), vec![(3, Rupiah::from_rupiah(50_000))]);
+    }
+
+    #[test]
+    fn test_transaction_discount_is_allocated_across_lines() {
+        let daftar = vec![
+            promosi(1, AturanPromosi::DiskonNominal { potongan: Rupiah::from_rupiah(10_000) }, CakupanPromosi::Produk { id_produk: 1 }),
+            promosi(2, AturanPromosi::DiskonNominal { potongan: Rupiah::from_rupiah(1_000) }, CakupanPromosi::Transaksi),
+        ];
+
+        let rincian = MesinPromosi::new(&daftar, 1, waktu()).hitung(&[
+            baris(1, "Semen", 100_000, 1),
+            baris(2, "Cat", 45_000, 1),
+            baris(3, "Pipa", 45_000, 1),
+        ]);
+
+        // Sisa 90.000 : 45.000 : 45.000 dari potongan Rp 1.000
+        assert_eq!(potongan(&rincian, 0), vec![(1, Rupiah::from_rupiah(10_000)), (2, Rupiah::from_rupiah(500))]);
+        assert_eq!(potongan(&rincian, 1), vec![(2, Rupiah::from_rupiah(250))]);
+        assert_eq!(potongan(&rincian, 2), vec![(2, Rupiah::from_rupiah(250))]);
+        assert_eq!(rincian.total, Rupiah::from_rupiah(190_000 - 11_000));
+
+        let rincian = MesinPromosi::new(&daftar, 1, waktu()).hitung(&[
+            baris(2, "Cat", 1, 1),
+            baris(3, "Pipa", 1, 2),
+        ]);
+        let dibagi: Rupiah = rincian.diskon_per_baris.iter().flatten().map(|d| d.potongan).sum();
+        assert_eq!(dibagi, Rupiah::from_rupiah(3));
+        assert_eq!(rincian.total, Rupiah::ZERO);
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid = promosi(1, AturanPromosi::DiskonPersen { basis_poin: 1000 }, CakupanPromosi::Transaksi);
+        assert!(valid.validate().is_ok());
+
+        let mut invalid = promosi(1, AturanPromosi::BeliXGratisY { beli: 2, gratis: 1 }, CakupanPromosi::Transaksi);
+        assert!(invalid.validate().is_err());
+        invalid.aturan = AturanPromosi::HargaPelanggan { harga_satuan: Rupiah::from_rupiah(1) };
+        invalid.cakupan = CakupanPromosi::Produk { id_produk: 1 };
+        assert!(invalid.validate().is_err());
+        invalid.id_pelanggan = Some(1);
+        assert!(invalid.validate().is_ok());
+        invalid.berlaku_mulai = Some(waktu());
+        invalid.berlaku_sampai = Some(waktu());
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_rule_json_format() {
+        let aturan: AturanPromosi = serde_json::from_str(r#"{"jenis":"BELI_X_GRATIS_Y","beli":3,"gratis":1}"#).unwrap();
+        assert_eq!(aturan, AturanPromosi::BeliXGratisY { beli: 3, gratis: 1 });
+        let cakupan: CakupanPromosi = serde_json::from_str(r#"{"jenis":"KATEGORI","kategori":"Semen"}"#).unwrap();
+        assert_eq!(cakupan, CakupanPromosi::Kategori { kategori: "Semen".to_string() });
+    }
+}
@@ -0,0 +1,215 @@
+use rocket::serde::{Serialize, Deserialize};
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::model::payment::PaymentMethod;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct StoreProfile {
+    pub nama: String,
+    pub alamat: Option<String>,
+    pub telepon: Option<String>,
+    pub npwp: Option<String>,
+}
+
+impl Default for StoreProfile {
+    fn default() -> Self {
+        StoreProfile {
+            nama: "BuildingStore".to_string(),
+            alamat: None,
+            telepon: None,
+            npwp: None,
+        }
+    }
+}
+
+impl StoreProfile {
+    // STORE_NAME, STORE_ADDRESS, STORE_PHONE, STORE_NPWP; tarif PPN diatur KebijakanPajak
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let default = StoreProfile::default();
+        let text = |key: &str| lookup(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+        StoreProfile {
+            nama: text("STORE_NAME").unwrap_or(default.nama),
+            alamat: text("STORE_ADDRESS"),
+            telepon: text("STORE_PHONE"),
+            npwp: text("STORE_NPWP"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub enum ReceiptDocument {
+    Struk,
+    Faktur,
+    // Penawaran harga; dibuat dari penawaran, bukan dari transaksi
+    Penawaran,
+}
+
+impl ReceiptDocument {
+    pub fn from_string(document: &str) -> Option<Self> {
+        match document.to_lowercase().as_str() {
+            "struk" | "receipt" => Some(ReceiptDocument::Struk),
+            "faktur" | "invoice" => Some(ReceiptDocument::Faktur),
+            _ => None,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ReceiptDocument::Struk => "STRUK PENJUALAN",
+            ReceiptDocument::Faktur => "FAKTUR PENJUALAN",
+            ReceiptDocument::Penawaran => "PENAWARAN HARGA",
+        }
+    }
+
+    pub fn number(&self, id_transaksi: i32) -> String {
+        match self {
+            ReceiptDocument::Struk => format!("STR-{:06}", id_transaksi),
+            ReceiptDocument::Faktur => format!("INV-{:06}", id_transaksi),
+            ReceiptDocument::Penawaran => format!("QUO-{:06}", id_transaksi),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ReceiptLine {
+    pub nama_produk: String,
+    pub jumlah: u32,
+    pub harga_satuan: Rupiah,
+    // Total potongan promosi; subtotal sudah dikurangi nilai ini
+    pub diskon: Rupiah,
+    pub subtotal: Rupiah,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ReceiptPayment {
+    pub method: PaymentMethod,
+    pub amount: Rupiah,
+    pub reference: Option<String>,
+}
+
+impl ReceiptPayment {
+    pub fn label(&self) -> &'static str {
+        match self.method {
+            PaymentMethod::Cash => "Tunai",
+            PaymentMethod::CreditCard => "Kartu Kredit",
+            PaymentMethod::BankTransfer => "Transfer Bank",
+            PaymentMethod::EWallet => "E-Wallet",
+        }
+    }
+}
+
+// Ringkasan PPN transaksi: DPP + PPN = total. `inclusive` untuk harga yang sudah termasuk PPN,
+// yaitu DPP = total * 100 / (100 + tarif)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TaxBreakdown {
+    pub rate_bp: i64,
+    pub dpp: Rupiah,
+    pub ppn: Rupiah,
+}
+
+impl TaxBreakdown {
+    pub fn inclusive(total: Rupiah, rate_bp: i64) -> Self {
+        let dpp = total.apply_rate(10_000, 10_000 + rate_bp);
+        TaxBreakdown {
+            rate_bp,
+            dpp,
+            ppn: total - dpp,
+        }
+    }
+
+    // 1100 -> "11%", 1250 -> "12.5%"
+    pub fn rate_label(&self) -> String {
+        let whole = self.rate_bp / 100;
+        let fraction = self.rate_bp % 100;
+        if fraction == 0 {
+            format!("{}%", whole)
+        } else {
+            format!("{}.{}%", whole, format!("{:02}", fraction).trim_end_matches('0'))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Receipt {
+    pub document: ReceiptDocument,
+    pub nomor: String,
+    pub store: StoreProfile,
+    pub id_transaksi: i32,
+    pub tanggal_transaksi: String,
+    // Hanya untuk penawaran harga
+    #[serde(default)]
+    pub berlaku_sampai: Option<String>,
+    pub nama_pelanggan: String,
+    pub catatan: Option<String>,
+    pub lines: Vec<ReceiptLine>,
+    pub total: Rupiah,
+    pub tax: TaxBreakdown,
+    pub payments: Vec<ReceiptPayment>,
+    pub paid: Rupiah,
+    pub refunded: Rupiah,
+    pub outstanding: Rupiah,
+}
+
+impl Receipt {
+    pub fn item_count(&self) -> u32 {
+        self.lines.iter().map(|l| l.jumlah).sum()
+    }
+
+    pub fn is_paid_in_full(&self) -> bool {
+        !self.outstanding.is_positive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_store_profile_from_lookup() {
+        let profile = StoreProfile::from_lookup(|key| match key {
+            "STORE_NAME" => Some("Toko Bangunan Jaya".to_string()),
+            "STORE_ADDRESS" => Some("  ".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(profile.nama, "Toko Bangunan Jaya");
+        assert_eq!(profile.alamat, None);
+
+        let default = StoreProfile::from_lookup(|key| (key == "STORE_NAME").then(|| " ".to_string()));
+        assert_eq!(default, StoreProfile::default());
+    }
+
+    #[test]
+    fn test_inclusive_tax_breakdown() {
+        let tax = TaxBreakdown::inclusive(Rupiah::from_rupiah(111_000), 1100);
+        assert_eq!(tax.dpp, Rupiah::from_rupiah(100_000));
+        assert_eq!(tax.ppn, Rupiah::from_rupiah(11_000));
+        assert_eq!(tax.rate_label(), "11%");
+
+        let tax = TaxBreakdown::inclusive(Rupiah::from_rupiah(195_000), 1100);
+        assert_eq!(tax.dpp + tax.ppn, Rupiah::from_rupiah(195_000));
+        assert_eq!(tax.dpp, Rupiah::from_sen(17_567_568));
+
+        assert_eq!(TaxBreakdown::inclusive(Rupiah::ZERO, 1250).rate_label(), "12.5%");
+        assert_eq!(TaxBreakdown::inclusive(Rupiah::from_rupiah(1000), 0).ppn, Rupiah::ZERO);
+    }
+
+    #[test]
+    fn test_document_numbering() {
+        assert_eq!(ReceiptDocument::from_string("INVOICE"), Some(ReceiptDocument::Faktur));
+        assert_eq!(ReceiptDocument::Struk.number(42), "STR-000042");
+        assert_eq!(ReceiptDocument::Faktur.number(42), "INV-000042");
+        assert_eq!(ReceiptDocument::Penawaran.number(42), "QUO-000042");
+        assert_eq!(ReceiptDocument::from_string("nota"), None);
+    }
+}
@@ -0,0 +1,78 @@
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rocket::serde::{Serialize, Deserialize};
+
+pub const DEFAULT_RESERVATION_TTL_MINUTES: i64 = 30;
+
+// Stok yang ditahan satu baris detail transaksi yang masih diproses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ReservasiStok {
+    pub id_detail: i32,
+    pub id_transaksi: i32,
+    pub id_produk: i32,
+    pub jumlah: u32,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Lama reservasi sejak aktivitas terakhir pada keranjang (tambah/ubah item)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReservasiPolicy {
+    pub ttl: Duration,
+}
+
+impl Default for ReservasiPolicy {
+    fn default() -> Self {
+        ReservasiPolicy {
+            ttl: Duration::minutes(DEFAULT_RESERVATION_TTL_MINUTES),
+        }
+    }
+}
+
+impl ReservasiPolicy {
+    // STOCK_RESERVATION_TTL_MINUTES, default 30 menit
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        match lookup("STOCK_RESERVATION_TTL_MINUTES").and_then(|v| v.trim().parse::<i64>().ok()) {
+            Some(minutes) if minutes > 0 => ReservasiPolicy { ttl: Duration::minutes(minutes) },
+            _ => ReservasiPolicy::default(),
+        }
+    }
+
+    pub fn expires_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now + self.ttl
+    }
+}
+
+// Format tetap (UTC, detik, akhiran Z) supaya kolom expires_at bisa dibandingkan sebagai teks
+pub fn format_expires_at(value: DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_policy_from_lookup() {
+        let policy = ReservasiPolicy::from_lookup(|key| (key == "STOCK_RESERVATION_TTL_MINUTES").then(|| "15".to_string()));
+        assert_eq!(policy.ttl, Duration::minutes(15));
+
+        for invalid in ["0", "-5", "sebentar"] {
+            let policy = ReservasiPolicy::from_lookup(|_| Some(invalid.to_string()));
+            assert_eq!(policy, ReservasiPolicy::default());
+        }
+    }
+
+    #[test]
+    fn test_expires_at_is_sortable_text() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 15, 9, 59, 30).unwrap();
+        let policy = ReservasiPolicy::default();
+
+        assert_eq!(format_expires_at(policy.expires_at(now)), "2025-01-15T10:29:30Z");
+        assert!(format_expires_at(now) < format_expires_at(policy.expires_at(now)));
+    }
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use rocket::serde::{Serialize, Deserialize};
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::refund_reason::RefundReason;
+use crate::transaksi_penjualan::model::detail_transaksi::DetailTransaksi;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MetodePengembalian {
+    Refund,
+    KreditToko,
+}
+
+impl MetodePengembalian {
+    pub fn from_string(metode: &str) -> Option<Self> {
+        match metode.to_uppercase().as_str() {
+            "REFUND" => Some(MetodePengembalian::Refund),
+            "KREDIT_TOKO" => Some(MetodePengembalian::KreditToko),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MetodePengembalian {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetodePengembalian::Refund => write!(f, "REFUND"),
+            MetodePengembalian::KreditToko => write!(f, "KREDIT_TOKO"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct DetailRetur {
+    pub id: i32,
+    pub id_retur: i32,
+    pub id_detail_transaksi: i32,
+    pub id_produk: i32,
+    pub jumlah: u32,
+    // Barang rusak tidak dikembalikan ke stok
+    pub dapat_dijual_kembali: bool,
+    pub nilai: Rupiah,
+}
+
+impl DetailRetur {
+    // Nilai diambil dari total bruto baris asal (setelah diskon, termasuk PPN) secara kumulatif,
+    // jadi beberapa retur parsial untuk baris yang sama selalu berjumlah tepat total baris
+    pub fn hitung_nilai(asal: &DetailTransaksi, sudah_diretur: u32, jumlah: u32) -> Rupiah {
+        let terjual = asal.jumlah as i64;
+        let sampai = asal.total.apply_rate((sudah_diretur + jumlah) as i64, terjual);
+        let sebelum = asal.total.apply_rate(sudah_diretur as i64, terjual);
+        sampai - sebelum
+    }
+}
+
+// Dokumen retur terpisah dari transaksi asal; transaksi dan detailnya tidak ikut diubah
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ReturPenjualan {
+    pub id: i32,
+    pub id_transaksi: i32,
+    pub id_pelanggan: i32,
+    pub tanggal_retur: DateTime<Utc>,
+    pub alasan: RefundReason,
+    pub metode_pengembalian: MetodePengembalian,
+    pub total_pengembalian: Rupiah,
+    pub catatan: Option<String>,
+    pub detail: Vec<DetailRetur>,
+    // Refund yang dibuat untuk retur ini; kosong bila dikembalikan sebagai kredit toko
+    #[serde(default)]
+    pub id_refund: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SaldoKreditToko {
+    pub id_pelanggan: i32,
+    pub saldo: Rupiah,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_partial_returns_add_up_to_line_total() {
+        let mut asal = DetailTransaksi::new(1, 1, Rupiah::from_rupiah(100_000), 3);
+        asal.total = Rupiah::from_rupiah(100_000);
+
+        let pertama = DetailRetur::hitung_nilai(&asal, 0, 1);
+        let kedua = DetailRetur::hitung_nilai(&asal, 1, 1);
+        let ketiga = DetailRetur::hitung_nilai(&asal, 2, 1);
+
+        assert_eq!(pertama, Rupiah::from_sen(3_333_333));
+        assert_eq!(kedua, Rupiah::from_sen(3_333_334));
+        assert_eq!(pertama + kedua + ketiga, asal.total);
+    }
+
+    #[test]
+    fn test_metode_pengembalian_from_string() {
+        assert_eq!(MetodePengembalian::from_string("kredit_toko"), Some(MetodePengembalian::KreditToko));
+        assert_eq!(MetodePengembalian::from_string("REFUND"), Some(MetodePengembalian::Refund));
+        assert_eq!(MetodePengembalian::from_string("TUNAI"), None);
+        assert_eq!(MetodePengembalian::KreditToko.to_string(), "KREDIT_TOKO");
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use rocket::serde::{Serialize, Deserialize};
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+
+// Aktor untuk perubahan oleh job latar belakang dan permintaan tanpa sesi login
+pub const AKTOR_SISTEM: &str = "sistem";
+pub const AKTOR_ANONIM: &str = "anonim";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RiwayatStatusTransaksi {
+    pub id: i32,
+    pub id_transaksi: i32,
+    pub dari_status: StatusTransaksi,
+    pub ke_status: StatusTransaksi,
+    pub aktor: String,
+    pub alasan: Option<String>,
+    pub waktu: DateTime<Utc>,
+}
+
+// Siapa yang mengubah status dan alasannya, diteruskan dari controller ke service
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerubahanStatus {
+    pub aktor: String,
+    pub alasan: Option<String>,
+}
+
+impl PerubahanStatus {
+    pub fn oleh(user: Option<&AuthenticatedUser>, alasan: Option<String>) -> Self {
+        PerubahanStatus {
+            aktor: user.map(|u| u.username.clone()).unwrap_or_else(|| AKTOR_ANONIM.to_string()),
+            alasan: alasan.filter(|a| !a.trim().is_empty()),
+        }
+    }
+
+    pub fn sistem(alasan: &str) -> Self {
+        PerubahanStatus {
+            aktor: AKTOR_SISTEM.to_string(),
+            alasan: Some(alasan.to_string()),
+        }
+    }
+
+    pub fn catat(&self, id_transaksi: i32, dari_status: StatusTransaksi, ke_status: StatusTransaksi) -> RiwayatStatusTransaksi {
+        RiwayatStatusTransaksi {
+            id: 0,
+            id_transaksi,
+            dari_status,
+            ke_status,
+            aktor: self.aktor.clone(),
+            alasan: self.alasan.clone(),
+            waktu: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_perubahan_status_oleh() {
+        let kasir = AuthenticatedUser { user_id: 3, username: "kasir".to_string(), is_admin: false };
+
+        assert_eq!(PerubahanStatus::oleh(Some(&kasir), Some("Pelanggan batal".to_string())).aktor, "kasir");
+        let anonim = PerubahanStatus::oleh(None, Some("  ".to_string()));
+        assert_eq!(anonim.aktor, AKTOR_ANONIM);
+        assert_eq!(anonim.alasan, None);
+
+        let riwayat = PerubahanStatus::sistem("Reservasi kedaluwarsa").catat(7, StatusTransaksi::MasihDiproses, StatusTransaksi::Dibatalkan);
+        assert_eq!(riwayat.aktor, AKTOR_SISTEM);
+        assert_eq!(riwayat.id_transaksi, 7);
+        assert_eq!(riwayat.ke_status, StatusTransaksi::Dibatalkan);
+    }
+}
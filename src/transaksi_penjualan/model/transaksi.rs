@@ -1,6 +1,9 @@
 use chrono::{Utc, NaiveDateTime};
 use rocket::serde::{Serialize, Deserialize};
+use crate::common::money::Rupiah;
 use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+use crate::transaksi_penjualan::model::pajak::{KebijakanPajak, PajakBaris};
+use crate::transaksi_penjualan::patterns::state::transaksi_state::{StateAction, TransaksiState, TransaksiStateFactory};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
@@ -9,16 +12,28 @@ pub struct Transaksi {
     pub id_pelanggan: i32,
     pub nama_pelanggan: String,
     pub tanggal_transaksi: String,  
-    pub total_harga: f64,
+    // Total bruto (sudah termasuk PPN) = total_dpp + total_ppn
+    pub total_harga: Rupiah,
     pub status: StatusTransaksi,
     pub catatan: Option<String>,
+    #[serde(default)]
+    pub total_dpp: Rupiah,
+    #[serde(default)]
+    pub total_ppn: Rupiah,
+    #[serde(default)]
+    pub tarif_pajak_bp: i64,
+    #[serde(default)]
+    pub harga_termasuk_pajak: bool,
+    // Penawaran harga asal bila transaksi dibuat dari konversi penawaran
+    #[serde(default)]
+    pub id_penawaran: Option<i32>,
 }
 
 impl Transaksi {
     pub fn new(
         id_pelanggan: i32,
         nama_pelanggan: String,
-        total_harga: f64,
+        total_harga: Rupiah,
         catatan: Option<String>,
     ) -> Self {
         Transaksi {
@@ -29,86 +44,91 @@ impl Transaksi {
             total_harga,
             status: StatusTransaksi::MasihDiproses,
             catatan,
+            total_dpp: total_harga,
+            total_ppn: Rupiah::ZERO,
+            tarif_pajak_bp: 0,
+            harga_termasuk_pajak: true,
+            id_penawaran: None,
         }
     }
 
+    // Semua aturan status berasal dari TransaksiState
+    pub fn state(&self) -> Box<dyn TransaksiState> {
+        TransaksiStateFactory::create_state(&self.status)
+    }
+
     pub fn can_be_modified(&self) -> bool {
-        self.status == StatusTransaksi::MasihDiproses
+        self.state().can_be_modified()
     }
 
     pub fn can_be_cancelled(&self) -> bool {
-        self.status == StatusTransaksi::MasihDiproses
+        self.state().can_be_cancelled()
     }
 
     pub fn can_add_items(&self) -> bool {
-        self.status == StatusTransaksi::MasihDiproses
+        self.state().can_add_items()
     }
 
     pub fn can_update_items(&self) -> bool {
-        self.status == StatusTransaksi::MasihDiproses
+        self.state().can_update_items()
     }
 
     pub fn can_delete_items(&self) -> bool {
-        self.status == StatusTransaksi::MasihDiproses
+        self.state().can_delete_items()
     }
 
     pub fn can_be_completed(&self) -> bool {
-        self.status == StatusTransaksi::MasihDiproses
+        self.state().can_be_completed()
     }
 
-    pub fn complete(&mut self) -> Result<(), String> {
-        if !self.can_be_completed() {
-            return Err("Transaksi tidak dapat diselesaikan".to_string());
-        }
-        self.status = StatusTransaksi::Selesai;
+    pub fn can_be_reopened(&self) -> bool {
+        self.state().can_be_reopened()
+    }
+
+    pub fn apply(&mut self, action: StateAction) -> Result<(), String> {
+        self.status = self.state().next_state(action)?.status();
         Ok(())
     }
 
+    pub fn complete(&mut self) -> Result<(), String> {
+        self.apply(StateAction::Complete)
+    }
+
     pub fn cancel(&mut self) -> Result<(), String> {
-        if !self.can_be_cancelled() {
-            return Err("Transaksi tidak dapat dibatalkan".to_string());
-        }
-        self.status = StatusTransaksi::Dibatalkan;
-        Ok(())
+        self.apply(StateAction::Cancel)
     }
 
     pub fn reopen(&mut self) -> Result<(), String> {
-        if self.status == StatusTransaksi::MasihDiproses {
-            return Err("Transaksi sudah dalam status diproses".to_string());
-        }
-        self.status = StatusTransaksi::MasihDiproses;
-        Ok(())
+        self.apply(StateAction::Reopen)
     }
 
     pub fn update_status(&mut self, status: StatusTransaksi) {
         self.status = status;
     }
 
-    pub fn update_total_harga(&mut self, total_harga: f64) {
+    pub fn update_total_harga(&mut self, total_harga: Rupiah) {
         self.total_harga = total_harga;
     }
 
-    pub fn get_allowed_actions(&self) -> Vec<String> {
-        match self.status {
-            StatusTransaksi::MasihDiproses => vec![
-                "complete".to_string(),
-                "cancel".to_string(),
-                "add_item".to_string(),
-                "update_item".to_string(),
-                "delete_item".to_string(),
-            ],
-            StatusTransaksi::Selesai => vec![
-                "print_receipt".to_string(),
-                "view_details".to_string(),
-                "reopen".to_string(), 
-            ],
-            StatusTransaksi::Dibatalkan => vec![
-                "view_details".to_string(),
-                "reopen".to_string(), 
-            ],
+    pub fn terapkan_pajak(&mut self, kebijakan: KebijakanPajak, pajak: &[PajakBaris]) {
+        self.tarif_pajak_bp = kebijakan.tarif_bp;
+        self.harga_termasuk_pajak = kebijakan.harga_termasuk_pajak;
+        self.total_dpp = pajak.iter().map(|p| p.dpp).sum();
+        self.total_ppn = pajak.iter().map(|p| p.ppn).sum();
+        self.total_harga = self.total_dpp + self.total_ppn;
+    }
+
+    pub fn kebijakan_pajak(&self) -> KebijakanPajak {
+        KebijakanPajak {
+            tarif_bp: self.tarif_pajak_bp,
+            harga_termasuk_pajak: self.harga_termasuk_pajak,
         }
     }
 
+    pub fn get_allowed_actions(&self) -> Vec<String> {
+        self.state().get_allowed_actions()
+    }
+
     pub fn get_tanggal_as_datetime(&self) -> Result<NaiveDateTime, chrono::ParseError> {
         NaiveDateTime::parse_from_str(&self.tanggal_transaksi, "%Y-%m-%d %H:%M:%S")
     }
@@ -123,13 +143,13 @@ mod test {
         let transaksi = Transaksi::new(
             1,
             "Castorice".to_string(),
-            150000.0,
+            Rupiah::from_rupiah(150000),
             Some("Pembelian produk elektronik".to_string()),
         );
 
         assert_eq!(transaksi.id_pelanggan, 1);
         assert_eq!(transaksi.nama_pelanggan, "Castorice");
-        assert_eq!(transaksi.total_harga, 150000.0);
+        assert_eq!(transaksi.total_harga, Rupiah::from_rupiah(150000));
         assert_eq!(transaksi.status, StatusTransaksi::MasihDiproses);
         assert!(transaksi.can_be_modified());
     }
@@ -139,7 +159,7 @@ mod test {
         let mut transaksi = Transaksi::new(
             1,
             "Tribbie".to_string(),
-            200000.0,
+            Rupiah::from_rupiah(200000),
             None,
         );
 
@@ -153,21 +173,23 @@ mod test {
         let mut transaksi = Transaksi::new(
             2,
             "Hyacine".to_string(),
-            100000.0,
+            Rupiah::from_rupiah(100000),
             None,
         );
 
-        assert!(transaksi.complete().is_ok());
-        assert_eq!(transaksi.status, StatusTransaksi::Selesai);
+        assert!(transaksi.cancel().is_ok());
+        assert_eq!(transaksi.status, StatusTransaksi::Dibatalkan);
         assert!(!transaksi.can_be_modified());
 
         assert!(transaksi.reopen().is_ok());
         assert_eq!(transaksi.status, StatusTransaksi::MasihDiproses);
         assert!(transaksi.can_be_modified());
 
-        assert!(transaksi.cancel().is_ok());
-        assert_eq!(transaksi.status, StatusTransaksi::Dibatalkan);
+        assert!(transaksi.complete().is_ok());
+        assert_eq!(transaksi.status, StatusTransaksi::Selesai);
         assert!(!transaksi.can_be_modified());
+        assert!(transaksi.reopen().is_err());
+        assert!(transaksi.cancel().is_err());
     }
 
     #[test]
@@ -175,7 +197,7 @@ mod test {
         let mut transaksi = Transaksi::new(
             1,
             "Test".to_string(),
-            100000.0,
+            Rupiah::from_rupiah(100000),
             None,
         );
 
@@ -200,12 +222,30 @@ mod test {
         let mut transaksi = Transaksi::new(
             2,
             "Hyacine".to_string(),
-            100000.0,
+            Rupiah::from_rupiah(100000),
             None,
         );
 
-        transaksi.update_total_harga(175000.0);
-        assert_eq!(transaksi.total_harga, 175000.0);
+        transaksi.update_total_harga(Rupiah::from_rupiah(175000));
+        assert_eq!(transaksi.total_harga, Rupiah::from_rupiah(175000));
+    }
+
+    #[test]
+    fn test_terapkan_pajak() {
+        use crate::transaksi_penjualan::model::pajak::KategoriPajak;
+
+        let mut transaksi = Transaksi::new(1, "Cyrene".to_string(), Rupiah::ZERO, None);
+        let kebijakan = KebijakanPajak { tarif_bp: 1100, harga_termasuk_pajak: false };
+        let pajak = vec![
+            kebijakan.hitung(Rupiah::from_rupiah(200000), KategoriPajak::KenaPajak),
+            kebijakan.hitung(Rupiah::from_rupiah(50000), KategoriPajak::BebasPajak),
+        ];
+
+        transaksi.terapkan_pajak(kebijakan, &pajak);
+        assert_eq!(transaksi.total_dpp, Rupiah::from_rupiah(250000));
+        assert_eq!(transaksi.total_ppn, Rupiah::from_rupiah(22000));
+        assert_eq!(transaksi.total_harga, Rupiah::from_rupiah(272000));
+        assert_eq!(transaksi.kebijakan_pajak(), kebijakan);
     }
 
     #[test]
@@ -213,7 +253,7 @@ mod test {
         let transaksi = Transaksi::new(
             1,
             "Test DateTime".to_string(),
-            100000.0,
+            Rupiah::from_rupiah(100000),
             None,
         );
 
@@ -9,12 +9,13 @@ pub trait TransaksiState: Send + Sync {
     fn can_add_items(&self) -> bool;
     fn can_update_items(&self) -> bool;
     fn can_delete_items(&self) -> bool;
+    fn can_be_reopened(&self) -> bool;
     fn next_state(&self, action: StateAction) -> Result<Box<dyn TransaksiState>, String>;
     fn status(&self) -> StatusTransaksi;
     fn get_allowed_actions(&self) -> Vec<String>;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum StateAction {
     Complete,
     Cancel,
@@ -32,6 +33,7 @@ impl TransaksiState for MasihDiprosesState {
     fn can_add_items(&self) -> bool { true }
     fn can_update_items(&self) -> bool { true }
     fn can_delete_items(&self) -> bool { true }
+    fn can_be_reopened(&self) -> bool { false }
     
     fn next_state(&self, action: StateAction) -> Result<Box<dyn TransaksiState>, String> {
         match action {
@@ -44,7 +46,13 @@ impl TransaksiState for MasihDiprosesState {
     fn status(&self) -> StatusTransaksi { StatusTransaksi::MasihDiproses }
     
     fn get_allowed_actions(&self) -> Vec<String> {
-        vec!["complete".to_string(), "cancel".to_string(), "add_item".to_string(), "update_item".to_string()]
+        vec![
+            "complete".to_string(),
+            "cancel".to_string(),
+            "add_item".to_string(),
+            "update_item".to_string(),
+            "delete_item".to_string(),
+        ]
     }
 }
 
@@ -59,18 +67,17 @@ impl TransaksiState for SelesaiState {
     fn can_add_items(&self) -> bool { false }
     fn can_update_items(&self) -> bool { false }
     fn can_delete_items(&self) -> bool { false }
+    // Penjualan selesai tidak dibuka kembali; koreksi dilakukan lewat retur penjualan
+    fn can_be_reopened(&self) -> bool { false }
     
-    fn next_state(&self, action: StateAction) -> Result<Box<dyn TransaksiState>, String> {
-        match action {
-            StateAction::Reopen => Ok(Box::new(MasihDiprosesState)),
-            _ => Err("Transaksi selesai tidak dapat diubah statusnya".to_string()),
-        }
+    fn next_state(&self, _action: StateAction) -> Result<Box<dyn TransaksiState>, String> {
+        Err("Transaksi selesai tidak dapat diubah statusnya".to_string())
     }
     
     fn status(&self) -> StatusTransaksi { StatusTransaksi::Selesai }
     
     fn get_allowed_actions(&self) -> Vec<String> {
-        vec!["print_receipt".to_string(), "view_details".to_string()]
+        vec!["print_receipt".to_string(), "view_details".to_string(), "retur".to_string()]
     }
 }
 
@@ -85,6 +92,7 @@ impl TransaksiState for DibatalkanState {
     fn can_add_items(&self) -> bool { false }
     fn can_update_items(&self) -> bool { false }
     fn can_delete_items(&self) -> bool { false }
+    fn can_be_reopened(&self) -> bool { true }
     
     fn next_state(&self, action: StateAction) -> Result<Box<dyn TransaksiState>, String> {
         match action {
@@ -96,7 +104,7 @@ impl TransaksiState for DibatalkanState {
     fn status(&self) -> StatusTransaksi { StatusTransaksi::Dibatalkan }
     
     fn get_allowed_actions(&self) -> Vec<String> {
-        vec!["view_details".to_string()]
+        vec!["view_details".to_string(), "reopen".to_string()]
     }
 }
 
@@ -111,4 +119,37 @@ impl TransaksiStateFactory {
             StatusTransaksi::Dibatalkan => Box::new(DibatalkanState),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_allowed_actions_match_transitions() {
+        let aksi = [("complete", StateAction::Complete), ("cancel", StateAction::Cancel), ("reopen", StateAction::Reopen)];
+
+        for status in [StatusTransaksi::MasihDiproses, StatusTransaksi::Selesai, StatusTransaksi::Dibatalkan] {
+            let state = TransaksiStateFactory::create_state(&status);
+            let allowed = state.get_allowed_actions();
+
+            for (nama, action) in aksi {
+                assert_eq!(
+                    allowed.contains(&nama.to_string()),
+                    state.next_state(action).is_ok(),
+                    "{} dari {}", nama, status.to_string()
+                );
+            }
+            assert_eq!(allowed.contains(&"reopen".to_string()), state.can_be_reopened());
+            assert_eq!(allowed.contains(&"delete_item".to_string()), state.can_delete_items());
+        }
+    }
+
+    #[test]
+    fn test_next_state() {
+        let diproses = MasihDiprosesState;
+        assert_eq!(diproses.next_state(StateAction::Complete).unwrap().status(), StatusTransaksi::Selesai);
+        assert_eq!(diproses.next_state(StateAction::Cancel).unwrap().status(), StatusTransaksi::Dibatalkan);
+        assert_eq!(DibatalkanState.next_state(StateAction::Reopen).unwrap().status(), StatusTransaksi::MasihDiproses);
+        assert!(SelesaiState.next_state(StateAction::Reopen).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use rocket::http::ContentType;
+use crate::transaksi_penjualan::model::receipt::Receipt;
+use crate::transaksi_penjualan::patterns::strategy::receipt_renderer::{LineStyle, ReceiptRenderer, TextReceiptRenderer, THERMAL_58MM_COLUMNS};
+
+const ESC: u8 = 0x1B;
+const GS: u8 = 0x1D;
+const LF: u8 = 0x0A;
+
+// Jumlah baris kosong sebelum potong agar teks terakhir melewati pisau printer
+const FEED_BEFORE_CUT: u8 = 4;
+
+// Penyusun byte stream ESC/POS. Hanya memakai perintah dasar yang didukung
+// hampir semua printer thermal (Epson TM, Xprinter, dsb.) dengan Font A,
+// sehingga kolom tetap rata dengan tata letak `TextReceiptRenderer`.
+#[derive(Debug, Default)]
+pub struct EscPosEncoder {
+    bytes: Vec<u8>,
+}
+
+impl EscPosEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // ESC @ lalu ESC t 0 (code page PC437)
+    pub fn initialize(&mut self) -> &mut Self {
+        self.bytes.extend_from_slice(&[ESC, b'@', ESC, b't', 0]);
+        self
+    }
+
+    // ESC E n
+    pub fn bold(&mut self, on: bool) -> &mut Self {
+        self.bytes.extend_from_slice(&[ESC, b'E', on as u8]);
+        self
+    }
+
+    // GS ! n, hanya tinggi ganda supaya lebar kolom tidak berubah
+    pub fn double_height(&mut self, on: bool) -> &mut Self {
+        self.bytes.extend_from_slice(&[GS, b'!', if on { 0x01 } else { 0x00 }]);
+        self
+    }
+
+    // Code page PC437 hanya aman untuk ASCII; karakter lain diganti '?'
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        self.bytes.extend(text.chars().map(|c| match c {
+            ' '..='~' => c as u8,
+            _ => b'?',
+        }));
+        self
+    }
+
+    pub fn line(&mut self, text: &str) -> &mut Self {
+        self.text(text);
+        self.bytes.push(LF);
+        self
+    }
+
+    // ESC d n
+    pub fn feed(&mut self, lines: u8) -> &mut Self {
+        self.bytes.extend_from_slice(&[ESC, b'd', lines]);
+        self
+    }
+
+    // GS V 1 (partial cut)
+    pub fn cut(&mut self) -> &mut Self {
+        self.bytes.extend_from_slice(&[GS, b'V', 1]);
+        self
+    }
+
+    // ESC p 0 t1 t2: pulsa 50 ms ke pin 2 laci kas, jeda 500 ms (satuan 2 ms)
+    pub fn kick_drawer(&mut self) -> &mut Self {
+        self.bytes.extend_from_slice(&[ESC, b'p', 0, 25, 250]);
+        self
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+pub struct EscPosReceiptRenderer {
+    pub width: usize,
+    pub kick_drawer: bool,
+}
+
+impl EscPosReceiptRenderer {
+    pub fn new(width: usize, kick_drawer: bool) -> Self {
+        EscPosReceiptRenderer { width, kick_drawer }
+    }
+}
+
+impl ReceiptRenderer for EscPosReceiptRenderer {
+    fn render(&self, receipt: &Receipt) -> Vec<u8> {
+        let mut encoder = EscPosEncoder::new();
+        encoder.initialize();
+
+        for (style, line) in TextReceiptRenderer::new(self.width).styled_lines(receipt) {
+            match style {
+                LineStyle::Normal => {
+                    encoder.line(&line);
+                }
+                LineStyle::Bold => {
+                    encoder.bold(true).line(&line).bold(false);
+                }
+                LineStyle::Heading => {
+                    encoder.bold(true).double_height(true).line(&line).double_height(false).bold(false);
+                }
+            }
+        }
+
+        encoder.feed(FEED_BEFORE_CUT).cut();
+        if self.kick_drawer {
+            encoder.kick_drawer();
+        }
+        encoder.into_bytes()
+    }
+
+    fn content_type(&self) -> ContentType {
+        ContentType::Binary
+    }
+
+    fn get_name(&self) -> &'static str {
+        if self.width <= THERMAL_58MM_COLUMNS { "escpos_58mm" } else { "escpos_80mm" }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::money::Rupiah;
+    use crate::manajemen_pembayaran::model::payment::PaymentMethod;
+    use crate::transaksi_penjualan::model::receipt::{ReceiptDocument, ReceiptLine, ReceiptPayment, StoreProfile, TaxBreakdown};
+    use crate::transaksi_penjualan::patterns::strategy::receipt_renderer::THERMAL_80MM_COLUMNS;
+
+    fn receipt() -> Receipt {
+        let total = Rupiah::from_rupiah(130_000);
+        Receipt {
+            document: ReceiptDocument::Struk,
+            nomor: ReceiptDocument::Struk.number(7),
+            store: StoreProfile {
+                nama: "Toko Jaya".to_string(),
+                alamat: None,
+                telepon: None,
+                npwp: None,
+            },
+            id_transaksi: 7,
+            tanggal_transaksi: "2025-01-15 10:30:00".to_string(),
+            berlaku_sampai: None,
+            nama_pelanggan: "Budi".to_string(),
+            catatan: None,
+            lines: vec![ReceiptLine {
+                nama_produk: "Semen Tiga Roda 50kg".to_string(),
+                jumlah: 2,
+                harga_satuan: Rupiah::from_rupiah(65_000),
+                diskon: Rupiah::ZERO,
+                subtotal: Rupiah::from_rupiah(130_000),
+            }],
+            total,
+            tax: TaxBreakdown::inclusive(total, 1100),
+            payments: vec![ReceiptPayment { method: PaymentMethod::Cash, amount: total, reference: None }],
+            paid: total,
+            refunded: Rupiah::ZERO,
+            outstanding: Rupiah::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_encoder_commands() {
+        let mut encoder = EscPosEncoder::new();
+        encoder.initialize()
+            .bold(true).double_height(true).line("Toko").double_height(false).bold(false)
+            .line("Rp 1.000 ©")
+            .feed(4)
+            .cut()
+            .kick_drawer();
+
+        assert_eq!(encoder.into_bytes(), [
+            0x1B, 0x40, 0x1B, 0x74, 0x00,
+            0x1B, 0x45, 0x01, 0x1D, 0x21, 0x01, b'T', b'o', b'k', b'o', 0x0A, 0x1D, 0x21, 0x00, 0x1B, 0x45, 0x00,
+            b'R', b'p', b' ', b'1', b'.', b'0', b'0', b'0', b' ', b'?', 0x0A,
+            0x1B, 0x64, 0x04,
+            0x1D, 0x56, 0x01,
+            0x1B, 0x70, 0x00, 0x19, 0xFA,
+        ]);
+    }
+
+    #[test]
+    fn test_58mm_receipt_matches_golden_fixture() {
+        let bytes = EscPosReceiptRenderer::new(THERMAL_58MM_COLUMNS, true).render(&receipt());
+        assert_eq!(bytes, include_bytes!("fixtures/escpos_58mm.bin").to_vec());
+    }
+
+    #[test]
+    fn test_80mm_receipt_without_drawer_kick_matches_golden_fixture() {
+        let renderer = EscPosReceiptRenderer::new(THERMAL_80MM_COLUMNS, false);
+        let bytes = renderer.render(&receipt());
+
+        assert_eq!(bytes, include_bytes!("fixtures/escpos_80mm.bin").to_vec());
+        assert!(bytes.ends_with(&[0x1B, 0x64, 0x04, 0x1D, 0x56, 0x01]));
+        assert_eq!(renderer.content_type(), ContentType::Binary);
+        assert_eq!(renderer.get_name(), "escpos_80mm");
+    }
+}
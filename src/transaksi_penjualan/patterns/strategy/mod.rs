@@ -1 +1,3 @@
-pub mod sorting_strategy;
\ No newline at end of file
+pub mod sorting_strategy;
+pub mod receipt_renderer;
+pub mod escpos_renderer;
@@ -0,0 +1,579 @@
+use rocket::http::ContentType;
+use crate::common::money::Rupiah;
+use crate::transaksi_penjualan::model::receipt::{Receipt, ReceiptDocument};
+
+pub const THERMAL_58MM_COLUMNS: usize = 32;
+pub const THERMAL_80MM_COLUMNS: usize = 48;
+const PDF_COLUMNS: usize = 80;
+const PDF_LINES_PER_PAGE: usize = 68;
+
+pub trait ReceiptRenderer: Send + Sync {
+    fn render(&self, receipt: &Receipt) -> Vec<u8>;
+    fn content_type(&self) -> ContentType;
+    fn get_name(&self) -> &'static str;
+}
+
+// Format rupiah Indonesia: titik sebagai pemisah ribuan, koma untuk sen (hanya jika ada)
+pub fn format_rupiah(amount: Rupiah) -> String {
+    let sen = amount.sen().unsigned_abs();
+    let digits = (sen / 100).to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push('.');
+        }
+        grouped.push(digit);
+    }
+    let cents = sen % 100;
+    if cents > 0 {
+        grouped.push_str(&format!(",{:02}", cents));
+    }
+    if amount.is_negative() {
+        grouped.insert(0, '-');
+    }
+    grouped
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineStyle {
+    Normal,
+    Bold,
+    Heading,
+}
+
+// Tata letak teks monospace untuk printer thermal; juga dipakai sebagai isi PDF
+pub struct TextReceiptRenderer {
+    pub width: usize,
+}
+
+impl TextReceiptRenderer {
+    pub fn new(width: usize) -> Self {
+        TextReceiptRenderer { width }
+    }
+
+    pub fn lines(&self, receipt: &Receipt) -> Vec<String> {
+        self.styled_lines(receipt).into_iter().map(|(_, line)| line).collect()
+    }
+
+    // Baris yang sama dengan `lines`, ditambah gaya cetak untuk printer yang mendukungnya (ESC/POS)
+    pub fn styled_lines(&self, receipt: &Receipt) -> Vec<(LineStyle, String)> {
+        let mut lines = Vec::new();
+        let store = &receipt.store;
+
+        let header = [store.alamat.clone(), store.telepon.as_ref().map(|t| format!("Telp {}", t)), store.npwp.as_ref().map(|n| format!("NPWP {}", n))];
+        lines.extend(wrap(&store.nama, self.width).iter().map(|l| (LineStyle::Heading, self.center(l))));
+        for text in header.into_iter().flatten() {
+            lines.extend(wrap(&text, self.width).iter().map(|l| (LineStyle::Normal, self.center(l))));
+        }
+        lines.push((LineStyle::Normal, "=".repeat(self.width)));
+        lines.push((LineStyle::Bold, self.center(receipt.document.title())));
+        lines.push((LineStyle::Normal, self.row("No.", &receipt.nomor)));
+        lines.push((LineStyle::Normal, self.row("Tanggal", &receipt.tanggal_transaksi)));
+        lines.push((LineStyle::Normal, self.row("Pelanggan", &receipt.nama_pelanggan)));
+        if let Some(berlaku_sampai) = &receipt.berlaku_sampai {
+            lines.push((LineStyle::Normal, self.row("Berlaku s/d", berlaku_sampai)));
+        }
+        lines.push((LineStyle::Normal, "-".repeat(self.width)));
+
+        for line in &receipt.lines {
+            lines.extend(wrap(&line.nama_produk, self.width).into_iter().map(|l| (LineStyle::Normal, l)));
+            lines.push((LineStyle::Normal, self.row(
+                &format!("  {} x {}", line.jumlah, format_rupiah(line.harga_satuan)),
+                &format_rupiah(line.subtotal),
+            )));
+            if line.diskon.is_positive() {
+                lines.push((LineStyle::Normal, self.row("  Diskon", &format_rupiah(-line.diskon))));
+            }
+        }
+        lines.push((LineStyle::Normal, "-".repeat(self.width)));
+
+        lines.push((LineStyle::Normal, self.row(&format!("Jumlah Item: {}", receipt.item_count()), "")));
+        lines.push((LineStyle::Normal, self.row("DPP", &format_rupiah(receipt.tax.dpp))));
+        lines.push((LineStyle::Normal, self.row(&format!("PPN {}", receipt.tax.rate_label()), &format_rupiah(receipt.tax.ppn))));
+        lines.push((LineStyle::Bold, self.row("TOTAL", &format_rupiah(receipt.total))));
+        lines.push((LineStyle::Normal, "-".repeat(self.width)));
+
+        // Penawaran belum dibayar, jadi tidak ada bagian pembayaran
+        if receipt.document != ReceiptDocument::Penawaran {
+            for payment in &receipt.payments {
+                lines.push((LineStyle::Normal, self.row(payment.label(), &format_rupiah(payment.amount))));
+                if let Some(reference) = &payment.reference {
+                    lines.extend(wrap(&format!("  Ref: {}", reference), self.width).into_iter().map(|l| (LineStyle::Normal, l)));
+                }
+            }
+            lines.push((LineStyle::Normal, self.row("Dibayar", &format_rupiah(receipt.paid))));
+            if receipt.refunded.is_positive() {
+                lines.push((LineStyle::Normal, self.row("Refund", &format_rupiah(receipt.refunded))));
+            }
+            if receipt.outstanding.is_positive() {
+                lines.push((LineStyle::Normal, self.row("Sisa Tagihan", &format_rupiah(receipt.outstanding))));
+            }
+            if receipt.document == ReceiptDocument::Faktur {
+                lines.push((LineStyle::Normal, self.row("Status", if receipt.is_paid_in_full() { "LUNAS" } else { "BELUM LUNAS" })));
+            }
+        }
+
+        if let Some(catatan) = &receipt.catatan {
+            lines.extend(wrap(&format!("Catatan: {}", catatan), self.width).into_iter().map(|l| (LineStyle::Normal, l)));
+        }
+        lines.push((LineStyle::Normal, "=".repeat(self.width)));
+        lines.push((LineStyle::Normal, self.center("Terima kasih")));
+
+        lines
+    }
+
+    fn center(&self, text: &str) -> String {
+        let text = truncate(text, self.width);
+        let padding = (self.width - text.chars().count()) / 2;
+        format!("{}{}", " ".repeat(padding), text)
+    }
+
+    // Label rata kiri, nilai rata kanan; label dipotong bila tidak muat
+    fn row(&self, label: &str, value: &str) -> String {
+        let value = truncate(value, self.width);
+        let value_width = value.chars().count();
+        if value_width == 0 {
+            return truncate(label, self.width);
+        }
+        let label = truncate(label, self.width.saturating_sub(value_width + 1));
+        let gap = self.width - label.chars().count() - value_width;
+        format!("{}{}{}", label, " ".repeat(gap), value)
+    }
+}
+
+impl ReceiptRenderer for TextReceiptRenderer {
+    fn render(&self, receipt: &Receipt) -> Vec<u8> {
+        let mut text = self.lines(receipt).join("\n");
+        text.push('\n');
+        text.into_bytes()
+    }
+
+    fn content_type(&self) -> ContentType {
+        ContentType::Plain
+    }
+
+    fn get_name(&self) -> &'static str {
+        if self.width <= THERMAL_58MM_COLUMNS { "thermal_58mm" } else { "thermal_80mm" }
+    }
+}
+
+pub struct HtmlReceiptRenderer;
+
+impl ReceiptRenderer for HtmlReceiptRenderer {
+    fn render(&self, receipt: &Receipt) -> Vec<u8> {
+        let store = &receipt.store;
+        let mut html = String::new();
+
+        html.push_str("<!DOCTYPE html>\n<html lang=\"id\">\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{} {}</title>\n", escape_html(receipt.document.title()), escape_html(&receipt.nomor)));
+        html.push_str("<style>body{font-family:sans-serif;max-width:720px;margin:auto}table{width:100%;border-collapse:collapse}\
+                       td,th{padding:4px;border-bottom:1px solid #ddd}.num{text-align:right}</style>\n</head>\n<body>\n");
+
+        html.push_str(&format!("<header>\n<h1>{}</h1>\n", escape_html(&store.nama)));
+        for text in [store.alamat.clone(), store.telepon.as_ref().map(|t| format!("Telp {}", t)), store.npwp.as_ref().map(|n| format!("NPWP {}", n))]
+            .into_iter()
+            .flatten()
+        {
+            html.push_str(&format!("<p>{}</p>\n", escape_html(&text)));
+        }
+        html.push_str("</header>\n");
+
+        html.push_str(&format!("<h2>{}</h2>\n<dl>\n", escape_html(receipt.document.title())));
+        for (label, value) in [("No.", &receipt.nomor), ("Tanggal", &receipt.tanggal_transaksi), ("Pelanggan", &receipt.nama_pelanggan)] {
+            html.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", label, escape_html(value)));
+        }
+        if let Some(berlaku_sampai) = &receipt.berlaku_sampai {
+            html.push_str(&format!("<dt>Berlaku s/d</dt><dd>{}</dd>\n", escape_html(berlaku_sampai)));
+        }
+        html.push_str("</dl>\n");
+
+        html.push_str("<table class=\"items\">\n<thead><tr><th>Produk</th><th class=\"num\">Jumlah</th><th class=\"num\">Harga</th><th class=\"num\">Subtotal</th></tr></thead>\n<tbody>\n");
+        for line in &receipt.lines {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+                escape_html(&line.nama_produk), line.jumlah, format_rupiah(line.harga_satuan), format_rupiah(line.subtotal)
+            ));
+            if line.diskon.is_positive() {
+                html.push_str(&format!(
+                    "<tr class=\"diskon\"><td colspan=\"3\">Diskon</td><td class=\"num\">{}</td></tr>\n",
+                    format_rupiah(-line.diskon)
+                ));
+            }
+        }
+        html.push_str("</tbody>\n</table>\n");
+
+        html.push_str("<table class=\"totals\">\n");
+        let mut total_row = |label: &str, amount: Rupiah| {
+            html.push_str(&format!("<tr><td>{}</td><td class=\"num\">{}</td></tr>\n", escape_html(label), format_rupiah(amount)));
+        };
+        total_row("DPP", receipt.tax.dpp);
+        total_row(&format!("PPN {}", receipt.tax.rate_label()), receipt.tax.ppn);
+        total_row("Total", receipt.total);
+        if receipt.document != ReceiptDocument::Penawaran {
+            for payment in &receipt.payments {
+                match &payment.reference {
+                    Some(reference) => total_row(&format!("{} ({})", payment.label(), reference), payment.amount),
+                    None => total_row(payment.label(), payment.amount),
+                }
+            }
+            total_row("Dibayar", receipt.paid);
+            if receipt.refunded.is_positive() {
+                total_row("Refund", receipt.refunded);
+            }
+            if receipt.outstanding.is_positive() {
+                total_row("Sisa Tagihan", receipt.outstanding);
+            }
+        }
+        html.push_str("</table>\n");
+
+        if receipt.document == ReceiptDocument::Faktur {
+            html.push_str(&format!("<p class=\"status\">Status: {}</p>\n", if receipt.is_paid_in_full() { "LUNAS" } else { "BELUM LUNAS" }));
+        }
+        if let Some(catatan) = &receipt.catatan {
+            html.push_str(&format!("<p class=\"catatan\">Catatan: {}</p>\n", escape_html(catatan)));
+        }
+        html.push_str("<footer><p>Terima kasih</p></footer>\n</body>\n</html>\n");
+
+        html.into_bytes()
+    }
+
+    fn content_type(&self) -> ContentType {
+        ContentType::HTML
+    }
+
+    fn get_name(&self) -> &'static str { "html" }
+}
+
+// PDF A4 sederhana dengan font Courier bawaan, berisi tata letak teks 80 kolom
+pub struct PdfReceiptRenderer;
+
+impl ReceiptRenderer for PdfReceiptRenderer {
+    fn render(&self, receipt: &Receipt) -> Vec<u8> {
+        pdf_document(&TextReceiptRenderer::new(PDF_COLUMNS).lines(receipt))
+    }
+
+    fn content_type(&self) -> ContentType {
+        ContentType::PDF
+    }
+
+    fn get_name(&self) -> &'static str { "pdf" }
+}
+
+pub struct ReceiptRendererFactory;
+
+impl ReceiptRendererFactory {
+    pub fn create(format: &str) -> Option<Box<dyn ReceiptRenderer>> {
+        match format.to_lowercase().as_str() {
+            "58mm" | "thermal_58mm" | "text58" => Some(Box::new(TextReceiptRenderer::new(THERMAL_58MM_COLUMNS))),
+            "80mm" | "thermal_80mm" | "text80" | "text" => Some(Box::new(TextReceiptRenderer::new(THERMAL_80MM_COLUMNS))),
+            "html" => Some(Box::new(HtmlReceiptRenderer)),
+            "pdf" => Some(Box::new(PdfReceiptRenderer)),
+            _ => None,
+        }
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+// Bungkus per kata; kata yang lebih panjang dari satu baris dipotong per `width` karakter
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > width {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+        let word: String = word.into_iter().collect();
+        if word.is_empty() {
+            continue;
+        }
+
+        let needed = if current.is_empty() { word.chars().count() } else { current.chars().count() + 1 + word.chars().count() };
+        if needed > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// Font standar PDF hanya aman untuk ASCII, karakter lain diganti '?'
+fn escape_pdf_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+fn pdf_document(lines: &[String]) -> Vec<u8> {
+    let pages: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(PDF_LINES_PER_PAGE).collect()
+    };
+
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + i * 2).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_string(),
+    ];
+
+    for (page, page_id) in pages.iter().zip(&page_ids) {
+        let mut content = String::from("BT\n/F1 9 Tf\n11 TL\n50 800 Td\n");
+        for line in page.iter() {
+            content.push_str(&format!("({}) Tj\nT*\n", escape_pdf_text(line)));
+        }
+        content.push_str("ET");
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            page_id + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref_offset));
+
+    pdf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manajemen_pembayaran::model::payment::PaymentMethod;
+    use crate::transaksi_penjualan::model::receipt::{ReceiptLine, ReceiptPayment, StoreProfile, TaxBreakdown};
+
+    fn receipt(document: ReceiptDocument) -> Receipt {
+        let total = Rupiah::from_rupiah(195_000);
+        Receipt {
+            nomor: document.number(7),
+            document,
+            store: StoreProfile {
+                nama: "Toko Bangunan Jaya".to_string(),
+                alamat: Some("Jl. Margonda Raya No. 1, Depok".to_string()),
+                telepon: Some("021-555-0101".to_string()),
+                npwp: None,
+            },
+            id_transaksi: 7,
+            tanggal_transaksi: "2025-01-15 10:30:00".to_string(),
+            berlaku_sampai: None,
+            nama_pelanggan: "Budi".to_string(),
+            catatan: None,
+            lines: vec![
+                ReceiptLine {
+                    nama_produk: "Semen Tiga Roda 50kg".to_string(),
+                    jumlah: 2,
+                    harga_satuan: Rupiah::from_rupiah(65_000),
+                    diskon: Rupiah::ZERO,
+                    subtotal: Rupiah::from_rupiah(130_000),
+                },
+                ReceiptLine {
+                    nama_produk: "Cat Tembok Dulux Weathershield Putih 2.5L".to_string(),
+                    jumlah: 1,
+                    harga_satuan: Rupiah::from_rupiah(65_000),
+                    diskon: Rupiah::ZERO,
+                    subtotal: Rupiah::from_rupiah(65_000),
+                },
+            ],
+            total,
+            tax: TaxBreakdown::inclusive(total, 1100),
+            payments: vec![ReceiptPayment { method: PaymentMethod::Cash, amount: Rupiah::from_rupiah(150_000), reference: None }],
+            paid: Rupiah::from_rupiah(150_000),
+            refunded: Rupiah::ZERO,
+            outstanding: Rupiah::from_rupiah(45_000),
+        }
+    }
+
+    #[test]
+    fn test_format_rupiah() {
+        assert_eq!(format_rupiah(Rupiah::ZERO), "0");
+        assert_eq!(format_rupiah(Rupiah::from_rupiah(999)), "999");
+        assert_eq!(format_rupiah(Rupiah::from_rupiah(1_000)), "1.000");
+        assert_eq!(format_rupiah(Rupiah::from_rupiah(1_234_567)), "1.234.567");
+        assert_eq!(format_rupiah(Rupiah::from_sen(17_567_568)), "175.675,68");
+        assert_eq!(format_rupiah(Rupiah::from_rupiah(-30_000)), "-30.000");
+    }
+
+    #[test]
+    fn test_thermal_58mm_layout() {
+        let text = String::from_utf8(TextReceiptRenderer::new(THERMAL_58MM_COLUMNS).render(&receipt(ReceiptDocument::Struk))).unwrap();
+        let expected = "       Toko Bangunan Jaya
+ Jl. Margonda Raya No. 1, Depok
+       Telp 021-555-0101
+================================
+        STRUK PENJUALAN
+No.                   STR-000007
+Tanggal      2025-01-15 10:30:00
+Pelanggan                   Budi
+--------------------------------
+Semen Tiga Roda 50kg
+  2 x 65.000             130.000
+Cat Tembok Dulux Weathershield
+Putih 2.5L
+  1 x 65.000              65.000
+--------------------------------
+Jumlah Item: 3
+DPP                   175.675,68
+PPN 11%                19.324,32
+TOTAL                    195.000
+--------------------------------
+Tunai                    150.000
+Dibayar                  150.000
+Sisa Tagihan              45.000
+================================
+          Terima kasih
+";
+        assert_eq!(text, expected);
+        assert!(text.lines().all(|l| l.chars().count() <= THERMAL_58MM_COLUMNS));
+    }
+
+    #[test]
+    fn test_thermal_80mm_fits_width_and_shows_invoice_status() {
+        let renderer = TextReceiptRenderer::new(THERMAL_80MM_COLUMNS);
+        let mut invoice = receipt(ReceiptDocument::Faktur);
+        invoice.nama_pelanggan = "PT Konstruksi Nusantara Sejahtera Abadi Makmur Sentosa".to_string();
+        let lines = renderer.lines(&invoice);
+
+        assert!(lines.iter().all(|l| l.chars().count() <= THERMAL_80MM_COLUMNS));
+        assert!(lines.contains(&"Cat Tembok Dulux Weathershield Putih 2.5L".to_string()));
+        assert!(lines.iter().any(|l| l.starts_with("No.") && l.ends_with("INV-000007")));
+        assert!(lines.iter().any(|l| l.starts_with("Status") && l.ends_with("BELUM LUNAS")));
+        assert_eq!(renderer.get_name(), "thermal_80mm");
+    }
+
+    #[test]
+    fn test_line_discount_is_shown() {
+        let mut receipt = receipt(ReceiptDocument::Struk);
+        receipt.lines[0].diskon = Rupiah::from_rupiah(13_000);
+        receipt.lines[0].subtotal = Rupiah::from_rupiah(117_000);
+
+        let lines = TextReceiptRenderer::new(THERMAL_58MM_COLUMNS).lines(&receipt);
+        let diskon = lines.iter().position(|l| l.starts_with("  Diskon")).unwrap();
+        assert!(lines[diskon].ends_with("-13.000"));
+        assert!(lines[diskon - 1].ends_with("117.000"));
+
+        let html = String::from_utf8(HtmlReceiptRenderer.render(&receipt)).unwrap();
+        assert!(html.contains("<td colspan=\"3\">Diskon</td><td class=\"num\">-13.000</td>"));
+    }
+
+    #[test]
+    fn test_html_escapes_content() {
+        let mut receipt = receipt(ReceiptDocument::Faktur);
+        receipt.nama_pelanggan = "<script>alert('x')</script>".to_string();
+        receipt.catatan = Some("Kirim & pasang".to_string());
+        let html = String::from_utf8(HtmlReceiptRenderer.render(&receipt)).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Toko Bangunan Jaya</h1>"));
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("Kirim &amp; pasang"));
+        assert!(html.contains("<td class=\"num\">175.675,68</td>"));
+        assert!(html.contains("Status: BELUM LUNAS"));
+    }
+
+    #[test]
+    fn test_quotation_shows_validity_without_payments() {
+        let mut receipt = receipt(ReceiptDocument::Penawaran);
+        receipt.berlaku_sampai = Some("2025-01-29 10:30:00".to_string());
+        let lines = TextReceiptRenderer::new(THERMAL_80MM_COLUMNS).lines(&receipt);
+
+        assert!(lines.iter().any(|l| l.contains("PENAWARAN HARGA")));
+        assert!(lines.iter().any(|l| l.starts_with("No.") && l.ends_with("QUO-000007")));
+        assert!(lines.iter().any(|l| l.starts_with("Berlaku s/d") && l.ends_with("2025-01-29 10:30:00")));
+        assert!(lines.iter().any(|l| l.starts_with("TOTAL")));
+        assert!(!lines.iter().any(|l| l.starts_with("Tunai") || l.starts_with("Dibayar") || l.starts_with("Sisa Tagihan")));
+
+        let html = String::from_utf8(HtmlReceiptRenderer.render(&receipt)).unwrap();
+        assert!(html.contains("<dt>Berlaku s/d</dt><dd>2025-01-29 10:30:00</dd>"));
+        assert!(!html.contains("Dibayar"));
+    }
+
+    #[test]
+    fn test_pdf_structure() {
+        let mut receipt = receipt(ReceiptDocument::Faktur);
+        receipt.catatan = Some("Antar (lantai 2) \\ gudang".to_string());
+        let pdf = PdfReceiptRenderer.render(&receipt);
+        let text = String::from_utf8(pdf.clone()).unwrap();
+
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/BaseFont /Courier"));
+        assert!(text.contains("(Catatan: Antar \\(lantai 2\\) \\\\ gudang) Tj"));
+
+        // Setiap offset di tabel xref harus menunjuk tepat ke awal objeknya
+        let xref_start: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        let xref = &text[xref_start..];
+        assert!(xref.starts_with("xref\n0 6\n"));
+        for (i, entry) in xref.lines().skip(3).take(5).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[test]
+    fn test_pdf_paginates_long_documents() {
+        let mut receipt = receipt(ReceiptDocument::Faktur);
+        receipt.lines = receipt.lines.iter().cycle().take(60).cloned().collect();
+        let text = String::from_utf8(PdfReceiptRenderer.render(&receipt)).unwrap();
+
+        assert!(text.contains("/Count 3"));
+        assert_eq!(text.matches("/Type /Page ").count(), 3);
+    }
+
+    #[test]
+    fn test_renderer_factory() {
+        assert_eq!(ReceiptRendererFactory::create("58mm").unwrap().get_name(), "thermal_58mm");
+        assert_eq!(ReceiptRendererFactory::create("TEXT").unwrap().get_name(), "thermal_80mm");
+        assert_eq!(ReceiptRendererFactory::create("pdf").unwrap().content_type(), ContentType::PDF);
+        assert_eq!(ReceiptRendererFactory::create("html").unwrap().content_type(), ContentType::HTML);
+        assert!(ReceiptRendererFactory::create("docx").is_none());
+    }
+}
@@ -0,0 +1,203 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::any::AnyRow;
+use sqlx::{AnyConnection, Row};
+
+use crate::transaksi_penjualan::model::keranjang_tertahan::{BarisKeranjang, KeranjangTertahan};
+use crate::transaksi_penjualan::repository::get_optional;
+
+pub struct KeranjangTertahanRepository;
+
+const KOLOM_KERANJANG: &str = "id, id_kasir, nama, id_pelanggan, nama_pelanggan, catatan, created_at, updated_at";
+
+const KOLOM_BARIS: &str = "id, id_keranjang, id_produk, nama_produk, jumlah";
+
+// Format tetap (UTC, detik, akhiran Z) supaya updated_at bisa dibandingkan sebagai teks
+fn format_waktu(waktu: DateTime<Utc>) -> String {
+    waktu.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+impl KeranjangTertahanRepository {
+    pub async fn create_keranjang(db: &mut AnyConnection, keranjang: &KeranjangTertahan) -> Result<KeranjangTertahan, sqlx::Error> {
+        let row = sqlx::query(&format!("
+                INSERT INTO keranjang_tertahan (id_kasir, nama, id_pelanggan, nama_pelanggan, catatan, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING {}
+            ", KOLOM_KERANJANG))
+            .bind(keranjang.id_kasir)
+            .bind(&keranjang.nama)
+            .bind(keranjang.id_pelanggan)
+            .bind(keranjang.nama_pelanggan.as_deref().unwrap_or(""))
+            .bind(keranjang.catatan.as_deref().unwrap_or(""))
+            .bind(format_waktu(keranjang.created_at))
+            .bind(format_waktu(keranjang.updated_at))
+            .fetch_one(&mut *db)
+            .await?;
+        let mut created = Self::parse_row_to_keranjang(&row)?;
+
+        created.detail = Self::simpan_baris(db, created.id, &keranjang.detail).await?;
+        Ok(created)
+    }
+
+    // Mengganti header dan seluruh baris keranjang milik kasir; RowNotFound bila bukan miliknya
+    pub async fn update_keranjang(db: &mut AnyConnection, keranjang: &KeranjangTertahan) -> Result<KeranjangTertahan, sqlx::Error> {
+        let row = sqlx::query(&format!("
+                UPDATE keranjang_tertahan
+                SET nama = $1, id_pelanggan = $2, nama_pelanggan = $3, catatan = $4, updated_at = $5
+                WHERE id = $6 AND id_kasir = $7
+                RETURNING {}
+            ", KOLOM_KERANJANG))
+            .bind(&keranjang.nama)
+            .bind(keranjang.id_pelanggan)
+            .bind(keranjang.nama_pelanggan.as_deref().unwrap_or(""))
+            .bind(keranjang.catatan.as_deref().unwrap_or(""))
+            .bind(format_waktu(keranjang.updated_at))
+            .bind(keranjang.id)
+            .bind(keranjang.id_kasir)
+            .fetch_one(&mut *db)
+            .await?;
+        let mut updated = Self::parse_row_to_keranjang(&row)?;
+
+        sqlx::query("DELETE FROM detail_keranjang_tertahan WHERE id_keranjang = $1")
+            .bind(updated.id)
+            .execute(&mut *db)
+            .await?;
+        updated.detail = Self::simpan_baris(db, updated.id, &keranjang.detail).await?;
+        Ok(updated)
+    }
+
+    pub async fn get_keranjang_by_id(db: &mut AnyConnection, id: i32, id_kasir: i64) -> Result<KeranjangTertahan, sqlx::Error> {
+        let row = sqlx::query(&format!("SELECT {} FROM keranjang_tertahan WHERE id = $1 AND id_kasir = $2", KOLOM_KERANJANG))
+            .bind(id)
+            .bind(id_kasir)
+            .fetch_one(&mut *db)
+            .await?;
+
+        let mut keranjang = Self::parse_row_to_keranjang(&row)?;
+        Self::load_baris(db, std::slice::from_mut(&mut keranjang)).await?;
+        Ok(keranjang)
+    }
+
+    pub async fn get_keranjang_by_kasir(db: &mut AnyConnection, id_kasir: i64) -> Result<Vec<KeranjangTertahan>, sqlx::Error> {
+        let rows = sqlx::query(&format!("SELECT {} FROM keranjang_tertahan WHERE id_kasir = $1 ORDER BY updated_at, id", KOLOM_KERANJANG))
+            .bind(id_kasir)
+            .fetch_all(&mut *db)
+            .await?;
+
+        let mut keranjang = rows.iter().map(Self::parse_row_to_keranjang).collect::<Result<Vec<_>, _>>()?;
+        Self::load_baris(db, &mut keranjang).await?;
+        Ok(keranjang)
+    }
+
+    pub async fn nama_dipakai(db: &mut AnyConnection, id_kasir: i64, nama: &str, kecuali_id: i32) -> Result<bool, sqlx::Error> {
+        let row = sqlx::query("SELECT COUNT(*) AS jumlah FROM keranjang_tertahan WHERE id_kasir = $1 AND nama = $2 AND id <> $3")
+            .bind(id_kasir)
+            .bind(nama)
+            .bind(kecuali_id)
+            .fetch_one(&mut *db)
+            .await?;
+
+        Ok(row.get::<i64, _>("jumlah") > 0)
+    }
+
+    pub async fn delete_keranjang(db: &mut AnyConnection, id: i32, id_kasir: i64) -> Result<bool, sqlx::Error> {
+        sqlx::query("DELETE FROM detail_keranjang_tertahan WHERE id_keranjang IN (SELECT id FROM keranjang_tertahan WHERE id = $1 AND id_kasir = $2)")
+            .bind(id)
+            .bind(id_kasir)
+            .execute(&mut *db)
+            .await?;
+        let result = sqlx::query("DELETE FROM keranjang_tertahan WHERE id = $1 AND id_kasir = $2")
+            .bind(id)
+            .bind(id_kasir)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    // Menghapus keranjang yang terakhir diubah sebelum `batas`; mengembalikan ID yang dihapus
+    pub async fn delete_kedaluwarsa(db: &mut AnyConnection, batas: DateTime<Utc>) -> Result<Vec<i32>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id FROM keranjang_tertahan WHERE updated_at < $1 ORDER BY id")
+            .bind(format_waktu(batas))
+            .fetch_all(&mut *db)
+            .await?;
+        let ids: Vec<i32> = rows.iter().map(|row| row.get("id")).collect();
+
+        for id in &ids {
+            sqlx::query("DELETE FROM detail_keranjang_tertahan WHERE id_keranjang = $1")
+                .bind(id)
+                .execute(&mut *db)
+                .await?;
+            sqlx::query("DELETE FROM keranjang_tertahan WHERE id = $1")
+                .bind(id)
+                .execute(&mut *db)
+                .await?;
+        }
+
+        Ok(ids)
+    }
+
+    async fn simpan_baris(db: &mut AnyConnection, id_keranjang: i32, detail: &[BarisKeranjang]) -> Result<Vec<BarisKeranjang>, sqlx::Error> {
+        let mut created = Vec::with_capacity(detail.len());
+        for baris in detail {
+            let row = sqlx::query(&format!("
+                    INSERT INTO detail_keranjang_tertahan (id_keranjang, id_produk, nama_produk, jumlah)
+                    VALUES ($1, $2, $3, $4)
+                    RETURNING {}
+                ", KOLOM_BARIS))
+                .bind(id_keranjang)
+                .bind(baris.id_produk)
+                .bind(&baris.nama_produk)
+                .bind(baris.jumlah as i32)
+                .fetch_one(&mut *db)
+                .await?;
+            created.push(Self::parse_row_to_baris(&row));
+        }
+
+        Ok(created)
+    }
+
+    async fn load_baris(db: &mut AnyConnection, keranjang: &mut [KeranjangTertahan]) -> Result<(), sqlx::Error> {
+        for item in keranjang.iter_mut() {
+            let rows = sqlx::query(&format!("SELECT {} FROM detail_keranjang_tertahan WHERE id_keranjang = $1 ORDER BY id", KOLOM_BARIS))
+                .bind(item.id)
+                .fetch_all(&mut *db)
+                .await?;
+            item.detail = rows.iter().map(Self::parse_row_to_baris).collect();
+        }
+
+        Ok(())
+    }
+
+    fn parse_row_to_keranjang(row: &AnyRow) -> Result<KeranjangTertahan, sqlx::Error> {
+        let waktu = |column: &str| -> Result<DateTime<Utc>, sqlx::Error> {
+            let value: String = row.get(column);
+            DateTime::parse_from_rfc3339(&value)
+                .map(|waktu| waktu.with_timezone(&Utc))
+                .map_err(|e| sqlx::Error::ColumnDecode { index: column.to_string(), source: Box::new(e) })
+        };
+        let nama_pelanggan: String = row.get("nama_pelanggan");
+        let catatan: String = row.get("catatan");
+
+        Ok(KeranjangTertahan {
+            id: row.get("id"),
+            id_kasir: row.get("id_kasir"),
+            nama: row.get("nama"),
+            id_pelanggan: get_optional(row, "id_pelanggan")?,
+            nama_pelanggan: Some(nama_pelanggan).filter(|n| !n.is_empty()),
+            catatan: Some(catatan).filter(|c| !c.is_empty()),
+            created_at: waktu("created_at")?,
+            updated_at: waktu("updated_at")?,
+            detail: Vec::new(),
+        })
+    }
+
+    fn parse_row_to_baris(row: &AnyRow) -> BarisKeranjang {
+        BarisKeranjang {
+            id: row.get("id"),
+            id_keranjang: row.get("id_keranjang"),
+            id_produk: row.get("id_produk"),
+            nama_produk: row.get("nama_produk"),
+            jumlah: row.get::<i32, _>("jumlah") as u32,
+        }
+    }
+}
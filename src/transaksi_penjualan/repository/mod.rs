@@ -1 +1,22 @@
-pub mod transaksi;
\ No newline at end of file
+use sqlx::any::AnyRow;
+use sqlx::{Any, Decode, Row, Type, TypeInfo, ValueRef};
+
+pub mod transaksi;
+pub mod reservasi_stok;
+pub mod promosi;
+pub mod retur_penjualan;
+pub mod riwayat_status;
+pub mod penawaran;
+pub mod keranjang_tertahan;
+pub mod pengiriman;
+
+// Driver `Any` tidak bisa men-decode NULL ke `Option<T>`, jadi cek jenis tipe nilai mentahnya
+pub(crate) fn get_optional<'r, T>(row: &'r AnyRow, column: &str) -> Result<Option<T>, sqlx::Error>
+where
+    T: Decode<'r, Any> + Type<Any>,
+{
+    if row.try_get_raw(column)?.type_info().name() == "NULL" {
+        return Ok(None);
+    }
+    row.try_get(column).map(Some)
+}
@@ -0,0 +1,174 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::any::AnyRow;
+use sqlx::{AnyConnection, Row};
+
+use crate::common::money::Rupiah;
+use crate::transaksi_penjualan::model::pajak::KategoriPajak;
+use crate::transaksi_penjualan::model::penawaran::{DetailPenawaran, Penawaran, StatusPenawaran};
+
+pub struct PenawaranRepository;
+
+const KOLOM_PENAWARAN: &str = "id, id_pelanggan, nama_pelanggan, tanggal_penawaran, berlaku_sampai, status,
+    CAST(total_harga AS TEXT) AS total_harga, CAST(total_diskon AS TEXT) AS total_diskon,
+    CAST(total_dpp AS TEXT) AS total_dpp, CAST(total_ppn AS TEXT) AS total_ppn, tarif_pajak_bp, harga_termasuk_pajak, catatan";
+
+const KOLOM_DETAIL_PENAWARAN: &str = "id, id_penawaran, id_produk, nama_produk, CAST(harga_satuan AS TEXT) AS harga_satuan, jumlah,
+    CAST(diskon AS TEXT) AS diskon, diskon_diterapkan, CAST(subtotal AS TEXT) AS subtotal, kategori_pajak, tarif_pajak_bp,
+    CAST(dpp AS TEXT) AS dpp, CAST(ppn AS TEXT) AS ppn, CAST(total AS TEXT) AS total";
+
+impl PenawaranRepository {
+    pub async fn create_penawaran(db: &mut AnyConnection, penawaran: &Penawaran) -> Result<Penawaran, sqlx::Error> {
+        let row = sqlx::query(&format!("
+                INSERT INTO penawaran_harga (id_pelanggan, nama_pelanggan, tanggal_penawaran, berlaku_sampai, status,
+                    total_harga, total_diskon, total_dpp, total_ppn, tarif_pajak_bp, harga_termasuk_pajak, catatan, updated_at)
+                VALUES ($1, $2, $3, $4, $5, CAST($6 AS DECIMAL(15,2)), CAST($7 AS DECIMAL(15,2)), CAST($8 AS DECIMAL(15,2)),
+                    CAST($9 AS DECIMAL(15,2)), $10, $11, $12, $13)
+                RETURNING {}
+            ", KOLOM_PENAWARAN))
+            .bind(penawaran.id_pelanggan)
+            .bind(&penawaran.nama_pelanggan)
+            .bind(penawaran.tanggal_penawaran.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .bind(penawaran.berlaku_sampai.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .bind(penawaran.status.to_string())
+            .bind(penawaran.total_harga.to_string())
+            .bind(penawaran.total_diskon.to_string())
+            .bind(penawaran.total_dpp.to_string())
+            .bind(penawaran.total_ppn.to_string())
+            .bind(penawaran.tarif_pajak_bp)
+            .bind(penawaran.harga_termasuk_pajak as i32)
+            .bind(penawaran.catatan.as_deref().unwrap_or(""))
+            .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+            .fetch_one(&mut *db)
+            .await?;
+        let mut created = Self::parse_row_to_penawaran(&row)?;
+
+        for detail in &penawaran.detail {
+            let diskon_diterapkan = serde_json::to_string(&detail.diskon_diterapkan)
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+            let row = sqlx::query(&format!("
+                    INSERT INTO detail_penawaran_harga (id_penawaran, id_produk, nama_produk, harga_satuan, jumlah, diskon,
+                        diskon_diterapkan, subtotal, kategori_pajak, tarif_pajak_bp, dpp, ppn, total)
+                    VALUES ($1, $2, $3, CAST($4 AS DECIMAL(15,2)), $5, CAST($6 AS DECIMAL(15,2)), $7, CAST($8 AS DECIMAL(15,2)),
+                        $9, $10, CAST($11 AS DECIMAL(15,2)), CAST($12 AS DECIMAL(15,2)), CAST($13 AS DECIMAL(15,2)))
+                    RETURNING {}
+                ", KOLOM_DETAIL_PENAWARAN))
+                .bind(created.id)
+                .bind(detail.id_produk)
+                .bind(&detail.nama_produk)
+                .bind(detail.harga_satuan.to_string())
+                .bind(detail.jumlah as i32)
+                .bind(detail.diskon.to_string())
+                .bind(diskon_diterapkan)
+                .bind(detail.subtotal.to_string())
+                .bind(detail.kategori_pajak.to_string())
+                .bind(detail.tarif_pajak_bp)
+                .bind(detail.dpp.to_string())
+                .bind(detail.ppn.to_string())
+                .bind(detail.total.to_string())
+                .fetch_one(&mut *db)
+                .await?;
+            created.detail.push(Self::parse_row_to_detail(&row)?);
+        }
+
+        Ok(created)
+    }
+
+    pub async fn get_penawaran_by_id(db: &mut AnyConnection, id: i32) -> Result<Penawaran, sqlx::Error> {
+        let row = sqlx::query(&format!("SELECT {} FROM penawaran_harga WHERE id = $1", KOLOM_PENAWARAN))
+            .bind(id)
+            .fetch_one(&mut *db)
+            .await?;
+        let mut penawaran = Self::parse_row_to_penawaran(&row)?;
+
+        let rows = sqlx::query(&format!("SELECT {} FROM detail_penawaran_harga WHERE id_penawaran = $1 ORDER BY id", KOLOM_DETAIL_PENAWARAN))
+            .bind(id)
+            .fetch_all(&mut *db)
+            .await?;
+        penawaran.detail = rows.iter().map(Self::parse_row_to_detail).collect::<Result<_, _>>()?;
+
+        penawaran.id_transaksi = sqlx::query("SELECT id FROM transaksi WHERE id_penawaran = $1")
+            .bind(id)
+            .fetch_optional(&mut *db)
+            .await?
+            .map(|row| row.get("id"));
+
+        Ok(penawaran)
+    }
+
+    // UPDATE bersyarat: gagal (false) bila status sudah diubah permintaan lain, sehingga satu
+    // penawaran tidak bisa dikonversi dua kali
+    pub async fn ubah_status(
+        db: &mut AnyConnection,
+        id: i32,
+        dari: StatusPenawaran,
+        ke: StatusPenawaran,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE penawaran_harga SET status = $1, updated_at = $2 WHERE id = $3 AND status = $4")
+            .bind(ke.to_string())
+            .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+            .bind(id)
+            .bind(dari.to_string())
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    fn parse_row_to_penawaran(row: &AnyRow) -> Result<Penawaran, sqlx::Error> {
+        let waktu = |column: &str| -> Result<DateTime<Utc>, sqlx::Error> {
+            let value: String = row.get(column);
+            DateTime::parse_from_rfc3339(&value)
+                .map(|waktu| waktu.with_timezone(&Utc))
+                .map_err(|e| sqlx::Error::ColumnDecode { index: column.to_string(), source: Box::new(e) })
+        };
+        let status: String = row.get("status");
+        let catatan: String = row.get("catatan");
+
+        Ok(Penawaran {
+            id: row.get("id"),
+            id_pelanggan: row.get("id_pelanggan"),
+            nama_pelanggan: row.get("nama_pelanggan"),
+            tanggal_penawaran: waktu("tanggal_penawaran")?,
+            berlaku_sampai: waktu("berlaku_sampai")?,
+            status: StatusPenawaran::from_string(&status).ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: "status".to_string(),
+                source: format!("status penawaran tidak dikenal: {}", status).into(),
+            })?,
+            total_harga: Rupiah::from_row(row, "total_harga")?,
+            total_diskon: Rupiah::from_row(row, "total_diskon")?,
+            total_dpp: Rupiah::from_row(row, "total_dpp")?,
+            total_ppn: Rupiah::from_row(row, "total_ppn")?,
+            tarif_pajak_bp: row.get("tarif_pajak_bp"),
+            harga_termasuk_pajak: row.get::<i32, _>("harga_termasuk_pajak") != 0,
+            catatan: Some(catatan).filter(|c| !c.is_empty()),
+            detail: Vec::new(),
+            id_transaksi: None,
+        })
+    }
+
+    fn parse_row_to_detail(row: &AnyRow) -> Result<DetailPenawaran, sqlx::Error> {
+        let kategori: String = row.get("kategori_pajak");
+        let diskon_diterapkan: String = row.get("diskon_diterapkan");
+
+        Ok(DetailPenawaran {
+            id: row.get("id"),
+            id_penawaran: row.get("id_penawaran"),
+            id_produk: row.get("id_produk"),
+            nama_produk: row.get("nama_produk"),
+            harga_satuan: Rupiah::from_row(row, "harga_satuan")?,
+            jumlah: row.get::<i32, _>("jumlah") as u32,
+            diskon: Rupiah::from_row(row, "diskon")?,
+            diskon_diterapkan: serde_json::from_str(&diskon_diterapkan)
+                .map_err(|e| sqlx::Error::ColumnDecode { index: "diskon_diterapkan".to_string(), source: Box::new(e) })?,
+            subtotal: Rupiah::from_row(row, "subtotal")?,
+            kategori_pajak: KategoriPajak::from_string(&kategori).ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: "kategori_pajak".to_string(),
+                source: format!("kategori pajak tidak dikenal: {}", kategori).into(),
+            })?,
+            tarif_pajak_bp: row.get("tarif_pajak_bp"),
+            dpp: Rupiah::from_row(row, "dpp")?,
+            ppn: Rupiah::from_row(row, "ppn")?,
+            total: Rupiah::from_row(row, "total")?,
+        })
+    }
+}
@@ -0,0 +1,226 @@
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use sqlx::any::AnyRow;
+use sqlx::{AnyConnection, Row};
+use std::collections::HashMap;
+
+use crate::transaksi_penjualan::model::pengiriman::{DetailPengiriman, Pengiriman, StatusPengiriman};
+
+pub struct PengirimanRepository;
+
+const KOLOM_PENGIRIMAN: &str = "p.id, p.id_transaksi, t.nama_pelanggan, p.alamat, p.tanggal_kirim, p.kendaraan, p.pengemudi,
+    p.status, p.catatan, p.bukti_pengiriman, p.penerima, p.created_at, p.updated_at";
+
+const KOLOM_DETAIL_PENGIRIMAN: &str = "id, id_pengiriman, id_detail_transaksi, id_produk, nama_produk, jumlah";
+
+const FORMAT_TANGGAL: &str = "%Y-%m-%d";
+
+fn format_waktu(waktu: DateTime<Utc>) -> String {
+    waktu.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+impl PengirimanRepository {
+    pub async fn create_pengiriman(db: &mut AnyConnection, pengiriman: &Pengiriman) -> Result<Pengiriman, sqlx::Error> {
+        let row = sqlx::query("
+                INSERT INTO pengiriman (id_transaksi, alamat, tanggal_kirim, kendaraan, pengemudi, status, catatan,
+                    bukti_pengiriman, penerima, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                RETURNING id
+            ")
+            .bind(pengiriman.id_transaksi)
+            .bind(&pengiriman.alamat)
+            .bind(pengiriman.tanggal_kirim.format(FORMAT_TANGGAL).to_string())
+            .bind(pengiriman.kendaraan.as_deref().unwrap_or(""))
+            .bind(pengiriman.pengemudi.as_deref().unwrap_or(""))
+            .bind(pengiriman.status.to_string())
+            .bind(pengiriman.catatan.as_deref().unwrap_or(""))
+            .bind(pengiriman.bukti_pengiriman.as_deref().unwrap_or(""))
+            .bind(pengiriman.penerima.as_deref().unwrap_or(""))
+            .bind(format_waktu(pengiriman.created_at))
+            .bind(format_waktu(pengiriman.updated_at))
+            .fetch_one(&mut *db)
+            .await?;
+        let id: i32 = row.get("id");
+
+        for detail in &pengiriman.detail {
+            sqlx::query("
+                    INSERT INTO detail_pengiriman (id_pengiriman, id_detail_transaksi, id_produk, nama_produk, jumlah)
+                    VALUES ($1, $2, $3, $4, $5)
+                ")
+                .bind(id)
+                .bind(detail.id_detail_transaksi)
+                .bind(detail.id_produk)
+                .bind(&detail.nama_produk)
+                .bind(detail.jumlah as i32)
+                .execute(&mut *db)
+                .await?;
+        }
+
+        Self::get_pengiriman_by_id(db, id).await
+    }
+
+    pub async fn get_pengiriman_by_id(db: &mut AnyConnection, id: i32) -> Result<Pengiriman, sqlx::Error> {
+        let row = sqlx::query(&format!("
+                SELECT {} FROM pengiriman p JOIN transaksi t ON t.id = p.id_transaksi
+                WHERE p.id = $1
+            ", KOLOM_PENGIRIMAN))
+            .bind(id)
+            .fetch_one(&mut *db)
+            .await?;
+
+        let mut pengiriman = Self::parse_row_to_pengiriman(&row)?;
+        Self::load_detail(db, std::slice::from_mut(&mut pengiriman)).await?;
+        Ok(pengiriman)
+    }
+
+    pub async fn get_pengiriman_by_transaksi(db: &mut AnyConnection, id_transaksi: i32) -> Result<Vec<Pengiriman>, sqlx::Error> {
+        let rows = sqlx::query(&format!("
+                SELECT {} FROM pengiriman p JOIN transaksi t ON t.id = p.id_transaksi
+                WHERE p.id_transaksi = $1
+                ORDER BY p.tanggal_kirim, p.id
+            ", KOLOM_PENGIRIMAN))
+            .bind(id_transaksi)
+            .fetch_all(&mut *db)
+            .await?;
+
+        let mut pengiriman = rows.iter().map(Self::parse_row_to_pengiriman).collect::<Result<Vec<_>, _>>()?;
+        Self::load_detail(db, &mut pengiriman).await?;
+        Ok(pengiriman)
+    }
+
+    // Daftar muat per tanggal, dikelompokkan per kendaraan
+    pub async fn get_pengiriman_by_tanggal(db: &mut AnyConnection, tanggal: NaiveDate) -> Result<Vec<Pengiriman>, sqlx::Error> {
+        let rows = sqlx::query(&format!("
+                SELECT {} FROM pengiriman p JOIN transaksi t ON t.id = p.id_transaksi
+                WHERE p.tanggal_kirim = $1
+                ORDER BY p.kendaraan, p.id
+            ", KOLOM_PENGIRIMAN))
+            .bind(tanggal.format(FORMAT_TANGGAL).to_string())
+            .fetch_all(&mut *db)
+            .await?;
+
+        let mut pengiriman = rows.iter().map(Self::parse_row_to_pengiriman).collect::<Result<Vec<_>, _>>()?;
+        Self::load_detail(db, &mut pengiriman).await?;
+        Ok(pengiriman)
+    }
+
+    // Jumlah per id detail transaksi yang sudah masuk pengiriman; pengiriman gagal tidak dihitung
+    // sehingga barangnya bisa dijadwalkan ulang
+    pub async fn jumlah_dijadwalkan(db: &mut AnyConnection, id_transaksi: i32) -> Result<HashMap<i32, u32>, sqlx::Error> {
+        let rows = sqlx::query("
+                SELECT d.id_detail_transaksi, d.jumlah
+                FROM detail_pengiriman d
+                JOIN pengiriman p ON p.id = d.id_pengiriman
+                WHERE p.id_transaksi = $1 AND p.status <> $2
+            ")
+            .bind(id_transaksi)
+            .bind(StatusPengiriman::Gagal.to_string())
+            .fetch_all(&mut *db)
+            .await?;
+
+        let mut jumlah: HashMap<i32, u32> = HashMap::new();
+        for row in rows {
+            *jumlah.entry(row.get("id_detail_transaksi")).or_default() += row.get::<i32, _>("jumlah") as u32;
+        }
+        Ok(jumlah)
+    }
+
+    // Hanya berhasil bila status di database masih `dari`, sehingga dua perubahan bersamaan tidak saling menimpa
+    pub async fn ubah_status_jika(
+        db: &mut AnyConnection,
+        pengiriman: &Pengiriman,
+        dari: StatusPengiriman,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("
+                UPDATE pengiriman
+                SET status = $1, bukti_pengiriman = $2, penerima = $3, updated_at = $4
+                WHERE id = $5 AND status = $6
+            ")
+            .bind(pengiriman.status.to_string())
+            .bind(pengiriman.bukti_pengiriman.as_deref().unwrap_or(""))
+            .bind(pengiriman.penerima.as_deref().unwrap_or(""))
+            .bind(format_waktu(pengiriman.updated_at))
+            .bind(pengiriman.id)
+            .bind(dari.to_string())
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    // Jadwal hanya bisa diubah selama pengiriman belum berangkat
+    pub async fn update_jadwal(db: &mut AnyConnection, pengiriman: &Pengiriman) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("
+                UPDATE pengiriman
+                SET alamat = $1, tanggal_kirim = $2, kendaraan = $3, pengemudi = $4, updated_at = $5
+                WHERE id = $6 AND status = $7
+            ")
+            .bind(&pengiriman.alamat)
+            .bind(pengiriman.tanggal_kirim.format(FORMAT_TANGGAL).to_string())
+            .bind(pengiriman.kendaraan.as_deref().unwrap_or(""))
+            .bind(pengiriman.pengemudi.as_deref().unwrap_or(""))
+            .bind(format_waktu(pengiriman.updated_at))
+            .bind(pengiriman.id)
+            .bind(StatusPengiriman::Dijadwalkan.to_string())
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn load_detail(db: &mut AnyConnection, pengiriman: &mut [Pengiriman]) -> Result<(), sqlx::Error> {
+        for item in pengiriman.iter_mut() {
+            let rows = sqlx::query(&format!("SELECT {} FROM detail_pengiriman WHERE id_pengiriman = $1 ORDER BY id", KOLOM_DETAIL_PENGIRIMAN))
+                .bind(item.id)
+                .fetch_all(&mut *db)
+                .await?;
+            item.detail = rows.iter().map(Self::parse_row_to_detail).collect();
+        }
+
+        Ok(())
+    }
+
+    fn parse_row_to_pengiriman(row: &AnyRow) -> Result<Pengiriman, sqlx::Error> {
+        let parse_waktu = |column: &str| -> Result<DateTime<Utc>, sqlx::Error> {
+            let waktu: String = row.get(column);
+            DateTime::parse_from_rfc3339(&waktu)
+                .map(|w| w.with_timezone(&Utc))
+                .map_err(|e| sqlx::Error::ColumnDecode { index: column.to_string(), source: Box::new(e) })
+        };
+        let opsional = |column: &str| Some(row.get::<String, _>(column)).filter(|v| !v.is_empty());
+
+        let status: String = row.get("status");
+        let tanggal: String = row.get("tanggal_kirim");
+
+        Ok(Pengiriman {
+            id: row.get("id"),
+            id_transaksi: row.get("id_transaksi"),
+            nama_pelanggan: row.get("nama_pelanggan"),
+            alamat: row.get("alamat"),
+            tanggal_kirim: NaiveDate::parse_from_str(&tanggal, FORMAT_TANGGAL)
+                .map_err(|e| sqlx::Error::ColumnDecode { index: "tanggal_kirim".to_string(), source: Box::new(e) })?,
+            kendaraan: opsional("kendaraan"),
+            pengemudi: opsional("pengemudi"),
+            status: StatusPengiriman::from_string(&status).ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: "status".to_string(),
+                source: format!("nilai tidak dikenal: {}", status).into(),
+            })?,
+            catatan: opsional("catatan"),
+            bukti_pengiriman: opsional("bukti_pengiriman"),
+            penerima: opsional("penerima"),
+            created_at: parse_waktu("created_at")?,
+            updated_at: parse_waktu("updated_at")?,
+            detail: Vec::new(),
+        })
+    }
+
+    fn parse_row_to_detail(row: &AnyRow) -> DetailPengiriman {
+        DetailPengiriman {
+            id: row.get("id"),
+            id_pengiriman: row.get("id_pengiriman"),
+            id_detail_transaksi: row.get("id_detail_transaksi"),
+            id_produk: row.get("id_produk"),
+            nama_produk: row.get("nama_produk"),
+            jumlah: row.get::<i32, _>("jumlah") as u32,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::any::AnyRow;
+use sqlx::{AnyConnection, Row};
+
+use crate::transaksi_penjualan::model::promosi::{CakupanPromosi, Promosi};
+use crate::transaksi_penjualan::repository::get_optional;
+
+pub struct PromosiRepository;
+
+const KOLOM_PROMOSI: &str = "id, nama, aturan, cakupan, id_produk, kategori, id_pelanggan, berlaku_mulai, berlaku_sampai, prioritas, dapat_digabung, aktif";
+
+impl PromosiRepository {
+    pub async fn create_promosi(db: &mut AnyConnection, promosi: &Promosi) -> Result<Promosi, sqlx::Error> {
+        let aturan = serde_json::to_string(&promosi.aturan)
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let (cakupan, id_produk, kategori) = match &promosi.cakupan {
+            CakupanPromosi::Produk { id_produk } => ("PRODUK", Some(*id_produk), None),
+            CakupanPromosi::Kategori { kategori } => ("KATEGORI", None, Some(kategori.clone())),
+            CakupanPromosi::Transaksi => ("TRANSAKSI", None, None),
+        };
+
+        let row = sqlx::query(&format!("
+                INSERT INTO promosi (nama, aturan, cakupan, id_produk, kategori, id_pelanggan, berlaku_mulai, berlaku_sampai,
+                    prioritas, dapat_digabung, aktif, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                RETURNING {}
+            ", KOLOM_PROMOSI))
+            .bind(&promosi.nama)
+            .bind(aturan)
+            .bind(cakupan)
+            .bind(id_produk)
+            .bind(kategori)
+            .bind(promosi.id_pelanggan)
+            .bind(promosi.berlaku_mulai.map(format_waktu))
+            .bind(promosi.berlaku_sampai.map(format_waktu))
+            .bind(promosi.prioritas)
+            .bind(promosi.dapat_digabung as i32)
+            .bind(promosi.aktif as i32)
+            .bind(format_waktu(Utc::now()))
+            .fetch_one(&mut *db)
+            .await?;
+
+        Self::parse_row(&row)
+    }
+
+    pub async fn get_all_promosi(db: &mut AnyConnection) -> Result<Vec<Promosi>, sqlx::Error> {
+        let rows = sqlx::query(&format!("SELECT {} FROM promosi ORDER BY prioritas, id", KOLOM_PROMOSI))
+            .fetch_all(&mut *db)
+            .await?;
+
+        rows.iter().map(Self::parse_row).collect()
+    }
+
+    // Masa berlaku dan pelanggan disaring oleh MesinPromosi
+    pub async fn get_promosi_aktif(db: &mut AnyConnection) -> Result<Vec<Promosi>, sqlx::Error> {
+        let rows = sqlx::query(&format!("SELECT {} FROM promosi WHERE aktif = 1 ORDER BY prioritas, id", KOLOM_PROMOSI))
+            .fetch_all(&mut *db)
+            .await?;
+
+        rows.iter().map(Self::parse_row).collect()
+    }
+
+    // Promosi tidak dihapus karena masih dirujuk jejak diskon transaksi lama
+    pub async fn nonaktifkan_promosi(db: &mut AnyConnection, id: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE promosi SET aktif = 0 WHERE id = $1 AND aktif = 1")
+            .bind(id)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    fn parse_row(row: &AnyRow) -> Result<Promosi, sqlx::Error> {
+        let aturan: String = row.get("aturan");
+        let aturan = serde_json::from_str(&aturan)
+            .map_err(|e| sqlx::Error::ColumnDecode {
+                index: "aturan".to_string(),
+                source: Box::new(e),
+            })?;
+
+        let cakupan: String = row.get("cakupan");
+        let cakupan = match cakupan.as_str() {
+            "PRODUK" => CakupanPromosi::Produk { id_produk: row.get("id_produk") },
+            "KATEGORI" => CakupanPromosi::Kategori { kategori: row.get("kategori") },
+            "TRANSAKSI" => CakupanPromosi::Transaksi,
+            other => return Err(sqlx::Error::ColumnDecode {
+                index: "cakupan".to_string(),
+                source: format!("cakupan promosi tidak dikenal: {}", other).into(),
+            }),
+        };
+
+        Ok(Promosi {
+            id: row.get("id"),
+            nama: row.get("nama"),
+            aturan,
+            cakupan,
+            id_pelanggan: get_optional(row, "id_pelanggan")?,
+            berlaku_mulai: parse_waktu(row, "berlaku_mulai")?,
+            berlaku_sampai: parse_waktu(row, "berlaku_sampai")?,
+            prioritas: row.get("prioritas"),
+            dapat_digabung: row.get::<i32, _>("dapat_digabung") != 0,
+            aktif: row.get::<i32, _>("aktif") != 0,
+        })
+    }
+}
+
+fn format_waktu(waktu: DateTime<Utc>) -> String {
+    waktu.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn parse_waktu(row: &AnyRow, column: &str) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    let Some(value) = get_optional::<String>(row, column)? else {
+        return Ok(None);
+    };
+
+    DateTime::parse_from_rfc3339(&value)
+        .map(|waktu| Some(waktu.with_timezone(&Utc)))
+        .map_err(|e| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: Box::new(e),
+        })
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use sqlx::any::AnyRow;
+use sqlx::{AnyConnection, Row};
+
+use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+use crate::transaksi_penjualan::model::reservasi_stok::{format_expires_at, ReservasiStok};
+
+pub struct ReservasiStokRepository;
+
+impl ReservasiStokRepository {
+    // Satu reservasi per detail; detail yang diubah menimpa reservasi lamanya
+    pub async fn simpan(db: &mut AnyConnection, reservasi: &ReservasiStok) -> Result<(), sqlx::Error> {
+        sqlx::query("
+                INSERT INTO reservasi_stok (id_detail, id_transaksi, id_produk, jumlah, expires_at)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (id_detail) DO UPDATE
+                SET id_produk = EXCLUDED.id_produk, jumlah = EXCLUDED.jumlah, expires_at = EXCLUDED.expires_at
+            ")
+            .bind(reservasi.id_detail)
+            .bind(reservasi.id_transaksi)
+            .bind(reservasi.id_produk)
+            .bind(reservasi.jumlah as i32)
+            .bind(format_expires_at(reservasi.expires_at))
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+
+    // Aktivitas pada keranjang memperpanjang semua reservasinya
+    pub async fn perpanjang(db: &mut AnyConnection, id_transaksi: i32, expires_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE reservasi_stok SET expires_at = $1 WHERE id_transaksi = $2")
+            .bind(format_expires_at(expires_at))
+            .bind(id_transaksi)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_by_transaksi(db: &mut AnyConnection, id_transaksi: i32) -> Result<Vec<ReservasiStok>, sqlx::Error> {
+        let rows = sqlx::query("
+                SELECT id_detail, id_transaksi, id_produk, jumlah, expires_at
+                FROM reservasi_stok
+                WHERE id_transaksi = $1
+                ORDER BY id_produk, id_detail
+            ")
+            .bind(id_transaksi)
+            .fetch_all(&mut *db)
+            .await?;
+
+        rows.iter().map(Self::parse_row).collect()
+    }
+
+    // Transaksi yang masih diproses dan punya reservasi kedaluwarsa, yang paling lama lebih dulu.
+    // Transaksi yang sudah punya pembayaran aktif bukan keranjang terlantar dan tidak ikut.
+    pub async fn get_transaksi_kedaluwarsa(db: &mut AnyConnection, now: DateTime<Utc>) -> Result<Vec<i32>, sqlx::Error> {
+        let rows = sqlx::query("
+                SELECT r.id_transaksi, MIN(r.expires_at) AS expires_at
+                FROM reservasi_stok r
+                JOIN transaksi t ON t.id = r.id_transaksi
+                WHERE r.expires_at <= $1 AND t.status = $2
+                AND NOT EXISTS (
+                    SELECT 1 FROM payments p
+                    WHERE p.transaction_id = CAST(t.id AS TEXT) AND p.status NOT IN ($3, $4)
+                )
+                GROUP BY r.id_transaksi
+                ORDER BY MIN(r.expires_at), r.id_transaksi
+            ")
+            .bind(format_expires_at(now))
+            .bind(StatusTransaksi::MasihDiproses.to_string())
+            .bind(PaymentStatus::Failed.to_string())
+            .bind(PaymentStatus::Void.to_string())
+            .fetch_all(&mut *db)
+            .await?;
+
+        Ok(rows.iter().map(|row| row.get("id_transaksi")).collect())
+    }
+
+    // Pembayaran selain yang gagal atau dibatalkan berarti pelanggan sudah (atau sedang) membayar
+    pub async fn punya_pembayaran_aktif(db: &mut AnyConnection, id_transaksi: i32) -> Result<bool, sqlx::Error> {
+        let row = sqlx::query("
+                SELECT COUNT(*) AS jumlah FROM payments
+                WHERE transaction_id = $1 AND status NOT IN ($2, $3)
+            ")
+            .bind(id_transaksi.to_string())
+            .bind(PaymentStatus::Failed.to_string())
+            .bind(PaymentStatus::Void.to_string())
+            .fetch_one(&mut *db)
+            .await?;
+
+        Ok(row.get::<i64, _>("jumlah") > 0)
+    }
+
+    pub async fn hapus_by_transaksi(db: &mut AnyConnection, id_transaksi: i32) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM reservasi_stok WHERE id_transaksi = $1")
+            .bind(id_transaksi)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn hapus_by_detail(db: &mut AnyConnection, id_detail: i32) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM reservasi_stok WHERE id_detail = $1")
+            .bind(id_detail)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+
+    fn parse_row(row: &AnyRow) -> Result<ReservasiStok, sqlx::Error> {
+        let expires_at: String = row.get("expires_at");
+        let expires_at = DateTime::parse_from_rfc3339(&expires_at)
+            .map_err(|e| sqlx::Error::ColumnDecode {
+                index: "expires_at".to_string(),
+                source: Box::new(e),
+            })?
+            .with_timezone(&Utc);
+
+        Ok(ReservasiStok {
+            id_detail: row.get("id_detail"),
+            id_transaksi: row.get("id_transaksi"),
+            id_produk: row.get("id_produk"),
+            jumlah: row.get::<i32, _>("jumlah") as u32,
+            expires_at,
+        })
+    }
+}
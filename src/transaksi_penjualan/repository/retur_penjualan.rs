@@ -0,0 +1,205 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::any::AnyRow;
+use sqlx::{AnyConnection, Row};
+use std::collections::HashMap;
+
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::refund_reason::RefundReason;
+use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+use crate::transaksi_penjualan::model::retur_penjualan::{DetailRetur, MetodePengembalian, ReturPenjualan};
+
+pub struct ReturPenjualanRepository;
+
+const KOLOM_RETUR: &str = "id, id_transaksi, id_pelanggan, tanggal_retur, alasan, metode_pengembalian,
+    CAST(total_pengembalian AS TEXT) AS total_pengembalian, catatan";
+
+const KOLOM_DETAIL_RETUR: &str = "id, id_retur, id_detail_transaksi, id_produk, jumlah, dapat_dijual_kembali, CAST(nilai AS TEXT) AS nilai";
+
+impl ReturPenjualanRepository {
+    pub async fn create_retur(db: &mut AnyConnection, retur: &ReturPenjualan) -> Result<ReturPenjualan, sqlx::Error> {
+        let row = sqlx::query(&format!("
+                INSERT INTO retur_penjualan (id_transaksi, id_pelanggan, tanggal_retur, alasan, metode_pengembalian, total_pengembalian, catatan)
+                VALUES ($1, $2, $3, $4, $5, CAST($6 AS DECIMAL(15,2)), $7)
+                RETURNING {}
+            ", KOLOM_RETUR))
+            .bind(retur.id_transaksi)
+            .bind(retur.id_pelanggan)
+            .bind(retur.tanggal_retur.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .bind(retur.alasan.to_string())
+            .bind(retur.metode_pengembalian.to_string())
+            .bind(retur.total_pengembalian.to_string())
+            .bind(retur.catatan.as_deref().unwrap_or(""))
+            .fetch_one(&mut *db)
+            .await?;
+        let mut created = Self::parse_row_to_retur(&row)?;
+
+        for detail in &retur.detail {
+            let row = sqlx::query(&format!("
+                    INSERT INTO detail_retur_penjualan (id_retur, id_detail_transaksi, id_produk, jumlah, dapat_dijual_kembali, nilai)
+                    VALUES ($1, $2, $3, $4, $5, CAST($6 AS DECIMAL(15,2)))
+                    RETURNING {}
+                ", KOLOM_DETAIL_RETUR))
+                .bind(created.id)
+                .bind(detail.id_detail_transaksi)
+                .bind(detail.id_produk)
+                .bind(detail.jumlah as i32)
+                .bind(detail.dapat_dijual_kembali as i32)
+                .bind(detail.nilai.to_string())
+                .fetch_one(&mut *db)
+                .await?;
+            created.detail.push(Self::parse_row_to_detail(&row)?);
+        }
+
+        for id_refund in &retur.id_refund {
+            sqlx::query("INSERT INTO refund_retur_penjualan (id_retur, id_refund) VALUES ($1, $2)")
+                .bind(created.id)
+                .bind(id_refund)
+                .execute(&mut *db)
+                .await?;
+        }
+        created.id_refund = retur.id_refund.clone();
+
+        Ok(created)
+    }
+
+    pub async fn get_retur_by_id(db: &mut AnyConnection, id: i32) -> Result<ReturPenjualan, sqlx::Error> {
+        let row = sqlx::query(&format!("SELECT {} FROM retur_penjualan WHERE id = $1", KOLOM_RETUR))
+            .bind(id)
+            .fetch_one(&mut *db)
+            .await?;
+
+        let mut retur = Self::parse_row_to_retur(&row)?;
+        Self::load_lampiran(db, std::slice::from_mut(&mut retur)).await?;
+        Ok(retur)
+    }
+
+    pub async fn get_retur_by_transaksi(db: &mut AnyConnection, id_transaksi: i32) -> Result<Vec<ReturPenjualan>, sqlx::Error> {
+        let rows = sqlx::query(&format!("SELECT {} FROM retur_penjualan WHERE id_transaksi = $1 ORDER BY id", KOLOM_RETUR))
+            .bind(id_transaksi)
+            .fetch_all(&mut *db)
+            .await?;
+
+        let mut retur = rows.iter().map(Self::parse_row_to_retur).collect::<Result<Vec<_>, _>>()?;
+        Self::load_lampiran(db, &mut retur).await?;
+        Ok(retur)
+    }
+
+    // Mengunci baris transaksi asal selama retur ditulis agar dua retur untuk transaksi yang sama
+    // tidak bisa sama-sama lolos pengecekan sisa jumlah. Nilai kolom tidak berubah.
+    pub async fn kunci_transaksi_selesai(db: &mut AnyConnection, id_transaksi: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE transaksi SET status = status WHERE id = $1 AND status = $2")
+            .bind(id_transaksi)
+            .bind(StatusTransaksi::Selesai.to_string())
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    // Jumlah yang sudah diretur per id detail transaksi
+    pub async fn jumlah_diretur(db: &mut AnyConnection, id_transaksi: i32) -> Result<HashMap<i32, u32>, sqlx::Error> {
+        let rows = sqlx::query("
+                SELECT d.id_detail_transaksi, d.jumlah
+                FROM detail_retur_penjualan d
+                JOIN retur_penjualan r ON r.id = d.id_retur
+                WHERE r.id_transaksi = $1
+            ")
+            .bind(id_transaksi)
+            .fetch_all(&mut *db)
+            .await?;
+
+        let mut jumlah: HashMap<i32, u32> = HashMap::new();
+        for row in rows {
+            *jumlah.entry(row.get("id_detail_transaksi")).or_default() += row.get::<i32, _>("jumlah") as u32;
+        }
+        Ok(jumlah)
+    }
+
+    pub async fn tambah_kredit_toko(
+        db: &mut AnyConnection,
+        id_pelanggan: i32,
+        id_retur: i32,
+        jumlah: Rupiah,
+        keterangan: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("
+                INSERT INTO kredit_toko (id_pelanggan, id_retur, jumlah, keterangan, created_at)
+                VALUES ($1, $2, CAST($3 AS DECIMAL(15,2)), $4, $5)
+            ")
+            .bind(id_pelanggan)
+            .bind(id_retur)
+            .bind(jumlah.to_string())
+            .bind(keterangan)
+            .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn saldo_kredit_toko(db: &mut AnyConnection, id_pelanggan: i32) -> Result<Rupiah, sqlx::Error> {
+        let row = sqlx::query("SELECT CAST(COALESCE(SUM(jumlah), 0) AS TEXT) AS saldo FROM kredit_toko WHERE id_pelanggan = $1")
+            .bind(id_pelanggan)
+            .fetch_one(&mut *db)
+            .await?;
+
+        Rupiah::from_row(&row, "saldo")
+    }
+
+    async fn load_lampiran(db: &mut AnyConnection, retur: &mut [ReturPenjualan]) -> Result<(), sqlx::Error> {
+        for item in retur.iter_mut() {
+            let rows = sqlx::query(&format!("SELECT {} FROM detail_retur_penjualan WHERE id_retur = $1 ORDER BY id", KOLOM_DETAIL_RETUR))
+                .bind(item.id)
+                .fetch_all(&mut *db)
+                .await?;
+            item.detail = rows.iter().map(Self::parse_row_to_detail).collect::<Result<_, _>>()?;
+
+            let rows = sqlx::query("SELECT id_refund FROM refund_retur_penjualan WHERE id_retur = $1 ORDER BY id_refund")
+                .bind(item.id)
+                .fetch_all(&mut *db)
+                .await?;
+            item.id_refund = rows.iter().map(|row| row.get("id_refund")).collect();
+        }
+
+        Ok(())
+    }
+
+    fn parse_row_to_retur(row: &AnyRow) -> Result<ReturPenjualan, sqlx::Error> {
+        let decode_error = |column: &str, value: String| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: format!("nilai tidak dikenal: {}", value).into(),
+        };
+
+        let alasan: String = row.get("alasan");
+        let metode: String = row.get("metode_pengembalian");
+        let tanggal: String = row.get("tanggal_retur");
+        let catatan: String = row.get("catatan");
+
+        Ok(ReturPenjualan {
+            id: row.get("id"),
+            id_transaksi: row.get("id_transaksi"),
+            id_pelanggan: row.get("id_pelanggan"),
+            tanggal_retur: DateTime::parse_from_rfc3339(&tanggal)
+                .map(|waktu| waktu.with_timezone(&Utc))
+                .map_err(|e| sqlx::Error::ColumnDecode { index: "tanggal_retur".to_string(), source: Box::new(e) })?,
+            alasan: RefundReason::from_string(&alasan).ok_or_else(|| decode_error("alasan", alasan.clone()))?,
+            metode_pengembalian: MetodePengembalian::from_string(&metode).ok_or_else(|| decode_error("metode_pengembalian", metode.clone()))?,
+            total_pengembalian: Rupiah::from_row(row, "total_pengembalian")?,
+            catatan: Some(catatan).filter(|c| !c.is_empty()),
+            detail: Vec::new(),
+            id_refund: Vec::new(),
+        })
+    }
+
+    fn parse_row_to_detail(row: &AnyRow) -> Result<DetailRetur, sqlx::Error> {
+        Ok(DetailRetur {
+            id: row.get("id"),
+            id_retur: row.get("id_retur"),
+            id_detail_transaksi: row.get("id_detail_transaksi"),
+            id_produk: row.get("id_produk"),
+            jumlah: row.get::<i32, _>("jumlah") as u32,
+            dapat_dijual_kembali: row.get::<i32, _>("dapat_dijual_kembali") != 0,
+            nilai: Rupiah::from_row(row, "nilai")?,
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::any::AnyRow;
+use sqlx::{AnyConnection, Row};
+
+use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+use crate::transaksi_penjualan::model::riwayat_status::RiwayatStatusTransaksi;
+
+pub struct RiwayatStatusRepository;
+
+impl RiwayatStatusRepository {
+    pub async fn catat(db: &mut AnyConnection, riwayat: &RiwayatStatusTransaksi) -> Result<RiwayatStatusTransaksi, sqlx::Error> {
+        let row = sqlx::query("
+                INSERT INTO transaksi_status_history (id_transaksi, dari_status, ke_status, aktor, alasan, waktu)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING id, id_transaksi, dari_status, ke_status, aktor, alasan, waktu
+            ")
+            .bind(riwayat.id_transaksi)
+            .bind(riwayat.dari_status.to_string())
+            .bind(riwayat.ke_status.to_string())
+            .bind(&riwayat.aktor)
+            .bind(riwayat.alasan.as_deref().unwrap_or(""))
+            .bind(riwayat.waktu.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .fetch_one(&mut *db)
+            .await?;
+
+        Self::parse_row(&row)
+    }
+
+    pub async fn get_by_transaksi(db: &mut AnyConnection, id_transaksi: i32) -> Result<Vec<RiwayatStatusTransaksi>, sqlx::Error> {
+        let rows = sqlx::query("
+                SELECT id, id_transaksi, dari_status, ke_status, aktor, alasan, waktu
+                FROM transaksi_status_history
+                WHERE id_transaksi = $1
+                ORDER BY id
+            ")
+            .bind(id_transaksi)
+            .fetch_all(&mut *db)
+            .await?;
+
+        rows.iter().map(Self::parse_row).collect()
+    }
+
+    fn parse_row(row: &AnyRow) -> Result<RiwayatStatusTransaksi, sqlx::Error> {
+        let status = |column: &str| -> Result<StatusTransaksi, sqlx::Error> {
+            let value: String = row.get(column);
+            StatusTransaksi::from_string(&value).ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: column.to_string(),
+                source: format!("status tidak dikenal: {}", value).into(),
+            })
+        };
+        let alasan: String = row.get("alasan");
+        let waktu: String = row.get("waktu");
+
+        Ok(RiwayatStatusTransaksi {
+            id: row.get("id"),
+            id_transaksi: row.get("id_transaksi"),
+            dari_status: status("dari_status")?,
+            ke_status: status("ke_status")?,
+            aktor: row.get("aktor"),
+            alasan: Some(alasan).filter(|a| !a.is_empty()),
+            waktu: DateTime::parse_from_rfc3339(&waktu)
+                .map(|waktu| waktu.with_timezone(&Utc))
+                .map_err(|e| sqlx::Error::ColumnDecode { index: "waktu".to_string(), source: Box::new(e) })?,
+        })
+    }
+}
@@ -1,74 +1,158 @@
 use sqlx::any::AnyRow;
-use sqlx::{Any, pool::PoolConnection};
+use sqlx::{Any, AnyConnection, pool::PoolConnection};
 use sqlx::Row;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::common::money::Rupiah;
+use crate::common::query_builder::SqlQueryBuilder;
 
 use crate::transaksi_penjualan::model::transaksi::Transaksi;
 use crate::transaksi_penjualan::model::detail_transaksi::DetailTransaksi;
+use crate::transaksi_penjualan::model::produk_stok::ProdukStok;
+use crate::transaksi_penjualan::model::pajak::KategoriPajak;
+use crate::transaksi_penjualan::model::promosi::DiskonTerapan;
 use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+use crate::transaksi_penjualan::repository::get_optional;
 
 pub struct TransaksiRepository;
 
+const KOLOM_TRANSAKSI: &str = "id, id_pelanggan, nama_pelanggan, tanggal_transaksi, CAST(total_harga AS TEXT) AS total_harga, status, catatan,
+    CAST(total_dpp AS TEXT) AS total_dpp, CAST(total_ppn AS TEXT) AS total_ppn, tarif_pajak_bp, harga_termasuk_pajak, id_penawaran";
+
+const KOLOM_DETAIL: &str = "id, id_transaksi, id_produk, CAST(harga_satuan AS TEXT) AS harga_satuan, jumlah, CAST(subtotal AS TEXT) AS subtotal,
+    CAST(diskon AS TEXT) AS diskon, kategori_pajak, tarif_pajak_bp, CAST(dpp AS TEXT) AS dpp, CAST(ppn AS TEXT) AS ppn, CAST(total AS TEXT) AS total";
+
 impl TransaksiRepository {
-    pub async fn create_transaksi(mut db: PoolConnection<Any>, transaksi: &Transaksi) -> Result<Transaksi, sqlx::Error> {
-        let result = sqlx::query("
-                INSERT INTO transaksi (id_pelanggan, nama_pelanggan, tanggal_transaksi, total_harga, status, catatan, created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-                RETURNING id, id_pelanggan, nama_pelanggan, tanggal_transaksi, total_harga, status, catatan
-            ")
+    pub async fn create_transaksi(db: &mut AnyConnection, transaksi: &Transaksi) -> Result<Transaksi, sqlx::Error> {
+        let result = sqlx::query(&format!("
+                INSERT INTO transaksi (id_pelanggan, nama_pelanggan, tanggal_transaksi, total_harga, status, catatan, created_at, updated_at,
+                    total_dpp, total_ppn, tarif_pajak_bp, harga_termasuk_pajak, id_penawaran)
+                VALUES ($1, $2, $3, CAST($4 AS DECIMAL(15,2)), $5, $6, $7, $8, CAST($9 AS DECIMAL(15,2)), CAST($10 AS DECIMAL(15,2)), $11, $12, $13)
+                RETURNING {}
+            ", KOLOM_TRANSAKSI))
             .bind(transaksi.id_pelanggan)
             .bind(&transaksi.nama_pelanggan)
             .bind(&transaksi.tanggal_transaksi)
-            .bind(transaksi.total_harga)
+            .bind(transaksi.total_harga.to_string())
             .bind(transaksi.status.to_string())
             .bind(transaksi.catatan.as_ref().map(|s| s.as_str()).unwrap_or(""))
             .bind(DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap().to_string())
             .bind(DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap().to_string())
+            .bind(transaksi.total_dpp.to_string())
+            .bind(transaksi.total_ppn.to_string())
+            .bind(transaksi.tarif_pajak_bp)
+            .bind(transaksi.harga_termasuk_pajak as i32)
+            .bind(transaksi.id_penawaran)
             .fetch_one(&mut *db)
             .await?;
         
-        let transaksi = Self::parse_row_to_transaksi(result);
+        let transaksi = Self::parse_row_to_transaksi(result)?;
         Ok(transaksi)
     }
 
     pub async fn get_transaksi_by_id(mut db: PoolConnection<Any>, id: i32) -> Result<Transaksi, sqlx::Error> {
-        let result = sqlx::query("
-                SELECT id, id_pelanggan, nama_pelanggan, tanggal_transaksi, total_harga, status, catatan
+        Self::find_transaksi(&mut db, id).await
+    }
+
+    // Kunci baris transaksi sampai transaksi database selesai, misalnya agar dua pembayaran
+    // bersamaan tidak sama-sama lolos pengecekan sisa tagihan. PostgreSQL memakai FOR UPDATE;
+    // SQLite memakai UPDATE tanpa perubahan untuk mengambil write lock lebih awal.
+    pub async fn kunci_transaksi(db: &mut AnyConnection, id: i32) -> Result<Transaksi, sqlx::Error> {
+        if db.backend_name().eq_ignore_ascii_case("postgresql") {
+            sqlx::query("SELECT id FROM transaksi WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_one(&mut *db)
+                .await?;
+        } else {
+            sqlx::query("UPDATE transaksi SET status = status WHERE id = $1")
+                .bind(id)
+                .execute(&mut *db)
+                .await?;
+        }
+
+        Self::find_transaksi(db, id).await
+    }
+
+    async fn find_transaksi(db: &mut AnyConnection, id: i32) -> Result<Transaksi, sqlx::Error> {
+        let result = sqlx::query(&format!("
+                SELECT {}
                 FROM transaksi
                 WHERE id = $1
-            ")
+            ", KOLOM_TRANSAKSI))
             .bind(id)
             .fetch_one(&mut *db)
             .await?;
         
-        let transaksi = Self::parse_row_to_transaksi(result);
+        let transaksi = Self::parse_row_to_transaksi(result)?;
         Ok(transaksi)
     }
 
-    pub async fn update_transaksi(mut db: PoolConnection<Any>, transaksi: &Transaksi) -> Result<Transaksi, sqlx::Error> {
-        let result = sqlx::query("
+    // Hanya kolom header yang boleh diubah klien; total dan rincian PPN ditulis lewat update_total,
+    // status hanya lewat ubah_status_jika. `transaksi.status` dipakai sebagai syarat: RowNotFound
+    // bila status transaksi sudah diubah proses lain sejak dibaca.
+    pub async fn update_transaksi(db: &mut AnyConnection, transaksi: &Transaksi) -> Result<Transaksi, sqlx::Error> {
+        let result = sqlx::query(&format!("
                 UPDATE transaksi
                 SET id_pelanggan = $1, nama_pelanggan = $2, tanggal_transaksi = $3, 
-                    total_harga = $4, status = $5, catatan = $6, updated_at = $7
-                WHERE id = $8
-                RETURNING id, id_pelanggan, nama_pelanggan, tanggal_transaksi, total_harga, status, catatan
-            ")
+                    catatan = $4, updated_at = $5
+                WHERE id = $6 AND status = $7
+                RETURNING {}
+            ", KOLOM_TRANSAKSI))
             .bind(transaksi.id_pelanggan)
             .bind(&transaksi.nama_pelanggan)
             .bind(&transaksi.tanggal_transaksi)
-            .bind(transaksi.total_harga)
-            .bind(transaksi.status.to_string())
             .bind(transaksi.catatan.as_ref().map(|s| s.as_str()).unwrap_or(""))
             .bind(DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap().to_string())
             .bind(transaksi.id)
+            .bind(transaksi.status.to_string())
             .fetch_one(&mut *db)
             .await?;
         
-        let transaksi = Self::parse_row_to_transaksi(result);
+        let transaksi = Self::parse_row_to_transaksi(result)?;
         Ok(transaksi)
     }
 
-    pub async fn delete_transaksi(mut db: PoolConnection<Any>, id: i32) -> Result<(), sqlx::Error> {
+    // Total harga dan rincian PPN hasil perhitungan ulang harga di server
+    pub async fn update_total(db: &mut AnyConnection, transaksi: &Transaksi) -> Result<(), sqlx::Error> {
+        sqlx::query("
+                UPDATE transaksi
+                SET total_harga = CAST($1 AS DECIMAL(15,2)), total_dpp = CAST($2 AS DECIMAL(15,2)), total_ppn = CAST($3 AS DECIMAL(15,2)),
+                    tarif_pajak_bp = $4, harga_termasuk_pajak = $5, updated_at = $6
+                WHERE id = $7
+            ")
+            .bind(transaksi.total_harga.to_string())
+            .bind(transaksi.total_dpp.to_string())
+            .bind(transaksi.total_ppn.to_string())
+            .bind(transaksi.tarif_pajak_bp)
+            .bind(transaksi.harga_termasuk_pajak as i32)
+            .bind(DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap().to_string())
+            .bind(transaksi.id)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+
+    // Perubahan status bersyarat; false bila status transaksi sudah diubah proses lain
+    pub async fn ubah_status_jika(
+        db: &mut AnyConnection,
+        id: i32,
+        dari: &StatusTransaksi,
+        ke: &StatusTransaksi
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE transaksi SET status = $1, updated_at = $2 WHERE id = $3 AND status = $4")
+            .bind(ke.to_string())
+            .bind(DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap().to_string())
+            .bind(id)
+            .bind(dari.to_string())
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn delete_transaksi(db: &mut AnyConnection, id: i32) -> Result<(), sqlx::Error> {
         sqlx::query("DELETE FROM transaksi WHERE id = $1")
             .bind(id)
             .execute(&mut *db)
@@ -78,17 +162,17 @@ impl TransaksiRepository {
     }
 
     pub async fn get_all_transaksi(mut db: PoolConnection<Any>) -> Result<Vec<Transaksi>, sqlx::Error> {
-        let rows = sqlx::query("
-                SELECT id, id_pelanggan, nama_pelanggan, tanggal_transaksi, total_harga, status, catatan
+        let rows = sqlx::query(&format!("
+                SELECT {}
                 FROM transaksi
                 ORDER BY tanggal_transaksi DESC
-            ")
+            ", KOLOM_TRANSAKSI))
             .fetch_all(&mut *db)
             .await?;
         
         let mut transaksi_list = Vec::new();
         for row in rows {
-            let transaksi = Self::parse_row_to_transaksi(row);
+            let transaksi = Self::parse_row_to_transaksi(row)?;
             transaksi_list.push(transaksi);
         }
         
@@ -96,19 +180,19 @@ impl TransaksiRepository {
     }
 
     pub async fn get_transaksi_by_pelanggan(mut db: PoolConnection<Any>, id_pelanggan: i32) -> Result<Vec<Transaksi>, sqlx::Error> {
-        let rows = sqlx::query("
-                SELECT id, id_pelanggan, nama_pelanggan, tanggal_transaksi, total_harga, status, catatan
+        let rows = sqlx::query(&format!("
+                SELECT {}
                 FROM transaksi
                 WHERE id_pelanggan = $1
                 ORDER BY tanggal_transaksi DESC
-            ")
+            ", KOLOM_TRANSAKSI))
             .bind(id_pelanggan)
             .fetch_all(&mut *db)
             .await?;
         
         let mut transaksi_list = Vec::new();
         for row in rows {
-            let transaksi = Self::parse_row_to_transaksi(row);
+            let transaksi = Self::parse_row_to_transaksi(row)?;
             transaksi_list.push(transaksi);
         }
         
@@ -116,85 +200,207 @@ impl TransaksiRepository {
     }
 
     pub async fn get_transaksi_by_status(mut db: PoolConnection<Any>, status: &StatusTransaksi) -> Result<Vec<Transaksi>, sqlx::Error> {
-        let rows = sqlx::query("
-                SELECT id, id_pelanggan, nama_pelanggan, tanggal_transaksi, total_harga, status, catatan
+        let rows = sqlx::query(&format!("
+                SELECT {}
                 FROM transaksi
                 WHERE status = $1
                 ORDER BY tanggal_transaksi DESC
-            ")
+            ", KOLOM_TRANSAKSI))
             .bind(status.to_string())
             .fetch_all(&mut *db)
             .await?;
         
         let mut transaksi_list = Vec::new();
         for row in rows {
-            let transaksi = Self::parse_row_to_transaksi(row);
+            let transaksi = Self::parse_row_to_transaksi(row)?;
             transaksi_list.push(transaksi);
         }
         
         Ok(transaksi_list)
     }
 
-    pub async fn create_detail_transaksi(mut db: PoolConnection<Any>, detail: &DetailTransaksi) -> Result<DetailTransaksi, sqlx::Error> {
-        let result = sqlx::query("
-                INSERT INTO detail_transaksi (id_transaksi, id_produk, harga_satuan, jumlah, subtotal, created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5, $6, $7)
-                RETURNING id, id_transaksi, id_produk, harga_satuan, jumlah, subtotal
-            ")
+    pub async fn create_detail_transaksi(db: &mut AnyConnection, detail: &DetailTransaksi) -> Result<DetailTransaksi, sqlx::Error> {
+        let result = sqlx::query(&format!("
+                INSERT INTO detail_transaksi (id_transaksi, id_produk, harga_satuan, jumlah, subtotal, diskon, created_at, updated_at,
+                    kategori_pajak, tarif_pajak_bp, dpp, ppn, total)
+                VALUES ($1, $2, CAST($3 AS DECIMAL(15,2)), $4, CAST($5 AS DECIMAL(15,2)), CAST($6 AS DECIMAL(15,2)), $7, $8,
+                    $9, $10, CAST($11 AS DECIMAL(15,2)), CAST($12 AS DECIMAL(15,2)), CAST($13 AS DECIMAL(15,2)))
+                RETURNING {}
+            ", KOLOM_DETAIL))
             .bind(detail.id_transaksi)
             .bind(detail.id_produk)
-            .bind(detail.harga_satuan)
-            .bind(detail.jumlah as i32)
-            .bind(detail.subtotal)
+            .bind(detail.harga_satuan.to_string())
+            .bind(Self::jumlah_i32(detail.jumlah)?)
+            .bind(detail.subtotal.to_string())
+            .bind(detail.diskon.to_string())
             .bind(DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap().to_string())
             .bind(DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap().to_string())
+            .bind(detail.kategori_pajak.to_string())
+            .bind(detail.tarif_pajak_bp)
+            .bind(detail.dpp.to_string())
+            .bind(detail.ppn.to_string())
+            .bind(detail.total.to_string())
             .fetch_one(&mut *db)
             .await?;
         
-        let detail = Self::parse_row_to_detail_transaksi(result);
-        Ok(detail)
+        let mut created = Self::parse_row_to_detail_transaksi(result)?;
+        created.diskon_diterapkan = detail.diskon_diterapkan.clone();
+        Self::simpan_diskon_detail(db, &created).await?;
+        Ok(created)
     }
 
-    pub async fn get_detail_by_transaksi_id(mut db: PoolConnection<Any>, id_transaksi: i32) -> Result<Vec<DetailTransaksi>, sqlx::Error> {
-        let rows = sqlx::query("
-                SELECT id, id_transaksi, id_produk, harga_satuan, jumlah, subtotal
+    pub async fn get_detail_by_transaksi_id(db: &mut AnyConnection, id_transaksi: i32) -> Result<Vec<DetailTransaksi>, sqlx::Error> {
+        let rows = sqlx::query(&format!("
+                SELECT {}
                 FROM detail_transaksi
                 WHERE id_transaksi = $1
-            ")
+                ORDER BY id
+            ", KOLOM_DETAIL))
             .bind(id_transaksi)
             .fetch_all(&mut *db)
             .await?;
         
+        let mut diskon = Self::get_diskon_by_transaksi_id(db, id_transaksi).await?;
         let mut detail_list = Vec::new();
         for row in rows {
-            let detail = Self::parse_row_to_detail_transaksi(row);
+            let mut detail = Self::parse_row_to_detail_transaksi(row)?;
+            detail.diskon_diterapkan = diskon.remove(&detail.id).unwrap_or_default();
             detail_list.push(detail);
         }
         
         Ok(detail_list)
     }
 
-    pub async fn update_detail_transaksi(mut db: PoolConnection<Any>, detail: &DetailTransaksi) -> Result<DetailTransaksi, sqlx::Error> {
-        let result = sqlx::query("
-                UPDATE detail_transaksi
-                SET id_produk = $1, harga_satuan = $2, jumlah = $3, subtotal = $4, updated_at = $5
-                WHERE id = $6
-                RETURNING id, id_transaksi, id_produk, harga_satuan, jumlah, subtotal
+    async fn get_diskon_by_transaksi_id(db: &mut AnyConnection, id_transaksi: i32) -> Result<HashMap<i32, Vec<DiskonTerapan>>, sqlx::Error> {
+        let rows = sqlx::query("
+                SELECT id_detail, id_promosi, nama_promosi, CAST(potongan AS TEXT) AS potongan
+                FROM diskon_detail_transaksi
+                WHERE id_transaksi = $1
+                ORDER BY id
             ")
+            .bind(id_transaksi)
+            .fetch_all(&mut *db)
+            .await?;
+
+        let mut diskon: HashMap<i32, Vec<DiskonTerapan>> = HashMap::new();
+        for row in rows {
+            diskon.entry(row.get("id_detail")).or_default().push(DiskonTerapan {
+                id_promosi: row.get("id_promosi"),
+                nama_promosi: row.get("nama_promosi"),
+                potongan: Rupiah::from_row(&row, "potongan")?,
+            });
+        }
+
+        Ok(diskon)
+    }
+
+    // Jejak diskon ditulis ulang seluruhnya setiap kali detail disimpan
+    async fn simpan_diskon_detail(db: &mut AnyConnection, detail: &DetailTransaksi) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM diskon_detail_transaksi WHERE id_detail = $1")
+            .bind(detail.id)
+            .execute(&mut *db)
+            .await?;
+
+        for diskon in &detail.diskon_diterapkan {
+            sqlx::query("
+                    INSERT INTO diskon_detail_transaksi (id_detail, id_transaksi, id_promosi, nama_promosi, potongan)
+                    VALUES ($1, $2, $3, $4, CAST($5 AS DECIMAL(15,2)))
+                ")
+                .bind(detail.id)
+                .bind(detail.id_transaksi)
+                .bind(diskon.id_promosi)
+                .bind(&diskon.nama_promosi)
+                .bind(diskon.potongan.to_string())
+                .execute(&mut *db)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // Produk yang sudah dihapus tidak ikut dikembalikan
+    pub async fn get_produk(db: &mut AnyConnection, id_produk: &[i32]) -> Result<HashMap<i32, ProdukStok>, sqlx::Error> {
+        let mut builder = SqlQueryBuilder::new();
+        builder.and_where_in("id", id_produk.iter().map(|id| *id as i64));
+        let sql = builder.to_sql("SELECT id, nama, kategori, kategori_pajak, CAST(harga AS TEXT) AS harga, stok FROM produk");
+
+        let rows = builder.bind(&sql).fetch_all(&mut *db).await?;
+        let mut produk = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let id = row.get::<i64, _>("id") as i32;
+            produk.insert(id, ProdukStok {
+                id,
+                nama: row.get("nama"),
+                kategori: row.get("kategori"),
+                kategori_pajak: Self::parse_kategori_pajak(&row)?,
+                harga: Rupiah::from_row(&row, "harga")?,
+                stok: row.get::<i32, _>("stok").max(0) as u32,
+            });
+        }
+
+        Ok(produk)
+    }
+
+    // Pengurangan bersyarat agar stok tidak pernah negatif; false bila stok tidak cukup.
+    // Jumlah di atas i32::MAX pasti melebihi stok, dan tidak boleh berputar menjadi negatif.
+    pub async fn kurangi_stok(db: &mut AnyConnection, id_produk: i32, jumlah: u32) -> Result<bool, sqlx::Error> {
+        let Ok(jumlah) = i32::try_from(jumlah) else {
+            return Ok(false);
+        };
+        let result = sqlx::query("UPDATE produk SET stok = stok - $1 WHERE id = $2 AND stok >= $1")
+            .bind(jumlah)
+            .bind(id_produk as i64)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn tambah_stok(db: &mut AnyConnection, id_produk: i32, jumlah: u32) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE produk SET stok = stok + $1 WHERE id = $2")
+            .bind(Self::jumlah_i32(jumlah)?)
+            .bind(id_produk as i64)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+
+    fn jumlah_i32(jumlah: u32) -> Result<i32, sqlx::Error> {
+        i32::try_from(jumlah).map_err(|_| sqlx::Error::Protocol(format!("Jumlah {} melebihi batas kolom INTEGER", jumlah)))
+    }
+
+    pub async fn update_detail_transaksi(db: &mut AnyConnection, detail: &DetailTransaksi) -> Result<DetailTransaksi, sqlx::Error> {
+        let result = sqlx::query(&format!("
+                UPDATE detail_transaksi
+                SET id_produk = $1, harga_satuan = CAST($2 AS DECIMAL(15,2)), jumlah = $3, subtotal = CAST($4 AS DECIMAL(15,2)),
+                    diskon = CAST($5 AS DECIMAL(15,2)), updated_at = $6, kategori_pajak = $7, tarif_pajak_bp = $8,
+                    dpp = CAST($9 AS DECIMAL(15,2)), ppn = CAST($10 AS DECIMAL(15,2)), total = CAST($11 AS DECIMAL(15,2))
+                WHERE id = $12
+                RETURNING {}
+            ", KOLOM_DETAIL))
             .bind(detail.id_produk)
-            .bind(detail.harga_satuan)
-            .bind(detail.jumlah as i32)
-            .bind(detail.subtotal)
+            .bind(detail.harga_satuan.to_string())
+            .bind(Self::jumlah_i32(detail.jumlah)?)
+            .bind(detail.subtotal.to_string())
+            .bind(detail.diskon.to_string())
             .bind(DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap().to_string())
+            .bind(detail.kategori_pajak.to_string())
+            .bind(detail.tarif_pajak_bp)
+            .bind(detail.dpp.to_string())
+            .bind(detail.ppn.to_string())
+            .bind(detail.total.to_string())
             .bind(detail.id)
             .fetch_one(&mut *db)
             .await?;
         
-        let detail = Self::parse_row_to_detail_transaksi(result);
-        Ok(detail)
+        let mut updated = Self::parse_row_to_detail_transaksi(result)?;
+        updated.diskon_diterapkan = detail.diskon_diterapkan.clone();
+        Self::simpan_diskon_detail(db, &updated).await?;
+        Ok(updated)
     }
 
-    pub async fn delete_detail_transaksi(mut db: PoolConnection<Any>, id: i32) -> Result<(), sqlx::Error> {
+    pub async fn delete_detail_transaksi(db: &mut AnyConnection, id: i32) -> Result<(), sqlx::Error> {
         sqlx::query("DELETE FROM detail_transaksi WHERE id = $1")
             .bind(id)
             .execute(&mut *db)
@@ -203,7 +409,7 @@ impl TransaksiRepository {
         Ok(())
     }
 
-    pub async fn delete_detail_by_transaksi_id(mut db: PoolConnection<Any>, id_transaksi: i32) -> Result<(), sqlx::Error> {
+    pub async fn delete_detail_by_transaksi_id(db: &mut AnyConnection, id_transaksi: i32) -> Result<(), sqlx::Error> {
         sqlx::query("DELETE FROM detail_transaksi WHERE id_transaksi = $1")
             .bind(id_transaksi)
             .execute(&mut *db)
@@ -212,33 +418,53 @@ impl TransaksiRepository {
         Ok(())
     }
 
-    fn parse_row_to_transaksi(row: AnyRow) -> Transaksi {
+    fn parse_row_to_transaksi(row: AnyRow) -> Result<Transaksi, sqlx::Error> {
         let status_str: String = row.get("status");
         let status = StatusTransaksi::from_string(&status_str).unwrap_or(StatusTransaksi::MasihDiproses);
 
         let mut transaksi = Transaksi::new(
             row.get("id_pelanggan"),
             row.get("nama_pelanggan"),
-            row.get("total_harga"),
+            Rupiah::from_row(&row, "total_harga")?,
             row.get("catatan"),
         );
 
         transaksi.id = row.get("id");
         transaksi.tanggal_transaksi = row.get("tanggal_transaksi");
         transaksi.status = status;
+        transaksi.total_dpp = Rupiah::from_row(&row, "total_dpp")?;
+        transaksi.total_ppn = Rupiah::from_row(&row, "total_ppn")?;
+        transaksi.tarif_pajak_bp = row.get::<i32, _>("tarif_pajak_bp") as i64;
+        transaksi.harga_termasuk_pajak = row.get::<i32, _>("harga_termasuk_pajak") != 0;
+        transaksi.id_penawaran = get_optional(&row, "id_penawaran")?;
 
-        transaksi
+        Ok(transaksi)
     }
 
-    fn parse_row_to_detail_transaksi(row: AnyRow) -> DetailTransaksi {
-        DetailTransaksi {
+    fn parse_row_to_detail_transaksi(row: AnyRow) -> Result<DetailTransaksi, sqlx::Error> {
+        Ok(DetailTransaksi {
             id: row.get("id"),
             id_transaksi: row.get("id_transaksi"),
             id_produk: row.get("id_produk"),
-            harga_satuan: row.get("harga_satuan"),
+            harga_satuan: Rupiah::from_row(&row, "harga_satuan")?,
             jumlah: row.get::<i32, _>("jumlah") as u32,
-            subtotal: row.get("subtotal"),
-        }
+            subtotal: Rupiah::from_row(&row, "subtotal")?,
+            diskon: Rupiah::from_row(&row, "diskon")?,
+            diskon_diterapkan: Vec::new(),
+            kategori_pajak: Self::parse_kategori_pajak(&row)?,
+            tarif_pajak_bp: row.get::<i32, _>("tarif_pajak_bp") as i64,
+            dpp: Rupiah::from_row(&row, "dpp")?,
+            ppn: Rupiah::from_row(&row, "ppn")?,
+            total: Rupiah::from_row(&row, "total")?,
+        })
+    }
+
+    fn parse_kategori_pajak(row: &AnyRow) -> Result<KategoriPajak, sqlx::Error> {
+        let kategori: String = row.get("kategori_pajak");
+        KategoriPajak::from_string(&kategori).ok_or_else(|| sqlx::Error::ColumnDecode {
+            index: "kategori_pajak".to_string(),
+            source: format!("kategori pajak tidak dikenal: {}", kategori).into(),
+        })
     }
 }
 
@@ -274,14 +500,14 @@ mod test {
         let transaksi = Transaksi::new(
             1,
             "Castorice".to_string(),
-            150000.0,
+            Rupiah::from_rupiah(150000),
             Some("Test transaction".to_string()),
         );
-        let created_transaksi = TransaksiRepository::create_transaksi(db.acquire().await.unwrap(), &transaksi).await.unwrap();
+        let created_transaksi = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi).await.unwrap();
 
         assert_eq!(created_transaksi.id_pelanggan, 1);
         assert_eq!(created_transaksi.nama_pelanggan, "Castorice");
-        assert_eq!(created_transaksi.total_harga, 150000.0);
+        assert_eq!(created_transaksi.total_harga, Rupiah::from_rupiah(150000));
         assert_eq!(created_transaksi.status, StatusTransaksi::MasihDiproses);
     }
 
@@ -292,16 +518,16 @@ mod test {
         let transaksi = Transaksi::new(
             2,
             "Tribbie".to_string(),
-            200000.0,
+            Rupiah::from_rupiah(200000),
             None,
         );
-        let created_transaksi = TransaksiRepository::create_transaksi(db.acquire().await.unwrap(), &transaksi).await.unwrap();
+        let created_transaksi = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi).await.unwrap();
 
         let fetched_transaksi = TransaksiRepository::get_transaksi_by_id(db.acquire().await.unwrap(), created_transaksi.id).await.unwrap();
 
         assert_eq!(fetched_transaksi.id_pelanggan, 2);
         assert_eq!(fetched_transaksi.nama_pelanggan, "Tribbie");
-        assert_eq!(fetched_transaksi.total_harga, 200000.0);
+        assert_eq!(fetched_transaksi.total_harga, Rupiah::from_rupiah(200000));
     }
 
     #[async_test]
@@ -311,33 +537,33 @@ mod test {
         let transaksi = Transaksi::new(
             1,
             "Hyacine".to_string(),
-            500000.0,
+            Rupiah::from_rupiah(500000),
             None,
         );
-        let created_transaksi = TransaksiRepository::create_transaksi(db.acquire().await.unwrap(), &transaksi).await.unwrap();
+        let created_transaksi = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi).await.unwrap();
 
         let detail = DetailTransaksi::new(
             created_transaksi.id,
             101,
-            15000000.0,
+            Rupiah::from_rupiah(15000000),
             1,
         );
-        let created_detail = TransaksiRepository::create_detail_transaksi(db.acquire().await.unwrap(), &detail).await.unwrap();
+        let created_detail = TransaksiRepository::create_detail_transaksi(&mut db.acquire().await.unwrap(), &detail).await.unwrap();
 
         assert_eq!(created_detail.id_transaksi, created_transaksi.id);
         assert_eq!(created_detail.id_produk, 101);
-        assert_eq!(created_detail.subtotal, 15000000.0);
+        assert_eq!(created_detail.subtotal, Rupiah::from_rupiah(15000000));
     }
 
     #[async_test]
     async fn test_get_all_transaksi() {
         let db = setup().await;
 
-        let transaksi1 = Transaksi::new(1, "Alice".to_string(), 100000.0, None);
-        let transaksi2 = Transaksi::new(2, "Bob".to_string(), 200000.0, None);
+        let transaksi1 = Transaksi::new(1, "Alice".to_string(), Rupiah::from_rupiah(100000), None);
+        let transaksi2 = Transaksi::new(2, "Bob".to_string(), Rupiah::from_rupiah(200000), None);
 
-        TransaksiRepository::create_transaksi(db.acquire().await.unwrap(), &transaksi1).await.unwrap();
-        TransaksiRepository::create_transaksi(db.acquire().await.unwrap(), &transaksi2).await.unwrap();
+        TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi1).await.unwrap();
+        TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi2).await.unwrap();
 
         let all_transaksi = TransaksiRepository::get_all_transaksi(db.acquire().await.unwrap()).await.unwrap();
         
@@ -353,15 +579,15 @@ mod test {
         let transaksi = Transaksi::new(
             99,
             "Data Type Test".to_string(),
-            999.99,
+            Rupiah::from_sen(99_999),
             Some("Testing simple data types".to_string()),
         );
 
-        let created = TransaksiRepository::create_transaksi(db.acquire().await.unwrap(), &transaksi).await.unwrap();
+        let created = TransaksiRepository::create_transaksi(&mut db.acquire().await.unwrap(), &transaksi).await.unwrap();
         
         assert!(created.id > 0);
         assert_eq!(created.id_pelanggan, 99);
-        assert_eq!(created.total_harga, 999.99);
+        assert_eq!(created.total_harga, Rupiah::from_sen(99_999));
         assert!(!created.tanggal_transaksi.is_empty());
         
         println!("Created transaksi with simple data types: {:?}", created);
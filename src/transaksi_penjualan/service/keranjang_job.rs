@@ -0,0 +1,20 @@
+use chrono::Utc;
+use rocket::fairing::AdHoc;
+
+use crate::common::job::interval_job;
+use crate::transaksi_penjualan::service::keranjang_tertahan::KeranjangTertahanService;
+
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+
+pub fn stage() -> AdHoc {
+    interval_job("Held Cart Purger", "HELD_CART_PURGE_INTERVAL_SECS", DEFAULT_INTERVAL_SECS, |db| async move {
+        match KeranjangTertahanService::purge_expired(&db, Utc::now()).await {
+            Ok(purged) if !purged.is_empty() => println!(
+                "[INFO] Held cart purger removed {} expired carts: {:?}",
+                purged.len(), purged
+            ),
+            Ok(_) => {}
+            Err(err) => eprintln!("[ERROR] Held cart purger failed: {:?}", err),
+        }
+    })
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Any, Acquire, AnyConnection, Pool};
+
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::transaksi_penjualan::dto::keranjang_request::{KonversiKeranjangRequest, TahanKeranjangRequest};
+use crate::transaksi_penjualan::model::keranjang_tertahan::{BarisKeranjang, KeranjangPolicy, KeranjangTertahan};
+use crate::transaksi_penjualan::model::transaksi::Transaksi;
+use crate::transaksi_penjualan::repository::keranjang_tertahan::KeranjangTertahanRepository;
+use crate::transaksi_penjualan::service::transaksi::{TransaksiError, TransaksiService};
+
+pub struct KeranjangTertahanService;
+
+impl KeranjangTertahanService {
+    pub async fn tahan_keranjang(
+        db: Pool<Any>,
+        kasir: &AuthenticatedUser,
+        request: &TahanKeranjangRequest,
+    ) -> Result<KeranjangTertahan, TransaksiError> {
+        request.validate().map_err(TransaksiError::Validasi)?;
+        let keranjang = Self::dari_request(0, kasir, request, Utc::now());
+
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+        Self::cek_nama(&mut tx, &keranjang).await?;
+        let created = KeranjangTertahanRepository::create_keranjang(&mut tx, &keranjang).await?;
+
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    // Menyimpan ulang keranjang yang dilanjutkan lalu ditahan lagi; masa berlakunya ikut diperpanjang
+    pub async fn update_keranjang(
+        db: Pool<Any>,
+        kasir: &AuthenticatedUser,
+        id: i32,
+        request: &TahanKeranjangRequest,
+    ) -> Result<KeranjangTertahan, TransaksiError> {
+        request.validate().map_err(TransaksiError::Validasi)?;
+        let keranjang = Self::dari_request(id, kasir, request, Utc::now());
+
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+        Self::cek_nama(&mut tx, &keranjang).await?;
+        let updated = KeranjangTertahanRepository::update_keranjang(&mut tx, &keranjang).await?;
+
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    pub async fn get_keranjang_kasir(db: Pool<Any>, kasir: &AuthenticatedUser) -> Result<Vec<KeranjangTertahan>, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        KeranjangTertahanRepository::get_keranjang_by_kasir(&mut db_connection, kasir.user_id).await
+    }
+
+    // Keranjang kasir lain diperlakukan seperti tidak ada
+    pub async fn get_keranjang_by_id(db: Pool<Any>, kasir: &AuthenticatedUser, id: i32) -> Result<KeranjangTertahan, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        KeranjangTertahanRepository::get_keranjang_by_id(&mut db_connection, id, kasir.user_id).await
+    }
+
+    pub async fn delete_keranjang(db: Pool<Any>, kasir: &AuthenticatedUser, id: i32) -> Result<(), sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+        if !KeranjangTertahanRepository::delete_keranjang(&mut tx, id, kasir.user_id).await? {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        tx.commit().await
+    }
+
+    // Keranjang dihapus dan transaksinya dibuat lewat jalur yang sama dengan create_transaksi_with_details,
+    // dalam satu transaksi database: stok kurang atau produk hilang membatalkan keduanya,
+    // dan keranjang yang sama tidak bisa dikonversi dua kali.
+    pub async fn konversi_keranjang(
+        db: Pool<Any>,
+        kasir: &AuthenticatedUser,
+        id: i32,
+        request: &KonversiKeranjangRequest,
+    ) -> Result<Transaksi, TransaksiError> {
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+
+        let keranjang = KeranjangTertahanRepository::get_keranjang_by_id(&mut tx, id, kasir.user_id).await?;
+        let transaksi_request = keranjang
+            .to_transaksi_request(
+                request.id_pelanggan,
+                request.nama_pelanggan.clone().filter(|n| !n.trim().is_empty()),
+                request.catatan.clone().filter(|c| !c.trim().is_empty()),
+            )
+            .map_err(TransaksiError::Validasi)?;
+
+        if !KeranjangTertahanRepository::delete_keranjang(&mut tx, id, kasir.user_id).await? {
+            return Err(TransaksiError::Database(sqlx::Error::RowNotFound));
+        }
+        let transaksi = TransaksiService::buat_transaksi(&mut tx, &transaksi_request).await?;
+
+        tx.commit().await?;
+        Ok(transaksi)
+    }
+
+    pub async fn purge_expired(db: &Pool<Any>, now: DateTime<Utc>) -> Result<Vec<i32>, sqlx::Error> {
+        let batas = KeranjangPolicy::from_env().batas_kedaluwarsa(now);
+
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+        let deleted = KeranjangTertahanRepository::delete_kedaluwarsa(&mut tx, batas).await?;
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
+
+    async fn cek_nama(db: &mut AnyConnection, keranjang: &KeranjangTertahan) -> Result<(), TransaksiError> {
+        if KeranjangTertahanRepository::nama_dipakai(db, keranjang.id_kasir, &keranjang.nama, keranjang.id).await? {
+            return Err(TransaksiError::Validasi(format!("Keranjang dengan nama '{}' sudah ada", keranjang.nama)));
+        }
+        Ok(())
+    }
+
+    fn dari_request(id: i32, kasir: &AuthenticatedUser, request: &TahanKeranjangRequest, now: DateTime<Utc>) -> KeranjangTertahan {
+        KeranjangTertahan {
+            id,
+            id_kasir: kasir.user_id,
+            nama: request.nama.trim().to_string(),
+            id_pelanggan: request.id_pelanggan,
+            nama_pelanggan: request.nama_pelanggan.clone().filter(|n| !n.trim().is_empty()),
+            catatan: request.catatan.clone().filter(|c| !c.trim().is_empty()),
+            created_at: now,
+            updated_at: now,
+            detail: request.detail.iter().map(|baris| BarisKeranjang {
+                id: 0,
+                id_keranjang: id,
+                id_produk: baris.id_produk,
+                nama_produk: baris.nama_produk.clone(),
+                jumlah: baris.jumlah,
+            }).collect(),
+        }
+    }
+}
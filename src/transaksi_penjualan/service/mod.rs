@@ -1 +1,10 @@
-pub mod transaksi;
\ No newline at end of file
+pub mod transaksi;
+pub mod receipt;
+pub mod reservasi_stok;
+pub mod reservasi_job;
+pub mod promosi;
+pub mod retur_penjualan;
+pub mod penawaran;
+pub mod keranjang_tertahan;
+pub mod keranjang_job;
+pub mod pengiriman;
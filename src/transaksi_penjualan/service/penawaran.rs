@@ -0,0 +1,172 @@
+use chrono::Utc;
+use sqlx::{Any, Acquire, Pool};
+
+use crate::common::money::Rupiah;
+use crate::transaksi_penjualan::dto::penawaran_request::{CreatePenawaranRequest, KonversiPenawaranRequest};
+use crate::transaksi_penjualan::dto::transaksi_request::{CreateDetailTransaksiRequest, CreateTransaksiRequest};
+use crate::transaksi_penjualan::model::detail_transaksi::DetailTransaksi;
+use crate::transaksi_penjualan::model::pajak::KebijakanPajak;
+use crate::transaksi_penjualan::model::penawaran::{DetailPenawaran, Penawaran, StatusPenawaran};
+use crate::transaksi_penjualan::model::receipt::{Receipt, ReceiptDocument, ReceiptLine, StoreProfile, TaxBreakdown};
+use crate::transaksi_penjualan::model::reservasi_stok::ReservasiPolicy;
+use crate::transaksi_penjualan::model::transaksi::Transaksi;
+use crate::transaksi_penjualan::repository::penawaran::PenawaranRepository;
+use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+use crate::transaksi_penjualan::service::transaksi::{TransaksiError, TransaksiService};
+
+pub struct PenawaranService;
+
+impl PenawaranService {
+    // Harga dihitung seperti transaksi (katalog, promosi aktif, PPN) tetapi stok tidak dikurangi
+    // maupun direservasi. Produk harus ada di katalog, stoknya belum diperiksa.
+    pub async fn create_penawaran(db: Pool<Any>, request: &CreatePenawaranRequest) -> Result<Penawaran, TransaksiError> {
+        let now = Utc::now();
+        request.validate(now).map_err(TransaksiError::Validasi)?;
+        let transaksi_request = request.to_transaksi_request();
+
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+
+        let product_ids: Vec<i32> = request.detail.iter().map(|d| d.id_produk).collect();
+        let produk = TransaksiRepository::get_produk(&mut tx, &product_ids).await?;
+        if let Some(detail) = request.detail.iter().find(|d| !produk.contains_key(&d.id_produk)) {
+            return Err(TransaksiError::ProdukTidakDitemukan(detail.id_produk));
+        }
+
+        let kebijakan = KebijakanPajak::from_env();
+        let detail = TransaksiService::hitung_harga(&mut tx, &transaksi_request, &produk, kebijakan).await?
+            .iter()
+            .map(|d| DetailPenawaran::dari_detail(d, produk[&d.id_produk].nama.clone()))
+            .collect();
+
+        let mut penawaran = Penawaran::new(
+            request.id_pelanggan,
+            request.nama_pelanggan.clone(),
+            request.catatan.clone().filter(|c| !c.trim().is_empty()),
+            now,
+            request.berlaku_sampai,
+        );
+        penawaran.terapkan_detail(kebijakan, detail);
+        let created = PenawaranRepository::create_penawaran(&mut tx, &penawaran).await?;
+
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    pub async fn get_penawaran_by_id(db: Pool<Any>, id: i32) -> Result<Penawaran, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        PenawaranRepository::get_penawaran_by_id(&mut db_connection, id).await
+    }
+
+    pub async fn terima_penawaran(db: Pool<Any>, id: i32) -> Result<Penawaran, TransaksiError> {
+        Self::ubah_status(db, id, |penawaran| penawaran.terima(Utc::now())).await
+    }
+
+    pub async fn tolak_penawaran(db: Pool<Any>, id: i32) -> Result<Penawaran, TransaksiError> {
+        Self::ubah_status(db, id, Penawaran::tolak).await
+    }
+
+    async fn ubah_status(
+        db: Pool<Any>,
+        id: i32,
+        aksi: impl FnOnce(&mut Penawaran) -> Result<(), String>,
+    ) -> Result<Penawaran, TransaksiError> {
+        let mut db_connection = db.acquire().await?;
+        let mut penawaran = PenawaranRepository::get_penawaran_by_id(&mut db_connection, id).await?;
+        let dari = penawaran.status;
+        aksi(&mut penawaran).map_err(TransaksiError::Validasi)?;
+
+        if !PenawaranRepository::ubah_status(&mut db_connection, id, dari, penawaran.status).await? {
+            return Err(TransaksiError::Validasi("Status penawaran sudah diubah oleh permintaan lain".to_string()));
+        }
+        Ok(penawaran)
+    }
+
+    // Penawaran yang diterima dan masih berlaku menjadi transaksi baru yang merujuk penawarannya.
+    // Stok divalidasi dan dikurangi seperti transaksi biasa. Tanpa `harga_ulang`, harga, diskon dan
+    // PPN dari penawaran dipakai apa adanya; dengan `harga_ulang`, dihitung dari katalog saat ini.
+    // Status penawaran dan transaksi baru ditulis dalam satu transaksi database.
+    pub async fn konversi_penawaran(
+        db: Pool<Any>,
+        id: i32,
+        request: &KonversiPenawaranRequest,
+    ) -> Result<Transaksi, TransaksiError> {
+        let expires_at = ReservasiPolicy::from_env().expires_at(Utc::now());
+
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+
+        let mut penawaran = PenawaranRepository::get_penawaran_by_id(&mut tx, id).await?;
+        penawaran.konversi(Utc::now()).map_err(TransaksiError::Validasi)?;
+        if !PenawaranRepository::ubah_status(&mut tx, id, StatusPenawaran::Diterima, StatusPenawaran::Dikonversi).await? {
+            return Err(TransaksiError::Validasi("Penawaran sudah dikonversi oleh permintaan lain".to_string()));
+        }
+
+        let transaksi_request = CreateTransaksiRequest {
+            id_pelanggan: penawaran.id_pelanggan,
+            nama_pelanggan: penawaran.nama_pelanggan.clone(),
+            catatan: request.catatan.clone().filter(|c| !c.trim().is_empty()).or(penawaran.catatan.clone()),
+            detail_transaksi: penawaran.detail.iter().map(|d| CreateDetailTransaksiRequest {
+                id_produk: d.id_produk,
+                nama_produk: d.nama_produk.clone(),
+                harga_satuan: d.harga_satuan,
+                jumlah: d.jumlah,
+            }).collect(),
+        };
+        let produk = TransaksiService::reserve_product_stock(&mut tx, &transaksi_request.detail_transaksi).await?;
+
+        let (kebijakan, details) = if request.harga_ulang {
+            let kebijakan = KebijakanPajak::from_env();
+            (kebijakan, TransaksiService::hitung_harga(&mut tx, &transaksi_request, &produk, kebijakan).await?)
+        } else {
+            let details: Vec<DetailTransaksi> = penawaran.detail.iter().map(|d| d.to_detail_transaksi(0)).collect();
+            (penawaran.kebijakan_pajak(), details)
+        };
+
+        let mut transaksi = Transaksi::new(
+            transaksi_request.id_pelanggan,
+            transaksi_request.nama_pelanggan.clone(),
+            Rupiah::ZERO,
+            transaksi_request.catatan.clone(),
+        );
+        transaksi.id_penawaran = Some(penawaran.id);
+        let created_transaksi = TransaksiService::simpan_transaksi(&mut tx, &mut transaksi, kebijakan, details, expires_at).await?;
+
+        tx.commit().await?;
+        Ok(created_transaksi)
+    }
+
+    // Dokumen penawaran memakai renderer struk; nomor QUO-xxxxxx dan tanpa bagian pembayaran
+    pub fn dokumen(penawaran: &Penawaran, store: StoreProfile) -> Receipt {
+        let document = ReceiptDocument::Penawaran;
+        Receipt {
+            nomor: document.number(penawaran.id),
+            document,
+            store,
+            id_transaksi: penawaran.id,
+            tanggal_transaksi: penawaran.tanggal_penawaran.format("%Y-%m-%d %H:%M:%S").to_string(),
+            berlaku_sampai: Some(penawaran.berlaku_sampai.format("%Y-%m-%d %H:%M:%S").to_string()),
+            nama_pelanggan: penawaran.nama_pelanggan.clone(),
+            catatan: penawaran.catatan.clone(),
+            lines: penawaran.detail.iter()
+                .map(|detail| ReceiptLine {
+                    nama_produk: detail.nama_produk.clone(),
+                    jumlah: detail.jumlah,
+                    harga_satuan: detail.harga_satuan,
+                    diskon: detail.diskon,
+                    subtotal: detail.subtotal,
+                })
+                .collect(),
+            total: penawaran.total_harga,
+            tax: TaxBreakdown {
+                rate_bp: penawaran.tarif_pajak_bp,
+                dpp: penawaran.total_dpp,
+                ppn: penawaran.total_ppn,
+            },
+            payments: Vec::new(),
+            paid: Rupiah::ZERO,
+            refunded: Rupiah::ZERO,
+            outstanding: Rupiah::ZERO,
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use chrono::{NaiveDate, Utc};
+use sqlx::{Any, Acquire, Pool};
+
+use crate::manajemen_pelanggan::repository::pelanggan::PelangganRepository;
+use crate::transaksi_penjualan::dto::pengiriman_request::{
+    CreatePengirimanRequest, JadwalPengirimanRequest, UpdateStatusPengirimanRequest,
+};
+use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+use crate::transaksi_penjualan::model::pengiriman::{Pengiriman, StatusPengiriman};
+use crate::transaksi_penjualan::repository::pengiriman::PengirimanRepository;
+use crate::transaksi_penjualan::repository::retur_penjualan::ReturPenjualanRepository;
+use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+use crate::transaksi_penjualan::service::transaksi::TransaksiError;
+
+pub struct PengirimanService;
+
+impl PengirimanService {
+    // Pengiriman hanya untuk transaksi selesai. Alamat kosong diambil dari data pelanggan;
+    // jumlah per baris dibatasi sisa yang belum masuk pengiriman lain yang tidak gagal.
+    pub async fn create_pengiriman(
+        db: Pool<Any>,
+        id_transaksi: i32,
+        request: &CreatePengirimanRequest,
+    ) -> Result<Pengiriman, TransaksiError> {
+        let now = Utc::now();
+        request.validate(now.date_naive()).map_err(TransaksiError::Validasi)?;
+
+        let transaksi = TransaksiRepository::get_transaksi_by_id(db.acquire().await?, id_transaksi).await?;
+        if transaksi.status != StatusTransaksi::Selesai {
+            return Err(TransaksiError::Validasi("Hanya transaksi yang sudah selesai yang dapat dikirim".to_string()));
+        }
+
+        let alamat = match request.alamat.clone().filter(|a| !a.trim().is_empty()) {
+            Some(alamat) => alamat,
+            None => match PelangganRepository::get_pelanggan_by_id(db.acquire().await?, transaksi.id_pelanggan).await {
+                Ok(pelanggan) => pelanggan.alamat,
+                Err(sqlx::Error::RowNotFound) => String::new(),
+                Err(e) => return Err(e.into()),
+            },
+        };
+        if alamat.trim().is_empty() {
+            return Err(TransaksiError::Validasi("Alamat pengiriman harus diisi".to_string()));
+        }
+
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+
+        // Kunci yang sama dengan retur, agar pengiriman dan retur bersamaan tidak melebihi jumlah terjual
+        if !ReturPenjualanRepository::kunci_transaksi_selesai(&mut tx, id_transaksi).await? {
+            return Err(TransaksiError::Validasi("Hanya transaksi yang sudah selesai yang dapat dikirim".to_string()));
+        }
+
+        let details = TransaksiRepository::get_detail_by_transaksi_id(&mut tx, id_transaksi).await?;
+        let sudah_dijadwalkan = PengirimanRepository::jumlah_dijadwalkan(&mut tx, id_transaksi).await?;
+        let sudah_diretur = ReturPenjualanRepository::jumlah_diretur(&mut tx, id_transaksi).await?;
+        let id_produk: Vec<i32> = details.iter().map(|d| d.id_produk).collect();
+        let produk = TransaksiRepository::get_produk(&mut tx, &id_produk).await?;
+        let detail = request
+            .to_detail_pengiriman(&details, &sudah_dijadwalkan, &sudah_diretur, &produk)
+            .map_err(TransaksiError::Validasi)?;
+
+        let pengiriman = PengirimanRepository::create_pengiriman(&mut tx, &Pengiriman {
+            id: 0,
+            id_transaksi,
+            nama_pelanggan: transaksi.nama_pelanggan,
+            alamat: alamat.trim().to_string(),
+            tanggal_kirim: request.tanggal_kirim,
+            kendaraan: request.kendaraan.clone().filter(|k| !k.trim().is_empty()),
+            pengemudi: request.pengemudi.clone().filter(|p| !p.trim().is_empty()),
+            status: StatusPengiriman::Dijadwalkan,
+            catatan: request.catatan.clone().filter(|c| !c.trim().is_empty()),
+            bukti_pengiriman: None,
+            penerima: None,
+            created_at: now,
+            updated_at: now,
+            detail,
+        }).await?;
+
+        tx.commit().await?;
+        Ok(pengiriman)
+    }
+
+    pub async fn get_pengiriman_by_id(db: Pool<Any>, id: i32) -> Result<Pengiriman, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        PengirimanRepository::get_pengiriman_by_id(&mut db_connection, id).await
+    }
+
+    pub async fn get_pengiriman_by_transaksi(db: Pool<Any>, id_transaksi: i32) -> Result<Vec<Pengiriman>, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        PengirimanRepository::get_pengiriman_by_transaksi(&mut db_connection, id_transaksi).await
+    }
+
+    pub async fn get_jadwal(db: Pool<Any>, tanggal: NaiveDate) -> Result<Vec<Pengiriman>, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        PengirimanRepository::get_pengiriman_by_tanggal(&mut db_connection, tanggal).await
+    }
+
+    pub async fn jadwal_ulang(
+        db: Pool<Any>,
+        id: i32,
+        request: &JadwalPengirimanRequest,
+    ) -> Result<Pengiriman, TransaksiError> {
+        let mut db_connection = db.acquire().await?;
+        let mut pengiriman = PengirimanRepository::get_pengiriman_by_id(&mut db_connection, id).await?;
+        if pengiriman.status != StatusPengiriman::Dijadwalkan {
+            return Err(TransaksiError::Validasi("Hanya pengiriman yang belum berangkat yang dapat dijadwalkan ulang".to_string()));
+        }
+
+        let now = Utc::now();
+        if let Some(tanggal) = request.tanggal_kirim {
+            if tanggal < now.date_naive() {
+                return Err(TransaksiError::Validasi("Tanggal kirim tidak boleh di masa lalu".to_string()));
+            }
+            pengiriman.tanggal_kirim = tanggal;
+        }
+        if let Some(alamat) = request.alamat.as_deref().map(str::trim).filter(|a| !a.is_empty()) {
+            pengiriman.alamat = alamat.to_string();
+        }
+        if let Some(kendaraan) = request.kendaraan.clone().filter(|k| !k.trim().is_empty()) {
+            pengiriman.kendaraan = Some(kendaraan);
+        }
+        if let Some(pengemudi) = request.pengemudi.clone().filter(|p| !p.trim().is_empty()) {
+            pengiriman.pengemudi = Some(pengemudi);
+        }
+        pengiriman.updated_at = now;
+
+        if !PengirimanRepository::update_jadwal(&mut db_connection, &pengiriman).await? {
+            return Err(TransaksiError::Validasi("Hanya pengiriman yang belum berangkat yang dapat dijadwalkan ulang".to_string()));
+        }
+        Ok(PengirimanRepository::get_pengiriman_by_id(&mut db_connection, id).await?)
+    }
+
+    pub async fn update_status(
+        db: Pool<Any>,
+        id: i32,
+        request: &UpdateStatusPengirimanRequest,
+    ) -> Result<Pengiriman, TransaksiError> {
+        let mut db_connection = db.acquire().await?;
+        let mut pengiriman = PengirimanRepository::get_pengiriman_by_id(&mut db_connection, id).await?;
+        let dari = pengiriman.status;
+
+        pengiriman
+            .ubah_status(request.status, request.bukti_pengiriman.clone(), request.penerima.clone())
+            .map_err(TransaksiError::Validasi)?;
+
+        if !PengirimanRepository::ubah_status_jika(&mut db_connection, &pengiriman, dari).await? {
+            return Err(TransaksiError::Validasi(format!(
+                "Status pengiriman sudah berubah dari {}, muat ulang data pengiriman",
+                dari
+            )));
+        }
+        Ok(pengiriman)
+    }
+}
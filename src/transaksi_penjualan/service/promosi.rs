@@ -0,0 +1,33 @@
+use sqlx::{Any, Pool};
+
+use crate::transaksi_penjualan::dto::promosi_request::CreatePromosiRequest;
+use crate::transaksi_penjualan::model::promosi::Promosi;
+use crate::transaksi_penjualan::repository::promosi::PromosiRepository;
+use crate::transaksi_penjualan::service::transaksi::TransaksiError;
+
+pub struct PromosiService;
+
+impl PromosiService {
+    pub async fn create_promosi(db: Pool<Any>, request: &CreatePromosiRequest) -> Result<Promosi, TransaksiError> {
+        let promosi = request.to_promosi();
+        promosi.validate().map_err(TransaksiError::Validasi)?;
+
+        let mut db_connection = db.acquire().await?;
+        Ok(PromosiRepository::create_promosi(&mut db_connection, &promosi).await?)
+    }
+
+    pub async fn get_all_promosi(db: Pool<Any>) -> Result<Vec<Promosi>, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        PromosiRepository::get_all_promosi(&mut db_connection).await
+    }
+
+    // Transaksi yang sudah ada tetap menyimpan diskonnya; promosi hanya tidak dipakai lagi
+    pub async fn nonaktifkan_promosi(db: Pool<Any>, id: i32) -> Result<(), sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        if PromosiRepository::nonaktifkan_promosi(&mut db_connection, id).await? {
+            Ok(())
+        } else {
+            Err(sqlx::Error::RowNotFound)
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use rocket::State;
+use sqlx::{Any, Pool};
+
+use crate::manajemen_pembayaran::model::payment::Payment;
+use crate::manajemen_pembayaran::service::payment_service::PaymentService;
+use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+use crate::transaksi_penjualan::model::receipt::{Receipt, ReceiptDocument, ReceiptLine, ReceiptPayment, StoreProfile, TaxBreakdown};
+use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+
+#[derive(Debug)]
+pub enum ReceiptError {
+    NotFound(String),
+    NotCompleted(String),
+    DatabaseError(String),
+}
+
+pub struct ReceiptService;
+
+impl ReceiptService {
+    // Struk/faktur hanya untuk transaksi yang sudah selesai
+    pub async fn build_receipt(
+        db: &State<Pool<Any>>,
+        id_transaksi: i32,
+        document: ReceiptDocument,
+        store: StoreProfile,
+    ) -> Result<Receipt, ReceiptError> {
+        let connection = db.acquire().await.map_err(|e| ReceiptError::DatabaseError(e.to_string()))?;
+        let transaksi = TransaksiRepository::get_transaksi_by_id(connection, id_transaksi).await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => ReceiptError::NotFound(format!("Transaksi {} tidak ditemukan", id_transaksi)),
+                _ => ReceiptError::DatabaseError(e.to_string()),
+            })?;
+
+        if transaksi.status != StatusTransaksi::Selesai {
+            return Err(ReceiptError::NotCompleted(format!(
+                "Transaksi {} belum selesai (status {})", id_transaksi, transaksi.status.to_string()
+            )));
+        }
+
+        let mut connection = db.acquire().await.map_err(|e| ReceiptError::DatabaseError(e.to_string()))?;
+        let details = TransaksiRepository::get_detail_by_transaksi_id(&mut connection, id_transaksi).await
+            .map_err(|e| ReceiptError::DatabaseError(e.to_string()))?;
+
+        let id_produk: Vec<i32> = details.iter().map(|d| d.id_produk).collect();
+        let produk = TransaksiRepository::get_produk(&mut connection, &id_produk).await
+            .map_err(|e| ReceiptError::DatabaseError(e.to_string()))?;
+        drop(connection);
+
+        let lines = details.iter()
+            .map(|detail| ReceiptLine {
+                nama_produk: produk.get(&detail.id_produk)
+                    .map(|p| p.nama.clone())
+                    .unwrap_or_else(|| format!("Produk #{}", detail.id_produk)),
+                jumlah: detail.jumlah,
+                harga_satuan: detail.harga_satuan,
+                diskon: detail.diskon,
+                subtotal: detail.subtotal,
+            })
+            .collect();
+
+        let summary = PaymentService::new().get_transaksi_payment_summary(db, id_transaksi).await
+            .map_err(|e| ReceiptError::DatabaseError(format!("{:?}", e)))?;
+
+        Ok(Receipt {
+            nomor: document.number(transaksi.id),
+            document,
+            tax: TaxBreakdown {
+                rate_bp: transaksi.tarif_pajak_bp,
+                dpp: transaksi.total_dpp,
+                ppn: transaksi.total_ppn,
+            },
+            store,
+            id_transaksi: transaksi.id,
+            tanggal_transaksi: transaksi.tanggal_transaksi,
+            berlaku_sampai: None,
+            nama_pelanggan: transaksi.nama_pelanggan,
+            catatan: transaksi.catatan,
+            lines,
+            total: transaksi.total_harga,
+            payments: summary.payments.iter().flat_map(Self::receipt_payments).collect(),
+            paid: summary.paid,
+            refunded: summary.refunded,
+            outstanding: summary.outstanding,
+        })
+    }
+
+    // Pembayaran split ditampilkan per baris tender; cicilan dan pembayaran lain dengan nominal yang sudah masuk
+    pub fn receipt_payments(payment: &Payment) -> Vec<ReceiptPayment> {
+        let amount_paid = payment.amount_paid();
+        if !amount_paid.is_positive() {
+            return Vec::new();
+        }
+
+        if payment.tender_lines.is_empty() || payment.installments_total().is_positive() {
+            return vec![ReceiptPayment {
+                method: payment.method.clone(),
+                amount: amount_paid,
+                reference: None,
+            }];
+        }
+
+        payment.tender_lines.iter()
+            .map(|line| ReceiptPayment {
+                method: line.method.clone(),
+                amount: line.amount,
+                reference: line.reference_number.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Utc;
+    use crate::common::money::Rupiah;
+    use crate::manajemen_pembayaran::enums::payment_status::PaymentStatus;
+    use crate::manajemen_pembayaran::model::payment::{Installment, PaymentMethod, TenderLine};
+
+    fn payment(status: PaymentStatus) -> Payment {
+        Payment {
+            id: "PMT-1".to_string(),
+            transaction_id: "1".to_string(),
+            amount: Rupiah::from_rupiah(300_000),
+            method: PaymentMethod::Cash,
+            status,
+            payment_date: Utc::now(),
+            installments: Vec::new(),
+            tender_lines: Vec::new(),
+            due_date: None,
+            late_fee_total: Rupiah::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_receipt_payments_split_tender() {
+        let mut split = payment(PaymentStatus::Paid);
+        split.tender_lines = vec![
+            TenderLine { id: "TL-1".to_string(), payment_id: "PMT-1".to_string(), method: PaymentMethod::Cash, amount: Rupiah::from_rupiah(100_000), reference_number: None },
+            TenderLine { id: "TL-2".to_string(), payment_id: "PMT-1".to_string(), method: PaymentMethod::CreditCard, amount: Rupiah::from_rupiah(200_000), reference_number: Some("APPR-9".to_string()) },
+        ];
+
+        let lines = ReceiptService::receipt_payments(&split);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].method, PaymentMethod::CreditCard);
+        assert_eq!(lines[1].reference.as_deref(), Some("APPR-9"));
+    }
+
+    #[test]
+    fn test_receipt_payments_installments_and_pending() {
+        let mut installment = payment(PaymentStatus::Installment);
+        installment.installments = vec![Installment {
+            id: "INST-1".to_string(),
+            payment_id: "PMT-1".to_string(),
+            amount: Rupiah::from_rupiah(120_000),
+            payment_date: Utc::now(),
+        }];
+
+        let lines = ReceiptService::receipt_payments(&installment);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].amount, Rupiah::from_rupiah(120_000));
+
+        assert!(ReceiptService::receipt_payments(&payment(PaymentStatus::Pending)).is_empty());
+    }
+}
@@ -0,0 +1,20 @@
+use chrono::Utc;
+use rocket::fairing::AdHoc;
+
+use crate::common::job::interval_job;
+use crate::transaksi_penjualan::service::reservasi_stok::ReservasiStokService;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+pub fn stage() -> AdHoc {
+    interval_job("Stock Reservation Sweeper", "STOCK_RESERVATION_SWEEP_INTERVAL_SECS", DEFAULT_INTERVAL_SECS, |db| async move {
+        match ReservasiStokService::release_expired(&db, Utc::now()).await {
+            Ok(released) if !released.is_empty() => println!(
+                "[INFO] Stock reservation sweeper cancelled {} expired transaksi: {:?}",
+                released.len(), released
+            ),
+            Ok(_) => {}
+            Err(err) => eprintln!("[ERROR] Stock reservation sweeper failed: {:?}", err),
+        }
+    })
+}
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Any, Acquire, Pool};
+
+use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+use crate::transaksi_penjualan::model::riwayat_status::PerubahanStatus;
+use crate::transaksi_penjualan::repository::reservasi_stok::ReservasiStokRepository;
+use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+use crate::transaksi_penjualan::service::transaksi::TransaksiService;
+
+pub struct ReservasiStokService;
+
+impl ReservasiStokService {
+    // Batalkan keranjang yang reservasinya sudah lewat waktu dan kembalikan stoknya.
+    // Tiap transaksi dilepas dalam transaksi database sendiri; transaksi yang sudah diselesaikan
+    // atau dibatalkan kasir di antara pencarian dan pelepasan dilewati. Transaksi dikunci lebih dulu
+    // seperti saat pembayaran dibuat, jadi pembayaran yang masuk di antaranya juga mencegah pembatalan.
+    pub async fn release_expired(db: &Pool<Any>, now: DateTime<Utc>) -> Result<Vec<i32>, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        let expired = ReservasiStokRepository::get_transaksi_kedaluwarsa(&mut db_connection, now).await?;
+
+        let perubahan = PerubahanStatus::sistem("Reservasi stok kedaluwarsa");
+        let mut released = Vec::new();
+        for id_transaksi in expired {
+            let mut tx = (*db_connection).begin().await?;
+            TransaksiRepository::kunci_transaksi(&mut tx, id_transaksi).await?;
+            if ReservasiStokRepository::punya_pembayaran_aktif(&mut tx, id_transaksi).await? {
+                continue;
+            }
+            match TransaksiService::release_transaksi(&mut tx, id_transaksi, &StatusTransaksi::MasihDiproses, &perubahan).await {
+                Ok(()) => {
+                    tx.commit().await?;
+                    released.push(id_transaksi);
+                }
+                Err(sqlx::Error::RowNotFound) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(released)
+    }
+}
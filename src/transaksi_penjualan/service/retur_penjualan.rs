@@ -0,0 +1,172 @@
+use chrono::Utc;
+use rocket::State;
+use sqlx::{Any, Acquire, AnyConnection, Pool};
+
+use crate::auth::guards::auth::AuthenticatedUser;
+use crate::common::money::Rupiah;
+use crate::manajemen_pembayaran::enums::refund_reason::RefundReason;
+use crate::manajemen_pembayaran::model::payment::Payment;
+use crate::manajemen_pembayaran::model::payment_query::PaymentQuery;
+use crate::manajemen_pembayaran::model::refund::Refund;
+use crate::manajemen_pembayaran::repository::payment_repository::PembayaranRepository;
+use crate::manajemen_pembayaran::repository::refund_repository::RefundRepository;
+use crate::manajemen_pembayaran::service::cash_drawer_service::CashDrawerService;
+use crate::manajemen_pembayaran::service::payment_service::PaymentError;
+use crate::manajemen_pembayaran::service::refund_service::{NewRefund, RefundService};
+use crate::transaksi_penjualan::dto::retur_request::CreateReturRequest;
+use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
+use crate::transaksi_penjualan::model::retur_penjualan::{MetodePengembalian, ReturPenjualan, SaldoKreditToko};
+use crate::transaksi_penjualan::repository::retur_penjualan::ReturPenjualanRepository;
+use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
+use crate::transaksi_penjualan::service::transaksi::TransaksiError;
+
+pub struct ReturPenjualanService;
+
+impl ReturPenjualanService {
+    // Retur hanya untuk transaksi selesai. Dokumen retur, refund, kredit toko dan pengembalian stok
+    // ditulis dalam satu transaksi database bersama tautan laci kas kasir; transaksi asal dan detailnya tidak diubah.
+    pub async fn create_retur(
+        db: &State<Pool<Any>>,
+        kasir: Option<&AuthenticatedUser>,
+        id_transaksi: i32,
+        request: &CreateReturRequest,
+    ) -> Result<ReturPenjualan, TransaksiError> {
+        let alasan = request.validate().map_err(TransaksiError::Validasi)?;
+
+        let transaksi = TransaksiRepository::get_transaksi_by_id(db.acquire().await?, id_transaksi).await?;
+        if transaksi.status != StatusTransaksi::Selesai {
+            return Err(TransaksiError::Validasi("Hanya transaksi yang sudah selesai yang dapat diretur".to_string()));
+        }
+
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+
+        if !ReturPenjualanRepository::kunci_transaksi_selesai(&mut tx, id_transaksi).await? {
+            return Err(TransaksiError::Validasi("Hanya transaksi yang sudah selesai yang dapat diretur".to_string()));
+        }
+
+        let details = TransaksiRepository::get_detail_by_transaksi_id(&mut tx, id_transaksi).await?;
+        let sudah_diretur = ReturPenjualanRepository::jumlah_diretur(&mut tx, id_transaksi).await?;
+        let detail = request.to_detail_retur(&details, &sudah_diretur).map_err(TransaksiError::Validasi)?;
+        let total_pengembalian = CreateReturRequest::total_pengembalian(&detail);
+
+        let refunds = match request.metode_pengembalian {
+            MetodePengembalian::Refund => {
+                let payments = Self::refundable_payments(&mut tx, id_transaksi).await?;
+                Self::plan_refunds(&payments, &alasan, total_pengembalian, id_transaksi)?
+            }
+            MetodePengembalian::KreditToko => Vec::new(),
+        };
+        for refund in &refunds {
+            RefundRepository::insert(&mut tx, refund).await?;
+            CashDrawerService::new().record_refund(&mut tx, kasir, refund, Utc::now()).await?;
+        }
+
+        let retur = ReturPenjualanRepository::create_retur(&mut tx, &ReturPenjualan {
+            id: 0,
+            id_transaksi,
+            id_pelanggan: transaksi.id_pelanggan,
+            tanggal_retur: Utc::now(),
+            alasan,
+            metode_pengembalian: request.metode_pengembalian,
+            total_pengembalian,
+            catatan: request.catatan.clone().filter(|c| !c.trim().is_empty()),
+            detail,
+            id_refund: refunds.iter().map(|r| r.id.clone()).collect(),
+        }).await?;
+
+        for baris in retur.detail.iter().filter(|d| d.dapat_dijual_kembali) {
+            TransaksiRepository::tambah_stok(&mut tx, baris.id_produk, baris.jumlah).await?;
+        }
+
+        if request.metode_pengembalian == MetodePengembalian::KreditToko {
+            ReturPenjualanRepository::tambah_kredit_toko(
+                &mut tx,
+                retur.id_pelanggan,
+                retur.id,
+                total_pengembalian,
+                &format!("Retur #{} transaksi #{}", retur.id, id_transaksi),
+            ).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(retur)
+    }
+
+    pub async fn get_retur_by_transaksi(db: Pool<Any>, id_transaksi: i32) -> Result<Vec<ReturPenjualan>, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        ReturPenjualanRepository::get_retur_by_transaksi(&mut db_connection, id_transaksi).await
+    }
+
+    pub async fn get_saldo_kredit_toko(db: Pool<Any>, id_pelanggan: i32) -> Result<SaldoKreditToko, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        let saldo = ReturPenjualanRepository::saldo_kredit_toko(&mut db_connection, id_pelanggan).await?;
+        Ok(SaldoKreditToko { id_pelanggan, saldo })
+    }
+
+    // Setiap pembayaran dikunci dengan kunci yang sama seperti refund biasa, lalu dibaca ulang
+    // bersama refund sebelumnya, agar refund bersamaan tidak melebihi jumlah yang sudah dibayar
+    async fn refundable_payments(
+        tx: &mut AnyConnection,
+        id_transaksi: i32,
+    ) -> Result<Vec<(Payment, Vec<Refund>)>, TransaksiError> {
+        let payments = PembayaranRepository::find_matching(tx, &PaymentQuery::by_transaction_id(&id_transaksi.to_string())).await?;
+
+        let mut result = Vec::with_capacity(payments.len());
+        for payment in payments {
+            let payment = PembayaranRepository::lock_payment(tx, &payment.id).await?;
+            let refunds = RefundRepository::find_for_payment(tx, &payment.id).await?;
+            result.push((payment, refunds));
+        }
+        Ok(result)
+    }
+
+    // Nilai retur dibagi ke pembayaran transaksi secara berurutan, sebanyak yang masih bisa direfund
+    fn plan_refunds(
+        payments: &[(Payment, Vec<Refund>)],
+        alasan: &RefundReason,
+        total: Rupiah,
+        id_transaksi: i32,
+    ) -> Result<Vec<Refund>, TransaksiError> {
+        let service = RefundService::new();
+        let mut sisa = total;
+        let mut refunds = Vec::new();
+
+        for (payment, existing) in payments {
+            if !sisa.is_positive() {
+                break;
+            }
+            let amount = service.refundable_amount(payment, existing).min(sisa);
+            if !amount.is_positive() {
+                continue;
+            }
+
+            refunds.push(service.build_refund(payment, existing, NewRefund {
+                amount,
+                reason: alasan.clone(),
+                method: None,
+                notes: Some(format!("Retur transaksi #{}", id_transaksi)),
+            }).map_err(Self::payment_error)?);
+            sisa -= amount;
+        }
+
+        if sisa.is_positive() {
+            return Err(TransaksiError::Validasi(format!(
+                "Dana yang dapat direfund tidak mencukupi, kurang {}. Gunakan kredit toko",
+                sisa
+            )));
+        }
+        Ok(refunds)
+    }
+
+    fn payment_error(error: PaymentError) -> TransaksiError {
+        match error {
+            PaymentError::InvalidInput(message) | PaymentError::InvalidTransition(message) => TransaksiError::Validasi(message),
+            PaymentError::NotFound(_) => TransaksiError::Database(sqlx::Error::RowNotFound),
+            PaymentError::DatabaseError(message) | PaymentError::Unauthorized(message) => {
+                TransaksiError::Database(sqlx::Error::Protocol(message))
+            }
+        }
+    }
+}
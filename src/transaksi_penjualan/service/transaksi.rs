@@ -1,11 +1,56 @@
-use sqlx::{Any, Pool};
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Utc};
+use sqlx::{Any, AnyConnection, Acquire, Pool};
+use crate::common::money::Rupiah;
+use crate::transaksi_penjualan::model::produk_stok::ProdukStok;
 use crate::transaksi_penjualan::model::transaksi::Transaksi;
-use crate::transaksi_penjualan::model::detail_transaksi::DetailTransaksi;
+use crate::transaksi_penjualan::model::detail_transaksi::{DetailTransaksi, MAX_JUMLAH};
+use crate::transaksi_penjualan::model::pajak::{KebijakanPajak, PajakBaris};
+use crate::transaksi_penjualan::model::promosi::{BarisPromosi, MesinPromosi};
+use crate::transaksi_penjualan::model::reservasi_stok::{ReservasiPolicy, ReservasiStok};
+use crate::transaksi_penjualan::model::riwayat_status::{PerubahanStatus, RiwayatStatusTransaksi};
+use crate::transaksi_penjualan::patterns::state::transaksi_state::{StateAction, TransaksiStateFactory};
+use crate::transaksi_penjualan::repository::promosi::PromosiRepository;
+use crate::transaksi_penjualan::repository::reservasi_stok::ReservasiStokRepository;
+use crate::transaksi_penjualan::repository::riwayat_status::RiwayatStatusRepository;
 use crate::transaksi_penjualan::repository::transaksi::TransaksiRepository;
 use crate::transaksi_penjualan::enums::status_transaksi::StatusTransaksi;
 
 pub struct TransaksiService;
 
+#[derive(Debug)]
+pub enum TransaksiError {
+    Validasi(String),
+    ProdukTidakDitemukan(i32),
+    StokTidakCukup {
+        id_produk: i32,
+        nama_produk: String,
+        tersedia: u32,
+        diminta: u32,
+    },
+    Database(sqlx::Error),
+}
+
+impl TransaksiError {
+    pub fn message(&self) -> String {
+        match self {
+            TransaksiError::Validasi(message) => message.clone(),
+            TransaksiError::ProdukTidakDitemukan(id_produk) => format!("Produk dengan ID {} tidak ditemukan", id_produk),
+            TransaksiError::StokTidakCukup { nama_produk, tersedia, diminta, .. } => format!(
+                "Stok produk '{}' tidak mencukupi. Tersedia: {}, Diminta: {}",
+                nama_produk, tersedia, diminta
+            ),
+            TransaksiError::Database(e) => e.to_string(),
+        }
+    }
+}
+
+impl From<sqlx::Error> for TransaksiError {
+    fn from(error: sqlx::Error) -> Self {
+        TransaksiError::Database(error)
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct TransaksiSearchParams {
@@ -42,108 +87,188 @@ impl TransaksiSearchResult {
 
 impl TransaksiService {
     pub async fn create_transaksi(db: Pool<Any>, transaksi: &Transaksi) -> Result<Transaksi, sqlx::Error> {
-        let db_connection = db.acquire().await?;
-        TransaksiRepository::create_transaksi(db_connection, transaksi).await
+        let mut db_connection = db.acquire().await?;
+        TransaksiRepository::create_transaksi(&mut db_connection, transaksi).await
     }
 
+    // Harga dan stok selalu diambil dari tabel produk; harga_satuan dari klien diabaikan.
+    // Header, detail, reservasi dan pengurangan stok ditulis dalam satu transaksi database, jadi
+    // kegagalan di tengah jalan tidak meninggalkan transaksi setengah jadi (rollback saat `tx` di-drop).
     pub async fn create_transaksi_with_details(
         db: Pool<Any>, 
         request: &crate::transaksi_penjualan::dto::transaksi_request::CreateTransaksiRequest
-    ) -> Result<Transaksi, sqlx::Error> {
-        if let Err(_err_msg) = request.validate() {
-            return Err(sqlx::Error::RowNotFound);
-        }
+    ) -> Result<Transaksi, TransaksiError> {
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
 
-        if let Err(_err_msg) = Self::validate_product_stock(&request.detail_transaksi).await {
-            return Err(sqlx::Error::RowNotFound);
-        }
+        let created_transaksi = Self::buat_transaksi(&mut tx, request).await?;
 
-        let product_prices = Self::fetch_product_prices(&request.detail_transaksi).await?;
-        let total_harga = request.calculate_total(&product_prices);
+        tx.commit().await?;
+        Ok(created_transaksi)
+    }
 
-        let transaksi = Transaksi::new(
+    // Isi `create_transaksi_with_details` tanpa commit, untuk pemanggil yang menulis data lain
+    // dalam transaksi database yang sama (mis. konversi keranjang tertahan)
+    pub(crate) async fn buat_transaksi(
+        db: &mut AnyConnection,
+        request: &crate::transaksi_penjualan::dto::transaksi_request::CreateTransaksiRequest
+    ) -> Result<Transaksi, TransaksiError> {
+        request.validate().map_err(TransaksiError::Validasi)?;
+        let expires_at = ReservasiPolicy::from_env().expires_at(Utc::now());
+
+        let produk = Self::reserve_product_stock(db, &request.detail_transaksi).await?;
+        let kebijakan = KebijakanPajak::from_env();
+        let details = Self::hitung_harga(db, request, &produk, kebijakan).await?;
+
+        let mut transaksi = Transaksi::new(
             request.id_pelanggan,
             request.nama_pelanggan.clone(),
-            total_harga,
+            Rupiah::ZERO,
             request.catatan.clone(),
         );
+        Ok(Self::simpan_transaksi(db, &mut transaksi, kebijakan, details, expires_at).await?)
+    }
 
-        let db_connection = db.acquire().await?;
-        let created_transaksi = TransaksiRepository::create_transaksi(db_connection, &transaksi).await?;
+    // Harga katalog, promosi aktif lalu PPN per baris; dipakai juga untuk penawaran harga
+    pub(crate) async fn hitung_harga(
+        db: &mut AnyConnection,
+        request: &crate::transaksi_penjualan::dto::transaksi_request::CreateTransaksiRequest,
+        produk: &HashMap<i32, ProdukStok>,
+        kebijakan: KebijakanPajak,
+    ) -> Result<Vec<DetailTransaksi>, sqlx::Error> {
+        let promosi = PromosiRepository::get_promosi_aktif(db).await?;
+        let rincian = request.calculate_total(produk, &promosi, Utc::now());
+
+        Ok(request.detail_transaksi.iter().zip(rincian.diskon_per_baris)
+            .map(|(detail_request, diskon)| {
+                let produk = &produk[&detail_request.id_produk];
+                let mut detail = detail_request.to_detail_transaksi(0, produk.harga);
+                detail.terapkan_diskon(diskon);
+                detail.terapkan_pajak(kebijakan.hitung(detail.subtotal, produk.kategori_pajak));
+                detail
+            })
+            .collect())
+    }
 
-        for detail_request in &request.detail_transaksi {
-            let harga_satuan = product_prices.get(&detail_request.id_produk).unwrap_or(&0.0);
-            let detail = detail_request.to_detail_transaksi(created_transaksi.id, *harga_satuan);
-            
-            let db_connection = db.acquire().await?;
-            TransaksiRepository::create_detail_transaksi(db_connection, &detail).await?;
-            
-            Self::reduce_product_stock(detail_request.id_produk, detail_request.jumlah).await?;
+    // Menulis header, detail dan reservasi untuk stok yang sudah dikurangi `reserve_product_stock`
+    pub(crate) async fn simpan_transaksi(
+        db: &mut AnyConnection,
+        transaksi: &mut Transaksi,
+        kebijakan: KebijakanPajak,
+        details: Vec<DetailTransaksi>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Transaksi, sqlx::Error> {
+        let pajak: Vec<PajakBaris> = details.iter().map(Self::pajak_detail).collect();
+        transaksi.terapkan_pajak(kebijakan, &pajak);
+        let created_transaksi = TransaksiRepository::create_transaksi(db, transaksi).await?;
+
+        for mut detail in details {
+            detail.id_transaksi = created_transaksi.id;
+            let created_detail = TransaksiRepository::create_detail_transaksi(db, &detail).await?;
+            Self::save_reservation(db, &created_detail, expires_at).await?;
         }
 
         Ok(created_transaksi)
     }
 
-    async fn reduce_product_stock(product_id: i32, quantity: u32) -> Result<(), sqlx::Error> {
-        println!("Mengurangi stok produk ID {} sebanyak {}", product_id, quantity);
-        Ok(())
-    }
-
-    async fn restore_product_stock(product_id: i32, quantity: u32) -> Result<(), sqlx::Error> {
-        println!("Mengembalikan stok produk ID {} sebanyak {}", product_id, quantity);
-        Ok(())
-    }
-
-    pub async fn validate_product_stock(
+    // Stok dikurangi lebih dulu dengan UPDATE bersyarat, baru kemudian dibaca. Pengecekan dan
+    // pengurangan menjadi satu langkah, jadi dua kasir tidak bisa sama-sama lolos untuk stok terakhir:
+    // PostgreSQL mengunci baris produk sampai commit, SQLite mengambil write lock pada tulis pertama.
+    // Produk diproses urut ID agar urutan penguncian antar transaksi selalu sama.
+    pub(crate) async fn reserve_product_stock(
+        db: &mut AnyConnection,
         detail_requests: &[crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest]
-    ) -> Result<(), String> {
+    ) -> Result<HashMap<i32, ProdukStok>, TransaksiError> {
+        let mut requested: BTreeMap<i32, u32> = BTreeMap::new();
         for detail in detail_requests {
-            let available_stock = Self::get_product_stock(detail.id_produk).await;
-
-            if available_stock == 0 {
-                return Err(format!("Produk dengan ID {} tidak ditemukan atau stok habis", detail.id_produk));
-            }
+            *requested.entry(detail.id_produk).or_default() += detail.jumlah;
+        }
 
-            if detail.jumlah > available_stock {
-                return Err(format!(
-                    "Stok produk '{}' tidak mencukupi. Tersedia: {}, Diminta: {}", 
-                    detail.nama_produk, available_stock, detail.jumlah
-                ));
+        for (id_produk, jumlah) in &requested {
+            if !TransaksiRepository::kurangi_stok(db, *id_produk, *jumlah).await? {
+                let produk = Self::get_produk(db, *id_produk).await?;
+                return Err(TransaksiError::StokTidakCukup {
+                    id_produk: produk.id,
+                    nama_produk: produk.nama,
+                    tersedia: produk.stok,
+                    diminta: *jumlah,
+                });
             }
         }
 
-        Ok(())
+        let product_ids: Vec<i32> = requested.keys().copied().collect();
+        Ok(TransaksiRepository::get_produk(db, &product_ids).await?)
+    }
+
+    async fn save_reservation(db: &mut AnyConnection, detail: &DetailTransaksi, expires_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        ReservasiStokRepository::simpan(db, &ReservasiStok {
+            id_detail: detail.id,
+            id_transaksi: detail.id_transaksi,
+            id_produk: detail.id_produk,
+            jumlah: detail.jumlah,
+            expires_at,
+        }).await?;
+        ReservasiStokRepository::perpanjang(db, detail.id_transaksi, expires_at).await
     }
 
-    async fn get_product_stock(product_id: i32) -> u32 {
-        match product_id {
-            1 => 100,
-            2 => 50,
-            3 => 25,
-            4 => 15,
-            5 => 30,
-            _ => 0,
+    async fn reduce_product_stock(db: &mut AnyConnection, id_produk: i32, quantity: u32) -> Result<(), TransaksiError> {
+        if TransaksiRepository::kurangi_stok(db, id_produk, quantity).await? {
+            return Ok(());
         }
+
+        let produk = Self::get_produk(db, id_produk).await?;
+        Err(TransaksiError::StokTidakCukup {
+            id_produk,
+            nama_produk: produk.nama,
+            tersedia: produk.stok,
+            diminta: quantity,
+        })
     }
 
-    async fn fetch_product_prices(
+    async fn get_produk(db: &mut AnyConnection, product_id: i32) -> Result<ProdukStok, TransaksiError> {
+        TransaksiRepository::get_produk(db, &[product_id]).await?
+            .remove(&product_id)
+            .ok_or(TransaksiError::ProdukTidakDitemukan(product_id))
+    }
+
+    pub async fn validate_product_stock(
+        db: Pool<Any>,
         detail_requests: &[crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest]
-    ) -> Result<std::collections::HashMap<i32, f64>, sqlx::Error> {
-        let mut prices = std::collections::HashMap::new();
-        
+    ) -> Result<HashMap<i32, ProdukStok>, TransaksiError> {
+        let mut db_connection = db.acquire().await?;
+        Self::check_product_stock(&mut db_connection, detail_requests).await
+    }
+
+    // Produk yang sama boleh muncul di beberapa baris, jadi jumlahnya dicek secara total
+    async fn check_product_stock(
+        db: &mut AnyConnection,
+        detail_requests: &[crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest]
+    ) -> Result<HashMap<i32, ProdukStok>, TransaksiError> {
+        let product_ids: Vec<i32> = detail_requests.iter().map(|d| d.id_produk).collect();
+        let produk = TransaksiRepository::get_produk(db, &product_ids).await?;
+
+        let mut requested: HashMap<i32, u32> = HashMap::new();
+        for detail in detail_requests {
+            *requested.entry(detail.id_produk).or_default() += detail.jumlah;
+        }
+
         for detail in detail_requests {
-            let price = match detail.id_produk {
-                1 => 100000.0,  
-                2 => 250000.0,   
-                3 => 500000.0, 
-                4 => 75000.0,  
-                5 => 150000.0,  
-                _ => 50000.0,  
+            let Some(item) = produk.get(&detail.id_produk) else {
+                return Err(TransaksiError::ProdukTidakDitemukan(detail.id_produk));
             };
-            prices.insert(detail.id_produk, price);
+
+            let diminta = requested[&detail.id_produk];
+            if diminta > item.stok {
+                return Err(TransaksiError::StokTidakCukup {
+                    id_produk: item.id,
+                    nama_produk: item.nama.clone(),
+                    tersedia: item.stok,
+                    diminta,
+                });
+            }
         }
 
-        Ok(prices)
+        Ok(produk)
     }
 
     pub async fn get_transaksi_by_id(db: Pool<Any>, id: i32) -> Result<Transaksi, sqlx::Error> {
@@ -158,27 +283,68 @@ impl TransaksiService {
             return Err(sqlx::Error::RowNotFound);
         }
 
-        let db_connection = db.acquire().await?;
-        TransaksiRepository::update_transaksi(db_connection, transaksi).await
+        // Total dan rincian PPN tidak ikut ditulis: hanya dihitung server lewat reprice_transaksi
+        let mut transaksi = transaksi.clone();
+        // Status tidak ditulis di sini (hanya lewat ubah_status); status yang dibaca menjadi syarat
+        // update sehingga transaksi yang baru saja diselesaikan tidak ikut berubah
+        transaksi.status = existing_transaksi.status;
+
+        let mut db_connection = db.acquire().await?;
+        TransaksiRepository::update_transaksi(&mut db_connection, &transaksi).await
     }
 
-    pub async fn delete_transaksi(db: Pool<Any>, id: i32) -> Result<(), sqlx::Error> {
+    pub async fn delete_transaksi(db: Pool<Any>, id: i32, perubahan: &PerubahanStatus) -> Result<(), sqlx::Error> {
         let existing_transaksi = Self::get_transaksi_by_id(db.clone(), id).await?;
         
         if !existing_transaksi.can_be_cancelled() {
             return Err(sqlx::Error::RowNotFound); 
         }
 
-        let details = Self::get_detail_by_transaksi_id(db.clone(), id).await?;
-        for detail in details {
-            Self::restore_product_stock(detail.id_produk, detail.jumlah).await?;
-        }
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+        Self::release_transaksi(&mut tx, id, &existing_transaksi.status, perubahan).await?;
+        TransaksiRepository::delete_detail_by_transaksi_id(&mut tx, id).await?;
+        TransaksiRepository::delete_transaksi(&mut tx, id).await?;
+        tx.commit().await
+    }
 
-        let db_connection_detail = db.acquire().await?;
-        TransaksiRepository::delete_detail_by_transaksi_id(db_connection_detail, id).await?;
+    // Satu-satunya jalan untuk mengubah status: status tujuan ditentukan TransaksiState, perubahan
+    // dilakukan bersyarat terhadap status asal, lalu dicatat di riwayat dalam transaksi database yang sama.
+    // Transisi yang tidak diizinkan atau status yang sudah diubah proses lain menghasilkan RowNotFound.
+    pub(crate) async fn ubah_status(
+        db: &mut AnyConnection,
+        id: i32,
+        dari: &StatusTransaksi,
+        action: StateAction,
+        perubahan: &PerubahanStatus
+    ) -> Result<StatusTransaksi, sqlx::Error> {
+        let ke = TransaksiStateFactory::create_state(dari)
+            .next_state(action)
+            .map_err(|_| sqlx::Error::RowNotFound)?
+            .status();
+
+        if !TransaksiRepository::ubah_status_jika(db, id, dari, &ke).await? {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        RiwayatStatusRepository::catat(db, &perubahan.catat(id, dari.clone(), ke.clone())).await?;
+        Ok(ke)
+    }
 
-        let db_connection = db.acquire().await?;
-        TransaksiRepository::delete_transaksi(db_connection, id).await
+    // Batalkan transaksi lalu kembalikan stok semua detail dan hapus reservasinya.
+    // Bila status sudah diubah proses lain (misalnya job reservasi kedaluwarsa), tidak ada yang
+    // dikembalikan dua kali dan RowNotFound dikembalikan.
+    pub(crate) async fn release_transaksi(
+        db: &mut AnyConnection,
+        id: i32,
+        dari: &StatusTransaksi,
+        perubahan: &PerubahanStatus
+    ) -> Result<(), sqlx::Error> {
+        Self::ubah_status(db, id, dari, StateAction::Cancel, perubahan).await?;
+
+        for detail in TransaksiRepository::get_detail_by_transaksi_id(db, id).await? {
+            TransaksiRepository::tambah_stok(db, detail.id_produk, detail.jumlah).await?;
+        }
+        ReservasiStokRepository::hapus_by_transaksi(db, id).await
     }
 
     pub async fn get_all_transaksi(db: Pool<Any>) -> Result<Vec<Transaksi>, sqlx::Error> {
@@ -196,65 +362,143 @@ impl TransaksiService {
         TransaksiRepository::get_transaksi_by_status(db_connection, status).await
     }
 
-    pub async fn complete_transaksi(db: Pool<Any>, id: i32) -> Result<Transaksi, sqlx::Error> {
-        let mut transaksi = Self::get_transaksi_by_id(db.clone(), id).await?;
-        
-        if !transaksi.can_be_modified() {
-            return Err(sqlx::Error::RowNotFound);
-        }
+    // Stok sudah dikurangi saat item ditambahkan; menyelesaikan transaksi hanya melepas reservasinya
+    pub async fn complete_transaksi(db: Pool<Any>, id: i32, perubahan: &PerubahanStatus) -> Result<Transaksi, sqlx::Error> {
+        let transaksi = Self::get_transaksi_by_id(db.clone(), id).await?;
 
-        transaksi.update_status(StatusTransaksi::Selesai);
-        Self::update_transaksi(db, &transaksi).await
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+        Self::ubah_status(&mut tx, id, &transaksi.status, StateAction::Complete, perubahan).await?;
+        ReservasiStokRepository::hapus_by_transaksi(&mut tx, id).await?;
+        tx.commit().await?;
+        drop(db_connection);
+
+        Self::get_transaksi_by_id(db, id).await
     }
 
-    pub async fn cancel_transaksi(db: Pool<Any>, id: i32) -> Result<Transaksi, sqlx::Error> {
-        let mut transaksi = Self::get_transaksi_by_id(db.clone(), id).await?;
-        
-        if !transaksi.status.can_be_cancelled() {
-            return Err(sqlx::Error::RowNotFound);
+    pub async fn cancel_transaksi(db: Pool<Any>, id: i32, perubahan: &PerubahanStatus) -> Result<Transaksi, sqlx::Error> {
+        let transaksi = Self::get_transaksi_by_id(db.clone(), id).await?;
+
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+        Self::release_transaksi(&mut tx, id, &transaksi.status, perubahan).await?;
+        tx.commit().await?;
+        drop(db_connection);
+
+        Self::get_transaksi_by_id(db, id).await
+    }
+
+    // Transaksi batal dibuka kembali dengan detail yang sama; stok diambil dan direservasi ulang,
+    // jadi pembukaan kembali gagal bila stok sudah tidak mencukupi
+    pub async fn reopen_transaksi(db: Pool<Any>, id: i32, perubahan: &PerubahanStatus) -> Result<Transaksi, TransaksiError> {
+        let transaksi = Self::get_transaksi_by_id(db.clone(), id).await?;
+        if !transaksi.can_be_reopened() {
+            return Err(TransaksiError::Validasi("Hanya transaksi yang dibatalkan yang dapat dibuka kembali".to_string()));
         }
 
-        let details = Self::get_detail_by_transaksi_id(db.clone(), id).await?;
-        for detail in details {
-            Self::restore_product_stock(detail.id_produk, detail.jumlah).await?;
+        let expires_at = ReservasiPolicy::from_env().expires_at(Utc::now());
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+        Self::ubah_status(&mut tx, id, &transaksi.status, StateAction::Reopen, perubahan).await?;
+
+        let mut details = TransaksiRepository::get_detail_by_transaksi_id(&mut tx, id).await?;
+        details.sort_by_key(|detail| detail.id_produk);
+        for detail in &details {
+            Self::reduce_product_stock(&mut tx, detail.id_produk, detail.jumlah).await?;
+            Self::save_reservation(&mut tx, detail, expires_at).await?;
         }
 
-        transaksi.update_status(StatusTransaksi::Dibatalkan);
-        Self::update_transaksi(db, &transaksi).await
+        tx.commit().await?;
+        drop(db_connection);
+
+        Ok(Self::get_transaksi_by_id(db, id).await?)
+    }
+
+    pub async fn get_riwayat_status(db: Pool<Any>, id: i32) -> Result<Vec<RiwayatStatusTransaksi>, sqlx::Error> {
+        let mut db_connection = db.acquire().await?;
+        RiwayatStatusRepository::get_by_transaksi(&mut db_connection, id).await
     }
 
-    pub async fn add_detail_transaksi(db: Pool<Any>, detail: &DetailTransaksi) -> Result<DetailTransaksi, sqlx::Error> {
+    pub async fn add_detail_transaksi(db: Pool<Any>, detail: &DetailTransaksi) -> Result<DetailTransaksi, TransaksiError> {
         let transaksi = Self::get_transaksi_by_id(db.clone(), detail.id_transaksi).await?;
         
         if !transaksi.can_be_modified() {
-            return Err(sqlx::Error::RowNotFound);
+            return Err(sqlx::Error::RowNotFound.into());
+        }
+        if detail.jumlah == 0 {
+            return Err(TransaksiError::Validasi("Jumlah produk tidak boleh 0".to_string()));
+        }
+        if detail.jumlah > MAX_JUMLAH {
+            return Err(TransaksiError::Validasi(format!("Jumlah produk tidak boleh lebih dari {}", MAX_JUMLAH)));
         }
 
-        let db_connection = db.acquire().await?;
-        let created_detail = TransaksiRepository::create_detail_transaksi(db_connection, detail).await?;
+        let expires_at = ReservasiPolicy::from_env().expires_at(Utc::now());
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
 
-        Self::recalculate_transaction_total(db, detail.id_transaksi).await?;
+        Self::reduce_product_stock(&mut tx, detail.id_produk, detail.jumlah).await?;
+        let produk = Self::get_produk(&mut tx, detail.id_produk).await?;
 
+        let detail = DetailTransaksi::new(detail.id_transaksi, produk.id, produk.harga, detail.jumlah);
+        let created_detail = TransaksiRepository::create_detail_transaksi(&mut tx, &detail).await?;
+        Self::save_reservation(&mut tx, &created_detail, expires_at).await?;
+        let created_detail = Self::reprice_transaksi(&mut tx, &transaksi).await?
+            .into_iter()
+            .find(|d| d.id == created_detail.id)
+            .unwrap_or(created_detail);
+
+        tx.commit().await?;
         Ok(created_detail)
     }
 
     pub async fn get_detail_by_transaksi_id(db: Pool<Any>, id_transaksi: i32) -> Result<Vec<DetailTransaksi>, sqlx::Error> {
-        let db_connection = db.acquire().await?;
-        TransaksiRepository::get_detail_by_transaksi_id(db_connection, id_transaksi).await
+        let mut db_connection = db.acquire().await?;
+        TransaksiRepository::get_detail_by_transaksi_id(&mut db_connection, id_transaksi).await
     }
 
-    pub async fn update_detail_transaksi(db: Pool<Any>, detail: &DetailTransaksi) -> Result<DetailTransaksi, sqlx::Error> {
+    // Selisih jumlah dikurangkan/dikembalikan ke stok; ganti produk berarti stok produk lama dikembalikan penuh
+    pub async fn update_detail_transaksi(db: Pool<Any>, detail: &DetailTransaksi) -> Result<DetailTransaksi, TransaksiError> {
         let transaksi = Self::get_transaksi_by_id(db.clone(), detail.id_transaksi).await?;
         
         if !transaksi.can_be_modified() {
-            return Err(sqlx::Error::RowNotFound);
+            return Err(sqlx::Error::RowNotFound.into());
+        }
+        if detail.jumlah == 0 {
+            return Err(TransaksiError::Validasi("Jumlah produk tidak boleh 0".to_string()));
+        }
+        if detail.jumlah > MAX_JUMLAH {
+            return Err(TransaksiError::Validasi(format!("Jumlah produk tidak boleh lebih dari {}", MAX_JUMLAH)));
         }
 
-        let db_connection = db.acquire().await?;
-        let updated_detail = TransaksiRepository::update_detail_transaksi(db_connection, detail).await?;
+        let existing = Self::get_detail_by_transaksi_id(db.clone(), detail.id_transaksi).await?
+            .into_iter()
+            .find(|d| d.id == detail.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let expires_at = ReservasiPolicy::from_env().expires_at(Utc::now());
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
+
+        if existing.id_produk != detail.id_produk {
+            Self::reduce_product_stock(&mut tx, detail.id_produk, detail.jumlah).await?;
+            TransaksiRepository::tambah_stok(&mut tx, existing.id_produk, existing.jumlah).await?;
+        } else if detail.jumlah > existing.jumlah {
+            Self::reduce_product_stock(&mut tx, detail.id_produk, detail.jumlah - existing.jumlah).await?;
+        } else if detail.jumlah < existing.jumlah {
+            TransaksiRepository::tambah_stok(&mut tx, detail.id_produk, existing.jumlah - detail.jumlah).await?;
+        }
+        let produk = Self::get_produk(&mut tx, detail.id_produk).await?;
 
-        Self::recalculate_transaction_total(db, detail.id_transaksi).await?;
+        let mut detail = detail.clone();
+        detail.update_harga_satuan(produk.harga);
+        let updated_detail = TransaksiRepository::update_detail_transaksi(&mut tx, &detail).await?;
+        Self::save_reservation(&mut tx, &updated_detail, expires_at).await?;
+        let updated_detail = Self::reprice_transaksi(&mut tx, &transaksi).await?
+            .into_iter()
+            .find(|d| d.id == updated_detail.id)
+            .unwrap_or(updated_detail);
 
+        tx.commit().await?;
         Ok(updated_detail)
     }
 
@@ -266,29 +510,66 @@ impl TransaksiService {
         }
 
         let details = Self::get_detail_by_transaksi_id(db.clone(), id_transaksi).await?;
+
+        let mut db_connection = db.acquire().await?;
+        let mut tx = (*db_connection).begin().await?;
         if let Some(detail_to_delete) = details.iter().find(|d| d.id == id) {
-            Self::restore_product_stock(detail_to_delete.id_produk, detail_to_delete.jumlah).await?;
+            TransaksiRepository::tambah_stok(&mut tx, detail_to_delete.id_produk, detail_to_delete.jumlah).await?;
+            ReservasiStokRepository::hapus_by_detail(&mut tx, id).await?;
         }
+        TransaksiRepository::delete_detail_transaksi(&mut tx, id).await?;
+        Self::reprice_transaksi(&mut tx, &transaksi).await?;
 
-        let db_connection = db.acquire().await?;
-        TransaksiRepository::delete_detail_transaksi(db_connection, id).await?;
-
-        Self::recalculate_transaction_total(db, id_transaksi).await?;
-
-        Ok(())
+        tx.commit().await
     }
 
-    async fn recalculate_transaction_total(db: Pool<Any>, id_transaksi: i32) -> Result<(), sqlx::Error> {
-        let details = Self::get_detail_by_transaksi_id(db.clone(), id_transaksi).await?;
-        let total: f64 = details.iter().map(|d| d.subtotal).sum();
+    // Promosi dihitung ulang untuk seluruh keranjang setiap kali isinya berubah, karena harga
+    // bertingkat, beli-X-gratis-Y dan diskon transaksi bergantung pada baris lain.
+    // PPN memakai tarif dan mode harga yang tersimpan di header, bukan konfigurasi saat ini.
+    async fn reprice_transaksi(db: &mut AnyConnection, transaksi: &Transaksi) -> Result<Vec<DetailTransaksi>, sqlx::Error> {
+        let details = TransaksiRepository::get_detail_by_transaksi_id(db, transaksi.id).await?;
+        let product_ids: Vec<i32> = details.iter().map(|d| d.id_produk).collect();
+        let produk = TransaksiRepository::get_produk(db, &product_ids).await?;
+        let promosi = PromosiRepository::get_promosi_aktif(db).await?;
+
+        let baris: Vec<BarisPromosi> = details.iter().map(|d| BarisPromosi {
+            id_produk: d.id_produk,
+            kategori: produk.get(&d.id_produk).map(|p| p.kategori.clone()),
+            harga_satuan: d.harga_satuan,
+            jumlah: d.jumlah,
+        }).collect();
+        let rincian = MesinPromosi::new(&promosi, transaksi.id_pelanggan, Utc::now()).hitung(&baris);
+
+        let kebijakan = transaksi.kebijakan_pajak();
+
+        let mut repriced = Vec::with_capacity(details.len());
+        for (mut detail, diskon) in details.into_iter().zip(rincian.diskon_per_baris) {
+            let kategori_pajak = produk.get(&detail.id_produk).map(|p| p.kategori_pajak).unwrap_or(detail.kategori_pajak);
+            let sebelum = (detail.diskon_diterapkan.clone(), Self::pajak_detail(&detail));
+            detail.terapkan_diskon(diskon);
+            detail.terapkan_pajak(kebijakan.hitung(detail.subtotal, kategori_pajak));
+            if sebelum != (detail.diskon_diterapkan.clone(), Self::pajak_detail(&detail)) {
+                detail = TransaksiRepository::update_detail_transaksi(db, &detail).await?;
+            }
+            repriced.push(detail);
+        }
 
-        let mut transaksi = Self::get_transaksi_by_id(db.clone(), id_transaksi).await?;
-        transaksi.update_total_harga(total);
+        let pajak: Vec<PajakBaris> = repriced.iter().map(Self::pajak_detail).collect();
+        let mut transaksi = transaksi.clone();
+        transaksi.terapkan_pajak(kebijakan, &pajak);
+        TransaksiRepository::update_total(db, &transaksi).await?;
 
-        let db_connection = db.acquire().await?;
-        TransaksiRepository::update_transaksi(db_connection, &transaksi).await?;
+        Ok(repriced)
+    }
 
-        Ok(())
+    fn pajak_detail(detail: &DetailTransaksi) -> PajakBaris {
+        PajakBaris {
+            kategori: detail.kategori_pajak,
+            tarif_bp: detail.tarif_pajak_bp,
+            dpp: detail.dpp,
+            ppn: detail.ppn,
+            total: detail.total,
+        }
     }
 
     pub async fn search_transaksi_with_pagination(
@@ -346,10 +627,10 @@ impl TransaksiService {
                 transaksi_list.sort_by(|a, b| b.tanggal_transaksi.cmp(&a.tanggal_transaksi));
             }
             "total" | "total_harga" => {
-                transaksi_list.sort_by(|a, b| a.total_harga.partial_cmp(&b.total_harga).unwrap());
+                transaksi_list.sort_by_key(|t| t.total_harga);
             }
             "total_desc" => {
-                transaksi_list.sort_by(|a, b| b.total_harga.partial_cmp(&a.total_harga).unwrap());
+                transaksi_list.sort_by_key(|t| std::cmp::Reverse(t.total_harga));
             }
             "pelanggan" | "nama_pelanggan" => {
                 transaksi_list.sort_by(|a, b| a.nama_pelanggan.cmp(&b.nama_pelanggan));
@@ -393,6 +674,10 @@ impl TransaksiService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::money::Rupiah;
+    use crate::transaksi_penjualan::model::pajak::KategoriPajak;
+    use crate::transaksi_penjualan::model::promosi::{AturanPromosi, CakupanPromosi, Promosi, TingkatHarga};
+    use crate::transaksi_penjualan::service::reservasi_stok::ReservasiStokService;
     use sqlx::any::install_default_drivers;
     use rocket::async_test;
 
@@ -409,10 +694,33 @@ mod tests {
             .run(&db)
             .await
             .unwrap();
+        seed_produk(&db).await;
         
         db
     }
 
+    // Katalog produk uji: (id, nama, harga, stok)
+    const PRODUK_UJI: [(i64, &str, i64, i32); 5] = [
+        (1, "Semen Portland 50kg", 100_000, 100),
+        (2, "Cat Tembok 5kg", 250_000, 50),
+        (3, "Keramik Lantai 60x60", 500_000, 25),
+        (4, "Pipa PVC 3m", 75_000, 15),
+        (5, "Besi Beton 10mm", 150_000, 30),
+    ];
+
+    async fn seed_produk(db: &Pool<Any>) {
+        for (id, nama, harga, stok) in PRODUK_UJI {
+            sqlx::query("INSERT INTO produk (id, nama, kategori, harga, stok) VALUES ($1, $2, 'Material', CAST($3 AS DECIMAL(15,2)), $4)")
+                .bind(id)
+                .bind(nama)
+                .bind(Rupiah::from_rupiah(harga).to_string())
+                .bind(stok)
+                .execute(db)
+                .await
+                .unwrap();
+        }
+    }
+
     #[async_test]
     async fn test_create_and_get_transaksi() {
         let db = setup().await;
@@ -420,7 +728,7 @@ mod tests {
         let transaksi = Transaksi::new(
             1,
             "Castorice".to_string(),
-            150000.0,
+            Rupiah::from_rupiah(150000),
             Some("Test transaction".to_string()),
         );
 
@@ -438,21 +746,21 @@ mod tests {
         let transaksi = Transaksi::new(
             1,
             "Tribbie".to_string(),
-            200000.0,
+            Rupiah::from_rupiah(200000),
             None,
         );
 
         let created = TransaksiService::create_transaksi(db.clone(), &transaksi).await.unwrap();
-        let completed = TransaksiService::complete_transaksi(db, created.id).await.unwrap();
+        let completed = TransaksiService::complete_transaksi(db, created.id, &PerubahanStatus::oleh(None, None)).await.unwrap();
 
         assert_eq!(completed.status, StatusTransaksi::Selesai);
     }
 
     #[async_test]
     async fn test_strategy_pattern_sorting() {
-        let transaksi1 = Transaksi::new(1, "Alice".to_string(), 100000.0, None);
-        let transaksi2 = Transaksi::new(2, "Bob".to_string(), 200000.0, None);
-        let transaksi3 = Transaksi::new(3, "Charlie".to_string(), 150000.0, None);
+        let transaksi1 = Transaksi::new(1, "Alice".to_string(), Rupiah::from_rupiah(100000), None);
+        let transaksi2 = Transaksi::new(2, "Bob".to_string(), Rupiah::from_rupiah(200000), None);
+        let transaksi3 = Transaksi::new(3, "Charlie".to_string(), Rupiah::from_rupiah(150000), None);
 
         let transaksi_list = vec![transaksi1, transaksi2, transaksi3];
         
@@ -462,16 +770,16 @@ mod tests {
         assert_eq!(sorted[2].nama_pelanggan, "Charlie");
 
         let sorted_total = TransaksiService::sort_transaksi(transaksi_list, "total");
-        assert_eq!(sorted_total[0].total_harga, 100000.0);
-        assert_eq!(sorted_total[1].total_harga, 150000.0);
-        assert_eq!(sorted_total[2].total_harga, 200000.0);
+        assert_eq!(sorted_total[0].total_harga, Rupiah::from_rupiah(100000));
+        assert_eq!(sorted_total[1].total_harga, Rupiah::from_rupiah(150000));
+        assert_eq!(sorted_total[2].total_harga, Rupiah::from_rupiah(200000));
     }
 
     #[async_test]
     async fn test_strategy_pattern_filtering() {
-        let transaksi1 = Transaksi::new(1, "Alice Smith".to_string(), 100000.0, None);
-        let transaksi2 = Transaksi::new(2, "Bob Johnson".to_string(), 200000.0, None);
-        let transaksi3 = Transaksi::new(3, "Alice Brown".to_string(), 150000.0, None);
+        let transaksi1 = Transaksi::new(1, "Alice Smith".to_string(), Rupiah::from_rupiah(100000), None);
+        let transaksi2 = Transaksi::new(2, "Bob Johnson".to_string(), Rupiah::from_rupiah(200000), None);
+        let transaksi3 = Transaksi::new(3, "Alice Brown".to_string(), Rupiah::from_rupiah(150000), None);
 
         let transaksi_list = vec![transaksi1, transaksi2, transaksi3];
         
@@ -484,9 +792,9 @@ mod tests {
     async fn test_search_with_pagination() {
         let db = setup().await;
 
-        let transaksi1 = Transaksi::new(1, "Alice".to_string(), 100000.0, None);
-        let transaksi2 = Transaksi::new(2, "Bob".to_string(), 200000.0, None);
-        let transaksi3 = Transaksi::new(1, "Alice Again".to_string(), 300000.0, None);
+        let transaksi1 = Transaksi::new(1, "Alice".to_string(), Rupiah::from_rupiah(100000), None);
+        let transaksi2 = Transaksi::new(2, "Bob".to_string(), Rupiah::from_rupiah(200000), None);
+        let transaksi3 = Transaksi::new(1, "Alice Again".to_string(), Rupiah::from_rupiah(300000), None);
 
         TransaksiService::create_transaksi(db.clone(), &transaksi1).await.unwrap();
         TransaksiService::create_transaksi(db.clone(), &transaksi2).await.unwrap();
@@ -515,14 +823,14 @@ mod tests {
     async fn test_detail_transaksi_operations() {
         let db = setup().await;
 
-        let transaksi = Transaksi::new(1, "Detail Test".to_string(), 0.0, None);
+        let transaksi = Transaksi::new(1, "Detail Test".to_string(), Rupiah::ZERO, None);
         let created_transaksi = TransaksiService::create_transaksi(db.clone(), &transaksi).await.unwrap();
 
-        let detail = DetailTransaksi::new(created_transaksi.id, 1, 100000.0, 2);
+        let detail = DetailTransaksi::new(created_transaksi.id, 1, Rupiah::from_rupiah(100000), 2);
         let created_detail = TransaksiService::add_detail_transaksi(db.clone(), &detail).await.unwrap();
 
         assert_eq!(created_detail.id_transaksi, created_transaksi.id);
-        assert_eq!(created_detail.subtotal, 200000.0);
+        assert_eq!(created_detail.subtotal, Rupiah::from_rupiah(200000));
 
         let details = TransaksiService::get_detail_by_transaksi_id(db.clone(), created_transaksi.id).await.unwrap();
         assert_eq!(details.len(), 1);
@@ -532,10 +840,491 @@ mod tests {
         updated_detail.update_jumlah(3);
         let result = TransaksiService::update_detail_transaksi(db.clone(), &updated_detail).await.unwrap();
         assert_eq!(result.jumlah, 3);
-        assert_eq!(result.subtotal, 300000.0);
+        assert_eq!(result.subtotal, Rupiah::from_rupiah(300000));
 
         TransaksiService::delete_detail_transaksi(db.clone(), created_detail.id, created_transaksi.id).await.unwrap();
         let remaining_details = TransaksiService::get_detail_by_transaksi_id(db, created_transaksi.id).await.unwrap();
         assert_eq!(remaining_details.len(), 0);
     }
-}
\ No newline at end of file
+
+    async fn stok(db: &Pool<Any>, id_produk: i64) -> i32 {
+        use sqlx::Row;
+        sqlx::query("SELECT stok FROM produk WHERE id = $1")
+            .bind(id_produk)
+            .fetch_one(db)
+            .await
+            .unwrap()
+            .get("stok")
+    }
+
+    fn create_request(items: &[(i32, u32)]) -> crate::transaksi_penjualan::dto::transaksi_request::CreateTransaksiRequest {
+        crate::transaksi_penjualan::dto::transaksi_request::CreateTransaksiRequest {
+            id_pelanggan: 1,
+            nama_pelanggan: "Stok Test".to_string(),
+            catatan: None,
+            detail_transaksi: items.iter().map(|(id_produk, jumlah)| {
+                crate::transaksi_penjualan::dto::transaksi_request::CreateDetailTransaksiRequest {
+                    id_produk: *id_produk,
+                    nama_produk: "Dari klien".to_string(),
+                    harga_satuan: Rupiah::from_rupiah(1),
+                    jumlah: *jumlah,
+                }
+            }).collect(),
+        }
+    }
+
+    #[async_test]
+    async fn test_create_transaksi_uses_database_price_and_reduces_stock() {
+        let db = setup().await;
+
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(2, 3), (4, 1)])).await.unwrap();
+        assert_eq!(created.total_harga, Rupiah::from_rupiah(3 * 250_000 + 75_000));
+
+        let details = TransaksiService::get_detail_by_transaksi_id(db.clone(), created.id).await.unwrap();
+        assert_eq!(details[0].harga_satuan, Rupiah::from_rupiah(250_000));
+        assert_eq!(stok(&db, 2).await, 47);
+        assert_eq!(stok(&db, 4).await, 14);
+    }
+
+    #[async_test]
+    async fn test_create_transaksi_rejects_unknown_product_and_insufficient_stock() {
+        let db = setup().await;
+
+        let result = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 1), (99, 1)])).await;
+        assert!(matches!(result, Err(TransaksiError::ProdukTidakDitemukan(99))));
+
+        // Baris terpisah untuk produk yang sama dijumlahkan sebelum dibandingkan dengan stok
+        let result = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(4, 10), (4, 10)])).await;
+        match result {
+            Err(TransaksiError::StokTidakCukup { id_produk, tersedia, diminta, .. }) => {
+                assert_eq!((id_produk, tersedia, diminta), (4, 15, 20));
+            }
+            other => panic!("expected StokTidakCukup, got {:?}", other),
+        }
+
+        assert!(TransaksiService::get_all_transaksi(db.clone()).await.unwrap().is_empty());
+        assert_eq!(stok(&db, 1).await, 100);
+        assert_eq!(stok(&db, 4).await, 15);
+    }
+
+    #[async_test]
+    async fn test_detail_changes_and_cancellation_adjust_stock() {
+        let db = setup().await;
+
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 2)])).await.unwrap();
+        let mut detail = TransaksiService::get_detail_by_transaksi_id(db.clone(), created.id).await.unwrap().remove(0);
+        assert_eq!(stok(&db, 1).await, 98);
+
+        let added = TransaksiService::add_detail_transaksi(db.clone(), &DetailTransaksi::new(created.id, 3, Rupiah::from_rupiah(1), 5)).await.unwrap();
+        assert_eq!(added.harga_satuan, Rupiah::from_rupiah(500_000));
+        assert_eq!(stok(&db, 3).await, 20);
+
+        let result = TransaksiService::add_detail_transaksi(db.clone(), &DetailTransaksi::new(created.id, 4, Rupiah::ZERO, 16)).await;
+        assert!(matches!(result, Err(TransaksiError::StokTidakCukup { .. })));
+        assert_eq!(stok(&db, 4).await, 15);
+
+        detail.update_jumlah(5);
+        TransaksiService::update_detail_transaksi(db.clone(), &detail).await.unwrap();
+        assert_eq!(stok(&db, 1).await, 95);
+
+        detail.id_produk = 5;
+        let updated = TransaksiService::update_detail_transaksi(db.clone(), &detail).await.unwrap();
+        assert_eq!(updated.subtotal, Rupiah::from_rupiah(5 * 150_000));
+        assert_eq!(stok(&db, 1).await, 100);
+        assert_eq!(stok(&db, 5).await, 25);
+
+        TransaksiService::delete_detail_transaksi(db.clone(), added.id, created.id).await.unwrap();
+        assert_eq!(stok(&db, 3).await, 25);
+
+        let cancelled = TransaksiService::cancel_transaksi(db.clone(), created.id, &PerubahanStatus::oleh(None, None)).await.unwrap();
+        assert_eq!(cancelled.total_harga, Rupiah::from_rupiah(5 * 150_000));
+        assert_eq!(stok(&db, 5).await, 30);
+    }
+
+    #[async_test]
+    async fn test_huge_jumlah_is_rejected_and_leaves_stock_unchanged() {
+        let db = setup().await;
+        let huge = MAX_JUMLAH + 1;
+
+        let result = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, huge)])).await;
+        assert!(matches!(result, Err(TransaksiError::Validasi(_))));
+        assert_eq!(stok(&db, 1).await, 100);
+
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 2)])).await.unwrap();
+        let result = TransaksiService::add_detail_transaksi(db.clone(), &DetailTransaksi::new(created.id, 3, Rupiah::ZERO, huge)).await;
+        assert!(matches!(result, Err(TransaksiError::Validasi(_))));
+        assert_eq!(stok(&db, 3).await, 25);
+
+        let mut detail = TransaksiService::get_detail_by_transaksi_id(db.clone(), created.id).await.unwrap().remove(0);
+        detail.update_jumlah(u32::MAX);
+        let result = TransaksiService::update_detail_transaksi(db.clone(), &detail).await;
+        assert!(matches!(result, Err(TransaksiError::Validasi(_))));
+        assert_eq!(stok(&db, 1).await, 98);
+
+        // Repository juga tidak membiarkan jumlah besar berputar menjadi negatif
+        let mut conn = db.acquire().await.unwrap();
+        assert!(!TransaksiRepository::kurangi_stok(&mut conn, 1, u32::MAX).await.unwrap());
+        assert!(TransaksiRepository::tambah_stok(&mut conn, 1, u32::MAX).await.is_err());
+        drop(conn);
+        assert_eq!(stok(&db, 1).await, 98);
+    }
+
+    async fn count(db: &Pool<Any>, table: &str) -> i64 {
+        use sqlx::Row;
+        sqlx::query(&format!("SELECT COUNT(*) AS total FROM {}", table))
+            .fetch_one(db)
+            .await
+            .unwrap()
+            .get("total")
+    }
+
+    // Kegagalan disuntikkan lewat trigger SQLite supaya terjadi di tengah penulisan
+    async fn inject_failure(db: &Pool<Any>, trigger: &str) {
+        sqlx::query(trigger).execute(db).await.unwrap();
+    }
+
+    async fn assert_nothing_persisted(db: &Pool<Any>) {
+        assert_eq!(count(db, "transaksi").await, 0);
+        assert_eq!(count(db, "detail_transaksi").await, 0);
+        for (id, _, _, stok_awal) in PRODUK_UJI {
+            assert_eq!(stok(db, id).await, stok_awal);
+        }
+    }
+
+    #[async_test]
+    async fn test_create_transaksi_rolls_back_when_detail_insert_fails() {
+        let db = setup().await;
+        inject_failure(&db, "CREATE TRIGGER gagal_detail BEFORE INSERT ON detail_transaksi
+            WHEN NEW.id_produk = 3 BEGIN SELECT RAISE(ABORT, 'kegagalan disengaja'); END").await;
+
+        let result = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 2), (2, 1), (3, 1)])).await;
+
+        assert!(matches!(result, Err(TransaksiError::Database(_))));
+        assert_nothing_persisted(&db).await;
+    }
+
+    #[async_test]
+    async fn test_create_transaksi_rolls_back_when_stock_update_fails() {
+        let db = setup().await;
+        inject_failure(&db, "CREATE TRIGGER gagal_stok BEFORE UPDATE OF stok ON produk
+            WHEN NEW.id = 2 BEGIN SELECT RAISE(ABORT, 'kegagalan disengaja'); END").await;
+
+        let result = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 2), (2, 1)])).await;
+
+        assert!(matches!(result, Err(TransaksiError::Database(_))));
+        assert_nothing_persisted(&db).await;
+
+        // Setelah rollback koneksi tetap bisa dipakai untuk transaksi berikutnya
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 2)])).await.unwrap();
+        assert_eq!(created.total_harga, Rupiah::from_rupiah(200_000));
+        assert_eq!(stok(&db, 1).await, 98);
+    }
+
+    #[async_test]
+    async fn test_create_transaksi_rolls_back_when_stock_runs_out_during_write() {
+        let db = setup().await;
+        // Stok produk 4 habis diambil pihak lain di tengah penulisan: UPDATE bersyarat gagal dan semuanya dibatalkan
+        inject_failure(&db, "CREATE TRIGGER stok_diambil AFTER UPDATE OF stok ON produk
+            WHEN NEW.id = 1 BEGIN UPDATE produk SET stok = 0 WHERE id = 4; END").await;
+
+        let result = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 2), (4, 5)])).await;
+
+        assert!(matches!(result, Err(TransaksiError::StokTidakCukup { id_produk: 4, tersedia: 0, .. })));
+        assert_nothing_persisted(&db).await;
+    }
+
+    async fn reservasi(db: &Pool<Any>, id_transaksi: i32) -> Vec<ReservasiStok> {
+        let mut db_connection = db.acquire().await.unwrap();
+        ReservasiStokRepository::get_by_transaksi(&mut db_connection, id_transaksi).await.unwrap()
+    }
+
+    #[async_test]
+    async fn test_expired_reservation_cancels_transaksi_and_releases_stock() {
+        let db = setup().await;
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 2), (3, 1)])).await.unwrap();
+
+        let reserved = reservasi(&db, created.id).await;
+        assert_eq!(reserved.iter().map(|r| (r.id_produk, r.jumlah)).collect::<Vec<_>>(), vec![(1, 2), (3, 1)]);
+        assert_eq!((stok(&db, 1).await, stok(&db, 3).await), (98, 24));
+
+        let now = Utc::now();
+        assert!(ReservasiStokService::release_expired(&db, now).await.unwrap().is_empty());
+
+        let released = ReservasiStokService::release_expired(&db, now + chrono::Duration::minutes(31)).await.unwrap();
+        assert_eq!(released, vec![created.id]);
+        assert_eq!(TransaksiService::get_transaksi_by_id(db.clone(), created.id).await.unwrap().status, StatusTransaksi::Dibatalkan);
+        assert_eq!((stok(&db, 1).await, stok(&db, 3).await), (100, 25));
+        assert!(reservasi(&db, created.id).await.is_empty());
+
+        // Kasir yang membatalkan setelah job berjalan tidak mengembalikan stok dua kali
+        assert!(TransaksiService::cancel_transaksi(db.clone(), created.id, &PerubahanStatus::oleh(None, None)).await.is_err());
+        assert_eq!(stok(&db, 1).await, 100);
+    }
+
+    #[async_test]
+    async fn test_expired_reservation_keeps_transaksi_with_active_payment() {
+        let db = setup().await;
+        let dibayar = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 2)])).await.unwrap();
+        let gagal = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 3)])).await.unwrap();
+        for (id, id_transaksi, status) in [("PMT-1", dibayar.id, "CICILAN"), ("PMT-2", gagal.id, "GAGAL")] {
+            sqlx::query("INSERT INTO payments (id, transaction_id, amount, method, status, payment_date) VALUES ($1, $2, 1000, 'CASH', $3, '2024-01-01T00:00:00Z')")
+                .bind(id)
+                .bind(id_transaksi.to_string())
+                .bind(status)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+
+        // Pembayaran gagal tidak menahan keranjang; cicilan yang berjalan menahannya
+        let released = ReservasiStokService::release_expired(&db, Utc::now() + chrono::Duration::minutes(31)).await.unwrap();
+        assert_eq!(released, vec![gagal.id]);
+        assert_eq!(TransaksiService::get_transaksi_by_id(db.clone(), dibayar.id).await.unwrap().status, StatusTransaksi::MasihDiproses);
+        assert_eq!(stok(&db, 1).await, 98);
+        assert_eq!(reservasi(&db, dibayar.id).await.len(), 1);
+    }
+
+    #[async_test]
+    async fn test_complete_and_cancel_clear_reservations() {
+        let db = setup().await;
+        let selesai = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(2, 4)])).await.unwrap();
+        let batal = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(2, 1)])).await.unwrap();
+        assert_eq!(stok(&db, 2).await, 45);
+
+        TransaksiService::complete_transaksi(db.clone(), selesai.id, &PerubahanStatus::oleh(None, None)).await.unwrap();
+        TransaksiService::cancel_transaksi(db.clone(), batal.id, &PerubahanStatus::oleh(None, None)).await.unwrap();
+
+        assert_eq!(count(&db, "reservasi_stok").await, 0);
+        assert_eq!(stok(&db, 2).await, 46);
+
+        let later = Utc::now() + chrono::Duration::hours(1);
+        assert!(ReservasiStokService::release_expired(&db, later).await.unwrap().is_empty());
+        assert_eq!(stok(&db, 2).await, 46);
+    }
+
+    #[async_test]
+    async fn test_reopen_retakes_stock_and_history_is_recorded() {
+        let db = setup().await;
+        let kasir = PerubahanStatus { aktor: "kasir".to_string(), alasan: Some("Pelanggan kembali".to_string()) };
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(4, 10)])).await.unwrap();
+
+        let released = ReservasiStokService::release_expired(&db, Utc::now() + chrono::Duration::minutes(31)).await.unwrap();
+        assert_eq!(released, vec![created.id]);
+        assert_eq!(stok(&db, 4).await, 15);
+
+        let reopened = TransaksiService::reopen_transaksi(db.clone(), created.id, &kasir).await.unwrap();
+        assert_eq!(reopened.status, StatusTransaksi::MasihDiproses);
+        assert_eq!(stok(&db, 4).await, 5);
+        assert_eq!(reservasi(&db, created.id).await.len(), 1);
+
+        TransaksiService::complete_transaksi(db.clone(), created.id, &PerubahanStatus::oleh(None, None)).await.unwrap();
+        assert!(matches!(
+            TransaksiService::reopen_transaksi(db.clone(), created.id, &kasir).await,
+            Err(TransaksiError::Validasi(_))
+        ));
+
+        let riwayat = TransaksiService::get_riwayat_status(db.clone(), created.id).await.unwrap();
+        let ringkas: Vec<_> = riwayat.iter()
+            .map(|r| (r.dari_status.clone(), r.ke_status.clone(), r.aktor.as_str()))
+            .collect();
+        assert_eq!(ringkas, vec![
+            (StatusTransaksi::MasihDiproses, StatusTransaksi::Dibatalkan, "sistem"),
+            (StatusTransaksi::Dibatalkan, StatusTransaksi::MasihDiproses, "kasir"),
+            (StatusTransaksi::MasihDiproses, StatusTransaksi::Selesai, "anonim"),
+        ]);
+        assert_eq!(riwayat[0].alasan.as_deref(), Some("Reservasi stok kedaluwarsa"));
+        assert_eq!(riwayat[1].alasan.as_deref(), Some("Pelanggan kembali"));
+        assert_eq!(riwayat[2].alasan, None);
+    }
+
+    #[async_test]
+    async fn test_reopen_fails_when_stock_was_sold_meanwhile() {
+        let db = setup().await;
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(4, 10)])).await.unwrap();
+        TransaksiService::cancel_transaksi(db.clone(), created.id, &PerubahanStatus::oleh(None, None)).await.unwrap();
+        TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(4, 10)])).await.unwrap();
+
+        let result = TransaksiService::reopen_transaksi(db.clone(), created.id, &PerubahanStatus::oleh(None, None)).await;
+        assert!(matches!(result, Err(TransaksiError::StokTidakCukup { id_produk: 4, tersedia: 5, .. })));
+        assert_eq!(TransaksiService::get_transaksi_by_id(db.clone(), created.id).await.unwrap().status, StatusTransaksi::Dibatalkan);
+        assert_eq!(TransaksiService::get_riwayat_status(db.clone(), created.id).await.unwrap().len(), 1);
+    }
+
+    #[async_test]
+    async fn test_cart_activity_extends_reservation() {
+        let db = setup().await;
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 1)])).await.unwrap();
+        sqlx::query("UPDATE reservasi_stok SET expires_at = '2000-01-01T00:00:00Z'")
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let added = TransaksiService::add_detail_transaksi(db.clone(), &DetailTransaksi::new(created.id, 5, Rupiah::ZERO, 2)).await.unwrap();
+
+        let reserved = reservasi(&db, created.id).await;
+        assert_eq!(reserved.len(), 2);
+        assert!(reserved.iter().all(|r| r.expires_at > Utc::now()));
+        assert!(ReservasiStokService::release_expired(&db, Utc::now()).await.unwrap().is_empty());
+
+        TransaksiService::delete_detail_transaksi(db.clone(), added.id, created.id).await.unwrap();
+        assert_eq!(reservasi(&db, created.id).await.len(), 1);
+    }
+
+    async fn buat_promosi(db: &Pool<Any>, id: i32, aturan: AturanPromosi, cakupan: CakupanPromosi, id_pelanggan: Option<i32>, prioritas: i32) {
+        let promosi = Promosi {
+            id,
+            nama: format!("Promo {}", id),
+            aturan,
+            cakupan,
+            id_pelanggan,
+            berlaku_mulai: None,
+            berlaku_sampai: None,
+            prioritas,
+            dapat_digabung: true,
+            aktif: true,
+        };
+        let mut db_connection = db.acquire().await.unwrap();
+        PromosiRepository::create_promosi(&mut db_connection, &promosi).await.unwrap();
+    }
+
+    fn potongan(detail: &DetailTransaksi) -> Vec<(i32, Rupiah)> {
+        detail.diskon_diterapkan.iter().map(|d| (d.id_promosi, d.potongan)).collect()
+    }
+
+    #[async_test]
+    async fn test_promotions_are_stored_per_detail_and_repriced_on_changes() {
+        let db = setup().await;
+        buat_promosi(&db, 1, AturanPromosi::HargaBertingkat { tingkat: vec![
+            TingkatHarga { min_jumlah: 10, harga_satuan: Rupiah::from_rupiah(90_000) },
+        ] }, CakupanPromosi::Produk { id_produk: 1 }, None, 0).await;
+        buat_promosi(&db, 2, AturanPromosi::DiskonPersen { basis_poin: 1000 }, CakupanPromosi::Transaksi, None, 10).await;
+        buat_promosi(&db, 3, AturanPromosi::HargaPelanggan { harga_satuan: Rupiah::from_rupiah(1) },
+            CakupanPromosi::Produk { id_produk: 4 }, Some(99), 0).await;
+
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 10), (4, 2)])).await.unwrap();
+        assert_eq!(created.total_harga, Rupiah::from_rupiah(1_150_000 - 205_000));
+
+        let details = TransaksiService::get_detail_by_transaksi_id(db.clone(), created.id).await.unwrap();
+        assert_eq!(potongan(&details[0]), vec![(1, Rupiah::from_rupiah(100_000)), (2, Rupiah::from_rupiah(90_000))]);
+        assert_eq!(details[0].harga_satuan, Rupiah::from_rupiah(100_000));
+        assert_eq!(details[0].subtotal, Rupiah::from_rupiah(810_000));
+        assert_eq!(potongan(&details[1]), vec![(2, Rupiah::from_rupiah(15_000))]);
+        assert_eq!(details[1].diskon, Rupiah::from_rupiah(15_000));
+
+        // Di bawah tingkat grosir, hanya diskon transaksi yang tersisa
+        let mut detail = details[0].clone();
+        detail.update_jumlah(5);
+        let updated = TransaksiService::update_detail_transaksi(db.clone(), &detail).await.unwrap();
+        assert_eq!(potongan(&updated), vec![(2, Rupiah::from_rupiah(50_000))]);
+        assert_eq!(TransaksiService::get_transaksi_by_id(db.clone(), created.id).await.unwrap().total_harga, Rupiah::from_rupiah(585_000));
+
+        TransaksiService::delete_detail_transaksi(db.clone(), details[1].id, created.id).await.unwrap();
+        assert_eq!(TransaksiService::get_transaksi_by_id(db.clone(), created.id).await.unwrap().total_harga, Rupiah::from_rupiah(450_000));
+        assert_eq!(count(&db, "diskon_detail_transaksi").await, 1);
+    }
+
+    #[async_test]
+    async fn test_tax_is_stored_per_detail_and_header() {
+        let db = setup().await;
+        sqlx::query("UPDATE produk SET kategori_pajak = 'BEBAS_PAJAK' WHERE id = 4").execute(&db).await.unwrap();
+
+        // Harga katalog termasuk PPN 11% (konfigurasi bawaan); produk 4 bebas pajak
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 1), (4, 2)])).await.unwrap();
+        assert_eq!((created.tarif_pajak_bp, created.harga_termasuk_pajak), (1100, true));
+        assert_eq!(created.total_harga, Rupiah::from_rupiah(250_000));
+        assert_eq!(created.total_dpp, Rupiah::from_sen(24_009_009));
+        assert_eq!(created.total_ppn, Rupiah::from_sen(990_991));
+
+        let details = TransaksiService::get_detail_by_transaksi_id(db.clone(), created.id).await.unwrap();
+        assert_eq!((details[0].dpp, details[0].ppn, details[0].total), (Rupiah::from_sen(9_009_009), Rupiah::from_sen(990_991), Rupiah::from_rupiah(100_000)));
+        assert_eq!((details[1].kategori_pajak, details[1].tarif_pajak_bp, details[1].ppn), (KategoriPajak::BebasPajak, 0, Rupiah::ZERO));
+        assert_eq!(details[1].total, Rupiah::from_rupiah(150_000));
+
+        // Mode harga belum termasuk PPN disimpan di header dan dipakai saat keranjang berubah
+        let mut transaksi = Transaksi::new(2, "Pajak Eksklusif".to_string(), Rupiah::ZERO, None);
+        transaksi.terapkan_pajak(KebijakanPajak { tarif_bp: 1100, harga_termasuk_pajak: false }, &[]);
+        let transaksi = TransaksiService::create_transaksi(db.clone(), &transaksi).await.unwrap();
+
+        let added = TransaksiService::add_detail_transaksi(db.clone(), &DetailTransaksi::new(transaksi.id, 1, Rupiah::ZERO, 2)).await.unwrap();
+        assert_eq!((added.subtotal, added.ppn, added.total), (Rupiah::from_rupiah(200_000), Rupiah::from_rupiah(22_000), Rupiah::from_rupiah(222_000)));
+        TransaksiService::add_detail_transaksi(db.clone(), &DetailTransaksi::new(transaksi.id, 4, Rupiah::ZERO, 1)).await.unwrap();
+
+        let transaksi = TransaksiService::get_transaksi_by_id(db.clone(), transaksi.id).await.unwrap();
+        assert_eq!(transaksi.total_dpp, Rupiah::from_rupiah(275_000));
+        assert_eq!(transaksi.total_ppn, Rupiah::from_rupiah(22_000));
+        assert_eq!(transaksi.total_harga, Rupiah::from_rupiah(297_000));
+    }
+
+    #[async_test]
+    async fn test_update_transaksi_ignores_client_totals() {
+        let db = setup().await;
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 1), (4, 2)])).await.unwrap();
+
+        let mut perubahan = created.clone();
+        perubahan.nama_pelanggan = "Nama Baru".to_string();
+        perubahan.total_harga = Rupiah::from_rupiah(1);
+        perubahan.total_dpp = Rupiah::ZERO;
+        perubahan.total_ppn = Rupiah::ZERO;
+
+        let updated = TransaksiService::update_transaksi(db.clone(), &perubahan).await.unwrap();
+        assert_eq!(updated.nama_pelanggan, "Nama Baru");
+        assert_eq!(updated.total_harga, created.total_harga);
+        assert_eq!((updated.total_dpp, updated.total_ppn), (created.total_dpp, created.total_ppn));
+    }
+
+    #[async_test]
+    async fn test_stale_update_does_not_reopen_completed_transaksi() {
+        let db = setup().await;
+        let created = TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 1)])).await.unwrap();
+
+        // PATCH membaca MASIH_DIPROSES, lalu transaksi diselesaikan sebelum PATCH menulis
+        TransaksiService::complete_transaksi(db.clone(), created.id, &PerubahanStatus::oleh(None, None)).await.unwrap();
+        let mut basi = created.clone();
+        basi.nama_pelanggan = "Nama Baru".to_string();
+        let result = TransaksiRepository::update_transaksi(&mut db.acquire().await.unwrap(), &basi).await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+
+        let transaksi = TransaksiService::get_transaksi_by_id(db.clone(), created.id).await.unwrap();
+        assert_eq!(transaksi.status, StatusTransaksi::Selesai);
+        assert_eq!(transaksi.nama_pelanggan, created.nama_pelanggan);
+    }
+
+    #[async_test]
+    async fn test_concurrent_sales_never_oversell() {
+        install_default_drivers();
+        let path = std::env::temp_dir().join(format!("reservasi-stok-{}.db", uuid::Uuid::new_v4()));
+        let db = sqlx::any::AnyPoolOptions::new()
+            .max_connections(8)
+            .connect(&format!("sqlite://{}?mode=rwc", path.display()))
+            .await
+            .unwrap();
+        sqlx::migrate!("migrations/test").run(&db).await.unwrap();
+        seed_produk(&db).await;
+        sqlx::query("UPDATE produk SET stok = 10 WHERE id = 1").execute(&db).await.unwrap();
+
+        let sales = (0..20).map(|_| {
+            let db = db.clone();
+            tokio::spawn(async move {
+                // Database sibuk dicoba ulang, seperti kasir yang menekan tombol bayar sekali lagi
+                loop {
+                    match TransaksiService::create_transaksi_with_details(db.clone(), &create_request(&[(1, 1)])).await {
+                        Err(TransaksiError::Database(_)) => tokio::task::yield_now().await,
+                        result => return result,
+                    }
+                }
+            })
+        });
+        let results: Vec<_> = futures::future::join_all(sales).await.into_iter().map(|r| r.unwrap()).collect();
+
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 10);
+        assert!(results.iter().filter(|r| r.is_err())
+            .all(|r| matches!(r, Err(TransaksiError::StokTidakCukup { tersedia: 0, diminta: 1, .. }))));
+        assert_eq!(stok(&db, 1).await, 0);
+        assert_eq!(count(&db, "reservasi_stok").await, 10);
+
+        db.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}